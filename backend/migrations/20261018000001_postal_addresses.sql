@@ -0,0 +1,37 @@
+-- Local copy of the road-name address (도로명주소) building DB used for address validation
+CREATE TABLE IF NOT EXISTS postal_addresses (
+    building_mgmt_no VARCHAR(25) PRIMARY KEY,
+    zip_code VARCHAR(5) NOT NULL,
+    sido VARCHAR(20) NOT NULL,
+    sigungu VARCHAR(40),
+    eupmyeondong VARCHAR(40),
+    road_name VARCHAR(80) NOT NULL,
+    is_underground BOOLEAN NOT NULL DEFAULT FALSE,
+    building_main INTEGER NOT NULL,
+    building_sub INTEGER NOT NULL DEFAULT 0,
+    building_name VARCHAR(100),
+    imported_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_postal_addresses_road
+    ON postal_addresses (road_name, building_main, building_sub);
+
+-- Zip code ranges couriers charge as remote/island delivery (도서산간)
+CREATE TABLE IF NOT EXISTS postal_remote_areas (
+    area_id SERIAL PRIMARY KEY,
+    zip_start VARCHAR(5) NOT NULL,
+    zip_end VARCHAR(5) NOT NULL,
+    area_name VARCHAR(50) NOT NULL,
+    is_jeju BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO postal_remote_areas (zip_start, zip_end, area_name, is_jeju)
+SELECT * FROM (VALUES
+    ('63000', '63644', '제주특별자치도', TRUE),
+    ('40200', '40240', '경북 울릉군', FALSE),
+    ('23100', '23136', '인천 옹진군', FALSE),
+    ('58800', '58866', '전남 신안군', FALSE)
+) AS seed(zip_start, zip_end, area_name, is_jeju)
+WHERE NOT EXISTS (SELECT 1 FROM postal_remote_areas);
+
+ALTER TABLE customer_addresses ADD COLUMN IF NOT EXISTS is_remote_area BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,27 @@
+-- Shipping calendar: courier off days, holidays and transit times per region
+CREATE TABLE IF NOT EXISTS shipping_calendar_config (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    -- Weekdays numbered from Monday (0) to Sunday (6)
+    non_delivery_weekdays INTEGER[] NOT NULL DEFAULT '{6}',
+    no_pickup_weekdays INTEGER[] NOT NULL DEFAULT '{6}',
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO shipping_calendar_config (id) VALUES (1) ON CONFLICT (id) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS shipping_holidays (
+    holiday_date DATE PRIMARY KEY,
+    holiday_name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS shipping_transit_times (
+    region VARCHAR(50) PRIMARY KEY,
+    transit_days INTEGER NOT NULL DEFAULT 1 CHECK (transit_days > 0)
+);
+
+INSERT INTO shipping_transit_times (region, transit_days) VALUES
+    ('기본', 1),
+    ('제주특별자치도', 2),
+    ('도서산간', 3)
+ON CONFLICT (region) DO NOTHING;
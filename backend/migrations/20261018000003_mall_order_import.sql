@@ -0,0 +1,39 @@
+-- Mall order import: every imported line remembers where it came from so re-runs are idempotent
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS mall_name VARCHAR(20);
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS mall_order_id VARCHAR(50);
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS mall_line_id VARCHAR(50);
+
+-- NULLs are distinct, so sales entered by hand are unaffected
+CREATE UNIQUE INDEX IF NOT EXISTS uq_sales_mall_line
+    ON sales (mall_name, mall_order_id, mall_line_id);
+
+-- Mall product code -> our product
+CREATE TABLE IF NOT EXISTS mall_product_mappings (
+    mapping_id SERIAL PRIMARY KEY,
+    mall_name VARCHAR(20) NOT NULL,
+    mall_product_code VARCHAR(100) NOT NULL,
+    mall_product_name TEXT,
+    product_id INTEGER NOT NULL REFERENCES products(product_id) ON DELETE CASCADE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP,
+    UNIQUE (mall_name, mall_product_code)
+);
+
+-- One row per import run
+CREATE TABLE IF NOT EXISTS mall_import_logs (
+    log_id SERIAL PRIMARY KEY,
+    mall_name VARCHAR(20) NOT NULL,
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP,
+    status VARCHAR(20) NOT NULL DEFAULT '진행중',
+    fetched_count INTEGER NOT NULL DEFAULT 0,
+    new_count INTEGER NOT NULL DEFAULT 0,
+    updated_count INTEGER NOT NULL DEFAULT 0,
+    skipped_count INTEGER NOT NULL DEFAULT 0,
+    new_customer_count INTEGER NOT NULL DEFAULT 0,
+    unmapped_products TEXT[] NOT NULL DEFAULT '{}',
+    error_message TEXT,
+    created_by VARCHAR(50)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mall_import_logs_started ON mall_import_logs (started_at DESC);
@@ -0,0 +1,23 @@
+-- Coupang takes invoices per shipment box, not per order line
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS mall_shipment_id VARCHAR(50);
+
+-- Outbound queue: courier + tracking number to push back to the originating mall.
+-- One row per sale; re-shipping with a new tracking number resets the row.
+CREATE TABLE IF NOT EXISTS mall_shipment_sync (
+    sync_id SERIAL PRIMARY KEY,
+    sales_id VARCHAR(20) NOT NULL UNIQUE REFERENCES sales(sales_id) ON DELETE CASCADE,
+    mall_name VARCHAR(20) NOT NULL,
+    courier_name VARCHAR(50),
+    tracking_number VARCHAR(50) NOT NULL,
+    shipping_date DATE,
+    status VARCHAR(20) NOT NULL DEFAULT '대기',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT,
+    synced_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_mall_shipment_sync_due
+    ON mall_shipment_sync (next_attempt_at) WHERE status = '대기';
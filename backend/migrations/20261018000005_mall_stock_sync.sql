@@ -0,0 +1,18 @@
+-- Per-channel stock publishing: keep a buffer back so a mall never shows the last units
+CREATE TABLE IF NOT EXISTS mall_stock_settings (
+    mall_name VARCHAR(20) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    safety_buffer INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP
+);
+
+INSERT INTO mall_stock_settings (mall_name) VALUES ('naver'), ('coupang')
+ON CONFLICT (mall_name) DO NOTHING;
+
+-- What was last sent for each mapped mall product
+ALTER TABLE mall_product_mappings ADD COLUMN IF NOT EXISTS last_published_qty INTEGER;
+ALTER TABLE mall_product_mappings ADD COLUMN IF NOT EXISTS stock_published_at TIMESTAMP;
+ALTER TABLE mall_product_mappings ADD COLUMN IF NOT EXISTS stock_attempted_at TIMESTAMP;
+ALTER TABLE mall_product_mappings ADD COLUMN IF NOT EXISTS stock_sync_error TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_inventory_logs_product_created ON inventory_logs (product_id, created_at DESC);
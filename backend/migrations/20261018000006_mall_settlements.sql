@@ -0,0 +1,40 @@
+-- Mall settlement (정산) imports: one batch per uploaded report file
+CREATE TABLE IF NOT EXISTS mall_settlement_batches (
+    batch_id SERIAL PRIMARY KEY,
+    mall_name VARCHAR(20) NOT NULL,
+    file_name TEXT,
+    settle_date DATE NOT NULL,
+    line_count INTEGER NOT NULL DEFAULT 0,
+    gross_amount BIGINT NOT NULL DEFAULT 0,
+    commission_amount BIGINT NOT NULL DEFAULT 0,
+    fee_amount BIGINT NOT NULL DEFAULT 0,
+    net_amount BIGINT NOT NULL DEFAULT 0,
+    imported_by VARCHAR(50),
+    imported_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS mall_settlements (
+    settlement_id SERIAL PRIMARY KEY,
+    batch_id INTEGER NOT NULL REFERENCES mall_settlement_batches(batch_id) ON DELETE CASCADE,
+    mall_name VARCHAR(20) NOT NULL,
+    mall_order_id VARCHAR(50) NOT NULL,
+    mall_line_id VARCHAR(50) NOT NULL DEFAULT '',
+    settle_date DATE NOT NULL,
+    gross_amount INTEGER NOT NULL DEFAULT 0,
+    commission_amount INTEGER NOT NULL DEFAULT 0,
+    fee_amount INTEGER NOT NULL DEFAULT 0,
+    net_amount INTEGER NOT NULL DEFAULT 0,
+    sales_id VARCHAR(20),
+    -- '일치', '금액불일치', '미매칭'
+    match_status VARCHAR(20) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Re-importing the same report must not double count; refund rows differ by date or amount
+CREATE UNIQUE INDEX IF NOT EXISTS uq_mall_settlements_line
+    ON mall_settlements (mall_name, mall_order_id, mall_line_id, settle_date, gross_amount);
+CREATE INDEX IF NOT EXISTS idx_mall_settlements_sales ON mall_settlements (sales_id);
+
+-- Commission and fee expenses created by an import disappear with the batch
+ALTER TABLE expenses ADD COLUMN IF NOT EXISTS settlement_batch_id INTEGER
+    REFERENCES mall_settlement_batches(batch_id) ON DELETE CASCADE;
@@ -0,0 +1,33 @@
+-- SMS history is tied to a customer so it can follow the customer through a merge
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS customer_id VARCHAR(20);
+CREATE INDEX IF NOT EXISTS idx_sms_logs_customer ON sms_logs (customer_id);
+
+-- Attach existing logs where the number belongs to exactly one customer
+UPDATE sms_logs l SET customer_id = m.customer_id
+FROM (
+    SELECT regexp_replace(mobile_number, '[^0-9]', '', 'g') AS digits, MIN(customer_id) AS customer_id
+    FROM customers
+    GROUP BY 1
+    HAVING COUNT(*) = 1
+) m
+WHERE l.customer_id IS NULL
+  AND regexp_replace(l.mobile_number, '[^0-9]', '', 'g') = m.digits;
+
+-- One row per merge: the removed customer as it was, and which rows were moved,
+-- so the merge can be undone without touching records added afterwards
+CREATE TABLE IF NOT EXISTS customer_merges (
+    merge_id SERIAL PRIMARY KEY,
+    source_customer_id VARCHAR(20) NOT NULL,
+    target_customer_id VARCHAR(20) NOT NULL,
+    source_snapshot JSONB NOT NULL,
+    moved_rows JSONB NOT NULL DEFAULT '{}'::jsonb,
+    filled_fields TEXT[] NOT NULL DEFAULT '{}',
+    balance_moved INTEGER NOT NULL DEFAULT 0,
+    merged_by VARCHAR(50),
+    merged_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    undone_by VARCHAR(50),
+    undone_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_customer_merges_target ON customer_merges (target_customer_id);
+CREATE INDEX IF NOT EXISTS idx_customer_merges_source ON customer_merges (source_customer_id);
@@ -0,0 +1,55 @@
+-- RFM scoring settings. In 'threshold' mode the arrays are the cut-offs for scores 2..5;
+-- in 'quantile' mode customers are scored by quintile and the arrays are ignored.
+CREATE TABLE IF NOT EXISTS rfm_config (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    mode VARCHAR(10) NOT NULL DEFAULT 'threshold' CHECK (mode IN ('threshold', 'quantile')),
+    -- Days since the last order below which R is 5, 4, 3, 2
+    recency_days INTEGER[] NOT NULL DEFAULT '{30,90,180,365}',
+    -- Order counts above which F is 2, 3, 4, 5
+    frequency_orders INTEGER[] NOT NULL DEFAULT '{2,5,10,20}',
+    -- Total amounts above which M is 2, 3, 4, 5
+    monetary_amounts BIGINT[] NOT NULL DEFAULT '{100000,500000,1000000,2000000}',
+    default_segment VARCHAR(50) NOT NULL DEFAULT '일반고객 (Need Attention)',
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO rfm_config (id) VALUES (1) ON CONFLICT (id) DO NOTHING;
+
+-- Segment rules on R/F/M scores; the first matching rule by priority names the segment
+CREATE TABLE IF NOT EXISTS rfm_segment_rules (
+    rule_id SERIAL PRIMARY KEY,
+    priority INTEGER NOT NULL DEFAULT 0,
+    segment_name VARCHAR(50) NOT NULL,
+    r_min INTEGER NOT NULL DEFAULT 1,
+    r_max INTEGER NOT NULL DEFAULT 5,
+    f_min INTEGER NOT NULL DEFAULT 1,
+    f_max INTEGER NOT NULL DEFAULT 5,
+    m_min INTEGER NOT NULL DEFAULT 1,
+    m_max INTEGER NOT NULL DEFAULT 5
+);
+
+-- Same segments the analysis used before rules were configurable
+INSERT INTO rfm_segment_rules (priority, segment_name, r_min, r_max, f_min, f_max)
+SELECT * FROM (VALUES
+    (10, '최우수고객 (Champions)', 5, 5, 4, 5),
+    (11, '최우수고객 (Champions)', 4, 4, 5, 5),
+    (20, '잠재고객 (Promising)', 4, 5, 2, 2),
+    (21, '잠재고객 (Promising)', 3, 3, 3, 3),
+    (30, '이탈위험 (At Risk)', 1, 2, 4, 5),
+    (40, '휴면고객 (Hibernating)', 1, 1, 1, 2),
+    (41, '휴면고객 (Hibernating)', 2, 2, 1, 1)
+) AS seed(priority, segment_name, r_min, r_max, f_min, f_max)
+WHERE NOT EXISTS (SELECT 1 FROM rfm_segment_rules);
+
+-- Nightly copy of every customer's segment, for migration reports between dates
+CREATE TABLE IF NOT EXISTS customer_segment_snapshots (
+    snapshot_date DATE NOT NULL,
+    customer_id VARCHAR(20) NOT NULL,
+    segment_name VARCHAR(50) NOT NULL,
+    recency INTEGER NOT NULL,
+    frequency INTEGER NOT NULL,
+    monetary INTEGER NOT NULL,
+    PRIMARY KEY (snapshot_date, customer_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_segment_snapshots_customer ON customer_segment_snapshots (customer_id, snapshot_date);
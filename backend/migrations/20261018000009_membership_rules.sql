@@ -0,0 +1,31 @@
+-- Rule-based membership levels, re-evaluated by a daily job
+CREATE TABLE IF NOT EXISTS membership_settings (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    auto_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    lookback_months INTEGER NOT NULL DEFAULT 12 CHECK (lookback_months > 0),
+    allow_downgrade BOOLEAN NOT NULL DEFAULT FALSE,
+    notify_upgrade BOOLEAN NOT NULL DEFAULT FALSE,
+    last_run_at TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO membership_settings (id) VALUES (1) ON CONFLICT (id) DO NOTHING;
+
+-- A customer gets the highest-ranked level whose conditions are met; a zero threshold is ignored
+CREATE TABLE IF NOT EXISTS membership_rules (
+    level_name VARCHAR(20) PRIMARY KEY,
+    level_rank INTEGER NOT NULL,
+    min_amount BIGINT NOT NULL DEFAULT 0,
+    min_orders INTEGER NOT NULL DEFAULT 0,
+    -- FALSE: either condition is enough
+    require_both BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+INSERT INTO membership_rules (level_name, level_rank, min_amount, min_orders) VALUES
+    ('일반', 0, 0, 0),
+    ('VIP', 1, 1000000, 5),
+    ('VVIP', 2, 3000000, 10)
+ON CONFLICT (level_name) DO NOTHING;
+
+-- Why a value changed (e.g. automatic level changes)
+ALTER TABLE customer_logs ADD COLUMN IF NOT EXISTS reason TEXT;
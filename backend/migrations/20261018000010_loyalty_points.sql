@@ -0,0 +1,53 @@
+-- Loyalty points: earned on delivered orders, spent as a payment, expiring after N months
+CREATE TABLE IF NOT EXISTS points_settings (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    expiry_months INTEGER NOT NULL DEFAULT 12 CHECK (expiry_months > 0),
+    -- Orders placed before this date never earn points (no retroactive earning when enabled)
+    earn_start_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    min_redeem_points INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO points_settings (id) VALUES (1) ON CONFLICT (id) DO NOTHING;
+
+-- Earn rate in percent of the paid amount. NULL channel/level matches any; the most specific rule wins.
+-- channel is the mall name of the order, or 'direct' for orders entered by hand.
+CREATE TABLE IF NOT EXISTS point_earn_rules (
+    rule_id SERIAL PRIMARY KEY,
+    channel VARCHAR(20),
+    membership_level VARCHAR(20),
+    earn_rate NUMERIC(5, 2) NOT NULL CHECK (earn_rate >= 0)
+);
+
+INSERT INTO point_earn_rules (channel, membership_level, earn_rate)
+SELECT * FROM (VALUES
+    (NULL::varchar, NULL::varchar, 1.00),
+    (NULL, 'VIP', 2.00),
+    (NULL, 'VVIP', 3.00)
+) AS seed(channel, membership_level, earn_rate)
+WHERE NOT EXISTS (SELECT 1 FROM point_earn_rules);
+
+-- '적립', '사용', '사용취소', '회수', '소멸', '조정'. Positive rows are lots spent first-to-expire;
+-- `remaining` is what is left of a lot.
+CREATE TABLE IF NOT EXISTS point_ledger (
+    entry_id SERIAL PRIMARY KEY,
+    customer_id VARCHAR(20) NOT NULL,
+    entry_type VARCHAR(20) NOT NULL,
+    points INTEGER NOT NULL,
+    remaining INTEGER NOT NULL DEFAULT 0,
+    expires_at DATE,
+    sales_id VARCHAR(20),
+    memo TEXT,
+    created_by VARCHAR(50),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_point_ledger_customer ON point_ledger (customer_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_point_ledger_sales ON point_ledger (sales_id);
+CREATE INDEX IF NOT EXISTS idx_point_ledger_lots ON point_ledger (customer_id, expires_at) WHERE remaining > 0;
+-- A sale earns once
+CREATE UNIQUE INDEX IF NOT EXISTS uq_point_ledger_earn ON point_ledger (sales_id) WHERE entry_type = '적립';
+
+-- Points spent on an order, alongside paid_amount
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS points_used INTEGER NOT NULL DEFAULT 0;
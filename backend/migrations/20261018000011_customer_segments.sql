@@ -0,0 +1,10 @@
+-- Saved customer segments: a filter definition evaluated on demand, usable as SMS recipients
+CREATE TABLE IF NOT EXISTS customer_segments (
+    segment_id SERIAL PRIMARY KEY,
+    segment_name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    definition JSONB NOT NULL DEFAULT '{}',
+    created_by VARCHAR(50),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,41 @@
+-- Marketing consent history per channel ('sms', 'kakao', 'email'); the latest record is the current state.
+-- customers.marketing_consent mirrors the current 'sms' consent.
+CREATE TABLE IF NOT EXISTS customer_consents (
+    consent_id SERIAL PRIMARY KEY,
+    customer_id VARCHAR(20) NOT NULL,
+    channel VARCHAR(10) NOT NULL DEFAULT 'sms',
+    consented BOOLEAN NOT NULL,
+    -- Where it was given or withdrawn, e.g. '고객정보', '080수신거부', '문자회신', '기존 데이터'
+    source VARCHAR(30) NOT NULL,
+    memo TEXT,
+    recorded_by VARCHAR(50),
+    recorded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_customer_consents_current ON customer_consents (customer_id, channel, recorded_at DESC);
+
+INSERT INTO customer_consents (customer_id, channel, consented, source, recorded_at)
+SELECT customer_id, 'sms', TRUE, '기존 데이터', COALESCE(updated_at, created_at, CURRENT_TIMESTAMP)
+FROM customers
+WHERE marketing_consent = TRUE
+  AND NOT EXISTS (SELECT 1 FROM customer_consents);
+
+-- Numbers that refused advertising (080 line, reply keyword), including non-customers
+CREATE TABLE IF NOT EXISTS marketing_opt_outs (
+    mobile_digits VARCHAR(20) PRIMARY KEY,
+    source VARCHAR(30) NOT NULL,
+    memo TEXT,
+    opted_out_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Sender name and free opt-out number printed on advertising messages
+CREATE TABLE IF NOT EXISTS marketing_settings (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    business_name VARCHAR(50),
+    optout_number VARCHAR(20),
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO marketing_settings (id) VALUES (1) ON CONFLICT (id) DO NOTHING;
+
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS is_ad BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,28 @@
+-- Retention of personal data: customers inactive past the retention period get a notice,
+-- then their personal fields are anonymized (sales amounts are kept)
+CREATE TABLE IF NOT EXISTS privacy_settings (
+    id INTEGER PRIMARY KEY DEFAULT 1 CHECK (id = 1),
+    auto_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    retention_months INTEGER NOT NULL DEFAULT 36 CHECK (retention_months > 0),
+    -- Days between the notice and anonymization
+    notice_days INTEGER NOT NULL DEFAULT 30 CHECK (notice_days >= 0),
+    last_run_at TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO privacy_settings (id) VALUES (1) ON CONFLICT (id) DO NOTHING;
+
+-- '사전안내' (notice) and '익명화' (anonymized). A notice belongs to the last activity it was
+-- sent for; activity after it starts a new cycle.
+CREATE TABLE IF NOT EXISTS privacy_actions (
+    action_id SERIAL PRIMARY KEY,
+    customer_id VARCHAR(20) NOT NULL,
+    action VARCHAR(20) NOT NULL,
+    last_activity DATE,
+    scheduled_for DATE,
+    performed_by VARCHAR(50),
+    performed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (customer_id, action, last_activity)
+);
+
+ALTER TABLE customers ADD COLUMN IF NOT EXISTS anonymized_at TIMESTAMP;
@@ -0,0 +1,40 @@
+-- Referrals: each customer can get a code to hand out; orders can carry the code they came with
+ALTER TABLE customers ADD COLUMN IF NOT EXISTS referral_code VARCHAR(20);
+ALTER TABLE customers ADD COLUMN IF NOT EXISTS referred_by VARCHAR(20);
+CREATE UNIQUE INDEX IF NOT EXISTS uq_customers_referral_code ON customers (referral_code) WHERE referral_code IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_customers_referred_by ON customers (referred_by) WHERE referred_by IS NOT NULL;
+
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS referral_code VARCHAR(20);
+CREATE INDEX IF NOT EXISTS idx_sales_referral_code ON sales (referral_code) WHERE referral_code IS NOT NULL;
+
+-- recipient: 'referrer' or 'referee'. reward_type 'points' credits `amount` points once the
+-- order is delivered; 'discount' gives the referee `amount` percent off the order with the code.
+CREATE TABLE IF NOT EXISTS referral_reward_rules (
+    rule_id SERIAL PRIMARY KEY,
+    rule_name VARCHAR(50) NOT NULL,
+    recipient VARCHAR(10) NOT NULL CHECK (recipient IN ('referrer', 'referee')),
+    reward_type VARCHAR(10) NOT NULL CHECK (reward_type IN ('points', 'discount')),
+    amount INTEGER NOT NULL CHECK (amount > 0),
+    min_order_amount INTEGER NOT NULL DEFAULT 0,
+    -- Only the referee's first order with a code counts
+    first_order_only BOOLEAN NOT NULL DEFAULT TRUE,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Rewards given per referred order. Points rewards are also booked in point_ledger as '추천적립'.
+CREATE TABLE IF NOT EXISTS referral_rewards (
+    reward_id SERIAL PRIMARY KEY,
+    rule_id INTEGER NOT NULL,
+    sales_id VARCHAR(20) NOT NULL,
+    referrer_id VARCHAR(20) NOT NULL,
+    referee_id VARCHAR(20) NOT NULL,
+    recipient VARCHAR(10) NOT NULL,
+    reward_type VARCHAR(10) NOT NULL,
+    -- Points credited, or the discount in won
+    amount INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (rule_id, sales_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_referral_rewards_referrer ON referral_rewards (referrer_id);
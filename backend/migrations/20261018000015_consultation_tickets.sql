@@ -0,0 +1,40 @@
+-- Consultations as tickets: an assignee (users.id), a due time from the priority's SLA,
+-- and an optional link to the sale or claim they are about
+ALTER TABLE consultations ADD COLUMN IF NOT EXISTS assignee_id INTEGER;
+ALTER TABLE consultations ADD COLUMN IF NOT EXISTS due_at TIMESTAMP;
+ALTER TABLE consultations ADD COLUMN IF NOT EXISTS resolved_at TIMESTAMP;
+ALTER TABLE consultations ADD COLUMN IF NOT EXISTS sales_id VARCHAR(20);
+ALTER TABLE consultations ADD COLUMN IF NOT EXISTS claim_id INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_consultations_open_due ON consultations (due_at) WHERE status IN ('접수', '처리중');
+CREATE INDEX IF NOT EXISTS idx_consultations_assignee ON consultations (assignee_id);
+
+-- Hours allowed until a ticket of each priority should be answered
+CREATE TABLE IF NOT EXISTS consultation_sla (
+    priority VARCHAR(20) PRIMARY KEY,
+    response_hours INTEGER NOT NULL CHECK (response_hours > 0)
+);
+
+INSERT INTO consultation_sla (priority, response_hours) VALUES
+    ('긴급', 4),
+    ('높음', 24),
+    ('보통', 48),
+    ('낮음', 72)
+ON CONFLICT (priority) DO NOTHING;
+
+UPDATE consultations c SET due_at = COALESCE(c.created_at, c.consult_date::timestamp) + make_interval(hours => s.response_hours)
+FROM consultation_sla s
+WHERE s.priority = c.priority AND c.due_at IS NULL AND c.status IN ('접수', '처리중');
+
+-- Status, priority, assignee and link changes of a ticket
+CREATE TABLE IF NOT EXISTS consultation_history (
+    history_id SERIAL PRIMARY KEY,
+    consult_id INTEGER NOT NULL,
+    field_name VARCHAR(20) NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    changed_by VARCHAR(50),
+    changed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_consultation_history_consult ON consultation_history (consult_id, changed_at);
@@ -0,0 +1,46 @@
+-- Rules that message customers automatically: N days before their anniversary, or when a
+-- repurchase is predicted within N days
+CREATE TABLE IF NOT EXISTS automation_rules (
+    rule_id SERIAL PRIMARY KEY,
+    rule_name VARCHAR(100) NOT NULL,
+    -- 'anniversary' or 'repurchase'
+    trigger_type VARCHAR(20) NOT NULL,
+    offset_days INTEGER NOT NULL DEFAULT 0,
+    -- Category in the message templates
+    template_key VARCHAR(50) NOT NULL,
+    -- 'promotional' or 'transactional'
+    purpose VARCHAR(20) NOT NULL DEFAULT 'promotional',
+    -- Customers sent any automated message within this many days are skipped
+    dedup_days INTEGER NOT NULL DEFAULT 7 CHECK (dedup_days >= 0),
+    is_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS automation_runs (
+    run_id SERIAL PRIMARY KEY,
+    triggered_by VARCHAR(50),
+    started_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMP,
+    matched INTEGER NOT NULL DEFAULT 0,
+    sent INTEGER NOT NULL DEFAULT 0,
+    skipped INTEGER NOT NULL DEFAULT 0
+);
+
+-- Every target of a run; skip_reason is NULL for messages that went out. trigger_date is
+-- the anniversary or predicted repurchase date, so one event is messaged once per rule.
+CREATE TABLE IF NOT EXISTS automation_sends (
+    send_id SERIAL PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES automation_runs(run_id) ON DELETE CASCADE,
+    rule_id INTEGER NOT NULL,
+    customer_id VARCHAR(20) NOT NULL,
+    trigger_date DATE NOT NULL,
+    mobile_number VARCHAR(50),
+    content TEXT,
+    skip_reason TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_automation_sends_event
+    ON automation_sends (rule_id, customer_id, trigger_date) WHERE skip_reason IS NULL;
+CREATE INDEX IF NOT EXISTS idx_automation_sends_customer ON automation_sends (customer_id, created_at);
@@ -0,0 +1,11 @@
+-- Messages now go out through a real gateway: keep which one, the message type, what it
+-- cost and the gateway's own id so delivery reports can be matched later
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS provider VARCHAR(20);
+-- 'SMS', 'LMS' or 'MMS'
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS message_type VARCHAR(10);
+-- Won, only for messages the gateway accepted
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS cost INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS provider_message_id VARCHAR(100);
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS error_message TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_sms_logs_provider_message ON sms_logs (provider_message_id);
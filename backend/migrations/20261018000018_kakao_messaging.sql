@@ -0,0 +1,15 @@
+-- Approved AlimTalk templates, keyed by the message template category they replace
+CREATE TABLE IF NOT EXISTS kakao_templates (
+    template_key VARCHAR(50) PRIMARY KEY,
+    template_code VARCHAR(50) NOT NULL,
+    description TEXT,
+    is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 'ALIMTALK' / 'FRIENDTALK' rows carry the Kakao template code; an SMS sent because
+-- KakaoTalk failed points at the log id of that KakaoTalk send
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS template_code VARCHAR(50);
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS fallback_of VARCHAR(50);
+
+CREATE INDEX IF NOT EXISTS idx_sms_logs_fallback ON sms_logs (fallback_of) WHERE fallback_of IS NOT NULL;
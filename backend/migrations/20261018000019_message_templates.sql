@@ -0,0 +1,14 @@
+-- Message templates, formerly templates.json; several per category, in the order shown
+CREATE TABLE IF NOT EXISTS message_templates (
+    template_id SERIAL PRIMARY KEY,
+    category VARCHAR(50) NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    content TEXT NOT NULL,
+    updated_by VARCHAR(50),
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_templates_category ON message_templates (category, sort_order);
+
+-- Account customers pay into, for the ${bank_account} variable
+ALTER TABLE company_info ADD COLUMN IF NOT EXISTS bank_account VARCHAR(100);
@@ -0,0 +1,37 @@
+-- Sends handed to the background worker: large campaigns and scheduled messages.
+-- status: '대기' (waiting for its time), '발송중', '완료', '취소'
+CREATE TABLE IF NOT EXISTS message_campaigns (
+    campaign_id VARCHAR(30) PRIMARY KEY,
+    mode VARCHAR(20) NOT NULL,
+    purpose VARCHAR(20) NOT NULL,
+    content TEXT NOT NULL,
+    subject VARCHAR(200),
+    template_code VARCHAR(50),
+    image_name VARCHAR(200),
+    image_data BYTEA,
+    status VARCHAR(20) NOT NULL DEFAULT '대기',
+    scheduled_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_by VARCHAR(50),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP,
+    finished_at TIMESTAMP
+);
+
+-- One row per recipient. status: '대기', '발송', '실패', '제외' (no consent), '취소';
+-- message_id is the sms_logs log_id of the send that reached the recipient
+CREATE TABLE IF NOT EXISTS message_outbox (
+    outbox_id SERIAL PRIMARY KEY,
+    campaign_id VARCHAR(30) NOT NULL REFERENCES message_campaigns(campaign_id) ON DELETE CASCADE,
+    recipient VARCHAR(50) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT '대기',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_error TEXT,
+    message_id VARCHAR(50),
+    sent_at TIMESTAMP,
+    updated_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_outbox_campaign ON message_outbox (campaign_id, status);
+CREATE INDEX IF NOT EXISTS idx_message_outbox_due
+    ON message_outbox (next_attempt_at) WHERE status = '대기';
@@ -0,0 +1,21 @@
+-- Provider delivery reports: when the handset got the message, and that a report came in
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS delivered_at TIMESTAMP;
+ALTER TABLE sms_logs ADD COLUMN IF NOT EXISTS reported_at TIMESTAMP;
+
+-- Replies customers sent to our number or Kakao channel. A reply is filed as a
+-- consultation unless it was an opt-out; provider callbacks may repeat.
+CREATE TABLE IF NOT EXISTS inbound_messages (
+    inbound_id SERIAL PRIMARY KEY,
+    provider VARCHAR(20) NOT NULL,
+    provider_message_id VARCHAR(100),
+    channel VARCHAR(10) NOT NULL DEFAULT 'sms',
+    mobile_number VARCHAR(20) NOT NULL,
+    content TEXT NOT NULL,
+    customer_id VARCHAR(20),
+    consult_id INTEGER REFERENCES consultations(consult_id) ON DELETE SET NULL,
+    opted_out BOOLEAN NOT NULL DEFAULT FALSE,
+    received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provider, provider_message_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_inbound_messages_customer ON inbound_messages (customer_id);
@@ -214,6 +214,7 @@ pub async fn get_sales_on_date(
 
 pub async fn save_general_sales_batch_bridge(
     State((pool, _)): State<(DbPool, PathBuf)>,
+    Extension(claims): Extension<Claims>,
     Json(payload): Json<Value>,
 ) -> impl IntoResponse {
     let items_val = payload.get("items");
@@ -231,7 +232,13 @@ pub async fn save_general_sales_batch_bridge(
         vec![]
     };
 
-    let res = save_general_sales_batch_internal(&pool, items, deleted_ids).await;
+    let res = save_general_sales_batch_internal(
+        &pool,
+        claims.username.as_deref().unwrap_or("Admin"),
+        items,
+        deleted_ids,
+    )
+    .await;
 
     match res {
         Ok(_) => Json(json!({ "success": true })),
@@ -257,6 +264,17 @@ pub async fn get_shipments_bridge(
     }
 }
 
+/// The status change itself stands when the message cannot be sent; the failure is logged.
+async fn notify_customer(pool: &DbPool, category: &str, sales_id: &str) -> bool {
+    match crate::commands::message_template::send_order_message(pool, category, sales_id).await {
+        Ok(sent) => sent,
+        Err(e) => {
+            tracing::warn!("{} message for order {} failed: {}", category, sales_id, e);
+            false
+        }
+    }
+}
+
 pub async fn update_sale_status_bridge(
     State((pool, _)): State<(DbPool, PathBuf)>,
     Extension(claims): Extension<Claims>,
@@ -267,6 +285,10 @@ pub async fn update_sale_status_bridge(
         .and_then(|v| v.as_str())
         .unwrap_or("");
     let status = payload.get("status").and_then(|v| v.as_str()).unwrap_or("");
+    let notify = payload
+        .get("notify")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
     let username = claims.username.as_deref().unwrap_or("Admin");
 
     if sales_id.is_empty() || status.is_empty() {
@@ -291,7 +313,13 @@ pub async fn update_sale_status_bridge(
     match res {
         Ok(_) => {
             let _ = tx.commit().await;
-            Json(json!({ "success": true }))
+            crate::commands::points::sync_sale_points(&pool, sales_id).await;
+            let category = crate::commands::message_template::order_message_category(status);
+            let notified = match category.filter(|_| notify) {
+                Some(category) => notify_customer(&pool, category, sales_id).await,
+                None => false,
+            };
+            Json(json!({ "success": true, "notified": notified }))
         }
         Err(e) => Json(json!({ "success": false, "error": e.to_string() })),
     }
@@ -323,12 +351,16 @@ pub async fn complete_shipment_bridge(
         .and_then(|v| v.as_str())
         .unwrap_or("");
 
+    let notify = payload
+        .get("notify")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
     let username = claims.username.as_deref().unwrap_or("Admin");
 
     match crate::commands::sales::order::complete_shipment(
         crate::stubs::State::from(&pool),
         username,
-        sales_id,
+        sales_id.clone(),
         None, // memo
         carrier,
         tracking,
@@ -336,7 +368,25 @@ pub async fn complete_shipment_bridge(
     )
     .await
     {
-        Ok(_) => Json(json!({ "success": true })),
+        Ok(_) => {
+            // Push the tracking number to the originating mall without holding up the response
+            let sync_pool = pool.clone();
+            tokio::spawn(async move {
+                let _ = crate::commands::sales::mall_sync::process_mall_shipment_queue(&sync_pool)
+                    .await;
+            });
+
+            // Perishable goods: warn when the parcel would wait in a depot over a weekend/holiday
+            let ship_date = crate::commands::sales::utils::parse_date_safe(shipping_date_str)
+                .unwrap_or_else(|| chrono::Local::now().date_naive());
+            let warning = crate::commands::shipping_calendar::check_shipment_arrival(
+                &pool, &sales_id, ship_date,
+            )
+            .await
+            .unwrap_or(None);
+            let notified = notify && notify_customer(&pool, "shipping_done", &sales_id).await;
+            Json(json!({ "success": true, "warning": warning, "notified": notified }))
+        }
         Err(e) => Json(json!({ "success": false, "error": e.to_string() })),
     }
 }
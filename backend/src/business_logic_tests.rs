@@ -78,4 +78,1128 @@ mod tests {
         assert_eq!(supply, 9091);
         assert_eq!(vat, 909);
     }
+
+    #[test]
+    fn test_pick_list_aggregation() {
+        use crate::commands::logistics::PendingShipment;
+        use crate::commands::packing::aggregate_pick_list;
+
+        let line = |id: &str, product: &str, spec: &str, qty: i32| PendingShipment {
+            sales_id: id.to_string(),
+            order_date: None,
+            customer_name: None,
+            customer_mobile_number: None,
+            shipping_name: None,
+            shipping_mobile_number: None,
+            shipping_zip_code: None,
+            shipping_address_primary: None,
+            shipping_address_detail: None,
+            product_name: product.to_string(),
+            specification: Some(spec.to_string()),
+            unit_price: 0,
+            quantity: qty,
+            total_amount: 0,
+            memo: None,
+            courier_name: None,
+            tracking_number: None,
+            transit_days: None,
+            safe_ship_date: None,
+        };
+
+        let items = aggregate_pick_list(&[
+            line("S-1", "표고버섯", "1kg", 2),
+            line("S-2", "느타리버섯", "500g", 1),
+            line("S-3", "표고버섯", "1kg", 3),
+            line("S-4", "표고버섯", "2kg", 1),
+        ]);
+
+        // Same product with a different spec is picked separately
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].product_name, "느타리버섯");
+        assert_eq!(items[1].specification.as_deref(), Some("1kg"));
+        assert_eq!(items[1].total_quantity, 5);
+        assert_eq!(items[1].order_count, 2);
+        assert_eq!(items[2].total_quantity, 1);
+    }
+
+    #[test]
+    fn test_road_address_parsing() {
+        use crate::commands::address::{address_dedup_key, parse_road_address};
+
+        let p = parse_road_address("서울 강남구 테헤란로 123-4, 5층 (역삼동)").unwrap();
+        assert_eq!(p.sido.as_deref(), Some("서울특별시"));
+        assert_eq!(p.sigungu.as_deref(), Some("강남구"));
+        assert_eq!(p.road_name, "테헤란로");
+        assert_eq!((p.building_main, p.building_sub), (123, 4));
+        assert_eq!(p.detail, "5층");
+
+        // Split road name and glued building number
+        let p = parse_road_address("경기도 성남시 분당구 봉은사로 1길 23").unwrap();
+        assert_eq!(p.road_name, "봉은사로1길");
+        assert_eq!(p.sigungu.as_deref(), Some("성남시 분당구"));
+        let p = parse_road_address("테헤란로123 지하상가").unwrap();
+        assert_eq!(p.building_main, 123);
+
+        assert!(parse_road_address("강남구 역삼동 어딘가").is_none());
+
+        // Same place written differently
+        assert_eq!(
+            address_dedup_key("서울특별시 강남구 테헤란로 123", Some("101동 1203호")),
+            address_dedup_key("서울 강남구 테헤란로123", Some("101-1203"))
+        );
+        assert_ne!(
+            address_dedup_key("서울특별시 강남구 테헤란로 123", Some("101동 1203호")),
+            address_dedup_key("서울특별시 강남구 테헤란로 123", Some("102동 1203호"))
+        );
+    }
+
+    #[test]
+    fn test_postal_db_line_and_remote_area() {
+        use crate::commands::address::{find_remote_area, parse_postal_building_line, RemoteArea};
+
+        let line = "5011025022|제주특별자치도|제주시|애월읍|고내리|0|1|0|501104459043|애월해안로|0|200|0|카페||5011025022100010000000001|01|5011025000|애월읍|63045|";
+        let row = parse_postal_building_line(line).unwrap();
+        assert_eq!(row.zip_code, "63045");
+        assert_eq!(row.road_name, "애월해안로");
+        assert_eq!(row.building_main, 200);
+        assert!(parse_postal_building_line("garbage|line").is_none());
+
+        let areas = vec![RemoteArea {
+            area_id: 1,
+            zip_start: "63000".to_string(),
+            zip_end: "63644".to_string(),
+            area_name: "제주".to_string(),
+            is_jeju: true,
+        }];
+        assert!(find_remote_area("63045", &areas).is_some());
+        assert!(find_remote_area("06236", &areas).is_none());
+        assert!(find_remote_area("630", &areas).is_none());
+    }
+
+    #[test]
+    fn test_shipping_calendar_safe_dates() {
+        use crate::commands::shipping_calendar::{parse_holiday_list, ShippingCalendar};
+        use chrono::NaiveDate;
+
+        let d = |m: u32, day: u32| NaiveDate::from_ymd_opt(2026, m, day).unwrap();
+        // No delivery or pickup on Sundays, 2026-10-09 (Fri) is 한글날
+        let calendar = ShippingCalendar {
+            non_delivery_weekdays: vec![6],
+            no_pickup_weekdays: vec![6],
+            holidays: [d(10, 9)].into_iter().collect(),
+        };
+
+        // Fri 10-16 -> Sat arrival is fine for next-day delivery
+        assert!(calendar.is_safe_ship_date(d(10, 16), 1));
+        // Sat 10-17 -> waits over Sunday, arrives Monday
+        assert_eq!(calendar.estimate_arrival(d(10, 17), 1), d(10, 19));
+        assert!(!calendar.is_safe_ship_date(d(10, 17), 1));
+        assert_eq!(
+            calendar.earliest_safe_ship_date(d(10, 17), 1),
+            Some(d(10, 19))
+        );
+        assert!(calendar.ship_date_warning(d(10, 17), 1).is_some());
+        assert!(calendar.ship_date_warning(d(10, 18), 1).is_some());
+
+        // Two-day transit (Jeju) must not leave on Friday
+        assert!(!calendar.is_safe_ship_date(d(10, 16), 2));
+        assert!(calendar.is_safe_ship_date(d(10, 15), 2));
+
+        // Holiday blocks both pickup and delivery
+        assert!(!calendar.is_safe_ship_date(d(10, 8), 1));
+        assert_eq!(
+            calendar.earliest_safe_ship_date(d(10, 8), 1),
+            Some(d(10, 12))
+        );
+
+        let holidays =
+            parse_holiday_list("locdate,dateName\n20261003,개천절\n2026-10-09, 한글날\n");
+        assert_eq!(holidays.len(), 2);
+        assert_eq!(holidays[0].holiday_date, d(10, 3));
+        assert_eq!(holidays[1].holiday_name, "한글날");
+    }
+
+    #[test]
+    fn test_mall_import_keys_and_mobile_matching() {
+        use crate::commands::sales::external::MallOrderItem;
+        use crate::commands::sales::mall_import::{
+            format_mobile, is_personal_mobile, mapping_key, mobile_digits,
+        };
+
+        assert_eq!(mobile_digits("010-1234-5678"), "01012345678");
+        assert!(is_personal_mobile("01012345678"));
+        assert!(is_personal_mobile("0111234567"));
+        // Coupang/Naver relay numbers do not identify a customer
+        assert!(!is_personal_mobile(&mobile_digits("0502-1234-5678")));
+        assert_eq!(format_mobile("01012345678"), "010-1234-5678");
+        assert_eq!(format_mobile("0111234567"), "011-123-4567");
+
+        let mut item = MallOrderItem {
+            order_id: "1".to_string(),
+            line_id: "1".to_string(),
+            mall_product_code: Some(" 8812345678 ".to_string()),
+            mall_shipment_id: None,
+            order_date: None,
+            customer_name: String::new(),
+            receiver_name: String::new(),
+            mobile: String::new(),
+            zip: String::new(),
+            address: String::new(),
+            mall_product_name: "표고버섯 1kg".to_string(),
+            qty: 1,
+            unit_price: 0,
+            memo: None,
+        };
+        assert_eq!(mapping_key(&item), "8812345678");
+        item.mall_product_code = None;
+        assert_eq!(mapping_key(&item), "표고버섯 1kg");
+    }
+
+    #[test]
+    fn test_mall_courier_codes_and_retry_backoff() {
+        use crate::commands::sales::mall_sync::{mall_courier_code, next_retry_delay_minutes};
+
+        assert_eq!(mall_courier_code("CJ대한통운"), Some("CJGLS"));
+        assert_eq!(mall_courier_code("우체국택배"), Some("EPOST"));
+        assert_eq!(mall_courier_code("롯데택배"), Some("HYUNDAI"));
+        assert_eq!(mall_courier_code("로젠택배"), Some("KGB"));
+        assert_eq!(mall_courier_code("직접배송"), None);
+
+        assert_eq!(next_retry_delay_minutes(0), 5);
+        assert_eq!(next_retry_delay_minutes(2), 20);
+        assert_eq!(next_retry_delay_minutes(7), 360);
+    }
+
+    #[test]
+    fn test_mall_publishable_quantity() {
+        use crate::commands::sales::mall_stock::publishable_quantity;
+
+        assert_eq!(publishable_quantity(10, 3), 7);
+        assert_eq!(publishable_quantity(2, 3), 0);
+        assert_eq!(publishable_quantity(-4, 0), 0);
+        // A negative buffer is treated as none
+        assert_eq!(publishable_quantity(5, -2), 5);
+    }
+
+    #[test]
+    fn test_settlement_csv_parsing() {
+        use crate::commands::finance::settlement::{parse_amount, parse_settlement_csv};
+
+        assert_eq!(parse_amount("1,234원"), 1234);
+        assert_eq!(parse_amount("-2,000"), -2000);
+        assert_eq!(parse_amount("(500)"), -500);
+        assert_eq!(parse_amount(""), 0);
+
+        // Naver: quoted amounts, negative fees, a total row at the end
+        let naver = "\u{feff}정산내역 상세\n\
+            주문번호,상품주문번호,정산완료일,결제금액,매출연동수수료,결제수수료,정산금액\n\
+            2026101512345,2026101598765,2026.10.17,\"25,000\",\"-500\",\"-920\",\"23,580\"\n\
+            합계,,,\"25,000\",\"-500\",\"-920\",\"23,580\"\n";
+        let lines = parse_settlement_csv(naver).unwrap();
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert_eq!(line.mall_order_id, "2026101512345");
+        assert_eq!(line.mall_line_id, "2026101598765");
+        assert_eq!(
+            line.settle_date,
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 17)
+        );
+        assert_eq!(
+            (line.gross_amount, line.commission_amount, line.fee_amount, line.net_amount),
+            (25000, 500, 920, 23580)
+        );
+
+        // Coupang without a net column: net is derived from the fees
+        let coupang = "주문ID,옵션ID,매출인식일,판매액,판매수수료\n\
+            30000111,70000222,2026-10-16 00:00:00,10000,1080\n";
+        let lines = parse_settlement_csv(coupang).unwrap();
+        assert_eq!(lines[0].mall_line_id, "70000222");
+        assert_eq!(lines[0].net_amount, 8920);
+        assert_eq!(
+            lines[0].settle_date,
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 16)
+        );
+
+        // A refund row gives the fees back, whichever sign the mall prints them with
+        let refund = "주문번호,결제금액,매출연동수수료,결제수수료\n\
+            2026101512345,\"-25,000\",500,\"-920\"\n";
+        let lines = parse_settlement_csv(refund).unwrap();
+        let line = &lines[0];
+        assert_eq!(
+            (
+                line.gross_amount,
+                line.commission_amount,
+                line.fee_amount,
+                line.net_amount
+            ),
+            (-25000, -500, -920, -23580)
+        );
+
+        assert!(parse_settlement_csv("상품명,금액\n버섯,1000\n").is_err());
+    }
+
+    #[test]
+    fn test_customer_duplicate_scoring() {
+        use crate::commands::customer_merge::{find_duplicate_pairs, name_distance, DuplicateKey};
+
+        assert_eq!(name_distance("홍길동", "홍 길동"), 0);
+        assert_eq!(name_distance("홍길동", "홍길둥"), 1);
+        assert_eq!(name_distance("홍길동", "김철수"), 3);
+
+        let customer = |id: &str, name: &str, mobile: &str, addr: Option<&str>| DuplicateKey {
+            customer_id: id.to_string(),
+            customer_name: name.to_string(),
+            mobile_number: mobile.to_string(),
+            address_primary: addr.map(|a| a.to_string()),
+            address_detail: Some("101동 202호".to_string()),
+        };
+        let customers = vec![
+            customer("C1", "홍길동", "010-1234-5678", None),
+            customer("C2", "홍 길동", "01012345678", None),
+            customer("C3", "홍길둥", "010-9999-0000", Some("서울 강남구 테헤란로 1")),
+            customer("C4", "홍길동", "010-8888-0000", Some("서울 강남구 테헤란로 1")),
+            // Same name only: a different person
+            customer("C5", "홍길동", "010-7777-1111", None),
+        ];
+        let pairs = find_duplicate_pairs(&customers);
+        let found: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|(i, j, _, _)| {
+                (
+                    customers[*i].customer_id.as_str(),
+                    customers[*j].customer_id.as_str(),
+                )
+            })
+            .collect();
+        // Same mobile written differently scores highest; similar name at the same address follows
+        assert_eq!(found[0], ("C1", "C2"));
+        assert!(found.contains(&("C3", "C4")));
+        assert!(!found.iter().any(|(a, b)| *a == "C5" || *b == "C5"));
+        assert!(pairs[0].3.contains(&"휴대폰 번호 일치"));
+    }
+
+    #[test]
+    fn test_rfm_configurable_scoring() {
+        use crate::commands::rfm::{
+            quantile_score, recency_threshold_score, score_customers, segment_for,
+            threshold_score, RfmSegmentRule, RfmSettings,
+        };
+        use crate::db::RawRfmData;
+
+        // Default cut-offs reproduce the original fixed scoring
+        let days = [30, 90, 180, 365];
+        assert_eq!(recency_threshold_score(Some(29), &days), 5);
+        assert_eq!(recency_threshold_score(Some(30), &days), 4);
+        assert_eq!(recency_threshold_score(Some(200), &days), 2);
+        assert_eq!(recency_threshold_score(Some(400), &days), 1);
+        assert_eq!(recency_threshold_score(None, &days), 1);
+        assert_eq!(threshold_score(21i64, &[2, 5, 10, 20]), 5);
+        assert_eq!(threshold_score(3i64, &[2, 5, 10, 20]), 2);
+        assert_eq!(threshold_score(100_000i64, &[100_000, 500_000, 1_000_000, 2_000_000]), 1);
+
+        let sorted = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        assert_eq!(quantile_score(10, &sorted, true), 5);
+        assert_eq!(quantile_score(1, &sorted, true), 1);
+        assert_eq!(quantile_score(1, &sorted, false), 5);
+        // Ties share a score
+        assert_eq!(quantile_score(3, &[3, 3, 3, 3], true), 1);
+
+        let rule = |name: &str, r: (i32, i32), f: (i32, i32)| RfmSegmentRule {
+            priority: 0,
+            segment_name: name.to_string(),
+            r_min: r.0,
+            r_max: r.1,
+            f_min: f.0,
+            f_max: f.1,
+            m_min: 1,
+            m_max: 5,
+        };
+        let mut settings = RfmSettings {
+            mode: "threshold".to_string(),
+            recency_days: days.to_vec(),
+            frequency_orders: vec![2, 5, 10, 20],
+            monetary_amounts: vec![100_000, 500_000, 1_000_000, 2_000_000],
+            default_segment: "일반".to_string(),
+            rules: vec![rule("VIP", (4, 5), (4, 5)), rule("휴면", (1, 1), (1, 5))],
+        };
+        assert_eq!(segment_for(&settings, 5, 4, 1), "VIP");
+        assert_eq!(segment_for(&settings, 1, 5, 5), "휴면");
+        assert_eq!(segment_for(&settings, 3, 3, 3), "일반");
+
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let raw = |id: &str, days_ago: i64, orders: i64, amount: i64| RawRfmData {
+            customer_id: id.to_string(),
+            customer_name: id.to_string(),
+            mobile_number: String::new(),
+            membership_level: None,
+            last_order_date: Some(today - chrono::Duration::days(days_ago)),
+            total_orders: orders,
+            total_amount: amount,
+        };
+        let customers = || vec![raw("A", 5, 3, 50_000), raw("B", 400, 1, 10_000)];
+        let scored = score_customers(&settings, customers(), today);
+        assert_eq!((scored[0].recency, scored[0].frequency), (5, 2));
+        assert_eq!(scored[1].rfm_segment, "휴면");
+
+        // In quantile mode the same small customer base spreads over the full range
+        settings.mode = "quantile".to_string();
+        let scored = score_customers(&settings, customers(), today);
+        assert_eq!((scored[0].recency, scored[0].frequency), (5, 5));
+        assert_eq!(scored[0].rfm_segment, "VIP");
+    }
+
+    #[test]
+    fn test_membership_rule_matching() {
+        use crate::commands::membership::{membership_reason, qualifying_level, MembershipRule};
+
+        let rule = |name: &str, rank: i32, amount: i64, orders: i32, both: bool| MembershipRule {
+            level_name: name.to_string(),
+            level_rank: rank,
+            min_amount: amount,
+            min_orders: orders,
+            require_both: both,
+        };
+        let rules = vec![
+            rule("일반", 0, 0, 0, true),
+            rule("VIP", 1, 1_000_000, 5, true),
+            rule("VVIP", 2, 3_000_000, 10, false),
+        ];
+        let level = |amount, orders| qualifying_level(&rules, amount, orders).map(|r| r.level_name.as_str());
+
+        assert_eq!(level(0, 0), Some("일반"));
+        // VIP needs both conditions
+        assert_eq!(level(1_500_000, 3), Some("일반"));
+        assert_eq!(level(1_500_000, 5), Some("VIP"));
+        // VVIP needs either one
+        assert_eq!(level(200_000, 12), Some("VVIP"));
+        assert_eq!(level(3_000_000, 1), Some("VVIP"));
+
+        assert_eq!(
+            membership_reason(12, 1_234_000, 7, true),
+            "최근 12개월 구매 1,234,000원 / 7회 기준 자동 승급"
+        );
+    }
+
+    #[test]
+    fn test_point_earn_rules_and_reversal() {
+        use crate::commands::points::{
+            earn_rate_for, points_for_amount, reversal_target, PointEarnRule,
+        };
+
+        let rule = |channel: Option<&str>, level: Option<&str>, rate: f64| PointEarnRule {
+            channel: channel.map(String::from),
+            membership_level: level.map(String::from),
+            earn_rate: rate,
+        };
+        let rules = vec![
+            rule(None, None, 1.0),
+            rule(None, Some("VIP"), 2.0),
+            rule(Some("naver"), None, 0.5),
+            rule(Some("naver"), Some("VIP"), 1.5),
+        ];
+
+        assert_eq!(earn_rate_for(&rules, "direct", None), 1.0);
+        assert_eq!(earn_rate_for(&rules, "direct", Some("VIP")), 2.0);
+        // A channel rule wins over a level-only rule
+        assert_eq!(earn_rate_for(&rules, "Naver", Some("VVIP")), 0.5);
+        assert_eq!(earn_rate_for(&rules, "naver", Some("VIP")), 1.5);
+        assert_eq!(earn_rate_for(&[], "direct", None), 0.0);
+
+        assert_eq!(points_for_amount(123_456, 1.5), 1851);
+        assert_eq!(points_for_amount(-5_000, 1.0), 0);
+
+        // Partial refunds reverse proportionally, cancellations reverse everything
+        assert_eq!(reversal_target(1000, 25_000, 100_000, false), 250);
+        assert_eq!(reversal_target(1000, 500_000, 100_000, false), 1000);
+        assert_eq!(reversal_target(1000, 0, 100_000, false), 0);
+        assert_eq!(reversal_target(1000, 0, 100_000, true), 1000);
+    }
+
+    #[test]
+    fn test_segment_definition_rules() {
+        use crate::commands::segment::{merge_recipients, SegmentDefinition};
+
+        let definition = SegmentDefinition {
+            membership_levels: vec!["VIP".to_string(), " ".to_string()],
+            regions: vec!["서울".to_string()],
+            purchase_within_days: Some(90),
+            min_orders: Some(3),
+            products: vec!["표고버섯 1kg".to_string()],
+            ..Default::default()
+        };
+        assert!(definition.validate().is_ok());
+        assert_eq!(
+            definition.summary(),
+            "등급 VIP, 지역 서울, 최근 90일 구매 3회 이상, 최근 90일 구매상품 표고버섯 1kg"
+        );
+        assert_eq!(SegmentDefinition::default().summary(), "전체 고객");
+
+        let inverted = SegmentDefinition {
+            min_amount: Some(500_000),
+            max_amount: Some(100_000),
+            ..Default::default()
+        };
+        assert!(inverted.validate().is_err());
+        let zero_days = SegmentDefinition {
+            no_order_within_days: Some(0),
+            ..Default::default()
+        };
+        assert!(zero_days.validate().is_err());
+
+        // Explicit numbers and segment customers are sent to once
+        let merged = merge_recipients([
+            vec!["010-1234-5678".to_string(), "".to_string()],
+            vec!["01012345678".to_string(), "010-9999-0000".to_string()],
+        ]);
+        assert_eq!(merged, vec!["010-1234-5678", "010-9999-0000"]);
+    }
+
+    #[test]
+    fn test_ad_message_rules() {
+        use crate::commands::consent::{
+            ad_content, is_ad_night_time, is_opt_out_keyword, MarketingSettings,
+        };
+        use chrono::NaiveTime;
+
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert!(is_ad_night_time(at(21, 0)));
+        assert!(is_ad_night_time(at(7, 59)));
+        assert!(!is_ad_night_time(at(8, 0)));
+        assert!(!is_ad_night_time(at(20, 59)));
+
+        assert!(is_opt_out_keyword("수신 거부"));
+        assert!(is_opt_out_keyword(" STOP "));
+        assert!(is_opt_out_keyword("광고 수신거부 합니다"));
+        assert!(!is_opt_out_keyword("배송 언제 오나요?"));
+
+        let settings = MarketingSettings {
+            business_name: Some("버섯농장".to_string()),
+            optout_number: Some("080-123-4567".to_string()),
+        };
+        assert_eq!(
+            ad_content("가을 할인 안내", &settings),
+            "(광고) 버섯농장\n가을 할인 안내\n무료수신거부 080-123-4567"
+        );
+        // Wording already present is not repeated
+        assert_eq!(
+            ad_content("[광고] 버섯농장 할인 안내 / 수신거부 0801234567", &settings),
+            "(광고) 버섯농장 할인 안내 / 수신거부 0801234567"
+        );
+        assert_eq!(
+            ad_content("할인 안내", &MarketingSettings::default()),
+            "(광고) 할인 안내"
+        );
+    }
+
+    #[test]
+    fn test_privacy_notice_and_due_date() {
+        use crate::commands::privacy::{is_due_for_anonymization, InactiveCustomer};
+        use chrono::NaiveDate;
+
+        let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let mut customer = InactiveCustomer {
+            customer_id: "C1".to_string(),
+            customer_name: "홍길동".to_string(),
+            mobile_number: "010-1234-5678".to_string(),
+            last_activity: Some(date(1)),
+            noticed_at: None,
+            anonymize_on: None,
+        };
+        // Never anonymized without a notice
+        assert!(!is_due_for_anonymization(&customer, date(18)));
+        customer.noticed_at = Some(date(1).and_hms_opt(9, 0, 0).unwrap());
+        customer.anonymize_on = Some(date(18));
+        assert!(!is_due_for_anonymization(&customer, date(17)));
+        assert!(is_due_for_anonymization(&customer, date(18)));
+    }
+
+    #[test]
+    fn test_timeline_type_filter_and_summary() {
+        use crate::commands::timeline::{
+            clamp_timeline_page, parse_event_types, summarize_events, TimelineEvent,
+            MAX_TIMELINE_PAGE,
+        };
+
+        assert_eq!(parse_event_types(None).unwrap().len(), 8);
+        assert_eq!(
+            parse_event_types(Some(" sale, consultation ,")).unwrap(),
+            vec!["sale", "consultation"]
+        );
+        assert!(parse_event_types(Some("sale,unknown")).is_err());
+
+        let event = TimelineEvent {
+            event_type: "sale".to_string(),
+            event_id: "S1".to_string(),
+            occurred_at: chrono::NaiveDate::from_ymd_opt(2026, 10, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0),
+            title: Some("표고버섯".to_string()),
+            detail: Some("1kg 2개".to_string()),
+            amount: Some(30000),
+            status: Some("배송완료".to_string()),
+        };
+        assert_eq!(
+            summarize_events(&[event.clone()]),
+            "- 2026-10-01 [sale] 표고버섯: 1kg 2개 (30000) <배송완료>"
+        );
+        // Message contents and changed values stay out of the prompt
+        let message = TimelineEvent {
+            event_type: "message".to_string(),
+            title: Some("문자".to_string()),
+            detail: Some("홍길동님 주문하신 상품이 발송되었습니다".to_string()),
+            amount: None,
+            status: Some("성공".to_string()),
+            ..event
+        };
+        assert_eq!(
+            summarize_events(&[message]),
+            "- 2026-10-01 [message] 문자 <성공>"
+        );
+        assert_eq!(summarize_events(&[]), "No activity");
+
+        assert_eq!(clamp_timeline_page(0, 0), (1, 1));
+        assert_eq!(clamp_timeline_page(3, 30), (3, 30));
+        assert_eq!(
+            clamp_timeline_page(i64::MAX, i64::MAX),
+            (MAX_TIMELINE_PAGE, 200)
+        );
+    }
+
+    #[test]
+    fn test_referral_rules_and_codes() {
+        use crate::commands::referral::{
+            discount_reward, generate_referral_code, normalize_code, ReferralRewardRule,
+        };
+
+        let code = generate_referral_code();
+        assert_eq!(code.len(), 7);
+        assert!(code.starts_with('R'));
+        assert!(!code.contains(['0', 'O', '1', 'I']));
+        assert_eq!(normalize_code(" rabc23 "), "RABC23");
+
+        // Actual discount, capped by the rule
+        assert_eq!(discount_reward(20000, 18000, 10), 2000);
+        assert_eq!(discount_reward(20000, 10000, 10), 2000);
+        assert_eq!(discount_reward(20000, 20000, 10), 0);
+
+        let rule = |recipient: &str, reward_type: &str, amount| ReferralRewardRule {
+            rule_id: None,
+            rule_name: "추천".to_string(),
+            recipient: recipient.to_string(),
+            reward_type: reward_type.to_string(),
+            amount,
+            min_order_amount: 0,
+            first_order_only: true,
+            is_active: true,
+        };
+        assert!(rule("referrer", "points", 1000).validate().is_ok());
+        assert!(rule("referee", "discount", 10).validate().is_ok());
+        assert!(rule("referrer", "discount", 10).validate().is_err());
+        assert!(rule("referee", "discount", 150).validate().is_err());
+        assert!(rule("referee", "cash", 10).validate().is_err());
+        assert!(rule("referee", "points", 0).validate().is_err());
+    }
+
+    #[test]
+    fn test_consultation_workflow_and_sla() {
+        use crate::commands::consultation::{can_transition, sla_due_at};
+
+        assert!(can_transition("접수", "처리중"));
+        assert!(can_transition("접수", "완료"));
+        assert!(can_transition("보류", "처리중"));
+        assert!(can_transition("완료", "처리중"));
+        assert!(can_transition("처리중", "처리중"));
+        assert!(!can_transition("처리중", "접수"));
+        assert!(!can_transition("완료", "보류"));
+        assert!(!can_transition("접수", "종결"));
+
+        let opened = chrono::NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(22, 0, 0)
+            .unwrap();
+        assert_eq!(
+            sla_due_at(opened, Some(4)),
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 19)
+                .unwrap()
+                .and_hms_opt(2, 0, 0)
+        );
+        assert_eq!(sla_due_at(opened, None), None);
+    }
+
+    #[test]
+    fn test_automation_rules_and_anniversaries() {
+        use crate::commands::automation::{anniversary_falls_on, AutomationRule};
+        use chrono::NaiveDate;
+
+        let d = |y, m, dd| NaiveDate::from_ymd_opt(y, m, dd).unwrap();
+        assert!(anniversary_falls_on(d(1990, 10, 21), d(2026, 10, 21)));
+        assert!(!anniversary_falls_on(d(1990, 10, 21), d(2026, 10, 20)));
+        // 29 February falls on the 28th outside leap years
+        assert!(anniversary_falls_on(d(2000, 2, 29), d(2026, 2, 28)));
+        assert!(anniversary_falls_on(d(2000, 2, 29), d(2028, 2, 29)));
+        assert!(!anniversary_falls_on(d(2000, 2, 29), d(2028, 2, 28)));
+
+        let rule = |trigger: &str, offset, purpose: &str| AutomationRule {
+            rule_id: None,
+            rule_name: "자동".to_string(),
+            trigger_type: trigger.to_string(),
+            offset_days: offset,
+            template_key: "anniversary".to_string(),
+            purpose: purpose.to_string(),
+            dedup_days: 7,
+            is_enabled: true,
+        };
+        assert!(rule("anniversary", 3, "promotional").validate().is_ok());
+        assert!(rule("repurchase", 0, "transactional").validate().is_ok());
+        assert!(rule("anniversary", -1, "promotional").validate().is_err());
+        assert!(rule("repurchase", 20, "promotional").validate().is_err());
+        assert!(rule("birthday", 0, "promotional").validate().is_err());
+        assert!(rule("anniversary", 0, "spam").validate().is_err());
+    }
+
+    #[test]
+    fn test_rank_recommendations() {
+        use crate::commands::recommendation::{rank_recommendations, AssociationRule};
+        use std::collections::HashSet;
+
+        let rule = |a: &str, c: &str, confidence, lift| AssociationRule {
+            antecedent: a.to_string(),
+            consequent: c.to_string(),
+            pair_count: 3,
+            support: 0.1,
+            confidence,
+            lift,
+        };
+        let rules = vec![
+            rule("표고", "느타리", 0.4, 1.5),
+            rule("새송이", "느타리", 0.6, 1.2),
+            rule("표고", "목이", 0.6, 2.0),
+            rule("표고", "새송이", 0.9, 3.0),
+            rule("표고", "팽이", 0.8, 0.9),
+            rule("송화", "영지", 0.9, 4.0),
+        ];
+        let owned: HashSet<String> = ["표고", "새송이"].iter().map(|s| s.to_string()).collect();
+
+        let ranked = rank_recommendations(&rules, &owned, 10);
+        let names: Vec<(&str, &str)> = ranked
+            .iter()
+            .map(|r| (r.consequent.as_str(), r.antecedent.as_str()))
+            .collect();
+        // Owned products and lift <= 1 are left out; the strongest rule wins per product
+        assert_eq!(names, [("목이", "표고"), ("느타리", "새송이")]);
+        assert_eq!(rank_recommendations(&rules, &owned, 1).len(), 1);
+        assert!(rank_recommendations(&rules, &HashSet::new(), 10).is_empty());
+    }
+
+    #[test]
+    fn test_message_kind_selection() {
+        use crate::commands::messaging::{
+            default_subject, message_bytes, select_message_kind, MessageKind,
+        };
+
+        assert_eq!(message_bytes("abc"), 3);
+        assert_eq!(message_bytes("표고 1kg"), 8);
+
+        // 45 Hangul characters are exactly 90 bytes
+        let short = "가".repeat(45);
+        assert_eq!(
+            select_message_kind(&short, false).unwrap(),
+            MessageKind::Sms
+        );
+        assert_eq!(
+            select_message_kind(&format!("{}!", short), false).unwrap(),
+            MessageKind::Lms
+        );
+        assert_eq!(select_message_kind("사진", true).unwrap(), MessageKind::Mms);
+        assert_eq!(
+            select_message_kind(&"a".repeat(2000), false).unwrap(),
+            MessageKind::Lms
+        );
+        assert!(select_message_kind(&"가".repeat(1001), false).is_err());
+
+        assert_eq!(default_subject("봄맞이 할인\n본문"), "봄맞이 할인");
+        assert_eq!(message_bytes(&default_subject(&"가".repeat(30))), 40);
+    }
+
+    #[test]
+    fn test_message_provider_responses() {
+        use crate::commands::config::{MessageCosts, SaveSmsPayload, SmsSettings};
+        use crate::commands::messaging::{
+            aligo, gateway_from_settings, nhn_cloud, solapi, MessageProvider,
+        };
+        use serde_json::json;
+
+        assert_eq!(
+            solapi::authorization_header("key", "test-secret", "2026-10-19T01:02:03Z", "abc123")
+                .unwrap(),
+            "HMAC-SHA256 apiKey=key, date=2026-10-19T01:02:03Z, salt=abc123, \
+             signature=b0e32a817bf6632c29732c6a8bb2bb18372e28bb6c48643e773b0aa016d23369"
+        );
+
+        let solapi_results = solapi::parse_send_response(&json!({
+            "messageList": [{ "to": "01011112222", "messageId": "M4V1", "statusCode": "2000" }],
+            "failedMessageList": [{ "to": "0101", "statusCode": "1062", "statusMessage": "수신번호 형식 오류" }],
+        }));
+        assert_eq!(solapi_results.len(), 2);
+        assert!(solapi_results[0].accepted);
+        assert_eq!(
+            solapi_results[0].provider_message_id.as_deref(),
+            Some("M4V1")
+        );
+        assert_eq!(solapi_results[1].recipient, "0101");
+        assert_eq!(
+            solapi_results[1].error.as_deref(),
+            Some("수신번호 형식 오류")
+        );
+
+        let nhn_results = nhn_cloud::parse_send_response(&json!({
+            "header": { "isSuccessful": true, "resultCode": 0 },
+            "body": { "data": { "requestId": "R-1", "sendResultList": [
+                { "recipientNo": "01011112222", "resultCode": 0, "recipientSeq": 1 },
+                { "recipientNo": "01033334444", "resultCode": -1002, "resultMessage": "Invalid recipient", "recipientSeq": 2 }
+            ]}}
+        }));
+        assert_eq!(nhn_results[0].provider_message_id.as_deref(), Some("R-1:1"));
+        assert!(!nhn_results[1].accepted);
+
+        let recipients = vec!["01011112222".to_string(), "01033334444".to_string()];
+        let aligo_results = aligo::parse_send_response(
+            &json!({ "result_code": 1, "message": "success", "msg_id": 123456, "success_cnt": 2 }),
+            &recipients,
+        )
+        .unwrap();
+        assert!(aligo_results.iter().all(|r| r.accepted));
+        assert_eq!(
+            aligo_results[1].provider_message_id.as_deref(),
+            Some("123456")
+        );
+        assert!(aligo::parse_send_response(
+            &json!({ "result_code": "-101", "message": "인증오류" }),
+            &recipients
+        )
+        .is_err());
+
+        let settings = |provider: &str, secret: &str| SmsSettings {
+            api_key: "key".to_string(),
+            api_secret: secret.to_string(),
+            sender_number: "031-000-0000".to_string(),
+            provider: provider.to_string(),
+            costs: MessageCosts::default(),
+            webhook_secret: String::new(),
+        };
+        // Nothing is faked as sent without settings
+        assert!(gateway_from_settings(None).is_err());
+        assert_eq!(
+            gateway_from_settings(Some(&settings("mock", "")))
+                .unwrap()
+                .name(),
+            "mock"
+        );
+        assert_eq!(
+            gateway_from_settings(Some(&settings("coolsms", "s")))
+                .unwrap()
+                .name(),
+            "solapi"
+        );
+        assert_eq!(
+            gateway_from_settings(Some(&settings("nhn", "s")))
+                .unwrap()
+                .name(),
+            "nhn"
+        );
+        assert!(gateway_from_settings(Some(&settings("aligo", ""))).is_err());
+        assert!(gateway_from_settings(Some(&settings("pigeon", "s"))).is_err());
+
+        // Saving the form without costs or a webhook secret keeps the stored ones
+        let stored = SmsSettings {
+            costs: MessageCosts {
+                sms: 8,
+                lms: 25,
+                mms: 90,
+            },
+            webhook_secret: "hook".to_string(),
+            ..settings("nhn", "s")
+        };
+        let form = |costs: Option<MessageCosts>| SaveSmsPayload {
+            api_key: "new-key".to_string(),
+            api_secret: "s".to_string(),
+            sender_number: "031-000-0000".to_string(),
+            provider: "nhn".to_string(),
+            costs,
+            webhook_secret: None,
+        };
+        let merged = form(None).merge_into(Some(stored.clone()));
+        assert_eq!(merged.api_key, "new-key");
+        assert_eq!((merged.costs.sms, merged.costs.mms), (8, 90));
+        assert_eq!(merged.webhook_secret, "hook");
+        let merged = form(Some(MessageCosts::default())).merge_into(Some(stored));
+        assert_eq!(merged.costs.lms, 30);
+        assert_eq!(form(None).merge_into(None).costs.sms, 9);
+    }
+
+    #[test]
+    fn test_kakao_responses_and_templates() {
+        use crate::commands::config::KakaoSettings;
+        use crate::commands::messaging::kakao::{
+            kakao_gateway_from_settings, parse_nhn_response, KakaoProvider, KakaoTemplate,
+        };
+        use serde_json::json;
+
+        let results = parse_nhn_response(&json!({
+            "header": { "isSuccessful": true, "resultCode": 0 },
+            "message": { "requestId": "20261019-AT", "sendResults": [
+                { "recipientSeq": 1, "recipientNo": "01011112222", "resultCode": 0 },
+                { "recipientSeq": 2, "recipientNo": "01033334444", "resultCode": -3001, "resultMessage": "Invalid template" }
+            ]}
+        }))
+        .unwrap();
+        assert_eq!(
+            results[0].provider_message_id.as_deref(),
+            Some("20261019-AT:1")
+        );
+        assert_eq!(results[1].error.as_deref(), Some("Invalid template"));
+        assert!(parse_nhn_response(&json!({
+            "header": { "isSuccessful": false, "resultMessage": "Unauthorized" }
+        }))
+        .is_err());
+
+        let template = |key: &str, code: &str| KakaoTemplate {
+            template_key: key.to_string(),
+            template_code: code.to_string(),
+            description: None,
+            is_enabled: true,
+        };
+        assert!(template("shipping", "SHIP_001").validate().is_ok());
+        assert!(template("shipping", " ").validate().is_err());
+
+        assert!(kakao_gateway_from_settings(None).unwrap().is_none());
+        let settings = |provider: &str, sender_key: &str| KakaoSettings {
+            provider: provider.to_string(),
+            api_key: "app".to_string(),
+            api_secret: "secret".to_string(),
+            sender_key: sender_key.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            kakao_gateway_from_settings(Some(&settings("nhn", "profile")))
+                .unwrap()
+                .unwrap()
+                .name(),
+            "nhn"
+        );
+        assert!(kakao_gateway_from_settings(Some(&settings("solapi", ""))).is_err());
+        assert!(kakao_gateway_from_settings(Some(&settings("kakao-direct", "p"))).is_err());
+    }
+
+    #[test]
+    fn test_message_template_rendering() {
+        use crate::commands::config::default_message_templates;
+        use crate::commands::message_template::{
+            describe_items, placeholders, render, unknown_variables, validate_templates,
+            TemplateContext, TemplateValue,
+        };
+        use chrono::NaiveDate;
+
+        assert_eq!(
+            placeholders("${name}님, ${ amount_due } 입금 부탁드립니다.").unwrap(),
+            vec!["name", "amount_due"]
+        );
+        assert!(placeholders("${name}님, ${amount").is_err());
+
+        assert!(
+            unknown_variables("shipping_done", "${courier} ${tracking_number}")
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            unknown_variables("shipping_paid", "${tracking_number} ${tracking_number}").unwrap(),
+            vec!["tracking_number"]
+        );
+        // Categories added by users only know the name
+        assert_eq!(
+            unknown_variables("event", "${name} ${level}").unwrap(),
+            vec!["level"]
+        );
+
+        assert!(validate_templates(&default_message_templates()).is_ok());
+        let mut templates = default_message_templates();
+        templates.insert(
+            "membership_upgrade".to_string(),
+            vec!["${name}님 ${grade}".to_string()],
+        );
+        let err = validate_templates(&templates).unwrap_err().to_string();
+        assert!(err.contains("[membership_upgrade] ${grade}"));
+
+        let mut context = TemplateContext::new();
+        context.insert("name", TemplateValue::Text("홍길동".to_string()));
+        context.insert("amount_due", TemplateValue::Money(1_234_500));
+        context.insert(
+            "date",
+            TemplateValue::Date(NaiveDate::from_ymd_opt(2026, 11, 1).unwrap()),
+        );
+        let rendered = render(
+            "${name}님, ${amount_due}을 ${date}까지 ${bank_account}로 보내주세요.",
+            &context,
+        );
+        assert_eq!(
+            rendered.text,
+            "홍길동님, 1,234,500원을 2026-11-01까지 로 보내주세요."
+        );
+        assert_eq!(rendered.missing, vec!["bank_account"]);
+        assert_eq!(TemplateValue::Money(900).display(), "900원");
+        assert_eq!(TemplateValue::Money(-1000).display(), "-1,000원");
+
+        let line = |name: &str, spec: Option<&str>, qty| {
+            (name.to_string(), spec.map(|s| s.to_string()), qty)
+        };
+        assert_eq!(
+            describe_items(&[line("표고", Some("1kg"), 2), line("건표고", None, 1)]),
+            "표고 1kg x2, 건표고 x1"
+        );
+        assert_eq!(
+            describe_items(&[
+                line("a", None, 1),
+                line("b", None, 1),
+                line("c", None, 1),
+                line("d", None, 1)
+            ]),
+            "a x1, b x1 외 2건"
+        );
+    }
+
+    #[test]
+    fn test_outbox_throttle_and_backoff() {
+        use crate::commands::messaging::outbox::{
+            batch_budget, provider_rate_per_minute, send_retry_delay_minutes,
+        };
+
+        assert_eq!(send_retry_delay_minutes(0), 1);
+        assert_eq!(send_retry_delay_minutes(3), 8);
+        assert_eq!(send_retry_delay_minutes(20), 60);
+
+        assert!(provider_rate_per_minute("aligo") < provider_rate_per_minute("solapi"));
+        assert_eq!(batch_budget("aligo"), 50);
+        assert_eq!(batch_budget("nhn"), 200);
+        assert!(batch_budget("mock") >= 1);
+    }
+
+    #[test]
+    fn test_messaging_webhook_signatures_and_payloads() {
+        use crate::commands::messaging::webhook::{
+            inbound_dedupe_key, parse_events, verify_signature, WebhookEvent,
+        };
+        use hmac::{Hmac, Mac};
+        use serde_json::json;
+        use sha2::Sha256;
+
+        let now = chrono::DateTime::parse_from_rfc3339("2026-10-19T09:05:00+09:00")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let body = br#"[{"type":"report","messageId":"M1","delivered":true}]"#;
+        let sign = |secret: &str| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+            mac.update(body);
+            mac.finalize().into_bytes().to_vec()
+        };
+        let secrets = vec!["old".to_string(), "hook-secret".to_string()];
+        let signature = hex::encode(sign("hook-secret"));
+        let verify = |provider, secrets: &[String], signature: Option<&str>| {
+            verify_signature(provider, secrets, signature, body, now)
+        };
+        assert!(verify("mock", &secrets, Some(&signature)).is_ok());
+        assert!(verify("mock", &secrets, Some(&hex::encode(sign("other")))).is_err());
+        assert!(verify("mock", &secrets, None).is_err());
+        assert!(verify("mock", &[], Some(&signature)).is_err());
+        assert!(verify("aligo", &secrets, Some(&signature)).is_err());
+
+        // NHN Cloud sends the signature set on its console as is
+        assert!(verify("nhn", &secrets, Some("hook-secret")).is_ok());
+        assert!(verify("nhn", &secrets, Some(&signature)).is_err());
+
+        // A recorded Solapi callback header: the signature covers date + salt, not the body
+        let solapi = "HMAC-SHA256 apiKey=NCSAYU7YDBXYORXC, date=2026-10-19T09:00:00+09:00, \
+                      salt=9f1c2b7e4a0d, \
+                      signature=0c18409f6bbb17398072c9f0d56b6460bb9180cf3bb1e1b8f313382b5e50ff5d";
+        assert!(verify("solapi", &secrets, Some(solapi)).is_ok());
+        assert!(verify("solapi", &["other".to_string()], Some(solapi)).is_err());
+        assert!(verify("solapi", &secrets, Some(&solapi.replace("0d,", "0e,"))).is_err());
+        let an_hour_later = now + chrono::Duration::hours(1);
+        assert!(verify_signature("solapi", &secrets, Some(solapi), body, an_hour_later).is_err());
+
+        let events = parse_events(
+            "solapi",
+            &json!([
+                { "messageId": "S1", "to": "01012345678", "statusCode": "4000" },
+                { "messageId": "S2", "to": "01087654321", "statusCode": "3059", "statusMessage": "변작된 발신번호" },
+                { "type": "MO", "messageId": "S3", "from": "01012345678", "text": "감사합니다" }
+            ]),
+        )
+        .unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            WebhookEvent::Report {
+                delivered: true,
+                ..
+            }
+        ));
+        assert_eq!(
+            events[1],
+            WebhookEvent::Report {
+                message_id: "S2".to_string(),
+                to: Some("01087654321".to_string()),
+                delivered: false,
+                error: Some("변작된 발신번호".to_string()),
+            }
+        );
+        assert!(matches!(&events[2], WebhookEvent::Inbound { channel, .. } if channel == "sms"));
+
+        let events = parse_events(
+            "nhn",
+            &json!({ "hooks": [
+                { "requestId": "20261019-AB", "recipientSeq": 1, "recipientNo": "01012345678", "resultCode": "1000" },
+                { "requestId": "20261019-AB", "recipientSeq": 2, "recipientNo": "01087654321", "resultCode": 3015, "resultMessage": "결번" }
+            ]}),
+        )
+        .unwrap();
+        assert!(
+            matches!(&events[0], WebhookEvent::Report { message_id, delivered: true, .. } if message_id == "20261019-AB:1")
+        );
+        assert!(
+            matches!(&events[1], WebhookEvent::Report { delivered: false, error: Some(e), .. } if e == "결번")
+        );
+
+        let events = parse_events(
+            "mock",
+            &json!({ "type": "inbound", "from": "010-1234-5678", "text": "문의" }),
+        )
+        .unwrap();
+        assert!(matches!(&events[0], WebhookEvent::Inbound { id: None, .. }));
+
+        // Without a provider id a resent reply is still recognised within the minute
+        let at = |m, s| {
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 19)
+                .unwrap()
+                .and_hms_opt(9, m, s)
+                .unwrap()
+        };
+        assert_eq!(
+            inbound_dedupe_key(Some("S3"), "01012345678", "문의", at(0, 0)),
+            "S3"
+        );
+        let key = inbound_dedupe_key(None, "01012345678", "문의", at(0, 5));
+        assert!(key.len() <= 100);
+        assert_eq!(
+            key,
+            inbound_dedupe_key(Some(" "), "01012345678", "문의", at(0, 50))
+        );
+        assert_ne!(
+            key,
+            inbound_dedupe_key(None, "01012345678", "문의", at(1, 5))
+        );
+        assert_ne!(
+            key,
+            inbound_dedupe_key(None, "01012345678", "감사", at(0, 5))
+        );
+    }
 }
@@ -0,0 +1,937 @@
+#![allow(non_snake_case)]
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PostalAddress {
+    pub building_mgmt_no: String,
+    pub zip_code: String,
+    pub sido: String,
+    pub sigungu: Option<String>,
+    pub eupmyeondong: Option<String>,
+    pub road_name: String,
+    pub is_underground: bool,
+    pub building_main: i32,
+    pub building_sub: i32,
+    pub building_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RemoteArea {
+    #[serde(default)]
+    pub area_id: i32,
+    pub zip_start: String,
+    pub zip_end: String,
+    pub area_name: String,
+    #[serde(default)]
+    pub is_jeju: bool,
+}
+
+/// Components pulled out of a free-text road-name address.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ParsedAddress {
+    pub sido: Option<String>,
+    pub sigungu: Option<String>,
+    pub road_name: String,
+    pub is_underground: bool,
+    pub building_main: i32,
+    pub building_sub: i32,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AddressValidation {
+    /// matched | ambiguous | not_found | unparsed
+    pub status: String,
+    pub zip_code: Option<String>,
+    pub address_primary: String,
+    pub address_detail: Option<String>,
+    pub is_remote_area: bool,
+    pub remote_area_name: Option<String>,
+    pub candidates: Vec<PostalAddress>,
+    pub duplicate_address_id: Option<i32>,
+}
+
+const SIDO_NAMES: &[(&str, &str)] = &[
+    ("서울", "서울특별시"),
+    ("서울시", "서울특별시"),
+    ("부산", "부산광역시"),
+    ("부산시", "부산광역시"),
+    ("대구", "대구광역시"),
+    ("대구시", "대구광역시"),
+    ("인천", "인천광역시"),
+    ("인천시", "인천광역시"),
+    ("광주", "광주광역시"),
+    ("대전", "대전광역시"),
+    ("대전시", "대전광역시"),
+    ("울산", "울산광역시"),
+    ("울산시", "울산광역시"),
+    ("세종", "세종특별자치시"),
+    ("세종시", "세종특별자치시"),
+    ("경기", "경기도"),
+    ("강원", "강원특별자치도"),
+    ("강원도", "강원특별자치도"),
+    ("충북", "충청북도"),
+    ("충남", "충청남도"),
+    ("전북", "전북특별자치도"),
+    ("전라북도", "전북특별자치도"),
+    ("전남", "전라남도"),
+    ("경북", "경상북도"),
+    ("경남", "경상남도"),
+    ("제주", "제주특별자치도"),
+    ("제주도", "제주특별자치도"),
+];
+
+/// Maps abbreviated province/city names ("서울", "경기") to the official name.
+pub fn normalize_sido(token: &str) -> Option<String> {
+    SIDO_NAMES
+        .iter()
+        .find(|(short, full)| token == *short || token == *full)
+        .map(|(_, full)| full.to_string())
+}
+
+fn is_road_token(token: &str) -> bool {
+    token.chars().count() >= 2 && (token.ends_with('로') || token.ends_with('길'))
+}
+
+fn parse_building_no(token: &str) -> Option<(i32, i32)> {
+    let token = token.trim_end_matches("번지").trim_end_matches('번');
+    let mut parts = token.splitn(2, '-');
+    let main = parts.next()?.parse::<i32>().ok()?;
+    let sub = match parts.next() {
+        Some(s) => s.parse::<i32>().ok()?,
+        None => 0,
+    };
+    Some((main, sub))
+}
+
+/// Splits tokens like "테헤란로123-4" into the road name and the building number.
+fn split_glued_token(token: &str) -> Vec<String> {
+    if parse_building_no(token).is_some() || is_road_token(token) {
+        return vec![token.to_string()];
+    }
+    for (idx, c) in token.char_indices().rev() {
+        if c == '로' || c == '길' {
+            let split = idx + c.len_utf8();
+            let (road, number) = token.split_at(split);
+            if is_road_token(road) && parse_building_no(number).is_some() {
+                return vec![road.to_string(), number.to_string()];
+            }
+        }
+    }
+    vec![token.to_string()]
+}
+
+/// Parses a free-text road-name address. Reference parts in parentheses are dropped,
+/// "봉은사로 1길" is joined back into one road name, and whatever follows the
+/// building number is kept as the detail address.
+pub fn parse_road_address(raw: &str) -> Option<ParsedAddress> {
+    let mut cleaned = String::with_capacity(raw.len());
+    let mut depth = 0;
+    for c in raw.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = (depth - 1).max(0),
+            ',' if depth == 0 => cleaned.push(' '),
+            _ if depth == 0 => cleaned.push(c),
+            _ => {}
+        }
+    }
+
+    let mut tokens: Vec<String> = cleaned
+        .split_whitespace()
+        .flat_map(split_glued_token)
+        .collect();
+
+    let mut i = 0;
+    while i + 1 < tokens.len() {
+        let next = &tokens[i + 1];
+        if tokens[i].ends_with('로')
+            && next.ends_with('길')
+            && next.chars().next().is_some_and(|c| c.is_ascii_digit())
+        {
+            let merged = format!("{}{}", tokens[i], next);
+            tokens[i] = merged;
+            tokens.remove(i + 1);
+        }
+        i += 1;
+    }
+
+    for (idx, token) in tokens.iter().enumerate() {
+        if !is_road_token(token) {
+            continue;
+        }
+        let mut num_idx = idx + 1;
+        let is_underground = tokens.get(num_idx).map(|t| t == "지하").unwrap_or(false);
+        if is_underground {
+            num_idx += 1;
+        }
+        let Some((building_main, building_sub)) =
+            tokens.get(num_idx).and_then(|t| parse_building_no(t))
+        else {
+            continue;
+        };
+
+        let region = &tokens[..idx];
+        let mut sido = None;
+        let mut sigungu_parts = Vec::new();
+        for (r_idx, r) in region.iter().enumerate() {
+            if r_idx == 0 {
+                if let Some(s) = normalize_sido(r) {
+                    sido = Some(s);
+                    continue;
+                }
+            }
+            if r.ends_with('시') || r.ends_with('군') || r.ends_with('구') {
+                sigungu_parts.push(r.as_str());
+            }
+        }
+
+        return Some(ParsedAddress {
+            sido,
+            sigungu: if sigungu_parts.is_empty() {
+                None
+            } else {
+                Some(sigungu_parts.join(" "))
+            },
+            road_name: token.clone(),
+            is_underground,
+            building_main,
+            building_sub,
+            detail: tokens[num_idx + 1..].join(" "),
+        });
+    }
+
+    None
+}
+
+/// Official notation: "시도 시군구 [읍면] 도로명 건물번호 (동, 건물명)"
+pub fn format_road_address(p: &PostalAddress) -> String {
+    let mut parts = vec![p.sido.clone()];
+    if let Some(sgg) = p.sigungu.as_deref().filter(|s| !s.is_empty()) {
+        parts.push(sgg.to_string());
+    }
+    let emd = p.eupmyeondong.as_deref().unwrap_or("");
+    if emd.ends_with('읍') || emd.ends_with('면') {
+        parts.push(emd.to_string());
+    }
+    parts.push(p.road_name.clone());
+    if p.is_underground {
+        parts.push("지하".to_string());
+    }
+    if p.building_sub > 0 {
+        parts.push(format!("{}-{}", p.building_main, p.building_sub));
+    } else {
+        parts.push(p.building_main.to_string());
+    }
+
+    let mut reference = Vec::new();
+    if emd.ends_with('동') || emd.ends_with('가') {
+        reference.push(emd.to_string());
+    }
+    if let Some(name) = p.building_name.as_deref().filter(|s| !s.is_empty()) {
+        reference.push(name.to_string());
+    }
+
+    let mut address = parts.join(" ");
+    if !reference.is_empty() {
+        address.push_str(&format!(" ({})", reference.join(", ")));
+    }
+    address
+}
+
+fn detail_key(detail: &str) -> String {
+    // "101동 1203호" and "101-1203" should compare equal
+    let mut letters = String::new();
+    let mut numbers: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in detail.chars() {
+        if c.is_ascii_digit() {
+            current.push(c);
+            continue;
+        }
+        if !current.is_empty() {
+            numbers.push(std::mem::take(&mut current));
+        }
+        if c.is_alphanumeric() && !matches!(c, '동' | '호' | '층' | '의') {
+            letters.extend(c.to_lowercase());
+        }
+    }
+    if !current.is_empty() {
+        numbers.push(current);
+    }
+    format!("{}:{}", letters, numbers.join("-"))
+}
+
+/// Key used to detect the same delivery address written in different ways.
+pub fn address_dedup_key(primary: &str, detail: Option<&str>) -> String {
+    let detail = detail.unwrap_or("");
+    match parse_road_address(&format!("{} {}", primary, detail)) {
+        Some(p) => format!(
+            "{}|{}|{}-{}|{}",
+            p.road_name,
+            p.is_underground,
+            p.building_main,
+            p.building_sub,
+            detail_key(&p.detail)
+        ),
+        None => {
+            let squashed: String = primary
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(|c| c.to_lowercase())
+                .collect();
+            format!("{}|{}", squashed, detail_key(detail))
+        }
+    }
+}
+
+pub fn find_remote_area<'a>(zip_code: &str, areas: &'a [RemoteArea]) -> Option<&'a RemoteArea> {
+    let zip = zip_code.trim();
+    if zip.len() != 5 || !zip.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    areas
+        .iter()
+        .find(|a| a.zip_start.as_str() <= zip && zip <= a.zip_end.as_str())
+}
+
+/// Parses one line of the 도로명주소 building DB (build_*.txt, '|' separated).
+pub fn parse_postal_building_line(line: &str) -> Option<PostalAddress> {
+    let f: Vec<&str> = line.split('|').map(str::trim).collect();
+    if f.len() < 20 {
+        return None;
+    }
+    let is_zip = |s: &str| s.len() == 5 && s.chars().all(|c| c.is_ascii_digit());
+    let zip_code = if is_zip(f[19]) {
+        f[19]
+    } else {
+        f.get(27).copied().filter(|s| is_zip(s))?
+    };
+    let non_empty = |s: &str| {
+        if s.is_empty() {
+            None
+        } else {
+            Some(s.to_string())
+        }
+    };
+
+    Some(PostalAddress {
+        building_mgmt_no: non_empty(f[15])?,
+        zip_code: zip_code.to_string(),
+        sido: non_empty(f[1])?,
+        sigungu: non_empty(f[2]),
+        eupmyeondong: non_empty(f[3]),
+        road_name: non_empty(f[9])?,
+        is_underground: f[10] == "1",
+        building_main: f[11].parse().ok()?,
+        building_sub: f[12].parse().unwrap_or(0),
+        building_name: non_empty(f[13]).or_else(|| f.get(25).and_then(|s| non_empty(s))),
+    })
+}
+
+pub async fn load_remote_areas(pool: &DbPool) -> MyceliumResult<Vec<RemoteArea>> {
+    Ok(
+        sqlx::query_as::<_, RemoteArea>("SELECT * FROM postal_remote_areas ORDER BY zip_start ASC")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+pub async fn find_postal_candidates(
+    pool: &DbPool,
+    parsed: &ParsedAddress,
+) -> MyceliumResult<Vec<PostalAddress>> {
+    // Sigungu is matched loosely since "분당구" alone should still find "성남시 분당구"
+    Ok(sqlx::query_as::<_, PostalAddress>(
+        "SELECT building_mgmt_no, zip_code, sido, sigungu, eupmyeondong, road_name,
+                is_underground, building_main, building_sub, building_name
+         FROM postal_addresses
+         WHERE road_name = $1 AND building_main = $2 AND building_sub = $3
+           AND is_underground = $4
+           AND ($5::text IS NULL OR sido = $5)
+           AND ($6::text IS NULL OR sigungu LIKE '%' || $6 || '%')
+         ORDER BY sido, sigungu
+         LIMIT 10",
+    )
+    .bind(&parsed.road_name)
+    .bind(parsed.building_main)
+    .bind(parsed.building_sub)
+    .bind(parsed.is_underground)
+    .bind(&parsed.sido)
+    .bind(&parsed.sigungu)
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn normalize_address_internal(
+    pool: &DbPool,
+    address_primary: &str,
+    address_detail: Option<&str>,
+) -> MyceliumResult<AddressValidation> {
+    let areas = load_remote_areas(pool).await?;
+    normalize_address_with_areas(pool, &areas, address_primary, address_detail).await
+}
+
+/// `normalize_address_internal` with the remote areas already loaded, for checking many
+/// addresses at once.
+pub async fn normalize_address_with_areas(
+    pool: &DbPool,
+    areas: &[RemoteArea],
+    address_primary: &str,
+    address_detail: Option<&str>,
+) -> MyceliumResult<AddressValidation> {
+    let combined = format!("{} {}", address_primary, address_detail.unwrap_or(""));
+
+    let mut result = AddressValidation {
+        status: "unparsed".to_string(),
+        zip_code: None,
+        address_primary: address_primary.trim().to_string(),
+        address_detail: address_detail
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty()),
+        is_remote_area: false,
+        remote_area_name: None,
+        candidates: Vec::new(),
+        duplicate_address_id: None,
+    };
+
+    let Some(parsed) = parse_road_address(&combined) else {
+        return Ok(result);
+    };
+
+    let candidates = find_postal_candidates(pool, &parsed).await?;
+    match candidates.len() {
+        0 => result.status = "not_found".to_string(),
+        1 => {
+            let matched = &candidates[0];
+            result.status = "matched".to_string();
+            result.zip_code = Some(matched.zip_code.clone());
+            result.address_primary = format_road_address(matched);
+            result.address_detail = if parsed.detail.is_empty() {
+                None
+            } else {
+                Some(parsed.detail.clone())
+            };
+            if let Some(area) = find_remote_area(&matched.zip_code, areas) {
+                result.is_remote_area = true;
+                result.remote_area_name = Some(area.area_name.clone());
+            }
+        }
+        _ => result.status = "ambiguous".to_string(),
+    }
+    result.candidates = candidates;
+    Ok(result)
+}
+
+/// Fills a missing zip code from the postal DB and reports whether the zip is in a
+/// remote/island zone. Unmatched addresses are left as entered.
+pub async fn resolve_zip_code(
+    pool: &DbPool,
+    zip_code: Option<String>,
+    address_primary: &str,
+    address_detail: Option<&str>,
+) -> MyceliumResult<(Option<String>, bool)> {
+    let areas = load_remote_areas(pool).await?;
+    let mut zip_code = zip_code.filter(|z| !z.trim().is_empty());
+    if zip_code.is_none() && !address_primary.trim().is_empty() {
+        let validation =
+            normalize_address_with_areas(pool, &areas, address_primary, address_detail).await?;
+        zip_code = validation.zip_code;
+    }
+    let is_remote = zip_code
+        .as_deref()
+        .is_some_and(|z| find_remote_area(z, &areas).is_some());
+    Ok((zip_code, is_remote))
+}
+
+/// An address as it is saved: the road address from the postal DB when it matched exactly
+/// one place, otherwise as entered.
+#[derive(Debug)]
+pub struct ResolvedAddress {
+    pub zip_code: Option<String>,
+    pub address_primary: String,
+    pub address_detail: Option<String>,
+    pub is_remote_area: bool,
+}
+
+/// Like `resolve_zip_code`, and also replaces the entered address with the normalized one
+/// when the lookup matched. An entered zip code is kept.
+pub async fn resolve_address(
+    pool: &DbPool,
+    zip_code: Option<String>,
+    address_primary: &str,
+    address_detail: Option<&str>,
+) -> MyceliumResult<ResolvedAddress> {
+    let areas = load_remote_areas(pool).await?;
+    let mut resolved = ResolvedAddress {
+        zip_code: zip_code.filter(|z| !z.trim().is_empty()),
+        address_primary: address_primary.trim().to_string(),
+        address_detail: address_detail
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty()),
+        is_remote_area: false,
+    };
+    if !resolved.address_primary.is_empty() {
+        let validation =
+            normalize_address_with_areas(pool, &areas, address_primary, address_detail).await?;
+        if validation.status == "matched" {
+            resolved.address_primary = validation.address_primary;
+            resolved.address_detail = validation.address_detail;
+            if resolved.zip_code.is_none() {
+                resolved.zip_code = validation.zip_code;
+            }
+        }
+    }
+    resolved.is_remote_area = resolved
+        .zip_code
+        .as_deref()
+        .is_some_and(|z| find_remote_area(z, &areas).is_some());
+    Ok(resolved)
+}
+
+/// Returns the id of an existing address of the customer that points to the same place.
+pub async fn find_duplicate_address_id(
+    pool: &DbPool,
+    customer_id: &str,
+    address_primary: &str,
+    address_detail: Option<&str>,
+    exclude_address_id: Option<i32>,
+) -> MyceliumResult<Option<i32>> {
+    let rows: Vec<(i32, String, Option<String>)> = sqlx::query_as(
+        "SELECT address_id, address_primary, address_detail FROM customer_addresses
+         WHERE customer_id = $1 AND ($2::int IS NULL OR address_id <> $2)",
+    )
+    .bind(customer_id)
+    .bind(exclude_address_id)
+    .fetch_all(pool)
+    .await?;
+
+    let key = address_dedup_key(address_primary, address_detail);
+    Ok(rows
+        .into_iter()
+        .find(|(_, p, d)| address_dedup_key(p, d.as_deref()) == key)
+        .map(|(id, _, _)| id))
+}
+
+#[derive(sqlx::FromRow)]
+struct AddressRow {
+    address_id: i32,
+    customer_id: String,
+    customer_name: Option<String>,
+    address_alias: String,
+    address_primary: String,
+    address_detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateAddressGroup {
+    pub customer_id: String,
+    pub customer_name: Option<String>,
+    pub address_ids: Vec<i32>,
+    pub aliases: Vec<String>,
+    pub address_primary: String,
+    pub address_detail: Option<String>,
+}
+
+pub async fn find_duplicate_addresses_internal(
+    pool: &DbPool,
+    customer_id: Option<String>,
+) -> MyceliumResult<Vec<DuplicateAddressGroup>> {
+    let rows = sqlx::query_as::<_, AddressRow>(
+        "SELECT a.address_id, a.customer_id, c.customer_name, a.address_alias,
+                a.address_primary, a.address_detail
+         FROM customer_addresses a
+         LEFT JOIN customers c ON a.customer_id = c.customer_id
+         WHERE ($1::text IS NULL OR a.customer_id = $1)
+         ORDER BY a.customer_id, a.address_id",
+    )
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut groups: Vec<(String, DuplicateAddressGroup)> = Vec::new();
+    for row in rows {
+        let AddressRow {
+            address_id,
+            customer_id: cid,
+            customer_name: cname,
+            address_alias: alias,
+            address_primary: primary,
+            address_detail: detail,
+        } = row;
+        let key = format!("{}#{}", cid, address_dedup_key(&primary, detail.as_deref()));
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, group)) => {
+                group.address_ids.push(address_id);
+                group.aliases.push(alias);
+            }
+            None => groups.push((
+                key,
+                DuplicateAddressGroup {
+                    customer_id: cid,
+                    customer_name: cname,
+                    address_ids: vec![address_id],
+                    aliases: vec![alias],
+                    address_primary: primary,
+                    address_detail: detail,
+                },
+            )),
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(_, g)| g)
+        .filter(|g| g.address_ids.len() > 1)
+        .collect())
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostalImportResult {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+/// Imports a 도로명주소 building DB file. The public files are CP949 encoded;
+/// files already converted to UTF-8 are accepted as well.
+pub async fn import_postal_db(pool: &DbPool, path: String) -> MyceliumResult<PostalImportResult> {
+    let (rows, skipped) = tokio::task::spawn_blocking(move || {
+        let bytes = std::fs::read(&path)?;
+        let text = match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => {
+                let (decoded, _, _) = encoding_rs::EUC_KR.decode(e.as_bytes());
+                decoded.into_owned()
+            }
+        };
+        let mut rows = Vec::new();
+        let mut skipped = 0;
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            match parse_postal_building_line(line) {
+                Some(row) => rows.push(row),
+                None => skipped += 1,
+            }
+        }
+        Ok::<_, MyceliumError>((rows, skipped))
+    })
+    .await
+    .map_err(|e| MyceliumError::Internal(e.to_string()))??;
+
+    if rows.is_empty() {
+        return Err(MyceliumError::Validation(
+            "가져올 수 있는 주소 데이터가 없습니다. 도로명주소 건물DB 파일인지 확인해주세요."
+                .to_string(),
+        ));
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    for chunk in rows.chunks(2000) {
+        let mut mgmt_nos = Vec::with_capacity(chunk.len());
+        let mut zips = Vec::with_capacity(chunk.len());
+        let mut sidos = Vec::with_capacity(chunk.len());
+        let mut sigungus = Vec::with_capacity(chunk.len());
+        let mut dongs = Vec::with_capacity(chunk.len());
+        let mut roads = Vec::with_capacity(chunk.len());
+        let mut undergrounds = Vec::with_capacity(chunk.len());
+        let mut mains = Vec::with_capacity(chunk.len());
+        let mut subs = Vec::with_capacity(chunk.len());
+        let mut names = Vec::with_capacity(chunk.len());
+        for r in chunk {
+            mgmt_nos.push(r.building_mgmt_no.clone());
+            zips.push(r.zip_code.clone());
+            sidos.push(r.sido.clone());
+            sigungus.push(r.sigungu.clone());
+            dongs.push(r.eupmyeondong.clone());
+            roads.push(r.road_name.clone());
+            undergrounds.push(r.is_underground);
+            mains.push(r.building_main);
+            subs.push(r.building_sub);
+            names.push(r.building_name.clone());
+        }
+
+        sqlx::query(
+            "INSERT INTO postal_addresses (
+                building_mgmt_no, zip_code, sido, sigungu, eupmyeondong, road_name,
+                is_underground, building_main, building_sub, building_name
+            )
+            SELECT * FROM UNNEST(
+                $1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[],
+                $7::bool[], $8::int[], $9::int[], $10::text[]
+            )
+            ON CONFLICT (building_mgmt_no) DO UPDATE SET
+                zip_code = EXCLUDED.zip_code,
+                sido = EXCLUDED.sido,
+                sigungu = EXCLUDED.sigungu,
+                eupmyeondong = EXCLUDED.eupmyeondong,
+                road_name = EXCLUDED.road_name,
+                is_underground = EXCLUDED.is_underground,
+                building_main = EXCLUDED.building_main,
+                building_sub = EXCLUDED.building_sub,
+                building_name = EXCLUDED.building_name,
+                imported_at = CURRENT_TIMESTAMP",
+        )
+        .bind(&mgmt_nos)
+        .bind(&zips)
+        .bind(&sidos)
+        .bind(&sigungus)
+        .bind(&dongs)
+        .bind(&roads)
+        .bind(&undergrounds)
+        .bind(&mains)
+        .bind(&subs)
+        .bind(&names)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(PostalImportResult {
+        imported: rows.len(),
+        skipped,
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct ShipmentAddressRow {
+    sales_id: String,
+    shipping_name: Option<String>,
+    shipping_zip_code: Option<String>,
+    shipping_address_primary: Option<String>,
+    shipping_address_detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShipmentAddressIssue {
+    pub sales_id: String,
+    pub shipping_name: Option<String>,
+    pub shipping_zip_code: Option<String>,
+    pub shipping_address_primary: Option<String>,
+    pub shipping_address_detail: Option<String>,
+    pub issue: String,
+    pub suggested_zip_code: Option<String>,
+    pub suggested_address: Option<String>,
+    pub remote_area_name: Option<String>,
+}
+
+/// Checks the shipping address of every order still waiting for shipment.
+pub async fn check_shipment_addresses_internal(
+    pool: &DbPool,
+) -> MyceliumResult<Vec<ShipmentAddressIssue>> {
+    let rows = sqlx::query_as::<_, ShipmentAddressRow>(
+        "SELECT sales_id, shipping_name, shipping_zip_code, shipping_address_primary, shipping_address_detail
+         FROM sales
+         WHERE status IN ('접수', '입금완료')
+         ORDER BY order_date ASC, sales_id ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let areas = load_remote_areas(pool).await?;
+    let mut issues = Vec::new();
+    for row in rows {
+        let ShipmentAddressRow {
+            sales_id,
+            shipping_name: name,
+            shipping_zip_code: zip,
+            shipping_address_primary: primary,
+            shipping_address_detail: detail,
+        } = row;
+        let primary_text = primary.as_deref().unwrap_or("").trim();
+        let zip_text = zip.as_deref().unwrap_or("").trim();
+
+        let mut issue = ShipmentAddressIssue {
+            sales_id,
+            shipping_name: name,
+            shipping_zip_code: zip.clone(),
+            shipping_address_primary: primary.clone(),
+            shipping_address_detail: detail.clone(),
+            issue: String::new(),
+            suggested_zip_code: None,
+            suggested_address: None,
+            remote_area_name: None,
+        };
+
+        if primary_text.is_empty() {
+            issue.issue = "주소 누락".to_string();
+            issues.push(issue);
+            continue;
+        }
+
+        let validation =
+            normalize_address_with_areas(pool, &areas, primary_text, detail.as_deref()).await?;
+        if validation.status == "matched" {
+            issue.suggested_address = Some(validation.address_primary.clone());
+            if zip_text.is_empty() {
+                issue.issue = "우편번호 누락".to_string();
+                issue.suggested_zip_code = validation.zip_code.clone();
+            } else if validation.zip_code.as_deref() != Some(zip_text) {
+                issue.issue = "우편번호 불일치".to_string();
+                issue.suggested_zip_code = validation.zip_code.clone();
+            }
+        } else if validation.status == "ambiguous" {
+            issue.issue = "주소 후보 다수 (시/군/구 확인 필요)".to_string();
+        } else {
+            issue.issue = "주소 확인 불가".to_string();
+        }
+
+        let effective_zip = issue
+            .suggested_zip_code
+            .clone()
+            .unwrap_or_else(|| zip_text.to_string());
+        if let Some(area) = find_remote_area(&effective_zip, &areas) {
+            issue.remote_area_name = Some(area.area_name.clone());
+            if issue.issue.is_empty() {
+                issue.issue = "도서산간".to_string();
+            }
+        }
+
+        if !issue.issue.is_empty() {
+            issues.push(issue);
+        }
+    }
+
+    Ok(issues)
+}
+
+#[derive(Deserialize)]
+pub struct AddressNormalizeInput {
+    pub address_primary: String,
+    pub address_detail: Option<String>,
+    pub customer_id: Option<String>,
+    pub address_id: Option<i32>,
+}
+
+pub async fn normalize_address_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<AddressNormalizeInput>,
+) -> MyceliumResult<Json<AddressValidation>> {
+    let mut result = normalize_address_internal(
+        &state.pool,
+        &input.address_primary,
+        input.address_detail.as_deref(),
+    )
+    .await?;
+
+    if let Some(customer_id) = input.customer_id.as_deref() {
+        result.duplicate_address_id = find_duplicate_address_id(
+            &state.pool,
+            customer_id,
+            &result.address_primary,
+            result.address_detail.as_deref(),
+            input.address_id,
+        )
+        .await?;
+    }
+    Ok(Json(result))
+}
+
+#[derive(Deserialize)]
+pub struct DuplicateAddressQuery {
+    pub customer_id: Option<String>,
+}
+
+pub async fn get_duplicate_addresses_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<DuplicateAddressQuery>,
+) -> MyceliumResult<Json<Vec<DuplicateAddressGroup>>> {
+    let customer_id = query.customer_id.filter(|c| !c.is_empty());
+    Ok(Json(
+        find_duplicate_addresses_internal(&state.pool, customer_id).await?,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct PostalImportPayload {
+    pub path: String,
+}
+
+pub async fn import_postal_db_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<PostalImportPayload>,
+) -> MyceliumResult<Json<PostalImportResult>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    Ok(Json(import_postal_db(&state.pool, payload.path).await?))
+}
+
+pub async fn get_postal_db_status_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<serde_json::Value>> {
+    let (count, last_imported): (i64, Option<chrono::NaiveDateTime>) =
+        sqlx::query_as("SELECT COUNT(*), MAX(imported_at) FROM postal_addresses")
+            .fetch_one(&state.pool)
+            .await?;
+    Ok(Json(serde_json::json!({
+        "total_count": count,
+        "last_imported_at": last_imported,
+    })))
+}
+
+pub async fn get_remote_areas_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<RemoteArea>>> {
+    Ok(Json(load_remote_areas(&state.pool).await?))
+}
+
+pub async fn save_remote_areas_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(areas): Json<Vec<RemoteArea>>,
+) -> MyceliumResult<Json<()>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    for a in &areas {
+        if find_remote_area(&a.zip_start, std::slice::from_ref(a)).is_none()
+            || a.zip_end.len() != 5
+            || a.zip_start > a.zip_end
+        {
+            return Err(MyceliumError::Validation(format!(
+                "잘못된 우편번호 범위입니다: {} ~ {}",
+                a.zip_start, a.zip_end
+            )));
+        }
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    sqlx::query("DELETE FROM postal_remote_areas")
+        .execute(&mut *tx)
+        .await?;
+    for a in &areas {
+        sqlx::query(
+            "INSERT INTO postal_remote_areas (zip_start, zip_end, area_name, is_jeju) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(&a.zip_start)
+        .bind(&a.zip_end)
+        .bind(&a.area_name)
+        .bind(a.is_jeju)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(Json(()))
+}
+
+pub async fn check_shipment_addresses_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<ShipmentAddressIssue>>> {
+    Ok(Json(check_shipment_addresses_internal(&state.pool).await?))
+}
@@ -0,0 +1,628 @@
+use crate::commands::config::load_integration_settings;
+use crate::commands::consent::MessagePurpose;
+use crate::commands::crm::{send_message_with, MessageChannels, MessageOptions};
+use crate::commands::message_template::{build_context, render, TemplateValue};
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+
+pub const TRIGGER_ANNIVERSARY: &str = "anniversary";
+pub const TRIGGER_REPURCHASE: &str = "repurchase";
+
+/// The scheduled run happens once a day inside this window, clear of the night-time
+/// advertising ban.
+const AUTO_RUN_START_HOUR: u32 = 10;
+const AUTO_RUN_END_HOUR: u32 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRule {
+    #[serde(default, alias = "rule_id")]
+    pub rule_id: Option<i32>,
+    #[serde(alias = "rule_name")]
+    pub rule_name: String,
+    #[serde(alias = "trigger_type")]
+    pub trigger_type: String,
+    /// Days before the anniversary, or the predicted days to repurchase at which to send
+    #[serde(default, alias = "offset_days")]
+    pub offset_days: i32,
+    /// Message template category. `${name}` and `${date}` are filled in, plus `${type}` for
+    /// anniversaries and `${product}` / `${recommend}` for repurchases.
+    #[serde(alias = "template_key")]
+    pub template_key: String,
+    #[serde(default = "default_purpose")]
+    pub purpose: String,
+    #[serde(default = "default_dedup_days", alias = "dedup_days")]
+    pub dedup_days: i32,
+    #[serde(default, alias = "is_enabled")]
+    pub is_enabled: bool,
+}
+
+fn default_purpose() -> String {
+    "promotional".to_string()
+}
+
+fn default_dedup_days() -> i32 {
+    7
+}
+
+impl AutomationRule {
+    pub fn validate(&self) -> MyceliumResult<()> {
+        if self.rule_name.trim().is_empty() || self.template_key.trim().is_empty() {
+            return Err(MyceliumError::Validation(
+                "규칙 이름과 템플릿을 입력해주세요.".to_string(),
+            ));
+        }
+        // Repurchase candidates only cover -5..=10 days around the predicted date
+        let offsets = match self.trigger_type.as_str() {
+            TRIGGER_ANNIVERSARY => 0..=60,
+            TRIGGER_REPURCHASE => -5..=10,
+            _ => {
+                return Err(MyceliumError::Validation(format!(
+                    "알 수 없는 자동화 조건입니다: {}",
+                    self.trigger_type
+                )))
+            }
+        };
+        if !offsets.contains(&self.offset_days) {
+            return Err(MyceliumError::Validation(format!(
+                "기준 일수는 {}~{}일 사이여야 합니다.",
+                offsets.start(),
+                offsets.end()
+            )));
+        }
+        if !(0..=365).contains(&self.dedup_days) {
+            return Err(MyceliumError::Validation(
+                "중복 방지 기간은 0~365일 사이여야 합니다.".to_string(),
+            ));
+        }
+        self.message_purpose().map(|_| ())
+    }
+
+    pub fn message_purpose(&self) -> MyceliumResult<MessagePurpose> {
+        match self.purpose.as_str() {
+            "promotional" => Ok(MessagePurpose::Promotional),
+            "transactional" => Ok(MessagePurpose::Transactional),
+            other => Err(MyceliumError::Validation(format!(
+                "알 수 없는 발송 목적입니다: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Whether an anniversary recurs on `day`; 29 February is kept on the 28th in other years.
+pub fn anniversary_falls_on(anniversary: NaiveDate, day: NaiveDate) -> bool {
+    if anniversary.month() == 2 && anniversary.day() == 29 && day.leap_year() {
+        return day.month() == 2 && day.day() == 29;
+    }
+    let (month, dom) = if anniversary.month() == 2 && anniversary.day() == 29 {
+        (2, 28)
+    } else {
+        (anniversary.month(), anniversary.day())
+    };
+    day.month() == month && day.day() == dom
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutomationTarget {
+    pub rule_id: i32,
+    pub rule_name: String,
+    pub customer_id: String,
+    pub customer_name: String,
+    pub mobile_number: String,
+    pub trigger_date: NaiveDate,
+    pub content: String,
+    /// Why the customer is not messaged; `None` means the message goes out
+    pub skip_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AutomationRunResult {
+    /// `None` for a dry run, which sends and records nothing
+    pub run_id: Option<i32>,
+    pub matched: usize,
+    pub sent: usize,
+    pub skipped: usize,
+    pub targets: Vec<AutomationTarget>,
+}
+
+pub async fn load_automation_rules(
+    pool: &DbPool,
+    enabled_only: bool,
+) -> MyceliumResult<Vec<AutomationRule>> {
+    Ok(sqlx::query_as::<_, AutomationRule>(
+        "SELECT rule_id, rule_name, trigger_type, offset_days, template_key, purpose, dedup_days, is_enabled
+         FROM automation_rules WHERE is_enabled OR NOT $1 ORDER BY rule_id",
+    )
+    .bind(enabled_only)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// A customer a rule fires for, with the template variables of the event
+struct RuleMatch {
+    customer_id: String,
+    customer_name: String,
+    mobile_number: String,
+    trigger_date: NaiveDate,
+    vars: Vec<(&'static str, TemplateValue)>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AnniversaryCustomer {
+    customer_id: String,
+    customer_name: String,
+    mobile_number: Option<String>,
+    anniversary_date: NaiveDate,
+    anniversary_type: Option<String>,
+}
+
+async fn match_rule(
+    pool: &DbPool,
+    rule: &AutomationRule,
+    today: NaiveDate,
+) -> MyceliumResult<Vec<RuleMatch>> {
+    if rule.trigger_type == TRIGGER_REPURCHASE {
+        let mut seen = HashSet::new();
+        // Most urgent product first, so a customer is messaged about that one
+        return Ok(crate::commands::crm::get_repurchase_candidates(pool)
+            .await?
+            .into_iter()
+            .filter(|c| c.predicted_days_remaining <= rule.offset_days)
+            .filter(|c| seen.insert(c.customer_id.clone()))
+            .map(|c| {
+                let trigger_date = today + Duration::days(c.predicted_days_remaining as i64);
+                RuleMatch {
+                    vars: vec![
+                        (
+                            "product",
+                            TemplateValue::Text(c.last_product.clone().unwrap_or_default()),
+                        ),
+                        (
+                            "recommend",
+                            TemplateValue::Text(c.recommended_product.clone().unwrap_or_default()),
+                        ),
+                        ("date", TemplateValue::Date(trigger_date)),
+                    ],
+                    customer_id: c.customer_id,
+                    customer_name: c.customer_name,
+                    mobile_number: c.mobile_number.unwrap_or_default(),
+                    trigger_date,
+                }
+            })
+            .collect());
+    }
+
+    let day = today + Duration::days(rule.offset_days as i64);
+    let customers = sqlx::query_as::<_, AnniversaryCustomer>(
+        "SELECT customer_id, customer_name, mobile_number, anniversary_date, anniversary_type
+             FROM customers
+             WHERE anniversary_date IS NOT NULL AND status = '정상' AND anonymized_at IS NULL
+               AND EXTRACT(MONTH FROM anniversary_date) = $1",
+    )
+    .bind(day.month() as i32)
+    .fetch_all(pool)
+    .await?;
+    Ok(customers
+        .into_iter()
+        .filter(|c| anniversary_falls_on(c.anniversary_date, day))
+        .map(|c| RuleMatch {
+            vars: vec![
+                (
+                    "type",
+                    TemplateValue::Text(c.anniversary_type.unwrap_or_else(|| "기념일".to_string())),
+                ),
+                ("date", TemplateValue::Date(day)),
+            ],
+            customer_id: c.customer_id,
+            customer_name: c.customer_name,
+            mobile_number: c.mobile_number.unwrap_or_default(),
+            trigger_date: day,
+        })
+        .collect())
+}
+
+/// Works out who each enabled rule would message today and why others are skipped:
+/// the event was already messaged, the customer got an automated message within the
+/// rule's de-duplication period, there is no number or template, or advertising consent
+/// is missing.
+pub async fn plan_automation(
+    pool: &DbPool,
+    today: NaiveDate,
+) -> MyceliumResult<Vec<AutomationTarget>> {
+    let templates = crate::commands::message_template::load_templates(pool).await?;
+    let mut targets = Vec::new();
+    let mut messaged_now: HashSet<String> = HashSet::new();
+
+    for rule in load_automation_rules(pool, true).await? {
+        let Some(rule_id) = rule.rule_id else {
+            continue;
+        };
+        let purpose = rule.message_purpose()?;
+        let template = templates
+            .get(&rule.template_key)
+            .and_then(|t| t.first())
+            .cloned();
+        let matches = match_rule(pool, &rule, today).await?;
+        if matches.is_empty() {
+            continue;
+        }
+
+        let ids: Vec<String> = matches.iter().map(|m| m.customer_id.clone()).collect();
+        let already_sent: HashSet<String> = sqlx::query_scalar(
+            "SELECT s.customer_id FROM automation_sends s
+             JOIN UNNEST($2::text[], $3::date[]) AS e (customer_id, trigger_date)
+               ON e.customer_id = s.customer_id AND e.trigger_date = s.trigger_date
+             WHERE s.rule_id = $1 AND s.skip_reason IS NULL",
+        )
+        .bind(rule_id)
+        .bind(&ids)
+        .bind(matches.iter().map(|m| m.trigger_date).collect::<Vec<_>>())
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+        let recently_sent: HashSet<String> = sqlx::query_scalar(
+            "SELECT DISTINCT customer_id FROM automation_sends
+             WHERE customer_id = ANY($1) AND skip_reason IS NULL
+               AND created_at >= CURRENT_TIMESTAMP - make_interval(days => $2)",
+        )
+        .bind(&ids)
+        .bind(rule.dedup_days)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+        let refused: HashSet<String> = if purpose == MessagePurpose::Promotional {
+            let numbers = matches
+                .iter()
+                .map(|m| m.mobile_number.clone())
+                .filter(|m| !m.trim().is_empty())
+                .collect();
+            // Consent only; the time of day is checked when sending
+            crate::commands::consent::prepare_promotional(
+                pool,
+                "SMS",
+                numbers,
+                "",
+                NaiveTime::from_hms_opt(12, 0, 0).unwrap_or_default(),
+            )
+            .await?
+            .excluded
+            .into_iter()
+            .collect()
+        } else {
+            HashSet::new()
+        };
+
+        for m in matches {
+            if already_sent.contains(&m.customer_id) {
+                continue;
+            }
+            let skip_reason = if m.mobile_number.trim().is_empty() {
+                Some("연락처 없음")
+            } else if template.is_none() {
+                Some("템플릿 없음")
+            } else if refused.contains(&m.mobile_number) {
+                Some("수신 동의 없음")
+            } else if rule.dedup_days > 0
+                && (recently_sent.contains(&m.customer_id) || messaged_now.contains(&m.customer_id))
+            {
+                Some("최근 발송")
+            } else {
+                None
+            };
+            if skip_reason.is_none() {
+                messaged_now.insert(m.customer_id.clone());
+            }
+            let content = match template.as_deref() {
+                Some(t) => {
+                    let mut context = build_context(pool, Some(&m.customer_id), None).await?;
+                    context.extend(m.vars);
+                    render(t, &context).text
+                }
+                None => String::new(),
+            };
+            targets.push(AutomationTarget {
+                rule_id,
+                rule_name: rule.rule_name.clone(),
+                content,
+                customer_id: m.customer_id,
+                customer_name: m.customer_name,
+                mobile_number: m.mobile_number,
+                trigger_date: m.trigger_date,
+                skip_reason: skip_reason.map(str::to_string),
+            });
+        }
+    }
+    Ok(targets)
+}
+
+/// Runs every enabled rule. A dry run only returns the plan; otherwise the messages are
+/// sent through the configured providers, see `run_automation_with`.
+pub async fn run_automation(
+    pool: &DbPool,
+    username: &str,
+    dry_run: bool,
+) -> MyceliumResult<AutomationRunResult> {
+    if dry_run {
+        let targets = plan_automation(pool, Local::now().date_naive()).await?;
+        let skipped = targets.iter().filter(|t| t.skip_reason.is_some()).count();
+        return Ok(AutomationRunResult {
+            run_id: None,
+            matched: targets.len(),
+            sent: targets.len() - skipped,
+            skipped,
+            targets,
+        });
+    }
+    let channels = MessageChannels::from_settings(load_integration_settings()?)?;
+    run_automation_with(pool, &channels, username).await
+}
+
+/// Sends the messages of every enabled rule and writes every target to the run log.
+pub async fn run_automation_with(
+    pool: &DbPool,
+    channels: &MessageChannels,
+    username: &str,
+) -> MyceliumResult<AutomationRunResult> {
+    let mut targets = plan_automation(pool, Local::now().date_naive()).await?;
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let (run_id,): (i32,) =
+        sqlx::query_as("INSERT INTO automation_runs (triggered_by) VALUES ($1) RETURNING run_id")
+            .bind(username)
+            .fetch_one(pool)
+            .await?;
+    let rules = load_automation_rules(pool, true).await?;
+
+    for target in targets.iter_mut() {
+        // The event's row is reserved before sending, so an overlapping run that reaches
+        // the same customer finds it taken instead of texting a second time
+        let send_id: Option<i32> = sqlx::query_scalar(
+            "INSERT INTO automation_sends
+                (run_id, rule_id, customer_id, trigger_date, mobile_number, content, skip_reason)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT DO NOTHING
+             RETURNING send_id",
+        )
+        .bind(run_id)
+        .bind(target.rule_id)
+        .bind(&target.customer_id)
+        .bind(target.trigger_date)
+        .bind(&target.mobile_number)
+        .bind(&target.content)
+        .bind(&target.skip_reason)
+        .fetch_optional(pool)
+        .await?;
+        if target.skip_reason.is_some() {
+            continue;
+        }
+        let Some(send_id) = send_id else {
+            target.skip_reason = Some("이미 발송".to_string());
+            continue;
+        };
+        let Some(rule) = rules.iter().find(|r| r.rule_id == Some(target.rule_id)) else {
+            continue;
+        };
+        let options = MessageOptions {
+            template_code: Some(rule.template_key.clone()),
+            ..Default::default()
+        };
+        if let Err(e) = send_message_with(
+            pool,
+            channels,
+            "SMS".to_string(),
+            vec![target.mobile_number.clone()],
+            target.content.clone(),
+            rule.message_purpose()?,
+            options,
+        )
+        .await
+        {
+            let reason: String = e.to_string().chars().take(50).collect();
+            sqlx::query("UPDATE automation_sends SET skip_reason = $2 WHERE send_id = $1")
+                .bind(send_id)
+                .bind(&reason)
+                .execute(pool)
+                .await?;
+            target.skip_reason = Some(reason);
+        }
+    }
+
+    let skipped = targets.iter().filter(|t| t.skip_reason.is_some()).count();
+    let sent = targets.len() - skipped;
+    sqlx::query(
+        "UPDATE automation_runs SET finished_at = CURRENT_TIMESTAMP, matched = $2, sent = $3, skipped = $4
+         WHERE run_id = $1",
+    )
+    .bind(run_id)
+    .bind(targets.len() as i32)
+    .bind(sent as i32)
+    .bind(skipped as i32)
+    .execute(pool)
+    .await?;
+
+    Ok(AutomationRunResult {
+        run_id: Some(run_id),
+        matched: targets.len(),
+        sent,
+        skipped,
+        targets,
+    })
+}
+
+/// Called by the background scheduler; runs once a day between 10:00 and 20:00 when a
+/// rule is enabled.
+pub async fn run_automation_if_due(pool: &DbPool) -> MyceliumResult<bool> {
+    let now = Local::now().naive_local();
+    if !(AUTO_RUN_START_HOUR..AUTO_RUN_END_HOUR).contains(&now.time().hour()) {
+        return Ok(false);
+    }
+    let (has_rules, ran_today): (bool, bool) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM automation_rules WHERE is_enabled),
+                EXISTS (SELECT 1 FROM automation_runs WHERE started_at::date = $1)",
+    )
+    .bind(now.date())
+    .fetch_one(pool)
+    .await?;
+    if !has_rules || ran_today {
+        return Ok(false);
+    }
+    run_automation(pool, "System", false).await?;
+    Ok(true)
+}
+
+// --- Axum Handlers ---
+
+pub async fn get_automation_rules_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<AutomationRule>>> {
+    Ok(Json(load_automation_rules(&state.pool, false).await?))
+}
+
+pub async fn save_automation_rule_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(rule): Json<AutomationRule>,
+) -> MyceliumResult<Json<i32>> {
+    rule.validate()?;
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let (rule_id,): (i32,) = match rule.rule_id {
+        Some(id) => sqlx::query_as(
+            "UPDATE automation_rules SET rule_name = $2, trigger_type = $3, offset_days = $4,
+                template_key = $5, purpose = $6, dedup_days = $7, is_enabled = $8,
+                updated_at = CURRENT_TIMESTAMP
+             WHERE rule_id = $1 RETURNING rule_id",
+        )
+        .bind(id),
+        None => sqlx::query_as(
+            "INSERT INTO automation_rules
+                (rule_name, trigger_type, offset_days, template_key, purpose, dedup_days, is_enabled)
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING rule_id",
+        ),
+    }
+    .bind(rule.rule_name.trim())
+    .bind(&rule.trigger_type)
+    .bind(rule.offset_days)
+    .bind(rule.template_key.trim())
+    .bind(&rule.purpose)
+    .bind(rule.dedup_days)
+    .bind(rule.is_enabled)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(rule_id))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRuleIdInput {
+    #[serde(alias = "rule_id")]
+    pub rule_id: i32,
+}
+
+/// The run log keeps its rows; only the rule goes.
+pub async fn delete_automation_rule_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<AutomationRuleIdInput>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query("DELETE FROM automation_rules WHERE rule_id = $1")
+        .bind(input.rule_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(Json(()))
+}
+
+pub async fn preview_automation_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<AutomationRunResult>> {
+    Ok(Json(run_automation(&state.pool, "Preview", true).await?))
+}
+
+pub async fn run_automation_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> MyceliumResult<Json<AutomationRunResult>> {
+    Ok(Json(
+        run_automation(
+            &state.pool,
+            claims.username.as_deref().unwrap_or("Admin"),
+            false,
+        )
+        .await?,
+    ))
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AutomationRun {
+    pub run_id: i32,
+    pub triggered_by: Option<String>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub matched: i32,
+    pub sent: i32,
+    pub skipped: i32,
+}
+
+pub async fn get_automation_runs_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<AutomationRun>>> {
+    Ok(Json(
+        sqlx::query_as::<_, AutomationRun>(
+            "SELECT run_id, triggered_by, started_at, finished_at, matched, sent, skipped
+             FROM automation_runs ORDER BY run_id DESC LIMIT 100",
+        )
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AutomationSend {
+    pub send_id: i32,
+    pub rule_id: i32,
+    pub rule_name: Option<String>,
+    pub customer_id: String,
+    pub customer_name: Option<String>,
+    pub trigger_date: NaiveDate,
+    pub mobile_number: Option<String>,
+    pub content: Option<String>,
+    pub skip_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationRunQuery {
+    #[serde(alias = "run_id")]
+    pub run_id: i32,
+}
+
+pub async fn get_automation_sends_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<AutomationRunQuery>,
+) -> MyceliumResult<Json<Vec<AutomationSend>>> {
+    Ok(Json(
+        sqlx::query_as::<_, AutomationSend>(
+            "SELECT s.send_id, s.rule_id, r.rule_name, s.customer_id, c.customer_name,
+                    s.trigger_date, s.mobile_number, s.content, s.skip_reason
+             FROM automation_sends s
+             LEFT JOIN automation_rules r ON r.rule_id = s.rule_id
+             LEFT JOIN customers c ON c.customer_id = s.customer_id
+             WHERE s.run_id = $1
+             ORDER BY s.skip_reason NULLS FIRST, s.send_id",
+        )
+        .bind(query.run_id)
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
@@ -1,5 +1,6 @@
 use crate::commands::backup::models::{
-    DeletionLog, ExperienceReservationBackup, ProductBomBackup, PurchaseBackup, SalesClaimBackup,
+    DeletionLog, ExperienceReservationBackup, KakaoTemplateBackup, MessageTemplateBackup,
+    PointLedgerBackup, ProductBomBackup, PurchaseBackup, SalesClaimBackup,
 };
 use crate::commands::backup::status::{get_last_backup_at, update_last_backup_at};
 
@@ -166,6 +167,14 @@ pub async fn backup_database_internal(
     let count_company: (i64,) = sqlx::query_as(&count_query("company_info", None))
         .fetch_one(pool)
         .await?;
+    let count_templates: (i64,) =
+        sqlx::query_as(&count_query("message_templates", Some("updated_at")))
+            .fetch_one(pool)
+            .await?;
+    let count_kakao_templates: (i64,) =
+        sqlx::query_as(&count_query("kakao_templates", Some("updated_at")))
+            .fetch_one(pool)
+            .await?;
     let count_expenses: (i64,) = sqlx::query_as(&count_query("expenses", None))
         .fetch_one(pool)
         .await?;
@@ -188,6 +197,9 @@ pub async fn backup_database_internal(
     let count_ledger: (i64,) = sqlx::query_as(&count_query("customer_ledger", None))
         .fetch_one(pool)
         .await?;
+    let count_points: (i64,) = sqlx::query_as(&count_query("point_ledger", Some("created_at")))
+        .fetch_one(pool)
+        .await?;
     let count_customer_logs: (i64,) = sqlx::query_as(&if let Some(s) = since {
         format!(
             "SELECT COUNT(*) FROM customer_logs WHERE changed_at > '{}'",
@@ -276,6 +288,8 @@ pub async fn backup_database_internal(
         + count_events.0
         + count_schedules.0
         + count_company.0
+        + count_templates.0
+        + count_kakao_templates.0
         + count_expenses.0
         + count_purchases.0
         + count_consultations.0
@@ -283,6 +297,7 @@ pub async fn backup_database_internal(
         + count_bom.0
         + count_inventory.0
         + count_ledger.0
+        + count_points.0
         + count_customer_logs.0
         + count_vendors.0
         + count_exp_programs.0
@@ -350,6 +365,18 @@ pub async fn backup_database_internal(
 
     backup_table!("users", User, None, "사용자 정보 백업 중...");
     backup_table!("company_info", CompanyInfo, None, "회사 정보 백업 중...");
+    backup_table!(
+        "message_templates",
+        MessageTemplateBackup,
+        Some("updated_at"),
+        "메시지 템플릿 백업 중..."
+    );
+    backup_table!(
+        "kakao_templates",
+        KakaoTemplateBackup,
+        Some("updated_at"),
+        "알림톡 템플릿 백업 중..."
+    );
     backup_table!("vendors", Vendor, None, "거래처 정보 백업 중...");
     backup_table!("products", Product, None, "품목 정보 백업 중...");
     backup_table!(
@@ -384,6 +411,12 @@ pub async fn backup_database_internal(
         "장부 정보 백업 중..."
     );
     backup_table!("sales", Sales, None, "판매 기록 백업 중...");
+    backup_table!(
+        "point_ledger",
+        PointLedgerBackup,
+        Some("created_at"),
+        "포인트 내역 백업 중..."
+    );
     backup_table!(
         "sales_claims",
         SalesClaimBackup,
@@ -603,10 +636,26 @@ pub async fn restore_database(pool: &DbPool, path: String) -> MyceliumResult<Str
                 }
                 "company_info" => {
                     let d: CompanyInfo = serde_json::from_value(data.clone())?;
-                    sqlx::query("INSERT INTO company_info (id, company_name, representative_name, address, business_type, item, phone_number, mobile_number, business_reg_number, registration_date, memo, certification_info, created_at, updated_at) 
-                                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) 
-                                 ON CONFLICT (id) DO UPDATE SET company_name=$2, representative_name=$3, address=$4, business_type=$5, item=$6, phone_number=$7, mobile_number=$8, business_reg_number=$9, registration_date=$10, memo=$11, certification_info=$12, updated_at=$14")
-                        .bind(d.id).bind(&d.company_name).bind(&d.representative_name).bind(&d.address).bind(&d.business_type).bind(&d.item).bind(&d.phone_number).bind(&d.mobile_number).bind(&d.business_reg_number).bind(d.registration_date).bind(&d.memo).bind(&d.certification_info).bind(d.created_at).bind(d.updated_at)
+                    sqlx::query("INSERT INTO company_info (id, company_name, representative_name, address, business_type, item, phone_number, mobile_number, business_reg_number, registration_date, memo, certification_info, created_at, updated_at, bank_account) 
+                                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15) 
+                                 ON CONFLICT (id) DO UPDATE SET company_name=$2, representative_name=$3, address=$4, business_type=$5, item=$6, phone_number=$7, mobile_number=$8, business_reg_number=$9, registration_date=$10, memo=$11, certification_info=$12, updated_at=$14, bank_account=$15")
+                        .bind(d.id).bind(&d.company_name).bind(&d.representative_name).bind(&d.address).bind(&d.business_type).bind(&d.item).bind(&d.phone_number).bind(&d.mobile_number).bind(&d.business_reg_number).bind(d.registration_date).bind(&d.memo).bind(&d.certification_info).bind(d.created_at).bind(d.updated_at).bind(&d.bank_account)
+                        .execute(&mut *tx).await?;
+                }
+                "message_templates" => {
+                    let d: MessageTemplateBackup = serde_json::from_value(data.clone())?;
+                    sqlx::query("INSERT INTO message_templates (template_id, category, sort_order, content, updated_by, updated_at) 
+                                 VALUES ($1, $2, $3, $4, $5, $6) 
+                                 ON CONFLICT (template_id) DO UPDATE SET category=$2, sort_order=$3, content=$4, updated_by=$5, updated_at=$6")
+                        .bind(d.template_id).bind(&d.category).bind(d.sort_order).bind(&d.content).bind(&d.updated_by).bind(d.updated_at)
+                        .execute(&mut *tx).await?;
+                }
+                "kakao_templates" => {
+                    let d: KakaoTemplateBackup = serde_json::from_value(data.clone())?;
+                    sqlx::query("INSERT INTO kakao_templates (template_key, template_code, description, is_enabled, updated_at) 
+                                 VALUES ($1, $2, $3, $4, $5) 
+                                 ON CONFLICT (template_key) DO UPDATE SET template_code=$2, description=$3, is_enabled=$4, updated_at=$5")
+                        .bind(&d.template_key).bind(&d.template_code).bind(&d.description).bind(d.is_enabled).bind(d.updated_at)
                         .execute(&mut *tx).await?;
                 }
                 "vendors" => {
@@ -657,10 +706,18 @@ pub async fn restore_database(pool: &DbPool, path: String) -> MyceliumResult<Str
                 }
                 "sales" => {
                     let d: Sales = serde_json::from_value(data.clone())?;
-                    sqlx::query("INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, specification, unit_price, quantity, total_amount, discount_rate, courier_name, tracking_number, memo, shipping_name, shipping_zip_code, shipping_address_primary, shipping_address_detail, shipping_mobile_number, shipping_date, paid_amount, payment_status, updated_at, product_code, product_id, supply_value, vat_amount, tax_type, tax_exempt_value, changed_by) 
-                                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29) 
-                                 ON CONFLICT (sales_id) DO UPDATE SET status=$3, order_date=$4, product_name=$5, specification=$6, unit_price=$7, quantity=$8, total_amount=$9, discount_rate=$10, courier_name=$11, tracking_number=$12, memo=$13, shipping_name=$14, shipping_zip_code=$15, shipping_address_primary=$16, shipping_address_detail=$17, shipping_mobile_number=$18, shipping_date=$19, paid_amount=$20, payment_status=$21, updated_at=$22, product_code=$23, product_id=$24, supply_value=$25, vat_amount=$26, tax_type=$27, tax_exempt_value=$28, changed_by=$29")
-                        .bind(&d.sales_id).bind(&d.customer_id).bind(&d.status).bind(d.order_date).bind(&d.product_name).bind(&d.specification).bind(d.unit_price).bind(d.quantity).bind(d.total_amount).bind(d.discount_rate).bind(&d.courier_name).bind(&d.tracking_number).bind(&d.memo).bind(&d.shipping_name).bind(&d.shipping_zip_code).bind(&d.shipping_address_primary).bind(&d.shipping_address_detail).bind(&d.shipping_mobile_number).bind(d.shipping_date).bind(d.paid_amount).bind(&d.payment_status).bind(d.updated_at).bind(&d.product_code).bind(d.product_id).bind(d.supply_value).bind(d.vat_amount).bind(&d.tax_type).bind(d.tax_exempt_value).bind(&d.changed_by)
+                    sqlx::query("INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, specification, unit_price, quantity, total_amount, discount_rate, courier_name, tracking_number, memo, shipping_name, shipping_zip_code, shipping_address_primary, shipping_address_detail, shipping_mobile_number, shipping_date, paid_amount, payment_status, updated_at, product_code, product_id, supply_value, vat_amount, tax_type, tax_exempt_value, changed_by, points_used) 
+                                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30) 
+                                 ON CONFLICT (sales_id) DO UPDATE SET status=$3, order_date=$4, product_name=$5, specification=$6, unit_price=$7, quantity=$8, total_amount=$9, discount_rate=$10, courier_name=$11, tracking_number=$12, memo=$13, shipping_name=$14, shipping_zip_code=$15, shipping_address_primary=$16, shipping_address_detail=$17, shipping_mobile_number=$18, shipping_date=$19, paid_amount=$20, payment_status=$21, updated_at=$22, product_code=$23, product_id=$24, supply_value=$25, vat_amount=$26, tax_type=$27, tax_exempt_value=$28, changed_by=$29, points_used=$30")
+                        .bind(&d.sales_id).bind(&d.customer_id).bind(&d.status).bind(d.order_date).bind(&d.product_name).bind(&d.specification).bind(d.unit_price).bind(d.quantity).bind(d.total_amount).bind(d.discount_rate).bind(&d.courier_name).bind(&d.tracking_number).bind(&d.memo).bind(&d.shipping_name).bind(&d.shipping_zip_code).bind(&d.shipping_address_primary).bind(&d.shipping_address_detail).bind(&d.shipping_mobile_number).bind(d.shipping_date).bind(d.paid_amount).bind(&d.payment_status).bind(d.updated_at).bind(&d.product_code).bind(d.product_id).bind(d.supply_value).bind(d.vat_amount).bind(&d.tax_type).bind(d.tax_exempt_value).bind(&d.changed_by).bind(d.points_used.unwrap_or(0))
+                        .execute(&mut *tx).await?;
+                }
+                "point_ledger" => {
+                    let d: PointLedgerBackup = serde_json::from_value(data.clone())?;
+                    sqlx::query("INSERT INTO point_ledger (entry_id, customer_id, entry_type, points, remaining, expires_at, sales_id, memo, created_by, created_at) 
+                                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) 
+                                 ON CONFLICT (entry_id) DO UPDATE SET remaining=$5")
+                        .bind(d.entry_id).bind(&d.customer_id).bind(&d.entry_type).bind(d.points).bind(d.remaining).bind(d.expires_at).bind(&d.sales_id).bind(&d.memo).bind(&d.created_by).bind(d.created_at)
                         .execute(&mut *tx).await?;
                 }
                 "inventory_logs" => {
@@ -92,3 +92,36 @@ pub struct ProductBomBackup {
     pub ratio: f64,
     pub created_at: Option<chrono::NaiveDateTime>,
 }
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct PointLedgerBackup {
+    pub entry_id: i32,
+    pub customer_id: String,
+    pub entry_type: String,
+    pub points: i32,
+    pub remaining: i32,
+    pub expires_at: Option<NaiveDate>,
+    pub sales_id: Option<String>,
+    pub memo: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct MessageTemplateBackup {
+    pub template_id: i32,
+    pub category: String,
+    pub sort_order: i32,
+    pub content: String,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct KakaoTemplateBackup {
+    pub template_key: String,
+    pub template_code: String,
+    pub description: Option<String>,
+    pub is_enabled: bool,
+    pub updated_at: Option<NaiveDateTime>,
+}
@@ -952,6 +952,9 @@ pub struct SaveCompanyInfoRequest {
     pub item: Option<String>,
     pub memo: Option<String>,
     pub certificationInfo: Option<serde_json::Value>,
+    /// Account customers pay into, for message templates
+    #[serde(default)]
+    pub bankAccount: Option<String>,
 }
 
 pub async fn save_company_info(
@@ -1000,6 +1003,7 @@ pub async fn save_company_info(
                 item = $9, 
                 memo = $10, 
                 certification_info = $11,
+                bank_account = COALESCE($12, bank_account),
                 updated_at = CURRENT_TIMESTAMP",
         )
         .bind(payload.companyName)
@@ -1013,6 +1017,7 @@ pub async fn save_company_info(
         .bind(payload.item)
         .bind(payload.memo)
         .bind(payload.certificationInfo)
+        .bind(payload.bankAccount)
         .execute(&state.pool)
         .await?;
     } else {
@@ -1020,8 +1025,8 @@ pub async fn save_company_info(
             "INSERT INTO company_info (
                 company_name, representative_name, phone_number, mobile_number, 
                 business_reg_number, registration_date, address, business_type, 
-                item, memo, certification_info
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+                item, memo, certification_info, bank_account
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
         )
         .bind(payload.companyName)
         .bind(payload.representativeName)
@@ -1034,6 +1039,7 @@ pub async fn save_company_info(
         .bind(payload.item)
         .bind(payload.memo)
         .bind(payload.certificationInfo)
+        .bind(payload.bankAccount)
         .execute(&state.pool)
         .await?;
     }
@@ -1118,6 +1124,7 @@ pub async fn log_audit(
 pub struct IntegrationSettings {
     pub gemini_api_key: Option<String>,
     pub sms: Option<SmsSettings>,
+    pub kakao: Option<KakaoSettings>,
     pub naver: Option<NaverSettings>,
     pub mall: Option<MallSettings>,
     pub courier: Option<CourierSettings>,
@@ -1137,8 +1144,90 @@ pub struct WeatherSettings {
 #[serde(rename_all = "camelCase")]
 pub struct SmsSettings {
     pub api_key: String,
+    /// Aligo user id, NHN Cloud secret key or Solapi API secret
+    #[serde(default)]
+    pub api_secret: String,
     pub sender_number: String,
     pub provider: String,
+    #[serde(default)]
+    pub costs: MessageCosts,
+    /// Proves the provider's delivery report and reply callbacks: the signature set on the
+    /// NHN Cloud console, or the secret Solapi signs them with
+    #[serde(default)]
+    pub webhook_secret: String,
+}
+
+/// Per-message price in won, as contracted with the provider
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageCosts {
+    pub sms: i32,
+    pub lms: i32,
+    pub mms: i32,
+}
+
+impl Default for MessageCosts {
+    fn default() -> Self {
+        Self {
+            sms: 9,
+            lms: 30,
+            mms: 100,
+        }
+    }
+}
+
+/// KakaoTalk business messages (AlimTalk / FriendTalk)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KakaoSettings {
+    /// "nhn" or "solapi"
+    pub provider: String,
+    /// NHN Cloud app key or Solapi API key
+    pub api_key: String,
+    pub api_secret: String,
+    /// Sender profile key (NHN) or channel pfId (Solapi)
+    pub sender_key: String,
+    /// Send SMS/LMS to recipients KakaoTalk could not reach
+    #[serde(default = "default_true")]
+    pub sms_fallback: bool,
+    #[serde(default)]
+    pub costs: KakaoCosts,
+    /// Proves the provider's delivery report and reply callbacks: the signature set on the
+    /// NHN Cloud console, or the secret Solapi signs them with
+    #[serde(default)]
+    pub webhook_secret: String,
+}
+
+impl Default for KakaoSettings {
+    fn default() -> Self {
+        Self {
+            provider: "mock".to_string(),
+            api_key: String::new(),
+            api_secret: String::new(),
+            sender_key: String::new(),
+            sms_fallback: true,
+            costs: KakaoCosts::default(),
+            webhook_secret: String::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KakaoCosts {
+    pub alimtalk: i32,
+    pub friendtalk: i32,
+}
+
+impl Default for KakaoCosts {
+    fn default() -> Self {
+        Self {
+            alimtalk: 8,
+            friendtalk: 15,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -1182,10 +1271,37 @@ pub struct SaveGeminiPayload {
     pub key: String,
 }
 
-#[derive(Deserialize)]
+/// The SMS settings form. What it leaves out keeps its stored value rather than going back
+/// to the default.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SaveSmsPayload {
-    #[serde(flatten)]
-    pub settings: SmsSettings,
+    pub api_key: String,
+    #[serde(default)]
+    pub api_secret: String,
+    pub sender_number: String,
+    pub provider: String,
+    #[serde(default)]
+    pub costs: Option<MessageCosts>,
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+}
+
+impl SaveSmsPayload {
+    pub fn merge_into(self, stored: Option<SmsSettings>) -> SmsSettings {
+        let (costs, webhook_secret) = match stored {
+            Some(s) => (s.costs, s.webhook_secret),
+            None => (MessageCosts::default(), String::new()),
+        };
+        SmsSettings {
+            api_key: self.api_key,
+            api_secret: self.api_secret,
+            sender_number: self.sender_number,
+            provider: self.provider,
+            costs: self.costs.unwrap_or(costs),
+            webhook_secret: self.webhook_secret.unwrap_or(webhook_secret),
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -1245,9 +1361,18 @@ pub async fn save_gemini_api_key_axum(
     Ok(Json(()))
 }
 
-pub async fn save_sms_config_axum(Json(payload): Json<SmsSettings>) -> MyceliumResult<Json<()>> {
+pub async fn save_sms_config_axum(Json(payload): Json<SaveSmsPayload>) -> MyceliumResult<Json<()>> {
     let mut settings = load_integration_settings()?;
-    settings.sms = Some(payload);
+    settings.sms = Some(payload.merge_into(settings.sms.take()));
+    save_integration_settings(&settings)?;
+    Ok(Json(()))
+}
+
+pub async fn save_kakao_config_axum(
+    Json(payload): Json<KakaoSettings>,
+) -> MyceliumResult<Json<()>> {
+    let mut settings = load_integration_settings()?;
+    settings.kakao = Some(payload);
     save_integration_settings(&settings)?;
     Ok(Json(()))
 }
@@ -1297,7 +1422,7 @@ pub async fn save_weather_config_axum(
 
 pub type MessageTemplates = std::collections::HashMap<String, Vec<String>>;
 
-fn default_message_templates() -> MessageTemplates {
+pub fn default_message_templates() -> MessageTemplates {
     let mut m = std::collections::HashMap::new();
     m.insert(
         "default".to_string(),
@@ -1323,10 +1448,23 @@ fn default_message_templates() -> MessageTemplates {
         "shipping_done".to_string(),
         vec!["발송 완료되었습니다! 맛있게 드세요. 🍄".to_string()],
     );
+    m.insert(
+        "membership_upgrade".to_string(),
+        vec!["${name}님, ${level} 등급이 되신 것을 축하드립니다! 늘 감사합니다. 🍄".to_string()],
+    );
+    m.insert(
+        "anniversary".to_string(),
+        vec!["${name}님, ${date} ${type}을 미리 축하드립니다! 🍄".to_string()],
+    );
+    m.insert(
+        "privacy_notice".to_string(),
+        vec!["${name}님, 장기간 이용 기록이 없어 ${date}에 개인정보가 파기될 예정입니다. 계속 이용을 원하시면 연락 부탁드립니다.".to_string()],
+    );
     m
 }
 
-fn load_message_templates_from_file() -> MyceliumResult<MessageTemplates> {
+/// The templates as kept before they moved to the database; read once to fill the table.
+pub fn load_message_templates_from_file() -> MyceliumResult<MessageTemplates> {
     let path = get_app_config_dir()?.join("templates.json");
     if path.exists() {
         let content = std::fs::read_to_string(path)?;
@@ -1338,36 +1476,11 @@ fn load_message_templates_from_file() -> MyceliumResult<MessageTemplates> {
     }
 }
 
-fn save_message_templates_to_file(templates: &MessageTemplates) -> MyceliumResult<()> {
-    let path = get_app_config_dir()?.join("templates.json");
-    let content = serde_json::to_string_pretty(templates)?;
-    std::fs::write(path, content)?;
-    Ok(())
-}
-
 #[derive(Deserialize)]
 pub struct SaveTemplatesPayload {
     pub templates: MessageTemplates,
 }
 
-pub async fn get_message_templates_axum() -> MyceliumResult<Json<MessageTemplates>> {
-    let templates = load_message_templates_from_file()?;
-    Ok(Json(templates))
-}
-
-pub async fn save_message_templates_axum(
-    Json(payload): Json<SaveTemplatesPayload>,
-) -> MyceliumResult<Json<()>> {
-    save_message_templates_to_file(&payload.templates)?;
-    Ok(Json(()))
-}
-
-pub async fn reset_message_templates_axum() -> MyceliumResult<Json<MessageTemplates>> {
-    let templates = default_message_templates();
-    save_message_templates_to_file(&templates)?;
-    Ok(Json(templates))
-}
-
 // --- Mobile Config ---
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
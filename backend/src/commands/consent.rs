@@ -0,0 +1,433 @@
+use crate::commands::customer::CustomerIdQuery;
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::atomic::Ordering;
+
+pub const CONSENT_CHANNELS: [&str; 3] = ["sms", "kakao", "email"];
+
+/// Channels an opt-out by phone number applies to
+const PHONE_CHANNELS: [&str; 2] = ["sms", "kakao"];
+
+const AD_PREFIX: &str = "(광고)";
+
+/// Replies that withdraw consent, compared without spaces and case
+const OPT_OUT_KEYWORDS: [&str; 6] = [
+    "수신거부",
+    "거부",
+    "광고거부",
+    "stop",
+    "unsubscribe",
+    "해지",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessagePurpose {
+    /// Advertising: consent, night-time and '(광고)' rules apply
+    #[default]
+    Promotional,
+    /// Order, shipping and account notices
+    Transactional,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ConsentRecord {
+    pub consent_id: i32,
+    pub customer_id: String,
+    pub channel: String,
+    pub consented: bool,
+    pub source: String,
+    pub memo: Option<String>,
+    pub recorded_by: Option<String>,
+    pub recorded_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MarketingSettings {
+    pub business_name: Option<String>,
+    pub optout_number: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreparedSend {
+    pub recipients: Vec<String>,
+    pub content: String,
+    /// Recipients dropped for missing consent or an opt-out
+    pub excluded: Vec<String>,
+}
+
+fn digits(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Advertising may not be sent between 21:00 and 08:00.
+pub fn is_ad_night_time(time: NaiveTime) -> bool {
+    let start = NaiveTime::from_hms_opt(21, 0, 0).unwrap_or_default();
+    let end = NaiveTime::from_hms_opt(8, 0, 0).unwrap_or_default();
+    time >= start || time < end
+}
+
+pub fn is_opt_out_keyword(message: &str) -> bool {
+    let normalized: String = message
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+    normalized.contains("수신거부") || OPT_OUT_KEYWORDS.contains(&normalized.as_str())
+}
+
+/// Starts the message with '(광고)' and the sender name, and ends it with the free
+/// opt-out number, adding whatever is missing.
+pub fn ad_content(content: &str, settings: &MarketingSettings) -> String {
+    let trimmed = content.trim();
+    let body = trimmed
+        .strip_prefix(AD_PREFIX)
+        .or_else(|| trimmed.strip_prefix("[광고]"))
+        .unwrap_or(trimmed)
+        .trim_start();
+
+    let name = settings
+        .business_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty() && !body.contains(*n));
+    let mut out = match name {
+        Some(name) => format!("{} {}\n{}", AD_PREFIX, name, body),
+        None => format!("{} {}", AD_PREFIX, body),
+    };
+
+    if let Some(number) = settings
+        .optout_number
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+    {
+        if !digits(body).contains(&digits(number)) {
+            out.push_str(&format!("\n무료수신거부 {}", number));
+        }
+    }
+    out
+}
+
+pub async fn load_marketing_settings(pool: &DbPool) -> MyceliumResult<MarketingSettings> {
+    Ok(sqlx::query_as::<_, MarketingSettings>(
+        "SELECT business_name, optout_number FROM marketing_settings WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or_default())
+}
+
+/// Records a consent change unless it matches the current state of the channel.
+/// The 'sms' consent is mirrored to `customers.marketing_consent`.
+pub async fn record_consent(
+    conn: &mut PgConnection,
+    customer_id: &str,
+    channel: &str,
+    consented: bool,
+    source: &str,
+    memo: Option<&str>,
+    username: &str,
+) -> MyceliumResult<bool> {
+    let inserted = sqlx::query(
+        "INSERT INTO customer_consents (customer_id, channel, consented, source, memo, recorded_by)
+         SELECT $1, $2, $3, $4, $5, $6
+         WHERE (SELECT consented FROM customer_consents
+                WHERE customer_id = $1 AND channel = $2
+                ORDER BY recorded_at DESC, consent_id DESC LIMIT 1) IS DISTINCT FROM $3",
+    )
+    .bind(customer_id)
+    .bind(channel)
+    .bind(consented)
+    .bind(source)
+    .bind(memo)
+    .bind(username)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if channel == "sms" {
+        sqlx::query("UPDATE customers SET marketing_consent = $1 WHERE customer_id = $2")
+            .bind(consented)
+            .bind(customer_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    if consented && PHONE_CHANNELS.contains(&channel) {
+        // Consenting again lifts an earlier opt-out of the same number
+        sqlx::query(
+            "DELETE FROM marketing_opt_outs WHERE mobile_digits =
+                (SELECT regexp_replace(mobile_number, '[^0-9]', '', 'g') FROM customers WHERE customer_id = $1)",
+        )
+        .bind(customer_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(inserted)
+}
+
+/// Withdraws advertising consent for a number, whether or not it belongs to a customer.
+/// Returns the customers it applied to.
+pub async fn register_opt_out(
+    pool: &DbPool,
+    username: &str,
+    mobile: &str,
+    source: &str,
+    memo: Option<&str>,
+) -> MyceliumResult<Vec<String>> {
+    let mobile_digits = digits(mobile);
+    if mobile_digits.len() < 9 {
+        return Err(MyceliumError::Validation(
+            "휴대폰 번호를 확인해주세요.".to_string(),
+        ));
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    sqlx::query(
+        "INSERT INTO marketing_opt_outs (mobile_digits, source, memo) VALUES ($1, $2, $3)
+         ON CONFLICT (mobile_digits) DO UPDATE SET source = $2, memo = $3, opted_out_at = CURRENT_TIMESTAMP",
+    )
+    .bind(&mobile_digits)
+    .bind(source)
+    .bind(memo)
+    .execute(&mut *tx)
+    .await?;
+
+    let customers: Vec<(String,)> = sqlx::query_as(
+        "SELECT customer_id FROM customers WHERE regexp_replace(mobile_number, '[^0-9]', '', 'g') = $1",
+    )
+    .bind(&mobile_digits)
+    .fetch_all(&mut *tx)
+    .await?;
+    for (customer_id,) in &customers {
+        for channel in PHONE_CHANNELS {
+            record_consent(&mut tx, customer_id, channel, false, source, memo, username).await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(customers.into_iter().map(|(id,)| id).collect())
+}
+
+/// Applies the advertising rules to a send: refuses at night, drops recipients without
+/// consent for the channel (or on the opt-out list) and adds the required wording.
+/// Recipients are mobile numbers; numbers of no known customer have no consent.
+pub async fn prepare_promotional(
+    pool: &DbPool,
+    mode: &str,
+    recipients: Vec<String>,
+    content: &str,
+    now: NaiveTime,
+) -> MyceliumResult<PreparedSend> {
+    if is_ad_night_time(now) {
+        return Err(MyceliumError::Validation(
+            "광고성 메시지는 21:00~08:00에 발송할 수 없습니다.".to_string(),
+        ));
+    }
+    let channel = if mode.eq_ignore_ascii_case("kakao") {
+        "kakao"
+    } else {
+        "sms"
+    };
+
+    let allowed: Vec<(String, bool)> = sqlx::query_as(
+        "SELECT r.recipient,
+                NOT EXISTS (SELECT 1 FROM marketing_opt_outs o WHERE r.digits <> '' AND o.mobile_digits = r.digits)
+                AND COALESCE((
+                    SELECT bool_and(COALESCE(
+                        (SELECT cc.consented FROM customer_consents cc
+                         WHERE cc.customer_id = c.customer_id AND cc.channel = $2
+                         ORDER BY cc.recorded_at DESC, cc.consent_id DESC LIMIT 1),
+                        (SELECT cc.consented FROM customer_consents cc
+                         WHERE cc.customer_id = c.customer_id AND cc.channel = 'sms'
+                         ORDER BY cc.recorded_at DESC, cc.consent_id DESC LIMIT 1),
+                        c.marketing_consent, FALSE))
+                    FROM customers c
+                    WHERE r.digits <> '' AND regexp_replace(c.mobile_number, '[^0-9]', '', 'g') = r.digits
+                ), FALSE)
+         FROM (SELECT x AS recipient, regexp_replace(x, '[^0-9]', '', 'g') AS digits
+               FROM UNNEST($1::text[]) AS x) r",
+    )
+    .bind(&recipients)
+    .bind(channel)
+    .fetch_all(pool)
+    .await?;
+
+    let (ok, refused): (Vec<_>, Vec<_>) = allowed.into_iter().partition(|(_, ok)| *ok);
+    let settings = load_marketing_settings(pool).await?;
+    Ok(PreparedSend {
+        recipients: ok.into_iter().map(|(r, _)| r).collect(),
+        content: ad_content(content, &settings),
+        excluded: refused.into_iter().map(|(r, _)| r).collect(),
+    })
+}
+
+pub async fn get_customer_consents_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<CustomerIdQuery>,
+) -> MyceliumResult<Json<Vec<ConsentRecord>>> {
+    Ok(Json(
+        sqlx::query_as::<_, ConsentRecord>(
+            "SELECT consent_id, customer_id, channel, consented, source, memo, recorded_by, recorded_at
+             FROM customer_consents WHERE customer_id = $1
+             ORDER BY recorded_at DESC, consent_id DESC",
+        )
+        .bind(&params.customer_id)
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsentInput {
+    #[serde(alias = "customer_id")]
+    pub customer_id: String,
+    pub channel: String,
+    pub consented: bool,
+    pub source: String,
+    pub memo: Option<String>,
+}
+
+pub async fn record_consent_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<ConsentInput>,
+) -> MyceliumResult<Json<bool>> {
+    if !CONSENT_CHANNELS.contains(&input.channel.as_str()) {
+        return Err(MyceliumError::Validation(format!(
+            "알 수 없는 채널입니다: {}",
+            input.channel
+        )));
+    }
+    if input.source.trim().is_empty() {
+        return Err(MyceliumError::Validation(
+            "동의 경로를 입력해주세요.".to_string(),
+        ));
+    }
+    let username = claims.username.as_deref().unwrap_or("Admin");
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    let changed = record_consent(
+        &mut tx,
+        &input.customer_id,
+        &input.channel,
+        input.consented,
+        input.source.trim(),
+        input.memo.as_deref(),
+        username,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(Json(changed))
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct OptOut {
+    pub mobile_digits: String,
+    pub source: String,
+    pub memo: Option<String>,
+    pub opted_out_at: Option<NaiveDateTime>,
+}
+
+pub async fn get_opt_outs_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<OptOut>>> {
+    Ok(Json(
+        sqlx::query_as::<_, OptOut>(
+            "SELECT mobile_digits, source, memo, opted_out_at FROM marketing_opt_outs
+             ORDER BY opted_out_at DESC",
+        )
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct OptOutInput {
+    pub mobile: String,
+    /// '080수신거부', '고객요청', ...
+    pub source: Option<String>,
+    pub memo: Option<String>,
+}
+
+pub async fn register_opt_out_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<OptOutInput>,
+) -> MyceliumResult<Json<Vec<String>>> {
+    Ok(Json(
+        register_opt_out(
+            &state.pool,
+            claims.username.as_deref().unwrap_or("Admin"),
+            &input.mobile,
+            input.source.as_deref().unwrap_or("080수신거부"),
+            input.memo.as_deref(),
+        )
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct InboundMessageInput {
+    pub mobile: String,
+    pub message: String,
+}
+
+/// A reply to one of our messages; opt-out keywords withdraw consent.
+pub async fn handle_inbound_opt_out(
+    pool: &DbPool,
+    mobile: &str,
+    message: &str,
+) -> MyceliumResult<bool> {
+    if !is_opt_out_keyword(message) {
+        return Ok(false);
+    }
+    register_opt_out(pool, "System", mobile, "문자회신", Some(message.trim())).await?;
+    Ok(true)
+}
+
+pub async fn inbound_opt_out_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<InboundMessageInput>,
+) -> MyceliumResult<Json<bool>> {
+    Ok(Json(
+        handle_inbound_opt_out(&state.pool, &input.mobile, &input.message).await?,
+    ))
+}
+
+pub async fn get_marketing_settings_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<MarketingSettings>> {
+    Ok(Json(load_marketing_settings(&state.pool).await?))
+}
+
+pub async fn save_marketing_settings_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<MarketingSettings>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query(
+        "UPDATE marketing_settings SET business_name = $1, optout_number = $2, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+    )
+    .bind(input.business_name.as_deref().map(str::trim))
+    .bind(input.optout_number.as_deref().map(str::trim))
+    .execute(&state.pool)
+    .await?;
+    Ok(Json(()))
+}
@@ -1,16 +1,44 @@
 #![allow(non_snake_case)]
 use crate::db::{Consultation, DbPool};
 use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
 use crate::stubs::State;
 use crate::DB_MODIFIED;
-use chrono::NaiveDate;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use sqlx::PgConnection;
 use std::sync::atomic::Ordering;
 
 use axum::{
     extract::{Query, State as AxumState},
-    Json,
+    Extension, Json,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+/// Ticket statuses; '접수' and '처리중' are open and count against the SLA
+pub const CONSULT_STATUSES: [&str; 4] = ["접수", "처리중", "보류", "완료"];
+const RESOLVED_STATUS: &str = "완료";
+/// `schedules.related_type` of follow-up reminders
+const FOLLOW_UP_RELATED_TYPE: &str = "CONSULTATION";
+
+/// Whether a ticket may move from `from` to `to`. Nothing returns to '접수' and a
+/// resolved ticket can only be reopened into '처리중'.
+pub fn can_transition(from: &str, to: &str) -> bool {
+    if from == to {
+        return CONSULT_STATUSES.contains(&to);
+    }
+    matches!(
+        (from, to),
+        ("접수", "처리중" | "보류" | "완료")
+            | ("처리중", "보류" | "완료")
+            | ("보류", "처리중" | "완료")
+            | ("완료", "처리중")
+    )
+}
+
+/// Due time of a ticket opened at `opened_at`; no SLA for the priority means no due time.
+pub fn sla_due_at(opened_at: NaiveDateTime, response_hours: Option<i32>) -> Option<NaiveDateTime> {
+    response_hours.map(|h| opened_at + Duration::hours(h as i64))
+}
 
 pub async fn create_consultation(
     state: State<'_, DbPool>,
@@ -73,8 +101,10 @@ pub async fn create_consultation_internal(
     };
 
     let consult_id: (i32,) = sqlx::query_as(
-        "INSERT INTO consultations (customer_id, guest_name, contact, channel, counselor_name, category, title, content, priority, sentiment) 
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING consult_id"
+        "INSERT INTO consultations (customer_id, guest_name, contact, channel, counselor_name, category, title, content, priority, sentiment, due_at) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10,
+                 CURRENT_TIMESTAMP + make_interval(hours => (SELECT response_hours FROM consultation_sla WHERE priority = $9)))
+         RETURNING consult_id"
     )
     .bind(customer_id)
     .bind(guest_name)
@@ -138,23 +168,237 @@ pub async fn update_consultation(
         };
     DB_MODIFIED.store(true, Ordering::Relaxed);
 
-    sqlx::query("UPDATE consultations SET answer=$1, status=$2, priority=$3, follow_up_date=$4 WHERE consult_id=$5")
-        .bind(answer)
-        .bind(status)
-        .bind(priority)
-        .bind(f_date)
-        .bind(consult_id)
-        .execute(&*state)
-        .await?;
+    update_consultation_internal(
+        state, "Admin", consult_id, answer, &status, &priority, f_date,
+    )
+    .await
+}
+
+/// Saves the answer, status, priority and follow-up date of a ticket. The status must be a
+/// valid transition; a priority change moves the due time to the new priority's SLA.
+pub async fn update_consultation_internal(
+    pool: &DbPool,
+    username: &str,
+    consult_id: i32,
+    answer: Option<String>,
+    status: &str,
+    priority: &str,
+    follow_up_date: Option<NaiveDate>,
+) -> MyceliumResult<()> {
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+
+    let (old_status, old_priority, created_at) = lock_ticket(&mut tx, consult_id).await?;
+    apply_status_change(&mut tx, consult_id, &old_status, status, username).await?;
+
+    let priority_changed = old_priority != priority;
+    let due_at = if priority_changed {
+        let hours = load_sla_hours(&mut tx, priority).await?;
+        sla_due_at(
+            created_at.unwrap_or_else(|| chrono::Local::now().naive_local()),
+            hours,
+        )
+    } else {
+        None
+    };
+    sqlx::query(
+        "UPDATE consultations SET answer = $1, priority = $2, follow_up_date = $3,
+                due_at = CASE WHEN $4 THEN $5 ELSE due_at END, updated_at = CURRENT_TIMESTAMP
+         WHERE consult_id = $6",
+    )
+    .bind(answer)
+    .bind(priority)
+    .bind(follow_up_date)
+    .bind(priority_changed)
+    .bind(due_at)
+    .bind(consult_id)
+    .execute(&mut *tx)
+    .await?;
+    record_history(
+        &mut tx,
+        consult_id,
+        "priority",
+        Some(&old_priority),
+        Some(priority),
+        username,
+    )
+    .await?;
 
+    sync_follow_up_schedule(&mut tx, consult_id).await?;
+    tx.commit().await?;
     Ok(())
 }
 
 pub async fn delete_consultation(state: State<'_, DbPool>, consult_id: i32) -> MyceliumResult<()> {
+    delete_consultation_internal(state, consult_id).await
+}
+
+/// Deletes a ticket with its history and follow-up reminder.
+pub async fn delete_consultation_internal(pool: &DbPool, consult_id: i32) -> MyceliumResult<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM schedules WHERE related_type = $1 AND related_id = $2")
+        .bind(FOLLOW_UP_RELATED_TYPE)
+        .bind(consult_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM consultation_history WHERE consult_id = $1")
+        .bind(consult_id)
+        .execute(&mut *tx)
+        .await?;
     sqlx::query("DELETE FROM consultations WHERE consult_id=$1")
         .bind(consult_id)
-        .execute(&*state)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn lock_ticket(
+    conn: &mut PgConnection,
+    consult_id: i32,
+) -> MyceliumResult<(String, String, Option<NaiveDateTime>)> {
+    sqlx::query_as(
+        "SELECT status, priority, created_at FROM consultations WHERE consult_id = $1 FOR UPDATE",
+    )
+    .bind(consult_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| MyceliumError::Validation("상담 내역을 찾을 수 없습니다.".to_string()))
+}
+
+async fn load_sla_hours(conn: &mut PgConnection, priority: &str) -> MyceliumResult<Option<i32>> {
+    Ok(
+        sqlx::query_scalar("SELECT response_hours FROM consultation_sla WHERE priority = $1")
+            .bind(priority)
+            .fetch_optional(&mut *conn)
+            .await?,
+    )
+}
+
+/// Moves a ticket to `new_status`, stamping `resolved_at` on '완료' and clearing it on reopen.
+async fn apply_status_change(
+    conn: &mut PgConnection,
+    consult_id: i32,
+    old_status: &str,
+    new_status: &str,
+    username: &str,
+) -> MyceliumResult<()> {
+    if !can_transition(old_status, new_status) {
+        return Err(MyceliumError::Validation(format!(
+            "'{}' 상태에서 '{}'(으)로 변경할 수 없습니다.",
+            old_status, new_status
+        )));
+    }
+    if old_status == new_status {
+        return Ok(());
+    }
+    sqlx::query(
+        "UPDATE consultations SET status = $1,
+                resolved_at = CASE WHEN $1 = $2 THEN CURRENT_TIMESTAMP END,
+                updated_at = CURRENT_TIMESTAMP
+         WHERE consult_id = $3",
+    )
+    .bind(new_status)
+    .bind(RESOLVED_STATUS)
+    .bind(consult_id)
+    .execute(&mut *conn)
+    .await?;
+    record_history(
+        conn,
+        consult_id,
+        "status",
+        Some(old_status),
+        Some(new_status),
+        username,
+    )
+    .await
+}
+
+async fn record_history(
+    conn: &mut PgConnection,
+    consult_id: i32,
+    field_name: &str,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+    username: &str,
+) -> MyceliumResult<()> {
+    if old_value == new_value {
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO consultation_history (consult_id, field_name, old_value, new_value, changed_by)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(consult_id)
+    .bind(field_name)
+    .bind(old_value)
+    .bind(new_value)
+    .bind(username)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+#[derive(sqlx::FromRow)]
+struct FollowUpTarget {
+    follow_up_date: Option<NaiveDate>,
+    status: String,
+    title: String,
+    customer: String,
+    content: String,
+}
+
+/// Keeps the ticket's follow-up reminder in `schedules` in line with its follow-up date:
+/// 09:00 on that day, 'Completed' once the ticket is resolved, removed when the date is cleared.
+pub async fn sync_follow_up_schedule(
+    conn: &mut PgConnection,
+    consult_id: i32,
+) -> MyceliumResult<()> {
+    sqlx::query("DELETE FROM schedules WHERE related_type = $1 AND related_id = $2")
+        .bind(FOLLOW_UP_RELATED_TYPE)
+        .bind(consult_id)
+        .execute(&mut *conn)
         .await?;
+
+    let ticket = sqlx::query_as::<_, FollowUpTarget>(
+        "SELECT c.follow_up_date, c.status, c.title, COALESCE(cu.customer_name, c.guest_name) AS customer, c.content
+         FROM consultations c
+         LEFT JOIN customers cu ON cu.customer_id = c.customer_id
+         WHERE c.consult_id = $1",
+    )
+    .bind(consult_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(FollowUpTarget {
+        follow_up_date: Some(date),
+        status,
+        title,
+        customer,
+        content,
+    }) = ticket
+    else {
+        return Ok(());
+    };
+
+    let start = date.and_hms_opt(9, 0, 0).unwrap_or_default();
+    let schedule_status = if status == RESOLVED_STATUS {
+        "Completed"
+    } else {
+        "Planned"
+    };
+    sqlx::query(
+        "INSERT INTO schedules (title, description, start_time, end_time, status, related_type, related_id)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(format!("[상담 후속] {} - {}", customer, title))
+    .bind(content)
+    .bind(start)
+    .bind(start + Duration::hours(1))
+    .bind(schedule_status)
+    .bind(FOLLOW_UP_RELATED_TYPE)
+    .bind(consult_id)
+    .execute(&mut *conn)
+    .await?;
     Ok(())
 }
 
@@ -202,6 +446,8 @@ pub async fn get_top_pending_consultations(
 pub struct ConsultDateQuery {
     pub start_date: Option<String>,
     pub end_date: Option<String>,
+    #[serde(alias = "assignee_id")]
+    pub assignee_id: Option<i32>,
 }
 
 pub async fn get_consultations_axum(
@@ -213,12 +459,13 @@ pub async fn get_consultations_axum(
             .map_err(|e| MyceliumError::Validation(format!("Invalid start date: {}", e)))?;
         let ed = NaiveDate::parse_from_str(&e, "%Y-%m-%d")
             .map_err(|e| MyceliumError::Validation(format!("Invalid end date: {}", e)))?;
-        sqlx::query_as::<_, Consultation>("SELECT * FROM consultations WHERE consult_date BETWEEN $1 AND $2 ORDER BY consult_date DESC, consult_id DESC")
-            .bind(sd).bind(ed).fetch_all(&state.pool).await?
+        sqlx::query_as::<_, Consultation>("SELECT * FROM consultations WHERE consult_date BETWEEN $1 AND $2 AND ($3::int IS NULL OR assignee_id = $3) ORDER BY consult_date DESC, consult_id DESC")
+            .bind(sd).bind(ed).bind(params.assignee_id).fetch_all(&state.pool).await?
     } else {
         sqlx::query_as::<_, Consultation>(
-            "SELECT * FROM consultations ORDER BY consult_date DESC, consult_id DESC LIMIT 200",
+            "SELECT * FROM consultations WHERE ($1::int IS NULL OR assignee_id = $1) ORDER BY consult_date DESC, consult_id DESC LIMIT 200",
         )
+        .bind(params.assignee_id)
         .fetch_all(&state.pool)
         .await?
     };
@@ -272,6 +519,7 @@ pub struct UpdateConsultInput {
 
 pub async fn update_consultation_axum(
     AxumState(state): AxumState<crate::state::AppState>,
+    Extension(claims): Extension<Claims>,
     Json(input): Json<UpdateConsultInput>,
 ) -> MyceliumResult<Json<()>> {
     let f_date =
@@ -288,14 +536,16 @@ pub async fn update_consultation_axum(
         };
     DB_MODIFIED.store(true, Ordering::Relaxed);
 
-    sqlx::query("UPDATE consultations SET answer=$1, status=$2, priority=$3, follow_up_date=$4 WHERE consult_id=$5")
-        .bind(input.answer)
-        .bind(input.status)
-        .bind(input.priority)
-        .bind(f_date)
-        .bind(input.consult_id)
-        .execute(&state.pool)
-        .await?;
+    update_consultation_internal(
+        &state.pool,
+        claims.username.as_deref().unwrap_or("Admin"),
+        input.consult_id,
+        input.answer,
+        &input.status,
+        &input.priority,
+        f_date,
+    )
+    .await?;
 
     Ok(Json(()))
 }
@@ -312,9 +562,361 @@ pub async fn delete_consultation_axum(
     Query(params): Query<DeleteConsultQuery>,
 ) -> MyceliumResult<Json<()>> {
     DB_MODIFIED.store(true, Ordering::Relaxed);
-    sqlx::query("DELETE FROM consultations WHERE consult_id=$1")
-        .bind(params.consult_id)
-        .execute(&state.pool)
+    delete_consultation_internal(&state.pool, params.consult_id).await?;
+    Ok(Json(()))
+}
+
+// --- Ticketing ---
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTicketInput {
+    #[serde(alias = "consult_id")]
+    pub consult_id: i32,
+    #[serde(alias = "assignee_id")]
+    pub assignee_id: Option<i32>,
+    /// Empty means the due time from the priority's SLA
+    #[serde(alias = "due_at")]
+    pub due_at: Option<NaiveDateTime>,
+    #[serde(alias = "sales_id")]
+    pub sales_id: Option<String>,
+    #[serde(alias = "claim_id")]
+    pub claim_id: Option<i32>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TicketState {
+    assignee_id: Option<i32>,
+    due_at: Option<NaiveDateTime>,
+    sales_id: Option<String>,
+    claim_id: Option<i32>,
+    priority: String,
+    created_at: Option<NaiveDateTime>,
+    customer_id: Option<String>,
+}
+
+async fn assignee_name(conn: &mut PgConnection, id: Option<i32>) -> MyceliumResult<Option<String>> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+    Ok(
+        sqlx::query_scalar("SELECT username FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await?,
+    )
+}
+
+/// Sets the assignee, due time and sale/claim link of a ticket. A claim fills in its sale,
+/// and a linked sale must belong to the ticket's customer.
+pub async fn update_ticket(
+    pool: &DbPool,
+    username: &str,
+    input: UpdateTicketInput,
+) -> MyceliumResult<()> {
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+
+    let current = sqlx::query_as::<_, TicketState>(
+        "SELECT assignee_id, due_at, sales_id, claim_id, priority, created_at, customer_id
+         FROM consultations WHERE consult_id = $1 FOR UPDATE",
+    )
+    .bind(input.consult_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| MyceliumError::Validation("상담 내역을 찾을 수 없습니다.".to_string()))?;
+
+    let new_assignee_name = assignee_name(&mut tx, input.assignee_id).await?;
+    if input.assignee_id.is_some() && new_assignee_name.is_none() {
+        return Err(MyceliumError::Validation(
+            "담당자를 찾을 수 없습니다.".to_string(),
+        ));
+    }
+
+    let mut sales_id = input.sales_id.filter(|s| !s.trim().is_empty());
+    if let Some(claim_id) = input.claim_id {
+        let claim_sale: String =
+            sqlx::query_scalar("SELECT sales_id FROM sales_claims WHERE claim_id = $1")
+                .bind(claim_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or_else(|| {
+                    MyceliumError::Validation("클레임을 찾을 수 없습니다.".to_string())
+                })?;
+        match &sales_id {
+            Some(s) if *s != claim_sale => {
+                return Err(MyceliumError::Validation(
+                    "클레임의 주문과 연결된 주문이 다릅니다.".to_string(),
+                ))
+            }
+            Some(_) => {}
+            None => sales_id = Some(claim_sale),
+        }
+    }
+    if let Some(s) = &sales_id {
+        let sale_customer: Option<Option<String>> =
+            sqlx::query_scalar("SELECT customer_id FROM sales WHERE sales_id = $1")
+                .bind(s)
+                .fetch_optional(&mut *tx)
+                .await?;
+        match sale_customer {
+            None => {
+                return Err(MyceliumError::Validation(
+                    "주문을 찾을 수 없습니다.".to_string(),
+                ))
+            }
+            Some(sale_customer)
+                if current.customer_id.is_some() && sale_customer != current.customer_id =>
+            {
+                return Err(MyceliumError::Validation(
+                    "다른 고객의 주문은 연결할 수 없습니다.".to_string(),
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let due_at = match input.due_at {
+        Some(due) => Some(due),
+        None => sla_due_at(
+            current
+                .created_at
+                .unwrap_or_else(|| chrono::Local::now().naive_local()),
+            load_sla_hours(&mut tx, &current.priority).await?,
+        ),
+    };
+
+    sqlx::query(
+        "UPDATE consultations SET assignee_id = $1, due_at = $2, sales_id = $3, claim_id = $4,
+                updated_at = CURRENT_TIMESTAMP
+         WHERE consult_id = $5",
+    )
+    .bind(input.assignee_id)
+    .bind(due_at)
+    .bind(&sales_id)
+    .bind(input.claim_id)
+    .bind(input.consult_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let old_assignee_name = assignee_name(&mut tx, current.assignee_id).await?;
+    let fmt_time = |t: Option<NaiveDateTime>| t.map(|t| t.format("%Y-%m-%d %H:%M").to_string());
+    let changes = [
+        ("assignee", old_assignee_name, new_assignee_name),
+        ("due_at", fmt_time(current.due_at), fmt_time(due_at)),
+        ("sales_id", current.sales_id, sales_id),
+        (
+            "claim_id",
+            current.claim_id.map(|c| c.to_string()),
+            input.claim_id.map(|c| c.to_string()),
+        ),
+    ];
+    for (field, old, new) in changes {
+        record_history(
+            &mut tx,
+            input.consult_id,
+            field,
+            old.as_deref(),
+            new.as_deref(),
+            username,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Moves a ticket along the status workflow.
+pub async fn change_ticket_status(
+    pool: &DbPool,
+    username: &str,
+    consult_id: i32,
+    status: &str,
+) -> MyceliumResult<()> {
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    let (old_status, _, _) = lock_ticket(&mut tx, consult_id).await?;
+    apply_status_change(&mut tx, consult_id, &old_status, status, username).await?;
+    sync_follow_up_schedule(&mut tx, consult_id).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct OverdueConsultation {
+    pub consult_id: i32,
+    pub title: String,
+    pub customer: String,
+    pub priority: String,
+    pub status: String,
+    pub assignee_id: Option<i32>,
+    pub assignee_name: Option<String>,
+    pub due_at: NaiveDateTime,
+    pub overdue_hours: i64,
+}
+
+/// Open tickets past their due time, most overdue first.
+pub async fn find_overdue_consultations(
+    pool: &DbPool,
+    assignee_id: Option<i32>,
+    limit: i64,
+) -> MyceliumResult<Vec<OverdueConsultation>> {
+    Ok(sqlx::query_as::<_, OverdueConsultation>(
+        "SELECT c.consult_id, c.title, COALESCE(cu.customer_name, c.guest_name) AS customer,
+                c.priority, c.status, c.assignee_id, u.username AS assignee_name, c.due_at,
+                (EXTRACT(EPOCH FROM (LOCALTIMESTAMP - c.due_at)) / 3600)::bigint AS overdue_hours
+         FROM consultations c
+         LEFT JOIN customers cu ON cu.customer_id = c.customer_id
+         LEFT JOIN users u ON u.id = c.assignee_id
+         WHERE c.status IN ('접수', '처리중') AND c.due_at < LOCALTIMESTAMP
+           AND ($1::int IS NULL OR c.assignee_id = $1)
+         ORDER BY c.due_at ASC, c.consult_id ASC
+         LIMIT $2",
+    )
+    .bind(assignee_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?)
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ConsultationHistory {
+    pub history_id: i32,
+    pub consult_id: i32,
+    pub field_name: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_by: Option<String>,
+    pub changed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsultationSla {
+    pub priority: String,
+    #[serde(alias = "response_hours")]
+    pub response_hours: i32,
+}
+
+/// Replaces the SLA of the given priorities. Due times of existing tickets are kept.
+pub async fn save_consultation_sla(pool: &DbPool, rules: &[ConsultationSla]) -> MyceliumResult<()> {
+    if let Some(rule) = rules
+        .iter()
+        .find(|r| r.priority.trim().is_empty() || r.response_hours <= 0)
+    {
+        return Err(MyceliumError::Validation(format!(
+            "응답 기한이 올바르지 않습니다: {}",
+            rule.priority
+        )));
+    }
+    let mut tx = pool.begin().await?;
+    for rule in rules {
+        sqlx::query(
+            "INSERT INTO consultation_sla (priority, response_hours) VALUES ($1, $2)
+             ON CONFLICT (priority) DO UPDATE SET response_hours = EXCLUDED.response_hours",
+        )
+        .bind(rule.priority.trim())
+        .bind(rule.response_hours)
+        .execute(&mut *tx)
         .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn update_ticket_axum(
+    AxumState(state): AxumState<crate::state::AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<UpdateTicketInput>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    update_ticket(
+        &state.pool,
+        claims.username.as_deref().unwrap_or("Admin"),
+        input,
+    )
+    .await?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketStatusInput {
+    #[serde(alias = "consult_id")]
+    pub consult_id: i32,
+    pub status: String,
+}
+
+pub async fn change_ticket_status_axum(
+    AxumState(state): AxumState<crate::state::AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<TicketStatusInput>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    change_ticket_status(
+        &state.pool,
+        claims.username.as_deref().unwrap_or("Admin"),
+        input.consult_id,
+        &input.status,
+    )
+    .await?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverdueQuery {
+    #[serde(alias = "assignee_id")]
+    pub assignee_id: Option<i32>,
+    pub limit: Option<i64>,
+}
+
+pub async fn get_overdue_consultations_axum(
+    AxumState(state): AxumState<crate::state::AppState>,
+    Query(query): Query<OverdueQuery>,
+) -> MyceliumResult<Json<Vec<OverdueConsultation>>> {
+    Ok(Json(
+        find_overdue_consultations(
+            &state.pool,
+            query.assignee_id,
+            query.limit.unwrap_or(100).clamp(1, 500),
+        )
+        .await?,
+    ))
+}
+
+pub async fn get_consultation_history_axum(
+    AxumState(state): AxumState<crate::state::AppState>,
+    Query(params): Query<DeleteConsultQuery>,
+) -> MyceliumResult<Json<Vec<ConsultationHistory>>> {
+    Ok(Json(
+        sqlx::query_as::<_, ConsultationHistory>(
+            "SELECT * FROM consultation_history WHERE consult_id = $1 ORDER BY changed_at, history_id",
+        )
+        .bind(params.consult_id)
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+pub async fn get_consultation_sla_axum(
+    AxumState(state): AxumState<crate::state::AppState>,
+) -> MyceliumResult<Json<Vec<ConsultationSla>>> {
+    Ok(Json(
+        sqlx::query_as::<_, ConsultationSla>(
+            "SELECT priority, response_hours FROM consultation_sla ORDER BY response_hours",
+        )
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+pub async fn save_consultation_sla_axum(
+    AxumState(state): AxumState<crate::state::AppState>,
+    Json(rules): Json<Vec<ConsultationSla>>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    save_consultation_sla(&state.pool, &rules).await?;
     Ok(Json(()))
 }
@@ -108,6 +108,7 @@ pub async fn sync_courier_status_internal(
                             .bind(&sales_id)
                             .execute(pool)
                             .await?;
+                        crate::commands::points::sync_sale_points(pool, &sales_id).await;
 
                         // SMS logic... (skipped for brevity but same as simulation)
                     }
@@ -147,6 +148,7 @@ pub async fn sync_courier_status_internal(
                 .bind(&sales_id)
                 .execute(pool)
                 .await?;
+            crate::commands::points::sync_sale_points(pool, &sales_id).await;
         }
 
         return Ok(CourierStatus {
@@ -1,10 +1,19 @@
+use crate::commands::config::{IntegrationSettings, KakaoSettings, MessageCosts};
+use crate::commands::consent::MessagePurpose;
+use crate::commands::messaging::kakao::{self, KakaoGateway, KakaoKind, KakaoMessage};
+use crate::commands::messaging::outbox::{self, NewCampaign};
+use crate::commands::messaging::{
+    self, Gateway, MessageImage, MessageProvider, MessageRequest, SendSummary,
+};
 use crate::db::{
     ChurnRiskCustomer, CustomerLifecycle, DbPool, LtvCustomer, ProductAssociation, RawRfmData,
 };
 use crate::error::MyceliumResult;
+use crate::middleware::auth::Claims;
 use crate::stubs::State;
 use crate::DB_MODIFIED;
-use axum::{extract::State as AxumState, Json};
+use axum::{extract::State as AxumState, Extension, Json};
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::Ordering;
 
 #[derive(Debug, serde::Serialize, sqlx::FromRow)]
@@ -27,13 +36,18 @@ pub struct SmsLog {
     pub content: String,
     pub status: String,
     pub sent_at: String,
+    pub provider: Option<String>,
+    pub message_type: Option<String>,
+    pub cost: i32,
+    pub error_message: Option<String>,
 }
 
 pub async fn get_sms_logs_axum(
     AxumState(state): AxumState<crate::state::AppState>,
 ) -> MyceliumResult<Json<Vec<SmsLog>>> {
     let logs = sqlx::query_as::<_, SmsLog>(
-        "SELECT log_id, recipient_name, mobile_number, content, status, sent_at::text as sent_at 
+        "SELECT log_id, recipient_name, mobile_number, content, status, sent_at::text as sent_at,
+                provider, message_type, cost, error_message
          FROM sms_logs ORDER BY sent_at DESC LIMIT 500",
     )
     .fetch_all(&state.pool)
@@ -122,78 +136,13 @@ pub async fn get_rfm_analysis(state: State<'_, DbPool>) -> MyceliumResult<Vec<Cu
         return Ok(vec![]);
     }
 
-    // RFM Scoring Logic (Simple version for MVP)
-    let mut results = Vec::new();
-    for d in raw_data {
-        let recency = match d.last_order_date {
-            Some(date) => {
-                let diff = (chrono::Local::now().date_naive() - date).num_days();
-                if diff < 30 {
-                    5
-                } else if diff < 90 {
-                    4
-                } else if diff < 180 {
-                    3
-                } else if diff < 365 {
-                    2
-                } else {
-                    1
-                }
-            }
-            None => 1,
-        };
-
-        let frequency = if d.total_orders > 20 {
-            5
-        } else if d.total_orders > 10 {
-            4
-        } else if d.total_orders > 5 {
-            3
-        } else if d.total_orders > 2 {
-            2
-        } else {
-            1
-        };
-        let monetary = if d.total_amount > 2000000 {
-            5
-        } else if d.total_amount > 1000000 {
-            4
-        } else if d.total_amount > 500000 {
-            3
-        } else if d.total_amount > 100000 {
-            2
-        } else {
-            1
-        };
-
-        let segment = match (recency, frequency) {
-            (5, 5) | (5, 4) | (4, 5) => "최우수고객 (Champions)",
-            (5, 2) | (4, 2) | (3, 3) => "잠재고객 (Promising)",
-            (2, 5) | (2, 4) | (1, 5) | (1, 4) => "이탈위험 (At Risk)",
-            (1, 1) | (1, 2) | (2, 1) => "휴면고객 (Hibernating)",
-            _ => "일반고객 (Need Attention)",
-        };
-
-        results.push(CustomerLifecycle {
-            customer_id: d.customer_id,
-            customer_name: d.customer_name,
-            mobile_number: d.mobile_number,
-            membership_level: d.membership_level,
-            last_order_date: d.last_order_date,
-            total_orders: d.total_orders,
-            total_amount: d.total_amount,
-            days_since_last_order: d
-                .last_order_date
-                .map(|date| (chrono::Local::now().date_naive() - date).num_days())
-                .unwrap_or(999),
-            rfm_segment: segment.to_string(),
-            recency,
-            frequency,
-            monetary,
-        });
-    }
-
-    Ok(results)
+    // Thresholds, quantile mode and segment names come from the RFM settings
+    let settings = super::rfm::load_rfm_settings(state).await?;
+    Ok(super::rfm::score_customers(
+        &settings,
+        raw_data,
+        chrono::Local::now().date_naive(),
+    ))
 }
 
 pub async fn update_customer_level(
@@ -372,63 +321,446 @@ pub async fn get_claim_targets_axum(
 
 #[derive(serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SmsSimulationRequest {
+pub struct SendSmsRequest {
     pub mode: String,
+    /// Mobile numbers
+    #[serde(default)]
     pub recipients: Vec<String>,
     pub content: String,
     pub template_code: Option<String>,
+    /// Saved segment whose customers are added to `recipients`
+    #[serde(default)]
+    pub segment_id: Option<i32>,
+    /// Customers whose mobile numbers are added to `recipients`
+    #[serde(default, alias = "customer_ids")]
+    pub customer_ids: Vec<String>,
+    /// Membership groups ("all", "vvip", "vip", "normal", "corp") added to `recipients`
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Whether this is advertising. Required, so a screen that leaves it out gets an error
+    /// instead of having its notices sent under the advertising rules.
+    pub purpose: MessagePurpose,
+    /// Title of an LMS/MMS; defaults to the first line
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// Base64 JPEG; attaching one sends MMS
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default, alias = "image_name")]
+    pub image_name: Option<String>,
+    /// Queues the send for this time instead of sending now
+    #[serde(default, alias = "scheduled_at")]
+    pub scheduled_at: Option<chrono::NaiveDateTime>,
+    /// Queues the send even for a few recipients
+    #[serde(default)]
+    pub queue: bool,
 }
 
-pub async fn send_sms_simulation_axum(
+/// Sends right away, or hands the send to the outbox worker when it is scheduled, asked to
+/// be queued or goes to more than `outbox::QUEUE_THRESHOLD` recipients.
+pub async fn send_sms_axum(
     AxumState(state): AxumState<crate::state::AppState>,
-    Json(payload): Json<SmsSimulationRequest>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<SendSmsRequest>,
 ) -> MyceliumResult<Json<serde_json::Value>> {
-    let result = send_sms_simulation(
+    let mut recipients = payload.recipients;
+    if let Some(segment_id) = payload.segment_id {
+        let from_segment = super::segment::segment_recipients(&state.pool, segment_id).await?;
+        recipients = super::segment::merge_recipients([recipients, from_segment]);
+        if recipients.is_empty() {
+            return Err(crate::error::MyceliumError::Validation(
+                "세그먼트에 해당하는 고객이 없습니다.".to_string(),
+            ));
+        }
+    }
+    if !payload.customer_ids.is_empty() || !payload.groups.is_empty() {
+        let from_customers =
+            customer_recipients(&state.pool, &payload.customer_ids, &payload.groups).await?;
+        recipients = super::segment::merge_recipients([recipients, from_customers]);
+        if recipients.is_empty() {
+            return Err(crate::error::MyceliumError::Validation(
+                "선택한 고객 중 휴대폰 번호가 있는 고객이 없습니다.".to_string(),
+            ));
+        }
+    }
+    let image = match payload.image.as_deref().filter(|i| !i.is_empty()) {
+        Some(encoded) => {
+            use base64::{engine::general_purpose, Engine as _};
+            // Data URLs from the browser carry a "data:image/jpeg;base64," prefix
+            let encoded = encoded.rsplit(',').next().unwrap_or(encoded);
+            Some(MessageImage {
+                file_name: payload
+                    .image_name
+                    .unwrap_or_else(|| "image.jpg".to_string()),
+                data: general_purpose::STANDARD.decode(encoded.trim())?,
+            })
+        }
+        None => None,
+    };
+    // Missing settings are reported now rather than by the worker after the fact
+    let channels = MessageChannels::from_settings(super::config::load_integration_settings()?)?;
+    if payload.queue || payload.scheduled_at.is_some() || recipients.len() > outbox::QUEUE_THRESHOLD
+    {
+        if payload.mode.eq_ignore_ascii_case("kakao") && channels.kakao.is_none() {
+            return Err(crate::error::MyceliumError::Validation(
+                KAKAO_NOT_CONFIGURED.to_string(),
+            ));
+        }
+        let campaign = NewCampaign {
+            mode: payload.mode,
+            purpose: payload.purpose,
+            recipients,
+            content: payload.content,
+            template_code: payload.template_code,
+            subject: payload.subject,
+            image,
+            scheduled_at: payload.scheduled_at,
+        };
+        let username = claims.username.as_deref().unwrap_or("Admin");
+        let progress = outbox::enqueue_campaign(&state.pool, campaign, username).await?;
+        return Ok(Json(serde_json::json!({
+            "success": true,
+            "queued": true,
+            "campaign_id": progress.campaign_id,
+            "count": progress.total,
+            "scheduled_at": progress.scheduled_at,
+            "message": format!("{}명에게 발송을 예약했습니다.", progress.total)
+        })));
+    }
+    let options = MessageOptions {
+        template_code: payload.template_code,
+        subject: payload.subject,
+        image,
+    };
+    let result = send_message_with(
         &state.pool,
+        &channels,
         payload.mode,
-        payload.recipients,
+        recipients,
         payload.content,
-        payload.template_code,
+        payload.purpose,
+        options,
     )
     .await?;
     Ok(Json(result))
 }
 
-pub async fn send_sms_simulation(
+/// `membership_level` values of a group on the SMS screen
+fn group_levels(group: &str) -> MyceliumResult<&'static [&'static str]> {
+    Ok(match group {
+        "vvip" => &["VVIP"],
+        "vip" => &["VIP"],
+        "normal" => &["일반"],
+        "corp" => &["법인/단체", "Group"],
+        other => {
+            return Err(crate::error::MyceliumError::Validation(format!(
+                "알 수 없는 발송 그룹입니다: {}",
+                other
+            )))
+        }
+    })
+}
+
+/// Mobile numbers of the given customers and of the active customers in the given
+/// membership groups. Customers without a number are left out.
+pub async fn customer_recipients(
+    pool: &DbPool,
+    customer_ids: &[String],
+    groups: &[String],
+) -> MyceliumResult<Vec<String>> {
+    let everyone = groups.iter().any(|g| g == "all");
+    let mut levels = Vec::new();
+    for group in groups.iter().filter(|g| *g != "all") {
+        levels.extend(group_levels(group)?.iter().map(|l| l.to_string()));
+    }
+    let numbers: Vec<(String,)> = sqlx::query_as(
+        "SELECT mobile_number FROM customers
+         WHERE COALESCE(mobile_number, '') <> ''
+           AND (customer_id = ANY($1)
+                OR (COALESCE(status, '정상') = '정상'
+                    AND ($2 OR COALESCE(NULLIF(membership_level, ''), '일반') = ANY($3))))
+         ORDER BY customer_id",
+    )
+    .bind(customer_ids)
+    .bind(everyone)
+    .bind(&levels)
+    .fetch_all(pool)
+    .await?;
+    Ok(numbers.into_iter().map(|(m,)| m).collect())
+}
+
+/// Optional parts of a send
+#[derive(Debug, Default)]
+pub struct MessageOptions {
+    /// Our template category (or a Kakao template code) the content was made from
+    pub template_code: Option<String>,
+    pub subject: Option<String>,
+    pub image: Option<MessageImage>,
+}
+
+/// Sends a text message through the providers configured in the settings. See
+/// `send_message`.
+pub async fn send_sms(
     pool: &DbPool,
     mode: String,
     recipients: Vec<String>,
     content: String,
-    _template_code: Option<String>,
+    template_code: Option<String>,
+    purpose: MessagePurpose,
 ) -> MyceliumResult<serde_json::Value> {
-    // 1. Simulation logic (simplified for brevity)
-    let msg_id = format!(
-        "SMS-{}-{}",
-        mode,
-        uuid::Uuid::new_v4().to_string()[..8].to_uppercase()
-    );
-
-    // Log to DB
-    for recipient in &recipients {
-        let _ = sqlx::query(
-            "INSERT INTO sms_logs (log_id, recipient_name, mobile_number, content, status, sent_at)
-             VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP)",
+    let options = MessageOptions {
+        template_code,
+        ..Default::default()
+    };
+    send_message(pool, mode, recipients, content, purpose, options).await
+}
+
+const KAKAO_NOT_CONFIGURED: &str =
+    "카카오톡 발송을 위해 '설정 > API 키'에서 카카오톡을 설정해주세요.";
+
+/// The gateways messages can go through
+pub struct MessageChannels {
+    /// Set when KakaoTalk is configured
+    pub kakao: Option<(KakaoGateway, KakaoSettings)>,
+    pub sms: Gateway,
+    pub sender: String,
+    pub sms_costs: MessageCosts,
+}
+
+impl MessageChannels {
+    pub fn from_settings(settings: IntegrationSettings) -> MyceliumResult<Self> {
+        let sms = messaging::gateway_from_settings(settings.sms.as_ref())?;
+        let sender = settings
+            .sms
+            .as_ref()
+            .map(|s| s.sender_number.clone())
+            .unwrap_or_default();
+        if sms.name() != "mock" && messaging::digits(&sender).is_empty() {
+            return Err(crate::error::MyceliumError::Validation(
+                "발신 번호를 설정해주세요.".to_string(),
+            ));
+        }
+        let kakao = match kakao::kakao_gateway_from_settings(settings.kakao.as_ref())? {
+            Some(gateway) => Some((gateway, settings.kakao.unwrap_or_default())),
+            None => None,
+        };
+        Ok(Self {
+            kakao,
+            sms,
+            sender,
+            sms_costs: settings.sms.map(|s| s.costs).unwrap_or_default(),
+        })
+    }
+}
+
+/// Sends through the channels in the settings; see `send_message_with`.
+pub async fn send_message(
+    pool: &DbPool,
+    mode: String,
+    recipients: Vec<String>,
+    content: String,
+    purpose: MessagePurpose,
+    options: MessageOptions,
+) -> MyceliumResult<serde_json::Value> {
+    let channels = MessageChannels::from_settings(super::config::load_integration_settings()?)?;
+    send_message_with(pool, &channels, mode, recipients, content, purpose, options).await
+}
+
+/// What one send did, per channel
+pub struct MessageDispatch {
+    /// KakaoTalk first when it was used, then SMS
+    pub summaries: Vec<SendSummary>,
+    /// Advertising recipients dropped for missing consent or an opt-out
+    pub excluded: Vec<String>,
+}
+
+impl MessageDispatch {
+    /// Numbers (digits only) accepted on some channel
+    pub fn reached(&self) -> HashSet<&str> {
+        self.summaries
+            .iter()
+            .flat_map(|s| &s.results)
+            .filter(|r| r.accepted)
+            .map(|r| r.recipient.as_str())
+            .collect()
+    }
+}
+
+/// Advertising goes through `consent::prepare_promotional` first: it is refused at night,
+/// recipients without consent are dropped and the '(광고)' wording is added.
+///
+/// KakaoTalk is used when the mode is "kakao", or for transactional messages whose template
+/// has an approved AlimTalk code once Kakao is set up; advertising goes as FriendTalk.
+/// Recipients Kakao could not reach get the same text by SMS/LMS (advertising only with SMS
+/// consent). Every attempt gets an `sms_logs` row.
+pub async fn dispatch_message(
+    pool: &DbPool,
+    channels: &MessageChannels,
+    mode: String,
+    recipients: Vec<String>,
+    content: String,
+    purpose: MessagePurpose,
+    options: MessageOptions,
+) -> MyceliumResult<MessageDispatch> {
+    let is_ad = purpose == MessagePurpose::Promotional;
+    let original_content = content.clone();
+    let (recipients, content, excluded) = if is_ad {
+        let prepared = super::consent::prepare_promotional(
+            pool,
+            &mode,
+            recipients,
+            &content,
+            chrono::Local::now().time(),
         )
-        .bind(&msg_id)
-        .bind("고객") // We don't have names here yet without extra lookups
-        .bind(recipient)
-        .bind(&content)
-        .bind("성공")
-        .execute(pool)
-        .await;
+        .await?;
+        if prepared.recipients.is_empty() {
+            return Ok(MessageDispatch {
+                summaries: Vec::new(),
+                excluded: prepared.excluded,
+            });
+        }
+        (prepared.recipients, prepared.content, prepared.excluded)
+    } else {
+        (recipients, content, Vec::new())
+    };
+
+    let alimtalk_code = match options.template_code.as_deref() {
+        Some(key) if !is_ad => kakao::find_alimtalk_template(pool, key).await?,
+        _ => None,
+    };
+    let kakao_mode = mode.eq_ignore_ascii_case("kakao");
+    if kakao_mode && channels.kakao.is_none() {
+        return Err(crate::error::MyceliumError::Validation(
+            KAKAO_NOT_CONFIGURED.to_string(),
+        ));
+    }
+    // KakaoTalk has no image messages here; those go as MMS
+    let kakao_channel = channels
+        .kakao
+        .as_ref()
+        .filter(|_| (kakao_mode || alimtalk_code.is_some()) && options.image.is_none());
+
+    let mut summaries = Vec::new();
+    let mut sms_recipients = recipients;
+    let mut fallback_of = None;
+    if let Some((gateway, kakao)) = kakao_channel {
+        let kind = if alimtalk_code.is_some() {
+            KakaoKind::AlimTalk
+        } else {
+            KakaoKind::FriendTalk
+        };
+        let message = KakaoMessage {
+            kind,
+            sender_key: kakao.sender_key.clone(),
+            template_code: alimtalk_code,
+            recipients: sms_recipients
+                .iter()
+                .map(|r| messaging::digits(r))
+                .collect(),
+            text: content.clone(),
+            is_ad,
+        };
+        let summary =
+            kakao::deliver_kakao(pool, gateway, message, &sms_recipients, &kakao.costs).await?;
+        let unreached: Vec<String> = sms_recipients
+            .iter()
+            .zip(&summary.results)
+            .filter(|(_, r)| !r.accepted)
+            .map(|(recipient, _)| recipient.clone())
+            .collect();
+        sms_recipients = if !kakao.sms_fallback || unreached.is_empty() {
+            Vec::new()
+        } else if is_ad {
+            // Consent for KakaoTalk advertising does not cover SMS
+            super::consent::prepare_promotional(
+                pool,
+                "sms",
+                unreached,
+                &original_content,
+                chrono::Local::now().time(),
+            )
+            .await?
+            .recipients
+        } else {
+            unreached
+        };
+        fallback_of = Some(summary.message_id.clone());
+        summaries.push(summary);
+    }
+
+    if !sms_recipients.is_empty() {
+        let request = MessageRequest {
+            sender: channels.sender.clone(),
+            recipients: sms_recipients,
+            text: content,
+            subject: options.subject,
+            image: options.image,
+            is_ad,
+            template_code: options.template_code,
+            fallback_of,
+        };
+        summaries
+            .push(messaging::deliver(pool, &channels.sms, request, &channels.sms_costs).await?);
+    }
+    Ok(MessageDispatch {
+        summaries,
+        excluded,
+    })
+}
+
+/// Sends right away (see `dispatch_message`) and reports the result; it is an error when
+/// nobody was reached.
+pub async fn send_message_with(
+    pool: &DbPool,
+    channels: &MessageChannels,
+    mode: String,
+    recipients: Vec<String>,
+    content: String,
+    purpose: MessagePurpose,
+    options: MessageOptions,
+) -> MyceliumResult<serde_json::Value> {
+    let dispatch =
+        dispatch_message(pool, channels, mode, recipients, content, purpose, options).await?;
+    let summaries = &dispatch.summaries;
+    if summaries.is_empty() && !dispatch.excluded.is_empty() {
+        return Err(crate::error::MyceliumError::Validation(format!(
+            "수신 동의한 받는 사람이 없습니다. (제외 {}명)",
+            dispatch.excluded.len()
+        )));
     }
+    let sent: usize = summaries.iter().map(|s| s.sent).sum();
+    if sent == 0 {
+        return Err(crate::error::MyceliumError::Validation(format!(
+            "메시지 발송에 실패했습니다: {}",
+            summaries
+                .iter()
+                .flat_map(|s| &s.results)
+                .find_map(|r| r.error.clone())
+                .unwrap_or_default()
+        )));
+    }
+    let first = &summaries[0];
+    // Recipients count once even when KakaoTalk failed and SMS went out
+    let reached = dispatch.reached();
+    let attempted: HashSet<&str> = first.results.iter().map(|r| r.recipient.as_str()).collect();
+    let fallback = summaries.get(1).map(|s| s.sent).unwrap_or(0);
 
     Ok(serde_json::json!({
         "success": true,
-        "count": recipients.len(),
-        "mode": "SIMULATION",
-        "message_id": msg_id,
-        "message": format!("{} sent to {} recipients (Logged)", mode, recipients.len())
+        "count": reached.len(),
+        "failed": attempted.len() - reached.len(),
+        "fallback": fallback,
+        "excluded": dispatch.excluded,
+        "mode": first.provider,
+        "message_type": first.message_type,
+        "cost": summaries.iter().map(|s| s.cost).sum::<i64>(),
+        "message_id": first.message_id,
+        "sends": summaries,
+        "message": summaries
+            .iter()
+            .map(|s| format!("{} {}건 발송, {}건 실패", s.message_type, s.sent, s.failed))
+            .collect::<Vec<_>>()
+            .join(" / ")
     }))
 }
 
@@ -454,37 +786,45 @@ pub async fn get_repurchase_candidates(
         ORDER BY predicted_days_remaining ASC
     "#;
 
-    Ok(sqlx::query_as::<_, crate::db::RepurchaseCandidate>(sql)
+    let mut candidates = sqlx::query_as::<_, crate::db::RepurchaseCandidate>(sql)
         .fetch_all(&*state)
-        .await?)
+        .await?;
+    super::recommendation::attach_recommendations(state, &mut candidates).await?;
+    Ok(candidates)
 }
 
 pub async fn get_product_associations(
     state: State<'_, DbPool>,
 ) -> MyceliumResult<Vec<ProductAssociation>> {
-    let total_bundles: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM (SELECT DISTINCT customer_id, order_date FROM sales WHERE customer_id IS NOT NULL AND order_date >= (CURRENT_DATE - INTERVAL '12 months') AND status != '취소') as t",
-    )
-    .fetch_one(&*state)
-    .await?;
-
-    if total_bundles == 0 {
-        return Ok(vec![]);
-    }
-
-    let sql = r#"
-        WITH SalesBundles AS (
-            SELECT customer_id, order_date, product_name FROM sales WHERE customer_id IS NOT NULL AND order_date >= (CURRENT_DATE - INTERVAL '12 months') AND status != '취소' GROUP BY customer_id, order_date, product_name
-        )
-        SELECT a.product_name as product_a, b.product_name as product_b, COUNT(*) as pair_count, (CAST(COUNT(*) AS FLOAT8) / $1 * 100.0) as support_percent
-        FROM SalesBundles a JOIN SalesBundles b ON a.customer_id = b.customer_id AND a.order_date = b.order_date
-        WHERE a.product_name < b.product_name GROUP BY a.product_name, b.product_name HAVING COUNT(*) >= 2 ORDER BY pair_count DESC, support_percent DESC LIMIT 50
-    "#;
-
-    Ok(sqlx::query_as::<_, ProductAssociation>(sql)
-        .bind(total_bundles)
-        .fetch_all(&*state)
-        .await?)
+    let rules = crate::commands::recommendation::load_association_rules(state).await?;
+    let reverse: HashMap<(&str, &str), f64> = rules
+        .iter()
+        .map(|r| ((r.antecedent.as_str(), r.consequent.as_str()), r.confidence))
+        .collect();
+
+    let mut associations: Vec<ProductAssociation> = rules
+        .iter()
+        .filter(|r| r.antecedent < r.consequent)
+        .map(|r| ProductAssociation {
+            product_a: r.antecedent.clone(),
+            product_b: r.consequent.clone(),
+            pair_count: r.pair_count,
+            support_percent: r.support * 100.0,
+            confidence_a_to_b: r.confidence,
+            confidence_b_to_a: reverse
+                .get(&(r.consequent.as_str(), r.antecedent.as_str()))
+                .copied()
+                .unwrap_or_default(),
+            lift: r.lift,
+        })
+        .collect();
+    associations.sort_by(|a, b| {
+        b.pair_count
+            .cmp(&a.pair_count)
+            .then(b.support_percent.total_cmp(&a.support_percent))
+    });
+    associations.truncate(50);
+    Ok(associations)
 }
 
 pub async fn get_product_associations_axum(
@@ -89,6 +89,7 @@ pub struct SetDefaultAddressInput {
 }
 use crate::commands::ai::call_gemini_ai_internal;
 use crate::commands::config::get_gemini_api_key;
+use crate::commands::timeline::{fetch_customer_timeline, parse_event_types, summarize_events};
 use crate::error::{MyceliumError, MyceliumResult};
 use crate::DB_MODIFIED;
 
@@ -110,29 +111,10 @@ pub async fn get_customer_ai_insight(
         .fetch_one(&*state)
         .await?;
 
-    // 2. Fetch Recent Sales
-    let sales: Vec<Sales> = sqlx::query_as(
-        "SELECT * FROM sales WHERE customer_id = $1 ORDER BY order_date DESC LIMIT 5",
-    )
-    .bind(&customerId)
-    .fetch_all(&*state)
-    .await
-    .unwrap_or_default();
-
-    let sales_summary = if sales.is_empty() {
-        "No purchase history".to_string()
-    } else {
-        sales
-            .iter()
-            .map(|s| {
-                format!(
-                    "{} (Qty: {}, Amt: {})",
-                    s.product_name, s.quantity, s.total_amount
-                )
-            })
-            .collect::<Vec<_>>()
-            .join(", ")
-    };
+    // 2. Recent activity from the customer timeline
+    let timeline =
+        fetch_customer_timeline(&*state, &customerId, &parse_event_types(None)?, 1, 20).await?;
+    let activity_summary = summarize_events(&timeline.items);
 
     // 3. Fetch Experience History
     let exp_count: (i64,) = sqlx::query_as(
@@ -156,7 +138,7 @@ pub async fn get_customer_ai_insight(
         Name: {}\n\
         Membership: {}\n\
         Address: {}\n\
-        Recent Sales: {}\n\
+        Recent Activity (newest first):\n{}\n\
         Experience Reservations: {} times\n\
         Claim History (Cancellation/Return): {} times\n\n\
         Please analyze this customer and provide:\n\
@@ -168,7 +150,7 @@ pub async fn get_customer_ai_insight(
         customer.customer_name,
         customer.membership_level.unwrap_or_else(|| "일반".to_string()),
         customer.address_primary.unwrap_or_else(|| "-".to_string()),
-        sales_summary,
+        activity_summary,
         exp_count.0,
         claim_count.0
     );
@@ -289,6 +271,19 @@ pub async fn create_customer(
     .execute(&mut *tx)
     .await?;
 
+    if marketingConsent.unwrap_or(false) {
+        crate::commands::consent::record_consent(
+            &mut tx,
+            &new_id,
+            "sms",
+            true,
+            "고객정보",
+            None,
+            username,
+        )
+        .await?;
+    }
+
     tx.commit().await?;
     Ok(new_id)
 }
@@ -368,6 +363,17 @@ pub async fn update_customer(
     .execute(&mut *tx)
     .await?;
 
+    crate::commands::consent::record_consent(
+        &mut tx,
+        &customerId,
+        "sms",
+        marketingConsent.unwrap_or(false),
+        "고객정보",
+        None,
+        username,
+    )
+    .await?;
+
     tx.commit().await?;
     Ok(())
 }
@@ -692,8 +698,12 @@ pub async fn check_duplicate_customer(
     name: String,
     mobile: String,
 ) -> MyceliumResult<Option<Customer>> {
+    // "010-1234-5678" and "01012345678", "홍 길동" and "홍길동" are the same customer
     Ok(sqlx::query_as::<_, Customer>(
-        "SELECT * FROM customers WHERE customer_name = $1 AND mobile_number = $2",
+        "SELECT * FROM customers
+         WHERE regexp_replace(customer_name, '\\s', '', 'g') = regexp_replace($1, '\\s', '', 'g')
+           AND regexp_replace(mobile_number, '[^0-9]', '', 'g') = regexp_replace($2, '[^0-9]', '', 'g')
+         LIMIT 1",
     )
     .bind(name)
     .bind(mobile)
@@ -887,6 +897,19 @@ pub async fn create_customer_axum(
     .execute(&mut *tx)
     .await?;
 
+    if input.marketing_consent.unwrap_or(false) {
+        crate::commands::consent::record_consent(
+            &mut tx,
+            &new_id,
+            "sms",
+            true,
+            "고객정보",
+            None,
+            username,
+        )
+        .await?;
+    }
+
     tx.commit().await?;
 
     Ok(Json(new_id))
@@ -956,6 +979,17 @@ pub async fn update_customer_axum(
     .execute(&mut *tx)
     .await?;
 
+    crate::commands::consent::record_consent(
+        &mut tx,
+        &customer_id,
+        "sms",
+        input.marketing_consent.unwrap_or(false),
+        "고객정보",
+        None,
+        username,
+    )
+    .await?;
+
     // 3. Log Changes (Selective)
     let mut changes = Vec::new();
     if old.customer_name != input.customer_name {
@@ -1086,6 +1120,28 @@ pub async fn create_customer_address_axum(
     let mut tx = state.pool.begin().await?;
     crate::db::set_db_user_context(&mut *tx, username).await?;
 
+    let address = crate::commands::address::resolve_address(
+        &state.pool,
+        input.zip,
+        &input.addr1,
+        input.addr2.as_deref(),
+    )
+    .await?;
+    if let Some(existing_id) = crate::commands::address::find_duplicate_address_id(
+        &state.pool,
+        &input.customer_id,
+        &address.address_primary,
+        address.address_detail.as_deref(),
+        None,
+    )
+    .await?
+    {
+        return Err(MyceliumError::Validation(format!(
+            "이미 등록된 배송지와 같은 주소입니다. (배송지 번호: {})",
+            existing_id
+        )));
+    }
+
     if input.is_default {
         sqlx::query("UPDATE customer_addresses SET is_default = FALSE WHERE customer_id = $1")
             .bind(&input.customer_id)
@@ -1096,18 +1152,19 @@ pub async fn create_customer_address_axum(
     let row: (i32,) = sqlx::query_as(
         "INSERT INTO customer_addresses (
             customer_id, address_alias, recipient_name, mobile_number, zip_code, 
-            address_primary, address_detail, is_default, shipping_memo
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING address_id",
+            address_primary, address_detail, is_default, shipping_memo, is_remote_area
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING address_id",
     )
     .bind(&input.customer_id)
     .bind(&input.alias)
     .bind(&input.recipient)
     .bind(&input.mobile)
-    .bind(input.zip)
-    .bind(&input.addr1)
-    .bind(input.addr2)
+    .bind(address.zip_code)
+    .bind(address.address_primary)
+    .bind(address.address_detail)
     .bind(input.is_default)
     .bind(input.memo)
+    .bind(address.is_remote_area)
     .fetch_one(&mut *tx)
     .await?;
 
@@ -1131,6 +1188,28 @@ pub async fn update_customer_address_axum(
             .fetch_one(&mut *tx)
             .await?;
 
+    let address = crate::commands::address::resolve_address(
+        &state.pool,
+        input.zip,
+        &input.addr1,
+        input.addr2.as_deref(),
+    )
+    .await?;
+    if let Some(existing_id) = crate::commands::address::find_duplicate_address_id(
+        &state.pool,
+        &customer_id.0,
+        &address.address_primary,
+        address.address_detail.as_deref(),
+        Some(address_id),
+    )
+    .await?
+    {
+        return Err(MyceliumError::Validation(format!(
+            "이미 등록된 배송지와 같은 주소입니다. (배송지 번호: {})",
+            existing_id
+        )));
+    }
+
     if input.is_default {
         sqlx::query("UPDATE customer_addresses SET is_default = FALSE WHERE customer_id = $1")
             .bind(&customer_id.0)
@@ -1141,17 +1220,19 @@ pub async fn update_customer_address_axum(
     sqlx::query(
         "UPDATE customer_addresses SET 
             address_alias = $1, recipient_name = $2, mobile_number = $3, zip_code = $4, 
-            address_primary = $5, address_detail = $6, is_default = $7, shipping_memo = $8
-        WHERE address_id = $9",
+            address_primary = $5, address_detail = $6, is_default = $7, shipping_memo = $8,
+            is_remote_area = $9
+        WHERE address_id = $10",
     )
     .bind(&input.alias)
     .bind(&input.recipient)
     .bind(&input.mobile)
-    .bind(input.zip)
-    .bind(&input.addr1)
-    .bind(input.addr2)
+    .bind(address.zip_code)
+    .bind(address.address_primary)
+    .bind(address.address_detail)
     .bind(input.is_default)
     .bind(input.memo)
+    .bind(address.is_remote_area)
     .bind(address_id)
     .execute(&mut *tx)
     .await?;
@@ -1229,29 +1310,16 @@ pub async fn get_customer_ai_insight_axum(
         .fetch_one(&state.pool)
         .await?;
 
-    // 2. Fetch Recent Sales
-    let sales: Vec<Sales> = sqlx::query_as(
-        "SELECT * FROM sales WHERE customer_id = $1 ORDER BY order_date DESC LIMIT 5",
+    // 2. Recent activity from the customer timeline
+    let timeline = fetch_customer_timeline(
+        &state.pool,
+        &params.customer_id,
+        &parse_event_types(None)?,
+        1,
+        20,
     )
-    .bind(&params.customer_id)
-    .fetch_all(&state.pool)
-    .await
-    .unwrap_or_default();
-
-    let sales_summary = if sales.is_empty() {
-        "No purchase history".to_string()
-    } else {
-        sales
-            .iter()
-            .map(|s| {
-                format!(
-                    "{} (Qty: {}, Amt: {})",
-                    s.product_name, s.quantity, s.total_amount
-                )
-            })
-            .collect::<Vec<_>>()
-            .join(", ")
-    };
+    .await?;
+    let activity_summary = summarize_events(&timeline.items);
 
     // 3. Fetch Experience History
     let exp_count: (i64,) = sqlx::query_as(
@@ -1275,7 +1343,7 @@ pub async fn get_customer_ai_insight_axum(
         Name: {}\n\
         Membership: {}\n\
         Address: {}\n\
-        Recent Sales: {}\n\
+        Recent Activity (newest first):\n{}\n\
         Experience Reservations: {} times\n\
         Claim History (Cancellation/Return): {} times\n\n\
         Please analyze this customer and provide:\n\
@@ -1287,7 +1355,7 @@ pub async fn get_customer_ai_insight_axum(
         customer.customer_name,
         customer.membership_level.unwrap_or_else(|| "일반".to_string()),
         customer.address_primary.unwrap_or_else(|| "-".to_string()),
-        sales_summary,
+        activity_summary,
         exp_count.0,
         claim_count.0
     );
@@ -0,0 +1,662 @@
+use crate::commands::address::address_dedup_key;
+use crate::commands::sales::mall_import::mobile_digits;
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::Ordering;
+
+/// Pairs scoring below this are not shown as duplicate candidates
+pub const DUPLICATE_MIN_SCORE: i32 = 45;
+
+/// Rows that follow the surviving customer: the table, the column pointing at the customer
+/// and the expression identifying a row. `sms_logs` has no primary key (one log_id per send,
+/// shared by all recipients).
+const MERGE_TABLES: [(&str, &str, &str); 13] = [
+    ("sales", "customer_id", "sales_id"),
+    ("sales_claims", "customer_id", "claim_id::text"),
+    ("customer_ledger", "customer_id", "ledger_id::text"),
+    ("consultations", "customer_id", "consult_id::text"),
+    ("customer_addresses", "customer_id", "address_id::text"),
+    (
+        "sms_logs",
+        "customer_id",
+        "COALESCE(log_id, '') || '|' || mobile_number || '|' || COALESCE(sent_at::text, '')",
+    ),
+    (
+        "experience_reservations",
+        "customer_id",
+        "reservation_id::text",
+    ),
+    ("point_ledger", "customer_id", "entry_id::text"),
+    ("customer_consents", "customer_id", "consent_id::text"),
+    ("automation_sends", "customer_id", "send_id::text"),
+    ("inbound_messages", "customer_id", "inbound_id::text"),
+    ("referral_rewards", "referrer_id", "reward_id::text"),
+    ("referral_rewards", "referee_id", "reward_id::text"),
+];
+
+/// Customers the removed one had referred are credited to the survivor
+const REFERRED_BY: (&str, &str, &str) = ("customers", "referred_by", "customer_id");
+
+/// Where a table's moved rows are listed in `moved_rows`
+fn moved_key(table: &str, column: &str) -> String {
+    if column == "customer_id" {
+        table.to_string()
+    } else {
+        format!("{}.{}", table, column)
+    }
+}
+
+/// Rows left with the removed customer because the survivor already has the same one
+fn merge_filter(table: &str) -> &'static str {
+    match table {
+        // One automated message per rule and event
+        "automation_sends" => {
+            " AND NOT (skip_reason IS NULL AND EXISTS (
+                 SELECT 1 FROM automation_sends o
+                 WHERE o.customer_id = $1 AND o.rule_id = automation_sends.rule_id
+                   AND o.trigger_date = automation_sends.trigger_date AND o.skip_reason IS NULL))"
+        }
+        _ => "",
+    }
+}
+
+/// Profile fields copied to the surviving customer when it has no value of its own
+const FILLABLE_FIELDS: [&str; 7] = [
+    "phone_number",
+    "email",
+    "zip_code",
+    "address_primary",
+    "address_detail",
+    "anniversary_date",
+    "anniversary_type",
+];
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DuplicateKey {
+    pub customer_id: String,
+    pub customer_name: String,
+    pub mobile_number: String,
+    pub address_primary: Option<String>,
+    pub address_detail: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateCustomer {
+    pub customer_id: String,
+    pub customer_name: String,
+    pub mobile_number: String,
+    pub address_primary: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateCandidate {
+    pub score: i32,
+    pub reasons: Vec<&'static str>,
+    pub customers: [DuplicateCustomer; 2],
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct CustomerMerge {
+    pub merge_id: i32,
+    pub source_customer_id: String,
+    pub target_customer_id: String,
+    pub source_name: Option<String>,
+    pub moved_rows: serde_json::Value,
+    pub filled_fields: Vec<String>,
+    pub balance_moved: i32,
+    pub merged_by: Option<String>,
+    pub merged_at: Option<NaiveDateTime>,
+    pub undone_by: Option<String>,
+    pub undone_at: Option<NaiveDateTime>,
+}
+
+fn name_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Edit distance between two names, counted in characters (one Hangul syllable is one edit).
+pub fn name_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = name_key(a).chars().collect();
+    let b: Vec<char> = name_key(b).chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+fn address_key(c: &DuplicateKey) -> Option<String> {
+    c.address_primary
+        .as_deref()
+        .filter(|a| !a.trim().is_empty())
+        .map(|a| address_dedup_key(a, c.address_detail.as_deref()))
+}
+
+/// Scores how likely two customer records are the same person.
+pub fn duplicate_score(a: &DuplicateKey, b: &DuplicateKey) -> (i32, Vec<&'static str>) {
+    let mut score = 0;
+    let mut reasons = Vec::new();
+
+    let (mobile_a, mobile_b) = (
+        mobile_digits(&a.mobile_number),
+        mobile_digits(&b.mobile_number),
+    );
+    if mobile_a.len() >= 10 && mobile_a == mobile_b {
+        score += 60;
+        reasons.push("휴대폰 번호 일치");
+    } else if mobile_a.len() >= 4
+        && mobile_b.len() >= 4
+        && mobile_a[mobile_a.len() - 4..] == mobile_b[mobile_b.len() - 4..]
+    {
+        score += 15;
+        reasons.push("휴대폰 뒷자리 일치");
+    }
+
+    match name_distance(&a.customer_name, &b.customer_name) {
+        0 => {
+            score += 30;
+            reasons.push("이름 일치");
+        }
+        1 if name_key(&a.customer_name).chars().count() >= 2 => {
+            score += 15;
+            reasons.push("이름 유사");
+        }
+        _ => {}
+    }
+
+    if let (Some(addr_a), Some(addr_b)) = (address_key(a), address_key(b)) {
+        if addr_a == addr_b {
+            score += 30;
+            reasons.push("주소 일치");
+        }
+    }
+    (score, reasons)
+}
+
+/// Finds likely duplicate customers. Only records sharing a mobile number, name or address
+/// are compared, so the cost stays close to linear in the number of customers.
+pub fn find_duplicate_pairs(
+    customers: &[DuplicateKey],
+) -> Vec<(usize, usize, i32, Vec<&'static str>)> {
+    let mut buckets: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, c) in customers.iter().enumerate() {
+        let digits = mobile_digits(&c.mobile_number);
+        if digits.len() >= 10 {
+            buckets.entry(format!("m:{}", digits)).or_default().push(i);
+        }
+        buckets
+            .entry(format!("n:{}", name_key(&c.customer_name)))
+            .or_default()
+            .push(i);
+        if let Some(addr) = address_key(c) {
+            buckets.entry(format!("a:{}", addr)).or_default().push(i);
+        }
+    }
+
+    let mut pairs = BTreeSet::new();
+    for members in buckets.values() {
+        // A bucket this large is a common name or a shared office address, not a duplicate
+        if members.len() > 50 {
+            continue;
+        }
+        for (x, &i) in members.iter().enumerate() {
+            for &j in &members[x + 1..] {
+                pairs.insert((i.min(j), i.max(j)));
+            }
+        }
+    }
+
+    let mut result: Vec<_> = pairs
+        .into_iter()
+        .filter_map(|(i, j)| {
+            let (score, reasons) = duplicate_score(&customers[i], &customers[j]);
+            (score >= DUPLICATE_MIN_SCORE).then_some((i, j, score, reasons))
+        })
+        .collect();
+    result.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+    result
+}
+
+pub async fn find_duplicate_customers_internal(
+    pool: &DbPool,
+    limit: usize,
+) -> MyceliumResult<Vec<DuplicateCandidate>> {
+    let customers: Vec<DuplicateKey> = sqlx::query_as(
+        "SELECT customer_id, customer_name, mobile_number, address_primary, address_detail
+         FROM customers WHERE COALESCE(status, '정상') != '말소' ORDER BY customer_id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let to_view = |c: &DuplicateKey| DuplicateCustomer {
+        customer_id: c.customer_id.clone(),
+        customer_name: c.customer_name.clone(),
+        mobile_number: c.mobile_number.clone(),
+        address_primary: c.address_primary.clone(),
+    };
+    Ok(find_duplicate_pairs(&customers)
+        .into_iter()
+        .take(limit)
+        .map(|(i, j, score, reasons)| DuplicateCandidate {
+            score,
+            reasons,
+            customers: [to_view(&customers[i]), to_view(&customers[j])],
+        })
+        .collect())
+}
+
+async fn write_merge_log(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    customer_id: &str,
+    field: &str,
+    old_value: &str,
+    new_value: &str,
+    username: &str,
+) -> MyceliumResult<()> {
+    sqlx::query(
+        "INSERT INTO customer_logs (customer_id, field_name, old_value, new_value, changed_by)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(customer_id)
+    .bind(field)
+    .bind(old_value)
+    .bind(new_value)
+    .bind(username)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Folds `source_id` into `target_id` in one transaction: all history is re-pointed, the ledger
+/// balance and missing profile fields carry over, and the source record is removed.
+/// Returns the merge id, which is also written to both customers' logs.
+pub async fn merge_customers(
+    pool: &DbPool,
+    username: &str,
+    source_id: &str,
+    target_id: &str,
+) -> MyceliumResult<i32> {
+    if source_id == target_id {
+        return Err(MyceliumError::Validation(
+            "같은 고객끼리는 병합할 수 없습니다.".to_string(),
+        ));
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+
+    let source: Option<(serde_json::Value, String, i32)> = sqlx::query_as(
+        "SELECT to_jsonb(c), c.customer_name, COALESCE(c.current_balance, 0)
+         FROM customers c WHERE customer_id = $1 FOR UPDATE",
+    )
+    .bind(source_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let target: Option<(String,)> =
+        sqlx::query_as("SELECT customer_name FROM customers WHERE customer_id = $1 FOR UPDATE")
+            .bind(target_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let ((snapshot, source_name, balance), (target_name,)) = match (source, target) {
+        (Some(s), Some(t)) => (s, t),
+        _ => {
+            return Err(MyceliumError::Validation(
+                "병합할 고객을 찾을 수 없습니다.".to_string(),
+            ))
+        }
+    };
+
+    // The survivor keeps its own default address
+    let cleared_defaults: Vec<(i32,)> = sqlx::query_as(
+        "UPDATE customer_addresses SET is_default = FALSE
+         WHERE customer_id = $1 AND is_default
+           AND EXISTS (SELECT 1 FROM customer_addresses WHERE customer_id = $2 AND is_default)
+         RETURNING address_id",
+    )
+    .bind(source_id)
+    .bind(target_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    // A survivor referred by the removed customer would otherwise refer itself
+    let cleared_referrer = sqlx::query(
+        "UPDATE customers SET referred_by = NULL WHERE customer_id = $1 AND referred_by = $2",
+    )
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    let mut moved = serde_json::Map::new();
+    for (table, column, key) in MERGE_TABLES.into_iter().chain([REFERRED_BY]) {
+        let filter = merge_filter(table);
+        let keys: Vec<(String,)> = sqlx::query_as(&format!(
+            "UPDATE {table} SET {column} = $1 WHERE {column} = $2{filter} RETURNING {key}"
+        ))
+        .bind(target_id)
+        .bind(source_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        moved.insert(
+            moved_key(table, column),
+            keys.into_iter().map(|(k,)| k).collect::<Vec<_>>().into(),
+        );
+    }
+    moved.insert("cleared_referrer".to_string(), cleared_referrer.into());
+    moved.insert(
+        "default_address_ids".to_string(),
+        cleared_defaults
+            .into_iter()
+            .map(|(id,)| id)
+            .collect::<Vec<_>>()
+            .into(),
+    );
+
+    let mut filled = Vec::new();
+    for field in FILLABLE_FIELDS {
+        let updated = sqlx::query(&format!(
+            "UPDATE customers t SET {field} = s.{field}
+             FROM customers s
+             WHERE t.customer_id = $1 AND s.customer_id = $2
+               AND NULLIF(t.{field}::text, '') IS NULL AND NULLIF(s.{field}::text, '') IS NOT NULL"
+        ))
+        .bind(target_id)
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if updated > 0 {
+            filled.push(field.to_string());
+        }
+    }
+
+    sqlx::query(
+        "UPDATE customers SET current_balance = COALESCE(current_balance, 0) + $1 WHERE customer_id = $2",
+    )
+    .bind(balance)
+    .bind(target_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM customers WHERE customer_id = $1")
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let (merge_id,): (i32,) = sqlx::query_as(
+        "INSERT INTO customer_merges (source_customer_id, target_customer_id, source_snapshot, moved_rows,
+            filled_fields, balance_moved, merged_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING merge_id",
+    )
+    .bind(source_id)
+    .bind(target_id)
+    .bind(&snapshot)
+    .bind(serde_json::Value::Object(moved))
+    .bind(&filled)
+    .bind(balance)
+    .bind(username)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    write_merge_log(
+        &mut tx,
+        target_id,
+        "merge",
+        &format!("{}({})", source_name, source_id),
+        &format!("병합 #{}", merge_id),
+        username,
+    )
+    .await?;
+    write_merge_log(
+        &mut tx,
+        source_id,
+        "merge",
+        source_id,
+        &format!("{}({}) 병합 #{}", target_name, target_id, merge_id),
+        username,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(merge_id)
+}
+
+/// Reverses a merge: the removed customer is restored from its snapshot and the rows moved by
+/// the merge go back to it. Rows added to the survivor after the merge stay where they are.
+pub async fn undo_customer_merge(
+    pool: &DbPool,
+    username: &str,
+    merge_id: i32,
+) -> MyceliumResult<()> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+
+    let merge: Option<(String, String, serde_json::Value, serde_json::Value, Vec<String>, i32)> =
+        sqlx::query_as(
+            "SELECT source_customer_id, target_customer_id, source_snapshot, moved_rows, filled_fields, balance_moved
+             FROM customer_merges WHERE merge_id = $1 AND undone_at IS NULL FOR UPDATE",
+        )
+        .bind(merge_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let (source_id, target_id, snapshot, moved, filled, balance) = merge.ok_or_else(|| {
+        MyceliumError::Validation("되돌릴 수 있는 병합 기록이 없습니다.".to_string())
+    })?;
+
+    let (exists,): (bool,) =
+        sqlx::query_as("SELECT EXISTS (SELECT 1 FROM customers WHERE customer_id = $1)")
+            .bind(&source_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    if exists {
+        return Err(MyceliumError::Validation(format!(
+            "고객 번호 {}가 이미 사용 중이라 병합을 되돌릴 수 없습니다.",
+            source_id
+        )));
+    }
+
+    sqlx::query("INSERT INTO customers SELECT * FROM jsonb_populate_record(NULL::customers, $1)")
+        .bind(&snapshot)
+        .execute(&mut *tx)
+        .await?;
+
+    for (table, column, key) in MERGE_TABLES.into_iter().chain([REFERRED_BY]) {
+        let keys: Vec<String> = moved
+            .get(moved_key(table, column))
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        if keys.is_empty() {
+            continue;
+        }
+        sqlx::query(&format!(
+            "UPDATE {table} SET {column} = $1 WHERE {column} = $2 AND ({key}) = ANY($3)"
+        ))
+        .bind(&source_id)
+        .bind(&target_id)
+        .bind(&keys)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if moved.get("cleared_referrer") == Some(&serde_json::Value::Bool(true)) {
+        sqlx::query(
+            "UPDATE customers SET referred_by = $1 WHERE customer_id = $2 AND referred_by IS NULL",
+        )
+        .bind(&source_id)
+        .bind(&target_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let default_ids: Vec<i32> = moved
+        .get("default_address_ids")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    sqlx::query(
+        "UPDATE customer_addresses SET is_default = TRUE WHERE customer_id = $1 AND address_id = ANY($2)",
+    )
+    .bind(&source_id)
+    .bind(&default_ids)
+    .execute(&mut *tx)
+    .await?;
+
+    // Copied fields are cleared again unless someone has edited them since
+    for field in &filled {
+        if !FILLABLE_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        sqlx::query(&format!(
+            "UPDATE customers SET {field} = NULL
+             WHERE customer_id = $1 AND {field}::text IS NOT DISTINCT FROM ($2::jsonb ->> '{field}')"
+        ))
+        .bind(&target_id)
+        .bind(&snapshot)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query(
+        "UPDATE customers SET current_balance = COALESCE(current_balance, 0) - $1 WHERE customer_id = $2",
+    )
+    .bind(balance)
+    .bind(&target_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE customer_merges SET undone_by = $1, undone_at = CURRENT_TIMESTAMP WHERE merge_id = $2",
+    )
+    .bind(username)
+    .bind(merge_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let note = format!("병합 #{} 취소", merge_id);
+    write_merge_log(
+        &mut tx,
+        &target_id,
+        "merge_undo",
+        &source_id,
+        &note,
+        username,
+    )
+    .await?;
+    write_merge_log(
+        &mut tx,
+        &source_id,
+        "merge_undo",
+        &target_id,
+        &note,
+        username,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct DuplicateQuery {
+    pub limit: Option<usize>,
+}
+
+pub async fn find_duplicate_customers_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<DuplicateQuery>,
+) -> MyceliumResult<Json<Vec<DuplicateCandidate>>> {
+    Ok(Json(
+        find_duplicate_customers_internal(&state.pool, query.limit.unwrap_or(200)).await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeCustomersInput {
+    #[serde(alias = "source_customer_id")]
+    pub source_customer_id: String,
+    #[serde(alias = "target_customer_id")]
+    pub target_customer_id: String,
+}
+
+pub async fn merge_customers_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<MergeCustomersInput>,
+) -> MyceliumResult<Json<i32>> {
+    Ok(Json(
+        merge_customers(
+            &state.pool,
+            claims.username.as_deref().unwrap_or("Admin"),
+            &input.source_customer_id,
+            &input.target_customer_id,
+        )
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoMergeInput {
+    #[serde(alias = "merge_id")]
+    pub merge_id: i32,
+}
+
+pub async fn undo_customer_merge_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<UndoMergeInput>,
+) -> MyceliumResult<Json<()>> {
+    undo_customer_merge(
+        &state.pool,
+        claims.username.as_deref().unwrap_or("Admin"),
+        input.merge_id,
+    )
+    .await?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+pub struct MergeHistoryQuery {
+    #[serde(alias = "customerId")]
+    pub customer_id: Option<String>,
+}
+
+pub async fn get_customer_merges_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<MergeHistoryQuery>,
+) -> MyceliumResult<Json<Vec<CustomerMerge>>> {
+    Ok(Json(
+        sqlx::query_as::<_, CustomerMerge>(
+            "SELECT merge_id, source_customer_id, target_customer_id, source_snapshot ->> 'customer_name' AS source_name,
+                    moved_rows, filled_fields, balance_moved, merged_by, merged_at, undone_by, undone_at
+             FROM customer_merges
+             WHERE ($1::text IS NULL OR source_customer_id = $1 OR target_customer_id = $1)
+             ORDER BY merged_at DESC LIMIT 200",
+        )
+        .bind(query.customer_id.filter(|c| !c.is_empty()))
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
@@ -38,6 +38,15 @@ pub async fn get_dashboard_schedule_stats(
     Ok(Json(count.0))
 }
 
+/// Open consultation tickets past their SLA due time
+pub async fn get_overdue_consultations(
+    State(state): State<AppState>,
+) -> MyceliumResult<Json<Vec<crate::commands::consultation::OverdueConsultation>>> {
+    Ok(Json(
+        crate::commands::consultation::find_overdue_consultations(&state.pool, None, 10).await?,
+    ))
+}
+
 pub async fn get_dashboard_stats(
     State(state): State<AppState>,
 ) -> MyceliumResult<Json<DashboardStats>> {
@@ -15,6 +15,7 @@ use serde::Deserialize;
 use std::sync::atomic::Ordering;
 
 pub mod pdf;
+pub mod settlement;
 
 #[derive(Debug, Deserialize)]
 pub struct FinanceReportQuery {
@@ -436,6 +437,8 @@ pub struct MonthlyPL {
     pub revenue: i64,
     pub cost: i64,
     pub profit: i64,
+    /// Mall commission and fees booked from settlement reports (already part of `cost`)
+    pub mall_fees: i64,
 }
 
 pub async fn get_monthly_pl_report(
@@ -454,6 +457,11 @@ pub async fn get_monthly_pl_report(
         SELECT TO_CHAR(expense_date, 'MM')::integer as month, SUM(amount)::bigint as amount
         FROM expenses WHERE EXTRACT(YEAR FROM expense_date) = $1 GROUP BY month
     "#;
+    let mall_fee_sql = r#"
+        SELECT TO_CHAR(expense_date, 'MM')::integer as month, SUM(amount)::bigint as amount
+        FROM expenses WHERE EXTRACT(YEAR FROM expense_date) = $1 AND settlement_batch_id IS NOT NULL
+        GROUP BY month
+    "#;
 
     let sales: Vec<(i32, i64)> = sqlx::query_as(sales_sql)
         .bind(year)
@@ -467,6 +475,10 @@ pub async fn get_monthly_pl_report(
         .bind(year)
         .fetch_all(&*state)
         .await?;
+    let mall_fees: Vec<(i32, i64)> = sqlx::query_as(mall_fee_sql)
+        .bind(year)
+        .fetch_all(&*state)
+        .await?;
 
     let mut report = Vec::new();
     for m in 1..=12 {
@@ -485,6 +497,11 @@ pub async fn get_monthly_pl_report(
             .find(|(month, _)| *month == m)
             .map(|(_, amt)| *amt)
             .unwrap_or(0);
+        let mall_fee_amt = mall_fees
+            .iter()
+            .find(|(month, _)| *month == m)
+            .map(|(_, amt)| *amt)
+            .unwrap_or(0);
 
         let cost = purchase_amt + expense_amt;
         let profit = revenue - cost;
@@ -494,6 +511,7 @@ pub async fn get_monthly_pl_report(
             revenue,
             cost,
             profit,
+            mall_fees: mall_fee_amt,
         });
     }
 
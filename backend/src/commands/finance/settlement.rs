@@ -0,0 +1,527 @@
+use crate::commands::sales::mall_import::mall_label;
+use crate::commands::sales::utils::parse_date_safe;
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+pub const COMMISSION_CATEGORY: &str = "판매수수료";
+pub const FEE_CATEGORY: &str = "결제/기타수수료";
+
+const ORDER_ID_HEADERS: [&str; 2] = ["주문번호", "주문ID"];
+const LINE_ID_HEADERS: [&str; 4] = ["상품주문번호", "옵션ID", "벤더아이템ID", "vendorItemId"];
+const DATE_HEADERS: [&str; 6] = [
+    "정산완료일",
+    "정산예정일",
+    "정산일",
+    "지급일",
+    "지급예정일",
+    "매출인식일",
+];
+const GROSS_HEADERS: [&str; 5] = ["정산기준금액", "결제금액", "판매액", "판매금액", "매출금액"];
+const COMMISSION_HEADERS: [&str; 3] = ["매출연동수수료", "판매수수료", "수수료"];
+const NET_HEADERS: [&str; 5] = [
+    "정산예정금액",
+    "정산금액",
+    "지급액",
+    "지급예정금액",
+    "정산대상액",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettlementLine {
+    pub mall_order_id: String,
+    pub mall_line_id: String,
+    pub settle_date: Option<NaiveDate>,
+    pub gross_amount: i32,
+    pub commission_amount: i32,
+    pub fee_amount: i32,
+    pub net_amount: i32,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SettlementBatch {
+    pub batch_id: i32,
+    pub mall_name: String,
+    pub file_name: Option<String>,
+    pub settle_date: NaiveDate,
+    pub line_count: i32,
+    pub gross_amount: i64,
+    pub commission_amount: i64,
+    pub fee_amount: i64,
+    pub net_amount: i64,
+    pub imported_by: Option<String>,
+    pub imported_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettlementImportResult {
+    pub batch_id: Option<i32>,
+    pub parsed: usize,
+    pub imported: usize,
+    pub duplicates: usize,
+    pub matched: usize,
+    pub mismatched: usize,
+    pub unmatched: usize,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct UnsettledOrder {
+    pub sales_id: String,
+    pub mall_name: String,
+    pub mall_order_id: String,
+    pub order_date: Option<NaiveDate>,
+    pub product_name: String,
+    pub total_amount: i32,
+    pub status: String,
+    pub days_since_order: Option<i32>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SettlementIssue {
+    pub settlement_id: i32,
+    pub mall_name: String,
+    pub mall_order_id: String,
+    pub mall_line_id: String,
+    pub settle_date: NaiveDate,
+    pub gross_amount: i32,
+    pub net_amount: i32,
+    pub sales_id: Option<String>,
+    pub sales_amount: Option<i64>,
+    pub match_status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettlementReconciliation {
+    pub unsettled: Vec<UnsettledOrder>,
+    pub issues: Vec<SettlementIssue>,
+}
+
+/// Splits one CSV record, honouring double quotes ("1,234" stays one field).
+pub fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// "1,234원", "-1,234" and "(1,234)" style amounts; blanks are zero.
+pub fn parse_amount(raw: &str) -> i32 {
+    let negative = raw.contains('-') || (raw.starts_with('(') && raw.ends_with(')'));
+    let digits: String = raw.chars().filter(|c| c.is_ascii_digit()).collect();
+    let value: i64 = digits.parse().unwrap_or(0);
+    let value = value.min(i32::MAX as i64) as i32;
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+fn normalize_header(h: &str) -> String {
+    h.trim_start_matches('\u{feff}')
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '"')
+        .collect()
+}
+
+/// Parses a Naver (정산내역 상세) or Coupang (매출내역) settlement CSV.
+///
+/// Columns are found by header name, so the column order of either export does not matter.
+/// Fees are stored with the sign of the sale whatever sign the mall uses: positive when they
+/// were deducted, negative on a refund row where the mall gives them back. Any "수수료" column
+/// other than the sales commission is counted as a payment/other fee.
+pub fn parse_settlement_csv(text: &str) -> MyceliumResult<Vec<SettlementLine>> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header = lines
+        .by_ref()
+        .map(|l| split_csv_line(l).iter().map(|h| normalize_header(h)).collect::<Vec<_>>())
+        .find(|cols| cols.iter().any(|c| ORDER_ID_HEADERS.contains(&c.as_str())))
+        .ok_or_else(|| {
+            MyceliumError::Validation(
+                "정산 파일에서 '주문번호' 열을 찾을 수 없습니다. 네이버/쿠팡 정산 CSV인지 확인해주세요."
+                    .to_string(),
+            )
+        })?;
+
+    let find = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|n| header.iter().position(|h| h == n))
+    };
+    let order_col = find(&ORDER_ID_HEADERS).unwrap();
+    let line_col = find(&LINE_ID_HEADERS);
+    let date_col = find(&DATE_HEADERS);
+    let gross_col = find(&GROSS_HEADERS).ok_or_else(|| {
+        MyceliumError::Validation("정산 파일에서 결제/판매 금액 열을 찾을 수 없습니다.".to_string())
+    })?;
+    let commission_col = find(&COMMISSION_HEADERS);
+    let net_col = find(&NET_HEADERS);
+    let fee_cols: Vec<usize> = header
+        .iter()
+        .enumerate()
+        .filter(|(i, h)| h.contains("수수료") && !h.contains("합계") && Some(*i) != commission_col)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut result = Vec::new();
+    for line in lines {
+        let cols = split_csv_line(line);
+        let get = |i: usize| cols.get(i).map(|s| s.as_str()).unwrap_or("");
+        let order_id = get(order_col).trim_start_matches('\'').to_string();
+        // Summary rows ("합계") and blank lines carry no order number
+        if order_id.is_empty() || !order_id.chars().any(|c| c.is_ascii_digit()) {
+            continue;
+        }
+
+        let gross = parse_amount(get(gross_col));
+        let deduction = |raw: &str| {
+            let amount = parse_amount(raw).abs();
+            if gross < 0 {
+                -amount
+            } else {
+                amount
+            }
+        };
+        let commission = commission_col.map(|i| deduction(get(i))).unwrap_or(0);
+        let fee: i32 = fee_cols.iter().map(|i| deduction(get(*i))).sum();
+        let net = net_col
+            .map(|i| parse_amount(get(i)))
+            .unwrap_or(gross - commission - fee);
+
+        result.push(SettlementLine {
+            mall_order_id: order_id,
+            mall_line_id: line_col
+                .map(|i| get(i).trim_start_matches('\'').to_string())
+                .unwrap_or_default(),
+            settle_date: date_col.and_then(|i| {
+                let d = get(i).replace(['.', '/'], "-");
+                parse_date_safe(d.get(..10).unwrap_or(&d))
+            }),
+            gross_amount: gross,
+            commission_amount: commission,
+            fee_amount: fee,
+            net_amount: net,
+        });
+    }
+    Ok(result)
+}
+
+/// Stores parsed settlement lines as one batch, matches them to imported mall orders and
+/// books the commission and fees as expenses on the settlement date.
+pub async fn import_settlement_lines(
+    pool: &DbPool,
+    username: &str,
+    mall_name: &str,
+    file_name: Option<String>,
+    lines: &[SettlementLine],
+) -> MyceliumResult<SettlementImportResult> {
+    let today = Local::now().date_naive();
+    let settle_date = lines
+        .iter()
+        .filter_map(|l| l.settle_date)
+        .max()
+        .unwrap_or(today);
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+
+    let (batch_id,): (i32,) = sqlx::query_as(
+        "INSERT INTO mall_settlement_batches (mall_name, file_name, settle_date, imported_by)
+         VALUES ($1, $2, $3, $4) RETURNING batch_id",
+    )
+    .bind(mall_name)
+    .bind(&file_name)
+    .bind(settle_date)
+    .bind(username)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let mut result = SettlementImportResult {
+        batch_id: Some(batch_id),
+        parsed: lines.len(),
+        imported: 0,
+        duplicates: 0,
+        matched: 0,
+        mismatched: 0,
+        unmatched: 0,
+    };
+    let (mut gross, mut commission, mut fee, mut net) = (0i64, 0i64, 0i64, 0i64);
+
+    for line in lines {
+        // A line id pins one sale; without it the whole mall order is compared
+        let matched: Vec<(String, i32)> = sqlx::query_as(
+            "SELECT sales_id, total_amount FROM sales
+             WHERE mall_name = $1 AND mall_order_id = $2 AND ($3 = '' OR mall_line_id = $3)
+               AND status != '취소'",
+        )
+        .bind(mall_name)
+        .bind(&line.mall_order_id)
+        .bind(&line.mall_line_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let sales_total: i64 = matched.iter().map(|(_, amt)| *amt as i64).sum();
+        let match_status = if matched.is_empty() {
+            "미매칭"
+        } else if sales_total == line.gross_amount as i64 {
+            "일치"
+        } else {
+            "금액불일치"
+        };
+
+        let inserted = sqlx::query(
+            "INSERT INTO mall_settlements (batch_id, mall_name, mall_order_id, mall_line_id, settle_date,
+                gross_amount, commission_amount, fee_amount, net_amount, sales_id, match_status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             ON CONFLICT (mall_name, mall_order_id, mall_line_id, settle_date, gross_amount) DO NOTHING",
+        )
+        .bind(batch_id)
+        .bind(mall_name)
+        .bind(&line.mall_order_id)
+        .bind(&line.mall_line_id)
+        .bind(line.settle_date.unwrap_or(settle_date))
+        .bind(line.gross_amount)
+        .bind(line.commission_amount)
+        .bind(line.fee_amount)
+        .bind(line.net_amount)
+        .bind(matched.first().map(|(id, _)| id.clone()))
+        .bind(match_status)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            result.duplicates += 1;
+            continue;
+        }
+        result.imported += 1;
+        match match_status {
+            "일치" => result.matched += 1,
+            "금액불일치" => result.mismatched += 1,
+            _ => result.unmatched += 1,
+        }
+        gross += line.gross_amount as i64;
+        commission += line.commission_amount as i64;
+        fee += line.fee_amount as i64;
+        net += line.net_amount as i64;
+    }
+
+    if result.imported == 0 {
+        // Nothing new: the same report was imported before
+        tx.rollback().await?;
+        result.batch_id = None;
+        return Ok(result);
+    }
+
+    sqlx::query(
+        "UPDATE mall_settlement_batches SET line_count = $1, gross_amount = $2, commission_amount = $3,
+            fee_amount = $4, net_amount = $5
+         WHERE batch_id = $6",
+    )
+    .bind(result.imported as i32)
+    .bind(gross)
+    .bind(commission)
+    .bind(fee)
+    .bind(net)
+    .bind(batch_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let memo = format!(
+        "[정산] {} {} ({}건)",
+        mall_label(mall_name),
+        settle_date.format("%Y-%m-%d"),
+        result.imported
+    );
+    for (category, amount) in [(COMMISSION_CATEGORY, commission), (FEE_CATEGORY, fee)] {
+        if amount == 0 {
+            continue;
+        }
+        sqlx::query(
+            "INSERT INTO expenses (expense_date, category, amount, payment_method, memo, settlement_batch_id)
+             VALUES ($1, $2, $3, '정산차감', $4, $5)",
+        )
+        .bind(settle_date)
+        .bind(category)
+        .bind(amount.min(i32::MAX as i64) as i32)
+        .bind(&memo)
+        .bind(batch_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(result)
+}
+
+#[derive(Deserialize)]
+pub struct SettlementImportPayload {
+    pub mall_name: String,
+    pub path: String,
+}
+
+/// Settlement exports are CP949 from Naver and UTF-8 (with BOM) from Coupang.
+pub async fn import_settlement_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<SettlementImportPayload>,
+) -> MyceliumResult<Json<SettlementImportResult>> {
+    let path = payload.path.clone();
+    let text = tokio::task::spawn_blocking(move || {
+        let bytes = std::fs::read(&path)?;
+        Ok::<_, MyceliumError>(match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => encoding_rs::EUC_KR.decode(e.as_bytes()).0.into_owned(),
+        })
+    })
+    .await
+    .map_err(|e| MyceliumError::Internal(e.to_string()))??;
+
+    let lines = parse_settlement_csv(&text)?;
+    let file_name = std::path::Path::new(&payload.path)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string());
+    Ok(Json(
+        import_settlement_lines(
+            &state.pool,
+            claims.username.as_deref().unwrap_or("Admin"),
+            &payload.mall_name,
+            file_name,
+            &lines,
+        )
+        .await?,
+    ))
+}
+
+pub async fn get_settlement_batches_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<SettlementBatch>>> {
+    Ok(Json(
+        sqlx::query_as::<_, SettlementBatch>(
+            "SELECT * FROM mall_settlement_batches ORDER BY settle_date DESC, batch_id DESC LIMIT 200",
+        )
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct SettlementBatchDeletePayload {
+    pub batch_id: i32,
+}
+
+/// Undoes an import: its settlement lines and the expenses it booked are removed with it.
+pub async fn delete_settlement_batch_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(payload): Json<SettlementBatchDeletePayload>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query("DELETE FROM mall_settlement_batches WHERE batch_id = $1")
+        .bind(payload.batch_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+pub struct ReconciliationQuery {
+    pub mall_name: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+pub async fn get_settlement_reconciliation_internal(
+    pool: &DbPool,
+    mall_name: Option<&str>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> MyceliumResult<SettlementReconciliation> {
+    let unsettled = sqlx::query_as::<_, UnsettledOrder>(
+        "SELECT s.sales_id, s.mall_name, s.mall_order_id, s.order_date, s.product_name, s.total_amount, s.status,
+                (CURRENT_DATE - s.order_date)::integer AS days_since_order
+         FROM sales s
+         WHERE s.mall_name IS NOT NULL AND s.mall_order_id IS NOT NULL AND s.status != '취소'
+           AND s.order_date BETWEEN $2 AND $3
+           AND ($1::text IS NULL OR s.mall_name = $1)
+           AND NOT EXISTS (
+               SELECT 1 FROM mall_settlements m
+               WHERE m.mall_name = s.mall_name AND m.mall_order_id = s.mall_order_id
+                 AND (m.mall_line_id = '' OR m.mall_line_id = s.mall_line_id))
+         ORDER BY s.order_date, s.sales_id",
+    )
+    .bind(mall_name)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+
+    let issues = sqlx::query_as::<_, SettlementIssue>(
+        "SELECT m.settlement_id, m.mall_name, m.mall_order_id, m.mall_line_id, m.settle_date,
+                m.gross_amount, m.net_amount, m.sales_id,
+                (SELECT SUM(s.total_amount)::bigint FROM sales s
+                 WHERE s.mall_name = m.mall_name AND s.mall_order_id = m.mall_order_id
+                   AND (m.mall_line_id = '' OR s.mall_line_id = m.mall_line_id) AND s.status != '취소') AS sales_amount,
+                m.match_status
+         FROM mall_settlements m
+         WHERE m.match_status != '일치'
+           AND m.settle_date BETWEEN $2 AND $3
+           AND ($1::text IS NULL OR m.mall_name = $1)
+         ORDER BY m.settle_date, m.settlement_id",
+    )
+    .bind(mall_name)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(SettlementReconciliation { unsettled, issues })
+}
+
+pub async fn get_settlement_reconciliation_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<ReconciliationQuery>,
+) -> MyceliumResult<Json<SettlementReconciliation>> {
+    let today = Local::now().date_naive();
+    let start = query
+        .start_date
+        .as_deref()
+        .and_then(parse_date_safe)
+        .unwrap_or(today - chrono::Duration::days(90));
+    let end = query
+        .end_date
+        .as_deref()
+        .and_then(parse_date_safe)
+        .unwrap_or(today);
+    Ok(Json(
+        get_settlement_reconciliation_internal(
+            &state.pool,
+            query.mall_name.as_deref().filter(|m| !m.is_empty()),
+            start,
+            end,
+        )
+        .await?,
+    ))
+}
@@ -23,6 +23,10 @@ pub struct PendingShipment {
     pub memo: Option<String>,
     pub courier_name: Option<String>,
     pub tracking_number: Option<String>,
+    #[sqlx(default)]
+    pub transit_days: Option<i32>,
+    #[sqlx(default)]
+    pub safe_ship_date: Option<NaiveDate>,
 }
 
 pub async fn get_shipments_by_status_internal(
@@ -84,6 +88,7 @@ pub async fn get_shipments_by_status_internal(
 
     query_string.push_str(" ORDER BY s.order_date DESC, s.sales_id DESC LIMIT 500");
 
+    let is_pending = status == "접수" || status == "입금완료";
     let mut query = sqlx::query_as::<_, PendingShipment>(&query_string).bind(status);
 
     if let Some(ref s) = search {
@@ -104,7 +109,11 @@ pub async fn get_shipments_by_status_internal(
         }
     }
 
-    Ok(query.fetch_all(pool).await?)
+    let mut shipments = query.fetch_all(pool).await?;
+    if is_pending {
+        crate::commands::shipping_calendar::annotate_safe_ship_dates(pool, &mut shipments).await?;
+    }
+    Ok(shipments)
 }
 
 pub async fn get_shipments_by_status(
@@ -118,11 +127,62 @@ pub async fn get_shipments_by_status(
 }
 
 pub async fn get_shipping_base_date(state: State<'_, DbPool>) -> MyceliumResult<Option<NaiveDate>> {
+    get_shipping_base_date_internal(state).await
+}
+
+pub async fn get_shipping_base_date_internal(pool: &DbPool) -> MyceliumResult<Option<NaiveDate>> {
     Ok(
         sqlx::query_scalar(
             "SELECT MIN(order_date) FROM sales WHERE status IN ('접수', '입금완료')",
         )
-        .fetch_one(&*state)
+        .fetch_one(pool)
         .await?,
     )
 }
+
+/// Orders still waiting to be packed for the given shipping day: everything pending
+/// from the shipping base date up to and including `ship_date`.
+pub async fn get_pending_shipments_for_date_internal(
+    pool: &DbPool,
+    ship_date: NaiveDate,
+    sales_ids: Option<Vec<String>>,
+) -> MyceliumResult<Vec<PendingShipment>> {
+    let base_date = get_shipping_base_date_internal(pool)
+        .await?
+        .unwrap_or(ship_date);
+
+    let rows = sqlx::query_as::<_, PendingShipment>(
+        "SELECT 
+            s.sales_id, 
+            s.order_date, 
+            COALESCE(c.customer_name, e.event_name) as customer_name, 
+            c.mobile_number as customer_mobile_number,
+            s.shipping_name, 
+            s.shipping_mobile_number, 
+            s.shipping_zip_code,
+            s.shipping_address_primary,
+            s.shipping_address_detail,
+            s.product_name, 
+            s.specification, 
+            s.unit_price, 
+            s.quantity, 
+            s.total_amount, 
+            s.memo,
+            s.courier_name,
+            s.tracking_number
+         FROM sales s
+         LEFT JOIN customers c ON s.customer_id = c.customer_id
+         LEFT JOIN event e ON s.customer_id = e.event_id
+         WHERE s.status IN ('접수', '입금완료')
+           AND s.order_date BETWEEN $1 AND $2
+           AND ($3::text[] IS NULL OR s.sales_id = ANY($3))
+         ORDER BY s.order_date ASC, s.shipping_name ASC, s.sales_id ASC",
+    )
+    .bind(base_date)
+    .bind(ship_date)
+    .bind(sales_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
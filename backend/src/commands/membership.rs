@@ -0,0 +1,394 @@
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{extract::State as AxumState, Extension, Json};
+use chrono::{Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+/// Template category used for the upgrade notice
+pub const UPGRADE_TEMPLATE_KEY: &str = "membership_upgrade";
+
+const DEFAULT_UPGRADE_MESSAGE: &str = "${name}님, ${level} 등급이 되신 것을 축하드립니다!";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MembershipRule {
+    pub level_name: String,
+    pub level_rank: i32,
+    pub min_amount: i64,
+    pub min_orders: i32,
+    pub require_both: bool,
+}
+
+impl MembershipRule {
+    /// Zero thresholds are not conditions; a rule without any is met by everyone.
+    pub fn is_met(&self, amount: i64, orders: i64) -> bool {
+        let mut conditions = Vec::new();
+        if self.min_amount > 0 {
+            conditions.push(amount >= self.min_amount);
+        }
+        if self.min_orders > 0 {
+            conditions.push(orders >= self.min_orders as i64);
+        }
+        if conditions.is_empty() {
+            return true;
+        }
+        if self.require_both {
+            conditions.iter().all(|c| *c)
+        } else {
+            conditions.iter().any(|c| *c)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MembershipSettings {
+    pub auto_enabled: bool,
+    pub lookback_months: i32,
+    pub allow_downgrade: bool,
+    pub notify_upgrade: bool,
+    #[serde(default)]
+    pub last_run_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MembershipConfig {
+    pub settings: MembershipSettings,
+    pub rules: Vec<MembershipRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MembershipChange {
+    pub customer_id: String,
+    pub customer_name: String,
+    pub mobile_number: String,
+    pub old_level: String,
+    pub new_level: String,
+    pub upgrade: bool,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MembershipEvaluation {
+    pub evaluated: usize,
+    pub upgraded: usize,
+    pub downgraded: usize,
+    pub notified: usize,
+    pub changes: Vec<MembershipChange>,
+}
+
+#[derive(sqlx::FromRow)]
+struct CustomerSpend {
+    customer_id: String,
+    customer_name: String,
+    mobile_number: String,
+    membership_level: Option<String>,
+    amount: i64,
+    orders: i64,
+}
+
+/// Highest-ranked level whose conditions are met.
+pub fn qualifying_level(
+    rules: &[MembershipRule],
+    amount: i64,
+    orders: i64,
+) -> Option<&MembershipRule> {
+    rules
+        .iter()
+        .filter(|r| r.is_met(amount, orders))
+        .max_by_key(|r| r.level_rank)
+}
+
+fn format_won(amount: i64) -> String {
+    let digits = amount.abs().to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    if amount < 0 {
+        out.insert(0, '-');
+    }
+    format!("{}원", out)
+}
+
+pub fn membership_reason(months: i32, amount: i64, orders: i64, upgrade: bool) -> String {
+    format!(
+        "최근 {}개월 구매 {} / {}회 기준 자동 {}",
+        months,
+        format_won(amount),
+        orders,
+        if upgrade { "승급" } else { "강등" }
+    )
+}
+
+/// Level changes the rules call for. Customers whose current level is not one of the rule
+/// levels (set by hand, e.g. a corporate level) are left alone.
+fn plan_changes(
+    settings: &MembershipSettings,
+    rules: &[MembershipRule],
+    customers: Vec<CustomerSpend>,
+) -> Vec<MembershipChange> {
+    let rank_of = |level: &str| {
+        rules
+            .iter()
+            .find(|r| r.level_name == level)
+            .map(|r| r.level_rank)
+    };
+    let lowest = rules.iter().min_by_key(|r| r.level_rank);
+
+    customers
+        .into_iter()
+        .filter_map(|c| {
+            let current = c
+                .membership_level
+                .clone()
+                .filter(|l| !l.trim().is_empty())
+                .or_else(|| lowest.map(|r| r.level_name.clone()))?;
+            let current_rank = rank_of(&current)?;
+            let target = qualifying_level(rules, c.amount, c.orders)?;
+            let upgrade = target.level_rank > current_rank;
+            if target.level_rank == current_rank || (!upgrade && !settings.allow_downgrade) {
+                return None;
+            }
+            Some(MembershipChange {
+                reason: membership_reason(settings.lookback_months, c.amount, c.orders, upgrade),
+                customer_id: c.customer_id,
+                customer_name: c.customer_name,
+                mobile_number: c.mobile_number,
+                old_level: current,
+                new_level: target.level_name.clone(),
+                upgrade,
+            })
+        })
+        .collect()
+}
+
+pub async fn load_membership_config(pool: &DbPool) -> MyceliumResult<MembershipConfig> {
+    let settings = sqlx::query_as::<_, MembershipSettings>(
+        "SELECT auto_enabled, lookback_months, allow_downgrade, notify_upgrade, last_run_at
+         FROM membership_settings WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or(MembershipSettings {
+        auto_enabled: false,
+        lookback_months: 12,
+        allow_downgrade: false,
+        notify_upgrade: false,
+        last_run_at: None,
+    });
+    let rules = sqlx::query_as::<_, MembershipRule>(
+        "SELECT level_name, level_rank, min_amount, min_orders, require_both
+         FROM membership_rules ORDER BY level_rank",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(MembershipConfig { settings, rules })
+}
+
+/// Evaluates every active customer against the rules. With `apply`, levels are changed,
+/// each change is written to `customer_logs` with its reason, and upgrade notices are sent
+/// when enabled; without it the planned changes are only returned (preview).
+pub async fn evaluate_memberships(
+    pool: &DbPool,
+    username: &str,
+    apply: bool,
+) -> MyceliumResult<MembershipEvaluation> {
+    let MembershipConfig { settings, rules } = load_membership_config(pool).await?;
+    if rules.is_empty() {
+        return Ok(MembershipEvaluation::default());
+    }
+
+    let customers: Vec<CustomerSpend> = sqlx::query_as(
+        "SELECT c.customer_id, c.customer_name, c.mobile_number, c.membership_level,
+                COALESCE(SUM(s.total_amount), 0)::bigint AS amount, COUNT(s.sales_id) AS orders
+         FROM customers c
+         LEFT JOIN sales s ON s.customer_id = c.customer_id
+              AND s.status NOT IN ('취소', '반품', '반품완료')
+              AND s.order_date >= CURRENT_DATE - make_interval(months => $1)
+         WHERE COALESCE(c.status, '정상') = '정상'
+         GROUP BY c.customer_id
+         ORDER BY c.customer_id",
+    )
+    .bind(settings.lookback_months)
+    .fetch_all(pool)
+    .await?;
+
+    let evaluated = customers.len();
+    let changes = plan_changes(&settings, &rules, customers);
+    let mut result = MembershipEvaluation {
+        evaluated,
+        upgraded: changes.iter().filter(|c| c.upgrade).count(),
+        downgraded: changes.iter().filter(|c| !c.upgrade).count(),
+        notified: 0,
+        changes,
+    };
+    if !apply {
+        return Ok(result);
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    for change in &result.changes {
+        sqlx::query("UPDATE customers SET membership_level = $1 WHERE customer_id = $2")
+            .bind(&change.new_level)
+            .bind(&change.customer_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO customer_logs (customer_id, field_name, old_value, new_value, changed_by, reason)
+             VALUES ($1, 'membership_level', $2, $3, $4, $5)",
+        )
+        .bind(&change.customer_id)
+        .bind(&change.old_level)
+        .bind(&change.new_level)
+        .bind(username)
+        .bind(&change.reason)
+        .execute(&mut *tx)
+        .await?;
+    }
+    sqlx::query("UPDATE membership_settings SET last_run_at = CURRENT_TIMESTAMP WHERE id = 1")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    if settings.notify_upgrade {
+        let template = crate::commands::message_template::load_template(pool, UPGRADE_TEMPLATE_KEY)
+            .await?
+            .unwrap_or_else(|| DEFAULT_UPGRADE_MESSAGE.to_string());
+        for change in result.changes.iter().filter(|c| c.upgrade) {
+            let Ok(mut context) = crate::commands::message_template::build_context(
+                pool,
+                Some(&change.customer_id),
+                None,
+            )
+            .await
+            else {
+                continue;
+            };
+            context.insert(
+                "level",
+                crate::commands::message_template::TemplateValue::Text(change.new_level.clone()),
+            );
+            let content = crate::commands::message_template::render(&template, &context).text;
+            if crate::commands::crm::send_sms(
+                pool,
+                "SMS".to_string(),
+                vec![change.mobile_number.clone()],
+                content,
+                Some(UPGRADE_TEMPLATE_KEY.to_string()),
+                crate::commands::consent::MessagePurpose::Transactional,
+            )
+            .await
+            .is_ok()
+            {
+                result.notified += 1;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Called by the background scheduler; runs once a day after 3 a.m. when enabled.
+pub async fn run_membership_evaluation_if_due(pool: &DbPool) -> MyceliumResult<bool> {
+    let now = Local::now().naive_local();
+    if now.time() < chrono::NaiveTime::from_hms_opt(3, 0, 0).unwrap_or_default() {
+        return Ok(false);
+    }
+    let settings = load_membership_config(pool).await?.settings;
+    let ran_today = settings
+        .last_run_at
+        .map(|t| t.date() == now.date())
+        .unwrap_or(false);
+    if !settings.auto_enabled || ran_today {
+        return Ok(false);
+    }
+    evaluate_memberships(pool, "System", true).await?;
+    Ok(true)
+}
+
+pub async fn get_membership_config_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<MembershipConfig>> {
+    Ok(Json(load_membership_config(&state.pool).await?))
+}
+
+pub async fn save_membership_config_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(payload): Json<MembershipConfig>,
+) -> MyceliumResult<Json<()>> {
+    if payload.settings.lookback_months <= 0 {
+        return Err(MyceliumError::Validation(
+            "집계 기간은 1개월 이상이어야 합니다.".to_string(),
+        ));
+    }
+    for rule in &payload.rules {
+        if rule.level_name.trim().is_empty() || rule.min_amount < 0 || rule.min_orders < 0 {
+            return Err(MyceliumError::Validation(
+                "등급 이름과 0 이상의 기준값을 입력해주세요.".to_string(),
+            ));
+        }
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    let s = &payload.settings;
+    sqlx::query(
+        "UPDATE membership_settings SET auto_enabled = $1, lookback_months = $2, allow_downgrade = $3,
+            notify_upgrade = $4, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+    )
+    .bind(s.auto_enabled)
+    .bind(s.lookback_months)
+    .bind(s.allow_downgrade)
+    .bind(s.notify_upgrade)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM membership_rules")
+        .execute(&mut *tx)
+        .await?;
+    for rule in &payload.rules {
+        sqlx::query(
+            "INSERT INTO membership_rules (level_name, level_rank, min_amount, min_orders, require_both)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(rule.level_name.trim())
+        .bind(rule.level_rank)
+        .bind(rule.min_amount)
+        .bind(rule.min_orders)
+        .bind(rule.require_both)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(Json(()))
+}
+
+pub async fn preview_membership_evaluation_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<MembershipEvaluation>> {
+    Ok(Json(
+        evaluate_memberships(&state.pool, "Admin", false).await?,
+    ))
+}
+
+pub async fn run_membership_evaluation_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> MyceliumResult<Json<MembershipEvaluation>> {
+    Ok(Json(
+        evaluate_memberships(
+            &state.pool,
+            claims.username.as_deref().unwrap_or("Admin"),
+            true,
+        )
+        .await?,
+    ))
+}
@@ -0,0 +1,630 @@
+use crate::commands::config::{
+    default_message_templates, load_integration_settings, load_message_templates_from_file,
+    MessageTemplates, SaveTemplatesPayload,
+};
+use crate::commands::consent::MessagePurpose;
+use crate::commands::crm::{send_message_with, MessageChannels, MessageOptions};
+use crate::commands::messaging::{message_bytes, select_message_kind};
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{extract::State as AxumState, Extension, Json};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableKind {
+    Text,
+    /// Won, written as "35,000원"
+    Money,
+    Date,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateVariable {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: VariableKind,
+    pub sample: &'static str,
+}
+
+const fn var(
+    key: &'static str,
+    label: &'static str,
+    kind: VariableKind,
+    sample: &'static str,
+) -> TemplateVariable {
+    TemplateVariable {
+        key,
+        label,
+        kind,
+        sample,
+    }
+}
+
+pub const VARIABLES: &[TemplateVariable] = &[
+    var("name", "고객명", VariableKind::Text, "홍길동"),
+    var(
+        "items",
+        "주문 상품",
+        VariableKind::Text,
+        "표고버섯 1kg x2, 건표고 x1",
+    ),
+    var(
+        "tracking_number",
+        "운송장 번호",
+        VariableKind::Text,
+        "612345678901",
+    ),
+    var("courier", "택배사", VariableKind::Text, "CJ대한통운"),
+    var("amount_due", "입금하실 금액", VariableKind::Money, "35000"),
+    var(
+        "bank_account",
+        "입금 계좌",
+        VariableKind::Text,
+        "농협 301-1234-5678-91 (예금주)",
+    ),
+    var("level", "회원 등급", VariableKind::Text, "VIP"),
+    var("type", "기념일", VariableKind::Text, "생일"),
+    var("date", "날짜", VariableKind::Date, "2026-11-01"),
+    var(
+        "product",
+        "최근 구매 상품",
+        VariableKind::Text,
+        "표고버섯 1kg",
+    ),
+    var(
+        "recommend",
+        "추천 상품",
+        VariableKind::Text,
+        "느타리버섯 500g",
+    ),
+];
+
+/// The variables a template of the category may use. Categories added by users only know
+/// the customer's name.
+pub fn category_variables(category: &str) -> &'static [&'static str] {
+    match category {
+        "repurchase" => &["name", "product", "recommend", "date"],
+        "shipping_receipt" => &["name", "items", "amount_due", "bank_account"],
+        "shipping_paid" => &["name", "items"],
+        "shipping_done" => &["name", "items", "courier", "tracking_number"],
+        "membership_upgrade" => &["name", "level"],
+        "anniversary" => &["name", "type", "date"],
+        "privacy_notice" => &["name", "date"],
+        _ => &["name"],
+    }
+}
+
+fn variable(key: &str) -> Option<&'static TemplateVariable> {
+    VARIABLES.iter().find(|v| v.key == key)
+}
+
+/// `${key}` placeholders in order of appearance; an unclosed `${` is an error.
+pub fn placeholders(content: &str) -> MyceliumResult<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let end = after.find('}').ok_or_else(|| {
+            MyceliumError::Validation(format!(
+                "닫히지 않은 변수가 있습니다: {}",
+                &rest[start..].chars().take(20).collect::<String>()
+            ))
+        })?;
+        keys.push(after[..end].trim().to_string());
+        rest = &after[end + 1..];
+    }
+    Ok(keys)
+}
+
+/// Placeholders the category does not define, without repeats
+pub fn unknown_variables(category: &str, content: &str) -> MyceliumResult<Vec<String>> {
+    let allowed = category_variables(category);
+    let mut unknown: Vec<String> = Vec::new();
+    for key in placeholders(content)? {
+        if !allowed.contains(&key.as_str()) && !unknown.contains(&key) {
+            unknown.push(key);
+        }
+    }
+    Ok(unknown)
+}
+
+/// Every template must only use its category's variables.
+pub fn validate_templates(templates: &MessageTemplates) -> MyceliumResult<()> {
+    let mut problems = Vec::new();
+    let mut categories: Vec<&String> = templates.keys().collect();
+    categories.sort();
+    for category in categories {
+        if category.trim().is_empty() || category.len() > 50 {
+            return Err(MyceliumError::Validation(
+                "템플릿 분류 이름이 올바르지 않습니다.".to_string(),
+            ));
+        }
+        for content in &templates[category] {
+            let unknown = unknown_variables(category, content)
+                .map_err(|e| MyceliumError::Validation(format!("[{}] {}", category, e)))?;
+            if !unknown.is_empty() {
+                problems.push(format!(
+                    "[{}] {}",
+                    category,
+                    unknown
+                        .iter()
+                        .map(|k| format!("${{{}}}", k))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+    }
+    if !problems.is_empty() {
+        return Err(MyceliumError::Validation(format!(
+            "사용할 수 없는 변수가 있습니다. {}",
+            problems.join(" / ")
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateValue {
+    Text(String),
+    Money(i64),
+    Date(NaiveDate),
+}
+
+impl TemplateValue {
+    pub fn display(&self) -> String {
+        match self {
+            TemplateValue::Text(s) => s.clone(),
+            TemplateValue::Money(won) => format!("{}원", group_thousands(*won)),
+            TemplateValue::Date(d) => d.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    fn sample(variable: &TemplateVariable) -> Self {
+        match variable.kind {
+            VariableKind::Text => TemplateValue::Text(variable.sample.to_string()),
+            VariableKind::Money => TemplateValue::Money(variable.sample.parse().unwrap_or(0)),
+            VariableKind::Date => TemplateValue::Date(
+                NaiveDate::parse_from_str(variable.sample, "%Y-%m-%d").unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+fn group_thousands(value: i64) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    if value < 0 {
+        format!("-{}", grouped)
+    } else {
+        grouped
+    }
+}
+
+pub type TemplateContext = HashMap<&'static str, TemplateValue>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedMessage {
+    pub text: String,
+    /// Placeholders with no value; they are left out of the text
+    pub missing: Vec<String>,
+}
+
+pub fn render(content: &str, context: &TemplateContext) -> RenderedMessage {
+    let mut text = String::with_capacity(content.len());
+    let mut missing: Vec<String> = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start + 2..].find('}') else {
+            break;
+        };
+        text.push_str(&rest[..start]);
+        let key = rest[start + 2..start + 2 + end].trim();
+        match context.get(key) {
+            Some(value) => text.push_str(&value.display()),
+            None if !missing.iter().any(|m| m == key) => missing.push(key.to_string()),
+            None => {}
+        }
+        rest = &rest[start + 2 + end + 1..];
+    }
+    text.push_str(rest);
+    RenderedMessage { text, missing }
+}
+
+/// "상품 x수량" per line; past three lines the rest are counted
+pub fn describe_items(lines: &[(String, Option<String>, i32)]) -> String {
+    let describe = |(name, spec, qty): &(String, Option<String>, i32)| match spec
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+    {
+        Some(spec) => format!("{} {} x{}", name, spec.trim(), qty),
+        None => format!("{} x{}", name, qty),
+    };
+    if lines.len() <= 3 {
+        lines.iter().map(describe).collect::<Vec<_>>().join(", ")
+    } else {
+        format!(
+            "{} 외 {}건",
+            lines[..2]
+                .iter()
+                .map(describe)
+                .collect::<Vec<_>>()
+                .join(", "),
+            lines.len() - 2
+        )
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct OrderHead {
+    customer_id: Option<String>,
+    name: Option<String>,
+    order_date: Option<NaiveDate>,
+    courier_name: Option<String>,
+    tracking_number: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct OrderLine {
+    product_name: String,
+    specification: Option<String>,
+    quantity: i32,
+    total_amount: i32,
+    paid_amount: Option<i32>,
+    points_used: i32,
+}
+
+/// Values for a customer and/or an order. The order covers every line the customer placed
+/// that day; the amount due is what is left unpaid on them.
+pub async fn build_context(
+    pool: &DbPool,
+    customer_id: Option<&str>,
+    sales_id: Option<&str>,
+) -> MyceliumResult<TemplateContext> {
+    let mut context = TemplateContext::new();
+    if let Some(id) = customer_id {
+        let name: Option<String> =
+            sqlx::query_scalar("SELECT customer_name FROM customers WHERE customer_id = $1")
+                .bind(id)
+                .fetch_optional(pool)
+                .await?;
+        let name =
+            name.ok_or_else(|| MyceliumError::Validation("고객을 찾을 수 없습니다.".to_string()))?;
+        context.insert("name", TemplateValue::Text(name));
+    }
+    if let Some(id) = sales_id {
+        let head = sqlx::query_as::<_, OrderHead>(
+            "SELECT s.customer_id, COALESCE(c.customer_name, s.shipping_name) AS name, s.order_date,
+                    s.courier_name, s.tracking_number
+             FROM sales s LEFT JOIN customers c ON c.customer_id = s.customer_id
+             WHERE s.sales_id = $1",
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| MyceliumError::Validation("주문을 찾을 수 없습니다.".to_string()))?;
+        let lines = sqlx::query_as::<_, OrderLine>(
+            "SELECT product_name, specification, quantity, total_amount, paid_amount,
+                    COALESCE(points_used, 0) AS points_used
+             FROM sales
+             WHERE sales_id = $1
+                OR (customer_id = $2 AND order_date = $3 AND status != '취소')
+             ORDER BY sales_id",
+        )
+        .bind(id)
+        .bind(&head.customer_id)
+        .bind(head.order_date)
+        .fetch_all(pool)
+        .await?;
+
+        if let Some(name) = head.name {
+            context.entry("name").or_insert(TemplateValue::Text(name));
+        }
+        let items: Vec<(String, Option<String>, i32)> = lines
+            .iter()
+            .map(|l| (l.product_name.clone(), l.specification.clone(), l.quantity))
+            .collect();
+        context.insert("items", TemplateValue::Text(describe_items(&items)));
+        let due: i64 = lines
+            .iter()
+            .map(|l| {
+                l.total_amount as i64 - l.paid_amount.unwrap_or(0) as i64 - l.points_used as i64
+            })
+            .sum();
+        context.insert("amount_due", TemplateValue::Money(due.max(0)));
+        for (key, value) in [
+            ("courier", head.courier_name),
+            ("tracking_number", head.tracking_number),
+        ] {
+            if let Some(v) = value.filter(|v| !v.trim().is_empty()) {
+                context.insert(key, TemplateValue::Text(v));
+            }
+        }
+    }
+    let account: Option<String> =
+        sqlx::query_scalar("SELECT bank_account FROM company_info ORDER BY id LIMIT 1")
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+    if let Some(account) = account.filter(|a| !a.trim().is_empty()) {
+        context.insert("bank_account", TemplateValue::Text(account));
+    }
+    Ok(context)
+}
+
+/// Fills the table the first time it is used: from the old `templates.json` when there is
+/// one, else with the defaults.
+async fn ensure_seeded(pool: &DbPool) -> MyceliumResult<()> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM message_templates)")
+        .fetch_one(pool)
+        .await?;
+    if exists {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    sqlx::query("LOCK TABLE message_templates IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await?;
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM message_templates)")
+        .fetch_one(&mut *tx)
+        .await?;
+    if !exists {
+        let templates =
+            load_message_templates_from_file().unwrap_or_else(|_| default_message_templates());
+        insert_templates(&mut tx, &templates, "system").await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn insert_templates(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    templates: &MessageTemplates,
+    username: &str,
+) -> MyceliumResult<()> {
+    for (category, contents) in templates {
+        for (i, content) in contents.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO message_templates (category, sort_order, content, updated_by)
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(category.trim())
+            .bind(i as i32)
+            .bind(content)
+            .bind(username)
+            .execute(&mut **tx)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn load_templates(pool: &DbPool) -> MyceliumResult<MessageTemplates> {
+    ensure_seeded(pool).await?;
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT category, content FROM message_templates ORDER BY category, sort_order, template_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut templates = MessageTemplates::new();
+    for (category, content) in rows {
+        templates.entry(category).or_default().push(content);
+    }
+    Ok(templates)
+}
+
+/// The first template of the category
+pub async fn load_template(pool: &DbPool, category: &str) -> MyceliumResult<Option<String>> {
+    ensure_seeded(pool).await?;
+    Ok(sqlx::query_scalar(
+        "SELECT content FROM message_templates WHERE category = $1
+         ORDER BY sort_order, template_id LIMIT 1",
+    )
+    .bind(category)
+    .fetch_optional(pool)
+    .await?)
+}
+
+/// The template category customers are told about when their order moves to the status
+pub fn order_message_category(status: &str) -> Option<&'static str> {
+    match status {
+        "입금대기" => Some("shipping_receipt"),
+        "입금완료" => Some("shipping_paid"),
+        "배송중" => Some("shipping_done"),
+        _ => None,
+    }
+}
+
+/// Sends the category's template, rendered for the order, to the number the order ships to
+/// (else the customer's). Returns false when there is no template or no number.
+pub async fn send_order_message(
+    pool: &DbPool,
+    category: &str,
+    sales_id: &str,
+) -> MyceliumResult<bool> {
+    let channels = MessageChannels::from_settings(load_integration_settings()?)?;
+    send_order_message_with(pool, &channels, category, sales_id).await
+}
+
+pub async fn send_order_message_with(
+    pool: &DbPool,
+    channels: &MessageChannels,
+    category: &str,
+    sales_id: &str,
+) -> MyceliumResult<bool> {
+    let Some(template) = load_template(pool, category)
+        .await?
+        .filter(|t| !t.trim().is_empty())
+    else {
+        return Ok(false);
+    };
+    let (customer_id, mobile): (Option<String>, Option<String>) = sqlx::query_as(
+        "SELECT c.customer_id,
+                COALESCE(NULLIF(TRIM(s.shipping_mobile_number), ''), c.mobile_number)
+         FROM sales s LEFT JOIN customers c ON c.customer_id = s.customer_id
+         WHERE s.sales_id = $1",
+    )
+    .bind(sales_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| MyceliumError::Validation("주문을 찾을 수 없습니다.".to_string()))?;
+    let Some(mobile) = mobile.filter(|m| !m.trim().is_empty()) else {
+        return Ok(false);
+    };
+    let context = build_context(pool, customer_id.as_deref(), Some(sales_id)).await?;
+    send_message_with(
+        pool,
+        channels,
+        "SMS".to_string(),
+        vec![mobile],
+        render(&template, &context).text,
+        MessagePurpose::Transactional,
+        MessageOptions {
+            template_code: Some(category.to_string()),
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok(true)
+}
+
+/// Replaces the whole set, as the settings screen edits it.
+pub async fn save_templates(
+    pool: &DbPool,
+    templates: &MessageTemplates,
+    username: &str,
+) -> MyceliumResult<()> {
+    validate_templates(templates)?;
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    sqlx::query("DELETE FROM message_templates")
+        .execute(&mut *tx)
+        .await?;
+    insert_templates(&mut tx, templates, username).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn get_message_templates_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<MessageTemplates>> {
+    Ok(Json(load_templates(&state.pool).await?))
+}
+
+pub async fn save_message_templates_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<SaveTemplatesPayload>,
+) -> MyceliumResult<Json<()>> {
+    let username = claims.username.as_deref().unwrap_or("Admin");
+    save_templates(&state.pool, &payload.templates, username).await?;
+    Ok(Json(()))
+}
+
+pub async fn reset_message_templates_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> MyceliumResult<Json<MessageTemplates>> {
+    let username = claims.username.as_deref().unwrap_or("Admin");
+    let templates = default_message_templates();
+    save_templates(&state.pool, &templates, username).await?;
+    Ok(Json(templates))
+}
+
+/// Variables of every built-in category, for the editor
+pub async fn get_template_variables_axum(
+) -> MyceliumResult<Json<BTreeMap<String, Vec<TemplateVariable>>>> {
+    let mut categories: Vec<String> = default_message_templates().into_keys().collect();
+    categories.push("custom".to_string());
+    Ok(Json(
+        categories
+            .into_iter()
+            .map(|category| {
+                let variables = category_variables(&category)
+                    .iter()
+                    .filter_map(|k| variable(k).cloned())
+                    .collect();
+                (category, variables)
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePreviewInput {
+    pub category: String,
+    /// Text being edited; the saved template of the category when absent
+    pub content: Option<String>,
+    #[serde(alias = "customer_id")]
+    pub customer_id: Option<String>,
+    #[serde(alias = "sales_id")]
+    pub sales_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplatePreview {
+    pub content: String,
+    pub text: String,
+    pub missing: Vec<String>,
+    pub unknown: Vec<String>,
+    pub bytes: usize,
+    pub message_type: Option<&'static str>,
+}
+
+/// Renders a template for a real customer and/or order. Without either the sample values
+/// are used.
+pub async fn preview_template(
+    pool: &DbPool,
+    input: TemplatePreviewInput,
+) -> MyceliumResult<TemplatePreview> {
+    let content = match input.content {
+        Some(content) => content,
+        None => load_template(pool, &input.category).await?.ok_or_else(|| {
+            MyceliumError::Validation("해당 분류의 템플릿이 없습니다.".to_string())
+        })?,
+    };
+    let unknown = unknown_variables(&input.category, &content)?;
+    let customer_id = input.customer_id.as_deref().filter(|s| !s.is_empty());
+    let sales_id = input.sales_id.as_deref().filter(|s| !s.is_empty());
+    let context = if customer_id.is_none() && sales_id.is_none() {
+        VARIABLES
+            .iter()
+            .map(|v| (v.key, TemplateValue::sample(v)))
+            .collect()
+    } else {
+        build_context(pool, customer_id, sales_id).await?
+    };
+    let rendered = render(&content, &context);
+    Ok(TemplatePreview {
+        bytes: message_bytes(&rendered.text),
+        message_type: select_message_kind(&rendered.text, false)
+            .ok()
+            .map(|k| k.as_str()),
+        content,
+        text: rendered.text,
+        missing: rendered.missing,
+        unknown,
+    })
+}
+
+pub async fn preview_template_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<TemplatePreviewInput>,
+) -> MyceliumResult<Json<TemplatePreview>> {
+    Ok(Json(preview_template(&state.pool, input).await?))
+}
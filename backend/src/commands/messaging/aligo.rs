@@ -0,0 +1,106 @@
+use super::{DeliveryResult, MessageKind, MessageProvider, OutgoingMessage};
+use crate::error::{MyceliumError, MyceliumResult};
+use reqwest::multipart::{Form, Part};
+use serde_json::Value;
+
+pub const ALIGO_API_BASE: &str = "https://apis.aligo.in";
+
+/// Client for the Aligo SMS API. Authenticates with the API key plus the account id and
+/// takes one multipart form per message.
+pub struct AligoProvider {
+    base_url: String,
+    api_key: String,
+    user_id: String,
+    http: reqwest::Client,
+}
+
+impl AligoProvider {
+    pub fn new(api_key: &str, user_id: &str) -> Self {
+        Self {
+            base_url: ALIGO_API_BASE.to_string(),
+            api_key: api_key.to_string(),
+            user_id: user_id.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Points the client at another host, e.g. a local mock server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+/// Aligo answers `result_code` 1 for an accepted request and a negative code otherwise. It
+/// does not break the result down by recipient, so all of them share the outcome.
+pub fn parse_send_response(
+    body: &Value,
+    recipients: &[String],
+) -> MyceliumResult<Vec<DeliveryResult>> {
+    let code = match &body["result_code"] {
+        Value::Number(n) => n.as_i64().unwrap_or(-1),
+        Value::String(s) => s.trim().parse().unwrap_or(-1),
+        _ => -1,
+    };
+    if code != 1 {
+        return Err(MyceliumError::Internal(format!(
+            "알리고 API 오류: {}",
+            body["message"].as_str().unwrap_or("알 수 없는 오류")
+        )));
+    }
+    let msg_id = match &body["msg_id"] {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        _ => None,
+    };
+    Ok(recipients
+        .iter()
+        .map(|r| DeliveryResult::accepted(r, msg_id.clone()))
+        .collect())
+}
+
+impl MessageProvider for AligoProvider {
+    fn name(&self) -> &'static str {
+        "aligo"
+    }
+
+    /// `receiver` takes up to 1,000 numbers
+    fn max_recipients(&self) -> usize {
+        1_000
+    }
+
+    async fn send(&self, message: &OutgoingMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        let mut form = Form::new()
+            .text("key", self.api_key.clone())
+            .text("user_id", self.user_id.clone())
+            .text("sender", message.sender.clone())
+            .text("receiver", message.recipients.join(","))
+            .text("msg", message.text.clone())
+            .text("msg_type", message.kind.as_str());
+        if message.kind != MessageKind::Sms {
+            form = form.text("title", message.subject.clone().unwrap_or_default());
+        }
+        if let Some(image) = &message.image {
+            let part = Part::bytes(image.data.clone())
+                .file_name(image.file_name.clone())
+                .mime_str("image/jpeg")?;
+            form = form.part("image1", part);
+        }
+
+        let resp = self
+            .http
+            .post(format!("{}/send/", self.base_url))
+            .multipart(form)
+            .send()
+            .await?;
+        let status = resp.status();
+        let body: Value = resp.json().await?;
+        if !status.is_success() {
+            return Err(MyceliumError::Internal(format!(
+                "알리고 API 오류: HTTP {}",
+                status
+            )));
+        }
+        parse_send_response(&body, &message.recipients)
+    }
+}
@@ -0,0 +1,445 @@
+use super::solapi::SolapiProvider;
+use super::{align_results, digits, log_deliveries, DeliveryResult, LogBatch, SendSummary};
+use crate::commands::config::{KakaoCosts, KakaoSettings};
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{extract::State as AxumState, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::atomic::Ordering;
+
+pub const NHN_BIZMESSAGE_API_BASE: &str = "https://api-alimtalk.cloud.toast.com";
+
+/// AlimTalk carries transactional messages on a template Kakao approved; FriendTalk is free
+/// text (advertising allowed) that only reaches users who added the channel as a friend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum KakaoKind {
+    AlimTalk,
+    FriendTalk,
+}
+
+impl KakaoKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KakaoKind::AlimTalk => "ALIMTALK",
+            KakaoKind::FriendTalk => "FRIENDTALK",
+        }
+    }
+
+    pub fn unit_cost(&self, costs: &KakaoCosts) -> i32 {
+        match self {
+            KakaoKind::AlimTalk => costs.alimtalk,
+            KakaoKind::FriendTalk => costs.friendtalk,
+        }
+    }
+}
+
+/// One KakaoTalk message to many recipients. Numbers are digits only; for AlimTalk the
+/// text must be the approved template with its variables filled in.
+#[derive(Debug, Clone)]
+pub struct KakaoMessage {
+    pub kind: KakaoKind,
+    pub sender_key: String,
+    pub template_code: Option<String>,
+    pub recipients: Vec<String>,
+    pub text: String,
+    pub is_ad: bool,
+}
+
+/// A Kakao business message gateway. Results are per recipient, as with SMS.
+pub trait KakaoProvider {
+    fn name(&self) -> &'static str;
+
+    fn send_kakao(
+        &self,
+        message: &KakaoMessage,
+    ) -> impl Future<Output = MyceliumResult<Vec<DeliveryResult>>> + Send;
+}
+
+/// Client for the NHN Cloud KakaoTalk Bizmessage API (v2.3).
+pub struct NhnBizMessageProvider {
+    base_url: String,
+    app_key: String,
+    secret_key: String,
+    http: reqwest::Client,
+}
+
+impl NhnBizMessageProvider {
+    pub fn new(app_key: &str, secret_key: &str) -> Self {
+        Self {
+            base_url: NHN_BIZMESSAGE_API_BASE.to_string(),
+            app_key: app_key.to_string(),
+            secret_key: secret_key.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Points the client at another host, e.g. a local mock server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+}
+
+/// `message.sendResults` has a `resultCode` per recipient, 0 meaning accepted.
+pub fn parse_nhn_response(body: &Value) -> MyceliumResult<Vec<DeliveryResult>> {
+    if body["header"]["isSuccessful"].as_bool() != Some(true) {
+        return Err(MyceliumError::Internal(format!(
+            "NHN Cloud 비즈메시지 오류: {}",
+            body["header"]["resultMessage"]
+                .as_str()
+                .unwrap_or("알 수 없는 오류")
+        )));
+    }
+    let request_id = body["message"]["requestId"].as_str().unwrap_or_default();
+    Ok(body["message"]["sendResults"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .map(|r| {
+                    let recipient = r["recipientNo"].as_str().unwrap_or_default();
+                    if r["resultCode"].as_i64() == Some(0) {
+                        let id = match r["recipientSeq"].as_i64() {
+                            Some(seq) => format!("{}:{}", request_id, seq),
+                            None => request_id.to_string(),
+                        };
+                        DeliveryResult::accepted(recipient, Some(id))
+                    } else {
+                        DeliveryResult::failed(
+                            recipient,
+                            r["resultMessage"].as_str().unwrap_or("발송 실패"),
+                        )
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+impl KakaoProvider for NhnBizMessageProvider {
+    fn name(&self) -> &'static str {
+        "nhn"
+    }
+
+    async fn send_kakao(&self, message: &KakaoMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        let (path, body) = match message.kind {
+            // raw-messages takes the filled-in text instead of template parameters
+            KakaoKind::AlimTalk => (
+                format!("/alimtalk/v2.3/appkeys/{}/raw-messages", self.app_key),
+                json!({
+                    "senderKey": message.sender_key,
+                    "templateCode": message.template_code,
+                    "recipientList": message.recipients.iter()
+                        .map(|r| json!({ "recipientNo": r, "content": message.text }))
+                        .collect::<Vec<_>>(),
+                }),
+            ),
+            KakaoKind::FriendTalk => (
+                format!("/friendtalk/v2.3/appkeys/{}/messages", self.app_key),
+                json!({
+                    "senderKey": message.sender_key,
+                    "recipientList": message.recipients.iter()
+                        .map(|r| json!({ "recipientNo": r, "content": message.text, "isAd": message.is_ad }))
+                        .collect::<Vec<_>>(),
+                }),
+            ),
+        };
+        let resp = self
+            .http
+            .post(format!("{}{}", self.base_url, path))
+            .header("X-Secret-Key", &self.secret_key)
+            .json(&body)
+            .send()
+            .await?;
+        parse_nhn_response(&resp.json().await?)
+    }
+}
+
+/// Solapi sends KakaoTalk through the same message API as SMS (types ATA and CTA).
+impl KakaoProvider for SolapiProvider {
+    fn name(&self) -> &'static str {
+        "solapi"
+    }
+
+    async fn send_kakao(&self, message: &KakaoMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        let messages: Vec<Value> = message
+            .recipients
+            .iter()
+            .map(|r| {
+                let mut options = json!({
+                    "pfId": message.sender_key,
+                    // Falling back to SMS is done here, with our own logging
+                    "disableSms": true,
+                });
+                match message.kind {
+                    KakaoKind::AlimTalk => options["templateId"] = json!(message.template_code),
+                    KakaoKind::FriendTalk => options["adFlag"] = json!(message.is_ad),
+                }
+                json!({
+                    "to": r,
+                    "text": message.text,
+                    "type": if message.kind == KakaoKind::AlimTalk { "ATA" } else { "CTA" },
+                    "kakaoOptions": options,
+                })
+            })
+            .collect();
+        let body = self
+            .post(
+                "/messages/v4/send-many/detail",
+                &json!({ "messages": messages }),
+            )
+            .await?;
+        Ok(super::solapi::parse_send_response(&body))
+    }
+}
+
+/// Accepts every number except those marked as not reachable on KakaoTalk. Used when the
+/// mock provider is chosen in the Kakao settings and in tests.
+#[derive(Default)]
+pub struct MockKakaoProvider {
+    unreachable: HashSet<String>,
+}
+
+impl MockKakaoProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Numbers (any format) that fail, like users without KakaoTalk
+    pub fn with_unreachable(mut self, numbers: &[&str]) -> Self {
+        self.unreachable = numbers.iter().map(|n| digits(n)).collect();
+        self
+    }
+}
+
+impl KakaoProvider for MockKakaoProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn send_kakao(&self, message: &KakaoMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        Ok(message
+            .recipients
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                if self.unreachable.contains(r) || !super::mock::is_mobile_number(r) {
+                    DeliveryResult::failed(r, "카카오톡 미사용자")
+                } else {
+                    DeliveryResult::accepted(r, Some(format!("KAKAO-MOCK-{}", i + 1)))
+                }
+            })
+            .collect())
+    }
+}
+
+pub enum KakaoGateway {
+    NhnCloud(NhnBizMessageProvider),
+    Solapi(SolapiProvider),
+    Mock(MockKakaoProvider),
+}
+
+impl KakaoProvider for KakaoGateway {
+    fn name(&self) -> &'static str {
+        match self {
+            KakaoGateway::NhnCloud(p) => p.name(),
+            KakaoGateway::Solapi(p) => KakaoProvider::name(p),
+            KakaoGateway::Mock(p) => p.name(),
+        }
+    }
+
+    async fn send_kakao(&self, message: &KakaoMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        match self {
+            KakaoGateway::NhnCloud(p) => p.send_kakao(message).await,
+            KakaoGateway::Solapi(p) => p.send_kakao(message).await,
+            KakaoGateway::Mock(p) => p.send_kakao(message).await,
+        }
+    }
+}
+
+/// The Kakao gateway from the settings, `None` when KakaoTalk is not set up
+pub fn kakao_gateway_from_settings(
+    settings: Option<&KakaoSettings>,
+) -> MyceliumResult<Option<KakaoGateway>> {
+    let Some(s) = settings else {
+        return Ok(None);
+    };
+    if s.provider == "mock" {
+        return Ok(Some(KakaoGateway::Mock(MockKakaoProvider::new())));
+    }
+    if s.api_key.trim().is_empty()
+        || s.api_secret.trim().is_empty()
+        || s.sender_key.trim().is_empty()
+    {
+        return Err(MyceliumError::Validation(
+            "카카오톡 발송을 위해 '설정 > API 키'에서 키와 발신 프로필을 입력해주세요.".to_string(),
+        ));
+    }
+    match s.provider.as_str() {
+        "nhn" => Ok(Some(KakaoGateway::NhnCloud(NhnBizMessageProvider::new(
+            &s.api_key,
+            &s.api_secret,
+        )))),
+        "solapi" => Ok(Some(KakaoGateway::Solapi(SolapiProvider::new(
+            &s.api_key,
+            &s.api_secret,
+        )))),
+        other => Err(MyceliumError::Validation(format!(
+            "지원되지 않는 카카오 메시지 서비스입니다: {}",
+            other
+        ))),
+    }
+}
+
+/// Sends one KakaoTalk message and logs a row per recipient like `deliver` does. Unlike SMS
+/// a gateway error is not returned: everyone is logged as failed so they can fall back.
+pub async fn deliver_kakao(
+    pool: &DbPool,
+    gateway: &KakaoGateway,
+    message: KakaoMessage,
+    entered_recipients: &[String],
+    costs: &KakaoCosts,
+) -> MyceliumResult<SendSummary> {
+    let message_id = format!(
+        "KAKAO-{}",
+        uuid::Uuid::new_v4().to_string()[..8].to_uppercase()
+    );
+    let outcome = gateway.send_kakao(&message).await;
+    let results = align_results(&message.recipients, &outcome);
+    let unit_cost = message.kind.unit_cost(costs);
+    log_deliveries(
+        pool,
+        &LogBatch {
+            log_id: &message_id,
+            provider: gateway.name(),
+            message_type: message.kind.as_str(),
+            text: &message.text,
+            is_ad: message.is_ad,
+            unit_cost,
+            template_code: message.template_code.as_deref(),
+            fallback_of: None,
+        },
+        entered_recipients,
+        &results,
+    )
+    .await?;
+
+    let sent = results.iter().filter(|r| r.accepted).count();
+    Ok(SendSummary {
+        message_id,
+        provider: gateway.name().to_string(),
+        message_type: message.kind.as_str().to_string(),
+        sent,
+        failed: results.len() - sent,
+        cost: sent as i64 * unit_cost as i64,
+        results,
+    })
+}
+
+/// The approved AlimTalk template for one of our template categories. A Kakao template code
+/// itself is accepted too, for screens that pick the code directly.
+pub async fn find_alimtalk_template(
+    pool: &DbPool,
+    template_key: &str,
+) -> MyceliumResult<Option<String>> {
+    Ok(sqlx::query_scalar(
+        "SELECT template_code FROM kakao_templates
+         WHERE is_enabled AND (template_key = $1 OR template_code = $1)
+         ORDER BY (template_key = $1) DESC LIMIT 1",
+    )
+    .bind(template_key)
+    .fetch_optional(pool)
+    .await?)
+}
+
+/// An approved AlimTalk template and the category of our message templates it is used for
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct KakaoTemplate {
+    #[serde(alias = "template_key")]
+    pub template_key: String,
+    #[serde(alias = "template_code")]
+    pub template_code: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_enabled", alias = "is_enabled")]
+    pub is_enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl KakaoTemplate {
+    pub fn validate(&self) -> MyceliumResult<()> {
+        if self.template_key.trim().is_empty() || self.template_code.trim().is_empty() {
+            return Err(MyceliumError::Validation(
+                "템플릿 분류와 알림톡 템플릿 코드를 입력해주세요.".to_string(),
+            ));
+        }
+        if self.template_key.len() > 50 || self.template_code.len() > 50 {
+            return Err(MyceliumError::Validation(
+                "템플릿 분류와 코드는 50자 이내로 입력해주세요.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateKeyInput {
+    #[serde(alias = "template_key")]
+    pub template_key: String,
+}
+
+pub async fn get_kakao_templates_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<KakaoTemplate>>> {
+    Ok(Json(
+        sqlx::query_as::<_, KakaoTemplate>(
+            "SELECT template_key, template_code, description, is_enabled
+             FROM kakao_templates ORDER BY template_key",
+        )
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+pub async fn save_kakao_template_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(template): Json<KakaoTemplate>,
+) -> MyceliumResult<Json<()>> {
+    template.validate()?;
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query(
+        "INSERT INTO kakao_templates (template_key, template_code, description, is_enabled)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (template_key) DO UPDATE SET template_code = EXCLUDED.template_code,
+            description = EXCLUDED.description, is_enabled = EXCLUDED.is_enabled,
+            updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(template.template_key.trim())
+    .bind(template.template_code.trim())
+    .bind(&template.description)
+    .bind(template.is_enabled)
+    .execute(&state.pool)
+    .await?;
+    Ok(Json(()))
+}
+
+pub async fn delete_kakao_template_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<TemplateKeyInput>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query("DELETE FROM kakao_templates WHERE template_key = $1")
+        .bind(&input.template_key)
+        .execute(&state.pool)
+        .await?;
+    Ok(Json(()))
+}
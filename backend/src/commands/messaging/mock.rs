@@ -0,0 +1,76 @@
+use super::{DeliveryResult, MessageProvider, OutgoingMessage};
+use crate::error::{MyceliumError, MyceliumResult};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Accepts every well-formed mobile number without sending anything. Used when the mock
+/// provider is chosen in the settings and in tests.
+pub struct MockProvider {
+    sequence: AtomicU64,
+    max_recipients: usize,
+    unavailable: bool,
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self {
+            sequence: AtomicU64::new(0),
+            max_recipients: 1_000,
+            unavailable: false,
+        }
+    }
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Smaller batches, to see a send split into several calls
+    pub fn with_max_recipients(mut self, max_recipients: usize) -> Self {
+        self.max_recipients = max_recipients;
+        self
+    }
+
+    /// Every send fails as if the gateway could not be reached
+    pub fn unavailable(mut self) -> Self {
+        self.unavailable = true;
+        self
+    }
+}
+
+/// 010-1234-5678 or an old 011/016/017/018/019 number, digits only
+pub fn is_mobile_number(digits: &str) -> bool {
+    (digits.len() == 10 || digits.len() == 11)
+        && digits.starts_with("01")
+        && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+impl MessageProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn max_recipients(&self) -> usize {
+        self.max_recipients
+    }
+
+    async fn send(&self, message: &OutgoingMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        if self.unavailable {
+            return Err(MyceliumError::Internal(
+                "모의 발송 서버에 연결할 수 없습니다.".to_string(),
+            ));
+        }
+        Ok(message
+            .recipients
+            .iter()
+            .map(|r| {
+                if is_mobile_number(r) {
+                    let seq = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+                    DeliveryResult::accepted(r, Some(format!("MOCK-{}", seq)))
+                } else {
+                    DeliveryResult::failed(r, "잘못된 휴대폰 번호")
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,432 @@
+pub mod aligo;
+pub mod kakao;
+pub mod mock;
+pub mod nhn_cloud;
+pub mod outbox;
+pub mod solapi;
+pub mod webhook;
+
+use crate::commands::config::{MessageCosts, SmsSettings};
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use serde::Serialize;
+use std::future::Future;
+
+pub use aligo::AligoProvider;
+pub use mock::MockProvider;
+pub use nhn_cloud::NhnCloudProvider;
+pub use solapi::SolapiProvider;
+
+/// Longest text sent as SMS; anything longer goes as LMS
+pub const SMS_MAX_BYTES: usize = 90;
+pub const LMS_MAX_BYTES: usize = 2000;
+/// `sms_logs.error_message` for a recipient that isn't a mobile number
+pub const INVALID_NUMBER: &str = "잘못된 휴대폰 번호";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MessageKind {
+    Sms,
+    Lms,
+    Mms,
+}
+
+impl MessageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageKind::Sms => "SMS",
+            MessageKind::Lms => "LMS",
+            MessageKind::Mms => "MMS",
+        }
+    }
+
+    pub fn unit_cost(&self, costs: &MessageCosts) -> i32 {
+        match self {
+            MessageKind::Sms => costs.sms,
+            MessageKind::Lms => costs.lms,
+            MessageKind::Mms => costs.mms,
+        }
+    }
+}
+
+/// Length as the carriers count it: one byte for ASCII, two for anything else (EUC-KR).
+pub fn message_bytes(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+/// MMS with an image, otherwise SMS up to 90 bytes and LMS up to 2,000.
+pub fn select_message_kind(text: &str, has_image: bool) -> MyceliumResult<MessageKind> {
+    let bytes = message_bytes(text);
+    if bytes > LMS_MAX_BYTES {
+        return Err(MyceliumError::Validation(format!(
+            "메시지가 너무 깁니다. ({}/{}바이트)",
+            bytes, LMS_MAX_BYTES
+        )));
+    }
+    Ok(if has_image {
+        MessageKind::Mms
+    } else if bytes <= SMS_MAX_BYTES {
+        MessageKind::Sms
+    } else {
+        MessageKind::Lms
+    })
+}
+
+/// Title of an LMS/MMS: the first line, cut to 40 bytes.
+pub fn default_subject(text: &str) -> String {
+    let mut subject = String::new();
+    for c in text.lines().next().unwrap_or("").trim().chars() {
+        if message_bytes(&subject) + message_bytes(c.encode_utf8(&mut [0; 4])) > 40 {
+            break;
+        }
+        subject.push(c);
+    }
+    subject
+}
+
+pub fn digits(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct MessageImage {
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+/// One message to many recipients. Numbers are digits only.
+#[derive(Debug, Clone)]
+pub struct OutgoingMessage {
+    pub kind: MessageKind,
+    pub sender: String,
+    pub recipients: Vec<String>,
+    pub subject: Option<String>,
+    pub text: String,
+    pub image: Option<MessageImage>,
+    /// Advertising; some gateways take it on separate endpoints
+    pub is_ad: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryResult {
+    pub recipient: String,
+    /// Accepted by the gateway; the handset delivery is reported later
+    pub accepted: bool,
+    pub provider_message_id: Option<String>,
+    pub error: Option<String>,
+    /// Never judged by the gateway (it could not be reached or gave no answer), so another
+    /// try may succeed. A recipient the gateway rejected is not retried.
+    #[serde(skip)]
+    pub retryable: bool,
+}
+
+impl DeliveryResult {
+    pub fn accepted(recipient: &str, provider_message_id: Option<String>) -> Self {
+        Self {
+            recipient: recipient.to_string(),
+            accepted: true,
+            provider_message_id,
+            error: None,
+            retryable: false,
+        }
+    }
+
+    pub fn failed(recipient: &str, error: impl Into<String>) -> Self {
+        Self {
+            recipient: recipient.to_string(),
+            accepted: false,
+            provider_message_id: None,
+            error: Some(error.into()),
+            retryable: false,
+        }
+    }
+
+    pub fn unsent(recipient: &str, error: impl Into<String>) -> Self {
+        Self {
+            retryable: true,
+            ..Self::failed(recipient, error)
+        }
+    }
+}
+
+/// An SMS gateway. `send` returns one result per recipient; an `Err` means nothing was sent.
+pub trait MessageProvider {
+    fn name(&self) -> &'static str;
+
+    /// Most recipients one `send` may carry
+    fn max_recipients(&self) -> usize;
+
+    fn send(
+        &self,
+        message: &OutgoingMessage,
+    ) -> impl Future<Output = MyceliumResult<Vec<DeliveryResult>>> + Send;
+}
+
+/// The provider chosen in the SMS settings
+pub enum Gateway {
+    Aligo(AligoProvider),
+    NhnCloud(NhnCloudProvider),
+    Solapi(SolapiProvider),
+    Mock(MockProvider),
+}
+
+impl MessageProvider for Gateway {
+    fn name(&self) -> &'static str {
+        match self {
+            Gateway::Aligo(p) => p.name(),
+            Gateway::NhnCloud(p) => p.name(),
+            Gateway::Solapi(p) => p.name(),
+            Gateway::Mock(p) => p.name(),
+        }
+    }
+
+    fn max_recipients(&self) -> usize {
+        match self {
+            Gateway::Aligo(p) => p.max_recipients(),
+            Gateway::NhnCloud(p) => p.max_recipients(),
+            Gateway::Solapi(p) => p.max_recipients(),
+            Gateway::Mock(p) => p.max_recipients(),
+        }
+    }
+
+    async fn send(&self, message: &OutgoingMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        match self {
+            Gateway::Aligo(p) => p.send(message).await,
+            Gateway::NhnCloud(p) => p.send(message).await,
+            Gateway::Solapi(p) => p.send(message).await,
+            Gateway::Mock(p) => p.send(message).await,
+        }
+    }
+}
+
+/// Builds the configured gateway. The mock provider, which only logs messages, is used
+/// only when it is chosen in the settings.
+pub fn gateway_from_settings(settings: Option<&SmsSettings>) -> MyceliumResult<Gateway> {
+    let Some(s) = settings else {
+        return Err(MyceliumError::Validation(
+            "문자 발송을 위해 '설정 > API 키'에서 문자 서비스를 설정해주세요.".to_string(),
+        ));
+    };
+    let missing = |what: &str| {
+        MyceliumError::Validation(format!(
+            "문자 발송을 위해 '설정 > API 키'에서 {}를 입력해주세요.",
+            what
+        ))
+    };
+    if s.provider != "mock" && s.api_key.trim().is_empty() {
+        return Err(missing("API 키"));
+    }
+    let secret = s.api_secret.trim();
+    match s.provider.as_str() {
+        "aligo" if secret.is_empty() => Err(missing("알리고 아이디")),
+        "aligo" => Ok(Gateway::Aligo(AligoProvider::new(&s.api_key, secret))),
+        "nhn" if secret.is_empty() => Err(missing("시크릿 키")),
+        "nhn" => Ok(Gateway::NhnCloud(NhnCloudProvider::new(&s.api_key, secret))),
+        // CoolSMS is the same service under its former name
+        "solapi" | "coolsms" if secret.is_empty() => Err(missing("API 시크릿")),
+        "solapi" | "coolsms" => Ok(Gateway::Solapi(SolapiProvider::new(&s.api_key, secret))),
+        "mock" => Ok(Gateway::Mock(MockProvider::new())),
+        other => Err(MyceliumError::Validation(format!(
+            "지원되지 않는 문자 서비스입니다: {}",
+            other
+        ))),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SendSummary {
+    pub message_id: String,
+    pub provider: String,
+    pub message_type: String,
+    pub sent: usize,
+    pub failed: usize,
+    pub cost: i64,
+    pub results: Vec<DeliveryResult>,
+}
+
+/// A send as the caller sees it; recipients are numbers as entered.
+#[derive(Debug, Clone, Default)]
+pub struct MessageRequest {
+    pub sender: String,
+    pub recipients: Vec<String>,
+    pub text: String,
+    pub subject: Option<String>,
+    pub image: Option<MessageImage>,
+    pub is_ad: bool,
+    /// Our template category (or a Kakao template code) the text was made from
+    pub template_code: Option<String>,
+    /// Log id of the KakaoTalk send this SMS replaces
+    pub fallback_of: Option<String>,
+}
+
+/// What every `sms_logs` row of one send has in common
+pub(crate) struct LogBatch<'a> {
+    pub log_id: &'a str,
+    pub provider: &'a str,
+    pub message_type: &'a str,
+    pub text: &'a str,
+    pub is_ad: bool,
+    pub unit_cost: i32,
+    pub template_code: Option<&'a str>,
+    pub fallback_of: Option<&'a str>,
+}
+
+/// One row per recipient; the customer is filled in when the number matches exactly one.
+pub(crate) async fn log_deliveries(
+    pool: &DbPool,
+    batch: &LogBatch<'_>,
+    recipients: &[String],
+    results: &[DeliveryResult],
+) -> MyceliumResult<()> {
+    for (recipient, result) in recipients.iter().zip(results) {
+        sqlx::query(
+            "INSERT INTO sms_logs (log_id, recipient_name, mobile_number, content, status, sent_at, is_ad, customer_id,
+                                   provider, message_type, cost, provider_message_id, error_message,
+                                   template_code, fallback_of)
+             SELECT $1, COALESCE(m.customer_name, '고객'), $2, $3, $4, CURRENT_TIMESTAMP, $5, m.customer_id,
+                    $6, $7, $8, $9, $10, $11, $12
+             FROM (SELECT 1) AS one
+             LEFT JOIN (
+                SELECT MIN(customer_id) AS customer_id, MIN(customer_name) AS customer_name FROM customers
+                WHERE regexp_replace(mobile_number, '[^0-9]', '', 'g') = regexp_replace($2, '[^0-9]', '', 'g')
+                HAVING COUNT(*) = 1
+             ) m ON TRUE",
+        )
+        .bind(batch.log_id)
+        .bind(recipient)
+        .bind(batch.text)
+        .bind(if result.accepted { "성공" } else { "실패" })
+        .bind(batch.is_ad)
+        .bind(batch.provider)
+        .bind(batch.message_type)
+        .bind(if result.accepted { batch.unit_cost } else { 0 })
+        .bind(&result.provider_message_id)
+        .bind(&result.error)
+        .bind(batch.template_code)
+        .bind(batch.fallback_of)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Lines the gateway's results up with `recipients` (digits only); a gateway error fails
+/// everyone.
+pub(crate) fn align_results(
+    recipients: &[String],
+    outcome: &MyceliumResult<Vec<DeliveryResult>>,
+) -> Vec<DeliveryResult> {
+    match outcome {
+        Ok(results) => recipients
+            .iter()
+            .map(|r| {
+                results
+                    .iter()
+                    .find(|d| d.recipient == *r)
+                    .cloned()
+                    .unwrap_or_else(|| DeliveryResult::unsent(r, "발송 결과 없음"))
+            })
+            .collect(),
+        Err(e) => recipients
+            .iter()
+            .map(|r| DeliveryResult::unsent(r, e.to_string()))
+            .collect(),
+    }
+}
+
+/// Sends the request as SMS, LMS or MMS, in as many gateway calls as the provider's
+/// recipient limit needs, and writes one `sms_logs` row per recipient with its status,
+/// gateway message id and cost. A gateway error fails the recipients not sent yet; it is
+/// returned when nobody was sent to.
+pub async fn deliver(
+    pool: &DbPool,
+    gateway: &Gateway,
+    request: MessageRequest,
+    costs: &MessageCosts,
+) -> MyceliumResult<SendSummary> {
+    let kind = select_message_kind(&request.text, request.image.is_some())?;
+    let mut message = OutgoingMessage {
+        kind,
+        sender: digits(&request.sender),
+        recipients: Vec::new(),
+        subject: match kind {
+            MessageKind::Sms => None,
+            _ => request
+                .subject
+                .filter(|s| !s.trim().is_empty())
+                .or_else(|| Some(default_subject(&request.text))),
+        },
+        text: request.text.clone(),
+        image: request.image,
+        is_ad: request.is_ad,
+    };
+    let message_id = format!(
+        "{}-{}",
+        kind.as_str(),
+        uuid::Uuid::new_v4().to_string()[..8].to_uppercase()
+    );
+
+    let numbers: Vec<String> = request.recipients.iter().map(|r| digits(r)).collect();
+    let valid: Vec<String> = numbers
+        .iter()
+        .filter(|n| mock::is_mobile_number(n))
+        .cloned()
+        .collect();
+    let mut sent_results = Vec::with_capacity(valid.len());
+    let mut gateway_error: Option<MyceliumError> = None;
+    for chunk in valid.chunks(gateway.max_recipients().max(1)) {
+        if let Some(e) = &gateway_error {
+            sent_results.extend(
+                chunk
+                    .iter()
+                    .map(|r| DeliveryResult::unsent(r, e.to_string())),
+            );
+            continue;
+        }
+        message.recipients = chunk.to_vec();
+        let outcome = gateway.send(&message).await;
+        sent_results.extend(align_results(chunk, &outcome));
+        gateway_error = outcome.err();
+    }
+    // Numbers that can't be a mobile phone never reach the gateway; they are logged as failed.
+    let results: Vec<DeliveryResult> = numbers
+        .iter()
+        .map(|n| {
+            sent_results
+                .iter()
+                .find(|d| mock::is_mobile_number(n) && d.recipient == *n)
+                .cloned()
+                .unwrap_or_else(|| DeliveryResult::failed(n, INVALID_NUMBER))
+        })
+        .collect();
+    let unit_cost = kind.unit_cost(costs);
+    log_deliveries(
+        pool,
+        &LogBatch {
+            log_id: &message_id,
+            provider: gateway.name(),
+            message_type: kind.as_str(),
+            text: &request.text,
+            is_ad: request.is_ad,
+            unit_cost,
+            template_code: request.template_code.as_deref(),
+            fallback_of: request.fallback_of.as_deref(),
+        },
+        &request.recipients,
+        &results,
+    )
+    .await?;
+
+    let sent = results.iter().filter(|r| r.accepted).count();
+    if let Some(e) = gateway_error.filter(|_| sent == 0) {
+        return Err(e);
+    }
+    Ok(SendSummary {
+        message_id,
+        provider: gateway.name().to_string(),
+        message_type: kind.as_str().to_string(),
+        sent,
+        failed: results.len() - sent,
+        cost: sent as i64 * unit_cost as i64,
+        results,
+    })
+}
@@ -0,0 +1,139 @@
+use super::{DeliveryResult, MessageKind, MessageProvider, OutgoingMessage};
+use crate::error::{MyceliumError, MyceliumResult};
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::{json, Value};
+
+pub const NHN_CLOUD_SMS_API_BASE: &str = "https://api-sms.cloud.toast.com";
+
+/// Client for the NHN Cloud Notification SMS API (v3.0). The app key is part of every path
+/// and the secret key goes in the `X-Secret-Key` header.
+pub struct NhnCloudProvider {
+    base_url: String,
+    app_key: String,
+    secret_key: String,
+    http: reqwest::Client,
+}
+
+impl NhnCloudProvider {
+    pub fn new(app_key: &str, secret_key: &str) -> Self {
+        Self {
+            base_url: NHN_CLOUD_SMS_API_BASE.to_string(),
+            app_key: app_key.to_string(),
+            secret_key: secret_key.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Points the client at another host, e.g. a local mock server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> MyceliumResult<Value> {
+        let resp = self
+            .http
+            .post(format!(
+                "{}/sms/v3.0/appKeys/{}{}",
+                self.base_url, self.app_key, path
+            ))
+            .header("X-Secret-Key", &self.secret_key)
+            .json(body)
+            .send()
+            .await?;
+        let body: Value = resp.json().await?;
+        if body["header"]["isSuccessful"].as_bool() != Some(true) {
+            return Err(MyceliumError::Internal(format!(
+                "NHN Cloud API 오류: {}",
+                body["header"]["resultMessage"]
+                    .as_str()
+                    .unwrap_or("알 수 없는 오류")
+            )));
+        }
+        Ok(body)
+    }
+}
+
+/// `sendResultList` has a `resultCode` per recipient, 0 meaning accepted. The request id plus
+/// the recipient sequence identifies the message in delivery reports.
+pub fn parse_send_response(body: &Value) -> Vec<DeliveryResult> {
+    let data = &body["body"]["data"];
+    let request_id = data["requestId"].as_str().unwrap_or_default();
+    data["sendResultList"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .map(|r| {
+                    let recipient = r["recipientNo"].as_str().unwrap_or_default();
+                    if r["resultCode"].as_i64() == Some(0) {
+                        let id = match r["recipientSeq"].as_i64() {
+                            Some(seq) => format!("{}:{}", request_id, seq),
+                            None => request_id.to_string(),
+                        };
+                        DeliveryResult::accepted(recipient, Some(id))
+                    } else {
+                        DeliveryResult::failed(
+                            recipient,
+                            r["resultMessage"].as_str().unwrap_or("발송 실패"),
+                        )
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl MessageProvider for NhnCloudProvider {
+    fn name(&self) -> &'static str {
+        "nhn"
+    }
+
+    /// `recipientList` takes up to 1,000 entries
+    fn max_recipients(&self) -> usize {
+        1_000
+    }
+
+    async fn send(&self, message: &OutgoingMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        let recipient_list: Vec<Value> = message
+            .recipients
+            .iter()
+            .map(|r| json!({ "recipientNo": r }))
+            .collect();
+        let mut body = json!({
+            "body": message.text,
+            "sendNo": message.sender,
+            "recipientList": recipient_list,
+        });
+        if let Some(image) = &message.image {
+            let uploaded = self
+                .post(
+                    "/attachfile/binaryUpload",
+                    &json!({
+                        "fileName": image.file_name,
+                        "createUser": "mycelium",
+                        "fileBody": general_purpose::STANDARD.encode(&image.data),
+                    }),
+                )
+                .await?;
+            let file_id = uploaded["body"]["data"]["fileId"].as_i64().ok_or_else(|| {
+                MyceliumError::Internal("NHN Cloud API 오류: 첨부 파일 ID 없음".to_string())
+            })?;
+            body["attachFileIdList"] = json!([file_id]);
+        }
+        // LMS and MMS share one endpoint; SMS has no title. Advertising goes to the ad-
+        // endpoints, which check the wording and the opt-out number.
+        let path = match (message.kind == MessageKind::Sms, message.is_ad) {
+            (true, false) => "/sender/sms",
+            (true, true) => "/sender/ad-sms",
+            (false, is_ad) => {
+                body["title"] = json!(message.subject.clone().unwrap_or_default());
+                if is_ad {
+                    "/sender/ad-mms"
+                } else {
+                    "/sender/mms"
+                }
+            }
+        };
+        Ok(parse_send_response(&self.post(path, &body).await?))
+    }
+}
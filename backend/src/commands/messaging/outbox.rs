@@ -0,0 +1,501 @@
+use super::kakao::KakaoProvider;
+use super::{digits, select_message_kind, MessageImage, MessageProvider};
+use crate::commands::config::load_integration_settings;
+use crate::commands::consent::{is_ad_night_time, MessagePurpose};
+use crate::commands::crm::{dispatch_message, MessageChannels, MessageOptions};
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::Ordering;
+
+/// Sends to more recipients than this are queued instead of sent during the request
+pub const QUEUE_THRESHOLD: usize = 100;
+/// After this many failed attempts a recipient is given up on
+pub const MAX_SEND_ATTEMPTS: i32 = 5;
+/// How often the background worker runs
+pub const WORKER_INTERVAL_SECS: u64 = 10;
+/// Rows still '발송중' after this long belong to a run that died mid-send
+pub const STALE_CLAIM_MINUTES: i32 = 30;
+
+/// Messages per minute sent through each provider, kept under their published limits
+pub fn provider_rate_per_minute(provider: &str) -> usize {
+    match provider {
+        "aligo" => 300,
+        "nhn" | "solapi" => 1200,
+        _ => 6000,
+    }
+}
+
+/// Recipients one worker run may send through the provider
+pub fn batch_budget(provider: &str) -> usize {
+    (provider_rate_per_minute(provider) * WORKER_INTERVAL_SECS as usize / 60).max(1)
+}
+
+/// Backoff before the next attempt: 1, 2, 4 ... minutes, capped at an hour.
+pub fn send_retry_delay_minutes(attempts: i32) -> i64 {
+    (1i64 << attempts.clamp(0, 6)).min(60)
+}
+
+fn purpose_str(purpose: MessagePurpose) -> &'static str {
+    match purpose {
+        MessagePurpose::Promotional => "promotional",
+        MessagePurpose::Transactional => "transactional",
+    }
+}
+
+fn parse_purpose(value: &str) -> MessagePurpose {
+    match value {
+        "transactional" => MessagePurpose::Transactional,
+        _ => MessagePurpose::Promotional,
+    }
+}
+
+/// A send handed to the worker
+#[derive(Debug, Default)]
+pub struct NewCampaign {
+    pub mode: String,
+    pub purpose: MessagePurpose,
+    pub recipients: Vec<String>,
+    pub content: String,
+    pub template_code: Option<String>,
+    pub subject: Option<String>,
+    pub image: Option<MessageImage>,
+    /// Right away when absent
+    pub scheduled_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct CampaignProgress {
+    pub campaign_id: String,
+    pub mode: String,
+    pub purpose: String,
+    /// '대기', '발송중', '완료' or '취소'
+    pub status: String,
+    pub content: String,
+    pub scheduled_at: NaiveDateTime,
+    pub created_by: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub total: i64,
+    pub pending: i64,
+    pub sent: i64,
+    pub failed: i64,
+    pub excluded: i64,
+    pub cancelled: i64,
+}
+
+const PROGRESS_SELECT: &str =
+    "SELECT c.campaign_id, c.mode, c.purpose, c.status, c.content, c.scheduled_at,
+        c.created_by, c.created_at, c.started_at, c.finished_at,
+        COUNT(o.outbox_id) AS total,
+        COUNT(o.outbox_id) FILTER (WHERE o.status IN ('대기', '발송중')) AS pending,
+        COUNT(o.outbox_id) FILTER (WHERE o.status = '발송') AS sent,
+        COUNT(o.outbox_id) FILTER (WHERE o.status = '실패') AS failed,
+        COUNT(o.outbox_id) FILTER (WHERE o.status = '제외') AS excluded,
+        COUNT(o.outbox_id) FILTER (WHERE o.status = '취소') AS cancelled
+     FROM message_campaigns c LEFT JOIN message_outbox o ON o.campaign_id = c.campaign_id";
+
+pub async fn campaign_progress(
+    pool: &DbPool,
+    campaign_id: &str,
+) -> MyceliumResult<CampaignProgress> {
+    sqlx::query_as::<_, CampaignProgress>(&format!(
+        "{} WHERE c.campaign_id = $1 GROUP BY c.campaign_id",
+        PROGRESS_SELECT
+    ))
+    .bind(campaign_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| MyceliumError::Validation("발송 예약을 찾을 수 없습니다.".to_string()))
+}
+
+/// Stores the send with one outbox row per recipient; the worker sends it from the
+/// scheduled time on.
+pub async fn enqueue_campaign(
+    pool: &DbPool,
+    campaign: NewCampaign,
+    username: &str,
+) -> MyceliumResult<CampaignProgress> {
+    if campaign.content.trim().is_empty() {
+        return Err(MyceliumError::Validation(
+            "메시지 내용을 입력해주세요.".to_string(),
+        ));
+    }
+    select_message_kind(&campaign.content, campaign.image.is_some())?;
+    let mut seen = HashSet::new();
+    let recipients: Vec<String> = campaign
+        .recipients
+        .into_iter()
+        .map(|r| r.trim().to_string())
+        // "010-1234-5678" and "01012345678" are the same recipient
+        .filter(|r| {
+            let number = digits(r);
+            !r.is_empty() && seen.insert(if number.is_empty() { r.clone() } else { number })
+        })
+        .collect();
+    if recipients.is_empty() {
+        return Err(MyceliumError::Validation(
+            "받는 사람이 없습니다.".to_string(),
+        ));
+    }
+
+    let campaign_id = format!(
+        "CMP-{}",
+        uuid::Uuid::new_v4().to_string()[..8].to_uppercase()
+    );
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    sqlx::query(
+        "INSERT INTO message_campaigns (campaign_id, mode, purpose, content, subject, template_code,
+            image_name, image_data, scheduled_at, created_by)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, COALESCE($9, CURRENT_TIMESTAMP), $10)",
+    )
+    .bind(&campaign_id)
+    .bind(&campaign.mode)
+    .bind(purpose_str(campaign.purpose))
+    .bind(&campaign.content)
+    .bind(&campaign.subject)
+    .bind(&campaign.template_code)
+    .bind(campaign.image.as_ref().map(|i| &i.file_name))
+    .bind(campaign.image.as_ref().map(|i| &i.data))
+    .bind(campaign.scheduled_at)
+    .bind(username)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "INSERT INTO message_outbox (campaign_id, recipient, next_attempt_at)
+         SELECT $1, r, c.scheduled_at FROM UNNEST($2::text[]) AS r, message_campaigns c
+         WHERE c.campaign_id = $1",
+    )
+    .bind(&campaign_id)
+    .bind(&recipients)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    campaign_progress(pool, &campaign_id).await
+}
+
+#[derive(sqlx::FromRow)]
+struct DueCampaign {
+    campaign_id: String,
+    mode: String,
+    purpose: String,
+    content: String,
+    subject: Option<String>,
+    template_code: Option<String>,
+    image_name: Option<String>,
+    image_data: Option<Vec<u8>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct DueRecipient {
+    outbox_id: i32,
+    recipient: String,
+    attempts: i32,
+}
+
+/// Sends what is due through the configured providers; see `process_outbox_with`.
+pub async fn process_message_outbox(pool: &DbPool) -> MyceliumResult<usize> {
+    let due: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM message_outbox WHERE status = '대기'
+                        AND next_attempt_at <= CURRENT_TIMESTAMP)",
+    )
+    .fetch_one(pool)
+    .await?;
+    if !due {
+        return Ok(0);
+    }
+    let channels = MessageChannels::from_settings(load_integration_settings()?)?;
+    process_outbox_with(pool, &channels, batch_budget).await
+}
+
+/// Sends due outbox rows, campaign by campaign, taking at most `budget(provider)`
+/// recipients per provider. Advertising waits while it may not be sent (21:00~08:00).
+/// Recipients the gateway could not be asked about (unreachable, no answer) are retried
+/// with backoff up to `MAX_SEND_ATTEMPTS`; a rejected recipient (invalid number) or message
+/// (too long, no template) fails right away.
+/// Returns the number of recipients reached.
+pub async fn process_outbox_with(
+    pool: &DbPool,
+    channels: &MessageChannels,
+    budget: impl Fn(&str) -> usize,
+) -> MyceliumResult<usize> {
+    // Whether those went out is unknown; they are failed rather than risk a second text
+    sqlx::query(
+        "UPDATE message_outbox SET status = '실패', last_error = '발송 결과 확인 불가',
+            updated_at = CURRENT_TIMESTAMP
+         WHERE status = '발송중' AND updated_at < CURRENT_TIMESTAMP - make_interval(mins => $1)",
+    )
+    .bind(STALE_CLAIM_MINUTES)
+    .execute(pool)
+    .await?;
+
+    let campaigns: Vec<DueCampaign> = sqlx::query_as(
+        "SELECT c.campaign_id, c.mode, c.purpose, c.content, c.subject, c.template_code,
+                c.image_name, c.image_data
+         FROM message_campaigns c
+         WHERE c.status IN ('대기', '발송중') AND c.scheduled_at <= CURRENT_TIMESTAMP
+           AND EXISTS (SELECT 1 FROM message_outbox o
+                       WHERE o.campaign_id = c.campaign_id AND o.status = '대기'
+                         AND o.next_attempt_at <= CURRENT_TIMESTAMP)
+         ORDER BY c.scheduled_at, c.created_at",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let night = is_ad_night_time(chrono::Local::now().time());
+    let mut remaining: HashMap<&'static str, usize> = HashMap::new();
+    let mut reached_total = 0;
+    for campaign in campaigns {
+        let purpose = parse_purpose(&campaign.purpose);
+        if purpose == MessagePurpose::Promotional && night {
+            continue;
+        }
+        let via_kakao = campaign.mode.eq_ignore_ascii_case("kakao")
+            || (campaign.template_code.is_some() && channels.kakao.is_some());
+        let provider = match &channels.kakao {
+            Some((gateway, _)) if via_kakao => gateway.name(),
+            _ => channels.sms.name(),
+        };
+        let left = remaining
+            .entry(provider)
+            .or_insert_with(|| budget(provider));
+        if *left == 0 {
+            continue;
+        }
+
+        // Claimed rows are '발송중' until their outcome is written, so overlapping runs
+        // skip them. The CTE locks exactly the rows it picked.
+        let batch: Vec<DueRecipient> = sqlx::query_as(
+            "WITH due AS (
+                SELECT outbox_id FROM message_outbox
+                WHERE campaign_id = $1 AND status = '대기' AND next_attempt_at <= CURRENT_TIMESTAMP
+                ORDER BY outbox_id LIMIT $2
+                FOR UPDATE SKIP LOCKED
+             )
+             UPDATE message_outbox o SET status = '발송중', updated_at = CURRENT_TIMESTAMP
+             FROM due WHERE o.outbox_id = due.outbox_id
+             RETURNING o.outbox_id, o.recipient, o.attempts",
+        )
+        .bind(&campaign.campaign_id)
+        .bind(*left as i64)
+        .fetch_all(pool)
+        .await?;
+        if batch.is_empty() {
+            continue;
+        }
+        *left = left.saturating_sub(batch.len());
+        sqlx::query(
+            "UPDATE message_campaigns SET status = '발송중', started_at = CURRENT_TIMESTAMP
+             WHERE campaign_id = $1 AND status = '대기'",
+        )
+        .bind(&campaign.campaign_id)
+        .execute(pool)
+        .await?;
+
+        let options = MessageOptions {
+            template_code: campaign.template_code,
+            subject: campaign.subject,
+            image: match (campaign.image_name, campaign.image_data) {
+                (Some(file_name), Some(data)) => Some(MessageImage { file_name, data }),
+                _ => None,
+            },
+        };
+        let outcome = dispatch_message(
+            pool,
+            channels,
+            campaign.mode,
+            batch.iter().map(|r| r.recipient.clone()).collect(),
+            campaign.content,
+            purpose,
+            options,
+        )
+        .await;
+
+        for row in &batch {
+            let number = digits(&row.recipient);
+            let (status, message_id, error) = match &outcome {
+                Ok(dispatch) if dispatch.excluded.contains(&row.recipient) => {
+                    ("제외", None, Some("수신 동의 없음".to_string()))
+                }
+                Ok(dispatch) => {
+                    let results = dispatch.summaries.iter().flat_map(|s| {
+                        s.results
+                            .iter()
+                            .filter(|r| r.recipient == number)
+                            .map(move |r| (s, r))
+                    });
+                    let mut error = None;
+                    let mut message_id = None;
+                    // No result at all is as good as a gateway that did not answer
+                    let mut retryable = true;
+                    for (summary, result) in results {
+                        if result.accepted {
+                            message_id = Some(summary.message_id.clone());
+                        } else {
+                            // SMS after KakaoTalk has the last word on the recipient
+                            retryable = result.retryable;
+                            error = result.error.clone().or(error);
+                        }
+                    }
+                    match message_id {
+                        Some(id) => ("발송", Some(id), None),
+                        None => (
+                            if retryable { "대기" } else { "실패" },
+                            None,
+                            Some(error.unwrap_or_else(|| "발송 결과 없음".to_string())),
+                        ),
+                    }
+                }
+                Err(MyceliumError::Validation(e)) => ("실패", None, Some(e.clone())),
+                Err(e) => ("대기", None, Some(e.to_string())),
+            };
+
+            if status == "발송" {
+                reached_total += 1;
+                sqlx::query(
+                    "UPDATE message_outbox SET status = '발송', attempts = attempts + 1, message_id = $1,
+                        last_error = NULL, sent_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+                     WHERE outbox_id = $2",
+                )
+                .bind(&message_id)
+                .bind(row.outbox_id)
+                .execute(pool)
+                .await?;
+                continue;
+            }
+            let attempts = row.attempts + 1;
+            let status = if status == "대기" && attempts >= MAX_SEND_ATTEMPTS {
+                "실패"
+            } else {
+                status
+            };
+            // A retry of a campaign cancelled meanwhile is cancelled instead
+            sqlx::query(
+                "UPDATE message_outbox SET attempts = $2, last_error = $3,
+                    status = CASE WHEN $1 = '대기' AND EXISTS (
+                        SELECT 1 FROM message_campaigns c
+                        WHERE c.campaign_id = message_outbox.campaign_id AND c.status = '취소'
+                    ) THEN '취소' ELSE $1 END,
+                    next_attempt_at = CURRENT_TIMESTAMP + make_interval(mins => $4),
+                    updated_at = CURRENT_TIMESTAMP
+                 WHERE outbox_id = $5 AND status = '발송중'",
+            )
+            .bind(status)
+            .bind(attempts)
+            .bind(&error)
+            .bind(send_retry_delay_minutes(row.attempts) as i32)
+            .bind(row.outbox_id)
+            .execute(pool)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE message_campaigns SET status = '완료', finished_at = CURRENT_TIMESTAMP
+             WHERE campaign_id = $1 AND status = '발송중'
+               AND NOT EXISTS (SELECT 1 FROM message_outbox
+                               WHERE campaign_id = $1 AND status IN ('대기', '발송중'))",
+        )
+        .bind(&campaign.campaign_id)
+        .execute(pool)
+        .await?;
+    }
+    Ok(reached_total)
+}
+
+/// Stops a campaign; recipients already sent to stay sent.
+pub async fn cancel_campaign(
+    pool: &DbPool,
+    campaign_id: &str,
+    username: &str,
+) -> MyceliumResult<CampaignProgress> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    let status: Option<String> = sqlx::query_scalar(
+        "SELECT status FROM message_campaigns WHERE campaign_id = $1 FOR UPDATE",
+    )
+    .bind(campaign_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    match status.as_deref() {
+        None => {
+            return Err(MyceliumError::Validation(
+                "발송 예약을 찾을 수 없습니다.".to_string(),
+            ))
+        }
+        Some("대기") | Some("발송중") => {}
+        Some(_) => {
+            return Err(MyceliumError::Validation(
+                "이미 끝난 발송은 취소할 수 없습니다.".to_string(),
+            ))
+        }
+    }
+    sqlx::query(
+        "UPDATE message_outbox SET status = '취소', updated_at = CURRENT_TIMESTAMP
+         WHERE campaign_id = $1 AND status = '대기'",
+    )
+    .bind(campaign_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        "UPDATE message_campaigns SET status = '취소', finished_at = CURRENT_TIMESTAMP
+         WHERE campaign_id = $1",
+    )
+    .bind(campaign_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    campaign_progress(pool, campaign_id).await
+}
+
+pub async fn get_campaigns_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<CampaignProgress>>> {
+    Ok(Json(
+        sqlx::query_as::<_, CampaignProgress>(&format!(
+            "{} GROUP BY c.campaign_id ORDER BY c.created_at DESC LIMIT 100",
+            PROGRESS_SELECT
+        ))
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CampaignIdInput {
+    #[serde(alias = "campaign_id")]
+    pub campaign_id: String,
+}
+
+pub async fn get_campaign_progress_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<CampaignIdInput>,
+) -> MyceliumResult<Json<CampaignProgress>> {
+    Ok(Json(
+        campaign_progress(&state.pool, &query.campaign_id).await?,
+    ))
+}
+
+pub async fn cancel_campaign_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<CampaignIdInput>,
+) -> MyceliumResult<Json<CampaignProgress>> {
+    let username = claims.username.as_deref().unwrap_or("Admin");
+    Ok(Json(
+        cancel_campaign(&state.pool, &input.campaign_id, username).await?,
+    ))
+}
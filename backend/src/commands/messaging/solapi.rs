@@ -0,0 +1,161 @@
+use super::{DeliveryResult, MessageKind, MessageProvider, OutgoingMessage};
+use crate::error::{MyceliumError, MyceliumResult};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+pub const SOLAPI_API_BASE: &str = "https://api.solapi.com";
+
+/// Client for the Solapi (formerly CoolSMS) messaging API. Every request carries an
+/// HMAC-SHA256 signature over `date + salt`.
+pub struct SolapiProvider {
+    base_url: String,
+    api_key: String,
+    api_secret: String,
+    http: reqwest::Client,
+}
+
+impl SolapiProvider {
+    pub fn new(api_key: &str, api_secret: &str) -> Self {
+        Self {
+            base_url: SOLAPI_API_BASE.to_string(),
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Points the client at another host, e.g. a local mock server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub(super) async fn post(&self, path: &str, body: &Value) -> MyceliumResult<Value> {
+        let date = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let salt = uuid::Uuid::new_v4().simple().to_string();
+        let authorization = authorization_header(&self.api_key, &self.api_secret, &date, &salt)?;
+        let resp = self
+            .http
+            .post(format!("{}{}", self.base_url, path))
+            .header("Authorization", authorization)
+            .json(body)
+            .send()
+            .await?;
+        let status = resp.status();
+        let body: Value = resp.json().await?;
+        if !status.is_success() {
+            return Err(MyceliumError::Internal(format!(
+                "솔라피 API 오류: {}",
+                body["errorMessage"].as_str().unwrap_or("알 수 없는 오류")
+            )));
+        }
+        Ok(body)
+    }
+}
+
+pub fn authorization_header(
+    api_key: &str,
+    api_secret: &str,
+    date: &str,
+    salt: &str,
+) -> MyceliumResult<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(api_secret.as_bytes())
+        .map_err(|e| MyceliumError::Internal(format!("HMAC 키 오류: {}", e)))?;
+    mac.update(date.as_bytes());
+    mac.update(salt.as_bytes());
+    Ok(format!(
+        "HMAC-SHA256 apiKey={}, date={}, salt={}, signature={}",
+        api_key,
+        date,
+        salt,
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+/// Accepted messages come back in `messageList` and rejected ones in `failedMessageList`.
+pub fn parse_send_response(body: &Value) -> Vec<DeliveryResult> {
+    let mut results = Vec::new();
+    if let Some(list) = body["messageList"].as_array() {
+        for m in list {
+            results.push(DeliveryResult::accepted(
+                m["to"].as_str().unwrap_or_default(),
+                m["messageId"].as_str().map(str::to_string),
+            ));
+        }
+    }
+    if let Some(list) = body["failedMessageList"].as_array() {
+        for m in list {
+            results.push(DeliveryResult::failed(
+                m["to"].as_str().unwrap_or_default(),
+                m["statusMessage"].as_str().unwrap_or("발송 실패"),
+            ));
+        }
+    }
+    results
+}
+
+impl MessageProvider for SolapiProvider {
+    fn name(&self) -> &'static str {
+        "solapi"
+    }
+
+    /// `send-many` takes up to 10,000 messages
+    fn max_recipients(&self) -> usize {
+        10_000
+    }
+
+    async fn send(&self, message: &OutgoingMessage) -> MyceliumResult<Vec<DeliveryResult>> {
+        let image_id = match &message.image {
+            Some(image) => {
+                let uploaded = self
+                    .post(
+                        "/storage/v1/files",
+                        &json!({
+                            "file": general_purpose::STANDARD.encode(&image.data),
+                            "name": image.file_name,
+                            "type": "MMS",
+                        }),
+                    )
+                    .await?;
+                Some(
+                    uploaded["fileId"]
+                        .as_str()
+                        .ok_or_else(|| {
+                            MyceliumError::Internal("솔라피 API 오류: 이미지 ID 없음".to_string())
+                        })?
+                        .to_string(),
+                )
+            }
+            None => None,
+        };
+        let messages: Vec<Value> = message
+            .recipients
+            .iter()
+            .map(|r| {
+                let mut m = json!({
+                    "to": r,
+                    "from": message.sender,
+                    "text": message.text,
+                    "type": message.kind.as_str(),
+                });
+                if message.kind != MessageKind::Sms {
+                    m["subject"] = json!(message.subject.clone().unwrap_or_default());
+                }
+                if let Some(id) = &image_id {
+                    m["imageId"] = json!(id);
+                }
+                m
+            })
+            .collect();
+        let body = self
+            .post(
+                "/messages/v4/send-many/detail",
+                &json!({ "messages": messages }),
+            )
+            .await?;
+        Ok(parse_send_response(&body))
+    }
+}
@@ -0,0 +1,559 @@
+use super::{deliver, digits, MessageRequest};
+use crate::commands::config::{load_integration_settings, IntegrationSettings};
+use crate::commands::consent::handle_inbound_opt_out;
+use crate::commands::consultation::create_consultation_internal;
+use crate::commands::crm::MessageChannels;
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    body::Bytes,
+    extract::{Path, State as AxumState},
+    http::HeaderMap,
+    Json,
+};
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::Ordering;
+
+/// Something a provider told us about
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WebhookEvent {
+    /// Whether one recipient of a send got it on the handset
+    Report {
+        #[serde(rename = "messageId")]
+        message_id: String,
+        #[serde(default)]
+        to: Option<String>,
+        delivered: bool,
+        #[serde(default)]
+        error: Option<String>,
+    },
+    /// A message a customer sent to our number or Kakao channel
+    Inbound {
+        #[serde(default)]
+        id: Option<String>,
+        from: String,
+        text: String,
+        /// "sms" or "kakao"
+        #[serde(default = "default_channel")]
+        channel: String,
+    },
+}
+
+fn default_channel() -> String {
+    "sms".to_string()
+}
+
+/// How a provider proves a callback came from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// Hex HMAC-SHA256 of the raw body under the secret
+    BodyHmac,
+    /// The signature set on the console, sent unchanged (NHN Cloud)
+    StaticToken,
+    /// `HMAC-SHA256 apiKey=…, date=…, salt=…, signature=…`, the signature being the hex
+    /// HMAC-SHA256 of date + salt under the secret (Solapi, as on its API calls)
+    SolapiHmac,
+}
+
+/// How long a Solapi callback's date may be off our clock
+pub const SOLAPI_DATE_TOLERANCE_MINUTES: i64 = 15;
+
+/// Header carrying the provider's proof and how to check it. Aligo has no callbacks.
+pub fn signature_header(provider: &str) -> Option<(&'static str, SignatureScheme)> {
+    match provider {
+        "solapi" => Some(("authorization", SignatureScheme::SolapiHmac)),
+        "nhn" => Some(("x-toast-webhook-signature", SignatureScheme::StaticToken)),
+        "mock" => Some(("x-mock-signature", SignatureScheme::BodyHmac)),
+        _ => None,
+    }
+}
+
+fn hmac_matches(secret: &str, message: &[u8], expected: &[u8]) -> MyceliumResult<bool> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| MyceliumError::Internal(format!("HMAC 키 오류: {}", e)))?;
+    mac.update(message);
+    Ok(mac.verify_slice(expected).is_ok())
+}
+
+/// Compares without stopping at the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// The `key=value` parts of a Solapi authorization header
+fn solapi_auth_params(header: &str) -> Option<(&str, &str, &str)> {
+    let params = header.trim().strip_prefix("HMAC-SHA256")?;
+    let (mut date, mut salt, mut signature) = (None, None, None);
+    for part in params.split(',') {
+        match part.trim().split_once('=') {
+            Some(("date", v)) => date = Some(v.trim()),
+            Some(("salt", v)) => salt = Some(v.trim()),
+            Some(("signature", v)) => signature = Some(v.trim()),
+            _ => {}
+        }
+    }
+    Some((date?, salt?, signature?))
+}
+
+/// Accepts the callback when its proof matches one of the configured secrets (the SMS
+/// and the Kakao settings may both use the provider). `now` bounds Solapi's date.
+pub fn verify_signature(
+    provider: &str,
+    secrets: &[String],
+    signature: Option<&str>,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> MyceliumResult<()> {
+    let (_, scheme) = signature_header(provider).ok_or_else(|| {
+        MyceliumError::Validation(format!("지원하지 않는 수신 알림입니다: {}", provider))
+    })?;
+    if secrets.is_empty() {
+        return Err(MyceliumError::Auth(
+            "수신 알림 시크릿이 설정되지 않았습니다.".to_string(),
+        ));
+    }
+    let invalid = || MyceliumError::Auth("수신 알림 서명이 올바르지 않습니다.".to_string());
+    let signature = signature.map(str::trim).ok_or_else(invalid)?;
+    match scheme {
+        SignatureScheme::StaticToken => {
+            if secrets
+                .iter()
+                .any(|s| constant_time_eq(s.as_bytes(), signature.as_bytes()))
+            {
+                return Ok(());
+            }
+        }
+        SignatureScheme::BodyHmac => {
+            let expected = hex::decode(signature).map_err(|_| invalid())?;
+            for secret in secrets {
+                if hmac_matches(secret, body, &expected)? {
+                    return Ok(());
+                }
+            }
+        }
+        SignatureScheme::SolapiHmac => {
+            let (date, salt, signature) = solapi_auth_params(signature).ok_or_else(invalid)?;
+            let sent_at = DateTime::parse_from_rfc3339(date).map_err(|_| invalid())?;
+            if (now - sent_at.with_timezone(&Utc)).num_minutes().abs()
+                > SOLAPI_DATE_TOLERANCE_MINUTES
+            {
+                return Err(MyceliumError::Auth(
+                    "수신 알림 시각이 너무 오래되었습니다.".to_string(),
+                ));
+            }
+            let expected = hex::decode(signature).map_err(|_| invalid())?;
+            let message = format!("{}{}", date, salt);
+            for secret in secrets {
+                if hmac_matches(secret, message.as_bytes(), &expected)? {
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Err(invalid())
+}
+
+/// Webhook secrets of the settings that send through the provider
+pub fn webhook_secrets(settings: &IntegrationSettings, provider: &str) -> Vec<String> {
+    let sms = settings
+        .sms
+        .as_ref()
+        .map(|s| (normalize_provider(&s.provider), &s.webhook_secret));
+    let kakao = settings
+        .kakao
+        .as_ref()
+        .map(|k| (normalize_provider(&k.provider), &k.webhook_secret));
+    [sms, kakao]
+        .into_iter()
+        .flatten()
+        .filter(|(p, secret)| *p == provider && !secret.trim().is_empty())
+        .map(|(_, secret)| secret.trim().to_string())
+        .collect()
+}
+
+/// CoolSMS is Solapi under its former name
+fn normalize_provider(provider: &str) -> &str {
+    match provider {
+        "coolsms" => "solapi",
+        other => other,
+    }
+}
+
+/// A string or a number
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// One callback may carry a single event or a list
+fn items<'a>(body: &'a Value, list_keys: &[&str]) -> Vec<&'a Value> {
+    if let Some(list) = body.as_array() {
+        return list.iter().collect();
+    }
+    for key in list_keys {
+        if let Some(list) = body[*key].as_array() {
+            return list.iter().collect();
+        }
+    }
+    vec![body]
+}
+
+/// Solapi: reports carry `messageId`, `to` and `statusCode` ("4000" is delivered); replies
+/// have type "MO" (or "KAKAO_MO") with `from` and `text`.
+fn parse_solapi(body: &Value) -> Vec<WebhookEvent> {
+    items(body, &["messageList", "events"])
+        .into_iter()
+        .filter_map(|m| {
+            let kind = m["type"].as_str().unwrap_or_default();
+            if kind.ends_with("MO") {
+                return Some(WebhookEvent::Inbound {
+                    id: text(&m["messageId"]),
+                    from: text(&m["from"])?,
+                    text: text(&m["text"]).unwrap_or_default(),
+                    channel: if kind.starts_with("KAKAO") {
+                        "kakao"
+                    } else {
+                        "sms"
+                    }
+                    .to_string(),
+                });
+            }
+            let status = text(&m["statusCode"])?;
+            let delivered = status == "4000";
+            Some(WebhookEvent::Report {
+                message_id: text(&m["messageId"])?,
+                to: text(&m["to"]),
+                delivered,
+                error: if delivered {
+                    None
+                } else {
+                    Some(text(&m["statusMessage"]).unwrap_or(status))
+                },
+            })
+        })
+        .collect()
+}
+
+/// NHN Cloud: reports carry `requestId`, `recipientSeq`, `recipientNo` and `resultCode`
+/// ("1000" is delivered), matching the "requestId:seq" ids we log; replies carry `moNo`
+/// (the sender) and `body`.
+fn parse_nhn(body: &Value) -> Vec<WebhookEvent> {
+    items(body, &["hooks", "messages"])
+        .into_iter()
+        .filter_map(|m| {
+            if let Some(from) = text(&m["moNo"]) {
+                return Some(WebhookEvent::Inbound {
+                    id: text(&m["moId"]),
+                    from,
+                    text: text(&m["body"]).unwrap_or_default(),
+                    channel: "sms".to_string(),
+                });
+            }
+            let request_id = text(&m["requestId"])?;
+            let code = text(&m["resultCode"])?;
+            let delivered = code == "1000";
+            Some(WebhookEvent::Report {
+                message_id: match text(&m["recipientSeq"]) {
+                    Some(seq) => format!("{}:{}", request_id, seq),
+                    None => request_id,
+                },
+                to: text(&m["recipientNo"]),
+                delivered,
+                error: if delivered {
+                    None
+                } else {
+                    Some(
+                        text(&m["resultMessage"])
+                            .or_else(|| text(&m["resultCodeName"]))
+                            .unwrap_or(code),
+                    )
+                },
+            })
+        })
+        .collect()
+}
+
+pub fn parse_events(provider: &str, body: &Value) -> MyceliumResult<Vec<WebhookEvent>> {
+    match provider {
+        "solapi" => Ok(parse_solapi(body)),
+        "nhn" => Ok(parse_nhn(body)),
+        "mock" => Ok(serde_json::from_value(Value::Array(
+            items(body, &["events"]).into_iter().cloned().collect(),
+        ))?),
+        other => Err(MyceliumError::Validation(format!(
+            "지원하지 않는 수신 알림입니다: {}",
+            other
+        ))),
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct WebhookOutcome {
+    pub delivered: usize,
+    pub failed: usize,
+    /// SMS sent because a KakaoTalk message did not arrive
+    pub fallbacks: usize,
+    pub replies: usize,
+    pub opt_outs: usize,
+    /// Reports for sends we have no record of, and repeated callbacks
+    pub ignored: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct ReportedLog {
+    log_id: String,
+    mobile_number: String,
+    content: String,
+    message_type: Option<String>,
+    is_ad: bool,
+}
+
+/// Applies the events to `sms_logs` and the customers' consultations. A report is only
+/// taken once per log row. A KakaoTalk message reported as not delivered is sent again by
+/// SMS when the Kakao settings ask for it (not for advertising, which needs SMS consent).
+/// Replies withdraw consent on an opt-out keyword and otherwise become a consultation.
+pub async fn handle_webhook_events(
+    pool: &DbPool,
+    channels: Option<&MessageChannels>,
+    provider: &str,
+    events: Vec<WebhookEvent>,
+) -> MyceliumResult<WebhookOutcome> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut outcome = WebhookOutcome::default();
+    for event in events {
+        match event {
+            WebhookEvent::Report {
+                message_id,
+                to,
+                delivered,
+                error,
+            } => {
+                let rows: Vec<ReportedLog> = sqlx::query_as(
+                    "UPDATE sms_logs SET
+                        status = CASE WHEN $3 THEN status ELSE '실패' END,
+                        error_message = CASE WHEN $3 THEN error_message ELSE $4 END,
+                        cost = CASE WHEN $3 THEN cost ELSE 0 END,
+                        delivered_at = CASE WHEN $3 THEN CURRENT_TIMESTAMP END,
+                        reported_at = CURRENT_TIMESTAMP
+                     WHERE provider = $1 AND provider_message_id = $2
+                       AND status = '성공' AND reported_at IS NULL
+                       AND ($5::text IS NULL OR regexp_replace(mobile_number, '[^0-9]', '', 'g') = $5)
+                     RETURNING log_id, mobile_number, content, message_type, is_ad",
+                )
+                .bind(provider)
+                .bind(&message_id)
+                .bind(delivered)
+                .bind(error.as_deref().unwrap_or("수신 실패"))
+                .bind(to.as_deref().map(digits).filter(|d| !d.is_empty()))
+                .fetch_all(pool)
+                .await?;
+                if rows.is_empty() {
+                    outcome.ignored += 1;
+                    continue;
+                }
+                if delivered {
+                    outcome.delivered += rows.len();
+                    continue;
+                }
+                outcome.failed += rows.len();
+                for row in rows {
+                    if send_kakao_fallback(pool, channels, &row).await? {
+                        outcome.fallbacks += 1;
+                    }
+                }
+            }
+            WebhookEvent::Inbound {
+                id,
+                from,
+                text,
+                channel,
+            } => {
+                let number = digits(&from);
+                if number.is_empty() {
+                    outcome.ignored += 1;
+                    continue;
+                }
+                let key =
+                    inbound_dedupe_key(id.as_deref(), &number, &text, Local::now().naive_local());
+                let inbound_id: Option<i32> = sqlx::query_scalar(
+                    "INSERT INTO inbound_messages (provider, provider_message_id, channel, mobile_number, content)
+                     VALUES ($1, $2, $3, $4, $5)
+                     ON CONFLICT (provider, provider_message_id) DO NOTHING
+                     RETURNING inbound_id",
+                )
+                .bind(provider)
+                .bind(&key)
+                .bind(&channel)
+                .bind(&number)
+                .bind(&text)
+                .fetch_optional(pool)
+                .await?;
+                let Some(inbound_id) = inbound_id else {
+                    outcome.ignored += 1;
+                    continue;
+                };
+                outcome.replies += 1;
+                if handle_inbound_opt_out(pool, &from, &text).await? {
+                    outcome.opt_outs += 1;
+                    sqlx::query(
+                        "UPDATE inbound_messages SET opted_out = TRUE WHERE inbound_id = $1",
+                    )
+                    .bind(inbound_id)
+                    .execute(pool)
+                    .await?;
+                    continue;
+                }
+                attach_reply(pool, inbound_id, &from, &text, &channel).await?;
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+/// Identifies an inbound message for de-duplication: the provider's id, or when the
+/// provider sends none, a hash of the sender, the text and the minute it arrived in
+pub fn inbound_dedupe_key(
+    id: Option<&str>,
+    from_digits: &str,
+    text: &str,
+    received_at: NaiveDateTime,
+) -> String {
+    match id.map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => id.to_string(),
+        None => {
+            let minute = received_at.format("%Y-%m-%d %H:%M");
+            let hash = Sha256::digest(format!("{}|{}|{}", from_digits, minute, text));
+            format!("sha256:{}", hex::encode(hash))
+        }
+    }
+}
+
+async fn send_kakao_fallback(
+    pool: &DbPool,
+    channels: Option<&MessageChannels>,
+    row: &ReportedLog,
+) -> MyceliumResult<bool> {
+    let is_kakao = matches!(
+        row.message_type.as_deref(),
+        Some("ALIMTALK") | Some("FRIENDTALK")
+    );
+    let Some(channels) = channels else {
+        return Ok(false);
+    };
+    let fallback_enabled = channels
+        .kakao
+        .as_ref()
+        .map(|(_, kakao)| kakao.sms_fallback)
+        .unwrap_or(true);
+    if !is_kakao || row.is_ad || !fallback_enabled {
+        return Ok(false);
+    }
+    let already: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sms_logs WHERE fallback_of = $1
+                        AND regexp_replace(mobile_number, '[^0-9]', '', 'g') = $2)",
+    )
+    .bind(&row.log_id)
+    .bind(digits(&row.mobile_number))
+    .fetch_one(pool)
+    .await?;
+    if already {
+        return Ok(false);
+    }
+    let request = MessageRequest {
+        sender: channels.sender.clone(),
+        recipients: vec![row.mobile_number.clone()],
+        text: row.content.clone(),
+        fallback_of: Some(row.log_id.clone()),
+        ..Default::default()
+    };
+    // The attempt is logged either way; a gateway error must not fail the callback
+    Ok(deliver(pool, &channels.sms, request, &channels.sms_costs)
+        .await
+        .map(|summary| summary.sent > 0)
+        .unwrap_or(false))
+}
+
+/// Files the reply as a consultation of the customer with that number (a guest when there
+/// is no single match).
+async fn attach_reply(
+    pool: &DbPool,
+    inbound_id: i32,
+    from: &str,
+    text: &str,
+    channel: &str,
+) -> MyceliumResult<()> {
+    let customer: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT MIN(customer_id), MIN(customer_name) FROM customers
+         WHERE regexp_replace(mobile_number, '[^0-9]', '', 'g') = $1
+         HAVING COUNT(*) = 1",
+    )
+    .bind(digits(from))
+    .fetch_optional(pool)
+    .await?;
+    let (customer_id, customer_name) = customer.unwrap_or((None, None));
+    let channel_name = if channel == "kakao" {
+        "카카오톡"
+    } else {
+        "문자"
+    };
+    let consult_id = create_consultation_internal(
+        pool,
+        customer_id.clone(),
+        customer_name.unwrap_or_else(|| from.to_string()),
+        from.to_string(),
+        channel_name.to_string(),
+        String::new(),
+        "기타".to_string(),
+        format!("{} 회신", channel_name),
+        text.to_string(),
+        "보통".to_string(),
+    )
+    .await?;
+    sqlx::query(
+        "UPDATE inbound_messages SET customer_id = $1, consult_id = $2 WHERE inbound_id = $3",
+    )
+    .bind(customer_id)
+    .bind(consult_id)
+    .bind(inbound_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Delivery reports and replies from an SMS/Kakao provider. Public: the signature stands
+/// in for a login.
+pub async fn messaging_webhook_axum(
+    AxumState(state): AxumState<AppState>,
+    Path(provider): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> MyceliumResult<Json<WebhookOutcome>> {
+    let provider = normalize_provider(&provider).to_string();
+    let (header, _) = signature_header(&provider).ok_or_else(|| {
+        MyceliumError::Validation(format!("지원하지 않는 수신 알림입니다: {}", provider))
+    })?;
+    let settings = load_integration_settings()?;
+    verify_signature(
+        &provider,
+        &webhook_secrets(&settings, &provider),
+        headers.get(header).and_then(|v| v.to_str().ok()),
+        &body,
+        Utc::now(),
+    )?;
+    let events = parse_events(&provider, &serde_json::from_slice(&body)?)?;
+    // Without working send settings reports are still recorded, just with no SMS fallback
+    let channels = MessageChannels::from_settings(settings).ok();
+    Ok(Json(
+        handle_webhook_events(&state.pool, channels.as_ref(), &provider, events).await?,
+    ))
+}
@@ -1,11 +1,15 @@
+pub mod address;
 pub mod ai;
 pub mod analysis;
+pub mod automation;
 pub mod backup;
 pub mod config;
+pub mod consent;
 pub mod consultation;
 pub mod courier;
 pub mod crm;
 pub mod customer;
+pub mod customer_merge;
 pub mod dashboard;
 pub mod event;
 pub mod experience;
@@ -13,10 +17,22 @@ pub mod finance;
 pub mod iot;
 pub mod ledger;
 pub mod logistics;
+pub mod membership;
+pub mod message_template;
+pub mod messaging;
+pub mod packing;
 pub mod preset;
+pub mod privacy;
+pub mod points;
 pub mod product;
 pub mod production;
+pub mod recommendation;
+pub mod referral;
+pub mod rfm;
 pub mod sales;
+pub mod segment;
 pub mod schedule;
+pub mod shipping_calendar;
 pub mod system;
+pub mod timeline;
 pub mod utility;
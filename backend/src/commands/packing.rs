@@ -0,0 +1,605 @@
+#![allow(non_snake_case)]
+use crate::commands::logistics::{get_pending_shipments_for_date_internal, PendingShipment};
+use crate::db::{CompanyInfo, DbPool};
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::state::AppState;
+use axum::extract::{Query, State as AxumState};
+use chrono::NaiveDate;
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PickListItem {
+    pub product_name: String,
+    pub specification: Option<String>,
+    pub total_quantity: i64,
+    pub order_count: i64,
+}
+
+/// Sums quantities per product/specification across all pending shipments.
+/// Items are returned by product name so the sheet follows the storage layout.
+pub fn aggregate_pick_list(shipments: &[PendingShipment]) -> Vec<PickListItem> {
+    let mut items: Vec<PickListItem> = Vec::new();
+    for s in shipments {
+        match items
+            .iter_mut()
+            .find(|i| i.product_name == s.product_name && i.specification == s.specification)
+        {
+            Some(item) => {
+                item.total_quantity += s.quantity as i64;
+                item.order_count += 1;
+            }
+            None => items.push(PickListItem {
+                product_name: s.product_name.clone(),
+                specification: s.specification.clone(),
+                total_quantity: s.quantity as i64,
+                order_count: 1,
+            }),
+        }
+    }
+    items.sort_by(|a, b| {
+        a.product_name
+            .cmp(&b.product_name)
+            .then_with(|| a.specification.cmp(&b.specification))
+    });
+    items
+}
+
+fn load_font(doc: &PdfDocumentReference) -> MyceliumResult<IndirectFontRef> {
+    let font_path = std::path::Path::new("C:\\Windows\\Fonts\\malgun.ttf");
+    doc.add_external_font(
+        File::open(font_path).map_err(|e| MyceliumError::Internal(e.to_string()))?,
+    )
+    .map_err(|e| MyceliumError::Internal(e.to_string()))
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() > max_chars {
+        text.chars().take(max_chars - 1).collect::<String>() + ".."
+    } else {
+        text.to_string()
+    }
+}
+
+pub async fn generate_pick_list_pdf(
+    pool: &DbPool,
+    save_path: String,
+    ship_date: NaiveDate,
+) -> MyceliumResult<()> {
+    let shipments = get_pending_shipments_for_date_internal(pool, ship_date, None).await?;
+    let items = aggregate_pick_list(&shipments);
+    let order_total = shipments.len();
+
+    tokio::task::spawn_blocking(move || {
+        let (doc, page1, layer1) = PdfDocument::new("Pick List", Mm(210.0), Mm(297.0), "Layer 1");
+        let font = load_font(&doc)?;
+
+        let mut current_layer = doc.get_page(page1).get_layer(layer1);
+        let mut current_y: f32 = 270.0;
+        let margin_x: f32 = 15.0;
+        let content_w: f32 = 180.0;
+
+        let draw_text = |layer: &PdfLayerReference, x: f32, y: f32, size: f32, txt: &str| {
+            layer.begin_text_section();
+            layer.set_font(&font, size);
+            layer.set_text_cursor(Mm(x), Mm(y));
+            layer.write_text(txt, &font);
+            layer.end_text_section();
+        };
+
+        let draw_line = |layer: &PdfLayerReference, x1: f32, y1: f32, x2: f32, y2: f32| {
+            let line = Line::from_iter(vec![
+                (Point::new(Mm(x1), Mm(y1)), false),
+                (Point::new(Mm(x2), Mm(y2)), false),
+            ]);
+            layer.add_line(line);
+        };
+
+        let draw_rect = |layer: &PdfLayerReference, x: f32, y: f32, w: f32, h: f32| {
+            let pts = vec![
+                (Point::new(Mm(x), Mm(y)), false),
+                (Point::new(Mm(x + w), Mm(y)), false),
+                (Point::new(Mm(x + w), Mm(y + h)), false),
+                (Point::new(Mm(x), Mm(y + h)), false),
+            ];
+            let polygon = Polygon {
+                rings: vec![pts],
+                mode: PaintMode::Stroke,
+                winding_order: WindingOrder::NonZero,
+            };
+            layer.add_polygon(polygon);
+        };
+
+        draw_text(
+            &current_layer,
+            margin_x,
+            current_y,
+            20.0,
+            "피킹 리스트 (Pick List)",
+        );
+        current_y -= 8.0;
+        draw_text(
+            &current_layer,
+            margin_x,
+            current_y,
+            10.0,
+            &format!(
+                "출고일: {} | 대상 주문: {}건 | 품목: {}종",
+                ship_date.format("%Y-%m-%d"),
+                order_total,
+                items.len()
+            ),
+        );
+        current_y -= 12.0;
+
+        let headers = ["No", "품목", "규격", "수량", "주문건수", "확인"];
+        let widths = [12.0, 78.0, 40.0, 18.0, 20.0, 12.0];
+        let draw_header = |layer: &PdfLayerReference, y: f32| {
+            let mut cx = margin_x;
+            for (i, h) in headers.iter().enumerate() {
+                draw_text(layer, cx + 1.0, y, 9.0, h);
+                cx += widths[i];
+            }
+            draw_line(layer, margin_x, y - 3.0, margin_x + content_w, y - 3.0);
+        };
+
+        draw_header(&current_layer, current_y);
+        current_y -= 9.0;
+
+        let mut quantity_total: i64 = 0;
+        for (idx, item) in items.iter().enumerate() {
+            if current_y < 25.0 {
+                let (p, l) = doc.add_page(Mm(210.0), Mm(297.0), "Pick List");
+                current_layer = doc.get_page(p).get_layer(l);
+                current_y = 270.0;
+                draw_header(&current_layer, current_y);
+                current_y -= 9.0;
+            }
+
+            let mut cx = margin_x;
+            draw_text(
+                &current_layer,
+                cx + 1.0,
+                current_y,
+                10.0,
+                &(idx + 1).to_string(),
+            );
+            cx += widths[0];
+            draw_text(
+                &current_layer,
+                cx + 1.0,
+                current_y,
+                10.0,
+                &truncate(&item.product_name, 28),
+            );
+            cx += widths[1];
+            draw_text(
+                &current_layer,
+                cx + 1.0,
+                current_y,
+                10.0,
+                &truncate(item.specification.as_deref().unwrap_or("-"), 14),
+            );
+            cx += widths[2];
+            draw_text(
+                &current_layer,
+                cx + 1.0,
+                current_y,
+                10.0,
+                &item.total_quantity.to_string(),
+            );
+            cx += widths[3];
+            draw_text(
+                &current_layer,
+                cx + 1.0,
+                current_y,
+                10.0,
+                &item.order_count.to_string(),
+            );
+            cx += widths[4];
+            draw_rect(&current_layer, cx + 3.0, current_y - 1.0, 4.0, 4.0);
+
+            draw_line(
+                &current_layer,
+                margin_x,
+                current_y - 3.0,
+                margin_x + content_w,
+                current_y - 3.0,
+            );
+            quantity_total += item.total_quantity;
+            current_y -= 8.0;
+        }
+
+        current_y -= 2.0;
+        draw_text(
+            &current_layer,
+            margin_x + widths[0] + widths[1] + 1.0,
+            current_y,
+            10.0,
+            "합계",
+        );
+        draw_text(
+            &current_layer,
+            margin_x + widths[0] + widths[1] + widths[2] + 1.0,
+            current_y,
+            10.0,
+            &quantity_total.to_string(),
+        );
+        draw_text(
+            &current_layer,
+            margin_x + widths[0] + widths[1] + widths[2] + widths[3] + 1.0,
+            current_y,
+            10.0,
+            &order_total.to_string(),
+        );
+
+        draw_text(
+            &current_layer,
+            margin_x,
+            10.0,
+            8.0,
+            &format!(
+                "출력일시: {} | Mycelium Agri-Commerce OS",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+            ),
+        );
+
+        let file = File::create(save_path).map_err(|e| MyceliumError::Internal(e.to_string()))?;
+        doc.save(&mut BufWriter::new(file))
+            .map_err(|e| MyceliumError::Internal(e.to_string()))?;
+        Ok::<(), MyceliumError>(())
+    })
+    .await
+    .map_err(|e| MyceliumError::Internal(e.to_string()))??;
+
+    Ok(())
+}
+
+pub async fn generate_packing_slips_pdf(
+    pool: &DbPool,
+    save_path: String,
+    ship_date: NaiveDate,
+    sales_ids: Option<Vec<String>>,
+) -> MyceliumResult<()> {
+    let company_info = sqlx::query_as::<_, CompanyInfo>("SELECT * FROM company_info LIMIT 1")
+        .fetch_optional(pool)
+        .await?
+        .unwrap_or_default();
+
+    let shipments = get_pending_shipments_for_date_internal(pool, ship_date, sales_ids).await?;
+    if shipments.is_empty() {
+        return Err(MyceliumError::Validation(
+            "출고 대기 중인 주문이 없습니다.".to_string(),
+        ));
+    }
+
+    // QR matrices are built up front so encoding errors surface before drawing starts
+    let mut qr_codes = Vec::with_capacity(shipments.len());
+    for s in &shipments {
+        let matrix = qrcode_generator::to_matrix(&s.sales_id, qrcode_generator::QrCodeEcc::Low)
+            .map_err(|e| MyceliumError::Internal(e.to_string()))?;
+        qr_codes.push(matrix);
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let (doc, page1, layer1) =
+            PdfDocument::new("Packing Slips", Mm(210.0), Mm(297.0), "Layer 1");
+        let font = load_font(&doc)?;
+
+        let margin_x: f32 = 15.0;
+        let content_w: f32 = 180.0;
+        let slip_h: f32 = 138.0;
+
+        let draw_text = |layer: &PdfLayerReference, x: f32, y: f32, size: f32, txt: &str| {
+            layer.begin_text_section();
+            layer.set_font(&font, size);
+            layer.set_text_cursor(Mm(x), Mm(y));
+            layer.write_text(txt, &font);
+            layer.end_text_section();
+        };
+
+        let draw_line = |layer: &PdfLayerReference, x1: f32, y1: f32, x2: f32, y2: f32| {
+            let line = Line::from_iter(vec![
+                (Point::new(Mm(x1), Mm(y1)), false),
+                (Point::new(Mm(x2), Mm(y2)), false),
+            ]);
+            layer.add_line(line);
+        };
+
+        let draw_rect = |layer: &PdfLayerReference, x: f32, y: f32, w: f32, h: f32, mode| {
+            let pts = vec![
+                (Point::new(Mm(x), Mm(y)), false),
+                (Point::new(Mm(x + w), Mm(y)), false),
+                (Point::new(Mm(x + w), Mm(y + h)), false),
+                (Point::new(Mm(x), Mm(y + h)), false),
+            ];
+            let polygon = Polygon {
+                rings: vec![pts],
+                mode,
+                winding_order: WindingOrder::NonZero,
+            };
+            layer.add_polygon(polygon);
+        };
+
+        let draw_qr =
+            |layer: &PdfLayerReference, x: f32, y: f32, size: f32, matrix: &[Vec<bool>]| {
+                let modules = matrix.len().max(1) as f32;
+                let cell = size / modules;
+                for (row, line) in matrix.iter().enumerate() {
+                    for (col, dark) in line.iter().enumerate() {
+                        if *dark {
+                            draw_rect(
+                                layer,
+                                x + col as f32 * cell,
+                                y + size - (row as f32 + 1.0) * cell,
+                                cell,
+                                cell,
+                                PaintMode::Fill,
+                            );
+                        }
+                    }
+                }
+            };
+
+        let mut current_layer = doc.get_page(page1).get_layer(layer1);
+        for (idx, (s, qr)) in shipments.iter().zip(qr_codes.iter()).enumerate() {
+            // Two slips per A4 page, cut along the middle line
+            if idx > 0 && idx % 2 == 0 {
+                let (p, l) = doc.add_page(Mm(210.0), Mm(297.0), "Packing Slips");
+                current_layer = doc.get_page(p).get_layer(l);
+            }
+            let top: f32 = if idx % 2 == 0 {
+                285.0
+            } else {
+                285.0 - slip_h - 6.0
+            };
+            let bottom = top - slip_h;
+
+            current_layer.set_outline_thickness(0.5);
+            draw_rect(
+                &current_layer,
+                margin_x,
+                bottom,
+                content_w,
+                slip_h,
+                PaintMode::Stroke,
+            );
+
+            let mut y = top - 12.0;
+            draw_text(
+                &current_layer,
+                margin_x + 5.0,
+                y,
+                16.0,
+                "납품서 (Packing Slip)",
+            );
+            draw_text(
+                &current_layer,
+                margin_x + 5.0,
+                y - 7.0,
+                9.0,
+                &format!(
+                    "주문번호: {} | 주문일: {}",
+                    s.sales_id,
+                    s.order_date
+                        .map(|d| d.format("%Y-%m-%d").to_string())
+                        .unwrap_or_default()
+                ),
+            );
+            draw_qr(
+                &current_layer,
+                margin_x + content_w - 35.0,
+                top - 35.0,
+                30.0,
+                qr,
+            );
+
+            y -= 30.0;
+            draw_line(&current_layer, margin_x, y, margin_x + content_w, y);
+            y -= 8.0;
+
+            let receiver = s
+                .shipping_name
+                .as_deref()
+                .or(s.customer_name.as_deref())
+                .unwrap_or("-");
+            let mobile = s
+                .shipping_mobile_number
+                .as_deref()
+                .or(s.customer_mobile_number.as_deref())
+                .unwrap_or("-");
+            draw_text(
+                &current_layer,
+                margin_x + 5.0,
+                y,
+                11.0,
+                &format!("받는 분: {}", receiver),
+            );
+            draw_text(
+                &current_layer,
+                margin_x + 95.0,
+                y,
+                11.0,
+                &format!("연락처: {}", mobile),
+            );
+            y -= 7.0;
+            draw_text(
+                &current_layer,
+                margin_x + 5.0,
+                y,
+                10.0,
+                &format!(
+                    "주소: ({}) {}",
+                    s.shipping_zip_code.as_deref().unwrap_or("-"),
+                    truncate(s.shipping_address_primary.as_deref().unwrap_or(""), 45)
+                ),
+            );
+            y -= 6.0;
+            draw_text(
+                &current_layer,
+                margin_x + 17.0,
+                y,
+                10.0,
+                &truncate(s.shipping_address_detail.as_deref().unwrap_or(""), 45),
+            );
+            y -= 10.0;
+
+            draw_text(&current_layer, margin_x + 5.0, y, 9.0, "품목");
+            draw_text(&current_layer, margin_x + 100.0, y, 9.0, "규격");
+            draw_text(&current_layer, margin_x + 150.0, y, 9.0, "수량");
+            draw_line(
+                &current_layer,
+                margin_x + 5.0,
+                y - 3.0,
+                margin_x + content_w - 5.0,
+                y - 3.0,
+            );
+            y -= 9.0;
+            draw_text(
+                &current_layer,
+                margin_x + 5.0,
+                y,
+                11.0,
+                &truncate(&s.product_name, 30),
+            );
+            draw_text(
+                &current_layer,
+                margin_x + 100.0,
+                y,
+                11.0,
+                &truncate(s.specification.as_deref().unwrap_or("-"), 16),
+            );
+            draw_text(
+                &current_layer,
+                margin_x + 150.0,
+                y,
+                11.0,
+                &s.quantity.to_string(),
+            );
+            y -= 14.0;
+
+            draw_text(&current_layer, margin_x + 5.0, y, 10.0, "[고객 메시지]");
+            y -= 7.0;
+            let message = s.memo.as_deref().map(str::trim).unwrap_or("");
+            let message = if message.is_empty() { "-" } else { message };
+            let chars: Vec<char> = message.chars().collect();
+            for chunk in chars.chunks(45).take(4) {
+                draw_text(
+                    &current_layer,
+                    margin_x + 8.0,
+                    y,
+                    10.0,
+                    &chunk.iter().collect::<String>(),
+                );
+                y -= 6.0;
+            }
+
+            draw_text(
+                &current_layer,
+                margin_x + 5.0,
+                bottom + 6.0,
+                8.0,
+                &format!(
+                    "보내는 분: {} {}",
+                    company_info.company_name,
+                    company_info.phone_number.as_deref().unwrap_or("")
+                ),
+            );
+        }
+
+        let file = File::create(save_path).map_err(|e| MyceliumError::Internal(e.to_string()))?;
+        doc.save(&mut BufWriter::new(file))
+            .map_err(|e| MyceliumError::Internal(e.to_string()))?;
+        Ok::<(), MyceliumError>(())
+    })
+    .await
+    .map_err(|e| MyceliumError::Internal(e.to_string()))??;
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PackingQuery {
+    pub date: Option<String>,
+    pub sales_ids: Option<String>,
+}
+
+impl PackingQuery {
+    fn ship_date(&self) -> MyceliumResult<NaiveDate> {
+        match self.date.as_deref().filter(|d| !d.is_empty()) {
+            Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                .map_err(|_| MyceliumError::Validation(format!("Invalid date: {}", d))),
+            None => Ok(chrono::Local::now().date_naive()),
+        }
+    }
+
+    fn sales_ids(&self) -> Option<Vec<String>> {
+        self.sales_ids.as_ref().map(|ids| {
+            ids.split(',')
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect()
+        })
+    }
+}
+
+fn pdf_response(save_path: &str, file_name: &str) -> MyceliumResult<axum::response::Response> {
+    use axum::response::IntoResponse;
+    let file_content =
+        std::fs::read(save_path).map_err(|e| MyceliumError::Internal(e.to_string()))?;
+    let _ = std::fs::remove_file(save_path);
+
+    Ok((
+        [
+            (axum::http::header::CONTENT_TYPE, "application/pdf"),
+            (
+                axum::http::header::CONTENT_DISPOSITION,
+                &format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        file_content,
+    )
+        .into_response())
+}
+
+pub async fn generate_pick_list_pdf_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<PackingQuery>,
+) -> MyceliumResult<axum::response::Response> {
+    let ship_date = query.ship_date()?;
+    let file_name = format!(
+        "pick_list_{}_{}.pdf",
+        ship_date.format("%Y%m%d"),
+        chrono::Local::now().format("%H%M%S")
+    );
+    let save_path = std::env::temp_dir()
+        .join(&file_name)
+        .to_string_lossy()
+        .to_string();
+
+    generate_pick_list_pdf(&state.pool, save_path.clone(), ship_date).await?;
+    pdf_response(&save_path, &file_name)
+}
+
+pub async fn generate_packing_slips_pdf_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<PackingQuery>,
+) -> MyceliumResult<axum::response::Response> {
+    let ship_date = query.ship_date()?;
+    let file_name = format!(
+        "packing_slips_{}_{}.pdf",
+        ship_date.format("%Y%m%d"),
+        chrono::Local::now().format("%H%M%S")
+    );
+    let save_path = std::env::temp_dir()
+        .join(&file_name)
+        .to_string_lossy()
+        .to_string();
+
+    generate_packing_slips_pdf(&state.pool, save_path.clone(), ship_date, query.sales_ids())
+        .await?;
+    pdf_response(&save_path, &file_name)
+}
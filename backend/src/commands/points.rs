@@ -0,0 +1,783 @@
+use crate::commands::customer::CustomerIdQuery;
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::atomic::Ordering;
+
+/// Channel name used by earn rules for orders without a mall
+pub const DIRECT_CHANNEL: &str = "direct";
+
+/// Sales in these states have been fully refunded
+const REFUNDED_STATUSES: [&str; 3] = ["취소", "반품", "반품완료"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PointsSettings {
+    pub enabled: bool,
+    pub expiry_months: i32,
+    pub earn_start_date: NaiveDate,
+    pub min_redeem_points: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PointEarnRule {
+    #[serde(default)]
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub membership_level: Option<String>,
+    pub earn_rate: f64,
+}
+
+impl PointEarnRule {
+    fn matches(&self, channel: &str, level: Option<&str>) -> bool {
+        let channel_ok = self
+            .channel
+            .as_deref()
+            .is_none_or(|c| c.eq_ignore_ascii_case(channel));
+        let level_ok = match self.membership_level.as_deref() {
+            None => true,
+            Some(l) => level == Some(l),
+        };
+        channel_ok && level_ok
+    }
+
+    /// A rule naming a channel beats one naming only a level, which beats a catch-all.
+    fn specificity(&self) -> u8 {
+        (self.channel.is_some() as u8) * 2 + self.membership_level.is_some() as u8
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PointsConfig {
+    pub settings: PointsSettings,
+    pub rules: Vec<PointEarnRule>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PointEntry {
+    pub entry_id: i32,
+    pub entry_type: String,
+    pub points: i32,
+    pub remaining: i32,
+    pub expires_at: Option<NaiveDate>,
+    pub sales_id: Option<String>,
+    pub memo: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomerPoints {
+    pub customer_id: String,
+    pub balance: i64,
+    /// Points that expire within the next 30 days
+    pub expiring_soon: i64,
+    pub entries: Vec<PointEntry>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PointsJobResult {
+    pub earned: usize,
+    pub referral_rewards: usize,
+    pub reversed: usize,
+    pub expired: usize,
+}
+
+/// Earn rate (percent) for an order, from the most specific matching rule.
+pub fn earn_rate_for(rules: &[PointEarnRule], channel: &str, level: Option<&str>) -> f64 {
+    rules
+        .iter()
+        .filter(|r| r.matches(channel, level))
+        .max_by_key(|r| r.specificity())
+        .map(|r| r.earn_rate)
+        .unwrap_or(0.0)
+}
+
+pub fn points_for_amount(amount: i64, rate: f64) -> i32 {
+    if amount <= 0 || rate <= 0.0 {
+        return 0;
+    }
+    (amount as f64 * rate / 100.0).floor() as i32
+}
+
+/// How many of `points` should be reversed for a sale refunded by `refunded` out of `total`.
+pub fn reversal_target(points: i32, refunded: i64, total: i64, fully_refunded: bool) -> i32 {
+    if fully_refunded {
+        return points;
+    }
+    if points <= 0 || total <= 0 || refunded <= 0 {
+        return 0;
+    }
+    let share = refunded.min(total) as f64 / total as f64;
+    (points as f64 * share).round() as i32
+}
+
+pub async fn load_points_config(pool: &DbPool) -> MyceliumResult<PointsConfig> {
+    let settings = sqlx::query_as::<_, PointsSettings>(
+        "SELECT enabled, expiry_months, earn_start_date, min_redeem_points
+         FROM points_settings WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or(PointsSettings {
+        enabled: false,
+        expiry_months: 12,
+        earn_start_date: Local::now().date_naive(),
+        min_redeem_points: 0,
+    });
+    let rules = sqlx::query_as::<_, PointEarnRule>(
+        "SELECT channel, membership_level, earn_rate::float8 AS earn_rate
+         FROM point_earn_rules ORDER BY rule_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(PointsConfig { settings, rules })
+}
+
+/// Spends open lots first-to-expire. Returns how much could be taken.
+async fn consume_lots(
+    conn: &mut PgConnection,
+    customer_id: &str,
+    points: i32,
+    skip_entry: Option<i32>,
+) -> MyceliumResult<i32> {
+    let lots: Vec<(i32, i32)> = sqlx::query_as(
+        "SELECT entry_id, remaining FROM point_ledger
+         WHERE customer_id = $1 AND remaining > 0 AND entry_id <> COALESCE($2, 0)
+         ORDER BY expires_at NULLS LAST, entry_id
+         FOR UPDATE",
+    )
+    .bind(customer_id)
+    .bind(skip_entry)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut left = points;
+    for (entry_id, remaining) in lots {
+        if left <= 0 {
+            break;
+        }
+        let take = remaining.min(left);
+        sqlx::query("UPDATE point_ledger SET remaining = remaining - $1 WHERE entry_id = $2")
+            .bind(take)
+            .bind(entry_id)
+            .execute(&mut *conn)
+            .await?;
+        left -= take;
+    }
+    Ok(points - left)
+}
+
+async fn customer_balance(conn: &mut PgConnection, customer_id: &str) -> MyceliumResult<i64> {
+    let (balance,): (i64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(points), 0)::bigint FROM point_ledger WHERE customer_id = $1",
+    )
+    .bind(customer_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(balance)
+}
+
+#[derive(sqlx::FromRow)]
+struct EarnCandidate {
+    sales_id: String,
+    customer_id: String,
+    channel: String,
+    membership_level: Option<String>,
+    amount: i64,
+}
+
+/// Credits points for delivered sales that have not earned yet (all of them, or one sale).
+/// Orders placed before `earn_start_date` never earn. Safe to run repeatedly.
+pub async fn award_delivered_points(
+    pool: &DbPool,
+    sales_id: Option<&str>,
+) -> MyceliumResult<usize> {
+    let PointsConfig { settings, rules } = load_points_config(pool).await?;
+    if !settings.enabled || rules.is_empty() {
+        return Ok(0);
+    }
+
+    let candidates: Vec<EarnCandidate> = sqlx::query_as(
+        "SELECT s.sales_id, s.customer_id, COALESCE(s.mall_name, $2) AS channel, c.membership_level,
+                GREATEST(s.total_amount - COALESCE(s.points_used, 0), 0)::bigint AS amount
+         FROM sales s
+         JOIN customers c ON c.customer_id = s.customer_id
+         WHERE s.status = '배송완료'
+           AND s.order_date >= $1
+           AND ($3::text IS NULL OR s.sales_id = $3)
+           AND NOT EXISTS (
+               SELECT 1 FROM point_ledger l WHERE l.sales_id = s.sales_id AND l.entry_type = '적립'
+           )
+         ORDER BY s.sales_id",
+    )
+    .bind(settings.earn_start_date)
+    .bind(DIRECT_CHANNEL)
+    .bind(sales_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut earned = 0;
+    for c in candidates {
+        let rate = earn_rate_for(&rules, &c.channel, c.membership_level.as_deref());
+        let points = points_for_amount(c.amount, rate);
+        if points <= 0 {
+            continue;
+        }
+        DB_MODIFIED.store(true, Ordering::Relaxed);
+        let inserted = sqlx::query(
+            "INSERT INTO point_ledger (customer_id, entry_type, points, remaining, expires_at, sales_id, memo, created_by)
+             VALUES ($1, '적립', $2, $2, CURRENT_DATE + make_interval(months => $3), $4, $5, 'System')
+             ON CONFLICT (sales_id) WHERE entry_type = '적립' DO NOTHING",
+        )
+        .bind(&c.customer_id)
+        .bind(points)
+        .bind(settings.expiry_months)
+        .bind(&c.sales_id)
+        .bind(format!("배송완료 적립 ({}%)", rate))
+        .execute(pool)
+        .await?;
+        earned += inserted.rows_affected() as usize;
+    }
+    Ok(earned)
+}
+
+/// Brings a sale's reversals in line with its refunds: earned points are taken back and
+/// spent points returned in proportion to the refunded amount (all of them once the sale
+/// is cancelled or returned, along with its referral rewards). Only the difference to
+/// earlier reversals is booked, so this can be called after every claim change.
+pub async fn reverse_sale_points(conn: &mut PgConnection, sales_id: &str) -> MyceliumResult<()> {
+    let sale: Option<(Option<String>, String, i32, i32)> = sqlx::query_as(
+        "SELECT customer_id, status, total_amount, COALESCE(points_used, 0) FROM sales WHERE sales_id = $1",
+    )
+    .bind(sales_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some((Some(customer_id), status, total, points_used)) = sale else {
+        return Ok(());
+    };
+
+    let (refunded,): (i64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(refund_amount), 0)::bigint FROM sales_claims
+         WHERE sales_id = $1 AND claim_status = '완료'",
+    )
+    .bind(sales_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    let fully = REFUNDED_STATUSES.contains(&status.as_str());
+
+    let earn: Option<(i32, i32)> = sqlx::query_as(
+        "SELECT entry_id, points FROM point_ledger WHERE sales_id = $1 AND entry_type = '적립'",
+    )
+    .bind(sales_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (clawed, restored): (i64, i64) = sqlx::query_as(
+        "SELECT COALESCE(-SUM(points) FILTER (WHERE entry_type = '회수'), 0)::bigint,
+                COALESCE(SUM(points) FILTER (WHERE entry_type = '사용취소'), 0)::bigint
+         FROM point_ledger WHERE sales_id = $1",
+    )
+    .bind(sales_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if let Some((earn_id, earned)) = earn {
+        let due = reversal_target(earned, refunded, total as i64, fully) as i64 - clawed;
+        if due > 0 {
+            let due = due as i32;
+            sqlx::query(
+                "INSERT INTO point_ledger (customer_id, entry_type, points, sales_id, memo, created_by)
+                 VALUES ($1, '회수', $2, $3, '환불에 따른 적립 회수', 'System')",
+            )
+            .bind(&customer_id)
+            .bind(-due)
+            .bind(sales_id)
+            .execute(&mut *conn)
+            .await?;
+            // Take it out of the sale's own lot first, then whatever else is open
+            let (from_lot,): (i32,) = sqlx::query_as(
+                "WITH old AS (SELECT remaining FROM point_ledger WHERE entry_id = $1 FOR UPDATE)
+                 UPDATE point_ledger SET remaining = GREATEST(point_ledger.remaining - $2, 0)
+                 FROM old WHERE entry_id = $1
+                 RETURNING LEAST(old.remaining, $2)",
+            )
+            .bind(earn_id)
+            .bind(due)
+            .fetch_one(&mut *conn)
+            .await?;
+            if from_lot < due {
+                consume_lots(conn, &customer_id, due - from_lot, Some(earn_id)).await?;
+            }
+        }
+    }
+
+    let due = reversal_target(points_used, refunded, total as i64, fully) as i64 - restored;
+    if due > 0 {
+        sqlx::query(
+            "INSERT INTO point_ledger (customer_id, entry_type, points, remaining, expires_at, sales_id, memo, created_by)
+             VALUES ($1, '사용취소', $2, $2,
+                     CURRENT_DATE + make_interval(months => (SELECT expiry_months FROM points_settings WHERE id = 1)),
+                     $3, '환불에 따른 사용 포인트 반환', 'System')",
+        )
+        .bind(&customer_id)
+        .bind(due as i32)
+        .bind(sales_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    if fully {
+        reverse_referral_rewards(conn, sales_id).await?;
+    }
+    Ok(())
+}
+
+/// Takes back the referral points a cancelled or returned sale earned, from either side of
+/// the referral, and drops its referral rewards so it no longer counts as the referee's
+/// order with a code.
+async fn reverse_referral_rewards(conn: &mut PgConnection, sales_id: &str) -> MyceliumResult<()> {
+    let owed: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT customer_id, SUM(points)::bigint FROM point_ledger
+         WHERE sales_id = $1 AND entry_type IN ('추천적립', '추천회수')
+         GROUP BY customer_id HAVING SUM(points) > 0",
+    )
+    .bind(sales_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    for (customer_id, due) in owed {
+        let due = due as i32;
+        sqlx::query(
+            "INSERT INTO point_ledger (customer_id, entry_type, points, sales_id, memo, created_by)
+             VALUES ($1, '추천회수', $2, $3, '취소된 추천 주문의 보상 회수', 'System')",
+        )
+        .bind(&customer_id)
+        .bind(-due)
+        .bind(sales_id)
+        .execute(&mut *conn)
+        .await?;
+        // The reward's own lots first, then whatever else is open
+        let lots: Vec<(i32, i32)> = sqlx::query_as(
+            "SELECT entry_id, remaining FROM point_ledger
+             WHERE sales_id = $1 AND customer_id = $2 AND entry_type = '추천적립' AND remaining > 0
+             ORDER BY entry_id
+             FOR UPDATE",
+        )
+        .bind(sales_id)
+        .bind(&customer_id)
+        .fetch_all(&mut *conn)
+        .await?;
+        let mut left = due;
+        for (entry_id, remaining) in lots {
+            let take = remaining.min(left);
+            if take <= 0 {
+                break;
+            }
+            sqlx::query("UPDATE point_ledger SET remaining = remaining - $1 WHERE entry_id = $2")
+                .bind(take)
+                .bind(entry_id)
+                .execute(&mut *conn)
+                .await?;
+            left -= take;
+        }
+        if left > 0 {
+            consume_lots(conn, &customer_id, left, None).await?;
+        }
+    }
+    sqlx::query("DELETE FROM referral_rewards WHERE sales_id = $1")
+        .bind(sales_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Reverses points of refunded sales that have any point activity. Safe to run repeatedly.
+pub async fn reverse_refunded_points(pool: &DbPool) -> MyceliumResult<usize> {
+    let sales: Vec<(String,)> = sqlx::query_as(
+        "SELECT DISTINCT l.sales_id FROM point_ledger l
+         JOIN sales s ON s.sales_id = l.sales_id
+         WHERE l.entry_type IN ('적립', '사용', '추천적립')
+           AND (s.status = ANY($1)
+                OR EXISTS (SELECT 1 FROM sales_claims sc
+                           WHERE sc.sales_id = s.sales_id AND sc.claim_status = '완료' AND sc.refund_amount > 0))",
+    )
+    .bind(REFUNDED_STATUSES.map(String::from).to_vec())
+    .fetch_all(pool)
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    let (before,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM point_ledger WHERE entry_type IN ('회수', '사용취소', '추천회수')",
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    for (sales_id,) in &sales {
+        reverse_sale_points(&mut tx, sales_id).await?;
+    }
+    let (after,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM point_ledger WHERE entry_type IN ('회수', '사용취소', '추천회수')",
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    if after > before {
+        DB_MODIFIED.store(true, Ordering::Relaxed);
+    }
+    Ok((after - before) as usize)
+}
+
+/// Books the unspent rest of every lot past its expiry date as '소멸'.
+pub async fn expire_points(pool: &DbPool) -> MyceliumResult<usize> {
+    let result = sqlx::query(
+        "WITH due AS (
+             SELECT entry_id, customer_id, remaining FROM point_ledger
+             WHERE remaining > 0 AND expires_at < CURRENT_DATE
+             FOR UPDATE
+         ), cleared AS (
+             UPDATE point_ledger l SET remaining = 0 FROM due WHERE l.entry_id = due.entry_id
+         )
+         INSERT INTO point_ledger (customer_id, entry_type, points, memo, created_by)
+         SELECT customer_id, '소멸', -remaining, '유효기간 만료 (#' || entry_id || ')', 'System' FROM due",
+    )
+    .execute(pool)
+    .await?;
+    let expired = result.rows_affected() as usize;
+    if expired > 0 {
+        DB_MODIFIED.store(true, Ordering::Relaxed);
+    }
+    Ok(expired)
+}
+
+/// Called by the background scheduler: earns for delivered sales (referral rewards
+/// included), reverses refunds made outside the claim screen and expires old points.
+pub async fn run_points_jobs(pool: &DbPool) -> MyceliumResult<PointsJobResult> {
+    Ok(PointsJobResult {
+        earned: award_delivered_points(pool, None).await?,
+        referral_rewards: crate::commands::referral::award_referral_rewards(pool, None).await?,
+        reversed: reverse_refunded_points(pool).await?,
+        expired: expire_points(pool).await?,
+    })
+}
+
+/// Earns (referral rewards included) or reverses points for one sale after its status
+/// changed. A failure here must not undo the status change, so it is only logged; the
+/// background job catches up.
+pub async fn sync_sale_points(pool: &DbPool, sales_id: &str) {
+    if let Err(e) = award_delivered_points(pool, Some(sales_id)).await {
+        tracing::warn!("Failed to award points for {}: {}", sales_id, e);
+    }
+    if let Err(e) = crate::commands::referral::award_referral_rewards(pool, Some(sales_id)).await {
+        tracing::warn!("Failed to give referral rewards for {}: {}", sales_id, e);
+    }
+    let result = async {
+        let mut tx = pool.begin().await?;
+        reverse_sale_points(&mut tx, sales_id).await?;
+        tx.commit().await?;
+        MyceliumResult::Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!("Failed to reverse points for {}: {}", sales_id, e);
+    }
+}
+
+pub async fn get_customer_points(
+    pool: &DbPool,
+    customer_id: &str,
+) -> MyceliumResult<CustomerPoints> {
+    let (balance, expiring_soon): (i64, i64) = sqlx::query_as(
+        "SELECT COALESCE(SUM(points), 0)::bigint,
+                COALESCE(SUM(remaining) FILTER (WHERE expires_at < CURRENT_DATE + 30), 0)::bigint
+         FROM point_ledger WHERE customer_id = $1",
+    )
+    .bind(customer_id)
+    .fetch_one(pool)
+    .await?;
+    let entries = sqlx::query_as::<_, PointEntry>(
+        "SELECT entry_id, entry_type, points, remaining, expires_at, sales_id, memo, created_by, created_at
+         FROM point_ledger WHERE customer_id = $1
+         ORDER BY created_at DESC, entry_id DESC",
+    )
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(CustomerPoints {
+        customer_id: customer_id.to_string(),
+        balance,
+        expiring_soon,
+        entries,
+    })
+}
+
+/// Pays part of an order with points. The points are recorded in `sales.points_used`,
+/// next to `paid_amount`, and taken from the customer's lots first-to-expire.
+pub async fn redeem_points(
+    pool: &DbPool,
+    username: &str,
+    sales_id: &str,
+    points: i32,
+) -> MyceliumResult<String> {
+    let settings = load_points_config(pool).await?.settings;
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    let customer_id = redeem_points_in(&mut tx, &settings, username, sales_id, points).await?;
+    tx.commit().await?;
+    Ok(customer_id)
+}
+
+/// `redeem_points` inside the caller's transaction, for a sale being saved in it
+pub async fn redeem_points_in(
+    conn: &mut PgConnection,
+    settings: &PointsSettings,
+    username: &str,
+    sales_id: &str,
+    points: i32,
+) -> MyceliumResult<String> {
+    if !settings.enabled {
+        return Err(MyceliumError::Validation(
+            "포인트 기능이 꺼져 있습니다.".to_string(),
+        ));
+    }
+    if points <= 0 || points < settings.min_redeem_points {
+        return Err(MyceliumError::Validation(format!(
+            "{}포인트 이상부터 사용할 수 있습니다.",
+            settings.min_redeem_points.max(1)
+        )));
+    }
+
+    let sale: Option<(Option<String>, String, i64)> = sqlx::query_as(
+        "SELECT customer_id, status,
+                (total_amount - COALESCE(paid_amount, 0) - COALESCE(points_used, 0))::bigint
+         FROM sales WHERE sales_id = $1 FOR UPDATE",
+    )
+    .bind(sales_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (customer_id, status, due) =
+        sale.ok_or_else(|| MyceliumError::Validation("주문을 찾을 수 없습니다.".to_string()))?;
+    let customer_id = customer_id
+        .ok_or_else(|| MyceliumError::Validation("고객이 지정되지 않은 주문입니다.".to_string()))?;
+    if REFUNDED_STATUSES.contains(&status.as_str()) {
+        return Err(MyceliumError::Validation(
+            "취소/반품된 주문에는 포인트를 사용할 수 없습니다.".to_string(),
+        ));
+    }
+    if points as i64 > due {
+        return Err(MyceliumError::Validation(format!(
+            "결제할 금액({}원)보다 많이 사용할 수 없습니다.",
+            due.max(0)
+        )));
+    }
+    let balance = customer_balance(conn, &customer_id).await?;
+    if points as i64 > balance {
+        return Err(MyceliumError::Validation(format!(
+            "포인트 잔액({})이 부족합니다.",
+            balance
+        )));
+    }
+
+    consume_lots(conn, &customer_id, points, None).await?;
+    sqlx::query(
+        "INSERT INTO point_ledger (customer_id, entry_type, points, sales_id, memo, created_by)
+         VALUES ($1, '사용', $2, $3, '주문 결제', $4)",
+    )
+    .bind(&customer_id)
+    .bind(-points)
+    .bind(sales_id)
+    .bind(username)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query("UPDATE sales SET points_used = COALESCE(points_used, 0) + $1 WHERE sales_id = $2")
+        .bind(points)
+        .bind(sales_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(customer_id)
+}
+
+/// Manual correction. Positive amounts become a new lot; negative ones are taken from open lots.
+pub async fn adjust_points(
+    pool: &DbPool,
+    username: &str,
+    customer_id: &str,
+    points: i32,
+    memo: Option<String>,
+) -> MyceliumResult<()> {
+    if points == 0 {
+        return Err(MyceliumError::Validation(
+            "조정할 포인트를 입력해주세요.".to_string(),
+        ));
+    }
+    let settings = load_points_config(pool).await?.settings;
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    if points < 0 {
+        let balance = customer_balance(&mut tx, customer_id).await?;
+        if (-points) as i64 > balance {
+            return Err(MyceliumError::Validation(format!(
+                "포인트 잔액({})보다 많이 차감할 수 없습니다.",
+                balance
+            )));
+        }
+        consume_lots(&mut tx, customer_id, -points, None).await?;
+    }
+    sqlx::query(
+        "INSERT INTO point_ledger (customer_id, entry_type, points, remaining, expires_at, memo, created_by)
+         VALUES ($1, '조정', $2, GREATEST($2, 0),
+                 CASE WHEN $2 > 0 THEN CURRENT_DATE + make_interval(months => $3) END, $4, $5)",
+    )
+    .bind(customer_id)
+    .bind(points)
+    .bind(settings.expiry_months)
+    .bind(memo)
+    .bind(username)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+fn blank_to_none(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+pub async fn get_customer_points_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<CustomerIdQuery>,
+) -> MyceliumResult<Json<CustomerPoints>> {
+    Ok(Json(
+        get_customer_points(&state.pool, &params.customer_id).await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedeemPointsInput {
+    #[serde(alias = "sales_id")]
+    pub sales_id: String,
+    pub points: i32,
+}
+
+pub async fn redeem_points_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<RedeemPointsInput>,
+) -> MyceliumResult<Json<CustomerPoints>> {
+    let customer_id = redeem_points(
+        &state.pool,
+        claims.username.as_deref().unwrap_or("Admin"),
+        &payload.sales_id,
+        payload.points,
+    )
+    .await?;
+    Ok(Json(get_customer_points(&state.pool, &customer_id).await?))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjustPointsInput {
+    #[serde(alias = "customer_id")]
+    pub customer_id: String,
+    pub points: i32,
+    pub memo: Option<String>,
+}
+
+pub async fn adjust_points_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<AdjustPointsInput>,
+) -> MyceliumResult<Json<CustomerPoints>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    adjust_points(
+        &state.pool,
+        claims.username.as_deref().unwrap_or("Admin"),
+        &payload.customer_id,
+        payload.points,
+        payload.memo,
+    )
+    .await?;
+    Ok(Json(
+        get_customer_points(&state.pool, &payload.customer_id).await?,
+    ))
+}
+
+pub async fn get_points_config_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<PointsConfig>> {
+    Ok(Json(load_points_config(&state.pool).await?))
+}
+
+pub async fn save_points_config_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<PointsConfig>,
+) -> MyceliumResult<Json<()>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    let s = &payload.settings;
+    if s.expiry_months <= 0 || s.min_redeem_points < 0 {
+        return Err(MyceliumError::Validation(
+            "유효기간은 1개월 이상, 최소 사용 포인트는 0 이상이어야 합니다.".to_string(),
+        ));
+    }
+    if payload
+        .rules
+        .iter()
+        .any(|r| !(0.0..=100.0).contains(&r.earn_rate))
+    {
+        return Err(MyceliumError::Validation(
+            "적립률은 0~100% 사이여야 합니다.".to_string(),
+        ));
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    // Switching points on never credits orders placed before today
+    sqlx::query(
+        "UPDATE points_settings SET
+            earn_start_date = CASE WHEN NOT enabled AND $1 THEN GREATEST($2, CURRENT_DATE) ELSE $2 END,
+            enabled = $1, expiry_months = $3, min_redeem_points = $4, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+    )
+    .bind(s.enabled)
+    .bind(s.earn_start_date)
+    .bind(s.expiry_months)
+    .bind(s.min_redeem_points)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM point_earn_rules")
+        .execute(&mut *tx)
+        .await?;
+    for rule in &payload.rules {
+        sqlx::query(
+            "INSERT INTO point_earn_rules (channel, membership_level, earn_rate)
+             VALUES ($1, $2, $3::numeric)",
+        )
+        .bind(blank_to_none(&rule.channel))
+        .bind(blank_to_none(&rule.membership_level))
+        .bind(rule.earn_rate)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(Json(()))
+}
@@ -0,0 +1,557 @@
+use crate::commands::config::load_integration_settings;
+use crate::commands::consent::MessagePurpose;
+use crate::commands::crm::{send_message_with, MessageChannels, MessageOptions};
+use crate::commands::customer::CustomerIdQuery;
+use crate::commands::message_template::{build_context, render, TemplateValue};
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::atomic::Ordering;
+
+/// Template category used for the notice before anonymization
+pub const NOTICE_TEMPLATE_KEY: &str = "privacy_notice";
+
+const DEFAULT_NOTICE_MESSAGE: &str =
+    "${name}님, 장기간 이용 기록이 없어 ${date}에 개인정보가 파기될 예정입니다.";
+
+/// Stands in for names once a customer is anonymized
+pub const ANONYMIZED_NAME: &str = "(익명)";
+
+/// Personal fields in `customer_logs` whose old and new values are masked as well
+const PERSONAL_LOG_FIELDS: [&str; 7] = [
+    "customer_name",
+    "mobile_number",
+    "phone_number",
+    "email",
+    "zip_code",
+    "address_primary",
+    "address_detail",
+];
+
+/// Tables exported for a data-subject request: (key, table, rows of the customer `$1`, order by)
+const EXPORT_TABLES: [(&str, &str, &str, &str); 16] = [
+    ("customer", "customers", "customer_id = $1", "customer_id"),
+    (
+        "addresses",
+        "customer_addresses",
+        "customer_id = $1",
+        "address_id",
+    ),
+    ("sales", "sales", "customer_id = $1", "order_date, sales_id"),
+    ("claims", "sales_claims", "customer_id = $1", "claim_id"),
+    ("ledger", "customer_ledger", "customer_id = $1", "ledger_id"),
+    (
+        "consultations",
+        "consultations",
+        "customer_id = $1",
+        "consult_id",
+    ),
+    (
+        "follow_up_schedules",
+        "schedules",
+        "related_type = 'CONSULTATION'
+         AND related_id IN (SELECT consult_id FROM consultations WHERE customer_id = $1)",
+        "start_time, schedule_id",
+    ),
+    (
+        "experience_reservations",
+        "experience_reservations",
+        "customer_id = $1",
+        "reservation_id",
+    ),
+    (
+        "messages",
+        "sms_logs",
+        "customer_id = $1",
+        "sent_at, log_id",
+    ),
+    (
+        "automated_messages",
+        "automation_sends",
+        "customer_id = $1",
+        "created_at, send_id",
+    ),
+    (
+        "inbound_messages",
+        "inbound_messages",
+        "customer_id = $1",
+        "received_at, inbound_id",
+    ),
+    (
+        "consents",
+        "customer_consents",
+        "customer_id = $1",
+        "consent_id",
+    ),
+    ("points", "point_ledger", "customer_id = $1", "entry_id"),
+    (
+        "referral_rewards",
+        "referral_rewards",
+        "referrer_id = $1 OR referee_id = $1",
+        "reward_id",
+    ),
+    ("change_logs", "customer_logs", "customer_id = $1", "log_id"),
+    (
+        "privacy_actions",
+        "privacy_actions",
+        "customer_id = $1",
+        "action_id",
+    ),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PrivacySettings {
+    pub auto_enabled: bool,
+    pub retention_months: i32,
+    pub notice_days: i32,
+    #[serde(default)]
+    pub last_run_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct InactiveCustomer {
+    pub customer_id: String,
+    pub customer_name: String,
+    pub mobile_number: String,
+    pub last_activity: Option<NaiveDate>,
+    /// Set once the notice for the current period of inactivity has gone out
+    pub noticed_at: Option<NaiveDateTime>,
+    pub anonymize_on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoticeFailure {
+    pub customer_id: String,
+    pub customer_name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RetentionRunResult {
+    pub noticed: usize,
+    pub anonymized: usize,
+    /// Notices that did not go out; those customers are not put on the anonymization clock
+    pub notice_failures: Vec<NoticeFailure>,
+}
+
+/// Whether a noticed customer may be anonymized today.
+pub fn is_due_for_anonymization(customer: &InactiveCustomer, today: NaiveDate) -> bool {
+    customer.noticed_at.is_some() && customer.anonymize_on.is_some_and(|d| d <= today)
+}
+
+pub async fn load_privacy_settings(pool: &DbPool) -> MyceliumResult<PrivacySettings> {
+    Ok(sqlx::query_as::<_, PrivacySettings>(
+        "SELECT auto_enabled, retention_months, notice_days, last_run_at FROM privacy_settings WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or(PrivacySettings {
+        auto_enabled: false,
+        retention_months: 36,
+        notice_days: 30,
+        last_run_at: None,
+    }))
+}
+
+/// Customers (not yet anonymized) whose last order, consultation, reservation or sign-up
+/// is older than the retention period, with the notice sent for that inactivity if any.
+pub async fn find_inactive_customers(
+    pool: &DbPool,
+    retention_months: i32,
+) -> MyceliumResult<Vec<InactiveCustomer>> {
+    Ok(sqlx::query_as::<_, InactiveCustomer>(
+        "WITH activity AS (
+             SELECT c.customer_id, c.customer_name, c.mobile_number,
+                    GREATEST(c.join_date, c.created_at::date,
+                        (SELECT MAX(order_date) FROM sales s WHERE s.customer_id = c.customer_id),
+                        (SELECT MAX(consult_date) FROM consultations co WHERE co.customer_id = c.customer_id),
+                        (SELECT MAX(reservation_date) FROM experience_reservations e WHERE e.customer_id = c.customer_id)
+                    ) AS last_activity
+             FROM customers c
+             WHERE c.anonymized_at IS NULL
+         )
+         SELECT a.customer_id, a.customer_name, a.mobile_number, a.last_activity,
+                n.performed_at AS noticed_at, n.scheduled_for AS anonymize_on
+         FROM activity a
+         LEFT JOIN privacy_actions n ON n.customer_id = a.customer_id AND n.action = '사전안내'
+              AND n.last_activity IS NOT DISTINCT FROM a.last_activity
+         WHERE a.last_activity IS NULL
+            OR a.last_activity < CURRENT_DATE - make_interval(months => $1)
+         ORDER BY a.last_activity NULLS FIRST, a.customer_id",
+    )
+    .bind(retention_months)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Clears a customer's personal data everywhere it is copied while keeping the rows,
+/// so order counts and amounts stay intact.
+pub async fn anonymize_customer(
+    conn: &mut PgConnection,
+    customer_id: &str,
+    username: &str,
+) -> MyceliumResult<()> {
+    let mobile: Option<(String,)> = sqlx::query_as(
+        "SELECT mobile_number FROM customers WHERE customer_id = $1 AND anonymized_at IS NULL FOR UPDATE",
+    )
+    .bind(customer_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some((mobile,)) = mobile else {
+        return Err(MyceliumError::Validation(
+            "고객을 찾을 수 없거나 이미 익명 처리되었습니다.".to_string(),
+        ));
+    };
+    sqlx::query(
+        "UPDATE customers SET customer_name = $2, mobile_number = '', phone_number = NULL, email = NULL,
+            zip_code = NULL, address_primary = NULL, address_detail = NULL, anniversary_date = NULL,
+            anniversary_type = NULL, family_type = NULL, health_concern = NULL, memo = NULL,
+            marketing_consent = FALSE, referred_by = NULL, status = '익명',
+            anonymized_at = CURRENT_TIMESTAMP
+         WHERE customer_id = $1",
+    )
+    .bind(customer_id)
+    .bind(ANONYMIZED_NAME)
+    .execute(&mut *conn)
+    .await?;
+
+    let statements = [
+        "UPDATE customer_addresses SET recipient_name = $2, mobile_number = '', zip_code = NULL,
+            address_primary = '', address_detail = NULL, shipping_memo = NULL
+         WHERE customer_id = $1",
+        "UPDATE sales SET shipping_name = NULL, shipping_zip_code = NULL, shipping_address_primary = NULL,
+            shipping_address_detail = NULL, shipping_mobile_number = NULL
+         WHERE customer_id = $1",
+        "UPDATE consultations SET guest_name = $2, contact = '' WHERE customer_id = $1",
+        "UPDATE experience_reservations SET guest_name = $2, guest_contact = '' WHERE customer_id = $1",
+        "UPDATE sms_logs SET recipient_name = $2, mobile_number = '' WHERE customer_id = $1",
+        "UPDATE automation_sends SET mobile_number = NULL, content = NULL WHERE customer_id = $1",
+        // Merged-away profiles are kept for undo
+        "UPDATE customer_merges SET source_snapshot = source_snapshot || jsonb_build_object(
+            'customer_name', $2::text, 'mobile_number', '', 'phone_number', NULL, 'email', NULL,
+            'zip_code', NULL, 'address_primary', NULL, 'address_detail', NULL)
+         WHERE source_customer_id = $1 OR target_customer_id = $1",
+    ];
+    for sql in statements {
+        sqlx::query(sql)
+            .bind(customer_id)
+            .bind(ANONYMIZED_NAME)
+            .execute(&mut *conn)
+            .await?;
+    }
+    // Replies from the number before it was matched to the customer are theirs as well
+    sqlx::query(
+        "UPDATE inbound_messages SET mobile_number = '', content = ''
+         WHERE customer_id = $1 OR (customer_id IS NULL AND mobile_number <> '' AND mobile_number = $2)",
+    )
+    .bind(customer_id)
+    .bind(crate::commands::messaging::digits(&mobile))
+    .execute(&mut *conn)
+    .await?;
+    // Follow-up reminders carry the customer's name in their title
+    let consult_ids: Vec<i32> =
+        sqlx::query_scalar("SELECT consult_id FROM consultations WHERE customer_id = $1")
+            .bind(customer_id)
+            .fetch_all(&mut *conn)
+            .await?;
+    for consult_id in consult_ids {
+        crate::commands::consultation::sync_follow_up_schedule(&mut *conn, consult_id).await?;
+    }
+    sqlx::query(
+        "UPDATE customer_logs SET old_value = CASE WHEN old_value IS NULL THEN NULL ELSE $2 END,
+            new_value = CASE WHEN new_value IS NULL THEN NULL ELSE $2 END
+         WHERE customer_id = $1 AND field_name = ANY($3)",
+    )
+    .bind(customer_id)
+    .bind(ANONYMIZED_NAME)
+    .bind(PERSONAL_LOG_FIELDS.map(String::from).to_vec())
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO privacy_actions (customer_id, action, performed_by) VALUES ($1, '익명화', $2)",
+    )
+    .bind(customer_id)
+    .bind(username)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Records the notice for the customer's current inactivity and texts it to them. The
+/// record is only kept once the message went out, so a failed notice is retried on the
+/// next run instead of starting the notice period. Returns false when it had already been
+/// recorded. Customers without a mobile number are noticed without a message.
+pub async fn send_notice(
+    pool: &DbPool,
+    channels: &MessageChannels,
+    customer: &InactiveCustomer,
+    anonymize_on: NaiveDate,
+    template: &str,
+    username: &str,
+) -> MyceliumResult<bool> {
+    // Holding the row until the send is done keeps a parallel run from texting twice
+    let mut tx = pool.begin().await?;
+    let inserted = sqlx::query(
+        "INSERT INTO privacy_actions (customer_id, action, last_activity, scheduled_for, performed_by)
+         VALUES ($1, '사전안내', $2, $3, $4)
+         ON CONFLICT (customer_id, action, last_activity) DO NOTHING",
+    )
+    .bind(&customer.customer_id)
+    .bind(customer.last_activity)
+    .bind(anonymize_on)
+    .bind(username)
+    .execute(&mut *tx)
+    .await?;
+    if inserted.rows_affected() == 0 {
+        return Ok(false);
+    }
+    if !customer.mobile_number.trim().is_empty() {
+        let mut context = build_context(pool, Some(&customer.customer_id), None).await?;
+        context.insert("date", TemplateValue::Date(anonymize_on));
+        let options = MessageOptions {
+            template_code: Some(NOTICE_TEMPLATE_KEY.to_string()),
+            ..Default::default()
+        };
+        // A legal notice, not advertising
+        send_message_with(
+            pool,
+            channels,
+            "SMS".to_string(),
+            vec![customer.mobile_number.clone()],
+            render(template, &context).text,
+            MessagePurpose::Transactional,
+            options,
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Sends the notice to inactive customers who have not had one, then anonymizes those
+/// whose notice period is over. Notices that could not be sent are reported and left
+/// for the next run.
+pub async fn run_retention(pool: &DbPool, username: &str) -> MyceliumResult<RetentionRunResult> {
+    let settings = load_privacy_settings(pool).await?;
+    let inactive = find_inactive_customers(pool, settings.retention_months).await?;
+    let today = Local::now().date_naive();
+    let mut result = RetentionRunResult::default();
+
+    let template = crate::commands::message_template::load_template(pool, NOTICE_TEMPLATE_KEY)
+        .await?
+        .unwrap_or_else(|| DEFAULT_NOTICE_MESSAGE.to_string());
+    let anonymize_on = today + chrono::Duration::days(settings.notice_days as i64);
+    let channels = MessageChannels::from_settings(load_integration_settings()?);
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    for customer in inactive.iter().filter(|c| c.noticed_at.is_none()) {
+        let sent = match &channels {
+            Ok(channels) => {
+                send_notice(pool, channels, customer, anonymize_on, &template, username).await
+            }
+            Err(e) => Err(MyceliumError::Validation(e.to_string())),
+        };
+        match sent {
+            Ok(true) => result.noticed += 1,
+            Ok(false) => {}
+            Err(e) => result.notice_failures.push(NoticeFailure {
+                customer_id: customer.customer_id.clone(),
+                customer_name: customer.customer_name.clone(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    for customer in inactive
+        .iter()
+        .filter(|c| is_due_for_anonymization(c, today))
+    {
+        let mut tx = pool.begin().await?;
+        crate::db::set_db_user_context(&mut *tx, username).await?;
+        anonymize_customer(&mut tx, &customer.customer_id, username).await?;
+        tx.commit().await?;
+        result.anonymized += 1;
+    }
+
+    sqlx::query("UPDATE privacy_settings SET last_run_at = CURRENT_TIMESTAMP WHERE id = 1")
+        .execute(pool)
+        .await?;
+    Ok(result)
+}
+
+/// Called by the background scheduler; runs once a day after 4 a.m. when enabled.
+pub async fn run_retention_if_due(pool: &DbPool) -> MyceliumResult<bool> {
+    let now = Local::now().naive_local();
+    if now.time() < chrono::NaiveTime::from_hms_opt(4, 0, 0).unwrap_or_default() {
+        return Ok(false);
+    }
+    let settings = load_privacy_settings(pool).await?;
+    let ran_today = settings
+        .last_run_at
+        .map(|t| t.date() == now.date())
+        .unwrap_or(false);
+    if !settings.auto_enabled || ran_today {
+        return Ok(false);
+    }
+    run_retention(pool, "System").await?;
+    Ok(true)
+}
+
+/// Everything held about one customer, table by table.
+pub async fn export_customer_data(
+    pool: &DbPool,
+    customer_id: &str,
+) -> MyceliumResult<serde_json::Value> {
+    let mut export = serde_json::Map::new();
+    for (key, table, filter, order_by) in EXPORT_TABLES {
+        let (rows,): (serde_json::Value,) = sqlx::query_as(&format!(
+            "SELECT COALESCE(json_agg(t), '[]'::json)
+             FROM (SELECT * FROM {table} WHERE {filter} ORDER BY {order_by}) t"
+        ))
+        .bind(customer_id)
+        .fetch_one(pool)
+        .await?;
+        export.insert(key.to_string(), rows);
+    }
+    let customer = export
+        .get_mut("customer")
+        .and_then(|v| v.as_array_mut())
+        .and_then(|rows| rows.pop());
+    match customer {
+        Some(customer) => {
+            export.insert("customer".to_string(), customer);
+        }
+        None => {
+            return Err(MyceliumError::Validation(
+                "고객을 찾을 수 없습니다.".to_string(),
+            ))
+        }
+    }
+    export.insert(
+        "exported_at".to_string(),
+        serde_json::json!(Local::now().naive_local()),
+    );
+    Ok(serde_json::Value::Object(export))
+}
+
+pub async fn get_privacy_settings_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> MyceliumResult<Json<PrivacySettings>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    Ok(Json(load_privacy_settings(&state.pool).await?))
+}
+
+pub async fn save_privacy_settings_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<PrivacySettings>,
+) -> MyceliumResult<Json<()>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    if payload.retention_months <= 0 || payload.notice_days < 0 {
+        return Err(MyceliumError::Validation(
+            "보관 기간은 1개월 이상, 안내 기간은 0일 이상이어야 합니다.".to_string(),
+        ));
+    }
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query(
+        "UPDATE privacy_settings SET auto_enabled = $1, retention_months = $2, notice_days = $3,
+            updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+    )
+    .bind(payload.auto_enabled)
+    .bind(payload.retention_months)
+    .bind(payload.notice_days)
+    .execute(&state.pool)
+    .await?;
+    Ok(Json(()))
+}
+
+pub async fn get_inactive_customers_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> MyceliumResult<Json<Vec<InactiveCustomer>>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    let settings = load_privacy_settings(&state.pool).await?;
+    Ok(Json(
+        find_inactive_customers(&state.pool, settings.retention_months).await?,
+    ))
+}
+
+pub async fn run_retention_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> MyceliumResult<Json<RetentionRunResult>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    Ok(Json(
+        run_retention(&state.pool, claims.username.as_deref().unwrap_or("Admin")).await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnonymizeInput {
+    #[serde(alias = "customer_id")]
+    pub customer_id: String,
+}
+
+/// Immediate anonymization, e.g. on the customer's own request.
+pub async fn anonymize_customer_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<AnonymizeInput>,
+) -> MyceliumResult<Json<()>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    let username = claims.username.as_deref().unwrap_or("Admin");
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    anonymize_customer(&mut tx, &input.customer_id, username).await?;
+    tx.commit().await?;
+    Ok(Json(()))
+}
+
+pub async fn export_customer_data_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<CustomerIdQuery>,
+) -> MyceliumResult<Json<serde_json::Value>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    Ok(Json(
+        export_customer_data(&state.pool, &params.customer_id).await?,
+    ))
+}
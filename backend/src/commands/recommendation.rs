@@ -0,0 +1,236 @@
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::state::AppState;
+use axum::{
+    extract::{Query, State as AxumState},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Pairs seen together fewer times than this are noise
+const MIN_PAIR_COUNT: i64 = 2;
+
+/// "Orders with `antecedent` also had `consequent`", over the last 12 months of orders
+/// (one customer's products on one day count as an order).
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AssociationRule {
+    pub antecedent: String,
+    pub consequent: String,
+    pub pair_count: i64,
+    /// Share of all orders that had both products
+    pub support: f64,
+    pub confidence: f64,
+    pub lift: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Recommendation {
+    pub product_name: String,
+    /// The customer's product that led to the suggestion
+    pub because_of: String,
+    pub confidence: f64,
+    pub lift: f64,
+    pub pair_count: i64,
+    pub product_id: Option<i32>,
+    pub unit_price: Option<i32>,
+}
+
+/// Both directions of every product pair, shared by the association report and the
+/// recommendations.
+pub async fn load_association_rules(pool: &DbPool) -> MyceliumResult<Vec<AssociationRule>> {
+    Ok(sqlx::query_as::<_, AssociationRule>(
+        "WITH bundles AS (
+            SELECT DISTINCT customer_id, order_date, product_name FROM sales
+            WHERE customer_id IS NOT NULL AND order_date >= (CURRENT_DATE - INTERVAL '12 months') AND status != '취소'
+         ),
+         total AS (SELECT COUNT(DISTINCT (customer_id, order_date)) AS bundle_count FROM bundles),
+         items AS (SELECT product_name, COUNT(*) AS item_count FROM bundles GROUP BY product_name),
+         pairs AS (
+            SELECT a.product_name AS antecedent, b.product_name AS consequent, COUNT(*) AS pair_count
+            FROM bundles a
+            JOIN bundles b ON a.customer_id = b.customer_id AND a.order_date = b.order_date
+                          AND a.product_name <> b.product_name
+            GROUP BY a.product_name, b.product_name
+            HAVING COUNT(*) >= $1
+         )
+         SELECT p.antecedent, p.consequent, p.pair_count,
+                p.pair_count::float8 / t.bundle_count AS support,
+                p.pair_count::float8 / ia.item_count AS confidence,
+                p.pair_count::float8 * t.bundle_count / (ia.item_count * ic.item_count) AS lift
+         FROM pairs p
+         JOIN items ia ON ia.product_name = p.antecedent
+         JOIN items ic ON ic.product_name = p.consequent
+         CROSS JOIN total t",
+    )
+    .bind(MIN_PAIR_COUNT)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Products suggested by the rules whose antecedent the customer owns, leaving out what
+/// they already have and pairs that are no more than chance (lift of 1 or less). Each
+/// product keeps its strongest rule; highest confidence first.
+pub fn rank_recommendations(
+    rules: &[AssociationRule],
+    owned: &HashSet<String>,
+    limit: usize,
+) -> Vec<AssociationRule> {
+    let mut best: HashMap<&str, &AssociationRule> = HashMap::new();
+    for rule in rules
+        .iter()
+        .filter(|r| r.lift > 1.0 && owned.contains(&r.antecedent) && !owned.contains(&r.consequent))
+    {
+        let entry = best.entry(rule.consequent.as_str()).or_insert(rule);
+        if (rule.confidence, rule.lift) > (entry.confidence, entry.lift) {
+            *entry = rule;
+        }
+    }
+    let mut ranked: Vec<AssociationRule> = best.into_values().cloned().collect();
+    ranked.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then(b.lift.total_cmp(&a.lift))
+            .then(a.consequent.cmp(&b.consequent))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Products each customer has ever bought (cancelled orders aside)
+pub async fn customer_products(
+    pool: &DbPool,
+    customer_ids: &[String],
+) -> MyceliumResult<HashMap<String, HashSet<String>>> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT DISTINCT customer_id, product_name FROM sales
+         WHERE customer_id = ANY($1) AND status != '취소'",
+    )
+    .bind(customer_ids)
+    .fetch_all(pool)
+    .await?;
+    let mut products: HashMap<String, HashSet<String>> = HashMap::new();
+    for (customer_id, product_name) in rows {
+        products
+            .entry(customer_id)
+            .or_default()
+            .insert(product_name);
+    }
+    Ok(products)
+}
+
+/// Fills in the top suggestion for each repurchase candidate.
+pub async fn attach_recommendations(
+    pool: &DbPool,
+    candidates: &mut [crate::db::RepurchaseCandidate],
+) -> MyceliumResult<()> {
+    if candidates.is_empty() {
+        return Ok(());
+    }
+    let rules = load_association_rules(pool).await?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<String> = candidates.iter().map(|c| c.customer_id.clone()).collect();
+    let owned = customer_products(pool, &ids).await?;
+    for candidate in candidates.iter_mut() {
+        if let Some(products) = owned.get(&candidate.customer_id) {
+            candidate.recommended_product = rank_recommendations(&rules, products, 1)
+                .pop()
+                .map(|r| r.consequent);
+        }
+    }
+    Ok(())
+}
+
+/// Suggestions for a customer's history plus any products in the order being entered.
+pub async fn recommend_products(
+    pool: &DbPool,
+    customer_id: Option<&str>,
+    extra_products: &[String],
+    limit: usize,
+) -> MyceliumResult<Vec<Recommendation>> {
+    let mut owned: HashSet<String> = extra_products.iter().cloned().collect();
+    if let Some(id) = customer_id {
+        owned.extend(
+            customer_products(pool, &[id.to_string()])
+                .await?
+                .remove(id)
+                .unwrap_or_default(),
+        );
+    }
+    if owned.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ranked = rank_recommendations(&load_association_rules(pool).await?, &owned, limit);
+    let names: Vec<String> = ranked.iter().map(|r| r.consequent.clone()).collect();
+    // Names can repeat across specifications; the sales screen gets the first one
+    let products: HashMap<String, (i32, i32)> = sqlx::query_as::<_, (String, i32, i32)>(
+        "SELECT DISTINCT ON (product_name) product_name, product_id, unit_price
+         FROM products WHERE product_name = ANY($1)
+         ORDER BY product_name, product_id",
+    )
+    .bind(&names)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|(name, id, price)| (name, (id, price)))
+    .collect();
+
+    Ok(ranked
+        .into_iter()
+        .map(|r| {
+            let product = products.get(&r.consequent);
+            Recommendation {
+                product_id: product.map(|p| p.0),
+                unit_price: product.map(|p| p.1),
+                product_name: r.consequent,
+                because_of: r.antecedent,
+                confidence: r.confidence,
+                lift: r.lift,
+                pair_count: r.pair_count,
+            }
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecommendationQuery {
+    #[serde(alias = "customer_id")]
+    pub customer_id: Option<String>,
+    /// Comma separated product names, e.g. the items already in the order
+    pub products: Option<String>,
+    pub limit: Option<usize>,
+}
+
+pub async fn get_recommendations_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<RecommendationQuery>,
+) -> MyceliumResult<Json<Vec<Recommendation>>> {
+    let customer_id = query.customer_id.as_deref().filter(|c| !c.is_empty());
+    let products: Vec<String> = query
+        .products
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect();
+    if customer_id.is_none() && products.is_empty() {
+        return Err(MyceliumError::Validation(
+            "고객 또는 상품을 지정해주세요.".to_string(),
+        ));
+    }
+    Ok(Json(
+        recommend_products(
+            &state.pool,
+            customer_id,
+            &products,
+            query.limit.unwrap_or(5).clamp(1, 20),
+        )
+        .await?,
+    ))
+}
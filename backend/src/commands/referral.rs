@@ -0,0 +1,783 @@
+use crate::commands::customer::CustomerIdQuery;
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use std::sync::atomic::Ordering;
+
+/// Acquisition channel set on customers who first ordered with a referral code
+pub const REFERRAL_CHANNEL: &str = "지인 소개";
+
+/// Without 0/O and 1/I so codes can be read out over the phone
+const CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferralRewardRule {
+    #[serde(default, alias = "rule_id")]
+    pub rule_id: Option<i32>,
+    #[serde(alias = "rule_name")]
+    pub rule_name: String,
+    pub recipient: String,
+    #[serde(alias = "reward_type")]
+    pub reward_type: String,
+    pub amount: i32,
+    #[serde(default, alias = "min_order_amount")]
+    pub min_order_amount: i32,
+    #[serde(default = "default_true", alias = "first_order_only")]
+    pub first_order_only: bool,
+    #[serde(default = "default_true", alias = "is_active")]
+    pub is_active: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ReferralRewardRule {
+    pub fn validate(&self) -> MyceliumResult<()> {
+        if self.rule_name.trim().is_empty() {
+            return Err(MyceliumError::Validation(
+                "규칙 이름을 입력해주세요.".to_string(),
+            ));
+        }
+        if !["referrer", "referee"].contains(&self.recipient.as_str())
+            || !["points", "discount"].contains(&self.reward_type.as_str())
+        {
+            return Err(MyceliumError::Validation(
+                "알 수 없는 보상 대상 또는 유형입니다.".to_string(),
+            ));
+        }
+        if self.amount <= 0 || self.min_order_amount < 0 {
+            return Err(MyceliumError::Validation(
+                "보상 값은 0보다 커야 합니다.".to_string(),
+            ));
+        }
+        if self.reward_type == "discount" {
+            // Only the person placing the order can get money off it
+            if self.recipient != "referee" {
+                return Err(MyceliumError::Validation(
+                    "할인 보상은 추천받은 고객에게만 줄 수 있습니다.".to_string(),
+                ));
+            }
+            if self.amount > 100 {
+                return Err(MyceliumError::Validation(
+                    "할인율은 100%를 넘을 수 없습니다.".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn generate_referral_code() -> String {
+    let mut rng = rand::rng();
+    let body: String = (0..6)
+        .map(|_| CODE_CHARS[rng.random_range(0..CODE_CHARS.len())] as char)
+        .collect();
+    format!("R{}", body)
+}
+
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+/// The discount given by a discount rule, in won: what was actually taken off the list
+/// price, but no more than the rule allows.
+pub fn discount_reward(list_amount: i64, total_amount: i64, rate: i32) -> i32 {
+    let allowed = list_amount * rate as i64 / 100;
+    (list_amount - total_amount).clamp(0, allowed) as i32
+}
+
+/// Returns the customer's code, creating one on first use.
+pub async fn ensure_referral_code(pool: &DbPool, customer_id: &str) -> MyceliumResult<String> {
+    for _ in 0..5 {
+        let existing: Option<(Option<String>,)> =
+            sqlx::query_as("SELECT referral_code FROM customers WHERE customer_id = $1")
+                .bind(customer_id)
+                .fetch_optional(pool)
+                .await?;
+        match existing {
+            None => {
+                return Err(MyceliumError::Validation(
+                    "고객을 찾을 수 없습니다.".to_string(),
+                ))
+            }
+            Some((Some(code),)) => return Ok(code),
+            Some((None,)) => {}
+        }
+        let result = sqlx::query(
+            "UPDATE customers SET referral_code = $2 WHERE customer_id = $1 AND referral_code IS NULL",
+        )
+        .bind(customer_id)
+        .bind(generate_referral_code())
+        .execute(pool)
+        .await;
+        match result {
+            Ok(_) => DB_MODIFIED.store(true, Ordering::Relaxed),
+            // Code taken by someone else: try another
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(MyceliumError::Internal(
+        "추천 코드를 만들지 못했습니다.".to_string(),
+    ))
+}
+
+async fn find_referrer(
+    conn: &mut PgConnection,
+    code: &str,
+) -> MyceliumResult<Option<(String, String)>> {
+    Ok(sqlx::query_as(
+        "SELECT customer_id, customer_name FROM customers
+         WHERE referral_code = $1 AND anonymized_at IS NULL",
+    )
+    .bind(normalize_code(code))
+    .fetch_optional(&mut *conn)
+    .await?)
+}
+
+/// Whether `customer_id` is up the referrer's chain of referrers, so linking the two would
+/// make them refer each other.
+async fn refers_back(
+    conn: &mut PgConnection,
+    customer_id: &str,
+    referrer_id: &str,
+) -> MyceliumResult<bool> {
+    let (cycle,): (bool,) = sqlx::query_as(
+        "WITH RECURSIVE chain (customer_id) AS (
+             SELECT referred_by FROM customers WHERE customer_id = $2
+             UNION
+             SELECT c.referred_by FROM customers c JOIN chain ON c.customer_id = chain.customer_id
+         )
+         SELECT EXISTS (SELECT 1 FROM chain WHERE customer_id = $1)",
+    )
+    .bind(customer_id)
+    .bind(referrer_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(cycle)
+}
+
+/// Whether the customer already ordered with a code (other than `sales_id`).
+async fn has_referred_order(
+    conn: &mut PgConnection,
+    customer_id: &str,
+    sales_id: Option<&str>,
+) -> MyceliumResult<bool> {
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (
+             SELECT 1 FROM sales WHERE customer_id = $1 AND referral_code IS NOT NULL
+               AND sales_id IS DISTINCT FROM $2 AND status NOT IN ('취소', '반품', '반품완료')
+         )",
+    )
+    .bind(customer_id)
+    .bind(sales_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(exists)
+}
+
+/// Records the code a sale was placed with. The ordering customer is linked to the
+/// referrer unless they already have one. A blank code is ignored.
+pub async fn attach_referral(
+    conn: &mut PgConnection,
+    sales_id: &str,
+    code: &str,
+) -> MyceliumResult<()> {
+    if code.trim().is_empty() {
+        return Ok(());
+    }
+    let Some((referrer_id, _)) = find_referrer(conn, code).await? else {
+        return Err(MyceliumError::Validation(format!(
+            "존재하지 않는 추천 코드입니다: {}",
+            code.trim()
+        )));
+    };
+    let (customer_id,): (Option<String>,) =
+        sqlx::query_as("SELECT customer_id FROM sales WHERE sales_id = $1")
+            .bind(sales_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    if customer_id.as_deref() == Some(referrer_id.as_str()) {
+        return Err(MyceliumError::Validation(
+            "본인의 추천 코드는 사용할 수 없습니다.".to_string(),
+        ));
+    }
+    if let Some(customer_id) = customer_id.as_deref() {
+        if refers_back(conn, customer_id, &referrer_id).await? {
+            return Err(MyceliumError::Validation(
+                "이 고객이 추천한 고객의 추천 코드는 사용할 수 없습니다.".to_string(),
+            ));
+        }
+    }
+
+    sqlx::query("UPDATE sales SET referral_code = $2 WHERE sales_id = $1")
+        .bind(sales_id)
+        .bind(normalize_code(code))
+        .execute(&mut *conn)
+        .await?;
+    if let Some(customer_id) = customer_id {
+        sqlx::query(
+            "UPDATE customers SET referred_by = $2,
+                acquisition_channel = COALESCE(NULLIF(acquisition_channel, ''), $3)
+             WHERE customer_id = $1 AND referred_by IS NULL",
+        )
+        .bind(customer_id)
+        .bind(&referrer_id)
+        .bind(REFERRAL_CHANNEL)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn load_reward_rules(
+    pool: &DbPool,
+    active_only: bool,
+) -> MyceliumResult<Vec<ReferralRewardRule>> {
+    Ok(sqlx::query_as::<_, ReferralRewardRule>(
+        "SELECT rule_id, rule_name, recipient, reward_type, amount, min_order_amount,
+                first_order_only, is_active
+         FROM referral_reward_rules WHERE is_active OR NOT $1 ORDER BY rule_id",
+    )
+    .bind(active_only)
+    .fetch_all(pool)
+    .await?)
+}
+
+#[derive(sqlx::FromRow)]
+struct ReferredSale {
+    sales_id: String,
+    referee_id: String,
+    referrer_id: String,
+    list_amount: i64,
+    total_amount: i64,
+}
+
+/// Gives the rewards of every active rule for delivered orders placed with a code (all
+/// of them, or one sale). Points rewards need the points program to be enabled. Safe to
+/// run repeatedly.
+pub async fn award_referral_rewards(
+    pool: &DbPool,
+    sales_id: Option<&str>,
+) -> MyceliumResult<usize> {
+    let rules = load_reward_rules(pool, true).await?;
+    if rules.is_empty() {
+        return Ok(0);
+    }
+    let points = crate::commands::points::load_points_config(pool)
+        .await?
+        .settings;
+
+    let sales: Vec<ReferredSale> = sqlx::query_as(
+        "SELECT s.sales_id, s.customer_id AS referee_id, r.customer_id AS referrer_id,
+                (s.unit_price::bigint * s.quantity) AS list_amount, s.total_amount::bigint AS total_amount
+         FROM sales s
+         JOIN customers r ON r.referral_code = s.referral_code
+         WHERE s.status = '배송완료' AND s.customer_id IS NOT NULL AND s.customer_id <> r.customer_id
+           AND ($1::text IS NULL OR s.sales_id = $1)
+         ORDER BY s.order_date, s.sales_id",
+    )
+    .bind(sales_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut given = 0;
+    for sale in &sales {
+        for rule in &rules {
+            let Some(rule_id) = rule.rule_id else {
+                continue;
+            };
+            if sale.total_amount < rule.min_order_amount as i64
+                || (rule.reward_type == "points" && !points.enabled)
+            {
+                continue;
+            }
+            let amount = match rule.reward_type.as_str() {
+                "discount" => discount_reward(sale.list_amount, sale.total_amount, rule.amount),
+                _ => rule.amount,
+            };
+            if amount <= 0 {
+                continue;
+            }
+
+            let mut tx = pool.begin().await?;
+            if rule.first_order_only {
+                let (earlier,): (bool,) = sqlx::query_as(
+                    "SELECT EXISTS (SELECT 1 FROM referral_rewards
+                                    WHERE rule_id = $1 AND referee_id = $2 AND sales_id <> $3)",
+                )
+                .bind(rule_id)
+                .bind(&sale.referee_id)
+                .bind(&sale.sales_id)
+                .fetch_one(&mut *tx)
+                .await?;
+                if earlier {
+                    continue;
+                }
+            }
+            let recipient_id = if rule.recipient == "referrer" {
+                &sale.referrer_id
+            } else {
+                &sale.referee_id
+            };
+            let inserted = sqlx::query(
+                "INSERT INTO referral_rewards (rule_id, sales_id, referrer_id, referee_id, recipient, reward_type, amount)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (rule_id, sales_id) DO NOTHING",
+            )
+            .bind(rule_id)
+            .bind(&sale.sales_id)
+            .bind(&sale.referrer_id)
+            .bind(&sale.referee_id)
+            .bind(&rule.recipient)
+            .bind(&rule.reward_type)
+            .bind(amount)
+            .execute(&mut *tx)
+            .await?;
+            if inserted.rows_affected() == 0 {
+                continue;
+            }
+            if rule.reward_type == "points" {
+                sqlx::query(
+                    "INSERT INTO point_ledger (customer_id, entry_type, points, remaining, expires_at, sales_id, memo, created_by)
+                     VALUES ($1, '추천적립', $2, $2, CURRENT_DATE + make_interval(months => $3), $4, $5, 'System')",
+                )
+                .bind(recipient_id)
+                .bind(amount)
+                .bind(points.expiry_months)
+                .bind(&sale.sales_id)
+                .bind(format!("추천 보상: {}", rule.rule_name))
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            DB_MODIFIED.store(true, Ordering::Relaxed);
+            given += 1;
+        }
+    }
+    Ok(given)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferralCodeCheck {
+    pub referrer_id: String,
+    pub referrer_name: String,
+    /// Best discount (percent) the ordering customer gets with this code, 0 if none
+    pub discount_rate: i32,
+    pub min_order_amount: i32,
+}
+
+/// Validates a code entered on an order and tells the order screen which discount applies.
+pub async fn check_referral_code(
+    pool: &DbPool,
+    code: &str,
+    customer_id: Option<&str>,
+) -> MyceliumResult<ReferralCodeCheck> {
+    let mut conn = pool.acquire().await?;
+    let Some((referrer_id, referrer_name)) = find_referrer(&mut conn, code).await? else {
+        return Err(MyceliumError::Validation(
+            "존재하지 않는 추천 코드입니다.".to_string(),
+        ));
+    };
+    if customer_id == Some(referrer_id.as_str()) {
+        return Err(MyceliumError::Validation(
+            "본인의 추천 코드는 사용할 수 없습니다.".to_string(),
+        ));
+    }
+    let repeat = match customer_id {
+        Some(id) => has_referred_order(&mut conn, id, None).await?,
+        None => false,
+    };
+    drop(conn);
+
+    let best = load_reward_rules(pool, true)
+        .await?
+        .into_iter()
+        .filter(|r| r.reward_type == "discount" && !(r.first_order_only && repeat))
+        .max_by_key(|r| r.amount);
+    Ok(ReferralCodeCheck {
+        referrer_id,
+        referrer_name,
+        discount_rate: best.as_ref().map(|r| r.amount).unwrap_or(0),
+        min_order_amount: best.map(|r| r.min_order_amount).unwrap_or(0),
+    })
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ReferredCustomer {
+    pub customer_id: String,
+    pub customer_name: String,
+    pub join_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomerReferralInfo {
+    pub referral_code: String,
+    pub referred_by: Option<String>,
+    pub referred_by_name: Option<String>,
+    pub referred_customers: Vec<ReferredCustomer>,
+}
+
+pub async fn get_customer_referral_info(
+    pool: &DbPool,
+    customer_id: &str,
+) -> MyceliumResult<CustomerReferralInfo> {
+    let referral_code = ensure_referral_code(pool, customer_id).await?;
+    let (referred_by, referred_by_name): (Option<String>, Option<String>) = sqlx::query_as(
+        "SELECT c.referred_by, r.customer_name FROM customers c
+         LEFT JOIN customers r ON r.customer_id = c.referred_by
+         WHERE c.customer_id = $1",
+    )
+    .bind(customer_id)
+    .fetch_one(pool)
+    .await?;
+    let referred_customers = sqlx::query_as::<_, ReferredCustomer>(
+        "SELECT customer_id, customer_name, join_date FROM customers
+         WHERE referred_by = $1 ORDER BY join_date DESC NULLS LAST, customer_id",
+    )
+    .bind(customer_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(CustomerReferralInfo {
+        referral_code,
+        referred_by,
+        referred_by_name,
+        referred_customers,
+    })
+}
+
+/// Links a customer to who referred them, by the referrer's code (set or corrected by hand).
+pub async fn set_referrer(
+    pool: &DbPool,
+    username: &str,
+    customer_id: &str,
+    code: Option<&str>,
+) -> MyceliumResult<()> {
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+    let referrer_id = match code.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(code) => {
+            let Some((referrer_id, _)) = find_referrer(&mut tx, code).await? else {
+                return Err(MyceliumError::Validation(
+                    "존재하지 않는 추천 코드입니다.".to_string(),
+                ));
+            };
+            if referrer_id == customer_id {
+                return Err(MyceliumError::Validation(
+                    "본인을 추천인으로 지정할 수 없습니다.".to_string(),
+                ));
+            }
+            if refers_back(&mut tx, customer_id, &referrer_id).await? {
+                return Err(MyceliumError::Validation(
+                    "이 고객이 추천한 고객을 추천인으로 지정할 수 없습니다.".to_string(),
+                ));
+            }
+            Some(referrer_id)
+        }
+        None => None,
+    };
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let old: (Option<String>,) =
+        sqlx::query_as("SELECT referred_by FROM customers WHERE customer_id = $1")
+            .bind(customer_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    sqlx::query("UPDATE customers SET referred_by = $2 WHERE customer_id = $1")
+        .bind(customer_id)
+        .bind(&referrer_id)
+        .execute(&mut *tx)
+        .await?;
+    if old.0 != referrer_id {
+        sqlx::query(
+            "INSERT INTO customer_logs (customer_id, field_name, old_value, new_value, changed_by)
+             VALUES ($1, 'referred_by', $2, $3, $4)",
+        )
+        .bind(customer_id)
+        .bind(old.0)
+        .bind(&referrer_id)
+        .bind(username)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TopReferrer {
+    pub customer_id: String,
+    pub customer_name: String,
+    pub referral_code: Option<String>,
+    pub referred_customers: i64,
+    pub referred_orders: i64,
+    pub referred_revenue: i64,
+    pub reward_points: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferralReport {
+    pub top_referrers: Vec<TopReferrer>,
+    pub total_referred_customers: i64,
+    pub total_referred_orders: i64,
+    pub total_referred_revenue: i64,
+    pub total_reward_points: i64,
+    pub total_discount: i64,
+}
+
+/// Referrers ranked by the revenue of orders placed with their code in the period
+/// (cancelled and returned orders excluded), with the customers they brought in who
+/// joined in the period.
+pub async fn referral_report(
+    pool: &DbPool,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    limit: i64,
+) -> MyceliumResult<ReferralReport> {
+    let orders = "SELECT r.customer_id AS referrer_id, s.sales_id, s.total_amount
+         FROM sales s JOIN customers r ON r.referral_code = s.referral_code
+         WHERE s.status NOT IN ('취소', '반품', '반품완료')
+           AND ($1::date IS NULL OR s.order_date >= $1) AND ($2::date IS NULL OR s.order_date <= $2)";
+    let rewards = "SELECT referrer_id, recipient, reward_type, amount FROM referral_rewards
+         WHERE ($1::date IS NULL OR created_at::date >= $1) AND ($2::date IS NULL OR created_at::date <= $2)";
+    let referred = "SELECT customer_id, referred_by FROM customers
+         WHERE referred_by IS NOT NULL
+           AND ($1::date IS NULL OR join_date >= $1) AND ($2::date IS NULL OR join_date <= $2)";
+
+    let top_referrers = sqlx::query_as::<_, TopReferrer>(&format!(
+        "WITH orders AS ({orders}), rewards AS ({rewards}), referred AS ({referred})
+         SELECT c.customer_id, c.customer_name, c.referral_code,
+                (SELECT COUNT(*) FROM referred x WHERE x.referred_by = c.customer_id) AS referred_customers,
+                COALESCE(o.cnt, 0) AS referred_orders,
+                COALESCE(o.revenue, 0) AS referred_revenue,
+                COALESCE((SELECT SUM(amount) FROM rewards w
+                          WHERE w.referrer_id = c.customer_id AND w.recipient = 'referrer'
+                            AND w.reward_type = 'points'), 0)::bigint AS reward_points
+         FROM customers c
+         LEFT JOIN (SELECT referrer_id, COUNT(*) AS cnt, SUM(total_amount)::bigint AS revenue
+                    FROM orders GROUP BY referrer_id) o ON o.referrer_id = c.customer_id
+         WHERE o.referrer_id IS NOT NULL
+            OR EXISTS (SELECT 1 FROM referred x WHERE x.referred_by = c.customer_id)
+         ORDER BY referred_revenue DESC, referred_customers DESC, c.customer_id
+         LIMIT $3"
+    ))
+    .bind(from)
+    .bind(to)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let (total_referred_customers,): (i64,) =
+        sqlx::query_as(&format!("SELECT COUNT(*) FROM ({referred}) x"))
+            .bind(from)
+            .bind(to)
+            .fetch_one(pool)
+            .await?;
+    let (total_referred_orders, total_referred_revenue): (i64, i64) = sqlx::query_as(&format!(
+        "SELECT COUNT(*), COALESCE(SUM(total_amount), 0)::bigint FROM ({orders}) o"
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_one(pool)
+    .await?;
+    let (total_reward_points, total_discount): (i64, i64) = sqlx::query_as(&format!(
+        "SELECT COALESCE(SUM(amount) FILTER (WHERE reward_type = 'points'), 0)::bigint,
+                COALESCE(SUM(amount) FILTER (WHERE reward_type = 'discount'), 0)::bigint
+         FROM ({rewards}) w"
+    ))
+    .bind(from)
+    .bind(to)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(ReferralReport {
+        top_referrers,
+        total_referred_customers,
+        total_referred_orders,
+        total_referred_revenue,
+        total_reward_points,
+        total_discount,
+    })
+}
+
+pub async fn get_customer_referral_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<CustomerIdQuery>,
+) -> MyceliumResult<Json<CustomerReferralInfo>> {
+    Ok(Json(
+        get_customer_referral_info(&state.pool, &params.customer_id).await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetReferrerInput {
+    #[serde(alias = "customer_id")]
+    pub customer_id: String,
+    /// Referrer's code; empty clears the link
+    #[serde(alias = "referral_code")]
+    pub referral_code: Option<String>,
+}
+
+pub async fn set_referrer_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<SetReferrerInput>,
+) -> MyceliumResult<Json<()>> {
+    set_referrer(
+        &state.pool,
+        claims.username.as_deref().unwrap_or("Admin"),
+        &input.customer_id,
+        input.referral_code.as_deref(),
+    )
+    .await?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+pub struct ReferralCheckQuery {
+    pub code: String,
+    #[serde(alias = "customerId")]
+    pub customer_id: Option<String>,
+}
+
+pub async fn check_referral_code_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<ReferralCheckQuery>,
+) -> MyceliumResult<Json<ReferralCodeCheck>> {
+    Ok(Json(
+        check_referral_code(&state.pool, &query.code, query.customer_id.as_deref()).await?,
+    ))
+}
+
+pub async fn get_referral_rules_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<ReferralRewardRule>>> {
+    Ok(Json(load_reward_rules(&state.pool, false).await?))
+}
+
+pub async fn save_referral_rule_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(rule): Json<ReferralRewardRule>,
+) -> MyceliumResult<Json<i32>> {
+    rule.validate()?;
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let saved: Option<(i32,)> = match rule.rule_id {
+        Some(id) => {
+            sqlx::query_as(
+                "UPDATE referral_reward_rules SET rule_name = $2, recipient = $3, reward_type = $4,
+                    amount = $5, min_order_amount = $6, first_order_only = $7, is_active = $8
+                 WHERE rule_id = $1 RETURNING rule_id",
+            )
+            .bind(id)
+        }
+        None => sqlx::query_as(
+            "INSERT INTO referral_reward_rules
+                (rule_name, recipient, reward_type, amount, min_order_amount, first_order_only, is_active)
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING rule_id",
+        ),
+    }
+    .bind(rule.rule_name.trim())
+    .bind(&rule.recipient)
+    .bind(&rule.reward_type)
+    .bind(rule.amount)
+    .bind(rule.min_order_amount)
+    .bind(rule.first_order_only)
+    .bind(rule.is_active)
+    .fetch_optional(&state.pool)
+    .await?;
+    let Some((rule_id,)) = saved else {
+        return Err(MyceliumError::Validation(
+            "추천 보상 규칙을 찾을 수 없습니다.".to_string(),
+        ));
+    };
+    Ok(Json(rule_id))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleIdInput {
+    #[serde(alias = "rule_id")]
+    pub rule_id: i32,
+}
+
+/// Rules that already gave rewards are deactivated instead, to keep the history readable.
+pub async fn delete_referral_rule_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<RuleIdInput>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query(
+        "DELETE FROM referral_reward_rules r WHERE rule_id = $1
+           AND NOT EXISTS (SELECT 1 FROM referral_rewards w WHERE w.rule_id = r.rule_id)",
+    )
+    .bind(input.rule_id)
+    .execute(&state.pool)
+    .await?;
+    sqlx::query("UPDATE referral_reward_rules SET is_active = FALSE WHERE rule_id = $1")
+        .bind(input.rule_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+pub struct ReferralReportQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub limit: Option<i64>,
+}
+
+pub async fn get_referral_report_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<ReferralReportQuery>,
+) -> MyceliumResult<Json<ReferralReport>> {
+    Ok(Json(
+        referral_report(
+            &state.pool,
+            query.from,
+            query.to,
+            query.limit.unwrap_or(20).clamp(1, 200),
+        )
+        .await?,
+    ))
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ReferralReward {
+    pub reward_id: i32,
+    pub rule_id: i32,
+    pub sales_id: String,
+    pub referrer_id: String,
+    pub referee_id: String,
+    pub recipient: String,
+    pub reward_type: String,
+    pub amount: i32,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+pub async fn get_referral_rewards_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(params): Query<CustomerIdQuery>,
+) -> MyceliumResult<Json<Vec<ReferralReward>>> {
+    Ok(Json(
+        sqlx::query_as::<_, ReferralReward>(
+            "SELECT * FROM referral_rewards WHERE referrer_id = $1 OR referee_id = $1
+             ORDER BY created_at DESC, reward_id DESC",
+        )
+        .bind(&params.customer_id)
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
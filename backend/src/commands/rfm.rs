@@ -0,0 +1,470 @@
+use crate::db::{CustomerLifecycle, DbPool, RawRfmData};
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Json,
+};
+use chrono::{Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+/// Label used in transition reports for customers missing from one of the two snapshots
+pub const NO_SEGMENT: &str = "(없음)";
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RfmSegmentRule {
+    #[serde(default)]
+    pub priority: i32,
+    pub segment_name: String,
+    pub r_min: i32,
+    pub r_max: i32,
+    pub f_min: i32,
+    pub f_max: i32,
+    pub m_min: i32,
+    pub m_max: i32,
+}
+
+impl RfmSegmentRule {
+    pub fn matches(&self, r: i32, f: i32, m: i32) -> bool {
+        (self.r_min..=self.r_max).contains(&r)
+            && (self.f_min..=self.f_max).contains(&f)
+            && (self.m_min..=self.m_max).contains(&m)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RfmSettings {
+    /// "threshold" or "quantile"
+    pub mode: String,
+    pub recency_days: Vec<i32>,
+    pub frequency_orders: Vec<i32>,
+    pub monetary_amounts: Vec<i64>,
+    pub default_segment: String,
+    /// Evaluated in order; the first match wins
+    pub rules: Vec<RfmSegmentRule>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SegmentTransition {
+    pub from_segment: String,
+    pub to_segment: String,
+    pub customers: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SegmentTransitionReport {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub transitions: Vec<SegmentTransition>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SegmentTransitionCustomer {
+    pub customer_id: String,
+    pub customer_name: Option<String>,
+    pub mobile_number: Option<String>,
+    pub from_segment: String,
+    pub to_segment: String,
+}
+
+/// Threshold score: one point for every cut-off the value is above, starting from 1.
+pub fn threshold_score<T: PartialOrd>(value: T, cutoffs: &[T]) -> i32 {
+    1 + cutoffs.iter().filter(|c| value > **c).count().min(4) as i32
+}
+
+/// Recency score: 5 below the first cut-off, one point less for every cut-off reached.
+pub fn recency_threshold_score(days: Option<i64>, cutoffs: &[i32]) -> i32 {
+    match days {
+        Some(d) => 5 - cutoffs.iter().filter(|c| d >= **c as i64).count().min(4) as i32,
+        None => 1,
+    }
+}
+
+/// Quintile score of `value` within `sorted` (ascending). Equal values share a score.
+pub fn quantile_score(value: i64, sorted: &[i64], higher_is_better: bool) -> i32 {
+    if sorted.is_empty() {
+        return 1;
+    }
+    let beaten = if higher_is_better {
+        sorted.partition_point(|v| *v < value)
+    } else {
+        sorted.len() - sorted.partition_point(|v| *v <= value)
+    };
+    // Percentile rank: the best customer gets 5 and the worst 1 whatever the base size
+    (1 + (beaten * 5 / (sorted.len() - 1).max(1)) as i32).min(5)
+}
+
+pub fn segment_for(settings: &RfmSettings, r: i32, f: i32, m: i32) -> String {
+    settings
+        .rules
+        .iter()
+        .find(|rule| rule.matches(r, f, m))
+        .map(|rule| rule.segment_name.clone())
+        .unwrap_or_else(|| settings.default_segment.clone())
+}
+
+/// Scores and segments customers according to the configured mode.
+pub fn score_customers(
+    settings: &RfmSettings,
+    raw: Vec<RawRfmData>,
+    today: NaiveDate,
+) -> Vec<CustomerLifecycle> {
+    let days = |d: &RawRfmData| d.last_order_date.map(|date| (today - date).num_days());
+    let quantile = settings.mode == "quantile";
+
+    // Customers without a last order count as the least recent
+    let mut recency_sorted: Vec<i64> = raw.iter().map(|d| days(d).unwrap_or(i64::MAX)).collect();
+    let mut frequency_sorted: Vec<i64> = raw.iter().map(|d| d.total_orders).collect();
+    let mut monetary_sorted: Vec<i64> = raw.iter().map(|d| d.total_amount).collect();
+    let order_cutoffs: Vec<i64> = settings
+        .frequency_orders
+        .iter()
+        .map(|v| *v as i64)
+        .collect();
+    recency_sorted.sort_unstable();
+    frequency_sorted.sort_unstable();
+    monetary_sorted.sort_unstable();
+
+    raw.into_iter()
+        .map(|d| {
+            let since = days(&d);
+            let (recency, frequency, monetary) = if quantile {
+                (
+                    quantile_score(since.unwrap_or(i64::MAX), &recency_sorted, false),
+                    quantile_score(d.total_orders, &frequency_sorted, true),
+                    quantile_score(d.total_amount, &monetary_sorted, true),
+                )
+            } else {
+                (
+                    recency_threshold_score(since, &settings.recency_days),
+                    threshold_score(d.total_orders, &order_cutoffs),
+                    threshold_score(d.total_amount, &settings.monetary_amounts),
+                )
+            };
+
+            CustomerLifecycle {
+                rfm_segment: segment_for(settings, recency, frequency, monetary),
+                customer_id: d.customer_id,
+                customer_name: d.customer_name,
+                mobile_number: d.mobile_number,
+                membership_level: d.membership_level,
+                last_order_date: d.last_order_date,
+                total_orders: d.total_orders,
+                total_amount: d.total_amount,
+                days_since_last_order: since.unwrap_or(999),
+                recency,
+                frequency,
+                monetary,
+            }
+        })
+        .collect()
+}
+
+#[derive(sqlx::FromRow)]
+struct RfmConfigRow {
+    mode: String,
+    recency_days: Vec<i32>,
+    frequency_orders: Vec<i32>,
+    monetary_amounts: Vec<i64>,
+    default_segment: String,
+}
+
+pub async fn load_rfm_settings(pool: &DbPool) -> MyceliumResult<RfmSettings> {
+    let config = sqlx::query_as::<_, RfmConfigRow>(
+        "SELECT mode, recency_days, frequency_orders, monetary_amounts, default_segment
+         FROM rfm_config WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await?
+    .unwrap_or_else(|| RfmConfigRow {
+        mode: "threshold".to_string(),
+        recency_days: vec![30, 90, 180, 365],
+        frequency_orders: vec![2, 5, 10, 20],
+        monetary_amounts: vec![100_000, 500_000, 1_000_000, 2_000_000],
+        default_segment: "일반고객 (Need Attention)".to_string(),
+    });
+    let rules = sqlx::query_as::<_, RfmSegmentRule>(
+        "SELECT priority, segment_name, r_min, r_max, f_min, f_max, m_min, m_max
+         FROM rfm_segment_rules ORDER BY priority, rule_id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(RfmSettings {
+        mode: config.mode,
+        recency_days: config.recency_days,
+        frequency_orders: config.frequency_orders,
+        monetary_amounts: config.monetary_amounts,
+        default_segment: config.default_segment,
+        rules,
+    })
+}
+
+fn validate_settings(s: &RfmSettings) -> MyceliumResult<()> {
+    let invalid = |msg: &str| Err(MyceliumError::Validation(msg.to_string()));
+    if s.mode != "threshold" && s.mode != "quantile" {
+        return invalid("RFM 방식은 threshold 또는 quantile 이어야 합니다.");
+    }
+    let ascending = |v: &[i64]| v.len() == 4 && v.windows(2).all(|w| w[0] < w[1]) && v[0] >= 0;
+    if !ascending(&s.recency_days.iter().map(|v| *v as i64).collect::<Vec<_>>())
+        || !ascending(
+            &s.frequency_orders
+                .iter()
+                .map(|v| *v as i64)
+                .collect::<Vec<_>>(),
+        )
+        || !ascending(&s.monetary_amounts)
+    {
+        return invalid("R/F/M 기준값은 4개씩, 작은 값부터 차례로 입력해주세요.");
+    }
+    if s.default_segment.trim().is_empty() {
+        return invalid("기본 세그먼트 이름을 입력해주세요.");
+    }
+    for rule in &s.rules {
+        let in_range =
+            |min: i32, max: i32| (1..=5).contains(&min) && (1..=5).contains(&max) && min <= max;
+        if rule.segment_name.trim().is_empty() {
+            return invalid("세그먼트 이름을 입력해주세요.");
+        }
+        if !in_range(rule.r_min, rule.r_max)
+            || !in_range(rule.f_min, rule.f_max)
+            || !in_range(rule.m_min, rule.m_max)
+        {
+            return invalid("세그먼트 조건의 점수 범위는 1~5 사이여야 합니다.");
+        }
+    }
+    Ok(())
+}
+
+/// Stores today's segment of every customer with orders. Running it again on the same day
+/// replaces that day's snapshot. Returns the number of customers recorded.
+pub async fn take_segment_snapshot(pool: &DbPool, date: NaiveDate) -> MyceliumResult<usize> {
+    let analysis = super::crm::get_rfm_analysis(crate::stubs::State::from(pool)).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM customer_segment_snapshots WHERE snapshot_date = $1")
+        .bind(date)
+        .execute(&mut *tx)
+        .await?;
+    for chunk in analysis.chunks(1000) {
+        sqlx::query(
+            "INSERT INTO customer_segment_snapshots (snapshot_date, customer_id, segment_name, recency, frequency, monetary)
+             SELECT $1, * FROM UNNEST($2::text[], $3::text[], $4::int[], $5::int[], $6::int[])",
+        )
+        .bind(date)
+        .bind(chunk.iter().map(|c| c.customer_id.clone()).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|c| c.rfm_segment.clone()).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|c| c.recency).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|c| c.frequency).collect::<Vec<_>>())
+        .bind(chunk.iter().map(|c| c.monetary).collect::<Vec<_>>())
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(analysis.len())
+}
+
+/// Called by the background scheduler; takes the day's snapshot once, after 2 a.m.
+pub async fn take_segment_snapshot_if_due(pool: &DbPool) -> MyceliumResult<bool> {
+    let now = Local::now();
+    if now.time() < chrono::NaiveTime::from_hms_opt(2, 0, 0).unwrap_or_default() {
+        return Ok(false);
+    }
+    let today = now.date_naive();
+    let (taken,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM customer_segment_snapshots WHERE snapshot_date = $1)",
+    )
+    .bind(today)
+    .fetch_one(pool)
+    .await?;
+    if taken {
+        return Ok(false);
+    }
+    take_segment_snapshot(pool, today).await?;
+    Ok(true)
+}
+
+/// Latest snapshot date on or before `date`
+async fn snapshot_on_or_before(
+    pool: &DbPool,
+    date: NaiveDate,
+) -> MyceliumResult<Option<NaiveDate>> {
+    let (found,): (Option<NaiveDate>,) = sqlx::query_as(
+        "SELECT MAX(snapshot_date) FROM customer_segment_snapshots WHERE snapshot_date <= $1",
+    )
+    .bind(date)
+    .fetch_one(pool)
+    .await?;
+    Ok(found)
+}
+
+const TRANSITION_JOIN: &str = "
+    FROM (SELECT customer_id, segment_name FROM customer_segment_snapshots WHERE snapshot_date = $1) a
+    FULL OUTER JOIN (SELECT customer_id, segment_name FROM customer_segment_snapshots WHERE snapshot_date = $2) b
+        ON a.customer_id = b.customer_id";
+
+/// Segment migrations between the snapshots nearest to (on or before) the two dates.
+pub async fn get_segment_transitions_internal(
+    pool: &DbPool,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> MyceliumResult<SegmentTransitionReport> {
+    let from_date = snapshot_on_or_before(pool, from).await?;
+    let to_date = snapshot_on_or_before(pool, to).await?;
+    let (Some(a), Some(b)) = (from_date, to_date) else {
+        return Ok(SegmentTransitionReport {
+            from_date,
+            to_date,
+            transitions: vec![],
+        });
+    };
+
+    let transitions = sqlx::query_as::<_, SegmentTransition>(&format!(
+        "SELECT COALESCE(a.segment_name, $3) AS from_segment, COALESCE(b.segment_name, $3) AS to_segment,
+                COUNT(*) AS customers
+         {TRANSITION_JOIN}
+         GROUP BY 1, 2
+         ORDER BY 1, 3 DESC"
+    ))
+    .bind(a)
+    .bind(b)
+    .bind(NO_SEGMENT)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(SegmentTransitionReport {
+        from_date,
+        to_date,
+        transitions,
+    })
+}
+
+#[derive(Deserialize)]
+pub struct TransitionQuery {
+    pub from_date: Option<String>,
+    pub to_date: Option<String>,
+    pub from_segment: Option<String>,
+    pub to_segment: Option<String>,
+}
+
+/// Defaults compare the start of the current month with the latest snapshot.
+fn transition_dates(query: &TransitionQuery) -> (NaiveDate, NaiveDate) {
+    use crate::commands::sales::utils::parse_date_safe;
+    let today = Local::now().date_naive();
+    let from = query
+        .from_date
+        .as_deref()
+        .and_then(parse_date_safe)
+        .unwrap_or_else(|| today.with_day(1).unwrap_or(today));
+    let to = query
+        .to_date
+        .as_deref()
+        .and_then(parse_date_safe)
+        .unwrap_or(today);
+    (from, to)
+}
+
+pub async fn get_rfm_settings_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<RfmSettings>> {
+    Ok(Json(load_rfm_settings(&state.pool).await?))
+}
+
+pub async fn save_rfm_settings_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(payload): Json<RfmSettings>,
+) -> MyceliumResult<Json<()>> {
+    validate_settings(&payload)?;
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    sqlx::query(
+        "UPDATE rfm_config SET mode = $1, recency_days = $2, frequency_orders = $3, monetary_amounts = $4,
+            default_segment = $5, updated_at = CURRENT_TIMESTAMP
+         WHERE id = 1",
+    )
+    .bind(&payload.mode)
+    .bind(&payload.recency_days)
+    .bind(&payload.frequency_orders)
+    .bind(&payload.monetary_amounts)
+    .bind(payload.default_segment.trim())
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM rfm_segment_rules")
+        .execute(&mut *tx)
+        .await?;
+    for (i, rule) in payload.rules.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO rfm_segment_rules (priority, segment_name, r_min, r_max, f_min, f_max, m_min, m_max)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(i as i32)
+        .bind(rule.segment_name.trim())
+        .bind(rule.r_min)
+        .bind(rule.r_max)
+        .bind(rule.f_min)
+        .bind(rule.f_max)
+        .bind(rule.m_min)
+        .bind(rule.m_max)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(Json(()))
+}
+
+pub async fn take_segment_snapshot_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<usize>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    Ok(Json(
+        take_segment_snapshot(&state.pool, Local::now().date_naive()).await?,
+    ))
+}
+
+pub async fn get_segment_transitions_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<TransitionQuery>,
+) -> MyceliumResult<Json<SegmentTransitionReport>> {
+    let (from, to) = transition_dates(&query);
+    Ok(Json(
+        get_segment_transitions_internal(&state.pool, from, to).await?,
+    ))
+}
+
+/// Customers behind one cell of the transition report (e.g. Champions → At Risk).
+pub async fn get_segment_transition_customers_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<TransitionQuery>,
+) -> MyceliumResult<Json<Vec<SegmentTransitionCustomer>>> {
+    let (from, to) = transition_dates(&query);
+    let (Some(a), Some(b)) = (
+        snapshot_on_or_before(&state.pool, from).await?,
+        snapshot_on_or_before(&state.pool, to).await?,
+    ) else {
+        return Ok(Json(vec![]));
+    };
+
+    Ok(Json(
+        sqlx::query_as::<_, SegmentTransitionCustomer>(&format!(
+            "SELECT COALESCE(a.customer_id, b.customer_id) AS customer_id, c.customer_name, c.mobile_number,
+                    COALESCE(a.segment_name, $3) AS from_segment, COALESCE(b.segment_name, $3) AS to_segment
+             {TRANSITION_JOIN}
+             LEFT JOIN customers c ON c.customer_id = COALESCE(a.customer_id, b.customer_id)
+             WHERE ($4::text IS NULL OR COALESCE(a.segment_name, $3) = $4)
+               AND ($5::text IS NULL OR COALESCE(b.segment_name, $3) = $5)
+             ORDER BY c.customer_name
+             LIMIT 1000"
+        ))
+        .bind(a)
+        .bind(b)
+        .bind(NO_SEGMENT)
+        .bind(query.from_segment.filter(|s| !s.is_empty()))
+        .bind(query.to_segment.filter(|s| !s.is_empty()))
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
@@ -372,14 +372,22 @@ pub struct GeneralSalesBatchItem {
     pub paymentStatus: Option<String>,
     pub discountRate: i32,
     pub isDirty: String,
+    /// Referral code the customer gave with the order
+    #[serde(default)]
+    pub referralCode: Option<String>,
+    /// Points the customer pays a new order with
+    #[serde(default)]
+    pub pointsUsed: Option<i32>,
 }
 
 pub async fn save_general_sales_batch_internal(
     pool: &DbPool,
+    username: &str,
     items: Vec<GeneralSalesBatchItem>,
     deleted_ids: Vec<String>,
 ) -> MyceliumResult<()> {
     let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
 
     for del_id in deleted_ids {
         sqlx::query("DELETE FROM sales WHERE sales_id = $1")
@@ -474,6 +482,9 @@ pub async fn save_general_sales_batch_internal(
 
                 sqlx::query("UPDATE sales SET customer_id = $1, product_name = $2, specification = $3, quantity = $4, unit_price = $5, total_amount = $6, status = $7, memo = $8, order_date = $9, shipping_name = $10, shipping_zip_code = $11, shipping_address_primary = $12, shipping_address_detail = $13, shipping_mobile_number = $14, paid_amount = $15, payment_status = $16, discount_rate = $17, product_id = $18, supply_value = $19, vat_amount = $20, tax_type = $21, tax_exempt_value = $22 WHERE sales_id = $23")
                 .bind(&item.customerId).bind(&item.productName).bind(&item.specification).bind(item.quantity).bind(item.unitPrice).bind(item.totalAmount).bind(&item.status).bind(&item.memo).bind(order_date_parsed).bind(&item.shippingName).bind(&item.shippingZipCode).bind(&item.shippingAddressPrimary).bind(&item.shippingAddressDetail).bind(&item.shippingMobileNumber).bind(item.paidAmount).bind(&item.paymentStatus).bind(item.discountRate).bind(product_id).bind(supply_value).bind(vat_amount).bind(actual_tax_type).bind(tax_exempt_value).bind(sid).execute(&mut *tx).await?;
+                if let Some(code) = &item.referralCode {
+                    crate::commands::referral::attach_referral(&mut tx, sid, code).await?;
+                }
                 continue;
             }
         }
@@ -486,6 +497,18 @@ pub async fn save_general_sales_batch_internal(
 
         sqlx::query("INSERT INTO sales (sales_id, customer_id, product_name, specification, quantity, unit_price, total_amount, status, memo, order_date, shipping_name, shipping_zip_code, shipping_address_primary, shipping_address_detail, shipping_mobile_number, paid_amount, payment_status, discount_rate, product_id, supply_value, vat_amount, tax_type, tax_exempt_value) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23)")
         .bind(&new_sid).bind(&item.customerId).bind(&item.productName).bind(&item.specification).bind(item.quantity).bind(item.unitPrice).bind(item.totalAmount).bind(&item.status).bind(&item.memo).bind(order_date_parsed).bind(&item.shippingName).bind(&item.shippingZipCode).bind(&item.shippingAddressPrimary).bind(&item.shippingAddressDetail).bind(&item.shippingMobileNumber).bind(item.paidAmount).bind(&item.paymentStatus).bind(item.discountRate).bind(product_id).bind(supply_value).bind(vat_amount).bind(actual_tax_type).bind(tax_exempt_value).execute(&mut *tx).await?;
+        if let Some(code) = &item.referralCode {
+            crate::commands::referral::attach_referral(&mut tx, &new_sid, code).await?;
+        }
+        if let Some(points) = item.pointsUsed.filter(|p| *p > 0) {
+            let settings = crate::commands::points::load_points_config(pool)
+                .await?
+                .settings;
+            crate::commands::points::redeem_points_in(
+                &mut tx, &settings, username, &new_sid, points,
+            )
+            .await?;
+        }
     }
 
     tx.commit().await?;
@@ -498,5 +521,5 @@ pub async fn save_general_sales_batch(
     items: Vec<GeneralSalesBatchItem>,
     deleted_ids: Vec<String>,
 ) -> MyceliumResult<()> {
-    save_general_sales_batch_internal(&*state, items, deleted_ids).await
+    save_general_sales_batch_internal(&*state, "Admin", items, deleted_ids).await
 }
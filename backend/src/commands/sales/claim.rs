@@ -154,6 +154,7 @@ pub async fn process_sales_claim_internal(
             .execute(&mut *tx)
             .await?;
     }
+    crate::commands::points::reverse_sale_points(&mut tx, &claim.sales_id).await?;
 
     tx.commit().await?;
     Ok(())
@@ -0,0 +1,307 @@
+use super::external::MallOrderItem;
+use super::naver_commerce::json_string;
+use super::utils::parse_date_safe;
+use crate::error::{MyceliumError, MyceliumResult};
+use chrono::{NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+pub const COUPANG_WING_API_BASE: &str = "https://api-gateway.coupang.com";
+
+/// Client for the Coupang Wing open API. Every request is signed with
+/// HMAC-SHA256 over `signed-date + method + path + query`.
+pub struct CoupangWingClient {
+    base_url: String,
+    access_key: String,
+    secret_key: String,
+    vendor_id: String,
+    http: reqwest::Client,
+}
+
+impl CoupangWingClient {
+    pub fn new(access_key: &str, secret_key: &str, vendor_id: &str) -> Self {
+        Self {
+            base_url: COUPANG_WING_API_BASE.to_string(),
+            access_key: access_key.to_string(),
+            secret_key: secret_key.to_string(),
+            vendor_id: vendor_id.to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Points the client at another host, e.g. a local mock server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub(crate) async fn send_signed(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &str,
+        body: Option<&Value>,
+    ) -> MyceliumResult<Value> {
+        let signed_date = Utc::now().format("%y%m%dT%H%M%SZ").to_string();
+        let authorization = authorization_header(
+            &self.access_key,
+            &self.secret_key,
+            method.as_str(),
+            path,
+            query,
+            &signed_date,
+        )?;
+
+        let url = if query.is_empty() {
+            format!("{}{}", self.base_url, path)
+        } else {
+            format!("{}{}?{}", self.base_url, path, query)
+        };
+        let mut req = self
+            .http
+            .request(method, url)
+            .header("Authorization", authorization)
+            .header("X-EXTENDED-TIMEOUT", "90000");
+        if let Some(b) = body {
+            req = req.json(b);
+        }
+        let resp = req.send().await?;
+
+        let status = resp.status();
+        let body: Value = resp.json().await?;
+        if !status.is_success() {
+            return Err(MyceliumError::Internal(format!(
+                "쿠팡 API 오류: {}",
+                body.get("message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("알 수 없는 오류")
+            )));
+        }
+        Ok(body)
+    }
+
+    /// Fetches paid orders (결제완료, status ACCEPT) created in the date range.
+    pub async fn fetch_new_orders(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> MyceliumResult<Vec<MallOrderItem>> {
+        let path = format!(
+            "/v2/providers/openapi/apis/api/v4/vendors/{}/ordersheets",
+            self.vendor_id
+        );
+
+        let mut items = Vec::new();
+        let mut next_token: Option<String> = None;
+        // Paging is bounded in case the API keeps returning the same token
+        for _ in 0..100 {
+            let mut query = format!(
+                "createdAtFrom={}&createdAtTo={}&status=ACCEPT&maxPerPage=50",
+                from.format("%Y-%m-%d"),
+                to.format("%Y-%m-%d")
+            );
+            if let Some(token) = &next_token {
+                query.push_str(&format!("&nextToken={}", urlencoding::encode(token)));
+            }
+
+            let body = self
+                .send_signed(reqwest::Method::GET, &path, &query, None)
+                .await?;
+            let (page, token) = parse_ordersheets(&body);
+            items.extend(page);
+
+            match token {
+                Some(t) if Some(&t) != next_token.as_ref() => next_token = Some(t),
+                _ => break,
+            }
+        }
+        Ok(items)
+    }
+
+    /// Moves a shipment box from 결제완료 to 상품준비중; invoices are only accepted after this.
+    pub async fn acknowledge_shipment(&self, shipment_box_id: &str) -> MyceliumResult<()> {
+        let path = format!(
+            "/v2/providers/openapi/apis/api/v4/vendors/{}/ordersheets/acknowledgement",
+            self.vendor_id
+        );
+        let body = json!({
+            "vendorId": self.vendor_id,
+            "shipmentBoxIds": [numeric_id(shipment_box_id)?],
+        });
+        let resp = self
+            .send_signed(reqwest::Method::PUT, &path, "", Some(&body))
+            .await?;
+        check_response_list(&resp)
+    }
+
+    /// Sets the sellable quantity of one vendor item (옵션).
+    pub async fn update_vendor_item_quantity(
+        &self,
+        vendor_item_id: &str,
+        quantity: i32,
+    ) -> MyceliumResult<()> {
+        let path = format!(
+            "/v2/providers/seller_api/apis/api/v1/marketplace/vendor-items/{}/quantities/{}",
+            numeric_id(vendor_item_id)?,
+            quantity.max(0)
+        );
+        let resp = self
+            .send_signed(reqwest::Method::PUT, &path, "", None)
+            .await?;
+        if resp["code"].as_str() == Some("ERROR") {
+            return Err(MyceliumError::Internal(format!(
+                "쿠팡 재고 변경 실패: {}",
+                json_string(&resp["message"]).unwrap_or_default()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Uploads the courier and invoice number for one order line.
+    pub async fn upload_invoice(
+        &self,
+        shipment_box_id: &str,
+        order_id: &str,
+        vendor_item_id: &str,
+        courier_code: &str,
+        invoice_number: &str,
+    ) -> MyceliumResult<()> {
+        let path = format!(
+            "/v2/providers/openapi/apis/api/v4/vendors/{}/orders/invoices",
+            self.vendor_id
+        );
+        let body = json!({
+            "vendorId": self.vendor_id,
+            "orderSheetInvoiceApplyDtos": [{
+                "shipmentBoxId": numeric_id(shipment_box_id)?,
+                "orderId": numeric_id(order_id)?,
+                "vendorItemId": numeric_id(vendor_item_id)?,
+                "deliveryCompanyCode": courier_code,
+                "invoiceNumber": invoice_number,
+                "splitShipping": false,
+                "preSplitShipped": false,
+                "estimatedShippingDate": "",
+            }]
+        });
+        let resp = self
+            .send_signed(reqwest::Method::POST, &path, "", Some(&body))
+            .await?;
+        check_response_list(&resp)
+    }
+}
+
+fn numeric_id(id: &str) -> MyceliumResult<i64> {
+    id.trim()
+        .parse()
+        .map_err(|_| MyceliumError::Validation(format!("쿠팡 주문 번호 형식 오류: {}", id)))
+}
+
+/// Batch endpoints answer 200 with a per-item `succeed` flag.
+fn check_response_list(body: &Value) -> MyceliumResult<()> {
+    let failed = body["data"]["responseList"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|r| r["succeed"].as_bool() == Some(false));
+    match failed {
+        Some(r) => Err(MyceliumError::Internal(format!(
+            "쿠팡 처리 실패: {}",
+            json_string(&r["resultMessage"])
+                .filter(|m| !m.is_empty())
+                .or_else(|| json_string(&r["resultCode"]))
+                .unwrap_or_else(|| "알 수 없는 오류".to_string())
+        ))),
+        None => Ok(()),
+    }
+}
+
+pub fn sign_request(
+    secret_key: &str,
+    signed_date: &str,
+    method: &str,
+    path: &str,
+    query: &str,
+) -> MyceliumResult<String> {
+    let message = format!("{}{}{}{}", signed_date, method, path, query);
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+        .map_err(|e| MyceliumError::Internal(e.to_string()))?;
+    mac.update(message.as_bytes());
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+pub fn authorization_header(
+    access_key: &str,
+    secret_key: &str,
+    method: &str,
+    path: &str,
+    query: &str,
+    signed_date: &str,
+) -> MyceliumResult<String> {
+    let signature = sign_request(secret_key, signed_date, method, path, query)?;
+    Ok(format!(
+        "CEA algorithm=HmacSHA256, access-key={}, signed-date={}, signature={}",
+        access_key, signed_date, signature
+    ))
+}
+
+/// Returns one item per order line and the next page token, if any.
+pub fn parse_ordersheets(body: &Value) -> (Vec<MallOrderItem>, Option<String>) {
+    let mut items = Vec::new();
+    if let Some(sheets) = body["data"].as_array() {
+        for sheet in sheets {
+            let Some(order_id) = json_string(&sheet["orderId"]) else {
+                continue;
+            };
+            let receiver = &sheet["receiver"];
+            let address = [
+                json_string(&receiver["addr1"]),
+                json_string(&receiver["addr2"]),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+            let memo = json_string(&sheet["parcelPrintMessage"]).filter(|s| !s.is_empty());
+            let order_date = json_string(&sheet["paidAt"])
+                .or_else(|| json_string(&sheet["orderedAt"]))
+                .and_then(|d| parse_date_safe(d.get(..10)?));
+
+            for line in sheet["orderItems"].as_array().into_iter().flatten() {
+                let qty = line["shippingCount"].as_i64().unwrap_or(1).max(1) as i32;
+                let order_price = line["orderPrice"].as_i64().unwrap_or(0) as i32;
+                let unit_price = if order_price > 0 {
+                    order_price / qty
+                } else {
+                    line["salesPrice"].as_i64().unwrap_or(0) as i32
+                };
+
+                let vendor_item_id = json_string(&line["vendorItemId"]);
+                items.push(MallOrderItem {
+                    order_id: order_id.clone(),
+                    line_id: vendor_item_id.clone().unwrap_or_default(),
+                    mall_product_code: vendor_item_id,
+                    mall_shipment_id: json_string(&sheet["shipmentBoxId"]),
+                    order_date,
+                    customer_name: json_string(&sheet["orderer"]["name"]).unwrap_or_default(),
+                    receiver_name: json_string(&receiver["name"]).unwrap_or_default(),
+                    mobile: json_string(&receiver["safeNumber"])
+                        .or_else(|| json_string(&receiver["receiverNumber"]))
+                        .unwrap_or_default(),
+                    zip: json_string(&receiver["postCode"]).unwrap_or_default(),
+                    address: address.clone(),
+                    mall_product_name: json_string(&line["vendorItemName"])
+                        .or_else(|| json_string(&line["sellerProductName"]))
+                        .unwrap_or_default(),
+                    qty,
+                    unit_price,
+                    memo: memo.clone(),
+                });
+            }
+        }
+    }
+
+    let next_token = json_string(&body["nextToken"]).filter(|t| !t.is_empty());
+    (items, next_token)
+}
@@ -1,20 +1,30 @@
+use super::coupang_wing::CoupangWingClient;
+use super::naver_commerce::NaverCommerceClient;
+use crate::commands::config::{load_integration_settings, MallSettings};
 use crate::db::DbPool;
 use crate::error::{MyceliumError, MyceliumResult};
-use crate::stubs::{AppHandle, Manager};
+use crate::stubs::AppHandle;
 use axum::{
     extract::{Query, State},
     response::IntoResponse,
     Json,
 };
+use chrono::{Duration, Local, NaiveDate, TimeZone};
 use serde_json::json;
 use std::collections::HashMap;
-use std::fs;
 use std::path::PathBuf;
 
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MallOrderItem {
     pub order_id: String,
+    /// Line id within the order (Naver productOrderId, Coupang vendorItemId)
+    pub line_id: String,
+    /// Mall-side product code used by the product mapping rules
+    pub mall_product_code: Option<String>,
+    /// Shipment unit the mall expects the invoice on (Coupang shipmentBoxId)
+    pub mall_shipment_id: Option<String>,
+    pub order_date: Option<NaiveDate>,
     pub customer_name: String,
     pub receiver_name: String,
     pub mobile: String,
@@ -23,86 +33,78 @@ pub struct MallOrderItem {
     pub mall_product_name: String,
     pub qty: i32,
     pub unit_price: i32,
+    pub memo: Option<String>,
 }
 
 pub async fn fetch_external_mall_orders(
-    app: AppHandle,
+    _app: AppHandle,
     mall_type: String,
 ) -> MyceliumResult<Vec<MallOrderItem>> {
-    // 1. Get Config
-    let config_dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|e| MyceliumError::Internal(e.to_string()))?;
-    let config_path = config_dir.join("config.json");
+    fetch_mall_orders_internal(&mall_type, None).await
+}
 
-    if !config_path.exists() {
-        return Err(MyceliumError::Internal("설정 파일이 없습니다.".to_string()));
+fn required_keys<'a>(
+    mall: Option<&'a MallSettings>,
+    keys: impl Fn(&'a MallSettings) -> Vec<&'a str>,
+    message: &str,
+) -> MyceliumResult<Vec<&'a str>> {
+    match mall.map(keys) {
+        Some(values) if values.iter().all(|v| !v.trim().is_empty()) => Ok(values),
+        _ => Err(MyceliumError::Internal(message.to_string())),
     }
+}
 
-    let content =
-        fs::read_to_string(&config_path).map_err(|e| MyceliumError::Internal(e.to_string()))?;
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap_or(serde_json::json!({}));
-
-    // 2. Routing by Provider
-    match mall_type.as_str() {
-        "sabangnet" => {
-            let api_key = json
-                .get("sabangnet_api_key")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let company_id = json
-                .get("sabangnet_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            if api_key.is_empty() || company_id.is_empty() {
-                return Err(MyceliumError::Internal("사방넷 연동을 위해 '설정 > API 키' 메뉴에서 인증코드와 회사 ID를 먼저 입력해주세요.".into()));
-            }
-
-            fetch_sabangnet_orders(api_key, company_id).await
-        }
-        "playauto" => {
-            let api_key = json
-                .get("playauto_api_key")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let member_id = json
-                .get("playauto_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            if api_key.is_empty() || member_id.is_empty() {
-                return Err(MyceliumError::Internal("플레이오토 연동을 위해 '설정 > API 키' 메뉴에서 대행사 키와 계정 ID를 먼저 입력해주세요.".into()));
-            }
-
-            fetch_playauto_orders(api_key, member_id).await
-        }
+/// Collects new orders from a mall using the keys saved in '설정 > API 키'.
+/// `since` defaults to yesterday so a daily import also catches late-night orders.
+pub async fn fetch_mall_orders_internal(
+    mall_type: &str,
+    since: Option<NaiveDate>,
+) -> MyceliumResult<Vec<MallOrderItem>> {
+    let settings = load_integration_settings()?;
+    let mall = settings.mall.as_ref();
+    let today = Local::now().date_naive();
+    let since = since.unwrap_or(today - Duration::days(1));
+
+    match mall_type {
+        // Their order APIs are not implemented yet; an empty result would look like no orders
+        "sabangnet" | "playauto" => Err(MyceliumError::Validation(format!(
+            "{}: 지원되지 않는 연동입니다",
+            super::mall_import::mall_label(mall_type)
+        ))),
         "naver" => {
-            let client_id = json
-                .get("naver_commerce_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            if client_id.is_empty() {
-                return Err(MyceliumError::Internal(
-                    "네이버 커머스 API 연동 설정이 필요합니다.".into(),
-                ));
-            }
-            // Existing placeholder for Naver
-            Ok(vec![])
+            let keys = required_keys(
+                mall,
+                |m| {
+                    vec![
+                        m.naver_commerce_id.as_str(),
+                        m.naver_commerce_secret.as_str(),
+                    ]
+                },
+                "네이버 커머스 API 연동 설정이 필요합니다.",
+            )?;
+            let since_local = Local
+                .from_local_datetime(&since.and_hms_opt(0, 0, 0).unwrap())
+                .single()
+                .unwrap_or_else(Local::now);
+            NaverCommerceClient::new(keys[0], keys[1])
+                .fetch_new_orders(since_local.to_utc())
+                .await
         }
         "coupang" => {
-            let access_key = json
-                .get("coupang_access_key")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            if access_key.is_empty() {
-                return Err(MyceliumError::Internal(
-                    "쿠팡 윙 API 연동 설정이 필요합니다.".into(),
-                ));
-            }
-            // Existing placeholder for Coupang
-            Ok(vec![])
+            let keys = required_keys(
+                mall,
+                |m| {
+                    vec![
+                        m.coupang_access_key.as_str(),
+                        m.coupang_secret_key.as_str(),
+                        m.coupang_vendor_id.as_str(),
+                    ]
+                },
+                "쿠팡 윙 API 연동 설정이 필요합니다.",
+            )?;
+            CoupangWingClient::new(keys[0], keys[1], keys[2])
+                .fetch_new_orders(since, today)
+                .await
         }
         _ => Err(MyceliumError::Internal(format!(
             "지원되지 않는 몰 타입입니다: {}",
@@ -111,140 +113,16 @@ pub async fn fetch_external_mall_orders(
     }
 }
 
-/// Actual HTTP fetching logic for Sabangnet
-async fn fetch_sabangnet_orders(
-    api_key: &str,
-    company_id: &str,
-) -> MyceliumResult<Vec<MallOrderItem>> {
-    println!(
-        "System: Contacting Sabangnet API for company: {}...",
-        company_id
-    );
-
-    // Sabangnet usually uses a specific XML endpoint for order retrieval
-    let client = reqwest::Client::new();
-    let res = client
-        .get("https://api.sabangnet.co.kr/v1/orders") // Placeholder endpoint
-        .query(&[("api_key", api_key), ("comp_id", company_id)])
-        .send()
-        .await;
-
-    match res {
-        Ok(resp) => {
-            if resp.status().is_success() {
-                // In real Sabangnet, we would parse XML/JSON.
-                Ok(vec![])
-            } else {
-                Err(MyceliumError::Internal("사방넷 서버 응답 오류".into()))
-            }
-        }
-        Err(e) => Err(MyceliumError::Internal(format!("사방넷 연결 실패: {}", e))),
-    }
-}
-
-/// Actual HTTP fetching logic for PlayAuto
-async fn fetch_playauto_orders(
-    api_key: &str,
-    member_id: &str,
-) -> MyceliumResult<Vec<MallOrderItem>> {
-    println!(
-        "System: Contacting PlayAuto API for member: {}...",
-        member_id
-    );
-
-    // PlayAuto typically uses a JSON API
-    let client = reqwest::Client::new();
-    let res = client
-        .get("https://api.playauto.co.kr/v1/orders")
-        .header("X-PlayAuto-Api-Key", api_key)
-        .header("X-PlayAuto-Member-Id", member_id)
-        .send()
-        .await;
-
-    match res {
-        Ok(resp) => {
-            if resp.status().is_success() {
-                Ok(vec![])
-            } else {
-                Err(MyceliumError::Internal("플레이오토 서버 응답 오류".into()))
-            }
-        }
-        Err(e) => Err(MyceliumError::Internal(format!(
-            "플레이오토 연결 실패: {}",
-            e
-        ))),
-    }
-}
-
 pub async fn fetch_external_mall_orders_axum(
-    State((_, config_dir)): State<(DbPool, PathBuf)>,
+    State((_, _config_dir)): State<(DbPool, PathBuf)>,
     Query(params): Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
     let mall_type = params.get("mallType").cloned().unwrap_or_default();
+    let since = params
+        .get("since")
+        .and_then(|s| super::utils::parse_date_safe(s));
 
-    // Config Load Logic duplication (refactor if needed, but keeping simple for now)
-    let config_path = config_dir.join("config.json");
-    if !config_path.exists() {
-        return Json(json!({ "success": false, "error": "설정 파일이 없습니다." })).into_response();
-    }
-
-    let content = match fs::read_to_string(&config_path) {
-        Ok(c) => c,
-        Err(e) => return Json(json!({ "success": false, "error": e.to_string() })).into_response(),
-    };
-
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap_or(serde_json::json!({}));
-
-    let result = match mall_type.as_str() {
-        "sabangnet" => {
-            let api_key = json
-                .get("sabangnet_api_key")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let company_id = json
-                .get("sabangnet_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            if api_key.is_empty() || company_id.is_empty() {
-                Err(MyceliumError::Internal(
-                    "사방넷 연동 설정이 필요합니다.".into(),
-                ))
-            } else {
-                fetch_sabangnet_orders(api_key, company_id).await
-            }
-        }
-        "playauto" => {
-            let api_key = json
-                .get("playauto_api_key")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            let member_id = json
-                .get("playauto_id")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            if api_key.is_empty() || member_id.is_empty() {
-                Err(MyceliumError::Internal(
-                    "플레이오토 연동 설정이 필요합니다.".into(),
-                ))
-            } else {
-                fetch_playauto_orders(api_key, member_id).await
-            }
-        }
-        "naver" => {
-            // Placeholder
-            Ok(vec![])
-        }
-        "coupang" => {
-            // Placeholder
-            Ok(vec![])
-        }
-        _ => Err(MyceliumError::Internal(format!(
-            "지원되지 않는 몰 타입입니다: {}",
-            mall_type
-        ))),
-    };
-
-    match result {
+    match fetch_mall_orders_internal(&mall_type, since).await {
         Ok(items) => Json(json!(items)).into_response(),
         Err(e) => Json(json!({ "success": false, "error": e.to_string() })).into_response(),
     }
@@ -0,0 +1,512 @@
+use super::external::{fetch_mall_orders_internal, MallOrderItem};
+use super::utils::{calculate_bom_tax_distribution, calculate_tax_from_total, parse_date_safe};
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MallProductMapping {
+    pub mapping_id: i32,
+    pub mall_name: String,
+    pub mall_product_code: String,
+    pub mall_product_name: Option<String>,
+    pub product_id: i32,
+    pub product_name: Option<String>,
+    pub specification: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MallImportLog {
+    pub log_id: i32,
+    pub mall_name: String,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+    pub status: String,
+    pub fetched_count: i32,
+    pub new_count: i32,
+    pub updated_count: i32,
+    pub skipped_count: i32,
+    pub new_customer_count: i32,
+    pub unmapped_products: Vec<String>,
+    pub error_message: Option<String>,
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MallImportCounts {
+    pub fetched: i32,
+    pub new: i32,
+    pub updated: i32,
+    pub skipped: i32,
+    pub new_customers: i32,
+    /// Mall products without a mapping rule; their lines are skipped
+    pub unmapped_products: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct MappedProduct {
+    mall_product_code: String,
+    product_id: i32,
+    product_name: String,
+    specification: Option<String>,
+    tax_type: Option<String>,
+}
+
+pub fn mall_label(mall_name: &str) -> &str {
+    match mall_name {
+        "naver" => "네이버 스마트스토어",
+        "coupang" => "쿠팡",
+        "sabangnet" => "사방넷",
+        "playauto" => "플레이오토",
+        other => other,
+    }
+}
+
+/// Key used to look up a mapping rule. Malls without product codes fall back to the name.
+pub fn mapping_key(item: &MallOrderItem) -> String {
+    item.mall_product_code
+        .as_deref()
+        .filter(|c| !c.trim().is_empty())
+        .unwrap_or(&item.mall_product_name)
+        .trim()
+        .to_string()
+}
+
+pub fn mobile_digits(mobile: &str) -> String {
+    mobile.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Mall "안심번호" (050x) are per-order relay numbers and must not be used to identify a customer.
+pub fn is_personal_mobile(digits: &str) -> bool {
+    digits.starts_with("01") && (digits.len() == 10 || digits.len() == 11)
+}
+
+pub fn format_mobile(digits: &str) -> String {
+    match digits.len() {
+        11 => format!("{}-{}-{}", &digits[..3], &digits[3..7], &digits[7..]),
+        10 => format!("{}-{}-{}", &digits[..3], &digits[3..6], &digits[6..]),
+        _ => digits.to_string(),
+    }
+}
+
+async fn tax_breakdown(
+    pool: &DbPool,
+    product_id: i32,
+    tax_type: &str,
+    total_amount: i32,
+) -> MyceliumResult<(i32, i32, i32, String)> {
+    if let Some((s, v, e)) = calculate_bom_tax_distribution(pool, product_id, total_amount).await? {
+        let actual = if e > 0 && (s + v) > 0 {
+            "복합"
+        } else if e > 0 {
+            "면세"
+        } else {
+            "과세"
+        };
+        return Ok((s, v, e, actual.to_string()));
+    }
+    if tax_type == "과세" {
+        let (s, v) = calculate_tax_from_total(total_amount);
+        return Ok((s, v, 0, tax_type.to_string()));
+    }
+    Ok((total_amount, 0, 0, tax_type.to_string()))
+}
+
+/// Writes fetched mall lines into `sales`.
+///
+/// A line is identified by (mall, order id, line id), so running the import again
+/// only refreshes lines that are not shipped yet and whose contents changed.
+/// Customers are matched by mobile number and created when unknown.
+pub async fn import_mall_items(
+    pool: &DbPool,
+    username: &str,
+    mall_name: &str,
+    items: &[MallOrderItem],
+) -> MyceliumResult<MallImportCounts> {
+    let mut counts = MallImportCounts {
+        fetched: items.len() as i32,
+        ..Default::default()
+    };
+    if items.is_empty() {
+        return Ok(counts);
+    }
+
+    let mappings: HashMap<String, MappedProduct> = sqlx::query_as::<_, MappedProduct>(
+        "SELECT m.mall_product_code, p.product_id, p.product_name, p.specification, p.tax_type
+         FROM mall_product_mappings m JOIN products p ON p.product_id = m.product_id
+         WHERE m.mall_name = $1",
+    )
+    .bind(mall_name)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|m| (m.mall_product_code.clone(), m))
+    .collect();
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = pool.begin().await?;
+    crate::db::set_db_user_context(&mut *tx, username).await?;
+
+    let today = Local::now().date_naive();
+    let mut customer_cache: HashMap<String, String> = HashMap::new();
+
+    for item in items {
+        let key = mapping_key(item);
+        let Some(product) = mappings.get(&key) else {
+            counts.skipped += 1;
+            let label = if key == item.mall_product_name {
+                key
+            } else {
+                format!("{} ({})", key, item.mall_product_name)
+            };
+            if !counts.unmapped_products.contains(&label) {
+                counts.unmapped_products.push(label);
+            }
+            continue;
+        };
+
+        // Customer matching by mobile number, ignoring formatting
+        let digits = mobile_digits(&item.mobile);
+        let customer_id = if !is_personal_mobile(&digits) {
+            None
+        } else if let Some(cid) = customer_cache.get(&digits) {
+            Some(cid.clone())
+        } else {
+            let existing: Option<(String,)> = sqlx::query_as(
+                "SELECT customer_id FROM customers
+                 WHERE regexp_replace(mobile_number, '[^0-9]', '', 'g') = $1
+                 ORDER BY (status = '정상') DESC, join_date NULLS LAST LIMIT 1",
+            )
+            .bind(&digits)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let cid = match existing {
+                Some((cid,)) => cid,
+                None => {
+                    let new_id =
+                        format!("C-{}", uuid::Uuid::new_v4().to_string()[..8].to_uppercase());
+                    let name = if item.customer_name.trim().is_empty() {
+                        &item.receiver_name
+                    } else {
+                        &item.customer_name
+                    };
+                    sqlx::query(
+                        "INSERT INTO customers (customer_id, customer_name, mobile_number, membership_level, zip_code, address_primary, acquisition_channel, memo, join_date, status)
+                         VALUES ($1, $2, $3, '일반', $4, $5, $6, $7, CURRENT_DATE, '정상')",
+                    )
+                    .bind(&new_id)
+                    .bind(name)
+                    .bind(format_mobile(&digits))
+                    .bind(Some(item.zip.as_str()).filter(|z| !z.is_empty()))
+                    .bind(Some(item.address.as_str()).filter(|a| !a.is_empty()))
+                    .bind(mall_label(mall_name))
+                    .bind(format!("[쇼핑몰] {} 주문 자동 등록", mall_label(mall_name)))
+                    .execute(&mut *tx)
+                    .await?;
+                    counts.new_customers += 1;
+                    new_id
+                }
+            };
+            customer_cache.insert(digits, cid.clone());
+            Some(cid)
+        };
+
+        let shipping_zip_code = if item.address.is_empty() {
+            Some(item.zip.clone()).filter(|z| !z.is_empty())
+        } else {
+            crate::commands::address::resolve_zip_code(
+                pool,
+                Some(item.zip.clone()).filter(|z| !z.is_empty()),
+                &item.address,
+                None,
+            )
+            .await?
+            .0
+        };
+
+        let total_amount = item.unit_price * item.qty;
+        let (supply_value, vat_amount, tax_exempt_value, tax_type) = tax_breakdown(
+            pool,
+            product.product_id,
+            product.tax_type.as_deref().unwrap_or("면세"),
+            total_amount,
+        )
+        .await?;
+
+        // Lines already in shipping or later are left alone; unchanged lines count as skipped
+        let inserted: Option<(bool,)> = sqlx::query_as(
+            "INSERT INTO sales (
+                sales_id, customer_id, product_name, specification, quantity, unit_price, total_amount,
+                order_date, memo, status, product_id, supply_value, vat_amount, tax_type, tax_exempt_value,
+                shipping_name, shipping_zip_code, shipping_address_primary, shipping_mobile_number,
+                paid_amount, mall_name, mall_order_id, mall_line_id, mall_shipment_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, '입금완료', $10, $11, $12, $13, $14, $15, $16, $17, $18, $7, $19, $20, $21, $22)
+            ON CONFLICT (mall_name, mall_order_id, mall_line_id) DO UPDATE SET
+                quantity = EXCLUDED.quantity,
+                unit_price = EXCLUDED.unit_price,
+                total_amount = EXCLUDED.total_amount,
+                supply_value = EXCLUDED.supply_value,
+                vat_amount = EXCLUDED.vat_amount,
+                tax_exempt_value = EXCLUDED.tax_exempt_value,
+                paid_amount = EXCLUDED.paid_amount,
+                memo = EXCLUDED.memo,
+                shipping_name = EXCLUDED.shipping_name,
+                shipping_zip_code = EXCLUDED.shipping_zip_code,
+                shipping_address_primary = EXCLUDED.shipping_address_primary,
+                shipping_mobile_number = EXCLUDED.shipping_mobile_number,
+                mall_shipment_id = EXCLUDED.mall_shipment_id,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE sales.status IN ('접수', '입금완료')
+              AND (sales.quantity, sales.unit_price, sales.memo, sales.shipping_name,
+                   sales.shipping_zip_code, sales.shipping_address_primary, sales.shipping_mobile_number)
+                  IS DISTINCT FROM
+                  (EXCLUDED.quantity, EXCLUDED.unit_price, EXCLUDED.memo, EXCLUDED.shipping_name,
+                   EXCLUDED.shipping_zip_code, EXCLUDED.shipping_address_primary, EXCLUDED.shipping_mobile_number)
+            RETURNING (xmax = 0)",
+        )
+        .bind(format!("S-{}", uuid::Uuid::new_v4().to_string()[..8].to_uppercase()))
+        .bind(&customer_id)
+        .bind(&product.product_name)
+        .bind(&product.specification)
+        .bind(item.qty)
+        .bind(item.unit_price)
+        .bind(total_amount)
+        .bind(item.order_date.unwrap_or(today))
+        .bind(&item.memo)
+        .bind(product.product_id)
+        .bind(supply_value)
+        .bind(vat_amount)
+        .bind(tax_type)
+        .bind(tax_exempt_value)
+        .bind(if item.receiver_name.is_empty() {
+            &item.customer_name
+        } else {
+            &item.receiver_name
+        })
+        .bind(shipping_zip_code)
+        .bind(&item.address)
+        .bind(&item.mobile)
+        .bind(mall_name)
+        .bind(&item.order_id)
+        .bind(&item.line_id)
+        .bind(&item.mall_shipment_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match inserted {
+            Some((true,)) => counts.new += 1,
+            Some((false,)) => counts.updated += 1,
+            None => counts.skipped += 1,
+        }
+    }
+
+    tx.commit().await?;
+    Ok(counts)
+}
+
+/// Fetches a mall and imports the result, recording the run in `mall_import_logs`.
+pub async fn import_mall_orders_internal(
+    pool: &DbPool,
+    username: &str,
+    mall_name: &str,
+    since: Option<NaiveDate>,
+) -> MyceliumResult<MallImportLog> {
+    let (log_id,): (i32,) = sqlx::query_as(
+        "INSERT INTO mall_import_logs (mall_name, created_by) VALUES ($1, $2) RETURNING log_id",
+    )
+    .bind(mall_name)
+    .bind(username)
+    .fetch_one(pool)
+    .await?;
+
+    let result = match fetch_mall_orders_internal(mall_name, since).await {
+        Ok(items) => import_mall_items(pool, username, mall_name, &items).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(counts) => {
+            sqlx::query(
+                "UPDATE mall_import_logs SET status = '완료', finished_at = CURRENT_TIMESTAMP,
+                    fetched_count = $1, new_count = $2, updated_count = $3, skipped_count = $4,
+                    new_customer_count = $5, unmapped_products = $6
+                 WHERE log_id = $7",
+            )
+            .bind(counts.fetched)
+            .bind(counts.new)
+            .bind(counts.updated)
+            .bind(counts.skipped)
+            .bind(counts.new_customers)
+            .bind(&counts.unmapped_products)
+            .bind(log_id)
+            .execute(pool)
+            .await?;
+        }
+        Err(e) => {
+            sqlx::query(
+                "UPDATE mall_import_logs SET status = '실패', finished_at = CURRENT_TIMESTAMP, error_message = $1
+                 WHERE log_id = $2",
+            )
+            .bind(e.to_string())
+            .bind(log_id)
+            .execute(pool)
+            .await?;
+            return Err(e);
+        }
+    }
+
+    Ok(
+        sqlx::query_as::<_, MallImportLog>("SELECT * FROM mall_import_logs WHERE log_id = $1")
+            .bind(log_id)
+            .fetch_one(pool)
+            .await?,
+    )
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MallImportPayload {
+    #[serde(alias = "mall_type")]
+    pub mall_type: String,
+    pub since: Option<String>,
+}
+
+pub async fn import_mall_orders_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<MallImportPayload>,
+) -> MyceliumResult<Json<MallImportLog>> {
+    let since = payload.since.as_deref().and_then(parse_date_safe);
+    Ok(Json(
+        import_mall_orders_internal(
+            &state.pool,
+            claims.username.as_deref().unwrap_or("Admin"),
+            &payload.mall_type,
+            since,
+        )
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MallImportLogQuery {
+    #[serde(alias = "mall_name")]
+    pub mall_name: Option<String>,
+    pub limit: Option<i64>,
+}
+
+pub async fn get_mall_import_logs_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<MallImportLogQuery>,
+) -> MyceliumResult<Json<Vec<MallImportLog>>> {
+    Ok(Json(
+        sqlx::query_as::<_, MallImportLog>(
+            "SELECT * FROM mall_import_logs
+             WHERE ($1::text IS NULL OR mall_name = $1)
+             ORDER BY started_at DESC, log_id DESC LIMIT $2",
+        )
+        .bind(query.mall_name)
+        .bind(query.limit.unwrap_or(50).clamp(1, 500))
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MallMappingQuery {
+    #[serde(alias = "mall_name")]
+    pub mall_name: Option<String>,
+}
+
+pub async fn get_mall_product_mappings_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<MallMappingQuery>,
+) -> MyceliumResult<Json<Vec<MallProductMapping>>> {
+    Ok(Json(
+        sqlx::query_as::<_, MallProductMapping>(
+            "SELECT m.mapping_id, m.mall_name, m.mall_product_code, m.mall_product_name, m.product_id,
+                    p.product_name, p.specification
+             FROM mall_product_mappings m LEFT JOIN products p ON p.product_id = m.product_id
+             WHERE ($1::text IS NULL OR m.mall_name = $1)
+             ORDER BY m.mall_name, m.mall_product_code",
+        )
+        .bind(query.mall_name)
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MallMappingInput {
+    #[serde(alias = "mall_name")]
+    pub mall_name: String,
+    #[serde(alias = "mall_product_code")]
+    pub mall_product_code: String,
+    #[serde(alias = "mall_product_name")]
+    pub mall_product_name: Option<String>,
+    #[serde(alias = "product_id")]
+    pub product_id: i32,
+}
+
+pub async fn save_mall_product_mapping_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<MallMappingInput>,
+) -> MyceliumResult<Json<i32>> {
+    if input.mall_name.trim().is_empty() || input.mall_product_code.trim().is_empty() {
+        return Err(MyceliumError::Validation(
+            "쇼핑몰과 상품코드를 입력해주세요.".to_string(),
+        ));
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let (mapping_id,): (i32,) = sqlx::query_as(
+        "INSERT INTO mall_product_mappings (mall_name, mall_product_code, mall_product_name, product_id)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (mall_name, mall_product_code) DO UPDATE SET
+            mall_product_name = COALESCE(EXCLUDED.mall_product_name, mall_product_mappings.mall_product_name),
+            product_id = EXCLUDED.product_id,
+            updated_at = CURRENT_TIMESTAMP
+         RETURNING mapping_id",
+    )
+    .bind(input.mall_name.trim())
+    .bind(input.mall_product_code.trim())
+    .bind(input.mall_product_name)
+    .bind(input.product_id)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(mapping_id))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MallMappingDeletePayload {
+    #[serde(alias = "mapping_id")]
+    pub mapping_id: i32,
+}
+
+pub async fn delete_mall_product_mapping_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(payload): Json<MallMappingDeletePayload>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query("DELETE FROM mall_product_mappings WHERE mapping_id = $1")
+        .bind(payload.mapping_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(Json(()))
+}
@@ -0,0 +1,261 @@
+use super::coupang_wing::CoupangWingClient;
+use super::naver_commerce::NaverCommerceClient;
+use crate::commands::config::load_integration_settings;
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{extract::State as AxumState, Json};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+/// A product is published only after its inventory log has been quiet this long,
+/// so a burst of sales or a harvest batch results in a single update per listing.
+pub const STOCK_DEBOUNCE_SECONDS: i32 = 60;
+
+/// Failed listings are retried after this many minutes instead of on every run
+const STOCK_RETRY_MINUTES: i32 = 5;
+
+/// Malls whose listings can receive stock updates
+const STOCK_MALLS: [&str; 2] = ["naver", "coupang"];
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MallStockSetting {
+    pub mall_name: String,
+    pub enabled: bool,
+    pub safety_buffer: i32,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MallStockStatus {
+    pub mapping_id: i32,
+    pub mall_name: String,
+    pub mall_product_code: String,
+    pub mall_product_name: Option<String>,
+    pub product_id: i32,
+    pub product_name: String,
+    pub specification: Option<String>,
+    pub stock_quantity: i32,
+    pub publish_quantity: i32,
+    pub last_published_qty: Option<i32>,
+    pub stock_published_at: Option<NaiveDateTime>,
+    pub stock_sync_error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MallStockPublishResult {
+    pub published: usize,
+    pub failed: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct PendingListing {
+    mapping_id: i32,
+    mall_name: String,
+    mall_product_code: String,
+    stock_quantity: i32,
+    safety_buffer: i32,
+    last_published_qty: Option<i32>,
+}
+
+/// Quantity shown on a mall: stock minus the channel's safety buffer, never negative.
+pub fn publishable_quantity(stock_quantity: i32, safety_buffer: i32) -> i32 {
+    (stock_quantity - safety_buffer.max(0)).max(0)
+}
+
+/// Pushes the current quantity of every mapped product whose published value is stale.
+/// With `force`, the debounce window and retry delay are ignored (manual "sync now").
+pub async fn publish_mall_stock(
+    pool: &DbPool,
+    force: bool,
+) -> MyceliumResult<MallStockPublishResult> {
+    let pending: Vec<PendingListing> = sqlx::query_as(
+        "SELECT m.mapping_id, m.mall_name, m.mall_product_code,
+                COALESCE(p.stock_quantity, 0) AS stock_quantity, c.safety_buffer, m.last_published_qty
+         FROM mall_product_mappings m
+         JOIN products p ON p.product_id = m.product_id
+         JOIN mall_stock_settings c ON c.mall_name = m.mall_name AND c.enabled
+         WHERE ($1 OR m.stock_sync_error IS NULL
+                OR m.stock_attempted_at < CURRENT_TIMESTAMP - make_interval(mins => $2))
+           AND ($1 OR NOT EXISTS (
+                SELECT 1 FROM inventory_logs l
+                WHERE l.product_id = p.product_id
+                  AND l.created_at > CURRENT_TIMESTAMP - make_interval(secs => $3)))
+         ORDER BY m.mall_name, m.mapping_id",
+    )
+    .bind(force)
+    .bind(STOCK_RETRY_MINUTES)
+    .bind(STOCK_DEBOUNCE_SECONDS)
+    .fetch_all(pool)
+    .await?;
+
+    let pending: Vec<(PendingListing, i32)> = pending
+        .into_iter()
+        .map(|l| {
+            let qty = publishable_quantity(l.stock_quantity, l.safety_buffer);
+            (l, qty)
+        })
+        .filter(|(l, qty)| l.last_published_qty != Some(*qty))
+        .collect();
+
+    let mut result = MallStockPublishResult::default();
+    if pending.is_empty() {
+        return Ok(result);
+    }
+
+    let settings = load_integration_settings().ok();
+    let mall = settings.as_ref().and_then(|s| s.mall.clone());
+    let key_error = |label: &str| {
+        MyceliumError::Validation(format!(
+            "{} API 키가 설정되지 않아 재고를 전송할 수 없습니다.",
+            label
+        ))
+    };
+    // One client per mall for the whole run so the Naver token is issued once
+    let naver = mall
+        .as_ref()
+        .filter(|m| !m.naver_commerce_id.is_empty() && !m.naver_commerce_secret.is_empty())
+        .map(|m| NaverCommerceClient::new(&m.naver_commerce_id, &m.naver_commerce_secret));
+    let coupang = mall
+        .as_ref()
+        .filter(|m| {
+            !m.coupang_access_key.is_empty()
+                && !m.coupang_secret_key.is_empty()
+                && !m.coupang_vendor_id.is_empty()
+        })
+        .map(|m| {
+            CoupangWingClient::new(
+                &m.coupang_access_key,
+                &m.coupang_secret_key,
+                &m.coupang_vendor_id,
+            )
+        });
+
+    for (listing, quantity) in &pending {
+        let pushed = match listing.mall_name.as_str() {
+            "naver" => match &naver {
+                Some(client) => {
+                    client
+                        .update_stock(&listing.mall_product_code, *quantity)
+                        .await
+                }
+                None => Err(key_error("네이버 커머스")),
+            },
+            "coupang" => match &coupang {
+                Some(client) => {
+                    client
+                        .update_vendor_item_quantity(&listing.mall_product_code, *quantity)
+                        .await
+                }
+                None => Err(key_error("쿠팡 윙")),
+            },
+            other => Err(MyceliumError::Validation(format!(
+                "{}은(는) 재고 연동을 지원하지 않습니다.",
+                super::mall_import::mall_label(other)
+            ))),
+        };
+
+        match pushed {
+            Ok(()) => {
+                sqlx::query(
+                    "UPDATE mall_product_mappings SET last_published_qty = $1, stock_published_at = CURRENT_TIMESTAMP,
+                        stock_attempted_at = CURRENT_TIMESTAMP, stock_sync_error = NULL
+                     WHERE mapping_id = $2",
+                )
+                .bind(*quantity)
+                .bind(listing.mapping_id)
+                .execute(pool)
+                .await?;
+                result.published += 1;
+            }
+            Err(e) => {
+                sqlx::query(
+                    "UPDATE mall_product_mappings SET stock_attempted_at = CURRENT_TIMESTAMP, stock_sync_error = $1
+                     WHERE mapping_id = $2",
+                )
+                .bind(e.to_string())
+                .bind(listing.mapping_id)
+                .execute(pool)
+                .await?;
+                result.failed += 1;
+            }
+        }
+    }
+    Ok(result)
+}
+
+pub async fn get_mall_stock_settings_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<MallStockSetting>>> {
+    Ok(Json(
+        sqlx::query_as::<_, MallStockSetting>(
+            "SELECT mall_name, enabled, safety_buffer FROM mall_stock_settings ORDER BY mall_name",
+        )
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+pub async fn save_mall_stock_settings_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(payload): Json<Vec<MallStockSetting>>,
+) -> MyceliumResult<Json<()>> {
+    for s in &payload {
+        if !STOCK_MALLS.contains(&s.mall_name.as_str()) {
+            return Err(MyceliumError::Validation(format!(
+                "{}은(는) 재고 연동을 지원하지 않습니다.",
+                super::mall_import::mall_label(&s.mall_name)
+            )));
+        }
+        if s.safety_buffer < 0 {
+            return Err(MyceliumError::Validation(
+                "안전 재고는 0 이상이어야 합니다.".to_string(),
+            ));
+        }
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    for s in &payload {
+        sqlx::query(
+            "INSERT INTO mall_stock_settings (mall_name, enabled, safety_buffer, updated_at)
+             VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
+             ON CONFLICT (mall_name) DO UPDATE SET enabled = EXCLUDED.enabled,
+                safety_buffer = EXCLUDED.safety_buffer, updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(&s.mall_name)
+        .bind(s.enabled)
+        .bind(s.safety_buffer)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(Json(()))
+}
+
+pub async fn get_mall_stock_status_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<MallStockStatus>>> {
+    Ok(Json(
+        sqlx::query_as::<_, MallStockStatus>(
+            "SELECT m.mapping_id, m.mall_name, m.mall_product_code, m.mall_product_name,
+                    p.product_id, p.product_name, p.specification,
+                    COALESCE(p.stock_quantity, 0) AS stock_quantity,
+                    GREATEST(COALESCE(p.stock_quantity, 0) - GREATEST(COALESCE(c.safety_buffer, 0), 0), 0) AS publish_quantity,
+                    m.last_published_qty, m.stock_published_at, m.stock_sync_error
+             FROM mall_product_mappings m
+             JOIN products p ON p.product_id = m.product_id
+             LEFT JOIN mall_stock_settings c ON c.mall_name = m.mall_name
+             ORDER BY m.mall_name, p.product_name",
+        )
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+pub async fn publish_mall_stock_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<MallStockPublishResult>> {
+    Ok(Json(publish_mall_stock(&state.pool, true).await?))
+}
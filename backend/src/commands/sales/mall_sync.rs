@@ -0,0 +1,339 @@
+use super::coupang_wing::CoupangWingClient;
+use super::naver_commerce::NaverCommerceClient;
+use crate::commands::config::{load_integration_settings, MallSettings};
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Json,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::Postgres;
+use std::sync::atomic::Ordering;
+
+/// After this many failed pushes the row stops retrying and waits for a manual retry
+pub const MAX_SYNC_ATTEMPTS: i32 = 8;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MallShipmentSync {
+    pub sync_id: i32,
+    pub sales_id: String,
+    pub mall_name: String,
+    pub mall_order_id: Option<String>,
+    pub shipping_name: Option<String>,
+    pub product_name: Option<String>,
+    pub courier_name: Option<String>,
+    pub tracking_number: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub last_error: Option<String>,
+    pub synced_at: Option<NaiveDateTime>,
+}
+
+#[derive(sqlx::FromRow)]
+struct DueSync {
+    sync_id: i32,
+    mall_name: String,
+    courier_name: Option<String>,
+    tracking_number: String,
+    shipping_date: Option<NaiveDate>,
+    attempts: i32,
+    mall_order_id: Option<String>,
+    mall_line_id: Option<String>,
+    mall_shipment_id: Option<String>,
+}
+
+/// Delivery company codes shared by the Naver Commerce and Coupang Wing APIs.
+pub fn mall_courier_code(courier_name: &str) -> Option<&'static str> {
+    let name = courier_name.replace(' ', "");
+    if name.contains("CJ") || name.contains("대한통운") {
+        Some("CJGLS")
+    } else if name.contains("우체국") {
+        Some("EPOST")
+    } else if name.contains("한진") {
+        Some("HANJIN")
+    } else if name.contains("롯데") {
+        Some("HYUNDAI")
+    } else if name.contains("로젠") {
+        Some("KGB")
+    } else if name.contains("경동") {
+        Some("KDEXP")
+    } else {
+        None
+    }
+}
+
+/// Backoff before the next attempt: 5, 10, 20 ... minutes, capped at 6 hours.
+pub fn next_retry_delay_minutes(attempts: i32) -> i64 {
+    (5i64 << attempts.clamp(0, 10)).min(360)
+}
+
+/// Queues the tracking number of a mall-imported sale. Sales entered by hand are ignored,
+/// and a line already pushed with the same courier and tracking number is left alone.
+pub async fn enqueue_tracking_sync<'a, E>(executor: E, sales_id: &str) -> MyceliumResult<()>
+where
+    E: sqlx::Executor<'a, Database = Postgres>,
+{
+    sqlx::query(
+        "INSERT INTO mall_shipment_sync (sales_id, mall_name, courier_name, tracking_number, shipping_date)
+         SELECT sales_id, mall_name, courier_name, tracking_number, shipping_date FROM sales
+         WHERE sales_id = $1 AND mall_name IS NOT NULL AND NULLIF(TRIM(tracking_number), '') IS NOT NULL
+         ON CONFLICT (sales_id) DO UPDATE SET
+            courier_name = EXCLUDED.courier_name,
+            tracking_number = EXCLUDED.tracking_number,
+            shipping_date = EXCLUDED.shipping_date,
+            status = '대기',
+            attempts = 0,
+            next_attempt_at = CURRENT_TIMESTAMP,
+            last_error = NULL,
+            updated_at = CURRENT_TIMESTAMP
+         WHERE mall_shipment_sync.status <> '완료'
+            OR mall_shipment_sync.tracking_number IS DISTINCT FROM EXCLUDED.tracking_number
+            OR mall_shipment_sync.courier_name IS DISTINCT FROM EXCLUDED.courier_name",
+    )
+    .bind(sales_id)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+fn mall_keys<'a>(
+    mall: Option<&'a MallSettings>,
+    keys: impl Fn(&'a MallSettings) -> Vec<&'a str>,
+    label: &str,
+) -> MyceliumResult<Vec<&'a str>> {
+    match mall.map(keys) {
+        Some(values) if values.iter().all(|v| !v.trim().is_empty()) => Ok(values),
+        _ => Err(MyceliumError::Validation(format!(
+            "{} API 키가 설정되지 않아 송장을 전송할 수 없습니다.",
+            label
+        ))),
+    }
+}
+
+/// API clients for one queue run, created on first use. Reusing the Naver client keeps its
+/// token, so a batch issues one instead of one per row.
+struct MallClients<'a> {
+    mall: Option<&'a MallSettings>,
+    naver: Option<NaverCommerceClient>,
+    coupang: Option<CoupangWingClient>,
+}
+
+impl<'a> MallClients<'a> {
+    fn new(mall: Option<&'a MallSettings>) -> Self {
+        Self {
+            mall,
+            naver: None,
+            coupang: None,
+        }
+    }
+
+    fn naver(&mut self) -> MyceliumResult<&NaverCommerceClient> {
+        let client = match self.naver.take() {
+            Some(client) => client,
+            None => {
+                let keys = mall_keys(
+                    self.mall,
+                    |m| {
+                        vec![
+                            m.naver_commerce_id.as_str(),
+                            m.naver_commerce_secret.as_str(),
+                        ]
+                    },
+                    "네이버 커머스",
+                )?;
+                NaverCommerceClient::new(keys[0], keys[1])
+            }
+        };
+        Ok(self.naver.insert(client))
+    }
+
+    fn coupang(&mut self) -> MyceliumResult<&CoupangWingClient> {
+        let client = match self.coupang.take() {
+            Some(client) => client,
+            None => {
+                let keys = mall_keys(
+                    self.mall,
+                    |m| {
+                        vec![
+                            m.coupang_access_key.as_str(),
+                            m.coupang_secret_key.as_str(),
+                            m.coupang_vendor_id.as_str(),
+                        ]
+                    },
+                    "쿠팡 윙",
+                )?;
+                CoupangWingClient::new(keys[0], keys[1], keys[2])
+            }
+        };
+        Ok(self.coupang.insert(client))
+    }
+}
+
+async fn push_tracking(clients: &mut MallClients<'_>, row: &DueSync) -> MyceliumResult<()> {
+    let courier_code = row
+        .courier_name
+        .as_deref()
+        .and_then(mall_courier_code)
+        .ok_or_else(|| {
+            MyceliumError::Validation(format!(
+                "쇼핑몰에 등록할 수 없는 택배사입니다: {}",
+                row.courier_name.as_deref().unwrap_or("-")
+            ))
+        })?;
+    let missing = || MyceliumError::Validation("쇼핑몰 주문 번호가 없는 매출입니다.".to_string());
+
+    match row.mall_name.as_str() {
+        "naver" => {
+            let client = clients.naver()?;
+            let product_order_id = row.mall_line_id.as_deref().ok_or_else(missing)?;
+            client
+                .dispatch_product_order(
+                    product_order_id,
+                    courier_code,
+                    &row.tracking_number,
+                    row.shipping_date
+                        .unwrap_or_else(|| chrono::Local::now().date_naive()),
+                )
+                .await
+        }
+        "coupang" => {
+            let client = clients.coupang()?;
+            let box_id = row.mall_shipment_id.as_deref().ok_or_else(missing)?;
+            let order_id = row.mall_order_id.as_deref().ok_or_else(missing)?;
+            let item_id = row.mall_line_id.as_deref().ok_or_else(missing)?;
+            // Already acknowledged boxes answer with an error here; the invoice call decides
+            let _ = client.acknowledge_shipment(box_id).await;
+            client
+                .upload_invoice(
+                    box_id,
+                    order_id,
+                    item_id,
+                    courier_code,
+                    &row.tracking_number,
+                )
+                .await
+        }
+        other => Err(MyceliumError::Validation(format!(
+            "{}은(는) 송장 자동 전송을 지원하지 않습니다.",
+            super::mall_import::mall_label(other)
+        ))),
+    }
+}
+
+/// Pushes due queue rows to the malls. Network and API errors are retried with backoff;
+/// configuration problems (missing keys, unknown courier) fail right away.
+/// Returns the number of rows synced.
+pub async fn process_mall_shipment_queue(pool: &DbPool) -> MyceliumResult<usize> {
+    // Claim rows by pushing their schedule forward so overlapping runs skip them
+    let due: Vec<DueSync> = sqlx::query_as(
+        "UPDATE mall_shipment_sync q SET next_attempt_at = CURRENT_TIMESTAMP + INTERVAL '10 minutes'
+         FROM sales s
+         WHERE q.sales_id = s.sales_id AND q.sync_id IN (
+            SELECT sync_id FROM mall_shipment_sync
+            WHERE status = '대기' AND next_attempt_at <= CURRENT_TIMESTAMP
+            ORDER BY next_attempt_at LIMIT 50
+            FOR UPDATE SKIP LOCKED
+         )
+         RETURNING q.sync_id, q.mall_name, q.courier_name, q.tracking_number, q.shipping_date, q.attempts,
+                   s.mall_order_id, s.mall_line_id, s.mall_shipment_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    if due.is_empty() {
+        return Ok(0);
+    }
+
+    let settings = load_integration_settings().ok();
+    let mut clients = MallClients::new(settings.as_ref().and_then(|s| s.mall.as_ref()));
+
+    let mut synced = 0;
+    for row in &due {
+        match push_tracking(&mut clients, row).await {
+            Ok(()) => {
+                sqlx::query(
+                    "UPDATE mall_shipment_sync SET status = '완료', attempts = attempts + 1, last_error = NULL,
+                        synced_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP
+                     WHERE sync_id = $1",
+                )
+                .bind(row.sync_id)
+                .execute(pool)
+                .await?;
+                synced += 1;
+            }
+            Err(e) => {
+                let attempts = row.attempts + 1;
+                let give_up =
+                    matches!(e, MyceliumError::Validation(_)) || attempts >= MAX_SYNC_ATTEMPTS;
+                sqlx::query(
+                    "UPDATE mall_shipment_sync SET status = $1, attempts = $2, last_error = $3,
+                        next_attempt_at = CURRENT_TIMESTAMP + make_interval(mins => $4),
+                        updated_at = CURRENT_TIMESTAMP
+                     WHERE sync_id = $5",
+                )
+                .bind(if give_up { "실패" } else { "대기" })
+                .bind(attempts)
+                .bind(e.to_string())
+                .bind(next_retry_delay_minutes(row.attempts) as i32)
+                .bind(row.sync_id)
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    Ok(synced)
+}
+
+#[derive(Deserialize)]
+pub struct MallSyncQuery {
+    pub status: Option<String>,
+    pub sales_id: Option<String>,
+}
+
+pub async fn get_mall_shipment_sync_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<MallSyncQuery>,
+) -> MyceliumResult<Json<Vec<MallShipmentSync>>> {
+    Ok(Json(
+        sqlx::query_as::<_, MallShipmentSync>(
+            "SELECT q.sync_id, q.sales_id, q.mall_name, s.mall_order_id, s.shipping_name, s.product_name,
+                    q.courier_name, q.tracking_number, q.status, q.attempts, q.next_attempt_at,
+                    q.last_error, q.synced_at
+             FROM mall_shipment_sync q JOIN sales s ON s.sales_id = q.sales_id
+             WHERE ($1::text IS NULL OR q.status = $1)
+               AND ($2::text IS NULL OR q.sales_id = $2)
+             ORDER BY (q.status = '완료'), q.created_at DESC
+             LIMIT 500",
+        )
+        .bind(query.status.filter(|s| !s.is_empty()))
+        .bind(query.sales_id.filter(|s| !s.is_empty()))
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct MallSyncRetryPayload {
+    /// Rows to retry; all failed rows when omitted
+    pub sync_ids: Option<Vec<i32>>,
+}
+
+pub async fn retry_mall_shipment_sync_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(payload): Json<MallSyncRetryPayload>,
+) -> MyceliumResult<Json<usize>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query(
+        "UPDATE mall_shipment_sync SET status = '대기', attempts = 0, next_attempt_at = CURRENT_TIMESTAMP,
+            updated_at = CURRENT_TIMESTAMP
+         WHERE status <> '완료' AND ($1::int[] IS NULL OR sync_id = ANY($1))",
+    )
+    .bind(payload.sync_ids)
+    .execute(&state.pool)
+    .await?;
+    Ok(Json(process_mall_shipment_queue(&state.pool).await?))
+}
@@ -1,6 +1,11 @@
 pub mod batch;
 pub mod claim;
+pub mod coupang_wing;
 pub mod external;
+pub mod mall_import;
+pub mod mall_stock;
+pub mod mall_sync;
+pub mod naver_commerce;
 pub mod order;
 pub mod query;
 pub mod utils;
@@ -0,0 +1,446 @@
+use super::external::MallOrderItem;
+use super::utils::parse_date_safe;
+use crate::error::{MyceliumError, MyceliumResult};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use serde_json::{json, Value};
+
+pub const NAVER_COMMERCE_API_BASE: &str = "https://api.commerce.naver.com";
+
+/// The product-order query endpoint accepts at most 300 ids per call
+const PRODUCT_ORDER_QUERY_LIMIT: usize = 300;
+
+/// Client for the Naver Commerce API (스마트스토어).
+///
+/// Authentication is OAuth2 client-credentials where the client secret is itself a
+/// bcrypt salt: the request carries `base64(bcrypt(client_id + "_" + timestamp, secret))`.
+pub struct NaverCommerceClient {
+    base_url: String,
+    client_id: String,
+    client_secret: String,
+    http: reqwest::Client,
+    /// Tokens live for hours; one per client instance keeps batch jobs under the issue rate limit
+    token: std::sync::Mutex<Option<String>>,
+}
+
+impl NaverCommerceClient {
+    pub fn new(client_id: &str, client_secret: &str) -> Self {
+        Self {
+            base_url: NAVER_COMMERCE_API_BASE.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            http: reqwest::Client::new(),
+            token: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Points the client at another host, e.g. a local mock server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub async fn issue_token(&self) -> MyceliumResult<String> {
+        let timestamp = Utc::now().timestamp_millis();
+        let sign = sign_client_secret(&self.client_id, &self.client_secret, timestamp)?;
+        let timestamp = timestamp.to_string();
+
+        let resp = self
+            .http
+            .post(format!("{}/external/v1/oauth2/token", self.base_url))
+            .form(&[
+                ("client_id", self.client_id.as_str()),
+                ("timestamp", timestamp.as_str()),
+                ("client_secret_sign", sign.as_str()),
+                ("grant_type", "client_credentials"),
+                ("type", "SELF"),
+            ])
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let body: Value = resp.json().await?;
+        if !status.is_success() {
+            return Err(MyceliumError::Internal(format!(
+                "네이버 커머스 인증 실패: {}",
+                api_error_message(&body)
+            )));
+        }
+
+        body.get("access_token")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| MyceliumError::Internal("네이버 커머스 토큰 응답 형식 오류".into()))
+    }
+
+    async fn access_token(&self) -> MyceliumResult<String> {
+        if let Some(token) = self.token.lock().unwrap().clone() {
+            return Ok(token);
+        }
+        let token = self.issue_token().await?;
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(token)
+    }
+
+    /// Fetches paid (발주 대기) product orders changed since `since`.
+    /// The API only allows 24 hour windows, so longer ranges are walked day by day.
+    pub async fn fetch_new_orders(
+        &self,
+        since: DateTime<Utc>,
+    ) -> MyceliumResult<Vec<MallOrderItem>> {
+        let token = self.access_token().await?;
+        let kst = FixedOffset::east_opt(9 * 3600).unwrap();
+        let now = Utc::now();
+
+        let mut product_order_ids: Vec<String> = Vec::new();
+        let mut window_start = since;
+        while window_start < now {
+            let window_end = (window_start + Duration::hours(24)).min(now);
+            let mut more_sequence: Option<String> = None;
+            let mut from = window_start;
+
+            loop {
+                let mut query = vec![
+                    (
+                        "lastChangedFrom",
+                        from.with_timezone(&kst)
+                            .format("%Y-%m-%dT%H:%M:%S%.3f%:z")
+                            .to_string(),
+                    ),
+                    (
+                        "lastChangedTo",
+                        window_end
+                            .with_timezone(&kst)
+                            .format("%Y-%m-%dT%H:%M:%S%.3f%:z")
+                            .to_string(),
+                    ),
+                    ("lastChangedType", "PAYED".to_string()),
+                ];
+                if let Some(seq) = &more_sequence {
+                    query.push(("moreSequence", seq.clone()));
+                }
+
+                let body = self
+                    .get_json(
+                        "/external/v1/pay-order/seller/product-orders/last-changed-statuses",
+                        &token,
+                        &query,
+                    )
+                    .await?;
+                let (ids, more) = parse_last_changed_statuses(&body);
+                for id in ids {
+                    if !product_order_ids.contains(&id) {
+                        product_order_ids.push(id);
+                    }
+                }
+
+                match more {
+                    Some((more_from, seq)) => {
+                        from = DateTime::parse_from_rfc3339(&more_from)
+                            .map(|d| d.with_timezone(&Utc))
+                            .unwrap_or(window_end);
+                        more_sequence = Some(seq);
+                    }
+                    None => break,
+                }
+            }
+            window_start = window_end;
+        }
+
+        let mut items = Vec::new();
+        for chunk in product_order_ids.chunks(PRODUCT_ORDER_QUERY_LIMIT) {
+            let resp = self
+                .http
+                .post(format!(
+                    "{}/external/v1/pay-order/seller/product-orders/query",
+                    self.base_url
+                ))
+                .bearer_auth(&token)
+                .json(&json!({ "productOrderIds": chunk }))
+                .send()
+                .await?;
+            let status = resp.status();
+            let body: Value = resp.json().await?;
+            if !status.is_success() {
+                return Err(MyceliumError::Internal(format!(
+                    "네이버 주문 상세 조회 실패: {}",
+                    api_error_message(&body)
+                )));
+            }
+            items.extend(parse_product_orders(&body));
+        }
+        Ok(items)
+    }
+
+    /// Registers 발송처리 (courier + tracking number) for one product order.
+    pub async fn dispatch_product_order(
+        &self,
+        product_order_id: &str,
+        courier_code: &str,
+        tracking_number: &str,
+        dispatch_date: NaiveDate,
+    ) -> MyceliumResult<()> {
+        let token = self.access_token().await?;
+        let dispatch_at = format!("{}T09:00:00.000+09:00", dispatch_date.format("%Y-%m-%d"));
+        let resp = self
+            .http
+            .post(format!(
+                "{}/external/v1/pay-order/seller/product-orders/dispatch",
+                self.base_url
+            ))
+            .bearer_auth(&token)
+            .json(&json!({
+                "dispatchProductOrders": [{
+                    "productOrderId": product_order_id,
+                    "deliveryMethod": "DELIVERY",
+                    "deliveryCompanyCode": courier_code,
+                    "trackingNumber": tracking_number,
+                    "dispatchDate": dispatch_at,
+                }]
+            }))
+            .send()
+            .await?;
+        let status = resp.status();
+        let body: Value = resp.json().await?;
+        if !status.is_success() {
+            return Err(MyceliumError::Internal(format!(
+                "네이버 발송처리 실패: {}",
+                api_error_message(&body)
+            )));
+        }
+
+        // The call succeeds as a whole even when individual orders are rejected
+        if let Some(fail) = body["data"]["failProductOrderInfos"]
+            .as_array()
+            .and_then(|f| f.first())
+        {
+            return Err(MyceliumError::Internal(format!(
+                "네이버 발송처리 실패: {}",
+                json_string(&fail["message"])
+                    .or_else(|| json_string(&fail["code"]))
+                    .unwrap_or_else(|| "알 수 없는 오류".to_string())
+            )));
+        }
+        Ok(())
+    }
+
+    /// Sets the sellable quantity of a listing. `product_code` is the mapping code used by
+    /// the order import: the channel product no, optionally followed by "-{optionCode}".
+    pub async fn update_stock(&self, product_code: &str, quantity: i32) -> MyceliumResult<()> {
+        let token = self.access_token().await?;
+        let (channel_no, option_id) = match product_code.split_once('-') {
+            Some((no, opt)) => (no, Some(opt)),
+            None => (product_code, None),
+        };
+
+        // Stock belongs to the origin product behind the channel listing
+        let product = self
+            .get_json(
+                &format!("/external/v2/products/channel-products/{}", channel_no),
+                &token,
+                &[],
+            )
+            .await?;
+        let origin_no = json_string(&product["originProductNo"]).ok_or_else(|| {
+            MyceliumError::Internal(format!(
+                "네이버 원상품 번호를 찾을 수 없습니다: {}",
+                channel_no
+            ))
+        })?;
+
+        let body = match option_id {
+            Some(opt) => {
+                let option_id: i64 = opt.parse().map_err(|_| {
+                    MyceliumError::Validation(format!("네이버 옵션 코드 형식 오류: {}", opt))
+                })?;
+                json!({
+                    "optionInfo": {
+                        "optionCombinations": [{ "id": option_id, "stockQuantity": quantity }]
+                    }
+                })
+            }
+            None => json!({ "stockQuantity": quantity }),
+        };
+        let resp = self
+            .http
+            .put(format!(
+                "{}/external/v1/products/origin-products/{}/option-stock",
+                self.base_url, origin_no
+            ))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+            .await?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body: Value = resp.json().await.unwrap_or(Value::Null);
+            return Err(MyceliumError::Internal(format!(
+                "네이버 재고 변경 실패: {}",
+                api_error_message(&body)
+            )));
+        }
+        Ok(())
+    }
+
+    async fn get_json(
+        &self,
+        path: &str,
+        token: &str,
+        query: &[(&str, String)],
+    ) -> MyceliumResult<Value> {
+        let resp = self
+            .http
+            .get(format!("{}{}", self.base_url, path))
+            .bearer_auth(token)
+            .query(query)
+            .send()
+            .await?;
+        let status = resp.status();
+        let body: Value = resp.json().await?;
+        if !status.is_success() {
+            return Err(MyceliumError::Internal(format!(
+                "네이버 주문 조회 실패: {}",
+                api_error_message(&body)
+            )));
+        }
+        Ok(body)
+    }
+}
+
+/// Builds `client_secret_sign`: the client secret is a bcrypt salt
+/// ("$2a$04$" + 22 chars) used to hash "{client_id}_{timestamp}".
+pub fn sign_client_secret(
+    client_id: &str,
+    client_secret: &str,
+    timestamp_ms: i64,
+) -> MyceliumResult<String> {
+    let parts: Vec<&str> = client_secret.split('$').collect();
+    if parts.len() != 4 || parts[3].len() < 22 {
+        return Err(MyceliumError::Validation(
+            "네이버 커머스 애플리케이션 시크릿 형식이 올바르지 않습니다.".into(),
+        ));
+    }
+    let cost: u32 = parts[2]
+        .parse()
+        .map_err(|_| MyceliumError::Validation("네이버 커머스 시크릿 cost 값 오류".into()))?;
+
+    let engine = general_purpose::GeneralPurpose::new(
+        &base64::alphabet::BCRYPT,
+        general_purpose::NO_PAD.with_decode_allow_trailing_bits(true),
+    );
+    let salt_bytes = engine.decode(&parts[3][..22])?;
+    let salt: [u8; 16] = salt_bytes
+        .try_into()
+        .map_err(|_| MyceliumError::Validation("네이버 커머스 시크릿 salt 길이 오류".into()))?;
+
+    let hashed = bcrypt::hash_with_salt(format!("{}_{}", client_id, timestamp_ms), cost, salt)?
+        .format_for_version(bcrypt::Version::TwoA);
+    Ok(general_purpose::STANDARD.encode(hashed))
+}
+
+fn api_error_message(body: &Value) -> String {
+    body.get("message")
+        .and_then(|v| v.as_str())
+        .or_else(|| body.get("code").and_then(|v| v.as_str()))
+        .unwrap_or("알 수 없는 오류")
+        .to_string()
+}
+
+/// Returns product order ids and the paging cursor (`moreFrom`, `moreSequence`) if any.
+pub fn parse_last_changed_statuses(body: &Value) -> (Vec<String>, Option<(String, String)>) {
+    let data = &body["data"];
+    let ids = data["lastChangeStatuses"]
+        .as_array()
+        .map(|rows| {
+            rows.iter()
+                .filter_map(|r| json_string(&r["productOrderId"]))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let more = match (
+        json_string(&data["more"]["moreFrom"]),
+        json_string(&data["more"]["moreSequence"]),
+    ) {
+        (Some(from), Some(seq)) => Some((from, seq)),
+        _ => None,
+    };
+    (ids, more)
+}
+
+pub fn parse_product_orders(body: &Value) -> Vec<MallOrderItem> {
+    let Some(rows) = body["data"].as_array() else {
+        return Vec::new();
+    };
+
+    rows.iter()
+        .filter_map(|row| {
+            let order = &row["order"];
+            let po = &row["productOrder"];
+            let addr = &po["shippingAddress"];
+
+            let qty = po["quantity"].as_i64().unwrap_or(1).max(1) as i32;
+            let paid = po["totalPaymentAmount"].as_i64().unwrap_or(0) as i32;
+            let unit_price = if paid > 0 {
+                paid / qty
+            } else {
+                po["unitPrice"].as_i64().unwrap_or(0) as i32
+            };
+
+            let product_name = json_string(&po["productName"]).unwrap_or_default();
+            let mall_product_name = match json_string(&po["productOption"]) {
+                Some(opt) if !opt.is_empty() => format!("{} / {}", product_name, opt),
+                _ => product_name,
+            };
+
+            let address = [
+                json_string(&addr["baseAddress"]),
+                json_string(&addr["detailedAddress"]),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+            // Options are sold as separate SKUs, so the option code is part of the key
+            let mall_product_code = match (
+                json_string(&po["productId"]),
+                json_string(&po["optionCode"]),
+            ) {
+                (Some(id), Some(opt)) if !opt.is_empty() => Some(format!("{}-{}", id, opt)),
+                (id, _) => id,
+            };
+
+            Some(MallOrderItem {
+                order_id: json_string(&order["orderId"])?,
+                line_id: json_string(&po["productOrderId"])?,
+                mall_product_code,
+                mall_shipment_id: None,
+                order_date: json_string(&order["paymentDate"])
+                    .or_else(|| json_string(&order["orderDate"]))
+                    .and_then(|d| parse_date_safe(d.get(..10)?)),
+                customer_name: json_string(&order["ordererName"]).unwrap_or_default(),
+                receiver_name: json_string(&addr["name"]).unwrap_or_default(),
+                mobile: json_string(&addr["tel1"])
+                    .or_else(|| json_string(&order["ordererTel"]))
+                    .unwrap_or_default(),
+                zip: json_string(&addr["zipCode"]).unwrap_or_default(),
+                address,
+                mall_product_name,
+                qty,
+                unit_price,
+                memo: json_string(&po["shippingMemo"]).filter(|s| !s.is_empty()),
+            })
+        })
+        .collect()
+}
+
+/// Mall APIs send ids as either strings or numbers
+pub(crate) fn json_string(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
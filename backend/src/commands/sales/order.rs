@@ -40,6 +40,8 @@ pub async fn create_sale(
         None,
         None,
         None,
+        None,
+        None,
     )
     .await
 }
@@ -65,6 +67,10 @@ pub async fn create_sale_internal(
     shipping_mobile_number: Option<String>,
     shipping_date: Option<String>,
     paid_amount: Option<i32>,
+    // Code the order was placed with, see `referral::attach_referral`
+    referral_code: Option<String>,
+    // Points the customer pays part of the order with, see `points::redeem_points`
+    points_used: Option<i32>,
 ) -> MyceliumResult<String> {
     DB_MODIFIED.store(true, Ordering::Relaxed);
     let mut tx = pool.begin().await?;
@@ -74,6 +80,21 @@ pub async fn create_sale_internal(
 
     let parsed_date = parse_date_safe(&order_date).unwrap_or_else(|| Local::now().date_naive());
 
+    // Fill a missing zip code from the local postal DB so the courier label is complete
+    let shipping_zip_code = match shipping_address_primary.as_deref() {
+        Some(addr) => {
+            crate::commands::address::resolve_zip_code(
+                pool,
+                shipping_zip_code,
+                addr,
+                shipping_address_detail.as_deref(),
+            )
+            .await?
+            .0
+        }
+        None => shipping_zip_code,
+    };
+
     // Find product_id and tax_type
     let p_info: Option<(i32, Option<String>)> = sqlx::query_as(
         "SELECT product_id, tax_type FROM products WHERE product_name = $1 AND specification IS NOT DISTINCT FROM $2",
@@ -152,6 +173,16 @@ pub async fn create_sale_internal(
     .bind(paid_amount)
     .execute(&mut *tx)
     .await?;
+    if let Some(code) = &referral_code {
+        crate::commands::referral::attach_referral(&mut tx, &sale_id, code).await?;
+    }
+    if let Some(points) = points_used.filter(|p| *p > 0) {
+        let settings = crate::commands::points::load_points_config(pool)
+            .await?
+            .settings;
+        crate::commands::points::redeem_points_in(&mut tx, &settings, username, &sale_id, points)
+            .await?;
+    }
 
     tx.commit().await?;
     Ok(sale_id)
@@ -176,6 +207,9 @@ pub struct CreateSaleRequest {
     pub shipping_mobile_number: Option<String>,
     pub shipping_date: Option<String>,
     pub paid_amount: Option<i32>,
+    pub referral_code: Option<String>,
+    #[serde(default)]
+    pub points_used: Option<i32>,
 }
 
 pub async fn create_sale_axum(
@@ -188,8 +222,20 @@ pub async fn create_sale_axum(
         Some(serde_json::Value::String(s)) => Some(s),
         _ => None,
     };
+    let referral_code = payload.referral_code.filter(|c| !c.trim().is_empty());
+    if let Some(code) = referral_code.as_deref() {
+        if let Err(e) = crate::commands::referral::check_referral_code(
+            &state.pool,
+            code,
+            customer_id_str.as_deref(),
+        )
+        .await
+        {
+            return axum::Json(serde_json::json!({ "success": false, "error": e.to_string() }));
+        }
+    }
 
-    match create_sale_internal(
+    let created = create_sale_internal(
         &state.pool,
         claims.username.as_deref().unwrap_or("Admin"),
         customer_id_str,
@@ -208,9 +254,12 @@ pub async fn create_sale_axum(
         payload.shipping_mobile_number,
         payload.shipping_date,
         payload.paid_amount,
+        referral_code,
+        payload.points_used,
     )
-    .await
-    {
+    .await;
+
+    match created {
         Ok(id) => axum::Json(serde_json::json!({ "success": true, "saleId": id })),
         Err(e) => axum::Json(serde_json::json!({ "success": false, "error": e.to_string() })),
     }
@@ -228,11 +277,12 @@ pub async fn update_sale_status(
 
     sqlx::query("UPDATE sales SET status = $1 WHERE sales_id = $2")
         .bind(status)
-        .bind(sales_id)
+        .bind(&sales_id)
         .execute(&mut *tx)
         .await?;
 
     tx.commit().await?;
+    crate::commands::points::sync_sale_points(state, &sales_id).await;
     Ok(())
 }
 
@@ -246,11 +296,12 @@ pub async fn cancel_sale(
     crate::db::set_db_user_context(&mut *tx, username).await?;
 
     sqlx::query("UPDATE sales SET status = '취소' WHERE sales_id = $1")
-        .bind(sales_id)
+        .bind(&sales_id)
         .execute(&mut *tx)
         .await?;
 
     tx.commit().await?;
+    crate::commands::points::sync_sale_points(state, &sales_id).await;
     Ok(())
 }
 
@@ -460,10 +511,13 @@ pub async fn complete_shipment(
     .bind(carrier)
     .bind(tracking_number)
     .bind(date_parsed)
-    .bind(sales_id)
+    .bind(&sales_id)
     .execute(&mut *tx)
     .await?;
 
+    // Mall orders also need the invoice registered on the mall side
+    super::mall_sync::enqueue_tracking_sync(&mut *tx, &sales_id).await?;
+
     tx.commit().await?;
     Ok(())
 }
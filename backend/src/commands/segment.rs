@@ -0,0 +1,426 @@
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+
+/// Conditions of a segment. Every condition that is set must hold; list conditions
+/// match when any of their values does.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SegmentDefinition {
+    pub membership_levels: Vec<String>,
+    /// Address prefixes such as "서울" or "경기"
+    pub regions: Vec<String>,
+    pub marketing_consent: Option<bool>,
+    /// Window for the order count, amount and product conditions; all time when unset
+    pub purchase_within_days: Option<i32>,
+    pub min_orders: Option<i64>,
+    pub max_orders: Option<i64>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+    /// Bought something in the last N days
+    pub last_order_within_days: Option<i32>,
+    /// Bought nothing in the last N days (dormant)
+    pub no_order_within_days: Option<i32>,
+    /// Bought any of these products
+    pub products: Vec<String>,
+    /// Anniversary falls within the next N days
+    pub anniversary_within_days: Option<i32>,
+}
+
+fn clean_list(values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+impl SegmentDefinition {
+    pub fn validate(&self) -> MyceliumResult<()> {
+        let days = [
+            self.purchase_within_days,
+            self.last_order_within_days,
+            self.no_order_within_days,
+            self.anniversary_within_days,
+        ];
+        let counts = [
+            self.min_orders,
+            self.max_orders,
+            self.min_amount,
+            self.max_amount,
+        ];
+        if days.iter().flatten().any(|d| *d <= 0) || counts.iter().flatten().any(|c| *c < 0) {
+            return Err(MyceliumError::Validation(
+                "기간은 1일 이상, 횟수와 금액은 0 이상이어야 합니다.".to_string(),
+            ));
+        }
+        let inverted =
+            |min: Option<i64>, max: Option<i64>| matches!((min, max), (Some(a), Some(b)) if a > b);
+        if inverted(self.min_orders, self.max_orders) || inverted(self.min_amount, self.max_amount)
+        {
+            return Err(MyceliumError::Validation(
+                "최소값이 최대값보다 클 수 없습니다.".to_string(),
+            ));
+        }
+        if self.anniversary_within_days.is_some_and(|d| d > 366) {
+            return Err(MyceliumError::Validation(
+                "기념일 조건은 366일 이내로 지정해주세요.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// One-line description for segment lists.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        let levels = clean_list(&self.membership_levels);
+        if !levels.is_empty() {
+            parts.push(format!("등급 {}", levels.join("/")));
+        }
+        let regions = clean_list(&self.regions);
+        if !regions.is_empty() {
+            parts.push(format!("지역 {}", regions.join("/")));
+        }
+        if let Some(consent) = self.marketing_consent {
+            let label = if consent {
+                "수신동의"
+            } else {
+                "수신거부"
+            };
+            parts.push(label.to_string());
+        }
+        let window = self
+            .purchase_within_days
+            .map(|d| format!("최근 {}일 ", d))
+            .unwrap_or_default();
+        match (self.min_orders, self.max_orders) {
+            (Some(a), Some(b)) => parts.push(format!("{}구매 {}~{}회", window, a, b)),
+            (Some(a), None) => parts.push(format!("{}구매 {}회 이상", window, a)),
+            (None, Some(b)) => parts.push(format!("{}구매 {}회 이하", window, b)),
+            (None, None) => {}
+        }
+        match (self.min_amount, self.max_amount) {
+            (Some(a), Some(b)) => parts.push(format!("{}구매액 {}~{}원", window, a, b)),
+            (Some(a), None) => parts.push(format!("{}구매액 {}원 이상", window, a)),
+            (None, Some(b)) => parts.push(format!("{}구매액 {}원 이하", window, b)),
+            (None, None) => {}
+        }
+        if let Some(d) = self.last_order_within_days {
+            parts.push(format!("{}일 이내 구매", d));
+        }
+        if let Some(d) = self.no_order_within_days {
+            parts.push(format!("{}일간 구매 없음", d));
+        }
+        let products = clean_list(&self.products);
+        if !products.is_empty() {
+            parts.push(format!("{}구매상품 {}", window, products.join("/")));
+        }
+        if let Some(d) = self.anniversary_within_days {
+            parts.push(format!("{}일 이내 기념일", d));
+        }
+        if parts.is_empty() {
+            "전체 고객".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct CustomerSegment {
+    pub segment_id: i32,
+    pub segment_name: String,
+    pub description: Option<String>,
+    pub definition: sqlx::types::Json<SegmentDefinition>,
+    pub created_by: Option<String>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomerSegmentInfo {
+    #[serde(flatten)]
+    pub segment: CustomerSegment,
+    pub summary: String,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SegmentCustomer {
+    pub customer_id: String,
+    pub customer_name: String,
+    pub mobile_number: String,
+    pub membership_level: Option<String>,
+    pub address_primary: Option<String>,
+    pub marketing_consent: Option<bool>,
+    pub anniversary_date: Option<NaiveDate>,
+    pub total_orders: i64,
+    pub total_amount: i64,
+    pub last_order_date: Option<NaiveDate>,
+}
+
+/// Runs a definition against active customers.
+pub async fn evaluate_segment(
+    pool: &DbPool,
+    definition: &SegmentDefinition,
+) -> MyceliumResult<Vec<SegmentCustomer>> {
+    definition.validate()?;
+    let today = Local::now().date_naive();
+    let since = definition
+        .purchase_within_days
+        .map(|d| today - Duration::days(d as i64));
+    let products = clean_list(&definition.products);
+
+    let mut qb: sqlx::QueryBuilder<'_, sqlx::Postgres> = sqlx::QueryBuilder::new(
+        "SELECT c.customer_id, c.customer_name, c.mobile_number, c.membership_level, c.address_primary,
+                c.marketing_consent, c.anniversary_date,
+                COALESCE(st.orders, 0) AS total_orders, COALESCE(st.amount, 0)::bigint AS total_amount,
+                st.last_order_date
+         FROM customers c
+         LEFT JOIN LATERAL (
+             SELECT COUNT(*) FILTER (WHERE ",
+    );
+    qb.push_bind(since);
+    qb.push("::date IS NULL OR s.order_date >= ");
+    qb.push_bind(since);
+    qb.push(") AS orders, SUM(s.total_amount) FILTER (WHERE ");
+    qb.push_bind(since);
+    qb.push("::date IS NULL OR s.order_date >= ");
+    qb.push_bind(since);
+    qb.push(
+        ") AS amount, MAX(s.order_date) AS last_order_date
+             FROM sales s
+             WHERE s.customer_id = c.customer_id AND s.status NOT IN ('취소', '반품', '반품완료')
+         ) st ON TRUE
+         WHERE COALESCE(c.status, '정상') = '정상'",
+    );
+
+    let levels = clean_list(&definition.membership_levels);
+    if !levels.is_empty() {
+        qb.push(" AND c.membership_level = ANY(");
+        qb.push_bind(levels);
+        qb.push(")");
+    }
+    let regions: Vec<String> = clean_list(&definition.regions)
+        .into_iter()
+        .map(|r| format!("{}%", r))
+        .collect();
+    if !regions.is_empty() {
+        qb.push(" AND c.address_primary LIKE ANY(");
+        qb.push_bind(regions);
+        qb.push(")");
+    }
+    if let Some(consent) = definition.marketing_consent {
+        qb.push(" AND COALESCE(c.marketing_consent, FALSE) = ");
+        qb.push_bind(consent);
+    }
+    if let Some(min) = definition.min_orders {
+        qb.push(" AND COALESCE(st.orders, 0) >= ");
+        qb.push_bind(min);
+    }
+    if let Some(max) = definition.max_orders {
+        qb.push(" AND COALESCE(st.orders, 0) <= ");
+        qb.push_bind(max);
+    }
+    if let Some(min) = definition.min_amount {
+        qb.push(" AND COALESCE(st.amount, 0) >= ");
+        qb.push_bind(min);
+    }
+    if let Some(max) = definition.max_amount {
+        qb.push(" AND COALESCE(st.amount, 0) <= ");
+        qb.push_bind(max);
+    }
+    if let Some(days) = definition.last_order_within_days {
+        qb.push(" AND st.last_order_date >= ");
+        qb.push_bind(today - Duration::days(days as i64));
+    }
+    if let Some(days) = definition.no_order_within_days {
+        qb.push(" AND (st.last_order_date IS NULL OR st.last_order_date < ");
+        qb.push_bind(today - Duration::days(days as i64));
+        qb.push(")");
+    }
+    if !products.is_empty() {
+        qb.push(
+            " AND EXISTS (SELECT 1 FROM sales s WHERE s.customer_id = c.customer_id
+               AND s.status NOT IN ('취소', '반품', '반품완료') AND s.product_name = ANY(",
+        );
+        qb.push_bind(products);
+        qb.push(") AND (");
+        qb.push_bind(since);
+        qb.push("::date IS NULL OR s.order_date >= ");
+        qb.push_bind(since);
+        qb.push("))");
+    }
+    if let Some(days) = definition.anniversary_within_days {
+        // Next occurrence of the anniversary (Feb 29 falls on Feb 28 in other years)
+        qb.push(
+            " AND c.anniversary_date IS NOT NULL AND (CASE WHEN c.anniversary_date >= CURRENT_DATE
+                THEN c.anniversary_date
+                ELSE c.anniversary_date + make_interval(years =>
+                    EXTRACT(YEAR FROM age(CURRENT_DATE - 1, c.anniversary_date))::int + 1)
+              END)::date BETWEEN CURRENT_DATE AND CURRENT_DATE + ",
+        );
+        qb.push_bind(days);
+    }
+    qb.push(" ORDER BY c.customer_name, c.customer_id");
+
+    Ok(qb
+        .build_query_as::<SegmentCustomer>()
+        .fetch_all(pool)
+        .await?)
+}
+
+async fn load_segment(pool: &DbPool, segment_id: i32) -> MyceliumResult<CustomerSegment> {
+    sqlx::query_as::<_, CustomerSegment>(
+        "SELECT segment_id, segment_name, description, definition, created_by, updated_at
+         FROM customer_segments WHERE segment_id = $1",
+    )
+    .bind(segment_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| MyceliumError::Validation("세그먼트를 찾을 수 없습니다.".to_string()))
+}
+
+/// Mobile numbers of a saved segment's customers.
+pub async fn segment_recipients(pool: &DbPool, segment_id: i32) -> MyceliumResult<Vec<String>> {
+    let segment = load_segment(pool, segment_id).await?;
+    Ok(evaluate_segment(pool, &segment.definition)
+        .await?
+        .into_iter()
+        .map(|c| c.mobile_number)
+        .collect())
+}
+
+/// Joins recipient lists, dropping blanks and numbers that differ only in formatting.
+pub fn merge_recipients(lists: impl IntoIterator<Item = Vec<String>>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    lists
+        .into_iter()
+        .flatten()
+        .map(|m| m.trim().to_string())
+        .filter(|m| {
+            let digits: String = m.chars().filter(|c| c.is_ascii_digit()).collect();
+            !digits.is_empty() && seen.insert(digits)
+        })
+        .collect()
+}
+
+pub async fn get_segments_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<Vec<CustomerSegmentInfo>>> {
+    let segments = sqlx::query_as::<_, CustomerSegment>(
+        "SELECT segment_id, segment_name, description, definition, created_by, updated_at
+         FROM customer_segments ORDER BY segment_name",
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(
+        segments
+            .into_iter()
+            .map(|segment| CustomerSegmentInfo {
+                summary: segment.definition.summary(),
+                segment,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSegmentInput {
+    #[serde(alias = "segment_id")]
+    pub segment_id: Option<i32>,
+    #[serde(alias = "segment_name")]
+    pub segment_name: String,
+    pub description: Option<String>,
+    pub definition: SegmentDefinition,
+}
+
+pub async fn save_segment_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(input): Json<SaveSegmentInput>,
+) -> MyceliumResult<Json<i32>> {
+    let name = input.segment_name.trim();
+    if name.is_empty() {
+        return Err(MyceliumError::Validation(
+            "세그먼트 이름을 입력해주세요.".to_string(),
+        ));
+    }
+    input.definition.validate()?;
+    let definition = sqlx::types::Json(&input.definition);
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let saved: Option<(i32,)> =
+        match input.segment_id {
+            Some(id) => sqlx::query_as(
+                "UPDATE customer_segments SET segment_name = $1, description = $2, definition = $3,
+                    updated_at = CURRENT_TIMESTAMP
+                 WHERE segment_id = $4 RETURNING segment_id",
+            )
+            .bind(name)
+            .bind(&input.description)
+            .bind(definition)
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?,
+            None => sqlx::query_as(
+                "INSERT INTO customer_segments (segment_name, description, definition, created_by)
+                 VALUES ($1, $2, $3, $4) RETURNING segment_id",
+            )
+            .bind(name)
+            .bind(&input.description)
+            .bind(definition)
+            .bind(claims.username.as_deref().unwrap_or("Admin"))
+            .fetch_optional(&state.pool)
+            .await?,
+        };
+    saved
+        .map(|(id,)| Json(id))
+        .ok_or_else(|| MyceliumError::Validation("세그먼트를 찾을 수 없습니다.".to_string()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentIdQuery {
+    #[serde(alias = "segment_id")]
+    pub segment_id: i32,
+}
+
+pub async fn delete_segment_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(input): Json<SegmentIdQuery>,
+) -> MyceliumResult<Json<()>> {
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query("DELETE FROM customer_segments WHERE segment_id = $1")
+        .bind(input.segment_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(Json(()))
+}
+
+/// Evaluates an unsaved definition, for the builder's live count.
+pub async fn preview_segment_axum(
+    AxumState(state): AxumState<AppState>,
+    Json(definition): Json<SegmentDefinition>,
+) -> MyceliumResult<Json<Vec<SegmentCustomer>>> {
+    Ok(Json(evaluate_segment(&state.pool, &definition).await?))
+}
+
+pub async fn get_segment_customers_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<SegmentIdQuery>,
+) -> MyceliumResult<Json<Vec<SegmentCustomer>>> {
+    let segment = load_segment(&state.pool, query.segment_id).await?;
+    Ok(Json(
+        evaluate_segment(&state.pool, &segment.definition).await?,
+    ))
+}
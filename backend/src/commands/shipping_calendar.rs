@@ -0,0 +1,470 @@
+#![allow(non_snake_case)]
+use crate::commands::address::{find_remote_area, load_remote_areas, parse_road_address};
+use crate::commands::logistics::PendingShipment;
+use crate::commands::sales::utils::parse_date_safe;
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::middleware::auth::Claims;
+use crate::state::AppState;
+use crate::DB_MODIFIED;
+use axum::{
+    extract::{Query, State as AxumState},
+    Extension, Json,
+};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+
+const DEFAULT_REGION: &str = "기본";
+const REMOTE_REGION: &str = "도서산간";
+const JEJU_REGION: &str = "제주특별자치도";
+const WEEKDAY_NAMES: [&str; 7] = ["월", "화", "수", "목", "금", "토", "일"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TransitTime {
+    pub region: String,
+    pub transit_days: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ShippingHoliday {
+    pub holiday_date: NaiveDate,
+    pub holiday_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShippingCalendarSettings {
+    /// Weekdays numbered from Monday (0) to Sunday (6)
+    pub non_delivery_weekdays: Vec<i32>,
+    pub no_pickup_weekdays: Vec<i32>,
+    pub transit_times: Vec<TransitTime>,
+}
+
+/// Courier working days used to plan shipments of perishable goods.
+#[derive(Debug, Clone, Default)]
+pub struct ShippingCalendar {
+    pub non_delivery_weekdays: Vec<i32>,
+    pub no_pickup_weekdays: Vec<i32>,
+    pub holidays: HashSet<NaiveDate>,
+}
+
+impl ShippingCalendar {
+    fn weekday_index(date: NaiveDate) -> i32 {
+        date.weekday().num_days_from_monday() as i32
+    }
+
+    pub fn is_delivery_day(&self, date: NaiveDate) -> bool {
+        !self.holidays.contains(&date)
+            && !self
+                .non_delivery_weekdays
+                .contains(&Self::weekday_index(date))
+    }
+
+    pub fn is_pickup_day(&self, date: NaiveDate) -> bool {
+        !self.holidays.contains(&date)
+            && !self.no_pickup_weekdays.contains(&Self::weekday_index(date))
+    }
+
+    /// Counts `transit_days` delivery days after the ship date.
+    pub fn estimate_arrival(&self, ship_date: NaiveDate, transit_days: i32) -> NaiveDate {
+        let mut date = ship_date;
+        let mut remaining = transit_days.max(1);
+        // Bounded so a misconfigured calendar (every weekday off) cannot loop forever
+        for _ in 0..60 {
+            date += Duration::days(1);
+            if self.is_delivery_day(date) {
+                remaining -= 1;
+                if remaining == 0 {
+                    break;
+                }
+            }
+        }
+        date
+    }
+
+    /// A ship date is safe when the courier collects that day and the parcel keeps
+    /// moving every day until it arrives, i.e. it never waits in a depot.
+    pub fn is_safe_ship_date(&self, ship_date: NaiveDate, transit_days: i32) -> bool {
+        self.is_pickup_day(ship_date)
+            && (self.estimate_arrival(ship_date, transit_days) - ship_date).num_days()
+                == transit_days.max(1) as i64
+    }
+
+    pub fn earliest_safe_ship_date(&self, from: NaiveDate, transit_days: i32) -> Option<NaiveDate> {
+        (0..60)
+            .map(|i| from + Duration::days(i))
+            .find(|d| self.is_safe_ship_date(*d, transit_days))
+    }
+
+    /// Human readable warning for a shipment sent on `ship_date`, if any.
+    pub fn ship_date_warning(&self, ship_date: NaiveDate, transit_days: i32) -> Option<String> {
+        if !self.is_pickup_day(ship_date) {
+            return Some(format!(
+                "{}({})은 택배 집하가 없는 날입니다.",
+                ship_date.format("%Y-%m-%d"),
+                WEEKDAY_NAMES[Self::weekday_index(ship_date) as usize]
+            ));
+        }
+        if self.is_safe_ship_date(ship_date, transit_days) {
+            return None;
+        }
+        let arrival = self.estimate_arrival(ship_date, transit_days);
+        let mut msg = format!(
+            "예상 도착일 {}({}): 주말/휴일 동안 택배사에 보관됩니다.",
+            arrival.format("%Y-%m-%d"),
+            WEEKDAY_NAMES[Self::weekday_index(arrival) as usize]
+        );
+        if let Some(safe) = self.earliest_safe_ship_date(ship_date, transit_days) {
+            msg.push_str(&format!(" 권장 발송일: {}", safe.format("%Y-%m-%d")));
+        }
+        Some(msg)
+    }
+}
+
+/// Picks the transit time for a destination: remote/island zip codes first
+/// (`is_jeju` is `None` when the zip is not in a remote area), then the province
+/// of the address, then the default entry.
+pub fn resolve_transit_days(
+    transit_times: &[TransitTime],
+    is_jeju: Option<bool>,
+    address: Option<&str>,
+) -> i32 {
+    let lookup = |region: &str| {
+        transit_times
+            .iter()
+            .find(|t| t.region == region)
+            .map(|t| t.transit_days)
+    };
+    let default_days = lookup(DEFAULT_REGION).unwrap_or(1);
+
+    match is_jeju {
+        Some(true) => return lookup(JEJU_REGION).unwrap_or(default_days),
+        Some(false) => return lookup(REMOTE_REGION).unwrap_or(default_days),
+        None => {}
+    }
+
+    let sido = address.and_then(|a| {
+        parse_road_address(a).and_then(|p| p.sido).or_else(|| {
+            a.split_whitespace()
+                .next()
+                .and_then(crate::commands::address::normalize_sido)
+        })
+    });
+    sido.and_then(|s| lookup(&s)).unwrap_or(default_days)
+}
+
+pub async fn load_shipping_calendar(pool: &DbPool) -> MyceliumResult<ShippingCalendar> {
+    let config: Option<(Vec<i32>, Vec<i32>)> = sqlx::query_as(
+        "SELECT non_delivery_weekdays, no_pickup_weekdays FROM shipping_calendar_config WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+    let (non_delivery_weekdays, no_pickup_weekdays) = config.unwrap_or((vec![6], vec![6]));
+
+    let holidays: Vec<(NaiveDate,)> = sqlx::query_as(
+        "SELECT holiday_date FROM shipping_holidays WHERE holiday_date >= CURRENT_DATE - INTERVAL '60 days'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ShippingCalendar {
+        non_delivery_weekdays,
+        no_pickup_weekdays,
+        holidays: holidays.into_iter().map(|(d,)| d).collect(),
+    })
+}
+
+pub async fn load_transit_times(pool: &DbPool) -> MyceliumResult<Vec<TransitTime>> {
+    Ok(sqlx::query_as::<_, TransitTime>(
+        "SELECT region, transit_days FROM shipping_transit_times ORDER BY region",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShipDatePlan {
+    pub ship_date: NaiveDate,
+    pub transit_days: i32,
+    pub estimated_arrival: NaiveDate,
+    pub is_safe: bool,
+    pub safe_ship_date: Option<NaiveDate>,
+    pub warning: Option<String>,
+}
+
+pub async fn plan_ship_date_internal(
+    pool: &DbPool,
+    ship_date: NaiveDate,
+    zip_code: Option<&str>,
+    address: Option<&str>,
+) -> MyceliumResult<ShipDatePlan> {
+    let calendar = load_shipping_calendar(pool).await?;
+    let transit_times = load_transit_times(pool).await?;
+    let areas = load_remote_areas(pool).await?;
+
+    let is_jeju = zip_code
+        .and_then(|z| find_remote_area(z, &areas))
+        .map(|a| a.is_jeju);
+    let transit_days = resolve_transit_days(&transit_times, is_jeju, address);
+
+    Ok(ShipDatePlan {
+        ship_date,
+        transit_days,
+        estimated_arrival: calendar.estimate_arrival(ship_date, transit_days),
+        is_safe: calendar.is_safe_ship_date(ship_date, transit_days),
+        safe_ship_date: calendar.earliest_safe_ship_date(ship_date, transit_days),
+        warning: calendar.ship_date_warning(ship_date, transit_days),
+    })
+}
+
+/// Warning for a shipment that was just completed, based on its destination.
+pub async fn check_shipment_arrival(
+    pool: &DbPool,
+    sales_id: &str,
+    ship_date: NaiveDate,
+) -> MyceliumResult<Option<String>> {
+    let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT shipping_zip_code, shipping_address_primary FROM sales WHERE sales_id = $1",
+    )
+    .bind(sales_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((zip, address)) = row else {
+        return Ok(None);
+    };
+    let plan = plan_ship_date_internal(pool, ship_date, zip.as_deref(), address.as_deref()).await?;
+    Ok(plan.warning)
+}
+
+/// Fills the suggested ship date on shipments that have not been sent yet.
+pub async fn annotate_safe_ship_dates(
+    pool: &DbPool,
+    shipments: &mut [PendingShipment],
+) -> MyceliumResult<()> {
+    if shipments.iter().all(|s| s.tracking_number.is_some()) {
+        return Ok(());
+    }
+    let calendar = load_shipping_calendar(pool).await?;
+    let transit_times = load_transit_times(pool).await?;
+    let areas = load_remote_areas(pool).await?;
+    let today = chrono::Local::now().date_naive();
+
+    for s in shipments.iter_mut().filter(|s| s.tracking_number.is_none()) {
+        let is_jeju = s
+            .shipping_zip_code
+            .as_deref()
+            .and_then(|z| find_remote_area(z, &areas))
+            .map(|a| a.is_jeju);
+        let transit_days = resolve_transit_days(
+            &transit_times,
+            is_jeju,
+            s.shipping_address_primary.as_deref(),
+        );
+        s.transit_days = Some(transit_days);
+        s.safe_ship_date = calendar.earliest_safe_ship_date(today, transit_days);
+    }
+    Ok(())
+}
+
+pub async fn get_shipping_calendar_settings_axum(
+    AxumState(state): AxumState<AppState>,
+) -> MyceliumResult<Json<ShippingCalendarSettings>> {
+    let calendar = load_shipping_calendar(&state.pool).await?;
+    Ok(Json(ShippingCalendarSettings {
+        non_delivery_weekdays: calendar.non_delivery_weekdays,
+        no_pickup_weekdays: calendar.no_pickup_weekdays,
+        transit_times: load_transit_times(&state.pool).await?,
+    }))
+}
+
+pub async fn save_shipping_calendar_settings_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<ShippingCalendarSettings>,
+) -> MyceliumResult<Json<()>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    let all_days = payload
+        .non_delivery_weekdays
+        .iter()
+        .chain(payload.no_pickup_weekdays.iter());
+    if all_days.clone().any(|d| !(0..=6).contains(d)) {
+        return Err(MyceliumError::Validation(
+            "요일 값은 0(월)~6(일) 사이여야 합니다.".to_string(),
+        ));
+    }
+    if payload.transit_times.iter().any(|t| t.transit_days < 1) {
+        return Err(MyceliumError::Validation(
+            "배송 소요일은 1일 이상이어야 합니다.".to_string(),
+        ));
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    sqlx::query(
+        "UPDATE shipping_calendar_config SET non_delivery_weekdays = $1, no_pickup_weekdays = $2, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+    )
+    .bind(&payload.non_delivery_weekdays)
+    .bind(&payload.no_pickup_weekdays)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM shipping_transit_times WHERE region <> $1")
+        .bind(DEFAULT_REGION)
+        .execute(&mut *tx)
+        .await?;
+    for t in &payload.transit_times {
+        sqlx::query(
+            "INSERT INTO shipping_transit_times (region, transit_days) VALUES ($1, $2)
+             ON CONFLICT (region) DO UPDATE SET transit_days = EXCLUDED.transit_days",
+        )
+        .bind(t.region.trim())
+        .bind(t.transit_days)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+pub struct HolidayQuery {
+    pub year: Option<i32>,
+}
+
+pub async fn get_shipping_holidays_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<HolidayQuery>,
+) -> MyceliumResult<Json<Vec<ShippingHoliday>>> {
+    let year = query
+        .year
+        .unwrap_or_else(|| chrono::Local::now().date_naive().year());
+    Ok(Json(
+        sqlx::query_as::<_, ShippingHoliday>(
+            "SELECT holiday_date, holiday_name FROM shipping_holidays
+             WHERE EXTRACT(YEAR FROM holiday_date) = $1 ORDER BY holiday_date",
+        )
+        .bind(year)
+        .fetch_all(&state.pool)
+        .await?,
+    ))
+}
+
+/// Parses a pasted or exported holiday list: one "date, name" per line.
+/// Dates may be written as 2026-10-03 or 20261003 (the public data portal format);
+/// header lines and anything without a date are ignored.
+pub fn parse_holiday_list(content: &str) -> Vec<ShippingHoliday> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches('\u{feff}');
+            let (date_part, name_part) = match line.find([',', '\t']) {
+                Some(idx) => (&line[..idx], &line[idx + 1..]),
+                None => line.split_once(' ').unwrap_or((line, "")),
+            };
+            let holiday_date = parse_date_safe(date_part.trim().trim_matches('"'))?;
+            let name = name_part.trim().trim_matches('"').trim();
+            Some(ShippingHoliday {
+                holiday_date,
+                holiday_name: if name.is_empty() {
+                    "휴일".to_string()
+                } else {
+                    name.to_string()
+                },
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+pub struct HolidayImportPayload {
+    pub content: String,
+}
+
+pub async fn import_shipping_holidays_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<HolidayImportPayload>,
+) -> MyceliumResult<Json<usize>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    let holidays = parse_holiday_list(&payload.content);
+    if holidays.is_empty() {
+        return Err(MyceliumError::Validation(
+            "가져올 휴일이 없습니다. '날짜,휴일명' 형식인지 확인해주세요.".to_string(),
+        ));
+    }
+
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    let mut tx = state.pool.begin().await?;
+    for h in &holidays {
+        sqlx::query(
+            "INSERT INTO shipping_holidays (holiday_date, holiday_name) VALUES ($1, $2)
+             ON CONFLICT (holiday_date) DO UPDATE SET holiday_name = EXCLUDED.holiday_name",
+        )
+        .bind(h.holiday_date)
+        .bind(&h.holiday_name)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(Json(holidays.len()))
+}
+
+#[derive(Deserialize)]
+pub struct HolidayDeletePayload {
+    pub holiday_date: NaiveDate,
+}
+
+pub async fn delete_shipping_holiday_axum(
+    AxumState(state): AxumState<AppState>,
+    Extension(claims): Extension<Claims>,
+    Json(payload): Json<HolidayDeletePayload>,
+) -> MyceliumResult<Json<()>> {
+    if !claims.is_admin() {
+        return Err(MyceliumError::Validation(
+            "Admin access required".to_string(),
+        ));
+    }
+    DB_MODIFIED.store(true, Ordering::Relaxed);
+    sqlx::query("DELETE FROM shipping_holidays WHERE holiday_date = $1")
+        .bind(payload.holiday_date)
+        .execute(&state.pool)
+        .await?;
+    Ok(Json(()))
+}
+
+#[derive(Deserialize)]
+pub struct ShipDateQuery {
+    pub date: Option<String>,
+    pub zip_code: Option<String>,
+    pub address: Option<String>,
+}
+
+pub async fn plan_ship_date_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<ShipDateQuery>,
+) -> MyceliumResult<Json<ShipDatePlan>> {
+    let ship_date = query
+        .date
+        .as_deref()
+        .and_then(parse_date_safe)
+        .unwrap_or_else(|| chrono::Local::now().date_naive());
+    Ok(Json(
+        plan_ship_date_internal(
+            &state.pool,
+            ship_date,
+            query.zip_code.as_deref(),
+            query.address.as_deref(),
+        )
+        .await?,
+    ))
+}
@@ -0,0 +1,242 @@
+use crate::db::DbPool;
+use crate::error::{MyceliumError, MyceliumResult};
+use crate::state::AppState;
+use axum::{
+    extract::{Query, State as AxumState},
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Event types in a customer timeline and the query producing each, all selecting
+/// (event_type, event_id, occurred_at, title, detail, amount, status) for `$1` = customer_id
+const TIMELINE_SOURCES: [(&str, &str); 8] = [
+    (
+        "sale",
+        "SELECT 'sale', sales_id, order_date::timestamp, product_name,
+                COALESCE(specification || ' ', '') || quantity || '개', total_amount::bigint, status
+         FROM sales WHERE customer_id = $1",
+    ),
+    (
+        "claim",
+        "SELECT 'claim', claim_id::text, created_at, claim_type,
+                COALESCE(reason_category, '') || COALESCE(' / ' || memo, ''), refund_amount::bigint, claim_status
+         FROM sales_claims WHERE customer_id = $1",
+    ),
+    (
+        "consultation",
+        "SELECT 'consultation', consult_id::text, consult_date::timestamp, title,
+                category || ' / ' || channel, NULL::bigint, status
+         FROM consultations WHERE customer_id = $1",
+    ),
+    (
+        "message",
+        "SELECT 'message', log_id, sent_at, CASE WHEN is_ad THEN '광고 문자' ELSE '문자' END,
+                content, NULL::bigint, status
+         FROM sms_logs WHERE customer_id = $1",
+    ),
+    (
+        "ledger",
+        "SELECT 'ledger', ledger_id::text, transaction_date::timestamp, transaction_type,
+                description, amount::bigint, NULL
+         FROM customer_ledger WHERE customer_id = $1",
+    ),
+    (
+        "reservation",
+        "SELECT 'reservation', r.reservation_id::text, r.reservation_date + r.reservation_time,
+                COALESCE(p.program_name, '체험'), r.participant_count || '명', r.total_amount::bigint, r.status
+         FROM experience_reservations r
+         LEFT JOIN experience_programs p ON p.program_id = r.program_id
+         WHERE r.customer_id = $1",
+    ),
+    (
+        "point",
+        "SELECT 'point', entry_id::text, created_at, entry_type, memo, points::bigint, NULL
+         FROM point_ledger WHERE customer_id = $1",
+    ),
+    (
+        "log",
+        "SELECT 'log', log_id::text, changed_at, field_name,
+                COALESCE(old_value, '') || ' → ' || COALESCE(new_value, '') || COALESCE(' (' || reason || ')', ''),
+                NULL::bigint, NULL
+         FROM customer_logs WHERE customer_id = $1",
+    ),
+];
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TimelineEvent {
+    pub event_type: String,
+    pub event_id: String,
+    pub occurred_at: Option<NaiveDateTime>,
+    pub title: Option<String>,
+    pub detail: Option<String>,
+    pub amount: Option<i64>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelinePage {
+    pub items: Vec<TimelineEvent>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+    pub has_more: bool,
+}
+
+/// Parses a comma separated type filter; empty means every type.
+pub fn parse_event_types(types: Option<&str>) -> MyceliumResult<Vec<&'static str>> {
+    let requested: Vec<&str> = types
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect();
+    if requested.is_empty() {
+        return Ok(TIMELINE_SOURCES.iter().map(|(t, _)| *t).collect());
+    }
+    requested
+        .into_iter()
+        .map(|t| {
+            TIMELINE_SOURCES
+                .iter()
+                .map(|(known, _)| *known)
+                .find(|known| *known == t)
+                .ok_or_else(|| {
+                    MyceliumError::Validation(format!("알 수 없는 이력 유형입니다: {}", t))
+                })
+        })
+        .collect()
+}
+
+/// Largest page size served
+pub const MAX_TIMELINE_PAGE_SIZE: i64 = 200;
+/// Pages past this are not served; no customer has that many events
+pub const MAX_TIMELINE_PAGE: i64 = 100_000;
+
+/// Keeps a requested page and page size in range.
+pub fn clamp_timeline_page(page: i64, page_size: i64) -> (i64, i64) {
+    (
+        page.clamp(1, MAX_TIMELINE_PAGE),
+        page_size.clamp(1, MAX_TIMELINE_PAGE_SIZE),
+    )
+}
+
+/// One page of a customer's events of the given types, newest first.
+pub async fn fetch_customer_timeline(
+    pool: &DbPool,
+    customer_id: &str,
+    event_types: &[&str],
+    page: i64,
+    page_size: i64,
+) -> MyceliumResult<TimelinePage> {
+    let (page, page_size) = clamp_timeline_page(page, page_size);
+    let union = TIMELINE_SOURCES
+        .iter()
+        .filter(|(t, _)| event_types.contains(t))
+        .map(|(_, sql)| *sql)
+        .collect::<Vec<_>>()
+        .join("\nUNION ALL\n");
+    if union.is_empty() {
+        return Ok(TimelinePage {
+            items: Vec::new(),
+            total: 0,
+            page,
+            page_size,
+            has_more: false,
+        });
+    }
+    let events = format!(
+        "SELECT * FROM ({union}) AS e (event_type, event_id, occurred_at, title, detail, amount, status)"
+    );
+
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM ({events}) t"))
+        .bind(customer_id)
+        .fetch_one(pool)
+        .await?;
+    let items = sqlx::query_as::<_, TimelineEvent>(&format!(
+        "{events} ORDER BY occurred_at DESC NULLS LAST, event_type, event_id DESC LIMIT $2 OFFSET $3"
+    ))
+    .bind(customer_id)
+    .bind(page_size)
+    .bind((page - 1).saturating_mul(page_size))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(TimelinePage {
+        has_more: page.saturating_mul(page_size) < total,
+        items,
+        total,
+        page,
+        page_size,
+    })
+}
+
+/// Event types whose detail is free text about the customer (message contents, old and new
+/// field values), left out of prompts sent to the AI
+const PRIVATE_DETAIL_TYPES: [&str; 2] = ["log", "message"];
+
+/// Compact text of timeline events, one per line, for prompts.
+pub fn summarize_events(events: &[TimelineEvent]) -> String {
+    if events.is_empty() {
+        return "No activity".to_string();
+    }
+    events
+        .iter()
+        .map(|e| {
+            let mut line = format!(
+                "- {} [{}] {}",
+                e.occurred_at
+                    .map(|t| t.format("%Y-%m-%d").to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                e.event_type,
+                e.title.as_deref().unwrap_or("")
+            );
+            let detail = e.detail.as_deref().filter(|d| {
+                !d.is_empty() && !PRIVATE_DETAIL_TYPES.contains(&e.event_type.as_str())
+            });
+            if let Some(detail) = detail {
+                line.push_str(&format!(
+                    ": {}",
+                    detail.chars().take(60).collect::<String>()
+                ));
+            }
+            if let Some(amount) = e.amount {
+                line.push_str(&format!(" ({})", amount));
+            }
+            if let Some(status) = e.status.as_deref() {
+                line.push_str(&format!(" <{}>", status));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineQuery {
+    #[serde(alias = "customer_id")]
+    pub customer_id: String,
+    /// Comma separated, e.g. `sale,consultation`
+    pub types: Option<String>,
+    pub page: Option<i64>,
+    #[serde(alias = "page_size")]
+    pub page_size: Option<i64>,
+}
+
+pub async fn get_customer_timeline_axum(
+    AxumState(state): AxumState<AppState>,
+    Query(query): Query<TimelineQuery>,
+) -> MyceliumResult<Json<TimelinePage>> {
+    let event_types = parse_event_types(query.types.as_deref())?;
+    Ok(Json(
+        fetch_customer_timeline(
+            &state.pool,
+            &query.customer_id,
+            &event_types,
+            query.page.unwrap_or(1),
+            query.page_size.unwrap_or(30),
+        )
+        .await?,
+    ))
+}
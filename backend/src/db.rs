@@ -148,6 +148,8 @@ pub struct Sales {
     pub tax_exempt_value: Option<i32>,
     #[sqlx(default)]
     pub changed_by: Option<String>,
+    #[sqlx(default)]
+    pub points_used: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -196,6 +198,10 @@ pub struct Customer {
     pub marketing_consent: Option<bool>,
     #[sqlx(default)]
     pub acquisition_channel: Option<String>,
+    #[sqlx(default)]
+    pub referral_code: Option<String>,
+    #[sqlx(default)]
+    pub referred_by: Option<String>,
 
     // Preferences
     #[sqlx(default)]
@@ -240,6 +246,9 @@ pub struct CustomerAddress {
     pub created_at: Option<NaiveDateTime>,
     #[sqlx(default)]
     pub updated_at: Option<NaiveDateTime>,
+    #[sqlx(default)]
+    #[serde(default)]
+    pub is_remote_area: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -251,6 +260,8 @@ pub struct CustomerLog {
     pub new_value: Option<String>,
     pub changed_at: Option<NaiveDateTime>,
     pub changed_by: Option<String>,
+    #[sqlx(default)]
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
@@ -403,6 +414,9 @@ pub struct CompanyInfo {
     pub memo: Option<String>,
     #[sqlx(default)]
     pub certification_info: Option<serde_json::Value>,
+    #[sqlx(default)]
+    #[serde(default)]
+    pub bank_account: Option<String>,
     pub created_at: Option<NaiveDateTime>,
     #[sqlx(default)]
     pub updated_at: Option<NaiveDateTime>,
@@ -485,6 +499,11 @@ pub struct ProductAssociation {
     pub product_b: String,
     pub pair_count: i64,
     pub support_percent: f64,
+    /// Share of orders with A that also had B
+    pub confidence_a_to_b: f64,
+    pub confidence_b_to_a: f64,
+    /// How much more often the pair occurs than if A and B were unrelated
+    pub lift: f64,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -575,6 +594,9 @@ pub struct RepurchaseCandidate {
     pub predicted_days_remaining: i32,
     pub last_product: Option<String>,
     pub purchase_count: i64,
+    /// Suggested from what customers with the same products also bought
+    #[sqlx(default)]
+    pub recommended_product: Option<String>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
@@ -597,6 +619,16 @@ pub struct Consultation {
     #[sqlx(default)]
     pub updated_at: Option<chrono::NaiveDateTime>,
     pub sentiment: Option<String>,
+    #[sqlx(default)]
+    pub assignee_id: Option<i32>,
+    #[sqlx(default)]
+    pub due_at: Option<chrono::NaiveDateTime>,
+    #[sqlx(default)]
+    pub resolved_at: Option<chrono::NaiveDateTime>,
+    #[sqlx(default)]
+    pub sales_id: Option<String>,
+    #[sqlx(default)]
+    pub claim_id: Option<i32>,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
@@ -22,6 +22,9 @@ mod tests {
         pool
     }
 
+    /// Held by tests that switch the points program on and restore it afterwards
+    static POINTS_SETTINGS: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
     #[tokio::test]
     async fn test_create_sale_integration() {
         let pool = setup_test_db().await;
@@ -48,6 +51,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
         )
         .await;
 
@@ -116,6 +121,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
         )
         .await;
 
@@ -174,6 +181,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -325,6 +334,8 @@ mod tests {
             None,
             None,
             None,
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -718,4 +729,3485 @@ mod tests {
         assert_eq!(logs.len(), 2, "Should have 2 BOM deduction logs");
         println!("BOM deduction verified successfully.");
     }
+
+    /// Serves `router` on a random local port and returns its base URL
+    async fn spawn_mock_server(router: axum::Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_naver_commerce_mock_order_fetch() {
+        use crate::commands::sales::naver_commerce::NaverCommerceClient;
+        use axum::{
+            http::{HeaderMap, StatusCode},
+            routing::{get, post},
+            Form, Json, Router,
+        };
+        use base64::{engine::general_purpose, Engine as _};
+        use std::collections::HashMap;
+
+        const CLIENT_ID: &str = "mock-client";
+        // Naver issues the application secret as a bcrypt salt
+        const CLIENT_SECRET: &str = "$2a$04$abcdefghijklmnopqrstuu";
+
+        let token = |Form(form): Form<HashMap<String, String>>| async move {
+            let sign = general_purpose::STANDARD
+                .decode(&form["client_secret_sign"])
+                .unwrap();
+            let password = format!("{}_{}", form["client_id"], form["timestamp"]);
+            // The server knows the registered secret, i.e. the salt the hash must carry
+            let hashed = std::str::from_utf8(&sign).unwrap();
+            let valid =
+                hashed.starts_with(CLIENT_SECRET) && bcrypt::verify(password, hashed).unwrap();
+            if !valid || form["grant_type"] != "client_credentials" {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(serde_json::json!({ "code": "GW.AUTHN", "message": "invalid sign" })),
+                );
+            }
+            let body: serde_json::Value =
+                serde_json::from_str(include_str!("commands/sales/fixtures/naver_token.json"))
+                    .unwrap();
+            (StatusCode::OK, Json(body))
+        };
+        let statuses = |headers: HeaderMap| async move {
+            assert_eq!(headers["authorization"], "Bearer mock-access-token");
+            include_str!("commands/sales/fixtures/naver_last_changed_statuses.json")
+        };
+        let query = |Json(body): Json<serde_json::Value>| async move {
+            assert_eq!(body["productOrderIds"].as_array().unwrap().len(), 2);
+            include_str!("commands/sales/fixtures/naver_product_orders.json")
+        };
+
+        let router = Router::new()
+            .route("/external/v1/oauth2/token", post(token))
+            .route(
+                "/external/v1/pay-order/seller/product-orders/last-changed-statuses",
+                get(statuses),
+            )
+            .route(
+                "/external/v1/pay-order/seller/product-orders/query",
+                post(query),
+            );
+        let base_url = spawn_mock_server(router).await;
+
+        let client = NaverCommerceClient::new(CLIENT_ID, CLIENT_SECRET).with_base_url(&base_url);
+        let items = client
+            .fetch_new_orders(chrono::Utc::now() - chrono::Duration::hours(2))
+            .await
+            .expect("Naver mock fetch failed");
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].order_id, "2026101812345601");
+        assert_eq!(items[0].line_id, "2026101898765401");
+        assert_eq!(items[0].mall_product_code.as_deref(), Some("8812345678"));
+        assert_eq!(
+            items[0].order_date,
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 18)
+        );
+        assert_eq!(
+            items[0].mall_product_name,
+            "유기농 표고버섯 1kg / 포장: 선물용"
+        );
+        assert_eq!(items[0].qty, 2);
+        assert_eq!(items[0].unit_price, 15000);
+        assert_eq!(items[0].receiver_name, "김수령");
+        assert_eq!(items[0].address, "서울특별시 강남구 테헤란로 123 5층");
+        assert_eq!(
+            items[0].memo.as_deref(),
+            Some("부재 시 경비실에 맡겨주세요")
+        );
+        assert_eq!(items[1].memo, None);
+
+        // A wrong secret must be rejected by the signature check
+        let bad = NaverCommerceClient::new(CLIENT_ID, "$2a$04$zzzzzzzzzzzzzzzzzzzzzz")
+            .with_base_url(&base_url);
+        assert!(bad.issue_token().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_coupang_wing_mock_order_fetch() {
+        use crate::commands::sales::coupang_wing::{sign_request, CoupangWingClient};
+        use axum::{
+            extract::RawQuery,
+            http::{HeaderMap, StatusCode},
+            routing::get,
+            Router,
+        };
+
+        const PATH: &str = "/v2/providers/openapi/apis/api/v4/vendors/A00012345/ordersheets";
+
+        let ordersheets = |headers: HeaderMap, RawQuery(query): RawQuery| async move {
+            let auth = headers["authorization"].to_str().unwrap().to_string();
+            let field = |name: &str| {
+                auth.split(", ")
+                    .find_map(|p| p.strip_prefix(&format!("{}=", name)))
+                    .unwrap_or("")
+                    .to_string()
+            };
+            let expected = sign_request(
+                "mock-secret",
+                &field("signed-date"),
+                "GET",
+                PATH,
+                query.as_deref().unwrap_or(""),
+            )
+            .unwrap();
+            if field("access-key") != "mock-access" || field("signature") != expected {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    "{\"code\":401,\"message\":\"invalid signature\"}",
+                );
+            }
+            (
+                StatusCode::OK,
+                include_str!("commands/sales/fixtures/coupang_ordersheets.json"),
+            )
+        };
+        let base_url = spawn_mock_server(Router::new().route(PATH, get(ordersheets))).await;
+
+        let today = chrono::Local::now().date_naive();
+        let items = CoupangWingClient::new("mock-access", "mock-secret", "A00012345")
+            .with_base_url(&base_url)
+            .fetch_new_orders(today - chrono::Duration::days(1), today)
+            .await
+            .expect("Coupang mock fetch failed");
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].order_id, "9100041863244");
+        assert_eq!(items[0].line_id, "3000001234");
+        assert_eq!(
+            items[0].mall_shipment_id.as_deref(),
+            Some("642538970006401429")
+        );
+        assert_eq!(
+            items[0].order_date,
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 17)
+        );
+        assert_eq!(items[0].qty, 3);
+        assert_eq!(items[0].unit_price, 15000);
+        assert_eq!(items[0].zip, "05510");
+        assert_eq!(
+            items[0].address,
+            "서울특별시 송파구 송파대로 570 101동 1203호"
+        );
+        assert_eq!(items[0].memo.as_deref(), Some("문 앞"));
+
+        let bad = CoupangWingClient::new("mock-access", "wrong-secret", "A00012345")
+            .with_base_url(&base_url)
+            .fetch_new_orders(today, today)
+            .await;
+        assert!(bad.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mall_import_is_idempotent() {
+        use crate::commands::sales::external::MallOrderItem;
+        use crate::commands::sales::mall_import::import_mall_items;
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..8].to_uppercase();
+        let mall = format!("mock-{}", tag);
+
+        let (product_id,): (i32,) = sqlx::query_as(
+            "INSERT INTO products (product_name, specification, unit_price, stock_quantity, tax_type)
+             VALUES ($1, '1kg', 15000, 100, '면세') RETURNING product_id",
+        )
+        .bind(format!("몰연동 표고버섯 {}", tag))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO mall_product_mappings (mall_name, mall_product_code, product_id) VALUES ($1, 'P-100', $2)",
+        )
+        .bind(&mall)
+        .bind(product_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Existing customer stored with dashes must be matched by digits only
+        let mobile_digits = format!("0109{}", &uuid::Uuid::new_v4().as_u128().to_string()[..7]);
+        let customer_id = format!("C-{}", tag);
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+             VALUES ($1, '기존고객', $2, CURRENT_DATE, '정상')",
+        )
+        .bind(&customer_id)
+        .bind(crate::commands::sales::mall_import::format_mobile(
+            &mobile_digits,
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let line = |line_id: &str, code: &str, qty: i32| MallOrderItem {
+            order_id: format!("ORD-{}", tag),
+            line_id: line_id.to_string(),
+            mall_product_code: Some(code.to_string()),
+            mall_shipment_id: None,
+            order_date: None,
+            customer_name: "기존고객".to_string(),
+            receiver_name: "기존고객".to_string(),
+            mobile: mobile_digits.clone(),
+            zip: "06236".to_string(),
+            address: "서울특별시 강남구 테헤란로 123 5층".to_string(),
+            mall_product_name: "표고버섯 1kg".to_string(),
+            qty,
+            unit_price: 15000,
+            memo: None,
+        };
+
+        let items = vec![line("L1", "P-100", 2), line("L2", "UNKNOWN", 1)];
+        let first = import_mall_items(&pool, "Admin", &mall, &items)
+            .await
+            .unwrap();
+        assert_eq!(first.fetched, 2);
+        assert_eq!(first.new, 1);
+        assert_eq!(first.skipped, 1);
+        assert_eq!(first.new_customers, 0);
+        assert_eq!(first.unmapped_products, vec!["UNKNOWN (표고버섯 1kg)"]);
+
+        // Re-running the same fetch must not create duplicates
+        let second = import_mall_items(&pool, "Admin", &mall, &items)
+            .await
+            .unwrap();
+        assert_eq!((second.new, second.updated, second.skipped), (0, 0, 2));
+
+        // A changed quantity on an unshipped line is an update
+        let changed = vec![line("L1", "P-100", 3)];
+        let third = import_mall_items(&pool, "Admin", &mall, &changed)
+            .await
+            .unwrap();
+        assert_eq!((third.new, third.updated), (0, 1));
+
+        let rows: Vec<(Option<String>, i32, i32)> = sqlx::query_as(
+            "SELECT customer_id, quantity, total_amount FROM sales WHERE mall_name = $1",
+        )
+        .bind(&mall)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows, vec![(Some(customer_id.clone()), 3, 45000)]);
+
+        sqlx::query("DELETE FROM sales WHERE mall_name = $1")
+            .bind(&mall)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM mall_product_mappings WHERE mall_name = $1")
+            .bind(&mall)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mall_tracking_push_mock() {
+        use crate::commands::sales::coupang_wing::CoupangWingClient;
+        use crate::commands::sales::naver_commerce::NaverCommerceClient;
+        use axum::{
+            routing::{post, put},
+            Json, Router,
+        };
+        use serde_json::{json, Value};
+
+        let token = || async { include_str!("commands/sales/fixtures/naver_token.json") };
+        let dispatch = |Json(body): Json<Value>| async move {
+            let order = &body["dispatchProductOrders"][0];
+            assert_eq!(order["deliveryCompanyCode"], "CJGLS");
+            assert_eq!(order["dispatchDate"], "2026-10-19T09:00:00.000+09:00");
+            // A second product order id is rejected to exercise partial failure handling
+            if order["productOrderId"] == "2026101898765401" {
+                Json(
+                    json!({ "data": { "successProductOrderIds": ["2026101898765401"], "failProductOrderInfos": [] } }),
+                )
+            } else {
+                Json(
+                    json!({ "data": { "successProductOrderIds": [], "failProductOrderInfos": [
+                    { "productOrderId": order["productOrderId"], "code": "104105", "message": "발송 처리할 수 없는 주문 상태입니다." }
+                ] } }),
+                )
+            }
+        };
+        let ack = |Json(body): Json<Value>| async move {
+            assert_eq!(body["shipmentBoxIds"][0], 642538970006401429i64);
+            Json(
+                json!({ "code": 200, "data": { "responseCode": 0, "responseList": [
+                { "shipmentBoxId": 642538970006401429i64, "succeed": true, "resultCode": "OK" }
+            ] } }),
+            )
+        };
+        let invoices = |Json(body): Json<Value>| async move {
+            let dto = &body["orderSheetInvoiceApplyDtos"][0];
+            assert_eq!(dto["orderId"], 9100041863244i64);
+            assert_eq!(dto["vendorItemId"], 3000001234i64);
+            let ok = dto["invoiceNumber"] == "123456789012";
+            Json(
+                json!({ "code": 200, "data": { "responseCode": if ok { 0 } else { 99 }, "responseList": [
+                { "shipmentBoxId": dto["shipmentBoxId"], "succeed": ok, "resultCode": if ok { "OK" } else { "INVALID_INVOICE" }, "resultMessage": if ok { "" } else { "송장번호 오류" } }
+            ] } }),
+            )
+        };
+
+        let router = Router::new()
+            .route("/external/v1/oauth2/token", post(token))
+            .route(
+                "/external/v1/pay-order/seller/product-orders/dispatch",
+                post(dispatch),
+            )
+            .route(
+                "/v2/providers/openapi/apis/api/v4/vendors/A00012345/ordersheets/acknowledgement",
+                put(ack),
+            )
+            .route(
+                "/v2/providers/openapi/apis/api/v4/vendors/A00012345/orders/invoices",
+                post(invoices),
+            );
+        let base_url = spawn_mock_server(router).await;
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+
+        let naver = NaverCommerceClient::new("mock-client", "$2a$04$abcdefghijklmnopqrstuu")
+            .with_base_url(&base_url);
+        naver
+            .dispatch_product_order("2026101898765401", "CJGLS", "123456789012", date)
+            .await
+            .expect("Naver dispatch failed");
+        let err = naver
+            .dispatch_product_order("2026101898765402", "CJGLS", "123456789012", date)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("발송 처리할 수 없는"));
+
+        let coupang = CoupangWingClient::new("mock-access", "mock-secret", "A00012345")
+            .with_base_url(&base_url);
+        coupang
+            .acknowledge_shipment("642538970006401429")
+            .await
+            .expect("Coupang acknowledgement failed");
+        coupang
+            .upload_invoice(
+                "642538970006401429",
+                "9100041863244",
+                "3000001234",
+                "CJGLS",
+                "123456789012",
+            )
+            .await
+            .expect("Coupang invoice upload failed");
+        let err = coupang
+            .upload_invoice(
+                "642538970006401429",
+                "9100041863244",
+                "3000001234",
+                "CJGLS",
+                "0000",
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("송장번호 오류"));
+    }
+
+    #[tokio::test]
+    async fn test_mall_shipment_sync_queue() {
+        use crate::commands::sales::mall_sync::{
+            enqueue_tracking_sync, process_mall_shipment_queue,
+        };
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..8].to_uppercase();
+        let mall_sale = format!("S-M{}", &tag[..7]);
+        let manual_sale = format!("S-H{}", &tag[..7]);
+
+        for (sales_id, mall) in [(&mall_sale, Some("playauto")), (&manual_sale, None)] {
+            sqlx::query(
+                "INSERT INTO sales (sales_id, status, order_date, product_name, unit_price, quantity, total_amount,
+                    courier_name, tracking_number, mall_name, mall_order_id, mall_line_id)
+                 VALUES ($1, '배송중', CURRENT_DATE, '큐 테스트', 1000, 1, 1000, 'CJ대한통운', '123456789012', $2, $3, '1')",
+            )
+            .bind(sales_id)
+            .bind(mall)
+            .bind(format!("ORD-{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+            enqueue_tracking_sync(&pool, sales_id).await.unwrap();
+        }
+
+        // Hand-entered sales are never queued; enqueueing twice keeps a single row
+        enqueue_tracking_sync(&pool, &mall_sale).await.unwrap();
+        let queued: Vec<(String,)> =
+            sqlx::query_as("SELECT sales_id FROM mall_shipment_sync WHERE sales_id IN ($1, $2)")
+                .bind(&mall_sale)
+                .bind(&manual_sale)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(queued, vec![(mall_sale.clone(),)]);
+
+        // A mall without tracking upload support fails right away instead of retrying
+        process_mall_shipment_queue(&pool).await.unwrap();
+        let (status, attempts, error): (String, i32, Option<String>) = sqlx::query_as(
+            "SELECT status, attempts, last_error FROM mall_shipment_sync WHERE sales_id = $1",
+        )
+        .bind(&mall_sale)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((status.as_str(), attempts), ("실패", 1));
+        assert!(error.unwrap().contains("지원하지 않습니다"));
+
+        // A new tracking number puts the row back in the queue
+        sqlx::query("UPDATE sales SET tracking_number = '999999999999' WHERE sales_id = $1")
+            .bind(&mall_sale)
+            .execute(&pool)
+            .await
+            .unwrap();
+        enqueue_tracking_sync(&pool, &mall_sale).await.unwrap();
+        let (status, attempts): (String, i32) =
+            sqlx::query_as("SELECT status, attempts FROM mall_shipment_sync WHERE sales_id = $1")
+                .bind(&mall_sale)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((status.as_str(), attempts), ("대기", 0));
+
+        sqlx::query("DELETE FROM sales WHERE sales_id IN ($1, $2)")
+            .bind(&mall_sale)
+            .bind(&manual_sale)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mall_stock_push_mock() {
+        use crate::commands::sales::coupang_wing::CoupangWingClient;
+        use crate::commands::sales::naver_commerce::NaverCommerceClient;
+        use axum::{
+            extract::Path,
+            routing::{get, post, put},
+            Json, Router,
+        };
+        use serde_json::{json, Value};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let token_calls = Arc::new(AtomicUsize::new(0));
+        let counter = token_calls.clone();
+        let token = move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { include_str!("commands/sales/fixtures/naver_token.json") }
+        };
+        let channel_product = |Path(no): Path<String>| async move {
+            Json(json!({ "originProductNo": format!("9{}", no) }))
+        };
+        let option_stock = |Path(origin): Path<String>, Json(body): Json<Value>| async move {
+            match origin.as_str() {
+                "98812345678" => assert_eq!(body, json!({ "stockQuantity": 7 })),
+                _ => assert_eq!(
+                    body["optionInfo"]["optionCombinations"][0],
+                    json!({ "id": 501, "stockQuantity": 0 })
+                ),
+            }
+            Json(json!({}))
+        };
+        let quantities = |Path((item, qty)): Path<(String, i32)>| async move {
+            if item == "3000001234" && qty == 7 {
+                Json(json!({ "code": "SUCCESS", "message": "" }))
+            } else {
+                Json(json!({ "code": "ERROR", "message": "판매중지된 옵션입니다." }))
+            }
+        };
+
+        let router = Router::new()
+            .route("/external/v1/oauth2/token", post(token))
+            .route(
+                "/external/v2/products/channel-products/{no}",
+                get(channel_product),
+            )
+            .route(
+                "/external/v1/products/origin-products/{origin}/option-stock",
+                put(option_stock),
+            )
+            .route(
+                "/v2/providers/seller_api/apis/api/v1/marketplace/vendor-items/{item}/quantities/{qty}",
+                put(quantities),
+            );
+        let base_url = spawn_mock_server(router).await;
+
+        let naver = NaverCommerceClient::new("mock-client", "$2a$04$abcdefghijklmnopqrstuu")
+            .with_base_url(&base_url);
+        naver.update_stock("8812345678", 7).await.unwrap();
+        naver.update_stock("8812345679-501", 0).await.unwrap();
+        // The token is reused across calls of one client
+        assert_eq!(token_calls.load(Ordering::SeqCst), 1);
+
+        let coupang = CoupangWingClient::new("mock-access", "mock-secret", "A00012345")
+            .with_base_url(&base_url);
+        coupang
+            .update_vendor_item_quantity("3000001234", 7)
+            .await
+            .unwrap();
+        let err = coupang
+            .update_vendor_item_quantity("3000009999", 7)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("판매중지"));
+    }
+
+    #[tokio::test]
+    async fn test_mall_stock_publish_debounce() {
+        use crate::commands::sales::mall_stock::publish_mall_stock;
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..8].to_uppercase();
+
+        let (product_id,): (i32,) = sqlx::query_as(
+            "INSERT INTO products (product_name, unit_price, stock_quantity) VALUES ($1, 1000, 10) RETURNING product_id",
+        )
+        .bind(format!("재고연동 {}", tag))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO mall_product_mappings (mall_name, mall_product_code, product_id) VALUES ('coupang', $1, $2)",
+        )
+        .bind(format!("9{}", product_id))
+        .bind(product_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "UPDATE mall_stock_settings SET enabled = TRUE, safety_buffer = 3 WHERE mall_name = 'coupang'",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // A fresh inventory log means the product is still changing: wait for it to settle
+        sqlx::query(
+            "INSERT INTO inventory_logs (product_id, product_name, change_type, change_quantity, current_stock)
+             VALUES ($1, '재고연동', '출고', -1, 10)",
+        )
+        .bind(product_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        publish_mall_stock(&pool, false).await.unwrap();
+        let (attempted,): (Option<chrono::NaiveDateTime>,) = sqlx::query_as(
+            "SELECT stock_attempted_at FROM mall_product_mappings WHERE product_id = $1",
+        )
+        .bind(product_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(attempted.is_none());
+
+        // Forcing skips the debounce; without API keys the attempt is recorded as an error
+        publish_mall_stock(&pool, true).await.unwrap();
+        let (published, error): (Option<i32>, Option<String>) = sqlx::query_as(
+            "SELECT last_published_qty, stock_sync_error FROM mall_product_mappings WHERE product_id = $1",
+        )
+        .bind(product_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(published, None);
+        assert!(error.is_some());
+
+        sqlx::query("UPDATE mall_stock_settings SET enabled = FALSE, safety_buffer = 0 WHERE mall_name = 'coupang'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM mall_product_mappings WHERE product_id = $1")
+            .bind(product_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM inventory_logs WHERE product_id = $1")
+            .bind(product_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mall_settlement_reconciliation() {
+        use crate::commands::finance::settlement::{
+            get_settlement_reconciliation_internal, import_settlement_lines, SettlementLine,
+        };
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..8].to_uppercase();
+        let today = chrono::Local::now().date_naive();
+        let orders = [
+            (format!("S-A{}", &tag[..7]), format!("NA{}", tag), 20000),
+            (format!("S-B{}", &tag[..7]), format!("NB{}", tag), 15000),
+            (format!("S-C{}", &tag[..7]), format!("NC{}", tag), 9000),
+        ];
+        for (sales_id, order_id, amount) in &orders {
+            sqlx::query(
+                "INSERT INTO sales (sales_id, status, order_date, product_name, unit_price, quantity, total_amount,
+                    mall_name, mall_order_id, mall_line_id)
+                 VALUES ($1, '배송완료', CURRENT_DATE, '정산 테스트', $2, 1, $2, 'naver', $3, $3)",
+            )
+            .bind(sales_id)
+            .bind(amount)
+            .bind(order_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let line = |order_id: &str, gross: i32| SettlementLine {
+            mall_order_id: order_id.to_string(),
+            mall_line_id: order_id.to_string(),
+            settle_date: Some(today),
+            gross_amount: gross,
+            commission_amount: gross / 50,
+            fee_amount: gross * 37 / 1000,
+            net_amount: gross - gross / 50 - gross * 37 / 1000,
+        };
+        // First order settles in full, second with a different amount, plus an order we never imported
+        let lines = vec![
+            line(&orders[0].1, 20000),
+            line(&orders[1].1, 14000),
+            line(&format!("NX{}", tag), 5000),
+        ];
+
+        let result = import_settlement_lines(&pool, "tester", "naver", None, &lines)
+            .await
+            .unwrap();
+        assert_eq!(
+            (result.imported, result.matched, result.mismatched, result.unmatched),
+            (3, 1, 1, 1)
+        );
+        let batch_id = result.batch_id.unwrap();
+
+        // Commission and fees are booked as expenses of the batch
+        let (expense_total,): (Option<i64>,) = sqlx::query_as(
+            "SELECT SUM(amount)::bigint FROM expenses WHERE settlement_batch_id = $1",
+        )
+        .bind(batch_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let expected: i64 = lines
+            .iter()
+            .map(|l| (l.commission_amount + l.fee_amount) as i64)
+            .sum();
+        assert_eq!(expense_total, Some(expected));
+
+        // Importing the same report again changes nothing
+        let again = import_settlement_lines(&pool, "tester", "naver", None, &lines)
+            .await
+            .unwrap();
+        assert_eq!((again.imported, again.duplicates), (0, 3));
+        assert!(again.batch_id.is_none());
+
+        let report = get_settlement_reconciliation_internal(&pool, Some("naver"), today, today)
+            .await
+            .unwrap();
+        let unsettled: Vec<&str> = report
+            .unsettled
+            .iter()
+            .map(|o| o.sales_id.as_str())
+            .filter(|id| id.ends_with(&tag[..7]))
+            .collect();
+        assert_eq!(unsettled, vec![orders[2].0.as_str()]);
+        let issues: Vec<(&str, &str)> = report
+            .issues
+            .iter()
+            .filter(|i| i.mall_order_id.ends_with(&tag))
+            .map(|i| (i.mall_order_id.as_str(), i.match_status.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (orders[1].1.as_str(), "금액불일치"),
+                (lines[2].mall_order_id.as_str(), "미매칭")
+            ]
+        );
+
+        // Deleting the batch removes its lines and expenses
+        sqlx::query("DELETE FROM mall_settlement_batches WHERE batch_id = $1")
+            .bind(batch_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (left,): (i64,) = sqlx::query_as(
+            "SELECT (SELECT COUNT(*) FROM expenses WHERE settlement_batch_id = $1)
+                  + (SELECT COUNT(*) FROM mall_settlements WHERE batch_id = $1)",
+        )
+        .bind(batch_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(left, 0);
+        for (sales_id, _, _) in &orders {
+            sqlx::query("DELETE FROM sales WHERE sales_id = $1")
+                .bind(sales_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_customer_merge_and_undo() {
+        use crate::commands::customer_merge::{merge_customers, undo_customer_merge};
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let source = format!("CM-S{}", tag);
+        let target = format!("CM-T{}", tag);
+
+        for (id, email, balance) in [(&source, Some("dup@example.com"), 5000), (&target, None, 1000)] {
+            sqlx::query(
+                "INSERT INTO customers (customer_id, customer_name, mobile_number, email, current_balance, join_date)
+                 VALUES ($1, '병합테스트', '010-5555-0000', $2, $3, CURRENT_DATE)",
+            )
+            .bind(id)
+            .bind(email)
+            .bind(balance)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let sale = format!("SM{}", tag);
+        sqlx::query(
+            "INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, unit_price, quantity, total_amount)
+             VALUES ($1, $2, '접수', CURRENT_DATE, '병합 상품', 1000, 1, 1000)",
+        )
+        .bind(&sale)
+        .bind(&source)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO customer_ledger (customer_id, transaction_date, transaction_type, amount)
+             VALUES ($1, CURRENT_DATE, '입금', 5000)",
+        )
+        .bind(&source)
+        .execute(&pool)
+        .await
+        .unwrap();
+        // The source referred both the survivor and another customer
+        let referee = format!("CM-R{}", tag);
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, referred_by, join_date)
+             VALUES ($1, '추천받은고객', '010-5555-0001', $2, CURRENT_DATE)",
+        )
+        .bind(&referee)
+        .bind(&source)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE customers SET referred_by = $1 WHERE customer_id = $2")
+            .bind(&source)
+            .bind(&target)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO customer_consents (customer_id, consented, source) VALUES ($1, TRUE, '고객정보')",
+        )
+        .bind(&source)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let merge_id = merge_customers(&pool, "tester", &source, &target)
+            .await
+            .unwrap();
+
+        let (owner,): (String,) = sqlx::query_as("SELECT customer_id FROM sales WHERE sales_id = $1")
+            .bind(&sale)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(owner, target);
+        let (email, balance): (Option<String>, Option<i32>) = sqlx::query_as(
+            "SELECT email, current_balance FROM customers WHERE customer_id = $1",
+        )
+        .bind(&target)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(email.as_deref(), Some("dup@example.com"));
+        assert_eq!(balance, Some(6000));
+        let (gone,): (bool,) =
+            sqlx::query_as("SELECT NOT EXISTS (SELECT 1 FROM customers WHERE customer_id = $1)")
+                .bind(&source)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(gone);
+        let referrers: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT customer_id, referred_by FROM customers WHERE customer_id IN ($1, $2) ORDER BY customer_id",
+        )
+        .bind(&referee)
+        .bind(&target)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            referrers,
+            vec![
+                (referee.clone(), Some(target.clone())),
+                (target.clone(), None)
+            ]
+        );
+        let (consent_owner,): (String,) = sqlx::query_as(
+            "SELECT customer_id FROM customer_consents WHERE customer_id IN ($1, $2)",
+        )
+        .bind(&source)
+        .bind(&target)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(consent_owner, target);
+        let (logged,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM customer_logs WHERE customer_id IN ($1, $2) AND field_name = 'merge'",
+        )
+        .bind(&source)
+        .bind(&target)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(logged, 2);
+
+        // A sale added to the survivor after the merge stays with it on undo
+        let later_sale = format!("SL{}", tag);
+        sqlx::query(
+            "INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, unit_price, quantity, total_amount)
+             VALUES ($1, $2, '접수', CURRENT_DATE, '병합 상품', 1000, 1, 1000)",
+        )
+        .bind(&later_sale)
+        .bind(&target)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        undo_customer_merge(&pool, "tester", merge_id).await.unwrap();
+        let owners: Vec<(String, String)> = sqlx::query_as(
+            "SELECT sales_id, customer_id FROM sales WHERE sales_id IN ($1, $2) ORDER BY sales_id",
+        )
+        .bind(&sale)
+        .bind(&later_sale)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            owners,
+            vec![
+                (later_sale.clone(), target.clone()),
+                (sale.clone(), source.clone())
+            ]
+        );
+        let restored: Vec<(String, Option<String>, Option<i32>)> = sqlx::query_as(
+            "SELECT customer_id, email, current_balance FROM customers WHERE customer_id IN ($1, $2) ORDER BY customer_id",
+        )
+        .bind(&source)
+        .bind(&target)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            restored,
+            vec![
+                (source.clone(), Some("dup@example.com".to_string()), Some(5000)),
+                (target.clone(), None, Some(1000))
+            ]
+        );
+        let (ledger_owner,): (String,) =
+            sqlx::query_as("SELECT customer_id FROM customer_ledger WHERE customer_id IN ($1, $2)")
+                .bind(&source)
+                .bind(&target)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(ledger_owner, source);
+        let referrers: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT customer_id, referred_by FROM customers WHERE customer_id IN ($1, $2) ORDER BY customer_id",
+        )
+        .bind(&referee)
+        .bind(&target)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            referrers,
+            vec![
+                (referee.clone(), Some(source.clone())),
+                (target.clone(), Some(source.clone()))
+            ]
+        );
+
+        // A merge can be undone only once
+        assert!(undo_customer_merge(&pool, "tester", merge_id).await.is_err());
+
+        for sql in [
+            "DELETE FROM sales WHERE customer_id IN ($1, $2)",
+            "DELETE FROM customer_ledger WHERE customer_id IN ($1, $2)",
+            "DELETE FROM customer_logs WHERE customer_id IN ($1, $2)",
+            "DELETE FROM customer_consents WHERE customer_id IN ($1, $2)",
+            "DELETE FROM customer_merges WHERE source_customer_id IN ($1, $2)",
+            "DELETE FROM customers WHERE customer_id IN ($1, $2)",
+        ] {
+            sqlx::query(sql)
+                .bind(&source)
+                .bind(&target)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("DELETE FROM customers WHERE customer_id = $1")
+            .bind(&referee)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rfm_segment_snapshot_transitions() {
+        use crate::commands::rfm::{get_segment_transitions_internal, take_segment_snapshot};
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let customer = format!("RF{}", tag);
+        let (from, to) = (
+            chrono::NaiveDate::from_ymd_opt(1990, 1, 1).unwrap(),
+            chrono::NaiveDate::from_ymd_opt(1990, 2, 1).unwrap(),
+        );
+        // Keep other snapshots out of the way: these dates are far in the past
+        sqlx::query("DELETE FROM customer_segment_snapshots WHERE snapshot_date IN ($1, $2)")
+            .bind(from)
+            .bind(to)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date)
+             VALUES ($1, 'RFM테스트', '010-0000-0000', CURRENT_DATE)",
+        )
+        .bind(&customer)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, unit_price, quantity, total_amount)
+             VALUES ($1, $2, '배송완료', CURRENT_DATE, 'RFM 상품', 1000, 1, 1000)",
+        )
+        .bind(format!("SR{}", tag))
+        .bind(&customer)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let recorded = take_segment_snapshot(&pool, from).await.unwrap();
+        assert!(recorded >= 1);
+        // Re-running the same day replaces the snapshot instead of failing
+        take_segment_snapshot(&pool, from).await.unwrap();
+
+        sqlx::query(
+            "INSERT INTO customer_segment_snapshots (snapshot_date, customer_id, segment_name, recency, frequency, monetary)
+             SELECT $1, customer_id, '테스트이동', 1, 1, 1 FROM customer_segment_snapshots
+             WHERE snapshot_date = $2 AND customer_id = $3",
+        )
+        .bind(to)
+        .bind(from)
+        .bind(&customer)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let report = get_segment_transitions_internal(&pool, from, to)
+            .await
+            .unwrap();
+        assert_eq!((report.from_date, report.to_date), (Some(from), Some(to)));
+        assert!(report
+            .transitions
+            .iter()
+            .any(|t| t.to_segment == "테스트이동" && t.customers == 1));
+
+        for sql in [
+            "DELETE FROM customer_segment_snapshots WHERE snapshot_date IN ($2, $3) OR customer_id = $1",
+            "DELETE FROM sales WHERE customer_id = $1",
+            "DELETE FROM customers WHERE customer_id = $1",
+        ] {
+            sqlx::query(sql)
+                .bind(&customer)
+                .bind(from)
+                .bind(to)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_membership_evaluation_preview() {
+        use crate::commands::membership::evaluate_memberships;
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let (regular, corporate) = (format!("MB{}", tag), format!("MC{}", tag));
+
+        for (id, level) in [(&regular, "일반"), (&corporate, "법인")] {
+            sqlx::query(
+                "INSERT INTO customers (customer_id, customer_name, mobile_number, membership_level, join_date, status)
+                 VALUES ($1, '등급테스트', '010-0000-1111', $2, CURRENT_DATE, '정상')",
+            )
+            .bind(id)
+            .bind(level)
+            .execute(&pool)
+            .await
+            .unwrap();
+            for n in 0..6 {
+                sqlx::query(
+                    "INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, unit_price, quantity, total_amount)
+                     VALUES ($1, $2, '배송완료', CURRENT_DATE - 10, '등급 상품', 200000, 1, 200000)",
+                )
+                .bind(format!("{}{}", &id[..2], &format!("{}{}", tag, n)))
+                .bind(id)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        }
+
+        // Preview only: nothing is changed yet
+        let result = evaluate_memberships(&pool, "tester", false).await.unwrap();
+        let change = result
+            .changes
+            .iter()
+            .find(|c| c.customer_id == regular)
+            .expect("regular customer should be upgraded");
+        assert_eq!((change.old_level.as_str(), change.new_level.as_str()), ("일반", "VIP"));
+        assert!(change.upgrade);
+        assert!(change.reason.contains("1,200,000원"));
+        // A level set by hand outside the rules is never touched
+        assert!(!result.changes.iter().any(|c| c.customer_id == corporate));
+
+        let (level,): (Option<String>,) =
+            sqlx::query_as("SELECT membership_level FROM customers WHERE customer_id = $1")
+                .bind(&regular)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(level.as_deref(), Some("일반"));
+
+        for sql in [
+            "DELETE FROM sales WHERE customer_id IN ($1, $2)",
+            "DELETE FROM customers WHERE customer_id IN ($1, $2)",
+        ] {
+            sqlx::query(sql)
+                .bind(&regular)
+                .bind(&corporate)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_loyalty_points_earn_redeem_reverse() {
+        use crate::commands::points::{
+            award_delivered_points, expire_points, get_customer_points, redeem_points,
+        };
+
+        let _points = POINTS_SETTINGS.lock().await;
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let customer = format!("PT{}", tag);
+        let (delivered, open) = (format!("PD{}", tag), format!("PO{}", tag));
+
+        let saved: (bool, chrono::NaiveDate) =
+            sqlx::query_as("SELECT enabled, earn_start_date FROM points_settings WHERE id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        sqlx::query(
+            "UPDATE points_settings SET enabled = TRUE, earn_start_date = CURRENT_DATE - 30 WHERE id = 1",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, membership_level, join_date, status)
+             VALUES ($1, '포인트테스트', '010-0000-2222', 'VIP', CURRENT_DATE, '정상')",
+        )
+        .bind(&customer)
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (id, status, amount) in [(&delivered, "배송완료", 100000), (&open, "접수", 50000)]
+        {
+            sqlx::query(
+                "INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, unit_price, quantity, total_amount)
+                 VALUES ($1, $2, $3, CURRENT_DATE - 1, '포인트 상품', $4, 1, $4)",
+            )
+            .bind(id)
+            .bind(&customer)
+            .bind(status)
+            .bind(amount)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // VIP earns 2% once, however often the sweep runs
+        assert_eq!(
+            award_delivered_points(&pool, Some(&delivered))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            award_delivered_points(&pool, Some(&delivered))
+                .await
+                .unwrap(),
+            0
+        );
+        let points = get_customer_points(&pool, &customer).await.unwrap();
+        assert_eq!(points.balance, 2000);
+
+        // Redeem on the open order
+        assert!(redeem_points(&pool, "tester", &open, 5000).await.is_err());
+        redeem_points(&pool, "tester", &open, 500).await.unwrap();
+        let (used,): (i32,) = sqlx::query_as("SELECT points_used FROM sales WHERE sales_id = $1")
+            .bind(&open)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(used, 500);
+        assert_eq!(
+            get_customer_points(&pool, &customer).await.unwrap().balance,
+            1500
+        );
+
+        // Returning the delivered order takes the earned points back
+        let (claim_id,): (i32,) = sqlx::query_as(
+            "INSERT INTO sales_claims (sales_id, customer_id, claim_type, reason_category, quantity)
+             VALUES ($1, $2, '반품', '단순변심', 1) RETURNING claim_id",
+        )
+        .bind(&delivered)
+        .bind(&customer)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        crate::commands::sales::claim::process_sales_claim_internal(
+            &pool,
+            "tester",
+            claim_id,
+            "완료".to_string(),
+            true,
+            100000,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            get_customer_points(&pool, &customer).await.unwrap().balance,
+            -500
+        );
+
+        // Cancelling the other order returns the spent points, once
+        crate::commands::sales::order::cancel_sale(&pool, "tester", open.clone())
+            .await
+            .unwrap();
+        crate::commands::points::sync_sale_points(&pool, &open).await;
+        let points = get_customer_points(&pool, &customer).await.unwrap();
+        assert_eq!(points.balance, 0);
+        let types: Vec<&str> = points
+            .entries
+            .iter()
+            .map(|e| e.entry_type.as_str())
+            .collect();
+        assert_eq!(types.iter().filter(|t| **t == "사용취소").count(), 1);
+        assert!(types.contains(&"회수"));
+
+        // Lots past their expiry date are written off
+        sqlx::query("UPDATE point_ledger SET expires_at = CURRENT_DATE - 1 WHERE customer_id = $1 AND remaining > 0")
+            .bind(&customer)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(expire_points(&pool).await.unwrap() >= 1);
+        let points = get_customer_points(&pool, &customer).await.unwrap();
+        assert_eq!(points.balance, -500);
+        assert!(points.entries.iter().all(|e| e.remaining == 0));
+
+        sqlx::query("UPDATE points_settings SET enabled = $1, earn_start_date = $2 WHERE id = 1")
+            .bind(saved.0)
+            .bind(saved.1)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for sql in [
+            "DELETE FROM point_ledger WHERE customer_id = $1",
+            "DELETE FROM sales_claims WHERE customer_id = $1",
+            "DELETE FROM sales WHERE customer_id = $1",
+            "DELETE FROM customers WHERE customer_id = $1",
+        ] {
+            sqlx::query(sql)
+                .bind(&customer)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_points_paid_when_sale_is_created() {
+        use crate::commands::points::{adjust_points, get_customer_points};
+
+        let _points = POINTS_SETTINGS.lock().await;
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let customer = format!("PC{}", tag);
+
+        let (enabled,): (bool,) =
+            sqlx::query_as("SELECT enabled FROM points_settings WHERE id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        sqlx::query("UPDATE points_settings SET enabled = TRUE WHERE id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+             VALUES ($1, '포인트결제', '010-0000-4444', CURRENT_DATE, '정상')",
+        )
+        .bind(&customer)
+        .execute(&pool)
+        .await
+        .unwrap();
+        adjust_points(&pool, "tester", &customer, 1000, None)
+            .await
+            .unwrap();
+
+        let create = |points: i32| {
+            create_sale_internal(
+                &pool,
+                "tester",
+                Some(customer.clone()),
+                "포인트 결제 상품".to_string(),
+                None,
+                1,
+                10000,
+                10000,
+                chrono::Local::now().date_naive().to_string(),
+                None,
+                Some("접수".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(9000),
+                None,
+                Some(points),
+            )
+        };
+        let sale_id = create(600).await.unwrap();
+        let (used,): (i32,) = sqlx::query_as("SELECT points_used FROM sales WHERE sales_id = $1")
+            .bind(&sale_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(used, 600);
+        assert_eq!(
+            get_customer_points(&pool, &customer).await.unwrap().balance,
+            400
+        );
+
+        // More points than the balance fail the sale as a whole
+        assert!(create(500).await.is_err());
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sales WHERE customer_id = $1")
+            .bind(&customer)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        sqlx::query("UPDATE points_settings SET enabled = $1 WHERE id = 1")
+            .bind(enabled)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for sql in [
+            "DELETE FROM point_ledger WHERE customer_id = $1",
+            "DELETE FROM sales WHERE customer_id = $1",
+            "DELETE FROM customers WHERE customer_id = $1",
+        ] {
+            sqlx::query(sql)
+                .bind(&customer)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_customer_segment_evaluation() {
+        use crate::commands::segment::{evaluate_segment, SegmentDefinition};
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let (seoul, busan) = (format!("SA{}", tag), format!("SB{}", tag));
+        let product = format!("세그먼트상품{}", tag);
+
+        for (id, level, address, consent) in [
+            (&seoul, "VIP", "서울특별시 강남구", true),
+            (&busan, "VIP", "부산광역시 해운대구", false),
+        ] {
+            sqlx::query(
+                "INSERT INTO customers (customer_id, customer_name, mobile_number, membership_level, address_primary,
+                    marketing_consent, anniversary_date, join_date, status)
+                 VALUES ($1, '세그먼트', '010-0000-3333', $2, $3, $4, CURRENT_DATE - 365 * 10 + 5, CURRENT_DATE, '정상')",
+            )
+            .bind(id)
+            .bind(level)
+            .bind(address)
+            .bind(consent)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        // Seoul bought the product recently, Busan only long ago
+        for (sales_id, customer, days_ago) in [
+            (format!("SS{}1", tag), &seoul, 10),
+            (format!("SS{}2", tag), &seoul, 20),
+            (format!("SS{}3", tag), &busan, 400),
+        ] {
+            sqlx::query(
+                "INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, unit_price, quantity, total_amount)
+                 VALUES ($1, $2, '배송완료', CURRENT_DATE - $3, $4, 30000, 1, 30000)",
+            )
+            .bind(sales_id)
+            .bind(customer)
+            .bind(days_ago)
+            .bind(&product)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let ids = |list: Vec<crate::commands::segment::SegmentCustomer>| -> Vec<String> {
+            list.into_iter()
+                .map(|c| c.customer_id)
+                .filter(|id| id.ends_with(&tag))
+                .collect()
+        };
+
+        let recent_buyers = SegmentDefinition {
+            products: vec![product.clone()],
+            purchase_within_days: Some(90),
+            min_orders: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(evaluate_segment(&pool, &recent_buyers).await.unwrap()),
+            vec![seoul.clone()]
+        );
+
+        let dormant = SegmentDefinition {
+            membership_levels: vec!["VIP".to_string()],
+            no_order_within_days: Some(180),
+            products: vec![product.clone()],
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(evaluate_segment(&pool, &dormant).await.unwrap()),
+            vec![busan.clone()]
+        );
+
+        let consenting_seoul = SegmentDefinition {
+            regions: vec!["서울".to_string(), "경기".to_string()],
+            marketing_consent: Some(true),
+            anniversary_within_days: Some(7),
+            ..Default::default()
+        };
+        let found = evaluate_segment(&pool, &consenting_seoul).await.unwrap();
+        let seoul_row = found
+            .iter()
+            .find(|c| c.customer_id == seoul)
+            .expect("seoul customer");
+        assert_eq!((seoul_row.total_orders, seoul_row.total_amount), (2, 60000));
+        assert!(!found.iter().any(|c| c.customer_id == busan));
+
+        for sql in [
+            "DELETE FROM sales WHERE customer_id IN ($1, $2)",
+            "DELETE FROM customers WHERE customer_id IN ($1, $2)",
+        ] {
+            sqlx::query(sql)
+                .bind(&seoul)
+                .bind(&busan)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_marketing_consent_and_opt_out() {
+        use crate::commands::consent::{
+            handle_inbound_opt_out, prepare_promotional, record_consent,
+        };
+        use chrono::NaiveTime;
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let (agreed, refused) = (format!("CA{}", tag), format!("CR{}", tag));
+        let suffix: String = uuid::Uuid::new_v4()
+            .as_u128()
+            .to_string()
+            .chars()
+            .take(8)
+            .collect();
+        let (agreed_mobile, refused_mobile) = (
+            format!("010-{}-{}", &suffix[..4], &suffix[4..]),
+            format!("011-{}-{}", &suffix[..4], &suffix[4..]),
+        );
+
+        for (id, mobile) in [(&agreed, &agreed_mobile), (&refused, &refused_mobile)] {
+            sqlx::query(
+                "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+                 VALUES ($1, '동의테스트', $2, CURRENT_DATE, '정상')",
+            )
+            .bind(id)
+            .bind(mobile)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let mut conn = pool.acquire().await.unwrap();
+        assert!(
+            record_consent(&mut conn, &agreed, "sms", true, "매장", None, "tester")
+                .await
+                .unwrap()
+        );
+        // Same state again is not a new record
+        assert!(
+            !record_consent(&mut conn, &agreed, "sms", true, "매장", None, "tester")
+                .await
+                .unwrap()
+        );
+        drop(conn);
+
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let unknown = "010-0000-0000".to_string();
+        let prepared = prepare_promotional(
+            &pool,
+            "SMS",
+            vec![
+                agreed_mobile.clone(),
+                refused_mobile.clone(),
+                unknown.clone(),
+            ],
+            "할인 안내",
+            noon,
+        )
+        .await
+        .unwrap();
+        assert_eq!(prepared.recipients, vec![agreed_mobile.clone()]);
+        assert_eq!(prepared.excluded, vec![refused_mobile.clone(), unknown]);
+        assert!(prepared.content.starts_with("(광고)"));
+
+        // Night sends are refused outright
+        let night = NaiveTime::from_hms_opt(22, 30, 0).unwrap();
+        assert!(
+            prepare_promotional(&pool, "SMS", vec![agreed_mobile.clone()], "할인", night)
+                .await
+                .is_err()
+        );
+
+        // A "수신거부" reply withdraws consent for that number
+        assert!(handle_inbound_opt_out(&pool, &agreed_mobile, "수신거부")
+            .await
+            .unwrap());
+        let prepared = prepare_promotional(&pool, "SMS", vec![agreed_mobile.clone()], "할인", noon)
+            .await
+            .unwrap();
+        assert!(prepared.recipients.is_empty());
+        let (consent,): (Option<bool>,) =
+            sqlx::query_as("SELECT marketing_consent FROM customers WHERE customer_id = $1")
+                .bind(&agreed)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(consent, Some(false));
+        let (sources,): (Vec<String>,) = sqlx::query_as(
+            "SELECT array_agg(source ORDER BY consent_id) FROM customer_consents WHERE customer_id = $1 AND channel = 'sms'",
+        )
+        .bind(&agreed)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(sources, vec!["매장", "문자회신"]);
+
+        sqlx::query("DELETE FROM marketing_opt_outs WHERE mobile_digits = $1")
+            .bind(agreed_mobile.replace('-', ""))
+            .execute(&pool)
+            .await
+            .unwrap();
+        for sql in [
+            "DELETE FROM customer_consents WHERE customer_id IN ($1, $2)",
+            "DELETE FROM customers WHERE customer_id IN ($1, $2)",
+        ] {
+            sqlx::query(sql)
+                .bind(&agreed)
+                .bind(&refused)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inactive_customer_notice_and_anonymization() {
+        use crate::commands::config::MessageCosts;
+        use crate::commands::crm::MessageChannels;
+        use crate::commands::messaging::{Gateway, MockProvider};
+        use crate::commands::privacy::{
+            anonymize_customer, export_customer_data, find_inactive_customers,
+            is_due_for_anonymization, send_notice, InactiveCustomer, ANONYMIZED_NAME,
+        };
+        use chrono::{Duration, Local};
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let customer_id = format!("PV{}", tag);
+        let sale_id = format!("PVS{}", tag);
+        let suffix: String = uuid::Uuid::new_v4()
+            .as_u128()
+            .to_string()
+            .chars()
+            .take(8)
+            .collect();
+        let mobile = format!("010-{}-{}", &suffix[..4], &suffix[4..]);
+
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, email, address_primary, join_date, created_at, status)
+             VALUES ($1, '휴면고객', $2, 'dormant@example.com', '서울시 어딘가', '2020-01-01', '2020-01-01', '정상')",
+        )
+        .bind(&customer_id)
+        .bind(&mobile)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO customer_addresses (customer_id, address_alias, recipient_name, mobile_number, address_primary)
+             VALUES ($1, '집', '휴면고객', $2, '서울시 어딘가')",
+        )
+        .bind(&customer_id)
+        .bind(&mobile)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO sales (sales_id, customer_id, status, product_name, quantity, unit_price, total_amount,
+                order_date, shipping_name, shipping_mobile_number, shipping_address_primary)
+             VALUES ($1, $2, '배송완료', '휴면 테스트', 1, 30000, 30000, '2021-03-01', '휴면고객', $3, '서울시 어딘가')",
+        )
+        .bind(&sale_id)
+        .bind(&customer_id)
+        .bind(&mobile)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // A reply that was never matched to the customer
+        sqlx::query(
+            "INSERT INTO inbound_messages (provider, provider_message_id, mobile_number, content)
+             VALUES ('mock', $1, $2, '휴면고객입니다')",
+        )
+        .bind(&customer_id)
+        .bind(crate::commands::messaging::digits(&mobile))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let find = |pool: sqlx::PgPool, id: String| async move {
+            find_inactive_customers(&pool, 36)
+                .await
+                .unwrap()
+                .into_iter()
+                .find(|c| c.customer_id == id)
+        };
+        let inactive = find(pool.clone(), customer_id.clone()).await.unwrap();
+        assert_eq!(inactive.last_activity.unwrap().to_string(), "2021-03-01");
+        assert!(inactive.noticed_at.is_none());
+
+        // Notice period already over, as if it had been sent a while ago
+        let today = Local::now().date_naive();
+        let anonymize_on = today - Duration::days(1);
+        let channels = MessageChannels {
+            kakao: None,
+            sms: Gateway::Mock(MockProvider::new()),
+            sender: String::new(),
+            sms_costs: MessageCosts::default(),
+        };
+        // A notice that cannot go out is not recorded
+        let unreachable = InactiveCustomer {
+            mobile_number: "02-000".to_string(),
+            ..inactive.clone()
+        };
+        assert!(send_notice(
+            &pool,
+            &channels,
+            &unreachable,
+            today - Duration::days(1),
+            "${name}님 ${date} 파기",
+            "tester"
+        )
+        .await
+        .is_err());
+        assert!(find(pool.clone(), customer_id.clone())
+            .await
+            .unwrap()
+            .noticed_at
+            .is_none());
+        assert!(send_notice(
+            &pool,
+            &channels,
+            &inactive,
+            anonymize_on,
+            "${name}님 ${date} 파기",
+            "tester"
+        )
+        .await
+        .unwrap());
+        assert!(!send_notice(
+            &pool,
+            &channels,
+            &inactive,
+            anonymize_on,
+            "${name}님 ${date} 파기",
+            "tester"
+        )
+        .await
+        .unwrap());
+        let noticed = find(pool.clone(), customer_id.clone()).await.unwrap();
+        assert!(is_due_for_anonymization(&noticed, today));
+
+        let mut conn = pool.acquire().await.unwrap();
+        anonymize_customer(&mut conn, &customer_id, "tester")
+            .await
+            .unwrap();
+        assert!(anonymize_customer(&mut conn, &customer_id, "tester")
+            .await
+            .is_err());
+        drop(conn);
+
+        let (name, email, status): (String, Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT customer_name, email, status FROM customers WHERE customer_id = $1",
+        )
+        .bind(&customer_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(name, ANONYMIZED_NAME);
+        assert_eq!(email, None);
+        assert_eq!(status.as_deref(), Some("익명"));
+        // Amounts stay, shipping details go
+        let (total, shipping_name): (i32, Option<String>) =
+            sqlx::query_as("SELECT total_amount, shipping_name FROM sales WHERE sales_id = $1")
+                .bind(&sale_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(total, 30000);
+        assert_eq!(shipping_name, None);
+        let (recipient,): (String,) =
+            sqlx::query_as("SELECT recipient_name FROM customer_addresses WHERE customer_id = $1")
+                .bind(&customer_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(recipient, ANONYMIZED_NAME);
+        let (leaked,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM sms_logs WHERE mobile_number = $1 OR customer_id = $2 AND recipient_name <> $3",
+        )
+        .bind(&mobile)
+        .bind(&customer_id)
+        .bind(ANONYMIZED_NAME)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(leaked, 0);
+        let (reply,): (String,) = sqlx::query_as(
+            "SELECT content FROM inbound_messages WHERE provider = 'mock' AND provider_message_id = $1",
+        )
+        .bind(&customer_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(reply, "");
+        assert!(find(pool.clone(), customer_id.clone()).await.is_none());
+
+        let export = export_customer_data(&pool, &customer_id).await.unwrap();
+        assert_eq!(export["customer"]["customer_name"], ANONYMIZED_NAME);
+        assert_eq!(export["sales"].as_array().unwrap().len(), 1);
+        assert_eq!(export["privacy_actions"].as_array().unwrap().len(), 2);
+        assert!(export_customer_data(&pool, "NO-SUCH-CUSTOMER")
+            .await
+            .is_err());
+
+        for sql in [
+            "DELETE FROM inbound_messages WHERE provider = 'mock' AND provider_message_id = $1",
+            "DELETE FROM privacy_actions WHERE customer_id = $1",
+            "DELETE FROM sms_logs WHERE customer_id = $1",
+            "DELETE FROM sales WHERE customer_id = $1",
+            "DELETE FROM customer_addresses WHERE customer_id = $1",
+            "DELETE FROM customers WHERE customer_id = $1",
+        ] {
+            sqlx::query(sql)
+                .bind(&customer_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_customer_timeline_merges_and_pages() {
+        use crate::commands::timeline::{fetch_customer_timeline, parse_event_types};
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let customer_id = format!("TL{}", tag);
+
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+             VALUES ($1, '타임라인', '010-0000-0000', '2026-01-01', '정상')",
+        )
+        .bind(&customer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (n, date) in [(1, "2026-01-01"), (2, "2026-02-01")] {
+            sqlx::query(
+                "INSERT INTO sales (sales_id, customer_id, status, product_name, quantity, unit_price, total_amount, order_date)
+                 VALUES ($1, $2, '배송완료', '타임라인 상품', 1, 10000, 10000, $3::date)",
+            )
+            .bind(format!("TLS{}{}", tag, n))
+            .bind(&customer_id)
+            .bind(date)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO customer_ledger (customer_id, transaction_date, transaction_type, amount)
+             VALUES ($1, '2026-01-15', '입금', 5000)",
+        )
+        .bind(&customer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO consultations (customer_id, guest_name, contact, channel, counselor_name, category,
+                title, content, status, priority, consult_date)
+             VALUES ($1, '타임라인', '010-0000-0000', '전화', '상담원', '배송', '배송 문의', '언제 오나요',
+                '완료', '보통', '2026-02-10')",
+        )
+        .bind(&customer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let all = parse_event_types(None).unwrap();
+        let first = fetch_customer_timeline(&pool, &customer_id, &all, 1, 2)
+            .await
+            .unwrap();
+        assert_eq!(first.total, 4);
+        assert!(first.has_more);
+        let types: Vec<&str> = first.items.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, vec!["consultation", "sale"]);
+        let last = fetch_customer_timeline(&pool, &customer_id, &all, 2, 2)
+            .await
+            .unwrap();
+        let types: Vec<&str> = last.items.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, vec!["ledger", "sale"]);
+        assert!(!last.has_more);
+
+        let sales_only = parse_event_types(Some("sale")).unwrap();
+        let sales = fetch_customer_timeline(&pool, &customer_id, &sales_only, 1, 30)
+            .await
+            .unwrap();
+        assert_eq!(sales.total, 2);
+        assert_eq!(sales.items[0].amount, Some(10000));
+
+        for sql in [
+            "DELETE FROM consultations WHERE customer_id = $1",
+            "DELETE FROM customer_ledger WHERE customer_id = $1",
+            "DELETE FROM sales WHERE customer_id = $1",
+            "DELETE FROM customers WHERE customer_id = $1",
+        ] {
+            sqlx::query(sql)
+                .bind(&customer_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_referral_codes_and_rewards() {
+        use crate::commands::points::reverse_sale_points;
+        use crate::commands::referral::{
+            attach_referral, award_referral_rewards, check_referral_code, ensure_referral_code,
+            referral_report, set_referrer,
+        };
+
+        let _points = POINTS_SETTINGS.lock().await;
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let (referrer, referee) = (format!("RA{}", tag), format!("RB{}", tag));
+        let (first, second) = (format!("RS{}1", tag), format!("RS{}2", tag));
+
+        let saved: (bool,) = sqlx::query_as("SELECT enabled FROM points_settings WHERE id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE points_settings SET enabled = TRUE WHERE id = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for id in [&referrer, &referee] {
+            sqlx::query(
+                "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+                 VALUES ($1, '추천테스트', '010-0000-0000', CURRENT_DATE, '정상')",
+            )
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let mut rule_ids = Vec::new();
+        for (name, recipient, reward_type, amount) in [
+            (format!("추천인 적립 {}", tag), "referrer", "points", 1000),
+            (format!("첫 주문 할인 {}", tag), "referee", "discount", 10),
+        ] {
+            let (id,): (i32,) = sqlx::query_as(
+                "INSERT INTO referral_reward_rules (rule_name, recipient, reward_type, amount)
+                 VALUES ($1, $2, $3, $4) RETURNING rule_id",
+            )
+            .bind(name)
+            .bind(recipient)
+            .bind(reward_type)
+            .bind(amount)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            rule_ids.push(id);
+        }
+
+        let code = ensure_referral_code(&pool, &referrer).await.unwrap();
+        assert_eq!(ensure_referral_code(&pool, &referrer).await.unwrap(), code);
+        let typed = code.to_lowercase();
+        let check = check_referral_code(&pool, &typed, Some(&referee))
+            .await
+            .unwrap();
+        assert_eq!(check.referrer_id, referrer);
+        assert!(check.discount_rate >= 10);
+        assert!(check_referral_code(&pool, &code, Some(&referrer))
+            .await
+            .is_err());
+        assert!(check_referral_code(&pool, "NO-SUCH-CODE", None)
+            .await
+            .is_err());
+
+        // 2 x 10,000 with 10% off
+        for sale in [&first, &second] {
+            sqlx::query(
+                "INSERT INTO sales (sales_id, customer_id, status, product_name, quantity, unit_price, total_amount, order_date)
+                 VALUES ($1, $2, '배송완료', '추천 상품', 2, 10000, 18000, CURRENT_DATE)",
+            )
+            .bind(sale)
+            .bind(&referee)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let mut conn = pool.acquire().await.unwrap();
+        attach_referral(&mut conn, &first, &typed).await.unwrap();
+        drop(conn);
+        let (referred_by, channel): (Option<String>, Option<String>) = sqlx::query_as(
+            "SELECT referred_by, acquisition_channel FROM customers WHERE customer_id = $1",
+        )
+        .bind(&referee)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(referred_by.as_deref(), Some(referrer.as_str()));
+        assert_eq!(channel.as_deref(), Some("지인 소개"));
+
+        assert_eq!(
+            award_referral_rewards(&pool, Some(&first)).await.unwrap(),
+            2
+        );
+        assert_eq!(
+            award_referral_rewards(&pool, Some(&first)).await.unwrap(),
+            0
+        );
+        let amounts: Vec<(String, i32)> = sqlx::query_as(
+            "SELECT reward_type, amount FROM referral_rewards WHERE sales_id = $1 ORDER BY reward_type",
+        )
+        .bind(&first)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            amounts,
+            vec![("discount".to_string(), 2000), ("points".to_string(), 1000)]
+        );
+        let (credited,): (i64,) = sqlx::query_as(
+            "SELECT COALESCE(SUM(points), 0)::bigint FROM point_ledger
+             WHERE customer_id = $1 AND entry_type = '추천적립'",
+        )
+        .bind(&referrer)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(credited, 1000);
+
+        // Both rules are first-order-only
+        let mut conn = pool.acquire().await.unwrap();
+        attach_referral(&mut conn, &second, &code).await.unwrap();
+        drop(conn);
+        assert_eq!(
+            award_referral_rewards(&pool, Some(&second)).await.unwrap(),
+            0
+        );
+
+        // A returned order placed with the code is neither revenue nor a first order
+        sqlx::query(
+            "INSERT INTO sales (sales_id, customer_id, status, product_name, quantity, unit_price, total_amount, order_date, referral_code)
+             VALUES ($1, $2, '반품완료', '추천 상품', 1, 10000, 9000, CURRENT_DATE, $3)",
+        )
+        .bind(format!("RS{}3", tag))
+        .bind(&referee)
+        .bind(&code)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let report = referral_report(&pool, None, None, 200).await.unwrap();
+        let top = report
+            .top_referrers
+            .iter()
+            .find(|r| r.customer_id == referrer)
+            .expect("referrer in report");
+        assert_eq!(top.referred_customers, 1);
+        assert_eq!(top.referred_orders, 2);
+        assert_eq!(top.referred_revenue, 36000);
+        assert_eq!(top.reward_points, 1000);
+
+        // The referee joined today: a period starting tomorrow has nobody they brought in
+        let tomorrow = chrono::Local::now().date_naive() + chrono::Duration::days(1);
+        let later = referral_report(&pool, Some(tomorrow), None, 200)
+            .await
+            .unwrap();
+        assert!(later
+            .top_referrers
+            .iter()
+            .all(|r| r.customer_id != referrer));
+
+        // The referee's code cannot be made the referrer's: they would refer each other
+        let referee_code = ensure_referral_code(&pool, &referee).await.unwrap();
+        assert!(
+            set_referrer(&pool, "tester", &referrer, Some(&referee_code))
+                .await
+                .is_err()
+        );
+
+        // Cancelling the rewarded order takes the reward back and frees the first order
+        sqlx::query("UPDATE sales SET status = '취소' WHERE sales_id = $1")
+            .bind(&first)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        reverse_sale_points(&mut tx, &first).await.unwrap();
+        reverse_sale_points(&mut tx, &first).await.unwrap();
+        tx.commit().await.unwrap();
+        let (balance, remaining): (i64, i64) = sqlx::query_as(
+            "SELECT COALESCE(SUM(points), 0)::bigint, COALESCE(SUM(remaining), 0)::bigint
+             FROM point_ledger WHERE customer_id = $1",
+        )
+        .bind(&referrer)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((balance, remaining), (0, 0));
+        let (left,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM referral_rewards WHERE sales_id = $1")
+                .bind(&first)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(left, 0);
+        assert_eq!(
+            award_referral_rewards(&pool, Some(&second)).await.unwrap(),
+            2
+        );
+
+        sqlx::query("UPDATE points_settings SET enabled = $1 WHERE id = 1")
+            .bind(saved.0)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM referral_rewards WHERE referrer_id = $1")
+            .bind(&referrer)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM referral_reward_rules WHERE rule_id = ANY($1)")
+            .bind(&rule_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for sql in [
+            "DELETE FROM point_ledger WHERE customer_id IN ($1, $2)",
+            "DELETE FROM sales WHERE customer_id IN ($1, $2)",
+            "DELETE FROM customers WHERE customer_id IN ($1, $2)",
+        ] {
+            sqlx::query(sql)
+                .bind(&referrer)
+                .bind(&referee)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consultation_ticket_lifecycle() {
+        use crate::commands::consultation::{
+            change_ticket_status, create_consultation_internal, delete_consultation_internal,
+            find_overdue_consultations, update_consultation_internal, update_ticket,
+            UpdateTicketInput,
+        };
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let (customer_id, sales_id) = (format!("TC{}", tag), format!("TS{}", tag));
+
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+             VALUES ($1, '상담테스트', '010-0000-0000', CURRENT_DATE, '정상')",
+        )
+        .bind(&customer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO sales (sales_id, customer_id, status, product_name, quantity, unit_price, total_amount, order_date)
+             VALUES ($1, $2, '배송완료', '상담상품', 1, 10000, 10000, CURRENT_DATE)",
+        )
+        .bind(&sales_id)
+        .bind(&customer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let (user_id,): (i32,) =
+            sqlx::query_as("INSERT INTO users (username, role) VALUES ($1, 'user') RETURNING id")
+                .bind(format!("agent_{}", tag))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+
+        let consult_id = create_consultation_internal(
+            &pool,
+            Some(customer_id.clone()),
+            "상담테스트".to_string(),
+            "010-0000-0000".to_string(),
+            "전화".to_string(),
+            "상담원".to_string(),
+            "배송".to_string(),
+            "배송 지연 문의".to_string(),
+            "언제 오나요".to_string(),
+            "긴급".to_string(),
+        )
+        .await
+        .unwrap();
+        let (hours,): (f64,) = sqlx::query_as(
+            "SELECT EXTRACT(EPOCH FROM (due_at - created_at))::float8 / 3600 FROM consultations WHERE consult_id = $1",
+        )
+        .bind(consult_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!((hours - 4.0).abs() < 0.01);
+
+        let ticket = |assignee_id, due_at| UpdateTicketInput {
+            consult_id,
+            assignee_id,
+            due_at,
+            sales_id: Some(sales_id.clone()),
+            claim_id: None,
+        };
+        assert!(update_ticket(&pool, "tester", ticket(Some(-1), None))
+            .await
+            .is_err());
+        let past_due = chrono::Local::now().naive_local() - chrono::Duration::hours(3);
+        update_ticket(&pool, "tester", ticket(Some(user_id), Some(past_due)))
+            .await
+            .unwrap();
+
+        let overdue = find_overdue_consultations(&pool, Some(user_id), 10)
+            .await
+            .unwrap();
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].consult_id, consult_id);
+        assert_eq!(overdue[0].customer, "상담테스트");
+        assert!(overdue[0].overdue_hours >= 2);
+
+        let follow_up = chrono::Local::now().date_naive() + chrono::Duration::days(2);
+        update_consultation_internal(
+            &pool,
+            "tester",
+            consult_id,
+            Some("확인 중입니다".to_string()),
+            "처리중",
+            "긴급",
+            Some(follow_up),
+        )
+        .await
+        .unwrap();
+        let schedule_status = || async {
+            sqlx::query_scalar::<_, String>(
+                "SELECT status FROM schedules WHERE related_type = 'CONSULTATION' AND related_id = $1",
+            )
+            .bind(consult_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        };
+        assert_eq!(schedule_status().await, vec!["Planned".to_string()]);
+
+        // No way back to '접수'
+        assert!(change_ticket_status(&pool, "tester", consult_id, "접수")
+            .await
+            .is_err());
+        change_ticket_status(&pool, "tester", consult_id, "완료")
+            .await
+            .unwrap();
+        assert_eq!(schedule_status().await, vec!["Completed".to_string()]);
+        let (resolved,): (bool,) = sqlx::query_as(
+            "SELECT resolved_at IS NOT NULL FROM consultations WHERE consult_id = $1",
+        )
+        .bind(consult_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(resolved);
+        assert!(find_overdue_consultations(&pool, Some(user_id), 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let history: Vec<(String, Option<String>)> = sqlx::query_as(
+            "SELECT field_name, new_value FROM consultation_history WHERE consult_id = $1 ORDER BY history_id",
+        )
+        .bind(consult_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let fields: Vec<&str> = history.iter().map(|(f, _)| f.as_str()).collect();
+        assert_eq!(
+            fields,
+            ["assignee", "due_at", "sales_id", "status", "status"]
+        );
+        assert_eq!(history[4].1.as_deref(), Some("완료"));
+
+        delete_consultation_internal(&pool, consult_id)
+            .await
+            .unwrap();
+        assert!(schedule_status().await.is_empty());
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM sales WHERE sales_id = $1")
+            .bind(&sales_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM customers WHERE customer_id = $1")
+            .bind(&customer_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_anniversary_automation_dry_run_send_and_dedup() {
+        use crate::commands::automation::{plan_automation, run_automation, run_automation_with};
+        use crate::commands::config::MessageCosts;
+        use crate::commands::crm::MessageChannels;
+        use crate::commands::messaging::{Gateway, MockProvider};
+        use chrono::Datelike;
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let digits: String = uuid::Uuid::new_v4()
+            .as_u128()
+            .to_string()
+            .chars()
+            .take(7)
+            .collect();
+        let customers = [
+            (
+                format!("AC{}1", tag),
+                format!("010-8{}1", &digits[..6]),
+                true,
+            ),
+            (
+                format!("AC{}2", tag),
+                format!("010-8{}2", &digits[..6]),
+                false,
+            ),
+        ];
+        let today = chrono::Local::now().date_naive();
+        let anniversary = (today + chrono::Duration::days(3))
+            .with_year(1990)
+            .unwrap_or_else(|| chrono::NaiveDate::from_ymd_opt(1990, 3, 1).unwrap());
+        for (id, mobile, consent) in &customers {
+            sqlx::query(
+                "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status,
+                                        anniversary_date, anniversary_type, marketing_consent)
+                 VALUES ($1, '기념일테스트', $2, CURRENT_DATE, '정상', $3, '결혼기념일', $4)",
+            )
+            .bind(id)
+            .bind(mobile)
+            .bind(anniversary)
+            .bind(consent)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let ids: Vec<String> = customers.iter().map(|(id, _, _)| id.clone()).collect();
+
+        let mut rule_ids = Vec::new();
+        for (purpose, enabled) in [
+            ("promotional", true),
+            ("transactional", false),
+            ("transactional", false),
+        ] {
+            let (rule_id,): (i32,) = sqlx::query_as(
+                "INSERT INTO automation_rules (rule_name, trigger_type, offset_days, template_key, purpose, dedup_days, is_enabled)
+                 VALUES ($1, 'anniversary', 3, 'anniversary', $2, 7, $3) RETURNING rule_id",
+            )
+            .bind(format!("기념일 {}", tag))
+            .bind(purpose)
+            .bind(enabled)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            rule_ids.push(rule_id);
+        }
+        let ours = |targets: Vec<crate::commands::automation::AutomationTarget>| {
+            let mut t: Vec<_> = targets
+                .into_iter()
+                .filter(|t| ids.contains(&t.customer_id))
+                .collect();
+            t.sort_by(|a, b| a.customer_id.cmp(&b.customer_id));
+            t
+        };
+
+        // Dry run: the customer without consent is skipped and nothing is recorded
+        let preview = ours(plan_automation(&pool, today).await.unwrap());
+        assert_eq!(preview.len(), 2);
+        assert_eq!(preview[0].skip_reason, None);
+        assert!(preview[0].content.contains("기념일테스트님"));
+        assert!(preview[0].content.contains("결혼기념일"));
+        assert_eq!(preview[1].skip_reason.as_deref(), Some("수신 동의 없음"));
+        let dry = run_automation(&pool, "tester", true).await.unwrap();
+        assert_eq!(dry.run_id, None);
+        let (logged,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM automation_sends WHERE customer_id = ANY($1)")
+                .bind(&ids)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(logged, 0);
+
+        // A transactional rule reaches both, once per anniversary
+        sqlx::query(
+            "UPDATE automation_rules SET is_enabled = (rule_id = $1) WHERE rule_id = ANY($2)",
+        )
+        .bind(rule_ids[1])
+        .bind(&rule_ids)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let channels = MessageChannels {
+            kakao: None,
+            sms: Gateway::Mock(MockProvider::new()),
+            sender: String::new(),
+            sms_costs: MessageCosts::default(),
+        };
+        // Overlapping runs text each customer once
+        let (run, other) = tokio::join!(
+            run_automation_with(&pool, &channels, "tester"),
+            run_automation_with(&pool, &channels, "tester")
+        );
+        let (run, other) = (run.unwrap(), other.unwrap());
+        assert!(run.run_id.is_some());
+        let sent: Vec<_> = ours(run.targets)
+            .into_iter()
+            .chain(ours(other.targets))
+            .filter(|t| t.skip_reason.is_none())
+            .collect();
+        assert_eq!(sent.len(), 2);
+        let (sms,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM sms_logs WHERE mobile_number = ANY($1)")
+                .bind(
+                    customers
+                        .iter()
+                        .map(|(_, m, _)| m.clone())
+                        .collect::<Vec<_>>(),
+                )
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(sms, 2);
+        assert!(ours(plan_automation(&pool, today).await.unwrap()).is_empty());
+
+        // Another rule for the same day falls within the de-duplication period
+        sqlx::query(
+            "UPDATE automation_rules SET is_enabled = (rule_id = $1) WHERE rule_id = ANY($2)",
+        )
+        .bind(rule_ids[2])
+        .bind(&rule_ids)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let again = ours(plan_automation(&pool, today).await.unwrap());
+        assert_eq!(again.len(), 2);
+        assert!(again
+            .iter()
+            .all(|t| t.skip_reason.as_deref() == Some("최근 발송")));
+
+        sqlx::query("DELETE FROM automation_runs WHERE run_id IN (SELECT run_id FROM automation_sends WHERE customer_id = ANY($1))")
+            .bind(&ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM automation_rules WHERE rule_id = ANY($1)")
+            .bind(&rule_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM sms_logs WHERE customer_id = ANY($1)")
+            .bind(&ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM customers WHERE customer_id = ANY($1)")
+            .bind(&ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_product_recommendations_for_customer_and_basket() {
+        use crate::commands::recommendation::recommend_products;
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let (a, b, c, d) = (
+            format!("추천A{}", tag),
+            format!("추천B{}", tag),
+            format!("추천C{}", tag),
+            format!("추천D{}", tag),
+        );
+        let target = format!("PR{}T", tag);
+        // Orders as (customer, days ago, products)
+        let mut orders: Vec<(String, i32, Vec<&String>)> = vec![
+            (format!("PR{}1", tag), 10, vec![&a, &b]),
+            (format!("PR{}2", tag), 20, vec![&a, &b]),
+            (format!("PR{}3", tag), 30, vec![&a, &c]),
+            (format!("PR{}4", tag), 40, vec![&a, &c]),
+            (format!("PR{}5", tag), 50, vec![&b]),
+            (target.clone(), 60, vec![&a]),
+        ];
+        // Unrelated orders, so that lift stays meaningful on a near-empty database
+        orders.extend((0..4).map(|i| (format!("PR{}5", tag), 70 + i, vec![&d])));
+        let mut customer_ids = Vec::new();
+        for (n, (customer, days_ago, products)) in orders.iter().enumerate() {
+            if !customer_ids.contains(customer) {
+                sqlx::query(
+                    "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+                     VALUES ($1, '추천테스트', '010-0000-0000', CURRENT_DATE, '정상')",
+                )
+                .bind(customer)
+                .execute(&pool)
+                .await
+                .unwrap();
+                customer_ids.push(customer.clone());
+            }
+            for (i, product) in products.iter().enumerate() {
+                sqlx::query(
+                    "INSERT INTO sales (sales_id, customer_id, status, product_name, quantity, unit_price, total_amount, order_date)
+                     VALUES ($1, $2, '배송완료', $3, 1, 10000, 10000, CURRENT_DATE - $4)",
+                )
+                .bind(format!("PS{}{}{}", tag, n, i))
+                .bind(customer)
+                .bind(*product)
+                .bind(*days_ago)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        }
+
+        // Both pairs have the same confidence; C is rarer, so its lift is higher
+        let for_customer = recommend_products(&pool, Some(&target), &[], 5)
+            .await
+            .unwrap();
+        let names: Vec<&str> = for_customer
+            .iter()
+            .map(|r| r.product_name.as_str())
+            .collect();
+        assert_eq!(names, [c.as_str(), b.as_str()]);
+        assert!(for_customer.iter().all(|r| r.because_of == a));
+        assert!((for_customer[0].confidence - 0.4).abs() < 1e-9);
+        assert!(for_customer[0].lift > for_customer[1].lift);
+
+        // Products of the order being entered count as owned
+        let for_basket = recommend_products(&pool, None, std::slice::from_ref(&b), 5)
+            .await
+            .unwrap();
+        assert_eq!(for_basket.len(), 1);
+        assert_eq!(for_basket[0].product_name, a);
+        assert!((for_basket[0].confidence - 2.0 / 3.0).abs() < 1e-9);
+        assert!(
+            recommend_products(&pool, Some(&target), &[b.clone(), c.clone()], 5)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        sqlx::query("DELETE FROM sales WHERE customer_id = ANY($1)")
+            .bind(&customer_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM customers WHERE customer_id = ANY($1)")
+            .bind(&customer_ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sms_delivery_logs_status_and_cost() {
+        use crate::commands::config::MessageCosts;
+        use crate::commands::crm::customer_recipients;
+        use crate::commands::messaging::{
+            deliver, Gateway, MessageRequest, MockProvider, INVALID_NUMBER,
+        };
+        use rand::Rng;
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let customer_id = format!("SMS{}", tag);
+        let mobile = format!(
+            "010-{:04}-{:04}",
+            rand::rng().random_range(0..10000),
+            rand::rng().random_range(0..10000)
+        );
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+             VALUES ($1, '문자수신', $2, CURRENT_DATE, '정상')",
+        )
+        .bind(&customer_id)
+        .bind(&mobile)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let gateway = Gateway::Mock(MockProvider::new());
+        let costs = MessageCosts::default();
+        let recipients = vec![mobile.clone(), "010-12".to_string()];
+        let request = MessageRequest {
+            recipients: recipients.clone(),
+            text: "짧은 안내".to_string(),
+            ..Default::default()
+        };
+        // One recipient per gateway call
+        let one_by_one = Gateway::Mock(MockProvider::new().with_max_recipients(1));
+        let sms = deliver(&pool, &one_by_one, request, &costs).await.unwrap();
+        assert_eq!(sms.message_type, "SMS");
+        assert_eq!((sms.sent, sms.failed, sms.cost), (1, 1, 9));
+
+        // The gateway's message id for accepted messages, the reason for failed ones
+        let rows: Vec<(String, Option<String>, String, i32, String)> = sqlx::query_as(
+            "SELECT recipient_name, customer_id, status, cost, COALESCE(provider_message_id, error_message)
+             FROM sms_logs WHERE log_id = $1 ORDER BY cost DESC",
+        )
+        .bind(&sms.message_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, "문자수신");
+        assert_eq!(rows[0].1.as_deref(), Some(customer_id.as_str()));
+        assert_eq!((rows[0].2.as_str(), rows[0].3), ("성공", 9));
+        assert!(rows[0].4.starts_with("MOCK-"));
+        assert_eq!(
+            (rows[1].0.as_str(), rows[1].2.as_str(), rows[1].3),
+            ("고객", "실패", 0)
+        );
+        // Not a mobile number: logged as invalid without reaching the gateway
+        assert_eq!(rows[1].4, INVALID_NUMBER);
+
+        // Customer IDs and membership groups resolve to mobile numbers
+        let by_id = customer_recipients(&pool, std::slice::from_ref(&customer_id), &[])
+            .await
+            .unwrap();
+        assert_eq!(by_id, vec![mobile.clone()]);
+        let normal = customer_recipients(&pool, &[], &["normal".to_string()])
+            .await
+            .unwrap();
+        assert!(normal.contains(&mobile));
+        let vip = customer_recipients(&pool, &[], &["vip".to_string()])
+            .await
+            .unwrap();
+        assert!(!vip.contains(&mobile));
+        assert!(customer_recipients(&pool, &[], &["various".to_string()])
+            .await
+            .is_err());
+
+        let long_text = "장문 안내 ".repeat(20);
+        let request = MessageRequest {
+            recipients: recipients[..1].to_vec(),
+            text: long_text,
+            is_ad: true,
+            template_code: Some("notice".to_string()),
+            ..Default::default()
+        };
+        let lms = deliver(&pool, &gateway, request, &costs).await.unwrap();
+        assert_eq!(lms.message_type, "LMS");
+        let (provider, message_type, cost, is_ad, template_code): (
+            String,
+            String,
+            i32,
+            bool,
+            Option<String>,
+        ) = sqlx::query_as(
+            "SELECT provider, message_type, cost, is_ad, template_code FROM sms_logs WHERE log_id = $1",
+        )
+        .bind(&lms.message_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            (provider.as_str(), message_type.as_str(), cost, is_ad),
+            ("mock", "LMS", 30, true)
+        );
+        assert_eq!(template_code.as_deref(), Some("notice"));
+
+        sqlx::query("DELETE FROM sms_logs WHERE log_id = ANY($1)")
+            .bind(vec![sms.message_id, lms.message_id])
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM customers WHERE customer_id = $1")
+            .bind(&customer_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_solapi_and_nhn_cloud_mock_send() {
+        use crate::commands::messaging::{
+            MessageImage, MessageKind, MessageProvider, NhnCloudProvider, OutgoingMessage,
+            SolapiProvider,
+        };
+        use axum::{http::HeaderMap, routing::post, Json, Router};
+        use serde_json::{json, Value};
+
+        let upload = |Json(body): Json<Value>| async move {
+            assert_eq!(body["type"], "MMS");
+            assert!(!body["file"].as_str().unwrap().is_empty());
+            Json(json!({ "fileId": "IMG01" }))
+        };
+        let solapi_send = |headers: HeaderMap, Json(body): Json<Value>| async move {
+            let auth = headers["authorization"].to_str().unwrap().to_string();
+            let field = |name: &str| {
+                auth.split(", ")
+                    .find_map(|p| {
+                        p.split_once(&format!("{}=", name))
+                            .map(|(_, v)| v.to_string())
+                    })
+                    .unwrap()
+            };
+            let expected = crate::commands::messaging::solapi::authorization_header(
+                "solapi-key",
+                "solapi-secret",
+                &field("date"),
+                &field("salt"),
+            )
+            .unwrap();
+            assert_eq!(auth, expected);
+            let messages = body["messages"].as_array().unwrap();
+            assert_eq!(messages[0]["type"], "MMS");
+            assert_eq!(messages[0]["imageId"], "IMG01");
+            assert_eq!(messages[0]["from"], "0310000000");
+            Json(json!({
+                "messageList": [{ "to": messages[0]["to"], "messageId": "M4V-1" }],
+                "failedMessageList": [{ "to": messages[1]["to"], "statusMessage": "수신거부" }],
+            }))
+        };
+        let nhn_send = |request_id: &'static str| {
+            move |headers: HeaderMap, Json(body): Json<Value>| async move {
+                assert_eq!(headers["x-secret-key"], "nhn-secret");
+                assert_eq!(body["title"], "공지");
+                let list: Vec<Value> = body["recipientList"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(i, r)| {
+                    json!({ "recipientNo": r["recipientNo"], "resultCode": 0, "recipientSeq": i + 1 })
+                })
+                .collect();
+                Json(json!({
+                    "header": { "isSuccessful": true, "resultCode": 0, "resultMessage": "SUCCESS" },
+                    "body": { "data": { "requestId": request_id, "sendResultList": list } }
+                }))
+            }
+        };
+        let router = Router::new()
+            .route("/storage/v1/files", post(upload))
+            .route("/messages/v4/send-many/detail", post(solapi_send))
+            .route(
+                "/sms/v3.0/appKeys/nhn-app/sender/mms",
+                post(nhn_send("REQ9")),
+            )
+            .route(
+                "/sms/v3.0/appKeys/nhn-app/sender/ad-mms",
+                post(nhn_send("AD9")),
+            );
+        let base_url = spawn_mock_server(router).await;
+
+        let mut message = OutgoingMessage {
+            kind: MessageKind::Mms,
+            sender: "0310000000".to_string(),
+            recipients: vec!["01011112222".to_string(), "01033334444".to_string()],
+            subject: Some("공지".to_string()),
+            text: "사진 안내".to_string(),
+            image: Some(MessageImage {
+                file_name: "a.jpg".to_string(),
+                data: vec![0xFF, 0xD8, 0xFF],
+            }),
+            is_ad: false,
+        };
+        let solapi = SolapiProvider::new("solapi-key", "solapi-secret").with_base_url(&base_url);
+        let results = solapi
+            .send(&message)
+            .await
+            .expect("Solapi mock send failed");
+        assert!(results[0].accepted);
+        assert_eq!(results[0].provider_message_id.as_deref(), Some("M4V-1"));
+        assert_eq!(results[1].error.as_deref(), Some("수신거부"));
+
+        message.kind = MessageKind::Lms;
+        message.image = None;
+        let nhn = NhnCloudProvider::new("nhn-app", "nhn-secret").with_base_url(&base_url);
+        let results = nhn.send(&message).await.expect("NHN mock send failed");
+        assert!(results.iter().all(|r| r.accepted));
+        assert_eq!(results[1].provider_message_id.as_deref(), Some("REQ9:2"));
+
+        // Advertising goes to its own endpoint
+        message.is_ad = true;
+        let results = nhn.send(&message).await.expect("NHN mock ad send failed");
+        assert_eq!(results[0].provider_message_id.as_deref(), Some("AD9:1"));
+    }
+
+    #[tokio::test]
+    async fn test_alimtalk_with_sms_fallback() {
+        use crate::commands::config::{KakaoSettings, MessageCosts};
+        use crate::commands::consent::MessagePurpose;
+        use crate::commands::crm::{send_message_with, MessageChannels, MessageOptions};
+        use crate::commands::messaging::kakao::{KakaoGateway, MockKakaoProvider};
+        use crate::commands::messaging::{Gateway, MockProvider};
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let template_key = format!("shipping{}", tag);
+        let template_code = format!("SHIP{}", tag);
+        sqlx::query("INSERT INTO kakao_templates (template_key, template_code) VALUES ($1, $2)")
+            .bind(&template_key)
+            .bind(&template_code)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let suffix: String = uuid::Uuid::new_v4()
+            .as_u128()
+            .to_string()
+            .chars()
+            .take(8)
+            .collect();
+        let (on_kakao, no_kakao) = (
+            format!("010-{}-{}", &suffix[..4], &suffix[4..]),
+            format!("011-{}-{}", &suffix[..4], &suffix[4..]),
+        );
+        let channels = |sms_fallback| MessageChannels {
+            kakao: Some((
+                KakaoGateway::Mock(MockKakaoProvider::new().with_unreachable(&[&no_kakao])),
+                KakaoSettings {
+                    sms_fallback,
+                    ..Default::default()
+                },
+            )),
+            sms: Gateway::Mock(MockProvider::new()),
+            sender: String::new(),
+            sms_costs: MessageCosts::default(),
+        };
+        let options = || MessageOptions {
+            template_code: Some(template_key.clone()),
+            ..Default::default()
+        };
+        let recipients = vec![on_kakao.clone(), no_kakao.clone()];
+
+        // A mapped template goes as AlimTalk even from an SMS screen
+        let result = send_message_with(
+            &pool,
+            &channels(true),
+            "SMS".to_string(),
+            recipients.clone(),
+            "주문하신 상품이 발송되었습니다.".to_string(),
+            MessagePurpose::Transactional,
+            options(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["message_type"], "ALIMTALK");
+        assert_eq!(
+            (result["count"].as_u64(), result["fallback"].as_u64()),
+            (Some(2), Some(1))
+        );
+        assert_eq!(result["cost"].as_i64(), Some(8 + 9));
+        let kakao_id = result["message_id"].as_str().unwrap().to_string();
+
+        // KakaoTalk rows carry the template code, the SMS fallback the KakaoTalk log id
+        let rows: Vec<(String, String, String, Option<String>)> = sqlx::query_as(
+            "SELECT mobile_number, message_type, status, COALESCE(fallback_of, template_code) FROM sms_logs
+             WHERE log_id = $1 OR fallback_of = $1 ORDER BY message_type, mobile_number",
+        )
+        .bind(&kakao_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            (rows[0].0.as_str(), rows[0].1.as_str(), rows[0].2.as_str()),
+            (on_kakao.as_str(), "ALIMTALK", "성공")
+        );
+        assert_eq!(rows[0].3.as_deref(), Some(template_code.as_str()));
+        assert_eq!(
+            (rows[1].0.as_str(), rows[1].2.as_str()),
+            (no_kakao.as_str(), "실패")
+        );
+        assert_eq!(
+            (rows[2].0.as_str(), rows[2].1.as_str(), rows[2].2.as_str()),
+            (no_kakao.as_str(), "SMS", "성공")
+        );
+        assert_eq!(rows[2].3.as_deref(), Some(kakao_id.as_str()));
+
+        // Without fallback the unreachable customer is simply not reached
+        let result = send_message_with(
+            &pool,
+            &channels(false),
+            "SMS".to_string(),
+            recipients.clone(),
+            "주문하신 상품이 발송되었습니다.".to_string(),
+            MessagePurpose::Transactional,
+            options(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            (result["count"].as_u64(), result["failed"].as_u64()),
+            (Some(1), Some(1))
+        );
+
+        // Unmapped templates stay on SMS
+        let result = send_message_with(
+            &pool,
+            &channels(true),
+            "SMS".to_string(),
+            recipients.clone(),
+            "안내".to_string(),
+            MessagePurpose::Transactional,
+            MessageOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["message_type"], "SMS");
+
+        // Asking for KakaoTalk without Kakao settings is refused rather than faked
+        let sms_only = MessageChannels {
+            kakao: None,
+            ..channels(true)
+        };
+        assert!(send_message_with(
+            &pool,
+            &sms_only,
+            "kakao".to_string(),
+            recipients.clone(),
+            "안내".to_string(),
+            MessagePurpose::Transactional,
+            options(),
+        )
+        .await
+        .is_err());
+
+        sqlx::query("DELETE FROM sms_logs WHERE mobile_number = ANY($1)")
+            .bind(&recipients)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM kakao_templates WHERE template_key = $1")
+            .bind(&template_key)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_message_templates_in_database_with_preview() {
+        use crate::commands::config::MessageCosts;
+        use crate::commands::crm::MessageChannels;
+        use crate::commands::message_template::{
+            build_context, load_template, load_templates, preview_template, render, save_templates,
+            send_order_message_with, TemplatePreviewInput,
+        };
+        use crate::commands::messaging::{Gateway, MockProvider};
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..6].to_uppercase();
+        let original = load_templates(&pool).await.unwrap();
+        assert!(original.contains_key("shipping_done"));
+
+        // Unknown variables are rejected and nothing changes
+        let mut bad = original.clone();
+        bad.insert(
+            "shipping_paid".to_string(),
+            vec!["${name}님 ${tracking_number}".to_string()],
+        );
+        let err = save_templates(&pool, &bad, "tester").await.unwrap_err();
+        assert!(err.to_string().contains("${tracking_number}"));
+        assert_eq!(load_templates(&pool).await.unwrap(), original);
+
+        let category = format!("event{}", tag);
+        let mut templates = original.clone();
+        templates.insert(
+            category.clone(),
+            vec![
+                "${name}님 첫번째".to_string(),
+                "${name}님 두번째".to_string(),
+            ],
+        );
+        save_templates(&pool, &templates, "tester").await.unwrap();
+        assert_eq!(load_templates(&pool).await.unwrap()[&category].len(), 2);
+        assert_eq!(
+            load_template(&pool, &category).await.unwrap().as_deref(),
+            Some("${name}님 첫번째")
+        );
+        save_templates(&pool, &original, "tester").await.unwrap();
+
+        // Preview against a real order: every line of that day counts, cancelled ones do not
+        let customer_id = format!("TPL{}", tag);
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+             VALUES ($1, 'Kim', '010-0000-0000', CURRENT_DATE, '정상')",
+        )
+        .bind(&customer_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        for (n, status, product, total, paid) in [
+            (1, "입금완료", "Shiitake", 20000, Some(5000)),
+            (2, "접수", "Oyster", 15000, None),
+            (3, "취소", "Enoki", 9000, None),
+        ] {
+            sqlx::query(
+                "INSERT INTO sales (sales_id, customer_id, status, order_date, product_name, specification,
+                    unit_price, quantity, total_amount, paid_amount, courier_name, tracking_number)
+                 VALUES ($1, $2, $3, CURRENT_DATE, $4, '1kg', $5, 1, $5, $6, 'CJ', '612345678901')",
+            )
+            .bind(format!("{}-{}", customer_id, n))
+            .bind(&customer_id)
+            .bind(status)
+            .bind(product)
+            .bind(total)
+            .bind(paid)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        // Points paid part of the second line
+        sqlx::query("UPDATE sales SET points_used = 2000 WHERE sales_id = $1")
+            .bind(format!("{}-2", customer_id))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let preview = |category: &str, content: &str| TemplatePreviewInput {
+            category: category.to_string(),
+            content: Some(content.to_string()),
+            customer_id: None,
+            sales_id: Some(format!("{}-1", customer_id)),
+        };
+        let receipt = preview_template(
+            &pool,
+            preview("shipping_receipt", "${name}: ${items} / ${amount_due}"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            receipt.text,
+            "Kim: Shiitake 1kg x1, Oyster 1kg x1 / 28,000원"
+        );
+        assert!(receipt.unknown.is_empty());
+        assert_eq!(receipt.message_type, Some("SMS"));
+
+        let done = preview_template(
+            &pool,
+            preview(
+                "shipping_done",
+                "${courier} ${tracking_number} ${amount_due}",
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(done.text.starts_with("CJ 612345678901"));
+        assert_eq!(done.unknown, vec!["amount_due"]);
+
+        // Without a customer or order the samples are shown
+        let sample = preview_template(
+            &pool,
+            TemplatePreviewInput {
+                category: "membership_upgrade".to_string(),
+                content: Some("${name} ${level}".to_string()),
+                customer_id: None,
+                sales_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(sample.text, "홍길동 VIP");
+
+        // The shipping screen sends the template to the number the order ships to
+        let mobile = format!("010{}", &uuid::Uuid::new_v4().as_u128().to_string()[..8]);
+        sqlx::query("UPDATE sales SET shipping_mobile_number = $1 WHERE sales_id = $2")
+            .bind(&mobile)
+            .bind(format!("{}-1", customer_id))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let channels = MessageChannels {
+            kakao: None,
+            sms: Gateway::Mock(MockProvider::new()),
+            sender: String::new(),
+            sms_costs: MessageCosts::default(),
+        };
+        assert!(send_order_message_with(
+            &pool,
+            &channels,
+            "shipping_paid",
+            &format!("{}-1", customer_id)
+        )
+        .await
+        .unwrap());
+        let (content, template_code): (String, Option<String>) =
+            sqlx::query_as("SELECT content, template_code FROM sms_logs WHERE mobile_number = $1")
+                .bind(&mobile)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let expected = render(
+            &load_template(&pool, "shipping_paid")
+                .await
+                .unwrap()
+                .unwrap(),
+            &build_context(
+                &pool,
+                Some(&customer_id),
+                Some(&format!("{}-1", customer_id)),
+            )
+            .await
+            .unwrap(),
+        );
+        assert_eq!(content, expected.text);
+        assert_eq!(template_code.as_deref(), Some("shipping_paid"));
+        sqlx::query("DELETE FROM sms_logs WHERE mobile_number = $1")
+            .bind(&mobile)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM sales WHERE customer_id = $1")
+            .bind(&customer_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM customers WHERE customer_id = $1")
+            .bind(&customer_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_message_outbox_throttle_retry_and_cancel() {
+        use crate::commands::config::MessageCosts;
+        use crate::commands::consent::MessagePurpose;
+        use crate::commands::crm::MessageChannels;
+        use crate::commands::messaging::outbox::{
+            campaign_progress, cancel_campaign, enqueue_campaign, process_outbox_with, NewCampaign,
+            MAX_SEND_ATTEMPTS,
+        };
+        use crate::commands::messaging::{Gateway, MockProvider};
+
+        let pool = setup_test_db().await;
+        let suffix: String = uuid::Uuid::new_v4()
+            .as_u128()
+            .to_string()
+            .chars()
+            .take(7)
+            .collect();
+        let numbers: Vec<String> = (1..=3).map(|n| format!("010{}{}", n, suffix)).collect();
+        let invalid = format!("02{}", suffix);
+        let channels = MessageChannels {
+            kakao: None,
+            sms: Gateway::Mock(MockProvider::new()),
+            sender: String::new(),
+            sms_costs: MessageCosts::default(),
+        };
+
+        let mut recipients = numbers.clone();
+        recipients.push(invalid.clone());
+        // The same number written differently is sent once
+        recipients.push(format!("010-{}-{}", &numbers[0][3..7], &numbers[0][7..]));
+        let campaign = enqueue_campaign(
+            &pool,
+            NewCampaign {
+                mode: "sms".to_string(),
+                purpose: MessagePurpose::Transactional,
+                recipients,
+                content: "배송 안내".to_string(),
+                ..Default::default()
+            },
+            "tester",
+        )
+        .await
+        .unwrap();
+        assert_eq!((campaign.total, campaign.pending), (4, 4));
+        let id = campaign.campaign_id.clone();
+
+        // Two per run
+        process_outbox_with(&pool, &channels, |_| 2).await.unwrap();
+        let progress = campaign_progress(&pool, &id).await.unwrap();
+        assert_eq!((progress.sent, progress.pending), (2, 2));
+        assert_eq!(progress.status, "발송중");
+
+        // The invalid number is rejected for good and not tried again
+        process_outbox_with(&pool, &channels, |_| 2).await.unwrap();
+        let progress = campaign_progress(&pool, &id).await.unwrap();
+        assert_eq!(
+            (progress.sent, progress.failed, progress.pending),
+            (3, 1, 0)
+        );
+        assert_eq!(progress.status, "완료");
+        let attempts: i32 = sqlx::query_scalar(
+            "SELECT attempts FROM message_outbox WHERE campaign_id = $1 AND recipient = $2",
+        )
+        .bind(&id)
+        .bind(&invalid)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(attempts, 1);
+        let logged: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM message_outbox o
+             JOIN sms_logs l ON l.log_id = o.message_id
+                AND regexp_replace(l.mobile_number, '[^0-9]', '', 'g') = regexp_replace(o.recipient, '[^0-9]', '', 'g')
+             WHERE o.campaign_id = $1 AND l.status = '성공'",
+        )
+        .bind(&id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(logged, 3);
+        assert!(cancel_campaign(&pool, &id, "tester").await.is_err());
+
+        // A gateway that can't be reached leaves the recipient waiting for a retry
+        let down = MessageChannels {
+            kakao: None,
+            sms: Gateway::Mock(MockProvider::new().unavailable()),
+            sender: String::new(),
+            sms_costs: MessageCosts::default(),
+        };
+        let outage = enqueue_campaign(
+            &pool,
+            NewCampaign {
+                mode: "sms".to_string(),
+                purpose: MessagePurpose::Transactional,
+                recipients: numbers[..1].to_vec(),
+                content: "점검 안내".to_string(),
+                ..Default::default()
+            },
+            "tester",
+        )
+        .await
+        .unwrap()
+        .campaign_id;
+        process_outbox_with(&pool, &down, |_| 10).await.unwrap();
+        let (attempts, waiting): (i32, bool) = sqlx::query_as(
+            "SELECT attempts, next_attempt_at > CURRENT_TIMESTAMP FROM message_outbox
+             WHERE campaign_id = $1",
+        )
+        .bind(&outage)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(attempts, 1);
+        assert!(waiting);
+
+        // Out of attempts: the recipient fails and the campaign is done
+        sqlx::query(
+            "UPDATE message_outbox SET attempts = $1, next_attempt_at = CURRENT_TIMESTAMP
+             WHERE campaign_id = $2 AND status = '대기'",
+        )
+        .bind(MAX_SEND_ATTEMPTS - 1)
+        .bind(&outage)
+        .execute(&pool)
+        .await
+        .unwrap();
+        process_outbox_with(&pool, &down, |_| 10).await.unwrap();
+        let progress = campaign_progress(&pool, &outage).await.unwrap();
+        assert_eq!((progress.failed, progress.pending), (1, 0));
+        assert_eq!(progress.status, "완료");
+
+        // A scheduled campaign waits for its time and can be cancelled
+        let scheduled = enqueue_campaign(
+            &pool,
+            NewCampaign {
+                mode: "sms".to_string(),
+                purpose: MessagePurpose::Transactional,
+                recipients: numbers.clone(),
+                content: "내일 발송".to_string(),
+                scheduled_at: Some(chrono::Local::now().naive_local() + chrono::Duration::days(1)),
+                ..Default::default()
+            },
+            "tester",
+        )
+        .await
+        .unwrap();
+        process_outbox_with(&pool, &channels, |_| 100)
+            .await
+            .unwrap();
+        let progress = campaign_progress(&pool, &scheduled.campaign_id)
+            .await
+            .unwrap();
+        assert_eq!((progress.status.as_str(), progress.pending), ("대기", 3));
+
+        // A row left claimed by a run that died is failed, not sent again
+        sqlx::query(
+            "UPDATE message_outbox SET status = '발송중', updated_at = CURRENT_TIMESTAMP - INTERVAL '1 hour'
+             WHERE outbox_id = (SELECT MIN(outbox_id) FROM message_outbox WHERE campaign_id = $1)",
+        )
+        .bind(&scheduled.campaign_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        process_outbox_with(&pool, &channels, |_| 100)
+            .await
+            .unwrap();
+        let progress = campaign_progress(&pool, &scheduled.campaign_id)
+            .await
+            .unwrap();
+        assert_eq!((progress.failed, progress.pending), (1, 2));
+
+        let progress = cancel_campaign(&pool, &scheduled.campaign_id, "tester")
+            .await
+            .unwrap();
+        assert_eq!((progress.status.as_str(), progress.cancelled), ("취소", 2));
+
+        sqlx::query("DELETE FROM message_campaigns WHERE campaign_id = ANY($1)")
+            .bind(vec![id, scheduled.campaign_id, outage])
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "DELETE FROM sms_logs WHERE regexp_replace(mobile_number, '[^0-9]', '', 'g') = ANY($1)",
+        )
+        .bind([numbers, vec![invalid]].concat())
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_messaging_webhooks_update_logs_and_file_replies() {
+        use crate::commands::config::{KakaoCosts, KakaoSettings, MessageCosts};
+        use crate::commands::crm::MessageChannels;
+        use crate::commands::messaging::kakao::{
+            deliver_kakao, KakaoGateway, KakaoKind, KakaoMessage, MockKakaoProvider,
+        };
+        use crate::commands::messaging::webhook::{handle_webhook_events, WebhookEvent};
+        use crate::commands::messaging::{deliver, Gateway, MessageRequest, MockProvider};
+
+        let pool = setup_test_db().await;
+        let suffix: String = uuid::Uuid::new_v4()
+            .as_u128()
+            .to_string()
+            .chars()
+            .take(7)
+            .collect();
+        let [kakao_number, sms_number, customer_number, opt_out_number] =
+            [1, 2, 3, 4].map(|n| format!("010{}{}", n, suffix));
+        let channels = MessageChannels {
+            kakao: Some((
+                KakaoGateway::Mock(MockKakaoProvider::new()),
+                KakaoSettings::default(),
+            )),
+            sms: Gateway::Mock(MockProvider::new()),
+            sender: String::new(),
+            sms_costs: MessageCosts::default(),
+        };
+
+        let kakao = deliver_kakao(
+            &pool,
+            &KakaoGateway::Mock(MockKakaoProvider::new()),
+            KakaoMessage {
+                kind: KakaoKind::AlimTalk,
+                sender_key: String::new(),
+                template_code: Some("SHIP_001".to_string()),
+                recipients: vec![kakao_number.clone()],
+                text: "발송되었습니다.".to_string(),
+                is_ad: false,
+            },
+            std::slice::from_ref(&kakao_number),
+            &KakaoCosts::default(),
+        )
+        .await
+        .unwrap();
+        let sms = deliver(
+            &pool,
+            &Gateway::Mock(MockProvider::new()),
+            MessageRequest {
+                recipients: vec![sms_number.clone()],
+                text: "입금 확인".to_string(),
+                ..Default::default()
+            },
+            &MessageCosts::default(),
+        )
+        .await
+        .unwrap();
+        let provider_id = |results: &[crate::commands::messaging::DeliveryResult]| {
+            results[0].provider_message_id.clone().unwrap()
+        };
+
+        let report = |message_id: String, to: &str, delivered: bool| WebhookEvent::Report {
+            message_id,
+            to: Some(to.to_string()),
+            delivered,
+            error: (!delivered).then(|| "카카오톡 미수신".to_string()),
+        };
+        let events = vec![
+            report(provider_id(&kakao.results), &kakao_number, false),
+            report(provider_id(&sms.results), &sms_number, true),
+            WebhookEvent::Inbound {
+                id: Some(format!("MO-{}", suffix)),
+                from: customer_number.clone(),
+                text: "배송 언제 오나요?".to_string(),
+                channel: "sms".to_string(),
+            },
+            // No provider id, and the number written with dashes
+            WebhookEvent::Inbound {
+                id: None,
+                from: format!(
+                    "{}-{}-{}",
+                    &opt_out_number[..3],
+                    &opt_out_number[3..7],
+                    &opt_out_number[7..]
+                ),
+                text: "수신거부".to_string(),
+                channel: "sms".to_string(),
+            },
+        ];
+
+        let customer_id = format!("WH{}", suffix);
+        sqlx::query(
+            "INSERT INTO customers (customer_id, customer_name, mobile_number, join_date, status)
+             VALUES ($1, 'Lee', $2, CURRENT_DATE, '정상')",
+        )
+        .bind(&customer_id)
+        .bind(&customer_number)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let outcome = handle_webhook_events(&pool, Some(&channels), "mock", events.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            (outcome.delivered, outcome.failed, outcome.fallbacks),
+            (1, 1, 1)
+        );
+        assert_eq!(
+            (outcome.replies, outcome.opt_outs, outcome.ignored),
+            (2, 1, 0)
+        );
+
+        // The same callback again changes nothing
+        let outcome = handle_webhook_events(&pool, Some(&channels), "mock", events)
+            .await
+            .unwrap();
+        assert_eq!(
+            (outcome.fallbacks, outcome.replies, outcome.ignored),
+            (0, 0, 4)
+        );
+
+        let (status, error, cost): (String, Option<String>, i32) =
+            sqlx::query_as("SELECT status, error_message, cost FROM sms_logs WHERE log_id = $1")
+                .bind(&kakao.message_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(status, "실패");
+        assert_eq!(error.as_deref(), Some("카카오톡 미수신"));
+        assert_eq!(cost, 0);
+        let (fallback_status, fallback_type): (String, Option<String>) =
+            sqlx::query_as("SELECT status, message_type FROM sms_logs WHERE fallback_of = $1")
+                .bind(&kakao.message_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            (fallback_status.as_str(), fallback_type.as_deref()),
+            ("성공", Some("SMS"))
+        );
+        let delivered: bool =
+            sqlx::query_scalar("SELECT delivered_at IS NOT NULL FROM sms_logs WHERE log_id = $1")
+                .bind(&sms.message_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(delivered);
+
+        let (consult_customer, channel, content): (Option<String>, String, String) =
+            sqlx::query_as(
+                "SELECT c.customer_id, c.channel, c.content FROM inbound_messages i
+                 JOIN consultations c ON c.consult_id = i.consult_id
+                 WHERE i.mobile_number = $1",
+            )
+            .bind(&customer_number)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(consult_customer.as_deref(), Some(customer_id.as_str()));
+        assert_eq!(channel, "문자");
+        assert_eq!(content, "배송 언제 오나요?");
+        let (opted_out, consult_id): (bool, Option<i32>) = sqlx::query_as(
+            "SELECT opted_out, consult_id FROM inbound_messages WHERE mobile_number = $1",
+        )
+        .bind(&opt_out_number)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(opted_out);
+        assert!(consult_id.is_none());
+
+        let numbers = vec![kakao_number, sms_number, customer_number, opt_out_number];
+        sqlx::query(
+            "DELETE FROM consultations WHERE consult_id IN
+                (SELECT consult_id FROM inbound_messages WHERE mobile_number = ANY($1))",
+        )
+        .bind(&numbers)
+        .execute(&pool)
+        .await
+        .unwrap();
+        for sql in [
+            "DELETE FROM inbound_messages WHERE mobile_number = ANY($1)",
+            "DELETE FROM sms_logs WHERE mobile_number = ANY($1)",
+            "DELETE FROM marketing_opt_outs WHERE mobile_digits = ANY($1)",
+        ] {
+            sqlx::query(sql)
+                .bind(&numbers)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("DELETE FROM customers WHERE customer_id = $1")
+            .bind(&customer_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_customer_address_saved_normalized() {
+        use crate::commands::address::resolve_address;
+
+        let pool = setup_test_db().await;
+        let tag = uuid::Uuid::new_v4().to_string()[..8].to_uppercase();
+        sqlx::query(
+            "INSERT INTO postal_addresses (building_mgmt_no, zip_code, sido, sigungu, eupmyeondong,
+                road_name, building_main, building_sub)
+             VALUES ($1, '06200', '서울특별시', '강남구', '역삼동', '정규화시험로', 12, 0)",
+        )
+        .bind(format!("NORM{}", tag))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let matched = resolve_address(&pool, None, "서울 강남구 정규화시험로 12", Some("301호"))
+            .await
+            .unwrap();
+        assert!(matched
+            .address_primary
+            .starts_with("서울특별시 강남구 정규화시험로 12"));
+        assert_eq!(matched.address_detail.as_deref(), Some("301호"));
+        assert_eq!(matched.zip_code.as_deref(), Some("06200"));
+        assert!(!matched.is_remote_area);
+
+        // An entered zip code is kept and an unknown address stays as entered
+        let unknown = resolve_address(&pool, Some("63100".to_string()), " 없는길 99 ", None)
+            .await
+            .unwrap();
+        assert_eq!(unknown.address_primary, "없는길 99");
+        assert_eq!(unknown.zip_code.as_deref(), Some("63100"));
+        assert!(unknown.is_remote_area);
+
+        sqlx::query("DELETE FROM postal_addresses WHERE building_mgmt_no = $1")
+            .bind(format!("NORM{}", tag))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
 }
@@ -248,6 +248,87 @@ async fn run_server() {
             }
         });
 
+        let mall_sync_pool = pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+            loop {
+                interval.tick().await;
+                let _ =
+                    commands::sales::mall_sync::process_mall_shipment_queue(&mall_sync_pool).await;
+            }
+        });
+
+        let mall_stock_pool = pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+            loop {
+                interval.tick().await;
+                let _ = commands::sales::mall_stock::publish_mall_stock(&mall_stock_pool, false)
+                    .await;
+            }
+        });
+
+        let segment_pool = pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let _ = commands::rfm::take_segment_snapshot_if_due(&segment_pool).await;
+            }
+        });
+
+        let membership_pool = pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let _ =
+                    commands::membership::run_membership_evaluation_if_due(&membership_pool).await;
+            }
+        });
+
+        let points_pool = pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+            loop {
+                interval.tick().await;
+                let _ = commands::points::run_points_jobs(&points_pool).await;
+            }
+        });
+
+        let privacy_pool = pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let _ = commands::privacy::run_retention_if_due(&privacy_pool).await;
+            }
+        });
+
+        let automation_pool = pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let _ = commands::automation::run_automation_if_due(&automation_pool).await;
+            }
+        });
+
+        let outbox_pool = pool.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                commands::messaging::outbox::WORKER_INTERVAL_SECS,
+            ));
+            loop {
+                interval.tick().await;
+                if let Err(e) =
+                    commands::messaging::outbox::process_message_outbox(&outbox_pool).await
+                {
+                    tracing::error!("Message outbox run failed: {}", e);
+                }
+            }
+        });
+
         SetupStatus::Configured
     } else {
         tracing::warn!("Failed to connect to database. Starting in Setup Mode.");
@@ -64,6 +64,7 @@ pub async fn auth_middleware(
     if !path.starts_with("/api/")
         || public_routes.contains(&path)
         || path.starts_with("/api/production/media/")
+        || path.starts_with("/api/webhooks/")
     {
         return Ok(next.run(request).await);
     }
@@ -20,6 +20,10 @@ pub fn router() -> Router<AppState> {
             "/api/settings/integrations/sms",
             post(commands::config::save_sms_config_axum),
         )
+        .route(
+            "/api/settings/integrations/kakao",
+            post(commands::config::save_kakao_config_axum),
+        )
         .route(
             "/api/settings/integrations/naver",
             post(commands::config::save_naver_keys_axum),
@@ -39,15 +43,23 @@ pub fn router() -> Router<AppState> {
         // Message Templates
         .route(
             "/api/settings/templates",
-            get(commands::config::get_message_templates_axum),
+            get(commands::message_template::get_message_templates_axum),
         )
         .route(
             "/api/settings/templates/save",
-            post(commands::config::save_message_templates_axum),
+            post(commands::message_template::save_message_templates_axum),
         )
         .route(
             "/api/settings/templates/reset",
-            post(commands::config::reset_message_templates_axum),
+            post(commands::message_template::reset_message_templates_axum),
+        )
+        .route(
+            "/api/settings/templates/variables",
+            get(commands::message_template::get_template_variables_axum),
+        )
+        .route(
+            "/api/settings/templates/preview",
+            post(commands::message_template::preview_template_axum),
         )
         // Mobile Config
         .route(
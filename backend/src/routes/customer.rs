@@ -59,14 +59,139 @@ pub fn router() -> Router<AppState> {
             "/api/customer/address/set-default",
             post(commands::customer::set_default_customer_address_axum),
         )
+        .route(
+            "/api/customer/address/normalize",
+            post(commands::address::normalize_address_axum),
+        )
+        .route(
+            "/api/customer/address/duplicates",
+            get(commands::address::get_duplicate_addresses_axum),
+        )
+        .route(
+            "/api/customer/address/postal-db/import",
+            post(commands::address::import_postal_db_axum),
+        )
+        .route(
+            "/api/customer/address/postal-db/status",
+            get(commands::address::get_postal_db_status_axum),
+        )
+        .route(
+            "/api/customer/address/remote-areas",
+            get(commands::address::get_remote_areas_axum)
+                .post(commands::address::save_remote_areas_axum),
+        )
         .route(
             "/api/customer/sales",
             get(commands::customer::get_sales_by_customer_id_axum),
         )
+        .route(
+            "/api/customer/points",
+            get(commands::points::get_customer_points_axum),
+        )
+        .route(
+            "/api/customer/points/redeem",
+            post(commands::points::redeem_points_axum),
+        )
+        .route(
+            "/api/customer/points/adjust",
+            post(commands::points::adjust_points_axum),
+        )
+        .route(
+            "/api/customer/points/settings",
+            get(commands::points::get_points_config_axum)
+                .post(commands::points::save_points_config_axum),
+        )
+        .route(
+            "/api/customer/consent",
+            get(commands::consent::get_customer_consents_axum)
+                .post(commands::consent::record_consent_axum),
+        )
+        .route(
+            "/api/customer/privacy/settings",
+            get(commands::privacy::get_privacy_settings_axum)
+                .post(commands::privacy::save_privacy_settings_axum),
+        )
+        .route(
+            "/api/customer/privacy/inactive",
+            get(commands::privacy::get_inactive_customers_axum),
+        )
+        .route(
+            "/api/customer/privacy/run",
+            post(commands::privacy::run_retention_axum),
+        )
+        .route(
+            "/api/customer/privacy/anonymize",
+            post(commands::privacy::anonymize_customer_axum),
+        )
+        .route(
+            "/api/customer/privacy/export",
+            get(commands::privacy::export_customer_data_axum),
+        )
+        .route(
+            "/api/customer/referral",
+            get(commands::referral::get_customer_referral_axum)
+                .post(commands::referral::set_referrer_axum),
+        )
+        .route(
+            "/api/customer/referral/check",
+            get(commands::referral::check_referral_code_axum),
+        )
+        .route(
+            "/api/customer/referral/rewards",
+            get(commands::referral::get_referral_rewards_axum),
+        )
+        .route(
+            "/api/customer/referral/rules",
+            get(commands::referral::get_referral_rules_axum)
+                .post(commands::referral::save_referral_rule_axum),
+        )
+        .route(
+            "/api/customer/referral/rules/delete",
+            post(commands::referral::delete_referral_rule_axum),
+        )
+        .route(
+            "/api/customer/referral/report",
+            get(commands::referral::get_referral_report_axum),
+        )
+        .route(
+            "/api/customer/timeline",
+            get(commands::timeline::get_customer_timeline_axum),
+        )
         .route(
             "/api/customer/ai-insight",
             get(commands::customer::get_customer_ai_insight_axum),
         )
+        // Duplicates & merge
+        .route(
+            "/api/customer/duplicates",
+            get(commands::customer_merge::find_duplicate_customers_axum),
+        )
+        .route(
+            "/api/customer/merge",
+            post(commands::customer_merge::merge_customers_axum),
+        )
+        .route(
+            "/api/customer/merge/undo",
+            post(commands::customer_merge::undo_customer_merge_axum),
+        )
+        .route(
+            "/api/customer/merges",
+            get(commands::customer_merge::get_customer_merges_axum),
+        )
+        // Membership rules
+        .route(
+            "/api/customer/membership/rules",
+            get(commands::membership::get_membership_config_axum)
+                .post(commands::membership::save_membership_config_axum),
+        )
+        .route(
+            "/api/customer/membership/preview",
+            get(commands::membership::preview_membership_evaluation_axum),
+        )
+        .route(
+            "/api/customer/membership/evaluate",
+            post(commands::membership::run_membership_evaluation_axum),
+        )
         // Customer Batch Operations
         .route(
             "/api/customer/batch/search",
@@ -105,15 +230,116 @@ pub fn router() -> Router<AppState> {
             "/api/crm/consultations/delete",
             get(commands::consultation::delete_consultation_axum),
         )
+        .route(
+            "/api/crm/consultations/ticket",
+            post(commands::consultation::update_ticket_axum),
+        )
+        .route(
+            "/api/crm/consultations/status",
+            post(commands::consultation::change_ticket_status_axum),
+        )
+        .route(
+            "/api/crm/consultations/history",
+            get(commands::consultation::get_consultation_history_axum),
+        )
+        .route(
+            "/api/crm/consultations/overdue",
+            get(commands::consultation::get_overdue_consultations_axum),
+        )
+        .route(
+            "/api/crm/consultations/sla",
+            get(commands::consultation::get_consultation_sla_axum)
+                .post(commands::consultation::save_consultation_sla_axum),
+        )
+        .route(
+            "/api/crm/automation/rules",
+            get(commands::automation::get_automation_rules_axum)
+                .post(commands::automation::save_automation_rule_axum),
+        )
+        .route(
+            "/api/crm/automation/rules/delete",
+            post(commands::automation::delete_automation_rule_axum),
+        )
+        .route(
+            "/api/crm/automation/preview",
+            get(commands::automation::preview_automation_axum),
+        )
+        .route(
+            "/api/crm/automation/run",
+            post(commands::automation::run_automation_axum),
+        )
+        .route(
+            "/api/crm/automation/runs",
+            get(commands::automation::get_automation_runs_axum),
+        )
+        .route(
+            "/api/crm/automation/sends",
+            get(commands::automation::get_automation_sends_axum),
+        )
         .route(
             "/api/crm/claim-targets",
             get(commands::crm::get_claim_targets_axum),
         )
+        .route("/api/crm/sms/send", post(commands::crm::send_sms_axum))
+        .route("/api/crm/sms/logs", get(commands::crm::get_sms_logs_axum))
         .route(
-            "/api/crm/sms/send",
-            post(commands::crm::send_sms_simulation_axum),
+            "/api/crm/campaigns",
+            get(commands::messaging::outbox::get_campaigns_axum),
+        )
+        .route(
+            "/api/crm/campaigns/progress",
+            get(commands::messaging::outbox::get_campaign_progress_axum),
+        )
+        .route(
+            "/api/crm/campaigns/cancel",
+            post(commands::messaging::outbox::cancel_campaign_axum),
+        )
+        .route(
+            "/api/webhooks/messaging/{provider}",
+            post(commands::messaging::webhook::messaging_webhook_axum),
+        )
+        .route(
+            "/api/crm/kakao/templates",
+            get(commands::messaging::kakao::get_kakao_templates_axum)
+                .post(commands::messaging::kakao::save_kakao_template_axum),
+        )
+        .route(
+            "/api/crm/kakao/templates/delete",
+            post(commands::messaging::kakao::delete_kakao_template_axum),
+        )
+        .route(
+            "/api/crm/opt-outs",
+            get(commands::consent::get_opt_outs_axum),
+        )
+        .route(
+            "/api/crm/opt-out",
+            post(commands::consent::register_opt_out_axum),
+        )
+        .route(
+            "/api/crm/opt-out/inbound",
+            post(commands::consent::inbound_opt_out_axum),
+        )
+        .route(
+            "/api/crm/marketing-settings",
+            get(commands::consent::get_marketing_settings_axum)
+                .post(commands::consent::save_marketing_settings_axum),
+        )
+        .route(
+            "/api/crm/segments",
+            get(commands::segment::get_segments_axum).post(commands::segment::save_segment_axum),
+        )
+        .route(
+            "/api/crm/segments/delete",
+            post(commands::segment::delete_segment_axum),
+        )
+        .route(
+            "/api/crm/segments/preview",
+            post(commands::segment::preview_segment_axum),
+        )
+        .route(
+            "/api/crm/segments/customers",
+            get(commands::segment::get_segment_customers_axum),
         )
-        .route("/api/crm/sms/logs", get(commands::crm::get_sms_logs_axum))
         .route(
             "/api/crm/special-care",
             get(commands::crm::get_special_care_customers_axum),
@@ -122,6 +348,22 @@ pub fn router() -> Router<AppState> {
             "/api/crm/rfm-analysis",
             get(commands::crm::get_rfm_analysis_axum),
         )
+        .route(
+            "/api/crm/rfm/settings",
+            get(commands::rfm::get_rfm_settings_axum).post(commands::rfm::save_rfm_settings_axum),
+        )
+        .route(
+            "/api/crm/rfm/snapshot",
+            post(commands::rfm::take_segment_snapshot_axum),
+        )
+        .route(
+            "/api/crm/rfm/transitions",
+            get(commands::rfm::get_segment_transitions_axum),
+        )
+        .route(
+            "/api/crm/rfm/transitions/customers",
+            get(commands::rfm::get_segment_transition_customers_axum),
+        )
         .route(
             "/api/crm/update-level",
             post(commands::crm::update_customer_level_axum),
@@ -134,6 +376,10 @@ pub fn router() -> Router<AppState> {
             "/api/crm/product-associations",
             get(commands::crm::get_product_associations_axum),
         )
+        .route(
+            "/api/crm/recommendations",
+            get(commands::recommendation::get_recommendations_axum),
+        )
         .route(
             "/api/crm/repurchase",
             get(commands::crm::get_repurchase_candidates_axum),
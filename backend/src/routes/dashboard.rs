@@ -55,4 +55,8 @@ pub fn router() -> Router<AppState> {
             "/api/dashboard/schedule-stats",
             get(commands::dashboard::get_dashboard_schedule_stats),
         )
+        .route(
+            "/api/dashboard/overdue-consultations",
+            get(commands::dashboard::get_overdue_consultations),
+        )
 }
@@ -95,4 +95,21 @@ pub fn router() -> Router<AppState> {
             "/api/finance/membership-sales",
             get(commands::finance::get_membership_sales_analysis_axum),
         )
+        // Mall settlements
+        .route(
+            "/api/finance/settlements",
+            get(commands::finance::settlement::get_settlement_batches_axum),
+        )
+        .route(
+            "/api/finance/settlements/import",
+            post(commands::finance::settlement::import_settlement_axum),
+        )
+        .route(
+            "/api/finance/settlements/delete",
+            post(commands::finance::settlement::delete_settlement_batch_axum),
+        )
+        .route(
+            "/api/finance/settlements/reconciliation",
+            get(commands::finance::settlement::get_settlement_reconciliation_axum),
+        )
 }
@@ -19,4 +19,75 @@ pub fn router() -> Router<AppState> {
             "/api/sales/search-all",
             get(commands::sales::query::search_sales_by_any_axum),
         )
+        .route(
+            "/api/sales/shipments/pick-list/pdf",
+            get(commands::packing::generate_pick_list_pdf_axum),
+        )
+        .route(
+            "/api/sales/shipments/packing-slips/pdf",
+            get(commands::packing::generate_packing_slips_pdf_axum),
+        )
+        .route(
+            "/api/sales/shipments/address-check",
+            get(commands::address::check_shipment_addresses_axum),
+        )
+        .route(
+            "/api/sales/shipping-calendar/settings",
+            get(commands::shipping_calendar::get_shipping_calendar_settings_axum)
+                .post(commands::shipping_calendar::save_shipping_calendar_settings_axum),
+        )
+        .route(
+            "/api/sales/shipping-calendar/holidays",
+            get(commands::shipping_calendar::get_shipping_holidays_axum),
+        )
+        .route(
+            "/api/sales/shipping-calendar/holidays/import",
+            post(commands::shipping_calendar::import_shipping_holidays_axum),
+        )
+        .route(
+            "/api/sales/shipping-calendar/holidays/delete",
+            post(commands::shipping_calendar::delete_shipping_holiday_axum),
+        )
+        .route(
+            "/api/sales/shipping-calendar/plan",
+            get(commands::shipping_calendar::plan_ship_date_axum),
+        )
+        .route(
+            "/api/sales/external/import",
+            post(commands::sales::mall_import::import_mall_orders_axum),
+        )
+        .route(
+            "/api/sales/external/import-logs",
+            get(commands::sales::mall_import::get_mall_import_logs_axum),
+        )
+        .route(
+            "/api/sales/external/mappings",
+            get(commands::sales::mall_import::get_mall_product_mappings_axum)
+                .post(commands::sales::mall_import::save_mall_product_mapping_axum),
+        )
+        .route(
+            "/api/sales/external/mappings/delete",
+            post(commands::sales::mall_import::delete_mall_product_mapping_axum),
+        )
+        .route(
+            "/api/sales/mall-sync",
+            get(commands::sales::mall_sync::get_mall_shipment_sync_axum),
+        )
+        .route(
+            "/api/sales/mall-sync/retry",
+            post(commands::sales::mall_sync::retry_mall_shipment_sync_axum),
+        )
+        .route(
+            "/api/sales/mall-stock/settings",
+            get(commands::sales::mall_stock::get_mall_stock_settings_axum)
+                .post(commands::sales::mall_stock::save_mall_stock_settings_axum),
+        )
+        .route(
+            "/api/sales/mall-stock/status",
+            get(commands::sales::mall_stock::get_mall_stock_status_axum),
+        )
+        .route(
+            "/api/sales/mall-stock/publish",
+            post(commands::sales::mall_stock::publish_mall_stock_axum),
+        )
 }
@@ -46,6 +46,7 @@ import IotSettings from './features/settings/IotSettings';
 import SettingsSessions from './features/settings/SettingsSessions';
 import SettingsSecurity from './features/settings/SettingsSecurity';
 import SmsLogManager from './features/settings/SmsLogManager';
+import KakaoTemplateManager from './features/settings/KakaoTemplateManager';
 import AuditLogManager from './features/settings/AuditLogManager';
 import SalesReception from './features/sales/SalesReception';
 import SalesSpecial from './features/sales/SalesSpecial';
@@ -232,6 +233,7 @@ function AppContent() {
             <Route path="settings/company-info" element={<SettingsCompany />} />
             <Route path="settings/api-keys" element={<SettingsApiKeys />} />
             <Route path="settings/template-mgmt" element={<SettingsTemplate />} />
+            <Route path="settings/kakao-templates" element={<KakaoTemplateManager />} />
             <Route path="settings/iot" element={<IotSettings />} />
             <Route path="settings/mobile-sync" element={<MobileSettings />} />
             <Route path="settings/db-reset" element={<SettingsDbReset />} />
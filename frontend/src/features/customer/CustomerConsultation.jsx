@@ -421,6 +421,7 @@ const CustomerConsultation = () => {
                                         <select value={editData.channel} onChange={e => setEditData({ ...editData, channel: e.target.value })} className="w-full h-10 px-3 rounded-lg border border-slate-200 text-sm font-bold text-slate-700 bg-white">
                                             <option value="전화">전화</option>
                                             <option value="문자">문자</option>
+                                            <option value="카카오톡">카카오톡</option>
                                             <option value="방문">방문</option>
                                             <option value="기타">기타</option>
                                         </select>
@@ -46,18 +46,42 @@ const CustomerSms = () => {
     // Message
     const [msgMode, setMsgMode] = useState('sms'); // 'sms' or 'kakao'
     const [message, setMessage] = useState('');
+    const [templateKey, setTemplateKey] = useState(''); // category the message was started from
     const [byteCount, setByteCount] = useState(0);
     const [msgType, setMsgType] = useState('SMS'); // SMS or LMS
+    const [scheduledAt, setScheduledAt] = useState(''); // 'YYYY-MM-DDTHH:mm', empty = now
     const [companyName, setCompanyName] = useState('Mycelium');
 
     // Stats
     const [estimatedCount, setEstimatedCount] = useState(0);
 
+    // Queued sends (campaigns) handled by the background worker
+    const [campaigns, setCampaigns] = useState([]);
+    const isActiveCampaign = (c) => c.status === '대기' || c.status === '발송중';
+    const activeCampaignIds = campaigns.filter(isActiveCampaign).map(c => c.campaign_id).join(',');
+
     // --- Initialization ---
     useEffect(() => {
         loadCompanyInfo();
+        loadCampaigns();
     }, []);
 
+    // Poll the progress of campaigns still being sent
+    useEffect(() => {
+        if (!activeCampaignIds) return;
+        const timer = setInterval(async () => {
+            try {
+                const updated = await Promise.all(
+                    activeCampaignIds.split(',').map(id => invoke('get_message_campaign_progress', { campaignId: id }))
+                );
+                setCampaigns(prev => prev.map(c => updated.find(u => u.campaign_id === c.campaign_id) || c));
+            } catch (e) {
+                console.error(e);
+            }
+        }, 5000);
+        return () => clearInterval(timer);
+    }, [activeCampaignIds]);
+
     const loadCompanyInfo = async () => {
         try {
             const info = await invoke('get_company_info');
@@ -124,8 +148,30 @@ const CustomerSms = () => {
         setMsgType(total > 90 ? 'LMS' : 'SMS');
     };
 
+    const loadCampaigns = async () => {
+        try {
+            const data = await invoke('get_message_campaigns');
+            setCampaigns(Array.isArray(data) ? data : []);
+        } catch (e) {
+            console.error(e);
+        }
+    };
+
+    const handleCancelCampaign = async (campaign) => {
+        const confirmed = await showConfirm('발송 취소', `예약 번호 ${campaign.campaign_id}의 남은 ${campaign.pending.toLocaleString()}건 발송을 취소하시겠습니까?\n(이미 발송된 메시지는 취소되지 않습니다)`);
+        if (!confirmed) return;
+        try {
+            const progress = await invoke('cancel_message_campaign', { campaignId: campaign.campaign_id });
+            setCampaigns(prev => prev.map(c => c.campaign_id === progress.campaign_id ? progress : c));
+        } catch (e) {
+            console.error(e);
+            showAlert('오류', e.message || '발송 취소에 실패했습니다.');
+        }
+    };
+
     const handleTemplateChange = (e) => {
         const key = e.target.value;
+        setTemplateKey(key);
         if (!key) return;
         let content = SMS_TEMPLATES[key] || '';
         content = content.replace(/\{COMPANY\}/g, companyName);
@@ -144,25 +190,41 @@ const CustomerSms = () => {
             return;
         }
 
+        if (scheduledAt && new Date(scheduledAt) <= new Date()) {
+            showAlert('알림', '예약 시각은 현재 이후로 선택해주세요.');
+            return;
+        }
+
         const modeText = msgMode === 'kakao' ? '카카오 알림톡' : '문자 메시지';
-        const confirmed = await showConfirm('발송 확인', `약 ${estimatedCount.toLocaleString()}명에게 ${modeText}를 발송하시겠습니까?\n(실제 발송은 API 설정에 따릅니다)`);
+        const whenText = scheduledAt ? `${scheduledAt.replace('T', ' ')}에 ` : '';
+        const confirmed = await showConfirm('발송 확인', `약 ${estimatedCount.toLocaleString()}명에게 ${whenText}${modeText}를 발송하시겠습니까?\n(실제 발송은 API 설정에 따릅니다)`);
 
         if (confirmed) {
             try {
-                const selectedGroups = Object.keys(targets).filter(k => targets[k] && k !== 'all');
+                const selectedGroups = Object.keys(targets).filter(k => targets[k] && k !== 'all' && k !== 'recovery');
                 if (targets.all) selectedGroups.push('all');
 
-                // Simulation Invoke
                 const result = await invoke('send_sms_simulation', {
                     mode: msgMode,
-                    recipients: selectedGroups,
+                    purpose: 'promotional',
+                    recipients: targets.recovery ? selectedClaimTargets : [],
+                    groups: selectedGroups,
                     content: message,
-                    templateCode: msgMode === 'kakao' ? 'TEMPLATE_001' : null
+                    // The AlimTalk template mapped to this category, if any, is looked up by the server
+                    templateCode: msgMode === 'kakao' ? (templateKey || null) : null,
+                    scheduledAt: scheduledAt ? `${scheduledAt}:00` : null
                 });
 
-                if (result.success) {
+                if (result.success && result.queued) {
+                    showAlert('발송 예약', `${result.message}\n예약 번호: ${result.campaign_id}\n순서대로 발송되며 진행 상황은 아래 발송 예약 현황에서 확인할 수 있습니다.`, 'success');
+                    setMessage('');
+                    setTemplateKey('');
+                    setScheduledAt('');
+                    loadCampaigns();
+                } else if (result.success) {
                     showAlert('발송 성공', `메시지 아이디: ${result.message_id || 'N/A'}\n성공적으로 접수되었습니다.`, 'success');
                     setMessage('');
+                    setTemplateKey('');
                 } else {
                     showAlert('발송 실패', result.error || '알 수 없는 오류');
                 }
@@ -297,6 +359,54 @@ const CustomerSms = () => {
                         </p>
                     </div>
 
+                    {/* Campaign Card */}
+                    <div className="bg-white rounded-2xl border border-slate-200 shadow-sm p-6">
+                        <div className="flex justify-between items-center mb-4">
+                            <h3 className="text-indigo-600 font-bold flex items-center gap-2">
+                                <span className="material-symbols-rounded">schedule_send</span> 발송 예약 현황
+                            </h3>
+                            <button onClick={loadCampaigns} className="p-1.5 rounded-lg text-slate-400 hover:bg-slate-50 hover:text-indigo-600 transition-colors">
+                                <span className="material-symbols-rounded text-lg">refresh</span>
+                            </button>
+                        </div>
+
+                        <div className="flex flex-col gap-3">
+                            {campaigns.map(c => {
+                                const done = c.sent + c.failed + c.excluded + c.cancelled;
+                                const percent = c.total > 0 ? Math.round((done / c.total) * 100) : 0;
+                                return (
+                                    <div key={c.campaign_id} className="p-3 border border-slate-100 rounded-xl bg-slate-50/50">
+                                        <div className="flex justify-between items-center gap-2">
+                                            <div className="flex flex-col min-w-0">
+                                                <span className="text-xs font-bold text-slate-700 truncate">{c.content}</span>
+                                                <span className="text-[10px] text-slate-400 font-mono">{c.campaign_id} · {c.scheduled_at?.replace('T', ' ').slice(0, 16)}</span>
+                                            </div>
+                                            <div className="flex items-center gap-2 shrink-0">
+                                                <span className={`px-2 py-0.5 rounded text-[10px] font-bold border ${c.status === '발송중' ? 'bg-violet-50 text-violet-700 border-violet-200' : c.status === '대기' ? 'bg-sky-50 text-sky-700 border-sky-200' : c.status === '취소' ? 'bg-red-50 text-red-600 border-red-200' : 'bg-slate-50 text-slate-500 border-slate-200'}`}>
+                                                    {c.status}
+                                                </span>
+                                                {isActiveCampaign(c) && (
+                                                    <button onClick={() => handleCancelCampaign(c)} className="px-2 py-0.5 border border-red-200 text-red-500 text-[10px] font-bold rounded hover:bg-red-50">
+                                                        취소
+                                                    </button>
+                                                )}
+                                            </div>
+                                        </div>
+                                        <div className="mt-2 h-1.5 bg-slate-100 rounded-full overflow-hidden">
+                                            <div className="h-full bg-indigo-500 transition-all" style={{ width: `${percent}%` }}></div>
+                                        </div>
+                                        <div className="mt-1 text-[10px] text-slate-400 font-medium">
+                                            발송 {c.sent.toLocaleString()} · 대기 {c.pending.toLocaleString()} · 실패 {c.failed.toLocaleString()} · 제외 {c.excluded.toLocaleString()} · 취소 {c.cancelled.toLocaleString()} / 총 {c.total.toLocaleString()}건
+                                        </div>
+                                    </div>
+                                );
+                            })}
+                            {campaigns.length === 0 && (
+                                <p className="text-xs text-slate-400 text-center py-4">예약된 발송이 없습니다.</p>
+                            )}
+                        </div>
+                    </div>
+
                 </div>
 
                 {/* Right: Composer */}
@@ -326,7 +436,7 @@ const CustomerSms = () => {
                             {/* Template Select */}
                             <div className="flex items-center gap-2">
                                 <span className="text-xs font-bold text-slate-500">템플릿</span>
-                                <select onChange={handleTemplateChange} className="bg-slate-50 border border-slate-200 text-slate-700 text-xs rounded-lg p-2 outline-none focus:border-violet-500 font-medium">
+                                <select value={templateKey} onChange={handleTemplateChange} aria-label="템플릿" className="bg-slate-50 border border-slate-200 text-slate-700 text-xs rounded-lg p-2 outline-none focus:border-violet-500 font-medium">
                                     <option value="">선택하세요</option>
                                     <option value="greeting">👋 안부 및 감사</option>
                                     <option value="promo">🎁 신상품 및 할인 행사</option>
@@ -343,7 +453,7 @@ const CustomerSms = () => {
                     {msgMode === 'kakao' && (
                         <div className="bg-yellow-50 border border-yellow-200 rounded-xl p-3 flex gap-3 text-xs text-yellow-800 font-medium animate-in fade-in slide-in-from-top-2">
                             <span className="material-symbols-rounded text-lg">info</span>
-                            <p><b>카카오 알림톡 알림:</b> 알림톡은 미리 승인된 템플릿만 전송 가능합니다. 템플릿 버튼을 사용하거나 관리자 승인을 확인하세요.</p>
+                            <p><b>카카오 알림톡 알림:</b> 알림톡은 미리 승인된 템플릿만 전송 가능합니다. 템플릿을 선택하면 '설정 &gt; 알림톡 템플릿 연결'에서 그 분류에 연결한 템플릿으로 발송됩니다.</p>
                         </div>
                     )}
 
@@ -365,15 +475,26 @@ const CustomerSms = () => {
 
                     {/* Actions */}
                     <div className="flex justify-between items-center pt-2">
-                        <button onClick={() => { setMessage(''); updateByteCount(''); }} className="px-4 py-3 rounded-xl border border-slate-200 text-slate-500 hover:bg-slate-50 hover:text-slate-700 font-bold text-sm flex items-center gap-2 transition-colors">
+                        <button onClick={() => { setMessage(''); setTemplateKey(''); updateByteCount(''); }} className="px-4 py-3 rounded-xl border border-slate-200 text-slate-500 hover:bg-slate-50 hover:text-slate-700 font-bold text-sm flex items-center gap-2 transition-colors">
                             <span className="material-symbols-rounded">delete_outline</span> 초기화
                         </button>
+                        <label className="ml-auto mr-3 flex items-center gap-2 text-xs font-bold text-slate-500">
+                            <span className="material-symbols-rounded text-base">schedule</span>
+                            예약 발송
+                            <input
+                                type="datetime-local"
+                                aria-label="예약 발송 시각"
+                                value={scheduledAt}
+                                onChange={(e) => setScheduledAt(e.target.value)}
+                                className="h-10 px-3 rounded-xl border border-slate-200 bg-white text-slate-700 font-bold outline-none focus:border-violet-500"
+                            />
+                        </label>
                         <button
                             onClick={handleSend}
                             className={`px-8 py-3 rounded-xl font-bold text-sm flex items-center gap-2 shadow-lg transition-all transform active:scale-95 ${msgMode === 'kakao' ? 'bg-yellow-400 hover:bg-yellow-500 text-slate-900 shadow-yellow-200' : 'bg-violet-600 hover:bg-violet-700 text-white shadow-violet-200'}`}
                         >
                             <span className="material-symbols-rounded">{msgMode === 'kakao' ? 'chat' : 'send'}</span>
-                            {msgMode === 'kakao' ? '알림톡 발송하기' : '즉시 발송하기'}
+                            {scheduledAt ? '예약 발송하기' : msgMode === 'kakao' ? '알림톡 발송하기' : '즉시 발송하기'}
                         </button>
                     </div>
                 </div>
@@ -1,4 +1,4 @@
-import { render, screen, waitFor } from '@testing-library/react';
+import { render, screen, waitFor, fireEvent } from '@testing-library/react';
 import userEvent from '@testing-library/user-event';
 import { describe, it, expect, vi, beforeEach } from 'vitest';
 import React from 'react';
@@ -72,4 +72,57 @@ describe('CustomerSms Component', () => {
         expect(screen.getByText(/카카오 알림톡 알림/i)).toBeInTheDocument();
         expect(screen.getByText('알림톡 발송하기')).toBeInTheDocument();
     });
+
+    it('sends the chosen template category in Kakao mode', async () => {
+        apiBridge.invoke.mockImplementation((cmd) => {
+            if (cmd === 'get_company_info') return Promise.resolve(mockCompanyInfo);
+            if (cmd === 'send_sms_simulation') return Promise.resolve({ success: true, message_id: 'KAKAO-1' });
+            return Promise.resolve([]);
+        });
+        render(
+            <ModalProvider>
+                <CustomerSms />
+            </ModalProvider>
+        );
+
+        await user.click(screen.getByText('전체 고객'));
+        await user.click(screen.getByText('카톡 알림톡'));
+        await user.selectOptions(screen.getByLabelText('템플릿'), 'anniversary');
+        await user.click(screen.getByText('알림톡 발송하기'));
+        await user.click(await screen.findByRole('button', { name: /^확인$/ }));
+
+        await waitFor(() => {
+            expect(apiBridge.invoke).toHaveBeenCalledWith('send_sms_simulation', expect.objectContaining({
+                mode: 'kakao',
+                templateCode: 'anniversary'
+            }));
+        });
+    });
+
+    it('schedules the send for the chosen time', async () => {
+        apiBridge.invoke.mockImplementation((cmd) => {
+            if (cmd === 'get_company_info') return Promise.resolve(mockCompanyInfo);
+            if (cmd === 'send_sms_simulation') return Promise.resolve({ success: true, queued: true, campaign_id: 'CMP-1', message: '예약했습니다.' });
+            return Promise.resolve([]);
+        });
+        render(
+            <ModalProvider>
+                <CustomerSms />
+            </ModalProvider>
+        );
+
+        await user.click(screen.getByText('전체 고객'));
+        await user.type(screen.getByPlaceholderText(/발송할 내용을 작성하거나/i), '내일 오픈합니다');
+        fireEvent.change(screen.getByLabelText('예약 발송 시각'), { target: { value: '2099-01-02T09:30' } });
+        await user.click(screen.getByText('예약 발송하기'));
+        await user.click(await screen.findByRole('button', { name: /^확인$/ }));
+
+        await waitFor(() => {
+            expect(apiBridge.invoke).toHaveBeenCalledWith('send_sms_simulation', expect.objectContaining({
+                groups: ['all'],
+                purpose: 'promotional',
+                scheduledAt: '2099-01-02T09:30:00'
+            }));
+        });
+    });
 });
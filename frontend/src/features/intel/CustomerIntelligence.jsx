@@ -960,6 +960,7 @@ const CustomerSummaryModal = ({ customerId, onClose }) => {
 
 const SmsSendModal = ({ customers, mode: initialMode, onClose, showAlert }) => {
     const [mode, setMode] = useState(initialMode); // 'sms' | 'kakao'
+    const [purpose, setPurpose] = useState('promotional'); // 'promotional' (광고성) | 'transactional' (정보성)
     const [message, setMessage] = useState('');
     const [isSending, setIsSending] = useState(false);
     const [showTemplates, setShowTemplates] = useState(false);
@@ -996,10 +997,10 @@ const SmsSendModal = ({ customers, mode: initialMode, onClose, showAlert }) => {
 
         setIsSending(true);
         try {
-            const selectedGroups = isBatch ? ['various'] : []; // Just a placeholder for group logic if needed
             const results = await invoke('send_sms_simulation', {
                 mode: mode,
-                recipients: isBatch ? customers.map(c => c.customer_id) : [firstCustomer.customer_id],
+                purpose,
+                customerIds: isBatch ? customers.map(c => c.customer_id) : [firstCustomer.customer_id],
                 content: message,
                 templateCode: null
             });
@@ -1072,6 +1073,18 @@ const SmsSendModal = ({ customers, mode: initialMode, onClose, showAlert }) => {
                                 <span className="material-symbols-rounded text-lg">chat</span> 카카오 알림톡
                             </button>
                         </div>
+                        {/* Advertising gets the consent, night-time and (광고) rules */}
+                        <div className="mt-3 flex items-center gap-4 text-xs font-bold text-slate-500">
+                            <span className="text-slate-400">메시지 성격</span>
+                            <label className="flex items-center gap-1.5 cursor-pointer">
+                                <input type="radio" name="sms-purpose" checked={purpose === 'promotional'} onChange={() => setPurpose('promotional')} className="text-indigo-600 focus:ring-indigo-500" />
+                                광고성 (할인·행사 안내)
+                            </label>
+                            <label className="flex items-center gap-1.5 cursor-pointer">
+                                <input type="radio" name="sms-purpose" checked={purpose === 'transactional'} onChange={() => setPurpose('transactional')} className="text-indigo-600 focus:ring-indigo-500" />
+                                정보성 (주문·배송·예약 안내)
+                            </label>
+                        </div>
                     </div>
 
                     <div className="p-6 flex-1 overflow-y-auto flex flex-col">
@@ -9,7 +9,7 @@ const SalesOnlineSync = () => {
     const { showAlert, showConfirm } = useModal();
     // State
     const [file, setFile] = useState(null);
-    const [mallType, setMallType] = useState('sabangnet');
+    const [mallType, setMallType] = useState('naver');
     const [parsedOrders, setParsedOrders] = useState([]);
     const [productList, setProductList] = useState([]);
     const [mappings, setMappings] = useState({});
@@ -29,11 +29,81 @@ const SalesOnlineSync = () => {
     const [aiGuidance, setAiGuidance] = useState(null);
     const [isAnalyzing, setIsAnalyzing] = useState(false);
 
+    // API import: result of the last run, run history and the mall's product mapping rules
+    const [importResult, setImportResult] = useState(null);
+    const [importLogs, setImportLogs] = useState([]);
+    const [mallMappings, setMallMappings] = useState([]);
+    const [mappingForm, setMappingForm] = useState({ code: '', name: '', productId: '' });
+    const isApiMall = !['custom', 'generic'].includes(mallType);
+
     useEffect(() => {
         loadBaseData();
         loadLocalMappings();
     }, []);
 
+    useEffect(() => {
+        if (!isApiMall) {
+            setImportLogs([]);
+            setMallMappings([]);
+            return;
+        }
+        loadImportLogs();
+        loadMallMappings();
+    }, [mallType]);
+
+    const loadImportLogs = async () => {
+        try {
+            const logs = await invoke('get_mall_import_logs', { mallName: mallType, limit: 10 });
+            setImportLogs(Array.isArray(logs) ? logs : []);
+        } catch (e) {
+            console.error(e);
+        }
+    };
+
+    const loadMallMappings = async () => {
+        try {
+            const list = await invoke('get_mall_product_mappings', { mallName: mallType });
+            setMallMappings(Array.isArray(list) ? list : []);
+        } catch (e) {
+            console.error(e);
+        }
+    };
+
+    const handleSaveMallMapping = async () => {
+        if (!mappingForm.code.trim() || !mappingForm.productId) {
+            await showAlert("알림", "쇼핑몰 상품코드와 내부 상품을 선택해주세요.");
+            return;
+        }
+        try {
+            await invoke('save_mall_product_mapping', {
+                mallName: mallType,
+                mallProductCode: mappingForm.code.trim(),
+                mallProductName: mappingForm.name.trim() || null,
+                productId: Number(mappingForm.productId)
+            });
+            setMappingForm({ code: '', name: '', productId: '' });
+            loadMallMappings();
+        } catch (e) {
+            await showAlert("오류", "매핑 저장 실패: " + e);
+        }
+    };
+
+    const handleDeleteMallMapping = async (mappingId) => {
+        try {
+            await invoke('delete_mall_product_mapping', { mappingId });
+            loadMallMappings();
+        } catch (e) {
+            await showAlert("오류", "매핑 삭제 실패: " + e);
+        }
+    };
+
+    // Unmapped products are reported as "code (name)", or just the code when it is the name
+    const openMappingFor = (label) => {
+        const m = label.match(/^(.*) \((.*)\)$/);
+        setMappingForm({ code: m ? m[1] : label, name: m ? m[2] : label, productId: '' });
+        setIsMappingModalOpen(true);
+    };
+
     const loadBaseData = async () => {
         try {
             const list = await invoke('get_product_list');
@@ -407,41 +477,21 @@ const SalesOnlineSync = () => {
     const [isApiLoading, setIsApiLoading] = useState(false);
 
     const handleApiSync = async () => {
-        if (mallType === 'generic') {
-            await showAlert("알림", "일반 형식은 실시간 연동을 지원하지 않습니다. 엑셀을 이용해주세요.");
+        if (!isApiMall) {
+            await showAlert("알림", "엑셀 양식은 실시간 연동을 지원하지 않습니다. 엑셀 업로드를 이용해주세요.");
             return;
         }
         setIsApiLoading(true);
         try {
-            const orders = await invoke('fetch_external_orders', { mallType });
-
-            if (!orders || orders.length === 0) {
-                await showAlert("결과", "가져올 새로운 주문 데이터가 없습니다.");
-                return;
-            }
-
-            // identify customers
-            for (const order of orders) {
-                try {
-                    if (!order.mobile) { order.isNewCustomer = true; continue; }
-                    const dups = await searchCustomerByMobile(order.mobile);
-                    if (dups && dups.length > 0) {
-                        order.isNewCustomer = false;
-                        order.existingCustomerId = dups[0].customer_id;
-                        order.existingCustomerName = dups[0].customer_name;
-                    } else {
-                        order.isNewCustomer = true;
-                    }
-                } catch (e) { console.warn(e); order.isNewCustomer = true; }
-                order.internalProductId = matchProduct(order.mallProductName, order.unitPrice);
-            }
-
-            setParsedOrders(orders);
-            setStep('review');
+            // The server matches lines it already has by order and line id, so running it again is safe
+            const result = await invoke('import_mall_orders', { mallType });
+            setImportResult(result);
         } catch (e) {
+            setImportResult(null);
             await showAlert("연동 오류", e.toString());
         } finally {
             setIsApiLoading(false);
+            loadImportLogs();
         }
     };
 
@@ -541,10 +591,11 @@ const SalesOnlineSync = () => {
                             <label htmlFor="mall-type-select" className="block text-xs font-black text-slate-400 uppercase mb-2 ml-1">쇼핑몰 선택</label>
                             <select id="mall-type-select" value={mallType} onChange={e => setMallType(e.target.value)}
                                 className="w-full h-12 px-4 rounded-xl bg-white border border-slate-200 font-bold text-slate-700 focus:ring-4 focus:ring-teal-500/20 focus:border-teal-500 transition-all outline-none">
-                                <option value="sabangnet">사방넷 (Sabangnet API)</option>
-                                <option value="playauto">플레이오토 (PlayAuto API)</option>
                                 <option value="naver">네이버 스마트스토어 (Commerce API)</option>
                                 <option value="coupang">쿠팡 (윙 API)</option>
+                                {/* Order import from the aggregators is not implemented on the server yet */}
+                                <option value="sabangnet" disabled>사방넷 (준비 중)</option>
+                                <option value="playauto" disabled>플레이오토 (준비 중)</option>
                                 <option value="custom">자유 양식 엑셀 (컬럼 직접 지정)</option>
                                 <option value="generic">기본 (이름,연락처,주소,상품명)</option>
                             </select>
@@ -554,7 +605,7 @@ const SalesOnlineSync = () => {
                             <div className="flex flex-col gap-2">
                                 <button
                                     onClick={handleApiSync}
-                                    disabled={isApiLoading || mallType === 'generic'}
+                                    disabled={isApiLoading || !isApiMall}
                                     className="h-24 rounded-2xl bg-teal-50 border-2 border-teal-100 hover:border-teal-400 hover:bg-teal-100/50 transition-all flex flex-col items-center justify-center gap-2 group disabled:opacity-50 disabled:grayscale"
                                 >
                                     {isApiLoading ? (
@@ -577,6 +628,76 @@ const SalesOnlineSync = () => {
                             </div>
                         </div>
 
+                        {importResult && (
+                            <div className="mb-4 bg-teal-50 p-4 rounded-2xl border border-teal-100 text-left animate-in fade-in slide-in-from-top-2">
+                                <div className="flex items-center justify-between mb-3">
+                                    <span className="text-[11px] font-black text-teal-700">주문 연동 결과</span>
+                                    <button onClick={() => setImportResult(null)} className="text-teal-400 hover:text-teal-600">
+                                        <span className="material-symbols-rounded text-sm">close</span>
+                                    </button>
+                                </div>
+                                <div className="grid grid-cols-5 gap-2 text-center">
+                                    {[
+                                        ['가져옴', importResult.fetched_count],
+                                        ['신규', importResult.new_count],
+                                        ['갱신', importResult.updated_count],
+                                        ['건너뜀', importResult.skipped_count],
+                                        ['신규 고객', importResult.new_customer_count]
+                                    ].map(([label, count]) => (
+                                        <div key={label} className="bg-white rounded-xl py-2 border border-teal-100">
+                                            <div className="text-lg font-black text-slate-700">{(count || 0).toLocaleString()}</div>
+                                            <div className="text-[10px] font-bold text-slate-400">{label}</div>
+                                        </div>
+                                    ))}
+                                </div>
+                                {importResult.unmapped_products?.length > 0 && (
+                                    <div className="mt-3">
+                                        <div className="text-[10px] font-black text-amber-600 mb-1">매핑 규칙이 없어 건너뛴 상품</div>
+                                        <div className="flex flex-wrap gap-1">
+                                            {importResult.unmapped_products.map(label => (
+                                                <button key={label} onClick={() => openMappingFor(label)}
+                                                    className="px-2 py-1 rounded-lg bg-white border border-amber-200 text-[10px] font-bold text-amber-700 hover:bg-amber-50">
+                                                    {label}
+                                                </button>
+                                            ))}
+                                        </div>
+                                    </div>
+                                )}
+                            </div>
+                        )}
+
+                        {isApiMall && importLogs.length > 0 && (
+                            <div className="mb-4 text-left">
+                                <div className="text-[10px] font-black text-slate-400 uppercase mb-1 ml-1">최근 연동 기록</div>
+                                <div className="max-h-40 overflow-auto stylish-scrollbar rounded-xl border border-slate-100">
+                                    <table className="w-full text-[11px]">
+                                        <thead className="bg-slate-50 sticky top-0">
+                                            <tr className="text-slate-400">
+                                                <th className="px-3 py-2 text-left font-black">실행 시각</th>
+                                                <th className="px-2 py-2 font-black">상태</th>
+                                                <th className="px-2 py-2 font-black">가져옴</th>
+                                                <th className="px-2 py-2 font-black">신규</th>
+                                                <th className="px-2 py-2 font-black">갱신</th>
+                                                <th className="px-2 py-2 font-black">건너뜀</th>
+                                            </tr>
+                                        </thead>
+                                        <tbody className="divide-y divide-slate-100">
+                                            {importLogs.map(log => (
+                                                <tr key={log.log_id} title={log.error_message || ''}>
+                                                    <td className="px-3 py-1.5 text-slate-500">{String(log.started_at).replace('T', ' ').slice(0, 16)}</td>
+                                                    <td className={`px-2 py-1.5 text-center font-bold ${log.status === '실패' ? 'text-red-500' : 'text-slate-600'}`}>{log.status}</td>
+                                                    <td className="px-2 py-1.5 text-center">{log.fetched_count}</td>
+                                                    <td className="px-2 py-1.5 text-center">{log.new_count}</td>
+                                                    <td className="px-2 py-1.5 text-center">{log.updated_count}</td>
+                                                    <td className="px-2 py-1.5 text-center">{log.skipped_count}</td>
+                                                </tr>
+                                            ))}
+                                        </tbody>
+                                    </table>
+                                </div>
+                            </div>
+                        )}
+
                         {file && (
                             <div className="mb-4 bg-emerald-50 p-3 rounded-xl flex items-center justify-between border border-emerald-100 animate-in fade-in slide-in-from-top-2">
                                 <div className="flex items-center gap-2">
@@ -804,6 +925,46 @@ const SalesOnlineSync = () => {
                             <button onClick={() => setIsMappingModalOpen(false)} className="text-slate-400 hover:text-white"><span className="material-symbols-rounded">close</span></button>
                         </div>
                         <div className="flex-1 overflow-auto p-0">
+                            {isApiMall && (
+                                <div className="border-b border-slate-200">
+                                    <div className="px-4 pt-4 pb-2 text-[11px] font-black text-slate-500">API 연동 상품 매핑 · {mallType}</div>
+                                    <div className="px-4 pb-3 flex gap-2">
+                                        <input value={mappingForm.code} onChange={e => setMappingForm({ ...mappingForm, code: e.target.value })}
+                                            placeholder="쇼핑몰 상품코드" aria-label="쇼핑몰 상품코드"
+                                            className="w-32 h-9 px-3 rounded-lg bg-slate-100 border-none font-bold text-xs focus:ring-2 focus:ring-teal-500" />
+                                        <input value={mappingForm.name} onChange={e => setMappingForm({ ...mappingForm, name: e.target.value })}
+                                            placeholder="쇼핑몰 상품명" aria-label="쇼핑몰 상품명"
+                                            className="flex-1 h-9 px-3 rounded-lg bg-slate-100 border-none font-bold text-xs focus:ring-2 focus:ring-teal-500" />
+                                        <select value={mappingForm.productId} onChange={e => setMappingForm({ ...mappingForm, productId: e.target.value })}
+                                            aria-label="내부 상품"
+                                            className="w-40 h-9 px-2 rounded-lg bg-slate-100 border-none font-bold text-xs focus:ring-2 focus:ring-teal-500">
+                                            <option value="">내부 상품 선택</option>
+                                            {productList.map(p => (
+                                                <option key={p.product_id} value={p.product_id}>{p.product_name} ({p.specification})</option>
+                                            ))}
+                                        </select>
+                                        <button onClick={handleSaveMallMapping}
+                                            className="h-9 px-4 rounded-lg bg-teal-600 text-white font-bold text-xs hover:bg-teal-500">저장</button>
+                                    </div>
+                                    <table className="w-full text-xs">
+                                        <tbody className="divide-y divide-slate-100">
+                                            {mallMappings.map(m => (
+                                                <tr key={m.mapping_id} className="hover:bg-slate-50">
+                                                    <td className="px-4 py-2 text-slate-600 font-medium">{m.mall_product_code}{m.mall_product_name ? ` · ${m.mall_product_name}` : ''}</td>
+                                                    <td className="px-4 py-2 text-blue-600 font-bold">{m.product_name ? `${m.product_name} (${m.specification || ''})` : `ID: ${m.product_id} (미확인)`}</td>
+                                                    <td className="px-4 py-2 w-16 text-center">
+                                                        <button onClick={() => handleDeleteMallMapping(m.mapping_id)} aria-label="매핑 삭제"
+                                                            className="w-8 h-8 rounded-lg hover:bg-red-50 text-slate-300 hover:text-red-500 transition-colors flex items-center justify-center mx-auto">
+                                                            <span className="material-symbols-rounded text-base">delete</span>
+                                                        </button>
+                                                    </td>
+                                                </tr>
+                                            ))}
+                                        </tbody>
+                                    </table>
+                                    <div className="px-4 pt-4 pb-2 text-[11px] font-black text-slate-500">엑셀 업로드 상품 매칭</div>
+                                </div>
+                            )}
                             <table className="w-full text-xs">
                                 <thead className="bg-slate-50 sticky top-0 border-b border-slate-200">
                                     <tr>
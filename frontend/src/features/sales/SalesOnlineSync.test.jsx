@@ -15,6 +15,14 @@ vi.mock('../../utils/apiBridge', () => ({
 describe('SalesOnlineSync Component', () => {
     let user;
 
+    // Uploads a file in the basic format (이름,연락처,우편번호,주소,상품명,수량,단가) and parses it
+    const uploadCsv = async (row) => {
+        await user.selectOptions(screen.getByLabelText(/쇼핑몰 선택/i), 'generic');
+        const csv = `구매자,연락처,우편번호,주소,상품명,수량,단가\n${row}`;
+        await user.upload(document.getElementById('file-upload'), new File([csv], 'orders.csv', { type: 'text/csv' }));
+        await user.click(screen.getByText(/엑셀 분석 시작/i));
+    };
+
     beforeEach(() => {
         user = userEvent.setup();
         vi.clearAllMocks();
@@ -41,24 +49,26 @@ describe('SalesOnlineSync Component', () => {
         expect(screen.getByText(/쇼핑몰 선택/i)).toBeInTheDocument();
     });
 
-    it('handles API real-time sync', async () => {
-        const mockOrders = [
-            {
-                orderId: 'ORD-123',
-                customerName: '홍길동',
-                mobile: '010-1111-2222',
-                mallProductName: '느타리버섯',
-                qty: 2,
-                unitPrice: 10000,
-                receiverName: '홍길동',
-                address: '서울시 강남구'
-            }
-        ];
+    it('defaults to Naver and offers only malls the server can import from', async () => {
+        render(
+            <ModalProvider>
+                <SalesOnlineSync />
+            </ModalProvider>
+        );
+
+        expect(screen.getByLabelText(/쇼핑몰 선택/i)).toHaveValue('naver');
+        expect(screen.getByRole('option', { name: /사방넷/ })).toBeDisabled();
+        expect(screen.getByRole('option', { name: /플레이오토/ })).toBeDisabled();
+    });
 
+    it('imports API orders through the server and shows the counts', async () => {
         apiBridge.invoke.mockImplementation((command, args) => {
             if (command === 'get_product_list') return Promise.resolve([{ product_id: 1, product_name: '느타리버섯', unit_price: 10000, item_type: 'product' }]);
-            if (command === 'fetch_external_orders') return Promise.resolve(mockOrders);
-            if (command === 'search_customers') return Promise.resolve([]);
+            if (command === 'import_mall_orders') return Promise.resolve({
+                log_id: 7, mall_name: 'naver', status: '완료',
+                fetched_count: 5, new_count: 3, updated_count: 1, skipped_count: 1, new_customer_count: 2,
+                unmapped_products: ['NV-9 (신규 버섯)']
+            });
             return Promise.resolve([]);
         });
 
@@ -68,37 +78,38 @@ describe('SalesOnlineSync Component', () => {
             </ModalProvider>
         );
 
-        // Click API Sync button
-        const apiBtn = screen.getByText(/API 실시간 연동/i);
-        await user.click(apiBtn);
+        await user.selectOptions(screen.getByLabelText(/쇼핑몰 선택/i), 'naver');
+        await user.click(screen.getByText(/API 실시간 연동/i));
 
-        // Verify transition to review step
-        expect(await screen.findByText(/분석된 주문 리스트/i)).toBeInTheDocument();
-        expect(screen.getByText('홍길동')).toBeInTheDocument();
-        // Product should be matched automatically
-        const matchedBadge = screen.getByText('MATCHED');
-        expect(matchedBadge).toBeInTheDocument();
+        expect(await screen.findByText(/주문 연동 결과/i)).toBeInTheDocument();
+        expect(apiBridge.invoke).toHaveBeenCalledWith('import_mall_orders', { mallType: 'naver' });
+        // Orders are never created one by one from the screen
+        expect(apiBridge.invoke).not.toHaveBeenCalledWith('fetch_external_orders', expect.anything());
+        expect(apiBridge.invoke).not.toHaveBeenCalledWith('create_sale', expect.anything());
+        expect(screen.getByText('신규')).toBeInTheDocument();
+        expect(screen.getByText('갱신')).toBeInTheDocument();
+        expect(screen.getByText('건너뜀')).toBeInTheDocument();
+
+        // An unmapped product opens the mapping form prefilled
+        await user.click(screen.getByText('NV-9 (신규 버섯)'));
+        expect(await screen.findByLabelText('쇼핑몰 상품코드')).toHaveValue('NV-9');
+        await user.selectOptions(screen.getByLabelText('내부 상품'), '1');
+        await user.click(screen.getByRole('button', { name: '저장' }));
+        await waitFor(() => {
+            expect(apiBridge.invoke).toHaveBeenCalledWith('save_mall_product_mapping', {
+                mallName: 'naver',
+                mallProductCode: 'NV-9',
+                mallProductName: '신규 버섯',
+                productId: 1
+            });
+        });
     });
 
     it('handles manual product matching and sync execution', async () => {
-        const mockOrders = [
-            {
-                orderId: 'ORD-999',
-                customerName: '김철수',
-                mobile: '010-9999-8888',
-                mallProductName: '신규 버섯', // Not in list
-                qty: 1,
-                unitPrice: 5000,
-                receiverName: '김철수',
-                address: '부산시 해운대구'
-            }
-        ];
-
         apiBridge.invoke.mockImplementation((command, args) => {
             if (command === 'get_product_list') return Promise.resolve([
                 { product_id: 1, product_name: '느타리버섯', specification: '1kg', unit_price: 10000, item_type: 'product' }
             ]);
-            if (command === 'fetch_external_orders') return Promise.resolve(mockOrders);
             if (command === 'search_customers') return Promise.resolve([]);
             if (command === 'create_customer') return Promise.resolve({ customerId: 'C123' });
             if (command === 'create_sale') return Promise.resolve({ success: true });
@@ -111,7 +122,7 @@ describe('SalesOnlineSync Component', () => {
             </ModalProvider>
         );
 
-        await user.click(screen.getByText(/API 실시간 연동/i));
+        await uploadCsv('김철수,010-9999-8888,48000,부산시 해운대구,신규 버섯,1,5000');
 
         // 1. Check Unmatched status
         expect(await screen.findByText('CHECK')).toBeInTheDocument();
@@ -143,19 +154,8 @@ describe('SalesOnlineSync Component', () => {
     });
 
     it('handles Quick Register for new product', async () => {
-        const mockOrders = [
-            {
-                orderId: 'ORD-777',
-                customerName: '박지성',
-                mallProductName: '산삼버섯',
-                unitPrice: 50000,
-                qty: 1
-            }
-        ];
-
         apiBridge.invoke.mockImplementation((command, args) => {
             if (command === 'get_product_list') return Promise.resolve([]);
-            if (command === 'fetch_external_orders') return Promise.resolve(mockOrders);
             if (command === 'create_product') return Promise.resolve({ productId: 100 });
             return Promise.resolve([]);
         });
@@ -166,7 +166,7 @@ describe('SalesOnlineSync Component', () => {
             </ModalProvider>
         );
 
-        await user.click(screen.getByText(/API 실시간 연동/i));
+        await uploadCsv('박지성,,,,산삼버섯,1,50000');
 
         // Select "새 상품으로 등록하기"
         const select = await screen.findByRole('combobox');
@@ -7,6 +7,7 @@ import { callBridge } from '../../utils/apiBridge';
 import ReceptionHeader from './components/reception/ReceptionHeader';
 import CustomerInfoBar from './components/reception/CustomerInfoBar';
 import SalesInputPanel from './components/reception/SalesInputPanel';
+import RecommendationBar from './components/reception/RecommendationBar';
 import SalesRowsTable from './components/reception/SalesRowsTable';
 import ReceptionFooter from './components/reception/ReceptionFooter';
 import CustomerSelectionModal from './components/reception/CustomerSelectionModal';
@@ -43,7 +44,8 @@ const SalesReception = () => {
         isDraftRestored,
         tempDraft,
         handleRestoreDraft,
-        handleDiscardDraft
+        handleDiscardDraft,
+        pointBalance, pointsUsed, setPointsUsed
     } = useSalesReception(showAlert, showConfirm);
 
     // Refs
@@ -166,6 +168,10 @@ const SalesReception = () => {
                     handleAddressSearch={handleAddressSearch} handleAddRow={handleAddRow}
                     editingTempId={editingTempId}
                 />
+                <RecommendationBar
+                    customer={customer} salesRows={salesRows}
+                    products={products} handleInputChange={handleInputChange}
+                />
                 <SalesRowsTable
                     salesRows={salesRows} editingTempId={editingTempId}
                     handleEditRow={handleEditRow} handleDeleteRow={handleDeleteRow}
@@ -174,6 +180,7 @@ const SalesReception = () => {
                     summary={summary} handleReset={handleReset}
                     handlePrintStatement={handlePrintStatement} handleSaveAll={handleSaveAll}
                     isProcessing={isProcessing} customer={customer} salesRows={salesRows}
+                    pointBalance={pointBalance} pointsUsed={pointsUsed} setPointsUsed={setPointsUsed}
                 />
             </div>
 
@@ -93,6 +93,8 @@ const SalesShipping = () => {
     const [shipments, setShipments] = useState([]);
     const [selectedIds, setSelectedIds] = useState(new Set());
     const [showGuide, setShowGuide] = useState(false);
+    // Sends the 배송 message templates (접수/입금 확인/발송 완료) as orders move on
+    const [notifyCustomer, setNotifyCustomer] = useState(true);
 
     // UI Interaction State
     const [isLoading, setIsLoading] = useState(false);
@@ -142,6 +144,7 @@ const SalesShipping = () => {
     }, [loadData]);
 
     // --- Computed Data ---
+    const today = new Date().toISOString().split('T')[0];
     const filteredData = useMemo(() => {
         if (statusFilter === '전체') return shipments;
         return shipments.filter(s => s.current_status === statusFilter);
@@ -174,6 +177,20 @@ const SalesShipping = () => {
         }
     };
 
+    // One message per order: lines of the same customer and day (and parcel) share it
+    const firstLinesOfOrders = (items, parcelKey = () => '') => {
+        if (!notifyCustomer) return new Set();
+        const seen = new Set();
+        return new Set(items.filter(i => {
+            const key = [i.customer_name, i.shipping_mobile_number || i.customer_mobile_number, i.order_date, parcelKey(i)].join('|');
+            if (seen.has(key)) return false;
+            seen.add(key);
+            return true;
+        }).map(i => i.sales_id));
+    };
+
+    const notifiedText = (count) => count > 0 ? ` (안내 문자 ${count}건 발송)` : '';
+
     const handleAction = async (actionType) => {
         const targets = shipments.filter(s => selectedIds.has(s.sales_id));
         if (targets.length === 0) { showAlert('알림', '선택된 항목이 없습니다.'); return; }
@@ -181,10 +198,13 @@ const SalesShipping = () => {
         if (actionType === 'set_pending') {
             if (await showConfirm('입금 대기 전환', `선택한 ${targets.length}건을 '입금대기' 상태로 변경하시겠습니까?`)) {
                 try {
+                    const notifyIds = firstLinesOfOrders(targets);
+                    let notified = 0;
                     for (const item of targets) {
-                        await callBridge('update_sale_status', { salesId: String(item.sales_id), status: '입금대기' });
+                        const res = await callBridge('update_sale_status', { salesId: String(item.sales_id), status: '입금대기', notify: notifyIds.has(item.sales_id) });
+                        if (res?.notified) notified++;
                     }
-                    showAlert('성공', '처리되었습니다.');
+                    showAlert('성공', `처리되었습니다.${notifiedText(notified)}`);
                     loadData();
                     setSelectedIds(new Set());
                 } catch (e) { showAlert('오류', `처리 중 오류: ${e}`); }
@@ -192,10 +212,13 @@ const SalesShipping = () => {
         } else if (actionType === 'confirm_payment') {
             if (await showConfirm('입금 확인', `선택한 ${targets.length}건을 '입금완료' 처리하시겠습니까?`)) {
                 try {
+                    const notifyIds = firstLinesOfOrders(targets);
+                    let notified = 0;
                     for (const item of targets) {
-                        await callBridge('update_sale_status', { salesId: String(item.sales_id), status: '입금완료' });
+                        const res = await callBridge('update_sale_status', { salesId: String(item.sales_id), status: '입금완료', notify: notifyIds.has(item.sales_id) });
+                        if (res?.notified) notified++;
                     }
-                    showAlert('성공', '처리되었습니다.');
+                    showAlert('성공', `처리되었습니다.${notifiedText(notified)}`);
                     loadData();
                     setSelectedIds(new Set());
                 } catch (e) { showAlert('오류', `처리 중 오류: ${e}`); }
@@ -253,19 +276,30 @@ const SalesShipping = () => {
 
     const submitShipping = async () => {
         try {
+            const notifyIds = firstLinesOfOrders(shippingItems, i => shippingForm.trackingMap[i.sales_id] || '');
+            let notified = 0;
+            const warnings = [];
             for (const item of shippingItems) {
                 const tracking = shippingForm.trackingMap[item.sales_id];
                 // carrier가 있으면 택배로 간주
-                await callBridge('complete_shipment', {
+                const res = await callBridge('complete_shipment', {
                     salesId: String(item.sales_id),
                     carrier: shippingForm.carrier,
                     trackingNumber: tracking || null,
                     shippingDate: shippingForm.date,
-                    memo: item.memo // 기존 메모 유지
+                    memo: item.memo, // 기존 메모 유지
+                    notify: notifyIds.has(item.sales_id)
                 });
+                if (res?.notified) notified++;
+                // Arrival falls on a day couriers do not deliver (weekend, holiday, remote area)
+                if (res?.warning) warnings.push(`- ${item.customer_name}: ${res.warning}`);
             }
             setShowShippingModal(false);
-            showAlert('성공', '배송 처리가 완료되었습니다.');
+            if (warnings.length > 0) {
+                showAlert('배송 일정 확인', `배송 처리가 완료되었습니다.${notifiedText(notified)}\n\n${warnings.join('\n')}`);
+            } else {
+                showAlert('성공', `배송 처리가 완료되었습니다.${notifiedText(notified)}`);
+            }
             loadData();
             setSelectedIds(new Set());
         } catch (e) {
@@ -517,6 +551,12 @@ const SalesShipping = () => {
 
                             return (
                                 <div className="flex gap-2 animate-in fade-in slide-in-from-right-4 duration-300">
+                                    {(canSetPending || canConfirmPayment || canProcessShipping) && (
+                                        <label className="h-8 px-3 rounded-lg bg-white border border-slate-200 text-slate-600 font-bold text-xs flex items-center gap-1.5 cursor-pointer">
+                                            <input type="checkbox" checked={notifyCustomer} onChange={(e) => setNotifyCustomer(e.target.checked)} className="accent-indigo-600" />
+                                            안내 문자
+                                        </label>
+                                    )}
                                     {canSetPending && (
                                         <button onClick={() => handleAction('set_pending')} className="h-8 px-3 rounded-lg bg-amber-50 text-amber-600 hover:bg-amber-100 font-bold text-xs flex items-center gap-1">
                                             <span className="material-symbols-rounded text-base">hourglass_top</span> 입금대기
@@ -595,6 +635,15 @@ const SalesShipping = () => {
                                                     <span className="text-slate-600 truncate max-w-[200px]" title={row.shipping_address_primary}>{row.shipping_address_primary}</span>
                                                     <span className="font-bold text-emerald-600 shrink-0">{row.shipping_name !== row.customer_name ? `(${row.shipping_name})` : ''}</span>
                                                 </div>
+                                                {!row.tracking_number && row.safe_ship_date && (
+                                                    <div
+                                                        className={`mt-1 inline-flex items-center gap-1 text-[10px] font-bold ${row.safe_ship_date > today ? 'text-amber-600' : 'text-slate-400'}`}
+                                                        title={row.transit_days ? `배송 소요 ${row.transit_days}일 기준` : undefined}
+                                                    >
+                                                        <span className="material-symbols-rounded text-[13px]">event_available</span>
+                                                        발송 권장일 {row.safe_ship_date}
+                                                    </div>
+                                                )}
                                             </td>
                                             <td className="px-4 py-3 border-b border-slate-50 text-right font-black text-slate-700">{formatCurrency(row.total_amount)}</td>
                                             <td className="px-4 py-3 border-b border-slate-50 text-center">
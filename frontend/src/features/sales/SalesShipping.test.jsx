@@ -122,11 +122,34 @@ describe('SalesShipping Component', () => {
         await waitFor(() => {
             expect(apiBridge.callBridge).toHaveBeenCalledWith('update_sale_status', expect.objectContaining({
                 salesId: 'S1',
-                status: '입금완료'
+                status: '입금완료',
+                notify: true
             }));
         });
     });
 
+    it('skips the customer message when 안내 문자 is unchecked', async () => {
+        render(
+            <ModalProvider>
+                <SalesShipping />
+            </ModalProvider>
+        );
+
+        const rows = await screen.findAllByText('홍길동');
+        await user.click(rows.find(el => el.closest('tr')));
+        await user.click(await screen.findByLabelText('안내 문자'));
+        await user.click(await screen.findByRole('button', { name: /입금대기/i }));
+        await user.click(await screen.findByRole('button', { name: /^확인$/ }));
+
+        await waitFor(() => {
+            expect(apiBridge.callBridge).toHaveBeenCalledWith('update_sale_status', {
+                salesId: 'S1',
+                status: '입금대기',
+                notify: false
+            });
+        });
+    });
+
     it('handles shipping process (Tracking Number)', async () => {
         render(
             <ModalProvider>
@@ -150,11 +173,39 @@ describe('SalesShipping Component', () => {
         await waitFor(() => {
             expect(apiBridge.callBridge).toHaveBeenCalledWith('complete_shipment', expect.objectContaining({
                 salesId: 'S2',
-                trackingNumber: '12345678'
+                trackingNumber: '12345678',
+                notify: true
             }));
         });
     });
 
+    it('shows the suggested ship date and the arrival warning after shipping', async () => {
+        apiBridge.callBridge.mockImplementation((command, args) => {
+            if (command === 'get_shipments_by_status') {
+                if (args.status === '입금완료') return Promise.resolve([{ ...mockShipments[1], safe_ship_date: '2099-01-04', transit_days: 2 }]);
+                return Promise.resolve([]);
+            }
+            if (command === 'complete_shipment') return Promise.resolve({ success: true, warning: '도착 예정일(1월 3일 토)에 택배 배송이 없습니다.', notified: false });
+            return Promise.resolve([]);
+        });
+        render(
+            <ModalProvider>
+                <SalesShipping />
+            </ModalProvider>
+        );
+
+        expect(await screen.findByText(/발송 권장일 2099-01-04/)).toBeInTheDocument();
+
+        const rows = await screen.findAllByText('김철수');
+        await user.click(rows.find(el => el.closest('tr')));
+        await user.click(await screen.findByRole('button', { name: /배송처리/i }));
+        await user.type(await screen.findByPlaceholderText(/운송장번호/i), '12345678');
+        await user.click(screen.getByRole('button', { name: /배송 처리 완료/i }));
+
+        expect(await screen.findByText('배송 일정 확인')).toBeInTheDocument();
+        expect(screen.getByText(/김철수: 도착 예정일\(1월 3일 토\)에 택배 배송이 없습니다/)).toBeInTheDocument();
+    });
+
     it('triggers CSV download', async () => {
         const mockCreateObjectURL = vi.fn().mockReturnValue('blob:mock-url');
         global.URL.createObjectURL = mockCreateObjectURL;
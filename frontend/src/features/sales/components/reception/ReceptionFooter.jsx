@@ -1,7 +1,8 @@
 import React from 'react';
 import { formatCurrency } from '../../../../utils/common';
 
-const ReceptionFooter = ({ summary, handleReset, handlePrintStatement, handleSaveAll, isProcessing, customer, salesRows }) => {
+const ReceptionFooter = ({ summary, handleReset, handlePrintStatement, handleSaveAll, isProcessing, customer, salesRows, pointBalance = 0, pointsUsed = 0, setPointsUsed }) => {
+    const points = Number(pointsUsed) || 0;
     return (
         <div className="bg-slate-900 p-4 px-8 flex justify-between items-center text-white border-t border-slate-800 rounded-b-[1.5rem]">
             <div className="flex gap-10 items-center">
@@ -20,9 +21,24 @@ const ReceptionFooter = ({ summary, handleReset, handlePrintStatement, handleSav
                     <span className="text-[9px] font-black text-indigo-400 uppercase tracking-widest italic mb-0.5">최종 합계</span>
                     <div className="flex items-baseline gap-1">
                         <span className="text-[9px] font-black text-indigo-400/50 uppercase">KRW</span>
-                        <span className="text-xl font-black text-indigo-400 leading-none">{formatCurrency(summary.amount)}원</span>
+                        <span className="text-xl font-black text-indigo-400 leading-none">{formatCurrency(summary.amount - points)}원</span>
                     </div>
                 </div>
+                {customer && pointBalance > 0 && setPointsUsed && (
+                    <div className="flex flex-col pl-10 border-l border-white/10 ml-2">
+                        <label htmlFor="reception-points-used" className="text-[9px] font-black text-emerald-400 uppercase tracking-widest mb-0.5">
+                            포인트 사용 (보유 {formatCurrency(pointBalance)}P)
+                        </label>
+                        <div className="flex items-center gap-2">
+                            <input id="reception-points-used" type="number" min="0" max={pointBalance} step="100"
+                                value={pointsUsed || ''}
+                                onChange={(e) => setPointsUsed(Math.max(0, Math.min(pointBalance, Number(e.target.value) || 0)))}
+                                className="w-28 h-8 px-2 rounded-lg bg-white/5 border border-white/10 text-right text-sm font-black text-emerald-300 outline-none focus:border-emerald-400" />
+                            <button type="button" onClick={() => setPointsUsed(Math.min(pointBalance, summary.amount))}
+                                className="px-2 h-8 rounded-lg bg-white/5 hover:bg-white/10 text-[10px] font-black text-slate-300">전액</button>
+                        </div>
+                    </div>
+                )}
             </div>
             <div className="flex gap-3 h-12">
                 <button onClick={handleReset} className="px-6 rounded-xl bg-slate-800 hover:bg-slate-700 text-slate-400 font-black transition-all text-xs">초기화</button>
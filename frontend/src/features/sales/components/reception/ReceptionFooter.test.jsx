@@ -43,4 +43,20 @@ describe('ReceptionFooter Component', () => {
         fireEvent.click(screen.getByText('일괄 저장하기'));
         expect(mockProps.handleSaveAll).toHaveBeenCalled();
     });
+
+    it('lets the customer pay with points and shows the remaining total', () => {
+        const setPointsUsed = vi.fn();
+        const { rerender } = render(<ReceptionFooter {...mockProps} pointBalance={3000} pointsUsed={0} setPointsUsed={setPointsUsed} />);
+        fireEvent.change(screen.getByLabelText(/포인트 사용/), { target: { value: '5000' } });
+        // Never more than the balance
+        expect(setPointsUsed).toHaveBeenCalledWith(3000);
+
+        rerender(<ReceptionFooter {...mockProps} pointBalance={3000} pointsUsed={2000} setPointsUsed={setPointsUsed} />);
+        expect(screen.getByText('98,000원')).toBeInTheDocument();
+    });
+
+    it('hides the point input without a balance', () => {
+        render(<ReceptionFooter {...mockProps} pointBalance={0} setPointsUsed={vi.fn()} />);
+        expect(screen.queryByLabelText(/포인트 사용/)).not.toBeInTheDocument();
+    });
 });
@@ -0,0 +1,55 @@
+import React, { useEffect, useState } from 'react';
+import { callBridge } from '../../../../utils/apiBridge';
+
+/**
+ * Products often bought with the customer's history and the items already in the order.
+ * Clicking one selects it in the input panel.
+ */
+const RecommendationBar = ({ customer, salesRows, products, handleInputChange }) => {
+    const [recommendations, setRecommendations] = useState([]);
+    const orderedNames = [...new Set(salesRows.map(r => r.product).filter(Boolean))].sort().join(',');
+    const customerId = customer?.customer_id;
+
+    useEffect(() => {
+        if (!customerId) {
+            setRecommendations([]);
+            return;
+        }
+        let cancelled = false;
+        callBridge('get_product_recommendations', { customerId, products: orderedNames, limit: 5 })
+            .then(data => { if (!cancelled) setRecommendations(Array.isArray(data) ? data : []); })
+            .catch(e => {
+                console.error(e);
+                if (!cancelled) setRecommendations([]);
+            });
+        return () => { cancelled = true; };
+    }, [customerId, orderedNames]);
+
+    if (recommendations.length === 0) return null;
+
+    const pick = (name) => handleInputChange({ target: { name: 'product', value: name, type: 'select-one' } });
+
+    return (
+        <div className="bg-white rounded-[1.5rem] px-3 py-2 border border-slate-100 shadow-sm flex items-center gap-3 overflow-x-auto custom-scrollbar">
+            <span className="text-[10.5px] font-bold text-slate-600 flex items-center gap-1 shrink-0">
+                <span className="material-symbols-rounded text-base text-indigo-500">recommend</span> 함께 많이 산 상품
+            </span>
+            {recommendations.map(r => {
+                const available = products.some(p => p.product_name === r.product_name);
+                return (
+                    <button
+                        key={r.product_name}
+                        onClick={() => pick(r.product_name)}
+                        disabled={!available}
+                        title={`${r.because_of} 구매 고객의 ${Math.round(r.confidence * 100)}%가 함께 구매`}
+                        className="px-3 py-1 rounded-full border border-indigo-100 bg-indigo-50 text-indigo-700 text-xs font-bold whitespace-nowrap hover:bg-indigo-100 disabled:opacity-40 disabled:cursor-not-allowed transition-colors">
+                        {r.product_name}
+                        <span className="ml-1 text-[10px] font-medium text-indigo-400">{Math.round(r.confidence * 100)}%</span>
+                    </button>
+                );
+            })}
+        </div>
+    );
+};
+
+export default RecommendationBar;
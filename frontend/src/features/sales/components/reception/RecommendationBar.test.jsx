@@ -0,0 +1,50 @@
+import { render, screen, fireEvent, waitFor } from '@testing-library/react';
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import React from 'react';
+import RecommendationBar from './RecommendationBar';
+import * as apiBridge from '../../../../utils/apiBridge';
+
+vi.mock('../../../../utils/apiBridge', () => ({
+    callBridge: vi.fn(),
+}));
+
+describe('RecommendationBar Component', () => {
+    const mockProducts = [{ product_id: 1, product_name: '느타리버섯' }, { product_id: 2, product_name: '표고버섯' }];
+    const mockHandleInputChange = vi.fn();
+
+    beforeEach(() => {
+        vi.clearAllMocks();
+        apiBridge.callBridge.mockResolvedValue([
+            { product_name: '느타리버섯', because_of: '표고버섯', confidence: 0.6, lift: 1.5, pair_count: 3 }
+        ]);
+    });
+
+    it('asks for suggestions with the customer and the ordered products', async () => {
+        render(
+            <RecommendationBar
+                customer={{ customer_id: 'C1' }}
+                salesRows={[{ product: '표고버섯' }]}
+                products={mockProducts}
+                handleInputChange={mockHandleInputChange}
+            />
+        );
+        expect(await screen.findByText('느타리버섯')).toBeInTheDocument();
+        expect(apiBridge.callBridge).toHaveBeenCalledWith('get_product_recommendations', { customerId: 'C1', products: '표고버섯', limit: 5 });
+    });
+
+    it('selects the product on click', async () => {
+        render(
+            <RecommendationBar customer={{ customer_id: 'C1' }} salesRows={[]} products={mockProducts} handleInputChange={mockHandleInputChange} />
+        );
+        fireEvent.click(await screen.findByText('느타리버섯'));
+        expect(mockHandleInputChange).toHaveBeenCalledWith({ target: { name: 'product', value: '느타리버섯', type: 'select-one' } });
+    });
+
+    it('renders nothing without a customer', async () => {
+        const { container } = render(
+            <RecommendationBar customer={null} salesRows={[]} products={mockProducts} handleInputChange={mockHandleInputChange} />
+        );
+        await waitFor(() => expect(container).toBeEmptyDOMElement());
+        expect(apiBridge.callBridge).not.toHaveBeenCalled();
+    });
+});
@@ -19,6 +19,8 @@ export const useSalesReception = (showAlert, showConfirm) => {
     const [showStatement, setShowStatement] = useState(false);
     const [paymentMethod, setPaymentMethod] = useState('현금');
     const [memo, setMemo] = useState('');
+    const [pointBalance, setPointBalance] = useState(0);
+    const [pointsUsed, setPointsUsed] = useState(0);
 
     // Form Input State
     const initialInputState = {
@@ -72,6 +74,16 @@ export const useSalesReception = (showAlert, showConfirm) => {
         }
     }, []);
 
+    const loadPointBalance = useCallback(async (cid) => {
+        try {
+            const points = await callBridge('get_customer_points', { customerId: String(cid) });
+            setPointBalance(points?.balance || 0);
+        } catch (e) {
+            console.error(e);
+            setPointBalance(0);
+        }
+    }, []);
+
     const loadSalesHistory = useCallback(async (cid, date) => {
         try {
             const history = await callBridge('get_customer_sales_on_date', {
@@ -126,6 +138,7 @@ export const useSalesReception = (showAlert, showConfirm) => {
     const handleRestoreDraft = () => {
         if (!tempDraft) return;
         setCustomer(tempDraft.customer);
+        if (tempDraft.customer) loadPointBalance(tempDraft.customer.customer_id);
         setOrderDate(tempDraft.orderDate || formatDate(new Date()));
         setSalesRows(tempDraft.salesRows || []);
         setDeletedSalesIds(tempDraft.deletedSalesIds || []);
@@ -155,6 +168,8 @@ export const useSalesReception = (showAlert, showConfirm) => {
     // --- Actions ---
     const selectCustomer = async (cust) => {
         setCustomer(cust);
+        setPointsUsed(0);
+        loadPointBalance(cust.customer_id);
         try {
             const addrs = await callBridge('get_customer_addresses', { customerId: cust.customer_id });
             setAddresses(addrs || []);
@@ -279,6 +294,12 @@ export const useSalesReception = (showAlert, showConfirm) => {
 
     const handleSaveAll = async () => {
         if (salesRows.length === 0 && deletedSalesIds.length === 0) return;
+        const points = Number(pointsUsed) || 0;
+        // Points pay part of one new order; the server rejects more than the balance
+        const pointsRow = points > 0 ? salesRows.find(r => !r.id && r.status !== '취소') : null;
+        if (points > 0 && !pointsRow) { showAlert('알림', '포인트는 새로 접수하는 주문에만 사용할 수 있습니다.'); return; }
+        if (points > pointBalance) { showAlert('알림', `사용 가능한 포인트는 ${pointBalance.toLocaleString()}P 입니다.`); return; }
+        if (pointsRow && points > Number(pointsRow.amount)) { showAlert('알림', '사용 포인트가 주문 금액보다 큽니다.'); return; }
         if (!await showConfirm('저장 확인', '모든 변경 사항을 저장하시겠습니까?')) return;
         setIsProcessing(true);
         try {
@@ -302,12 +323,15 @@ export const useSalesReception = (showAlert, showConfirm) => {
                 paymentStatus: '입금완료', // default to finished for mobile/general sales
                 paymentMethod: paymentMethod,
                 discountRate: Number(r.discountRate),
+                pointsUsed: r === pointsRow ? points : null,
                 isDirty: r.isDirty ? "true" : "false"
             }));
             await callBridge('save_general_sales_batch', { items: payload, deleted_ids: deletedSalesIds });
             await showAlert('성공', '정상적으로 저장되었습니다.');
             clearDraft();
+            setPointsUsed(0);
             loadSalesHistory(customer.customer_id, orderDate);
+            loadPointBalance(customer.customer_id);
         } catch (e) {
             showAlert('오류', `저장 중 오류가 발생했습니다: ${e}`);
         } finally { setIsProcessing(false); }
@@ -316,6 +340,7 @@ export const useSalesReception = (showAlert, showConfirm) => {
     const handleReset = async () => {
         if (isDirty && !await showConfirm('초기화', '작성 중인 내용이 있습니다. 정말 초기화하시겠습니까?')) return;
         setCustomer(null); setSalesRows([]); setInputState(initialInputState); setIsDirty(false);
+        setPointBalance(0); setPointsUsed(0);
         clearDraft();
     };
 
@@ -455,6 +480,7 @@ export const useSalesReception = (showAlert, showConfirm) => {
         handleDiscardDraft,
         paymentMethod, setPaymentMethod,
         memo, setMemo,
+        pointBalance, pointsUsed, setPointsUsed,
         updateRowQty
     };
 };
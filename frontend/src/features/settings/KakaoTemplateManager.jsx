@@ -0,0 +1,227 @@
+import React, { useState, useEffect, useRef } from 'react';
+import { useNavigate } from 'react-router-dom';
+import { callBridge as invoke } from '../../utils/apiBridge';
+import { useModal } from '../../contexts/ModalContext';
+import { useAdminGuard } from '../../hooks/useAdminGuard';
+import { MessageSquare, Save, Trash2, Lock, RefreshCw } from 'lucide-react';
+
+// Message template categories an approved AlimTalk template can stand in for:
+// the scenarios of the message template screen and the templates of the SMS screen
+const CATEGORIES = [
+    { key: 'shipping_receipt', label: '배송: 접수 안내' },
+    { key: 'shipping_paid', label: '배송: 입금 확인' },
+    { key: 'shipping_done', label: '배송: 발송 완료' },
+    { key: 'default', label: '일반/기본 홍보' },
+    { key: 'repurchase', label: '재구매 유도' },
+    { key: 'churn', label: '이탈 위험 관리' },
+    { key: 'greeting', label: '안부 및 감사' },
+    { key: 'promo', label: '신상품 및 할인 행사' },
+    { key: 'seasonal', label: '시즌 마케팅' },
+    { key: 'anniversary', label: '기념일 축하' },
+    { key: 'recovery', label: '클레임 대응' }
+];
+
+const emptyRow = (key) => ({ templateKey: key, templateCode: '', description: '', isEnabled: true, saved: false });
+
+const KakaoTemplateManager = () => {
+    const navigate = useNavigate();
+    const { showAlert, showConfirm } = useModal();
+    const { isAuthorized, checkAdmin, isVerifying } = useAdminGuard();
+    const [rows, setRows] = useState(CATEGORIES.map(c => emptyRow(c.key)));
+    const [isLoading, setIsLoading] = useState(false);
+
+    // --- Admin Guard Check ---
+    const checkRunComp = useRef(false);
+    useEffect(() => {
+        if (checkRunComp.current) return;
+        checkRunComp.current = true;
+
+        const init = async () => {
+            const ok = await checkAdmin();
+            if (!ok) navigate('/');
+        };
+        init();
+    }, []);
+
+    useEffect(() => {
+        if (isAuthorized) {
+            loadTemplates();
+        }
+    }, [isAuthorized]);
+
+    const loadTemplates = async () => {
+        setIsLoading(true);
+        try {
+            const data = await invoke('get_kakao_templates') || [];
+            const byKey = Object.fromEntries(data.map(t => [t.templateKey, t]));
+            // Known categories first, then anything mapped outside of them
+            const keys = [...CATEGORIES.map(c => c.key), ...data.map(t => t.templateKey).filter(k => !CATEGORIES.some(c => c.key === k))];
+            setRows(keys.map(key => byKey[key]
+                ? { ...byKey[key], description: byKey[key].description || '', saved: true }
+                : emptyRow(key)));
+        } catch (e) {
+            console.error(e);
+            showAlert('오류', '알림톡 템플릿을 불러오는데 실패했습니다.');
+        } finally {
+            setIsLoading(false);
+        }
+    };
+
+    const updateRow = (key, changes) => {
+        setRows(prev => prev.map(r => r.templateKey === key ? { ...r, ...changes } : r));
+    };
+
+    const handleSave = async (row) => {
+        if (!row.templateCode.trim()) {
+            showAlert('알림', '카카오에서 승인된 알림톡 템플릿 코드를 입력해주세요.');
+            return;
+        }
+        try {
+            await invoke('save_kakao_template', {
+                templateKey: row.templateKey,
+                templateCode: row.templateCode.trim(),
+                description: row.description.trim() || null,
+                isEnabled: row.isEnabled
+            });
+            updateRow(row.templateKey, { saved: true });
+            showAlert('저장 완료', '알림톡 템플릿이 연결되었습니다.', 'success');
+        } catch (e) {
+            showAlert('저장 실패', String(e));
+        }
+    };
+
+    const handleDelete = async (row) => {
+        const ok = await showConfirm('연결 해제', `'${labelOf(row.templateKey)}'의 알림톡 템플릿 연결을 해제하시겠습니까?\n해제하면 이 분류는 친구톡 또는 문자로 발송됩니다.`);
+        if (!ok) return;
+        try {
+            await invoke('delete_kakao_template', { templateKey: row.templateKey });
+            loadTemplates();
+        } catch (e) {
+            showAlert('삭제 실패', String(e));
+        }
+    };
+
+    const labelOf = (key) => CATEGORIES.find(c => c.key === key)?.label || key;
+
+    if (!isAuthorized) {
+        return (
+            <div className="flex h-full items-center justify-center bg-[#f8fafc]">
+                <div className="text-center animate-pulse">
+                    {isVerifying ? (
+                        <div className="w-12 h-12 border-4 border-slate-200 border-t-indigo-500 rounded-full animate-spin mx-auto mb-4" />
+                    ) : (
+                        <Lock size={48} className="mx-auto text-slate-300 mb-4" />
+                    )}
+                    <p className="text-slate-400 font-bold">
+                        {isVerifying ? '인증 확인 중...' : '인증 대기 중...'}
+                    </p>
+                </div>
+            </div>
+        );
+    }
+
+    return (
+        <div className="flex flex-col h-full bg-[#f8fafc] overflow-hidden animate-in fade-in duration-700">
+            <div className="flex-1 px-6 lg:px-8 pt-8 pb-8 overflow-y-auto">
+                <div className="max-w-5xl mx-auto">
+                    <div className="flex justify-between items-end mb-6">
+                        <div className="text-left">
+                            <div className="flex items-center gap-2 mb-1">
+                                <span className="w-6 h-1 bg-yellow-400 rounded-full"></span>
+                                <span className="text-[9px] font-black tracking-[0.2em] text-yellow-600 uppercase">KakaoTalk</span>
+                            </div>
+                            <h1 className="text-3xl font-black text-slate-600 tracking-tighter" style={{ fontFamily: '"Noto Sans KR", sans-serif' }}>
+                                알림톡 템플릿 연결
+                            </h1>
+                            <p className="text-[11px] font-bold text-slate-400 mt-1">
+                                분류별로 카카오에서 승인된 템플릿 코드를 연결하면 정보성 메시지가 알림톡으로 발송됩니다.
+                            </p>
+                        </div>
+                        <button
+                            onClick={loadTemplates}
+                            disabled={isLoading}
+                            className="h-10 px-5 bg-white border border-slate-200 text-slate-500 hover:text-slate-700 rounded-xl font-black text-xs flex items-center gap-2 shadow-sm"
+                        >
+                            <RefreshCw size={14} className={isLoading ? 'animate-spin' : ''} /> 새로고침
+                        </button>
+                    </div>
+
+                    <div className="bg-white rounded-[2rem] border border-slate-200 shadow-xl shadow-slate-200/50 ring-1 ring-slate-900/5 overflow-hidden">
+                        <table className="w-full text-sm">
+                            <thead className="bg-slate-50 text-[10px] font-black text-slate-400 uppercase tracking-widest">
+                                <tr>
+                                    <th className="px-6 py-4 text-left">분류</th>
+                                    <th className="px-3 py-4 text-left">알림톡 템플릿 코드</th>
+                                    <th className="px-3 py-4 text-left">설명</th>
+                                    <th className="px-3 py-4 text-center">사용</th>
+                                    <th className="px-6 py-4"></th>
+                                </tr>
+                            </thead>
+                            <tbody className="divide-y divide-slate-100">
+                                {rows.map(row => (
+                                    <tr key={row.templateKey}>
+                                        <td className="px-6 py-3 text-left">
+                                            <div className="flex items-center gap-2 font-bold text-slate-700">
+                                                <MessageSquare size={14} className={row.saved ? 'text-yellow-500' : 'text-slate-300'} />
+                                                {labelOf(row.templateKey)}
+                                            </div>
+                                            <div className="text-[10px] font-bold text-slate-400 ml-6">{row.templateKey}</div>
+                                        </td>
+                                        <td className="px-3 py-3">
+                                            <input
+                                                aria-label={`${labelOf(row.templateKey)} 템플릿 코드`}
+                                                value={row.templateCode}
+                                                onChange={e => updateRow(row.templateKey, { templateCode: e.target.value })}
+                                                maxLength={50}
+                                                className="w-full h-10 px-3 bg-slate-50 rounded-xl font-bold text-sm ring-1 ring-inset ring-slate-200 outline-none focus:ring-yellow-400"
+                                            />
+                                        </td>
+                                        <td className="px-3 py-3">
+                                            <input
+                                                aria-label={`${labelOf(row.templateKey)} 설명`}
+                                                value={row.description}
+                                                onChange={e => updateRow(row.templateKey, { description: e.target.value })}
+                                                className="w-full h-10 px-3 bg-slate-50 rounded-xl text-sm ring-1 ring-inset ring-slate-200 outline-none focus:ring-yellow-400"
+                                            />
+                                        </td>
+                                        <td className="px-3 py-3 text-center">
+                                            <input
+                                                type="checkbox"
+                                                aria-label={`${labelOf(row.templateKey)} 사용`}
+                                                checked={row.isEnabled}
+                                                onChange={e => updateRow(row.templateKey, { isEnabled: e.target.checked })}
+                                                className="rounded border-slate-300 text-yellow-500 focus:ring-yellow-500"
+                                            />
+                                        </td>
+                                        <td className="px-6 py-3">
+                                            <div className="flex justify-end gap-2">
+                                                <button
+                                                    onClick={() => handleSave(row)}
+                                                    aria-label={`${labelOf(row.templateKey)} 저장`}
+                                                    className="h-9 px-3 bg-yellow-400 hover:bg-yellow-300 text-slate-900 rounded-lg font-black text-[11px] flex items-center gap-1"
+                                                >
+                                                    <Save size={12} /> 저장
+                                                </button>
+                                                {row.saved && (
+                                                    <button
+                                                        onClick={() => handleDelete(row)}
+                                                        aria-label={`${labelOf(row.templateKey)} 연결 해제`}
+                                                        className="h-9 px-3 border border-rose-200 text-rose-500 hover:bg-rose-50 rounded-lg font-black text-[11px] flex items-center gap-1"
+                                                    >
+                                                        <Trash2 size={12} />
+                                                    </button>
+                                                )}
+                                            </div>
+                                        </td>
+                                    </tr>
+                                ))}
+                            </tbody>
+                        </table>
+                    </div>
+                </div>
+            </div>
+        </div>
+    );
+};
+
+export default KakaoTemplateManager;
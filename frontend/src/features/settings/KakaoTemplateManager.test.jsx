@@ -0,0 +1,87 @@
+import { render, screen, waitFor } from '@testing-library/react';
+import userEvent from '@testing-library/user-event';
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import React from 'react';
+import KakaoTemplateManager from './KakaoTemplateManager';
+import * as apiBridge from '../../utils/apiBridge';
+import { BrowserRouter } from 'react-router-dom';
+
+vi.mock('../../utils/apiBridge', () => ({
+    invoke: vi.fn(),
+    callBridge: vi.fn()
+}));
+
+const mockShowAlert = vi.fn().mockResolvedValue(true);
+const mockShowConfirm = vi.fn().mockResolvedValue(true);
+vi.mock('../../contexts/ModalContext', () => ({
+    useModal: () => ({
+        showAlert: mockShowAlert,
+        showConfirm: mockShowConfirm
+    }),
+    ModalProvider: ({ children }) => <div>{children}</div>
+}));
+
+vi.mock('../../hooks/useAdminGuard', () => ({
+    useAdminGuard: () => ({
+        isAuthorized: true,
+        checkAdmin: vi.fn().mockResolvedValue(true),
+        isVerifying: false
+    })
+}));
+
+describe('KakaoTemplateManager Component', () => {
+    let user;
+
+    beforeEach(() => {
+        user = userEvent.setup();
+        vi.clearAllMocks();
+
+        apiBridge.callBridge.mockImplementation((cmd) => {
+            if (cmd === 'get_kakao_templates') return Promise.resolve([
+                { templateKey: 'shipping_done', templateCode: 'SHIP_DONE_01', description: '발송 완료', isEnabled: true }
+            ]);
+            return Promise.resolve(null);
+        });
+    });
+
+    const renderComponent = () => render(
+        <BrowserRouter>
+            <KakaoTemplateManager />
+        </BrowserRouter>
+    );
+
+    it('shows the stored mapping next to every category', async () => {
+        renderComponent();
+
+        expect(await screen.findByLabelText('배송: 발송 완료 템플릿 코드')).toHaveValue('SHIP_DONE_01');
+        expect(screen.getByLabelText('배송: 입금 확인 템플릿 코드')).toHaveValue('');
+        expect(screen.getByLabelText('신상품 및 할인 행사 템플릿 코드')).toBeInTheDocument();
+    });
+
+    it('saves a template code for a category', async () => {
+        renderComponent();
+
+        const code = await screen.findByLabelText('배송: 입금 확인 템플릿 코드');
+        await user.type(code, 'PAID_01');
+        await user.click(screen.getByLabelText('배송: 입금 확인 저장'));
+
+        await waitFor(() => {
+            expect(apiBridge.callBridge).toHaveBeenCalledWith('save_kakao_template', {
+                templateKey: 'shipping_paid',
+                templateCode: 'PAID_01',
+                description: null,
+                isEnabled: true
+            });
+        });
+    });
+
+    it('removes a mapping after confirmation', async () => {
+        renderComponent();
+
+        await user.click(await screen.findByLabelText('배송: 발송 완료 연결 해제'));
+
+        await waitFor(() => {
+            expect(apiBridge.callBridge).toHaveBeenCalledWith('delete_kakao_template', { templateKey: 'shipping_done' });
+        });
+    });
+});
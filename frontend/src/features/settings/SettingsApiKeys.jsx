@@ -31,6 +31,7 @@ const SettingsApiKeys = () => {
     const [showKeys, setShowKeys] = useState({
         gemini: false,
         sms: false,
+        kakao: false,
         naver: false,
         naver_commerce_id: false,
         naver_commerce_secret: false,
@@ -45,8 +46,19 @@ const SettingsApiKeys = () => {
     const [formData, setFormData] = useState({
         gemini_api_key: '',
         sms_api_key: '',
+        sms_api_secret: '',
+        sms_webhook_secret: '',
         sms_sender_number: '',
         sms_provider: 'aligo', // default
+        sms_costs: { sms: 9, lms: 30, mms: 100 }, // 건당 요금 (원)
+        // KakaoTalk (AlimTalk / FriendTalk)
+        kakao_provider: 'nhn',
+        kakao_api_key: '',
+        kakao_api_secret: '',
+        kakao_sender_key: '',
+        kakao_webhook_secret: '',
+        kakao_sms_fallback: true,
+        kakao_costs: { alimtalk: 8, friendtalk: 15 },
         naver_client_id: '',
         naver_client_secret: '',
         // Mall Commerce
@@ -96,8 +108,18 @@ const SettingsApiKeys = () => {
                         ...prev,
                         gemini_api_key: config.gemini_api_key || '',
                         sms_api_key: config.sms?.apiKey || '',
+                        sms_api_secret: config.sms?.apiSecret || '',
+                        sms_webhook_secret: config.sms?.webhookSecret || '',
                         sms_sender_number: config.sms?.senderNumber || '',
                         sms_provider: config.sms?.provider || 'aligo',
+                        sms_costs: config.sms?.costs || { sms: 9, lms: 30, mms: 100 },
+                        kakao_provider: config.kakao?.provider || 'nhn',
+                        kakao_api_key: config.kakao?.apiKey || '',
+                        kakao_api_secret: config.kakao?.apiSecret || '',
+                        kakao_sender_key: config.kakao?.senderKey || '',
+                        kakao_webhook_secret: config.kakao?.webhookSecret || '',
+                        kakao_sms_fallback: config.kakao?.smsFallback ?? true,
+                        kakao_costs: config.kakao?.costs || { alimtalk: 8, friendtalk: 15 },
                         naver_client_id: config.naver?.clientId || '',
                         naver_client_secret: config.naver?.clientSecret || '',
                         naver_commerce_id: config.mall?.naver_commerce_id || '',
@@ -168,8 +190,11 @@ const SettingsApiKeys = () => {
         try {
             await invoke('save_sms_config', {
                 apiKey: formData.sms_api_key,
+                apiSecret: formData.sms_api_secret,
+                webhookSecret: formData.sms_webhook_secret,
                 senderNumber: formData.sms_sender_number,
-                provider: formData.sms_provider
+                provider: formData.sms_provider,
+                costs: formData.sms_costs
             });
             await showAlert('저장 완료', 'SMS 설정이 저장되었습니다.');
         } catch (err) {
@@ -179,6 +204,26 @@ const SettingsApiKeys = () => {
         }
     };
 
+    const handleSaveKakao = async () => {
+        setIsLoading(true);
+        try {
+            await invoke('save_kakao_config', {
+                provider: formData.kakao_provider,
+                apiKey: formData.kakao_api_key,
+                apiSecret: formData.kakao_api_secret,
+                senderKey: formData.kakao_sender_key,
+                webhookSecret: formData.kakao_webhook_secret,
+                smsFallback: formData.kakao_sms_fallback,
+                costs: formData.kakao_costs
+            });
+            await showAlert('저장 완료', '카카오톡 설정이 저장되었습니다.');
+        } catch (err) {
+            showAlert('저장 실패', err);
+        } finally {
+            setIsLoading(false);
+        }
+    };
+
     const handleSaveNaver = async () => {
         setIsLoading(true);
         try {
@@ -395,7 +440,7 @@ const SettingsApiKeys = () => {
                                                 <ExternalLink size={10} /> 서비스 센터
                                             </a>
                                         </div>
-                                        <p className="text-[11px] font-bold text-slate-400">문자 및 카카오 알림톡 발송 설정</p>
+                                        <p className="text-[11px] font-bold text-slate-400">문자 (SMS/LMS/MMS) 발송 설정</p>
                                     </div>
                                 </div>
                                 <button
@@ -427,6 +472,18 @@ const SettingsApiKeys = () => {
                                     </div>
                                 </div>
 
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">
+                                        {formData.sms_provider === 'aligo' ? '알리고 아이디' : formData.sms_provider === 'nhn' ? '시크릿 키 (Secret Key)' : 'API 시크릿'}
+                                    </label>
+                                    <input
+                                        type={formData.sms_provider === 'aligo' || showKeys.sms ? "text" : "password"}
+                                        value={formData.sms_api_secret}
+                                        onChange={e => setFormData({ ...formData, sms_api_secret: e.target.value })}
+                                        className="w-full h-12 px-5 bg-slate-50 border-none rounded-xl font-bold text-sm focus:ring-4 focus:ring-orange-500/10 focus:bg-white transition-all ring-1 ring-inset ring-slate-200"
+                                    />
+                                </div>
+
                                 <div className="space-y-3">
                                     <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">발신 번호 (Sender Number)</label>
                                     <div className="relative group">
@@ -440,10 +497,40 @@ const SettingsApiKeys = () => {
                                     </div>
                                 </div>
 
-                                <div className="space-y-3 col-span-1 md:col-span-2">
-                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1">발송 서비스 선택</label>
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">수신 알림 시크릿 (Webhook)</label>
+                                    <input
+                                        type={showKeys.sms ? "text" : "password"}
+                                        value={formData.sms_webhook_secret}
+                                        onChange={e => setFormData({ ...formData, sms_webhook_secret: e.target.value })}
+                                        className="w-full h-12 px-5 bg-slate-50 border-none rounded-xl font-bold text-sm focus:ring-4 focus:ring-orange-500/10 focus:bg-white transition-all ring-1 ring-inset ring-slate-200"
+                                        placeholder="/api/webhooks/messaging/{서비스명}"
+                                    />
+                                </div>
+
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">건당 발송 요금 (원)</label>
                                     <div className="grid grid-cols-3 gap-3">
-                                        {['aligo', 'coolsms', 'solapi'].map(provider => (
+                                        {['sms', 'lms', 'mms'].map(kind => (
+                                            <label key={kind} className="flex items-center gap-2 h-12 px-4 bg-slate-50 rounded-xl ring-1 ring-inset ring-slate-200">
+                                                <span className="text-[11px] font-black text-slate-400 uppercase">{kind}</span>
+                                                <input
+                                                    type="number"
+                                                    min="0"
+                                                    aria-label={`${kind.toUpperCase()} 요금`}
+                                                    value={formData.sms_costs[kind]}
+                                                    onChange={e => setFormData({ ...formData, sms_costs: { ...formData.sms_costs, [kind]: Math.max(0, parseInt(e.target.value, 10) || 0) } })}
+                                                    className="w-full bg-transparent border-none text-right font-bold text-sm outline-none"
+                                                />
+                                            </label>
+                                        ))}
+                                    </div>
+                                </div>
+
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1">발송 서비스 선택</label>
+                                    <div className="grid grid-cols-4 gap-3">
+                                        {['aligo', 'nhn', 'solapi', 'coolsms'].map(provider => (
                                             <button
                                                 key={provider}
                                                 type="button"
@@ -462,6 +549,147 @@ const SettingsApiKeys = () => {
                             </div>
                         </div>
 
+                        {/* KakaoTalk Business Message Card */}
+                        <div className="bg-white rounded-[2rem] shadow-xl shadow-slate-200/50 border border-slate-200 overflow-hidden ring-1 ring-slate-900/5 p-8 text-left transition-all">
+                            <div className="flex justify-between items-center mb-6">
+                                <div className="flex items-center gap-4">
+                                    <div className="w-10 h-10 rounded-xl bg-yellow-50 text-yellow-600 flex items-center justify-center">
+                                        <MessageSquare size={20} />
+                                    </div>
+                                    <div className="flex flex-col">
+                                        <div className="flex items-center gap-3">
+                                            <h2 className="text-lg font-black text-slate-700 tracking-tight">KakaoTalk Business Message</h2>
+                                            <button
+                                                type="button"
+                                                onClick={() => navigate('/settings/kakao-templates')}
+                                                className="inline-flex items-center gap-1.5 px-2.5 py-1 rounded-lg bg-yellow-50 text-yellow-700 text-[10px] font-black hover:bg-yellow-100 transition-colors"
+                                            >
+                                                <ExternalLink size={10} /> 알림톡 템플릿 연결
+                                            </button>
+                                        </div>
+                                        <p className="text-[11px] font-bold text-slate-400">카카오 알림톡 / 친구톡 발송 설정</p>
+                                    </div>
+                                </div>
+                                <button
+                                    onClick={handleSaveKakao}
+                                    disabled={isLoading}
+                                    className="h-10 px-5 bg-yellow-400 hover:bg-yellow-300 text-slate-900 rounded-xl font-black text-xs flex items-center gap-2 shadow-lg shadow-yellow-100 transition-all active:scale-[0.95]"
+                                >
+                                    <Save size={14} /> 저장
+                                </button>
+                            </div>
+
+                            <div className="grid grid-cols-1 md:grid-cols-2 gap-6">
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">
+                                        {formData.kakao_provider === 'nhn' ? '앱 키 (App Key)' : 'API 키'}
+                                    </label>
+                                    <div className="relative group">
+                                        <input
+                                            type={showKeys.kakao ? "text" : "password"}
+                                            aria-label="카카오 API 키"
+                                            value={formData.kakao_api_key}
+                                            onChange={e => setFormData({ ...formData, kakao_api_key: e.target.value })}
+                                            className="w-full h-12 px-5 pr-12 bg-slate-50 border-none rounded-xl font-bold text-sm focus:ring-4 focus:ring-yellow-500/10 focus:bg-white transition-all ring-1 ring-inset ring-slate-200"
+                                        />
+                                        <button
+                                            type="button"
+                                            onClick={() => toggleKeyVisibility('kakao')}
+                                            className="absolute right-4 top-1/2 -translate-y-1/2 text-slate-400 hover:text-slate-600 cursor-pointer"
+                                        >
+                                            {showKeys.kakao ? <EyeOff size={16} /> : <Eye size={16} />}
+                                        </button>
+                                    </div>
+                                </div>
+
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">
+                                        {formData.kakao_provider === 'nhn' ? '시크릿 키 (Secret Key)' : 'API 시크릿'}
+                                    </label>
+                                    <input
+                                        type={showKeys.kakao ? "text" : "password"}
+                                        aria-label="카카오 API 시크릿"
+                                        value={formData.kakao_api_secret}
+                                        onChange={e => setFormData({ ...formData, kakao_api_secret: e.target.value })}
+                                        className="w-full h-12 px-5 bg-slate-50 border-none rounded-xl font-bold text-sm focus:ring-4 focus:ring-yellow-500/10 focus:bg-white transition-all ring-1 ring-inset ring-slate-200"
+                                    />
+                                </div>
+
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">
+                                        {formData.kakao_provider === 'nhn' ? '발신 프로필 키 (Sender Key)' : '채널 ID (pfId)'}
+                                    </label>
+                                    <input
+                                        type="text"
+                                        aria-label="발신 프로필"
+                                        value={formData.kakao_sender_key}
+                                        onChange={e => setFormData({ ...formData, kakao_sender_key: e.target.value })}
+                                        className="w-full h-12 px-5 bg-slate-50 border-none rounded-xl font-bold text-sm focus:ring-4 focus:ring-yellow-500/10 focus:bg-white transition-all ring-1 ring-inset ring-slate-200"
+                                    />
+                                </div>
+
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">수신 알림 시크릿 (Webhook)</label>
+                                    <input
+                                        type={showKeys.kakao ? "text" : "password"}
+                                        aria-label="카카오 수신 알림 시크릿"
+                                        value={formData.kakao_webhook_secret}
+                                        onChange={e => setFormData({ ...formData, kakao_webhook_secret: e.target.value })}
+                                        className="w-full h-12 px-5 bg-slate-50 border-none rounded-xl font-bold text-sm focus:ring-4 focus:ring-yellow-500/10 focus:bg-white transition-all ring-1 ring-inset ring-slate-200"
+                                        placeholder="/api/webhooks/messaging/{서비스명}"
+                                    />
+                                </div>
+
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1 text-left">건당 발송 요금 (원)</label>
+                                    <div className="grid grid-cols-2 gap-3">
+                                        {[['alimtalk', '알림톡'], ['friendtalk', '친구톡']].map(([kind, label]) => (
+                                            <label key={kind} className="flex items-center gap-2 h-12 px-4 bg-slate-50 rounded-xl ring-1 ring-inset ring-slate-200">
+                                                <span className="text-[11px] font-black text-slate-400 whitespace-nowrap">{label}</span>
+                                                <input
+                                                    type="number"
+                                                    min="0"
+                                                    aria-label={`${label} 요금`}
+                                                    value={formData.kakao_costs[kind]}
+                                                    onChange={e => setFormData({ ...formData, kakao_costs: { ...formData.kakao_costs, [kind]: Math.max(0, parseInt(e.target.value, 10) || 0) } })}
+                                                    className="w-full bg-transparent border-none text-right font-bold text-sm outline-none"
+                                                />
+                                            </label>
+                                        ))}
+                                    </div>
+                                    <label className="flex items-center gap-2 ml-1 text-[11px] font-bold text-slate-500 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={formData.kakao_sms_fallback}
+                                            onChange={e => setFormData({ ...formData, kakao_sms_fallback: e.target.checked })}
+                                            className="rounded border-slate-300 text-yellow-500 focus:ring-yellow-500"
+                                        />
+                                        카카오톡 실패 시 문자로 대체 발송
+                                    </label>
+                                </div>
+
+                                <div className="space-y-3">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1">발송 서비스 선택</label>
+                                    <div className="grid grid-cols-2 gap-3">
+                                        {['nhn', 'solapi'].map(provider => (
+                                            <button
+                                                key={provider}
+                                                type="button"
+                                                onClick={() => setFormData({ ...formData, kakao_provider: provider })}
+                                                className={`h-12 rounded-xl border-2 font-black text-[11px] capitalize transition-all
+                                                ${formData.kakao_provider === provider
+                                                        ? 'bg-yellow-50 border-yellow-400 text-yellow-700 shadow-md transform -translate-y-0.5'
+                                                        : 'bg-white border-slate-100 text-slate-400 hover:border-slate-200'}
+                                            `}
+                                            >
+                                                {provider}
+                                            </button>
+                                        ))}
+                                    </div>
+                                </div>
+                            </div>
+                        </div>
+
                         {/* Naver Search API Card */}
                         <div className="bg-white rounded-[2rem] shadow-xl shadow-slate-200/50 border border-slate-200 overflow-hidden ring-1 ring-slate-900/5 p-8 text-left transition-all">
                             <div className="flex justify-between items-center mb-6">
@@ -1,4 +1,4 @@
-import { render, screen, waitFor } from '@testing-library/react';
+import { render, screen, waitFor, within } from '@testing-library/react';
 import userEvent from '@testing-library/user-event';
 import { describe, it, expect, vi, beforeEach } from 'vitest';
 import React from 'react';
@@ -34,7 +34,8 @@ vi.mock('../../hooks/useAdminGuard', () => ({
 
 const mockConfig = {
     gemini_api_key: 'test-gemini-key',
-    sms: { apiKey: 'sms-key-123', senderNumber: '010-1234-5678', provider: 'aligo' },
+    sms: { apiKey: 'sms-key-123', senderNumber: '010-1234-5678', provider: 'aligo', costs: { sms: 8, lms: 25, mms: 90 } },
+    kakao: { provider: 'solapi', apiKey: 'kakao-key', apiSecret: 'kakao-secret', senderKey: 'KA01PF-1', smsFallback: true, costs: { alimtalk: 7, friendtalk: 14 } },
     naver: { clientId: 'naver-id', clientSecret: 'naver-secret' },
     mall: {
         naver_commerce_id: '', naver_commerce_secret: '',
@@ -80,6 +81,7 @@ describe('SettingsApiKeys Component', () => {
 
         expect(await screen.findByText('Google Gemini AI')).toBeInTheDocument();
         expect(screen.getByText(/SMS & Messaging/)).toBeInTheDocument();
+        expect(screen.getByText(/KakaoTalk Business Message/)).toBeInTheDocument();
         expect(screen.getByText(/Naver Search & Trends/)).toBeInTheDocument();
         expect(screen.getByText(/Weather Service/)).toBeInTheDocument();
         expect(screen.getByText(/E-commerce & Mall Sync/)).toBeInTheDocument();
@@ -127,6 +129,12 @@ describe('SettingsApiKeys Component', () => {
 
         // Find the SMS save button (the one inside SMS card)
         // SMS card has "저장" button (4th character match)
+        // Contracted prices are edited and sent with the rest
+        const lmsCost = await screen.findByLabelText('LMS 요금');
+        expect(lmsCost).toHaveValue(25);
+        await user.clear(lmsCost);
+        await user.type(lmsCost, '27');
+
         const smsSection = screen.getByText(/SMS & Messaging/);
         const smsSaveBtn = smsSection.closest('div[class*="bg-white"]').querySelector('button[class*="bg-orange"]');
         await user.click(smsSaveBtn);
@@ -134,11 +142,36 @@ describe('SettingsApiKeys Component', () => {
         await waitFor(() => {
             expect(apiBridge.callBridge).toHaveBeenCalledWith('save_sms_config', expect.objectContaining({
                 apiKey: 'sms-key-123',
-                senderNumber: '010-1234-5678'
+                senderNumber: '010-1234-5678',
+                costs: { sms: 8, lms: 27, mms: 90 }
             }));
         });
     });
 
+    it('saves KakaoTalk config', async () => {
+        renderComponent();
+
+        const senderKey = await screen.findByLabelText('발신 프로필');
+        await waitFor(() => expect(senderKey).toHaveValue('KA01PF-1'));
+
+        const kakaoCard = screen.getByText(/KakaoTalk Business Message/).closest('div[class*="bg-white"]');
+        await user.click(within(kakaoCard).getByText('nhn'));
+        await user.click(screen.getByLabelText('카카오톡 실패 시 문자로 대체 발송'));
+        await user.click(kakaoCard.querySelector('button[class*="bg-yellow-400"]'));
+
+        await waitFor(() => {
+            expect(apiBridge.callBridge).toHaveBeenCalledWith('save_kakao_config', {
+                provider: 'nhn',
+                apiKey: 'kakao-key',
+                apiSecret: 'kakao-secret',
+                senderKey: 'KA01PF-1',
+                webhookSecret: '',
+                smsFallback: false,
+                costs: { alimtalk: 7, friendtalk: 14 }
+            });
+        });
+    });
+
     it('saves weather config', async () => {
         renderComponent();
 
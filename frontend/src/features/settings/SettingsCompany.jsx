@@ -30,6 +30,7 @@ const SettingsCompany = () => {
         business_type: '',
         item: '',
         memo: '',
+        bank_account: '',
         certification_info: { gap: '', haccp: '', organic: '' }
     });
 
@@ -65,6 +66,7 @@ const SettingsCompany = () => {
                             business_type: info.business_type || '',
                             item: info.item || '',
                             memo: info.memo || '',
+                            bank_account: info.bank_account || '',
                             certification_info: info.certification_info || { gap: '', haccp: '', organic: '' }
                         });
                     }
@@ -96,6 +98,7 @@ const SettingsCompany = () => {
                 businessType: formData.business_type || null,
                 item: formData.item || null,
                 memo: formData.memo || null,
+                bankAccount: formData.bank_account,
                 certificationInfo: formData.certification_info
             });
             window.dispatchEvent(new Event('company-info-changed'));
@@ -273,6 +276,17 @@ const SettingsCompany = () => {
                                         placeholder="010-0000-0000"
                                     />
                                 </div>
+
+                                <div className="space-y-1 md:col-span-2">
+                                    <label className="block text-[10px] font-black text-slate-400 uppercase tracking-widest ml-1">입금 계좌 (문자 안내용)</label>
+                                    <input
+                                        type="text"
+                                        value={formData.bank_account}
+                                        onChange={e => setFormData({ ...formData, bank_account: e.target.value })}
+                                        className="w-full h-11 px-4 bg-white border-none rounded-xl font-bold text-sm text-slate-900 focus:ring-4 focus:ring-indigo-500/10 transition-all ring-1 ring-inset ring-slate-200"
+                                        placeholder="농협 000-0000-0000-00 (예금주)"
+                                    />
+                                </div>
                             </div>
                         </div>
 
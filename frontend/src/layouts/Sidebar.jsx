@@ -196,6 +196,7 @@ const Sidebar = () => {
                                 <SubMenuItem to="/settings/event-mgmt" icon="fmd_good" label="행사(특판)장 관리" />
                                 <SubMenuItem to="/exp/program-mgmt" icon="settings_applications" label="체험 프로그램 설정" />
                                 <SubMenuItem to="/settings/template-mgmt" icon="chat_bubble" label="메시지 템플릿" />
+                                <SubMenuItem to="/settings/kakao-templates" icon="forum" label="알림톡 템플릿 연결" />
                             </MenuGroup>
 
                             <MenuGroup id="settings" icon="settings" label="시스템 및 보안" expanded={isExpanded('settings')} onToggle={toggleMenu} currentPath={location.pathname}>
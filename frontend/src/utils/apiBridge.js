@@ -38,6 +38,11 @@ export async function callBridge(commandName, args = {}) {
         'create_product': '/api/product/create',
         'create_sale': '/api/sales/create',
         'fetch_external_orders': '/api/sales/external/fetch',
+        'import_mall_orders': '/api/sales/external/import',
+        'get_mall_import_logs': '/api/sales/external/import-logs',
+        'get_mall_product_mappings': '/api/sales/external/mappings',
+        'save_mall_product_mapping': '/api/sales/external/mappings',
+        'delete_mall_product_mapping': '/api/sales/external/mappings/delete',
         'get_all_users': '/api/auth/users',
         'create_user': '/api/auth/users/create',
         'update_user': '/api/auth/users/update',
@@ -94,6 +99,10 @@ export async function callBridge(commandName, args = {}) {
         'get_all_integrations_config': '/api/settings/integrations',
         'save_gemini_api_key': '/api/settings/integrations/gemini',
         'save_sms_config': '/api/settings/integrations/sms',
+        'save_kakao_config': '/api/settings/integrations/kakao',
+        'get_kakao_templates': '/api/crm/kakao/templates',
+        'save_kakao_template': '/api/crm/kakao/templates',
+        'delete_kakao_template': '/api/crm/kakao/templates/delete',
         'save_naver_keys': '/api/settings/integrations/naver',
         'save_mall_keys': '/api/settings/integrations/mall',
         'save_courier_config': '/api/settings/integrations/courier',
@@ -115,6 +124,8 @@ export async function callBridge(commandName, args = {}) {
         'get_message_templates': '/api/settings/templates',
         'save_message_templates': '/api/settings/templates/save',
         'reset_message_templates': '/api/settings/templates/reset',
+        'get_template_variables': '/api/settings/templates/variables',
+        'preview_message_template': '/api/settings/templates/preview',
         'get_mobile_config': '/api/mobile/config',
         'save_mobile_config': '/api/mobile/config/save',
         'get_local_ip_command': '/api/mobile/local-ip',
@@ -176,12 +187,16 @@ export async function callBridge(commandName, args = {}) {
         'get_ai_repurchase_analysis': '/api/ai/repurchase',
         'update_customer_memo_batch': '/api/crm/update-memo-batch',
         'get_product_associations': '/api/crm/product-associations',
+        'get_product_recommendations': '/api/crm/recommendations',
         'get_ai_marketing_proposal': '/api/ai/marketing-proposal',
         'get_ai_detailed_plan': '/api/ai/detailed-plan',
         'fetch_naver_search': '/api/ai/naver-search',
         'analyze_online_sentiment': '/api/ai/online-sentiment',
         'get_claim_targets': '/api/crm/claim-targets',
         'send_sms_simulation': '/api/crm/sms/send',
+        'get_message_campaigns': '/api/crm/campaigns',
+        'get_message_campaign_progress': '/api/crm/campaigns/progress',
+        'cancel_message_campaign': '/api/crm/campaigns/cancel',
         'create_experience_reservation': '/api/experience/reservations/create',
         'get_experience_reservations': '/api/experience/reservations',
         'update_experience_reservation': '/api/experience/reservations/update',
@@ -198,6 +213,7 @@ export async function callBridge(commandName, args = {}) {
         'delete_farming_log': '/api/production/logs/delete',
         'get_customer_addresses': '/api/customer/addresses',
         'get_customer_logs': '/api/customer/logs',
+        'get_customer_points': '/api/customer/points',
         'update_customer': '/api/customer/update',
         'delete_customer': '/api/customer/delete',
         'reactivate_customer': '/api/customer/reactivate',
@@ -259,6 +275,8 @@ export async function callBridge(commandName, args = {}) {
         'create_product',
         'create_sale',
         'delete_sale',
+        'import_mall_orders',
+        'delete_mall_product_mapping',
         'create_user',
         'update_user',
         'delete_user',
@@ -292,7 +310,9 @@ export async function callBridge(commandName, args = {}) {
         'save_sensor',
         'save_sensor',
         'delete_sensor',
+        'delete_kakao_template',
         'reset_message_templates',
+        'preview_message_template',
         'run_daily_custom_backup',
         'restore_database',
         'run_db_maintenance',
@@ -317,6 +337,7 @@ export async function callBridge(commandName, args = {}) {
         'fetch_naver_search',
         'analyze_online_sentiment',
         'send_sms_simulation',
+        'cancel_message_campaign',
         'push_sensor_data',
         'login',
         'verify_mobile_pin',